@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS audit_events (
+    id varchar(100) NOT NULL,
+    entity_type varchar(50) NOT NULL,
+    entity_id varchar(100) NOT NULL,
+    action varchar(50) NOT NULL,
+    actor_id varchar(100) NOT NULL,
+    before_state text,
+    after_state text,
+    reason text,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (actor_id) REFERENCES users(id)
+);
+
+CREATE INDEX audit_events_entity_idx ON audit_events(entity_id);
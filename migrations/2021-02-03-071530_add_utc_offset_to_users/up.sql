@@ -0,0 +1 @@
+alter table users add column utc_offset int not null default 0;
\ No newline at end of file
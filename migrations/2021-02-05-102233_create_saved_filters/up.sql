@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS saved_filters (
+    id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    name varchar(100) NOT NULL,
+    target varchar(20) NOT NULL,
+    criteria text NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY saved_filters_name_idx (coach_id, target, name),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
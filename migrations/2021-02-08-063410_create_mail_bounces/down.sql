@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+alter table users drop column email_invalid;
+DROP TABLE mail_bounces;
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS mail_bounces (
+    id varchar(100) NOT NULL,
+    email varchar(255) NOT NULL,
+    bounce_type varchar(20) NOT NULL,
+    reason text,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX mail_bounces_email_idx ON mail_bounces(email);
+
+alter table users add column email_invalid tinyint(1) NOT NULL DEFAULT '0';
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+alter table session_files drop column metadata_status;
+alter table session_files drop column poster_path;
+alter table session_files drop column height;
+alter table session_files drop column width;
+alter table session_files drop column duration_seconds;
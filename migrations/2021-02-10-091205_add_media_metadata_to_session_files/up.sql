@@ -0,0 +1,5 @@
+alter table session_files add column duration_seconds int;
+alter table session_files add column width int;
+alter table session_files add column height int;
+alter table session_files add column poster_path varchar(255);
+alter table session_files add column metadata_status varchar(20) NOT NULL DEFAULT 'none';
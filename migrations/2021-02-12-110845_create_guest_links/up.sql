@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS guest_links (
+    id varchar(100) NOT NULL,
+    session_id varchar(100) NOT NULL,
+    created_by_id varchar(100) NOT NULL,
+    guest_name varchar(255) NOT NULL,
+    guest_email varchar(255),
+    token varchar(100) NOT NULL,
+    expires_at datetime NOT NULL,
+    revoked_at datetime,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY guest_links_token_idx (token),
+    FOREIGN KEY (session_id) REFERENCES sessions(id),
+    FOREIGN KEY (created_by_id) REFERENCES users(id)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS board_annotations (
+    id varchar(100) NOT NULL,
+    board_id varchar(100) NOT NULL,
+    board_name varchar(255) NOT NULL,
+    created_by_id varchar(100) NOT NULL,
+    overlay text NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY board_annotations_author_idx (board_id, board_name, created_by_id),
+    FOREIGN KEY (created_by_id) REFERENCES users(id)
+);
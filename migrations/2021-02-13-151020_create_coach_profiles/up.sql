@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS coach_profiles (
+    coach_id varchar(100) NOT NULL,
+    headline varchar(255) NOT NULL DEFAULT '',
+    specialties varchar(1024) NOT NULL DEFAULT '',
+    languages varchar(255) NOT NULL DEFAULT '',
+    is_listed boolean NOT NULL DEFAULT false,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (coach_id),
+    INDEX coach_profiles_listed_idx (is_listed),
+    FOREIGN KEY (coach_id) REFERENCES coaches(id)
+);
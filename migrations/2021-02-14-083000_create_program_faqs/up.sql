@@ -0,0 +1,39 @@
+CREATE TABLE IF NOT EXISTS program_faqs (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    question text NOT NULL,
+    answer text NOT NULL,
+    position integer NOT NULL DEFAULT 0,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (program_id) REFERENCES programs(id)
+);
+
+CREATE TABLE IF NOT EXISTS program_questions (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    asked_by_id varchar(100) NOT NULL,
+    question text NOT NULL,
+    answer text,
+    answered_at datetime,
+    faq_id varchar(100),
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (program_id) REFERENCES programs(id),
+    FOREIGN KEY (asked_by_id) REFERENCES users(id),
+    FOREIGN KEY (faq_id) REFERENCES program_faqs(id) ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    kind varchar(50) NOT NULL,
+    subject varchar(255) NOT NULL,
+    ref_id varchar(100) NOT NULL,
+    read_at datetime,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX notifications_user_idx (user_id, read_at),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
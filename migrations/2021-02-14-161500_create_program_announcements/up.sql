@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS program_announcements (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    title varchar(255) NOT NULL,
+    content text NOT NULL,
+    publish_at datetime,
+    published_at datetime,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX program_announcements_due_idx (published_at, publish_at),
+    FOREIGN KEY (program_id) REFERENCES programs(id),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    event varchar(50) NOT NULL,
+    format varchar(20) NOT NULL,
+    url varchar(512) NOT NULL,
+    is_active boolean NOT NULL DEFAULT true,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX webhook_subscriptions_event_idx (coach_id, event),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id varchar(100) NOT NULL,
+    subscription_id varchar(100) NOT NULL,
+    url varchar(512) NOT NULL,
+    body text NOT NULL,
+    status varchar(20) NOT NULL DEFAULT 'pending',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX webhook_deliveries_status_idx (status),
+    FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE stat_refresh_queue;
+DROP TABLE coach_daily_stats;
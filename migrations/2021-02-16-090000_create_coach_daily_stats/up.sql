@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS coach_daily_stats (
+    coach_id varchar(100) NOT NULL,
+    day date NOT NULL,
+    enrollments_created integer NOT NULL DEFAULT 0,
+    sessions_created integer NOT NULL DEFAULT 0,
+    sessions_done integer NOT NULL DEFAULT 0,
+    sessions_cancelled integer NOT NULL DEFAULT 0,
+    tasks_responded integer NOT NULL DEFAULT 0,
+    refreshed_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (coach_id, day),
+    FOREIGN KEY (coach_id) REFERENCES coaches(id)
+);
+
+CREATE TABLE IF NOT EXISTS stat_refresh_queue (
+    coach_id varchar(100) NOT NULL,
+    day date NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (coach_id, day),
+    INDEX stat_refresh_queue_created_idx (created_at),
+    FOREIGN KEY (coach_id) REFERENCES coaches(id)
+);
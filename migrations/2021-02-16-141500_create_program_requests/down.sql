@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE program_offers;
+DROP TABLE program_requests;
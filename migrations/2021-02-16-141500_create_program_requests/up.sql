@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS program_requests (
+    id varchar(100) NOT NULL,
+    member_id varchar(100) NOT NULL,
+    title varchar(255) NOT NULL,
+    needs text NOT NULL,
+    tags varchar(1024) NOT NULL DEFAULT '',
+    status varchar(20) NOT NULL DEFAULT 'open',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX program_requests_member_idx (member_id),
+    INDEX program_requests_status_idx (status, created_at),
+    FOREIGN KEY (member_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS program_offers (
+    id varchar(100) NOT NULL,
+    request_id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    message text NOT NULL,
+    status varchar(20) NOT NULL DEFAULT 'pending',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY program_offers_unique_idx (request_id, program_id),
+    FOREIGN KEY (request_id) REFERENCES program_requests(id) ON DELETE CASCADE,
+    FOREIGN KEY (coach_id) REFERENCES coaches(id),
+    FOREIGN KEY (program_id) REFERENCES programs(id)
+);
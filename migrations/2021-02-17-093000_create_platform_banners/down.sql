@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE banner_dismissals;
+DROP TABLE platform_banners;
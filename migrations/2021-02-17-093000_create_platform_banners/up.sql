@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS platform_banners (
+    id varchar(100) NOT NULL,
+    created_by_id varchar(100) NOT NULL,
+    title varchar(255) NOT NULL,
+    message text NOT NULL,
+    severity varchar(20) NOT NULL DEFAULT 'info',
+    audience varchar(20) NOT NULL DEFAULT 'all',
+    starts_at datetime NOT NULL,
+    ends_at datetime NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX platform_banners_window_idx (starts_at, ends_at),
+    FOREIGN KEY (created_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS banner_dismissals (
+    banner_id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    dismissed_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (banner_id, user_id),
+    FOREIGN KEY (banner_id) REFERENCES platform_banners(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
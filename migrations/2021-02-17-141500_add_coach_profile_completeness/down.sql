@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE coach_profiles
+    DROP INDEX coach_profiles_completeness_idx,
+    DROP COLUMN missing,
+    DROP COLUMN completeness,
+    DROP COLUMN intro_video_url,
+    DROP COLUMN availability,
+    DROP COLUMN avatar_url,
+    DROP COLUMN bio;
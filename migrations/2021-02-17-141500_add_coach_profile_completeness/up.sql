@@ -0,0 +1,13 @@
+ALTER TABLE coach_profiles
+    ADD COLUMN bio varchar(2000) NOT NULL DEFAULT '',
+    ADD COLUMN avatar_url varchar(255) NOT NULL DEFAULT '',
+    ADD COLUMN availability varchar(255) NOT NULL DEFAULT '',
+    ADD COLUMN intro_video_url varchar(255) NOT NULL DEFAULT '',
+    ADD COLUMN completeness integer NOT NULL DEFAULT 0,
+    ADD COLUMN missing varchar(255) NOT NULL DEFAULT ',availability,avatar,bio,intro_video,program,',
+    ADD INDEX coach_profiles_completeness_idx (completeness);
+
+-- The existing profiles have only their programs to count.
+UPDATE coach_profiles
+SET completeness = 20, missing = ',availability,avatar,bio,intro_video,'
+WHERE EXISTS (SELECT 1 FROM programs WHERE programs.coach_id = coach_profiles.coach_id AND programs.active = true);
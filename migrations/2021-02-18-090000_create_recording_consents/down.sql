@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS conference_recordings;
+DROP TABLE IF EXISTS recording_consents;
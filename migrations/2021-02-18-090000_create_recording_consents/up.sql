@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS recording_consents (
+    session_user_id varchar(100) NOT NULL,
+    conference_id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    decision varchar(20) NOT NULL,
+    decided_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (session_user_id),
+    INDEX recording_consents_conference_idx (conference_id),
+    FOREIGN KEY (session_user_id) REFERENCES session_users(id) ON DELETE CASCADE,
+    FOREIGN KEY (conference_id) REFERENCES conferences(id),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS conference_recordings (
+    id varchar(100) NOT NULL,
+    conference_id varchar(100) NOT NULL,
+    attached_by_id varchar(100) NOT NULL,
+    location varchar(1024) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX conference_recordings_conference_idx (conference_id, created_at),
+    FOREIGN KEY (conference_id) REFERENCES conferences(id),
+    FOREIGN KEY (attached_by_id) REFERENCES users(id)
+);
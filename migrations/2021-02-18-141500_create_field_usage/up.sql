@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS field_usage (
+    day date NOT NULL,
+    operation_name varchar(100) NOT NULL,
+    type_name varchar(100) NOT NULL,
+    field_name varchar(100) NOT NULL,
+    hits bigint NOT NULL DEFAULT 0,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (day, operation_name, type_name, field_name),
+    INDEX field_usage_field_idx (type_name, field_name, day)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS demo_sandboxes (
+    id varchar(100) NOT NULL,
+    coach_user_id varchar(100) NOT NULL,
+    member_user_id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (coach_user_id) REFERENCES users(id),
+    FOREIGN KEY (member_user_id) REFERENCES users(id),
+    FOREIGN KEY (program_id) REFERENCES programs(id)
+);
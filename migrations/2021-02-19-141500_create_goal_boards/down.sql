@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS goal_comments;
+DROP TABLE IF EXISTS goal_cards;
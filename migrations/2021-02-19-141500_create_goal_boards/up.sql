@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS goal_cards (
+    id varchar(100) NOT NULL,
+    enrollment_id varchar(100) NOT NULL,
+    column_name varchar(60) NOT NULL,
+    position int NOT NULL DEFAULT 0,
+    title varchar(200) NOT NULL,
+    description varchar(2000) NOT NULL DEFAULT '',
+    status varchar(20) NOT NULL DEFAULT 'open',
+    created_by_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX goal_cards_enrollment_idx (enrollment_id, column_name, position),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id),
+    FOREIGN KEY (created_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS goal_comments (
+    id varchar(100) NOT NULL,
+    card_id varchar(100) NOT NULL,
+    author_id varchar(100) NOT NULL,
+    comment varchar(1000) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX goal_comments_card_idx (card_id, created_at),
+    FOREIGN KEY (card_id) REFERENCES goal_cards(id) ON DELETE CASCADE,
+    FOREIGN KEY (author_id) REFERENCES users(id)
+);
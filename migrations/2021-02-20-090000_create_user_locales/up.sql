@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS user_locales (
+    user_id varchar(100) NOT NULL,
+    locale varchar(35) NOT NULL,
+    time_zone varchar(64) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS file_access_log (
+    id varchar(100) NOT NULL,
+    asset_class varchar(20) NOT NULL,
+    file_path varchar(1024) NOT NULL,
+    accessor_id varchar(100),
+    ip varchar(64),
+    accessed_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX file_access_log_file_idx (file_path(191), accessed_at),
+    INDEX file_access_log_accessor_idx (accessor_id, accessed_at)
+);
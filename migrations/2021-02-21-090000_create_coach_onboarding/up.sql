@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS coach_onboarding (
+    coach_id varchar(100) NOT NULL,
+    payment_account varchar(64) NULL,
+    completed_at datetime NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (coach_id),
+    FOREIGN KEY (coach_id) REFERENCES coaches(id) ON DELETE CASCADE
+);
+
+-- The coaches who joined before the onboarding have published already.
+INSERT INTO coach_onboarding (coach_id, completed_at)
+SELECT id, CURRENT_TIMESTAMP FROM coaches;
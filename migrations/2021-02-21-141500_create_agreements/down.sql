@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS agreement_acceptances;
+DROP TABLE IF EXISTS agreements;
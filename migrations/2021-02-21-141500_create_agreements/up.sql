@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS agreements (
+    id varchar(100) NOT NULL,
+    version varchar(20) NOT NULL,
+    title varchar(255) NOT NULL,
+    body text NOT NULL,
+    is_required boolean NOT NULL DEFAULT true,
+    published_by_id varchar(100) NOT NULL,
+    published_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY agreements_version_idx (version),
+    INDEX agreements_published_idx (is_required, published_at),
+    FOREIGN KEY (published_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS agreement_acceptances (
+    user_id varchar(100) NOT NULL,
+    agreement_id varchar(100) NOT NULL,
+    accepted_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, agreement_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (agreement_id) REFERENCES agreements(id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS fee_rules;
+DROP TABLE IF EXISTS fee_schedules;
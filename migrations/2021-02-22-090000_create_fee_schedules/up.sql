@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS fee_schedules (
+    id varchar(100) NOT NULL,
+    effective_from datetime NOT NULL,
+    note varchar(255) NOT NULL DEFAULT '',
+    created_by_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY fee_schedules_effective_idx (effective_from),
+    FOREIGN KEY (created_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS fee_rules (
+    id varchar(100) NOT NULL,
+    schedule_id varchar(100) NOT NULL,
+    genre_id varchar(50) NULL,
+    fee_bps integer NOT NULL,
+    PRIMARY KEY (id),
+    INDEX fee_rules_schedule_idx (schedule_id),
+    FOREIGN KEY (schedule_id) REFERENCES fee_schedules(id) ON DELETE CASCADE,
+    FOREIGN KEY (genre_id) REFERENCES program_genres(id)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS session_objectives (
+    session_id varchar(100) NOT NULL,
+    objective_id varchar(100) NOT NULL,
+    rating integer NULL,
+    tagged_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (session_id, objective_id),
+    INDEX session_objectives_objective_idx (objective_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (objective_id) REFERENCES objectives(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS task_extensions;
+DROP TABLE IF EXISTS member_absences;
+DROP TABLE IF EXISTS late_policies;
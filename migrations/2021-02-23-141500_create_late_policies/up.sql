@@ -0,0 +1,38 @@
+CREATE TABLE IF NOT EXISTS late_policies (
+    program_id varchar(100) NOT NULL,
+    grace_days integer NOT NULL DEFAULT 0,
+    extend_on_absence boolean NOT NULL DEFAULT false,
+    updated_by_id varchar(100) NOT NULL,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (program_id),
+    FOREIGN KEY (program_id) REFERENCES programs(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS member_absences (
+    id varchar(100) NOT NULL,
+    member_id varchar(100) NOT NULL,
+    from_date datetime NOT NULL,
+    to_date datetime NOT NULL,
+    reason varchar(255) NOT NULL,
+    recorded_by_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX member_absences_member_idx (member_id, from_date),
+    FOREIGN KEY (member_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (recorded_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS task_extensions (
+    id varchar(100) NOT NULL,
+    task_id varchar(100) NOT NULL,
+    absence_id varchar(100) NULL,
+    reason varchar(20) NOT NULL,
+    previous_end_date datetime NOT NULL,
+    new_end_date datetime NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY task_extensions_absence_idx (task_id, absence_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (absence_id) REFERENCES member_absences(id) ON DELETE CASCADE
+);
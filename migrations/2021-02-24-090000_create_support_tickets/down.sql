@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS ticket_messages;
+DROP TABLE IF EXISTS support_tickets;
+
+DELETE FROM correspondences WHERE program_id IS NULL OR enrollment_id IS NULL;
+ALTER TABLE correspondences MODIFY program_id varchar(100) NOT NULL;
+ALTER TABLE correspondences MODIFY enrollment_id varchar(100) NOT NULL;
@@ -0,0 +1,31 @@
+-- The mails of the support tickets belong to no program.
+ALTER TABLE correspondences MODIFY program_id varchar(100) NULL;
+ALTER TABLE correspondences MODIFY enrollment_id varchar(100) NULL;
+
+CREATE TABLE IF NOT EXISTS support_tickets (
+    id varchar(100) NOT NULL,
+    raised_by_id varchar(100) NOT NULL,
+    category varchar(20) NOT NULL,
+    subject varchar(255) NOT NULL,
+    status varchar(20) NOT NULL DEFAULT 'OPEN',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    resolved_at datetime NULL,
+    PRIMARY KEY (id),
+    INDEX support_tickets_raiser_idx (raised_by_id, created_at),
+    INDEX support_tickets_status_idx (status, created_at),
+    FOREIGN KEY (raised_by_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS ticket_messages (
+    id varchar(100) NOT NULL,
+    ticket_id varchar(100) NOT NULL,
+    author_id varchar(100) NOT NULL,
+    body text NOT NULL,
+    attachments text NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    INDEX ticket_messages_ticket_idx (ticket_id, created_at),
+    FOREIGN KEY (ticket_id) REFERENCES support_tickets(id) ON DELETE CASCADE,
+    FOREIGN KEY (author_id) REFERENCES users(id) ON DELETE CASCADE
+);
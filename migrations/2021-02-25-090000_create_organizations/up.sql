@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS organizations (
+    id varchar(100) NOT NULL,
+    name varchar(255) NOT NULL,
+    created_by_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY organizations_name_idx (name),
+    FOREIGN KEY (created_by_id) REFERENCES users(id)
+);
+
+CREATE TABLE IF NOT EXISTS organization_members (
+    organization_id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    role varchar(10) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (organization_id, user_id),
+    INDEX organization_members_user_idx (user_id),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
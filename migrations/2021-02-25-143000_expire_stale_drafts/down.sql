@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX sessions_stale_request_idx ON sessions;
+
+ALTER TABLE conferences DROP COLUMN expired_at;
+ALTER TABLE sessions DROP COLUMN expired_at;
@@ -0,0 +1,5 @@
+-- The requests and the conferences that were never scheduled are expired by the "stale-drafts" job.
+ALTER TABLE sessions ADD COLUMN expired_at datetime NULL;
+ALTER TABLE conferences ADD COLUMN expired_at datetime NULL;
+
+CREATE INDEX sessions_stale_request_idx ON sessions (is_request, expired_at, created_at);
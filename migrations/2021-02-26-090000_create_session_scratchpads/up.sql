@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS session_scratchpads (
+    session_id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    content mediumtext NOT NULL,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (session_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (coach_id) REFERENCES users(id) ON DELETE CASCADE
+);
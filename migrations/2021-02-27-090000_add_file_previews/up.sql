@@ -0,0 +1,2 @@
+-- The width and height, so far of the videos, now hold the dimensions of the images too.
+ALTER TABLE session_files ADD COLUMN page_count int NULL;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE programs DROP COLUMN description_text;
+ALTER TABLE program_announcements DROP COLUMN content_text;
+ALTER TABLE correspondences DROP COLUMN content_text;
@@ -0,0 +1,4 @@
+-- The plain text of the rich fields, written along with them; the rows before are left to the API to strip.
+ALTER TABLE programs ADD COLUMN description_text TEXT NULL;
+ALTER TABLE program_announcements ADD COLUMN content_text TEXT NULL;
+ALTER TABLE correspondences ADD COLUMN content_text TEXT NULL;
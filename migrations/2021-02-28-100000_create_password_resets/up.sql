@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS password_resets (
+    id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    token varchar(100) NOT NULL,
+    expires_at datetime NOT NULL,
+    used_at datetime,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY password_resets_token_idx (token),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
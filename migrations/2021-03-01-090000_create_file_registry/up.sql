@@ -0,0 +1,12 @@
+-- The uploaded files by their path, and the SHA-256 of their content; the content itself is stored once.
+CREATE TABLE IF NOT EXISTS file_registry (
+    id varchar(100) NOT NULL,
+    file_path varchar(700) NOT NULL,
+    checksum char(64) NOT NULL,
+    size bigint NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY file_registry_path_idx (file_path),
+    KEY file_registry_checksum_idx (checksum)
+);
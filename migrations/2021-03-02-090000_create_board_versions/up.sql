@@ -0,0 +1,13 @@
+-- Every upload of a board is kept as a version, so that its evolution can be replayed.
+CREATE TABLE IF NOT EXISTS board_versions (
+    id varchar(100) NOT NULL,
+    board_id varchar(100) NOT NULL,
+    board_name varchar(255) NOT NULL,
+    version int NOT NULL,
+    file_path varchar(700) NOT NULL,
+    checksum char(64) NOT NULL,
+    size bigint NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY board_versions_version_idx (board_id, board_name, version)
+);
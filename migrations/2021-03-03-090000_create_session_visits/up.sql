@@ -0,0 +1,12 @@
+-- A visit runs from the join of a user to the conference of a session until the leave.
+CREATE TABLE IF NOT EXISTS session_visits (
+    id varchar(100) NOT NULL,
+    session_id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    joined_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    left_at datetime NULL,
+    PRIMARY KEY (id),
+    INDEX session_visits_user_idx (session_id, user_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
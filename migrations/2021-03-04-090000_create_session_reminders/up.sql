@@ -0,0 +1,10 @@
+-- Marks the people reminded of a session, or of a conference, starting at a time.
+-- A session rescheduled after its reminder is reminded again for its new start.
+CREATE TABLE IF NOT EXISTS session_reminders (
+    event_id varchar(100) NOT NULL,
+    user_id varchar(100) NOT NULL,
+    starts_at datetime NOT NULL,
+    reminded_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (event_id, user_id, starts_at),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE program_waitlists;
+ALTER TABLE programs DROP COLUMN max_members;
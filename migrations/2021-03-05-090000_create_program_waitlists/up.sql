@@ -0,0 +1,14 @@
+-- A program may cap its members; those who come after the cap wait in its line.
+ALTER TABLE programs ADD COLUMN max_members INT NULL;
+
+CREATE TABLE IF NOT EXISTS program_waitlists (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    member_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    promoted_at datetime NULL,
+    PRIMARY KEY (id),
+    UNIQUE KEY program_waitlists_member_idx (program_id, member_id),
+    FOREIGN KEY (program_id) REFERENCES programs(id),
+    FOREIGN KEY (member_id) REFERENCES users(id)
+);
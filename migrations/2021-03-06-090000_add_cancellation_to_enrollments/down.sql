@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE enrollments DROP COLUMN end_reason;
+ALTER TABLE enrollments DROP COLUMN ended_at;
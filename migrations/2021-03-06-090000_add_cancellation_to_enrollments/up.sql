@@ -0,0 +1,3 @@
+-- An enrollment ends with a reason, by the member or by the coach; the row stays for the history.
+ALTER TABLE enrollments ADD COLUMN ended_at datetime NULL;
+ALTER TABLE enrollments ADD COLUMN end_reason text NULL;
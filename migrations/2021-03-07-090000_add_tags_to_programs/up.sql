@@ -0,0 +1,2 @@
+-- The tags of a program, lowercase and wrapped in commas like ",leadership,sales,", to be matched with LIKE.
+ALTER TABLE programs ADD COLUMN tags varchar(1024) NOT NULL DEFAULT '';
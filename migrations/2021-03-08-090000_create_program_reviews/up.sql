@@ -0,0 +1,19 @@
+-- A member reviews a program once per enrollment; the coach may hide an abusive review.
+CREATE TABLE IF NOT EXISTS program_reviews (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    enrollment_id varchar(100) NOT NULL,
+    member_id varchar(100) NOT NULL,
+    rating INT NOT NULL,
+    comment text NOT NULL,
+    status varchar(20) NOT NULL DEFAULT 'visible',
+    hidden_reason varchar(255) NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY program_reviews_enrollment_idx (enrollment_id),
+    KEY program_reviews_program_idx (program_id, status),
+    FOREIGN KEY (program_id) REFERENCES programs(id),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id),
+    FOREIGN KEY (member_id) REFERENCES users(id)
+);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE discussion_queue DROP COLUMN read_at;
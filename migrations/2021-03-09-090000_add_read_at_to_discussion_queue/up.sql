@@ -0,0 +1,2 @@
+-- When the feed was read, for the sender to know; a feed read before this change has none.
+ALTER TABLE discussion_queue ADD COLUMN read_at datetime NULL;
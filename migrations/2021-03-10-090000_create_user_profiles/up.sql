@@ -0,0 +1,12 @@
+-- What a user, coach or member, tells about oneself; the social links are kept one per line.
+CREATE TABLE IF NOT EXISTS user_profiles (
+    user_id varchar(100) NOT NULL,
+    headline varchar(255) NOT NULL DEFAULT '',
+    about text NOT NULL,
+    experience text NOT NULL,
+    social_links varchar(1536) NOT NULL DEFAULT '',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE weekly_digests;
+ALTER TABLE user_profiles DROP COLUMN weekly_digest;
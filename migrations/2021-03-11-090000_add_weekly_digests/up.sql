@@ -0,0 +1,11 @@
+-- The weekly digest of the enrollments, which a member may turn off on the profile.
+-- An enrollment is marked for the Monday of each week its digest went out.
+ALTER TABLE user_profiles ADD COLUMN weekly_digest bool NOT NULL DEFAULT true;
+
+CREATE TABLE IF NOT EXISTS weekly_digests (
+    enrollment_id varchar(100) NOT NULL,
+    week_of datetime NOT NULL,
+    sent_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (enrollment_id, week_of),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE coach_availability_exceptions;
+DROP TABLE coach_availability;
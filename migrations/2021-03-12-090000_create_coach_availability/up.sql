@@ -0,0 +1,29 @@
+-- The weekly windows a coach takes sessions in, in the local time of the coach:
+-- the weekday from 0 for Monday, and the minutes of the day the window opens and closes.
+CREATE TABLE IF NOT EXISTS coach_availability (
+    id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    weekday int NOT NULL,
+    from_minute int NOT NULL,
+    to_minute int NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
+
+CREATE INDEX coach_availability_coach_idx ON coach_availability (coach_id);
+
+-- The spans, in UTC, that depart from the weekly windows: a day off, or hours given besides.
+CREATE TABLE IF NOT EXISTS coach_availability_exceptions (
+    id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    from_date datetime NOT NULL,
+    to_date datetime NOT NULL,
+    is_available bool NOT NULL DEFAULT false,
+    reason varchar(255) NOT NULL DEFAULT '',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
+
+CREATE INDEX coach_availability_exceptions_coach_idx ON coach_availability_exceptions (coach_id, from_date);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE sessions DROP FOREIGN KEY sessions_parent_conference_fk;
+DROP INDEX sessions_parent_conference_idx ON sessions;
+ALTER TABLE sessions DROP COLUMN parent_conference_id;
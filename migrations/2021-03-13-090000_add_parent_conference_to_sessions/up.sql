@@ -0,0 +1,4 @@
+-- A one to one follow-up of a conference keeps the conference it came from.
+ALTER TABLE sessions ADD COLUMN parent_conference_id varchar(100) NULL;
+ALTER TABLE sessions ADD CONSTRAINT sessions_parent_conference_fk FOREIGN KEY (parent_conference_id) REFERENCES conferences(id);
+CREATE INDEX sessions_parent_conference_idx ON sessions (parent_conference_id);
@@ -0,0 +1,13 @@
+-- The structured summary a coach keeps of a session, shared with its member:
+-- what went well, the action items one per line, and the next steps.
+CREATE TABLE IF NOT EXISTS session_summaries (
+    session_id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    went_well text NOT NULL,
+    action_items text NOT NULL,
+    next_steps text NOT NULL,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (session_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (coach_id) REFERENCES users(id) ON DELETE CASCADE
+);
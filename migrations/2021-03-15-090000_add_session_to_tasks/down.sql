@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE tasks DROP FOREIGN KEY tasks_session_fk;
+DROP INDEX tasks_session_idx ON tasks;
+ALTER TABLE tasks DROP COLUMN session_id;
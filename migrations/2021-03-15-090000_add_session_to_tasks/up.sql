@@ -0,0 +1,4 @@
+-- A task made of an action item of a session keeps the session it came from.
+ALTER TABLE tasks ADD COLUMN session_id varchar(100) NULL;
+ALTER TABLE tasks ADD CONSTRAINT tasks_session_fk FOREIGN KEY (session_id) REFERENCES sessions(id);
+CREATE INDEX tasks_session_idx ON tasks (session_id);
@@ -0,0 +1,2 @@
+-- Who besides the coach of the enrollment reads an observation: none, the member, or the peer coaches.
+ALTER TABLE observations ADD COLUMN visibility varchar(20) NOT NULL DEFAULT 'private';
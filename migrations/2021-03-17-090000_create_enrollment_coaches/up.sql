@@ -0,0 +1,14 @@
+-- The peer coaches sharing an enrollment with the coach of its program, say to
+-- stand in for the sessions while the coach is away.
+CREATE TABLE IF NOT EXISTS enrollment_coaches (
+    enrollment_id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    added_by_id varchar(100) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (enrollment_id, coach_id),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id),
+    FOREIGN KEY (coach_id) REFERENCES users(id),
+    FOREIGN KEY (added_by_id) REFERENCES users(id)
+);
+
+CREATE INDEX enrollment_coaches_coach_idx ON enrollment_coaches (coach_id);
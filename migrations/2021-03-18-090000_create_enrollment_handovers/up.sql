@@ -0,0 +1,20 @@
+-- An enrollment handed over from the spawned program of one peer coach to another's,
+-- with the note the coach left for the one taking over.
+CREATE TABLE IF NOT EXISTS enrollment_handovers (
+    id varchar(100) NOT NULL,
+    enrollment_id varchar(100) NOT NULL,
+    from_program_id varchar(100) NOT NULL,
+    to_program_id varchar(100) NOT NULL,
+    from_coach_id varchar(100) NOT NULL,
+    to_coach_id varchar(100) NOT NULL,
+    note text NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id),
+    FOREIGN KEY (from_program_id) REFERENCES programs(id),
+    FOREIGN KEY (to_program_id) REFERENCES programs(id),
+    FOREIGN KEY (from_coach_id) REFERENCES users(id),
+    FOREIGN KEY (to_coach_id) REFERENCES users(id)
+);
+
+CREATE INDEX enrollment_handovers_enrollment_idx ON enrollment_handovers (enrollment_id);
@@ -0,0 +1,5 @@
+-- A program is drafted, submitted for review, published to the catalog and in the end retired.
+ALTER TABLE programs ADD COLUMN status varchar(20) NOT NULL DEFAULT 'draft';
+
+-- The programs so far were in the catalog as soon as they were created.
+UPDATE programs SET status = 'published';
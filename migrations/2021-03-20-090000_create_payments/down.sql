@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE payments;
+ALTER TABLE programs DROP COLUMN currency;
+ALTER TABLE programs DROP COLUMN price_cents;
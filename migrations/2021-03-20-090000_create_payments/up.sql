@@ -0,0 +1,26 @@
+-- The price of a program in the cents of its currency; a program without a price is free.
+ALTER TABLE programs ADD COLUMN price_cents int NULL;
+ALTER TABLE programs ADD COLUMN currency varchar(3) NULL;
+
+-- A payment intent of a member at the payment gateway, to enroll in a paid program.
+-- The card details never reach us; the gateway reference is the id of the intent
+-- at the gateway. A confirmed payment is used up by the enrollment it paid for.
+CREATE TABLE IF NOT EXISTS payments (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    member_id varchar(100) NOT NULL,
+    enrollment_id varchar(100) NULL,
+    amount_cents int NOT NULL,
+    currency varchar(3) NOT NULL,
+    gateway_ref varchar(255) NOT NULL,
+    status varchar(20) NOT NULL DEFAULT 'pending',
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY (gateway_ref),
+    FOREIGN KEY (program_id) REFERENCES programs(id),
+    FOREIGN KEY (member_id) REFERENCES users(id),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id)
+);
+
+CREATE INDEX payments_program_member_idx ON payments (program_id, member_id);
@@ -0,0 +1,31 @@
+-- A discount code the coach of a program hands out, for a percent or an amount
+-- off the price, within a window and up to a number of uses.
+CREATE TABLE IF NOT EXISTS coupons (
+    id varchar(100) NOT NULL,
+    program_id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    code varchar(40) NOT NULL,
+    percent_off int NULL,
+    amount_off_cents int NULL,
+    valid_from datetime NOT NULL,
+    valid_until datetime NOT NULL,
+    max_uses int NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY (program_id, code),
+    FOREIGN KEY (program_id) REFERENCES programs(id),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
+
+-- The coupon an enrollment was made with, and the discount it gave.
+CREATE TABLE IF NOT EXISTS coupon_redemptions (
+    enrollment_id varchar(100) NOT NULL,
+    coupon_id varchar(100) NOT NULL,
+    discount_cents int NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (enrollment_id),
+    FOREIGN KEY (enrollment_id) REFERENCES enrollments(id),
+    FOREIGN KEY (coupon_id) REFERENCES coupons(id)
+);
+
+CREATE INDEX coupon_redemptions_coupon_idx ON coupon_redemptions (coupon_id);
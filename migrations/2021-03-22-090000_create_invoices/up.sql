@@ -0,0 +1,17 @@
+-- The monthly invoice of a coach, for the enrollments paid for in the month,
+-- in one currency. The PDF is kept in the platform assets.
+CREATE TABLE IF NOT EXISTS invoices (
+    id varchar(100) NOT NULL,
+    coach_id varchar(100) NOT NULL,
+    period_start datetime NOT NULL,
+    currency varchar(3) NOT NULL,
+    enrollment_count int NOT NULL,
+    gross_cents bigint NOT NULL,
+    fee_cents bigint NOT NULL,
+    net_cents bigint NOT NULL,
+    file_path varchar(255) NOT NULL,
+    created_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY (coach_id, period_start, currency),
+    FOREIGN KEY (coach_id) REFERENCES users(id)
+);
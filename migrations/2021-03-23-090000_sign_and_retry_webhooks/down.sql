@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX webhook_deliveries_due_idx ON webhook_deliveries;
+
+ALTER TABLE webhook_deliveries DROP COLUMN updated_at;
+ALTER TABLE webhook_deliveries DROP COLUMN delivered_at;
+ALTER TABLE webhook_deliveries DROP COLUMN last_error;
+ALTER TABLE webhook_deliveries DROP COLUMN response_code;
+ALTER TABLE webhook_deliveries DROP COLUMN next_attempt_at;
+ALTER TABLE webhook_deliveries DROP COLUMN attempts;
+ALTER TABLE webhook_deliveries DROP COLUMN event;
+
+UPDATE webhook_subscriptions SET event = 'task_response' WHERE event = 'task.responded';
+ALTER TABLE webhook_subscriptions DROP COLUMN secret;
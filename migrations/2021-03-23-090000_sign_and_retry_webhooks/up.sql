@@ -0,0 +1,19 @@
+-- The deliveries are signed with a secret of the webhook, which the coach checks them with.
+ALTER TABLE webhook_subscriptions ADD COLUMN secret varchar(64) NOT NULL DEFAULT '';
+UPDATE webhook_subscriptions SET secret = SHA2(CONCAT(id, RAND(), NOW(6)), 256);
+
+-- The events are named as the coach reads them in the body.
+UPDATE webhook_subscriptions SET event = 'task.responded' WHERE event = 'task_response';
+
+-- A delivery that failed is tried again later, up to a number of attempts.
+ALTER TABLE webhook_deliveries ADD COLUMN event varchar(50) NOT NULL DEFAULT '';
+ALTER TABLE webhook_deliveries ADD COLUMN attempts int NOT NULL DEFAULT 0;
+ALTER TABLE webhook_deliveries ADD COLUMN next_attempt_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE webhook_deliveries ADD COLUMN response_code int NULL;
+ALTER TABLE webhook_deliveries ADD COLUMN last_error varchar(255) NULL;
+ALTER TABLE webhook_deliveries ADD COLUMN delivered_at datetime NULL;
+ALTER TABLE webhook_deliveries ADD COLUMN updated_at datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP;
+
+UPDATE webhook_deliveries d INNER JOIN webhook_subscriptions s ON s.id = d.subscription_id SET d.event = s.event;
+
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (status, next_attempt_at);
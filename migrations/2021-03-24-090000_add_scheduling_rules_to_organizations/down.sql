@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE organizations DROP COLUMN session_holidays;
+ALTER TABLE organizations DROP COLUMN session_hours;
@@ -0,0 +1,3 @@
+-- The scheduling rules of an organization take the place of those of the platform for its coaches.
+ALTER TABLE organizations ADD COLUMN session_hours varchar(11) NULL;
+ALTER TABLE organizations ADD COLUMN session_holidays text NULL;
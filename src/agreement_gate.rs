@@ -0,0 +1,134 @@
+/**
+ * The terms of service gate of the GraphQL endpoint. A user who is yet to accept
+ * the latest required agreement may only read it, accept it or sign in again;
+ * any other operation is answered with a TOS_REQUIRED error, in the shape of the
+ * GraphQL errors, carrying the agreement to accept.
+ *
+ * The clients send the token of the signed in user in the X-Session-Token
+ * header; see commons::session_tokens. A request without it may only sign in,
+ * register, or read and accept the agreement; any other is answered with the
+ * same error, as no one has accepted anything yet.
+ */
+use actix_web::error::BlockingError;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+
+use crate::commons::ids::UserId;
+use crate::commons::session_tokens;
+use crate::db_manager::checkout;
+use crate::field_usage;
+use crate::field_usage::FieldCatalog;
+use crate::graphql_schema::DBContext;
+use crate::models::agreements::Agreement;
+use crate::services::agreements::{latest_agreement, pending_agreement};
+
+pub const SESSION_HEADER: &str = "X-Session-Token";
+
+const TOS_REQUIRED: &str = "TOS_REQUIRED";
+
+const SIGN_IN_REQUIRED: &str = "Please sign in to go on.";
+
+// The root fields a user may use before accepting.
+const OPEN_FIELDS: [&str; 3] = ["authenticate", "getAgreementStatus", "acceptAgreement"];
+
+// The root fields open to the requests of no one; the register of a new user besides the above.
+const ANONYMOUS_FIELDS: [&str; 4] = ["authenticate", "createUser", "getAgreementStatus", "acceptAgreement"];
+
+/**
+ * The id of the signed in user who sent the request, if any; a token we did not
+ * sign, or one that has expired, signs no one in.
+ */
+pub fn caller(request: &HttpRequest) -> Option<UserId> {
+    let value = request.headers().get(SESSION_HEADER)?.to_str().ok()?;
+
+    session_tokens::verify(value)
+}
+
+pub async fn admit(request: &HttpRequest, ctx: web::Data<DBContext>, query: Option<&str>) -> Result<(), HttpResponse> {
+    let user_id = caller(request);
+    let open_fields: &[&str] = if user_id.is_some() { &OPEN_FIELDS } else { &ANONYMOUS_FIELDS };
+
+    // A query we cannot read is rejected by juniper anyway.
+    if query.map(|query| is_open(query, &ctx.usage.catalog, open_fields)).unwrap_or(true) {
+        return Ok(());
+    }
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        // None lets the request through; a request of no one is turned away with or without an agreement.
+        match user_id {
+            Some(user_id) => pending_agreement(&connection, &user_id).map(|agreement| agreement.map(Some)),
+            None => latest_agreement(&connection).map(Some),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(None) => Ok(()),
+        Ok(Some(agreement)) => Err(HttpResponse::Ok().content_type("application/json").body(tos_required(agreement.as_ref()).to_string())),
+        Err(BlockingError::Error(e)) => Err(HttpResponse::InternalServerError().body(e)),
+        Err(BlockingError::Canceled) => Err(HttpResponse::InternalServerError().finish()),
+    }
+}
+
+// True when every root field of the query is open; the introspection is always open.
+fn is_open(query: &str, catalog: &FieldCatalog, open_fields: &[&str]) -> bool {
+    let selection = field_usage::select(query, catalog.query_root.as_str(), catalog.mutation_root.as_str(), |type_name, field_name| {
+        catalog.returns(type_name, field_name)
+    });
+
+    let selection = match selection {
+        Some(value) => value,
+        None => return true,
+    };
+
+    selection
+        .fields
+        .iter()
+        .filter(|(type_name, _)| *type_name == catalog.query_root || *type_name == catalog.mutation_root)
+        .all(|(_, field_name)| open_fields.contains(&field_name.as_str()))
+}
+
+// Without a caller the latest agreement is still the one to accept; without any, the sign in is.
+fn tos_required(agreement: Option<&Agreement>) -> serde_json::Value {
+    let agreement = match agreement {
+        Some(value) => value,
+        None => {
+            return json!({
+                "data": null,
+                "errors": [{ "message": SIGN_IN_REQUIRED, "extensions": { "code": TOS_REQUIRED } }]
+            })
+        }
+    };
+
+    json!({
+        "data": null,
+        "errors": [{
+            "message": format!("Please accept the {} (version {}) to go on.", agreement.title, agreement.version),
+            "extensions": {
+                "code": TOS_REQUIRED,
+                "agreementId": agreement.id,
+                "version": agreement.version,
+            }
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::graphql_schema::create_gq_schema;
+
+    #[test]
+    fn should_open_only_the_sign_in_to_no_one() {
+        let catalog = FieldCatalog::of(&create_gq_schema());
+
+        assert!(is_open(r#"{ authenticate(request: {email: "a@b.c", password: "x"}) { token } }"#, &catalog, &ANONYMOUS_FIELDS));
+        assert!(is_open(r#"mutation { createUser(registration: {fullName: "A", email: "a@b.c", password: "x"}) { errors { message } } }"#, &catalog, &ANONYMOUS_FIELDS));
+        assert!(is_open("{ __schema { queryType { name } } }", &catalog, &ANONYMOUS_FIELDS));
+
+        assert!(!is_open(r#"{ getTasks(criteria: {enrollmentId: "e-1"}) { tasks { id } } }"#, &catalog, &ANONYMOUS_FIELDS));
+        assert!(!is_open(r#"mutation { createUser(registration: {fullName: "A", email: "a@b.c", password: "x"}) { errors { message } } }"#, &catalog, &OPEN_FIELDS));
+    }
+}
@@ -0,0 +1,168 @@
+/**
+ * The cross-origin and the content rules for serving the assets.
+ *
+ * The API is open to any origin, but the assets are not alike: the boards and
+ * the program contents are embedded in the web app, while the user contents
+ * are mostly downloaded. Each class of asset reads its rules from the
+ * environment, falling back to the defaults below.
+ *
+ * ASSET_<CLASS>_ORIGINS      comma separated origins, or * for any
+ * ASSET_<CLASS>_CSP          the Content-Security-Policy of the response
+ * ASSET_<CLASS>_DISPOSITION  inline or attachment
+ * ASSET_<CLASS>_CACHE        the Cache-Control of the response
+ *
+ * The files answer the conditional requests from their ETag and Last-Modified,
+ * and the range requests, as the videos of the programs are played by seeking
+ * within them; NamedFile does both once the validators are on. The Cache-Control
+ * goes with the file, and its 304 and 206, never with an error.
+ */
+use actix_cors::Cors;
+use actix_files::NamedFile;
+use actix_web::http::header::{self, ContentDisposition, DispositionParam, DispositionType, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::middleware::DefaultHeaders;
+use actix_web::{Error, HttpRequest, HttpResponse};
+
+const ANY_ORIGIN: &str = "*";
+
+// Assets are data, never code; nothing within them may load or run anything.
+const STRICT_CSP: &str = "default-src 'none'; img-src 'self'; media-src 'self'; style-src 'unsafe-inline'; sandbox";
+
+#[derive(Clone, Copy)]
+pub enum AssetClass {
+    Boards,
+    Programs,
+    Users,
+    Platform,
+}
+
+pub struct AssetPolicy {
+    pub origins: Vec<String>,
+    pub csp: String,
+    pub inline: bool,
+    pub cache: String,
+}
+
+impl AssetClass {
+    fn key(&self) -> &'static str {
+        match self {
+            AssetClass::Boards => "BOARDS",
+            AssetClass::Programs => "PROGRAMS",
+            AssetClass::Users => "USERS",
+            AssetClass::Platform => "PLATFORM",
+        }
+    }
+
+    // As the class is recorded in the file access log.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AssetClass::Boards => "boards",
+            AssetClass::Programs => "programs",
+            AssetClass::Users => "users",
+            AssetClass::Platform => "platform",
+        }
+    }
+
+    // A board is saved again under its name, so it is revalidated within the hour; the platform files seldom change.
+    fn cache_by_default(&self) -> &'static str {
+        match self {
+            AssetClass::Boards => "private, max-age=3600",
+            AssetClass::Programs => "private, max-age=86400",
+            AssetClass::Users => "private, max-age=3600",
+            AssetClass::Platform => "public, max-age=604800",
+        }
+    }
+
+    // The user contents are personal files; they are downloaded rather than rendered.
+    fn inline_by_default(&self) -> bool {
+        !matches!(self, AssetClass::Users)
+    }
+
+    pub fn policy(&self) -> AssetPolicy {
+        let setting = |name: &str| std::env::var(format!("ASSET_{}_{}", self.key(), name)).ok();
+
+        let origins = setting("ORIGINS")
+            .unwrap_or_else(|| String::from(ANY_ORIGIN))
+            .split(',')
+            .map(|origin| origin.trim().to_owned())
+            .filter(|origin| !origin.is_empty())
+            .collect();
+
+        let inline = match setting("DISPOSITION").as_deref() {
+            Some("inline") => true,
+            Some("attachment") => false,
+            _ => self.inline_by_default(),
+        };
+
+        let cache = setting("CACHE")
+            .filter(|value| HeaderValue::from_str(value).is_ok())
+            .unwrap_or_else(|| String::from(self.cache_by_default()));
+
+        AssetPolicy {
+            origins,
+            csp: setting("CSP").unwrap_or_else(|| String::from(STRICT_CSP)),
+            inline,
+            cache,
+        }
+    }
+}
+
+impl AssetPolicy {
+    pub fn cors(&self) -> Cors {
+        let cors = Cors::default().allowed_methods(vec!["GET"]).allow_any_header().max_age(3600);
+
+        if self.origins.iter().any(|origin| origin == ANY_ORIGIN) {
+            return cors.allow_any_origin();
+        }
+
+        self.origins.iter().fold(cors, |cors, origin| cors.allowed_origin(origin.as_str()))
+    }
+
+    pub fn headers(&self) -> DefaultHeaders {
+        DefaultHeaders::new().header("Content-Security-Policy", self.csp.as_str()).header("X-Content-Type-Options", "nosniff")
+    }
+
+    pub fn disposition(&self, file_name: &str) -> ContentDisposition {
+        let disposition = if self.inline { DispositionType::Inline } else { DispositionType::Attachment };
+
+        ContentDisposition {
+            disposition,
+            parameters: vec![DispositionParam::Filename(file_name.to_owned())],
+        }
+    }
+}
+
+/**
+ * The response of the file being served, with the disposition and the caching
+ * of its class.
+ */
+pub fn respond(file: NamedFile, class: AssetClass, request: &HttpRequest) -> Result<HttpResponse, Error> {
+    let policy = class.policy();
+    let file_name = file.path().file_name().and_then(|name| name.to_str()).unwrap_or("asset").to_owned();
+
+    let mut response = file
+        .set_content_disposition(policy.disposition(file_name.as_str()))
+        .use_etag(true)
+        .use_last_modified(true)
+        .into_response(request)?;
+
+    if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+        if let Ok(value) = HeaderValue::from_str(policy.cache.as_str()) {
+            response.headers_mut().insert(header::CACHE_CONTROL, value);
+        }
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_cache_the_boards_privately_by_default() {
+        assert_eq!("private, max-age=3600", AssetClass::Boards.policy().cache);
+        assert!(AssetClass::Platform.policy().cache.starts_with("public"));
+    }
+}
@@ -0,0 +1,279 @@
+/**
+ * A synthetic traffic generator for a running Ferris instance.
+ *
+ * It replays the workloads the web app produces - the dashboard queries,
+ * the session creation and the note uploads - from a number of concurrent
+ * workers and reports the latency percentiles per workload. It is meant to
+ * validate the pool size and the web::block offloading, not to test features.
+ *
+ * cargo run --features loadgen --bin loadgen -- --url http://localhost:8088 --concurrency 16 --requests 50 --scenario mixed
+ *
+ * The ids to act upon are taken from the environment:
+ *
+ * LOAD_USER_ID            the user whose dashboard is fetched
+ * LOAD_PROGRAM_ID         the program and the member for the new sessions
+ * LOAD_MEMBER_ID
+ * LOAD_SESSION_USER_ID    the session user who uploads the note files
+ *
+ * Creating sessions and uploading files write to the target database, so point
+ * it only at a staging or a local instance.
+ */
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const BOUNDARY: &str = "----ferris-loadgen-boundary";
+
+struct Config {
+    host: String,
+    concurrency: usize,
+    requests: usize,
+    scenario: String,
+}
+
+impl Config {
+    fn from_args() -> Config {
+        let mut config = Config {
+            host: String::from("localhost:8088"),
+            concurrency: 8,
+            requests: 25,
+            scenario: String::from("dashboard"),
+        };
+
+        let args: Vec<String> = std::env::args().skip(1).collect();
+
+        for pair in args.chunks(2) {
+            let value = pair.get(1).cloned().unwrap_or_default();
+            match pair[0].as_str() {
+                "--url" => config.host = value.trim_start_matches("http://").trim_end_matches('/').to_owned(),
+                "--concurrency" => config.concurrency = value.parse().unwrap_or(config.concurrency),
+                "--requests" => config.requests = value.parse().unwrap_or(config.requests),
+                "--scenario" => config.scenario = value,
+                other => eprintln!("Ignoring the unknown option {}", other),
+            }
+        }
+
+        config
+    }
+}
+
+fn env_id(key: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| panic!("{} should be set for this scenario", key))
+}
+
+struct Workload {
+    name: &'static str,
+    path: &'static str,
+    content_type: String,
+    body: Vec<u8>,
+}
+
+fn graphql(name: &'static str, query: String) -> Workload {
+    let body = serde_json::json!({ "query": query }).to_string();
+
+    Workload {
+        name,
+        path: "/graphql",
+        content_type: String::from("application/json"),
+        body: body.into_bytes(),
+    }
+}
+
+fn dashboard_workloads() -> Vec<Workload> {
+    let user_id = env_id("LOAD_USER_ID");
+
+    let today = chrono::Utc::now().date();
+    let from = (today - chrono::Duration::days(29)).format("%Y-%m-%d");
+    let to = today.format("%Y-%m-%d");
+
+    vec![
+        graphql(
+            "getPrograms",
+            format!(r#"{{ getPrograms(criteria: {{userId: "{}", programId: "", desire: ENROLLED}}) {{ error {{ message }} }} }}"#, user_id),
+        ),
+        graphql("getEvents", format!(r#"{{ getEvents(criteria: {{userId: "{}"}}) {{ error {{ message }} }} }}"#, user_id)),
+        graphql("getDue", format!(r#"{{ getDue(criteria: {{userId: "{}"}}) {{ error {{ message }} }} }}"#, user_id)),
+        graphql("getPendingDiscussions", format!(r#"{{ getPendingDiscussions(criteria: {{id: "{}"}}) {{ error {{ message }} }} }}"#, user_id)),
+        graphql(
+            "getCoachStats",
+            format!(r#"{{ getCoachStats(criteria: {{coachId: "{}", from: "{}", to: "{}"}}) {{ error {{ message }} }} }}"#, user_id, from, to),
+        ),
+    ]
+}
+
+fn session_workloads(sequence: usize) -> Vec<Workload> {
+    let program_id = env_id("LOAD_PROGRAM_ID");
+    let member_id = env_id("LOAD_MEMBER_ID");
+
+    // Spread the sessions over the coming days so that they do not pile up on a single slot.
+    let start = chrono::Utc::now() + chrono::Duration::days(1 + (sequence % 60) as i64);
+    let start_time = start.format("%Y-%m-%dT%H:%M:%SZ");
+
+    vec![graphql(
+        "createSession",
+        format!(
+            r#"mutation {{ createSession(newSessionRequest: {{programId: "{}", memberId: "{}", name: "Load {}", description: "Synthetic session", duration: 30, startTime: "{}"}}) {{ errors {{ message }} }} }}"#,
+            program_id, member_id, sequence, start_time
+        ),
+    )]
+}
+
+fn upload_workloads(sequence: usize) -> Vec<Workload> {
+    let session_user_id = env_id("LOAD_SESSION_USER_ID");
+
+    let content = "x".repeat(64 * 1024);
+
+    let body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"load-{sequence}.txt\"\r\nContent-Type: text/plain\r\n\r\n{content}\r\n--{boundary}--\r\n",
+        boundary = BOUNDARY,
+        name = session_user_id,
+        sequence = sequence,
+        content = content
+    );
+
+    vec![Workload {
+        name: "uploadNote",
+        path: "/assets/upload",
+        content_type: format!("multipart/form-data; boundary={}", BOUNDARY),
+        body: body.into_bytes(),
+    }]
+}
+
+fn workloads(scenario: &str, sequence: usize) -> Vec<Workload> {
+    match scenario {
+        "sessions" => session_workloads(sequence),
+        "uploads" => upload_workloads(sequence),
+        "mixed" => {
+            let mut all = dashboard_workloads();
+            if sequence % 5 == 0 {
+                all.extend(session_workloads(sequence));
+            }
+            if sequence % 10 == 0 {
+                all.extend(upload_workloads(sequence));
+            }
+            all
+        }
+        _ => dashboard_workloads(),
+    }
+}
+
+// A bare HTTP/1.1 exchange; we want the round trip of the server, not of a client library.
+fn send(host: &str, workload: &Workload) -> Result<u16, String> {
+    let mut stream = TcpStream::connect(host).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(Duration::from_secs(60))).map_err(|e| e.to_string())?;
+
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        workload.path,
+        host,
+        workload.content_type,
+        workload.body.len()
+    );
+
+    stream.write_all(head.as_bytes()).map_err(|e| e.to_string())?;
+    stream.write_all(&workload.body).map_err(|e| e.to_string())?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(|e| e.to_string())?;
+
+    let status_line = String::from_utf8_lossy(&response);
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| String::from("Unreadable response"))
+}
+
+#[derive(Default)]
+struct Stats {
+    latencies: Vec<Duration>,
+    failures: usize,
+}
+
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::from_millis(0);
+    }
+
+    let rank = ((pct / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
+
+fn main() {
+    let config = Config::from_args();
+
+    println!(
+        "Running the {} scenario against {} with {} workers, {} iterations each",
+        config.scenario, config.host, config.concurrency, config.requests
+    );
+
+    // Fail here, rather than in every worker, when an id is missing.
+    workloads(config.scenario.as_str(), 0);
+
+    let stats: Arc<Mutex<HashMap<&'static str, Stats>>> = Arc::new(Mutex::new(HashMap::new()));
+    let started = Instant::now();
+
+    let workers: Vec<_> = (0..config.concurrency)
+        .map(|worker| {
+            let stats = stats.clone();
+            let host = config.host.clone();
+            let scenario = config.scenario.clone();
+            let requests = config.requests;
+
+            thread::spawn(move || {
+                for iteration in 0..requests {
+                    let sequence = worker * requests + iteration;
+
+                    for workload in workloads(scenario.as_str(), sequence) {
+                        let begin = Instant::now();
+                        let outcome = send(host.as_str(), &workload);
+                        let elapsed = begin.elapsed();
+
+                        let mut stats = stats.lock().unwrap();
+                        let entry = stats.entry(workload.name).or_default();
+                        match outcome {
+                            Ok(status) if status < 400 => entry.latencies.push(elapsed),
+                            _ => entry.failures += 1,
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let total = started.elapsed();
+    let mut stats = stats.lock().unwrap();
+
+    println!("{:<24}{:>8}{:>8}{:>10}{:>10}{:>10}{:>10}", "workload", "ok", "failed", "p50 ms", "p90 ms", "p99 ms", "max ms");
+
+    let mut names: Vec<&'static str> = stats.keys().cloned().collect();
+    names.sort_unstable();
+
+    let mut completed = 0;
+    for name in names {
+        let entry = stats.get_mut(name).unwrap();
+        entry.latencies.sort();
+        completed += entry.latencies.len();
+
+        let sorted = &entry.latencies;
+        println!(
+            "{:<24}{:>8}{:>8}{:>10}{:>10}{:>10}{:>10}",
+            name,
+            sorted.len(),
+            entry.failures,
+            percentile(sorted, 50.0).as_millis(),
+            percentile(sorted, 90.0).as_millis(),
+            percentile(sorted, 99.0).as_millis(),
+            sorted.last().cloned().unwrap_or_default().as_millis()
+        );
+    }
+
+    println!("{} requests completed in {:.1}s ({:.1} per second)", completed, total.as_secs_f64(), completed as f64 / total.as_secs_f64());
+}
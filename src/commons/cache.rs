@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/**
+ * A cache of the hot reads, kept in the process for a short while.
+ *
+ * CACHE_TTL_SECS=30 (how long an entry is served; 0 turns the caching off)
+ *
+ * The writes of this process clear the entries they touch; the writes of the
+ * other instances of the server are seen once the entries expire.
+ */
+const TTL_KEY: &str = "CACHE_TTL_SECS";
+const DEFAULT_TTL_SECS: u64 = 30;
+
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration, capacity: usize) -> TtlCache<K, V> {
+        TtlCache {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_env(capacity: usize) -> TtlCache<K, V> {
+        let seconds = std::env::var(TTL_KEY).ok().and_then(|value| value.trim().parse().ok()).unwrap_or(DEFAULT_TTL_SECS);
+
+        TtlCache::new(Duration::from_secs(seconds), capacity)
+    }
+
+    /**
+     * The value of the key while it is fresh, else the loaded one, which is kept
+     * when the load succeeds. The loading runs outside the lock, so two requests
+     * missing together may both load.
+     */
+    pub fn get_or_load<E, F>(&self, key: &K, load: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        if self.ttl.is_zero() {
+            return load();
+        }
+
+        if let Some(value) = self.fresh(key) {
+            return Ok(value);
+        }
+
+        let value = load()?;
+        self.keep(key.clone(), value.clone());
+
+        Ok(value)
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn fresh(&self, key: &K) -> Option<V> {
+        let entries = self.lock();
+
+        entries.get(key).filter(|(kept_at, _)| kept_at.elapsed() < self.ttl).map(|(_, value)| value.clone())
+    }
+
+    // When full, the expired entries go first and then the oldest one.
+    fn keep(&self, key: K, value: V) {
+        let mut entries = self.lock();
+
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let ttl = self.ttl;
+            entries.retain(|_, (kept_at, _)| kept_at.elapsed() < ttl);
+
+            if entries.len() >= self.capacity {
+                let oldest = entries.iter().min_by_key(|(_, (kept_at, _))| *kept_at).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<K, (Instant, V)>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+
+    fn counted(loads: &Cell<i32>, value: i32) -> impl FnOnce() -> Result<i32, ()> + '_ {
+        move || {
+            loads.set(loads.get() + 1);
+            Ok(value)
+        }
+    }
+
+    #[test]
+    fn should_load_once_while_fresh() {
+        let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(60), 10);
+        let loads = Cell::new(0);
+
+        assert_eq!(Ok(1), cache.get_or_load(&"a", counted(&loads, 1)));
+        assert_eq!(Ok(1), cache.get_or_load(&"a", counted(&loads, 2)));
+        assert_eq!(1, loads.get());
+
+        cache.clear();
+        assert_eq!(Ok(3), cache.get_or_load(&"a", counted(&loads, 3)));
+        assert_eq!(2, loads.get());
+    }
+
+    #[test]
+    fn should_not_keep_without_a_ttl_or_on_failure() {
+        let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(0), 10);
+        let loads = Cell::new(0);
+
+        cache.get_or_load(&"a", counted(&loads, 1)).unwrap();
+        cache.get_or_load(&"a", counted(&loads, 1)).unwrap();
+        assert_eq!(2, loads.get());
+
+        let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(60), 10);
+        assert_eq!(Err(()), cache.get_or_load(&"a", || Err(())));
+        assert_eq!(Ok(4), cache.get_or_load(&"a", || Ok::<_, ()>(4)));
+    }
+
+    #[test]
+    fn should_drop_the_oldest_when_full() {
+        let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(60), 2);
+        let loads = Cell::new(0);
+
+        cache.get_or_load(&"a", counted(&loads, 1)).unwrap();
+        cache.get_or_load(&"b", counted(&loads, 2)).unwrap();
+        cache.get_or_load(&"c", counted(&loads, 3)).unwrap();
+        assert_eq!(3, loads.get());
+
+        cache.get_or_load(&"c", counted(&loads, 3)).unwrap();
+        cache.get_or_load(&"a", counted(&loads, 1)).unwrap();
+        assert_eq!(4, loads.get());
+    }
+}
@@ -1,15 +1,50 @@
+use crate::graphql_schema::DBContext;
 use crate::models::abstract_tasks::AbstractTask;
+use crate::models::admin::SystemStats;
+use crate::models::agreements::{Agreement, AgreementStatus};
+use crate::models::audit_events::AuditEntry;
+use crate::models::board_annotations::BoardAnnotation;
+use crate::models::board_versions::BoardVersion;
+use crate::models::coach_availability::{Availability, Slot};
+use crate::models::coach_onboarding::OnboardingChecklist;
+use crate::models::coach_profiles::{CoachProfile, DirectoryPage};
+use crate::models::coach_stats::CoachStats;
+use crate::models::conferences::{ConferenceMembers, Followups};
+use crate::models::correspondences::QueuedMails;
+use crate::models::coupons::Coupon;
+use crate::models::enrollment_coaches::EnrollmentCoach;
+use crate::models::enrollment_handovers::EnrollmentHandover;
 use crate::models::enrollments::Enrollment;
+use crate::models::fee_schedules::{EarningsStatement, FeeScheduleView};
+use crate::models::late_policies::{LatePolicy, MemberAbsence, TaskExtension};
 use crate::models::master_plans::MasterPlan;
+use crate::models::member_weeks::MemberWeek;
 use crate::models::master_tasks::MasterTask;
-use crate::models::notes::Note;
+use crate::models::notes::{Note, SessionFile};
+use crate::models::notifications::Notification;
 use crate::models::objectives::Objective;
 use crate::models::observations::Observation;
 use crate::models::options::Constraint;
+use crate::models::organizations::{Organization, OrganizationReport};
+use crate::models::payments::Payment;
+use crate::models::platform_banners::PlatformBanner;
+use crate::models::program_announcements::Announcement;
+use crate::models::program_faqs::{ProgramFaq, ProgramQuestion};
+use crate::models::program_requests::{ProgramOffer, ProgramRequest, RequestRow};
+use crate::models::program_reviews::ProgramReview;
+use crate::models::program_waitlists::{ProgramWaitlist, WaitingMember};
 use crate::models::programs::{Program,ProgramCoach};
+use crate::models::recording_consents::{ConferenceRecording, ConsentSheet, RecordingConsent};
+use crate::models::session_cancellations::CancellationPreview;
+use crate::models::session_merges::MergePreview;
+use crate::models::session_objectives::EnrollmentProgress;
+use crate::models::session_scratchpads::{Scratchpad, SessionScratchpad};
+use crate::models::session_summaries::SessionSummary;
+use crate::models::session_visits::{Attendee, SessionVisit};
 use crate::models::sessions::Session;
 use crate::models::session_users::SessionPeople;
 use crate::models::conferences::Conference;
+use crate::models::support_tickets::{SupportTicket, TicketThread};
 use crate::models::tasks::Task;
 use crate::models::user_events::{EventRow, PlanRow, ToDo};
 
@@ -20,6 +55,14 @@ use crate::models::user_artifacts::BoardRow;
 use crate::models::correspondences::Mailable;
 use crate::models::discussions::Discussion;
 use crate::models::discussion_queue::PendingFeed;
+use crate::models::data_fixes::FixPreview;
+use crate::models::field_usage::FieldReport;
+use crate::models::file_access_log::FileAccess;
+use crate::models::goal_boards::{GoalBoard, GoalCardView};
+use crate::models::user_locales::LocaleBundle;
+use crate::models::saved_filters::SavedFilter;
+use crate::models::guest_links::{GuestAccess, GuestLink};
+use crate::models::invoices::Invoice;
 
 /**
  * Important: The Mutation Result might seem like a Code Duplication,
@@ -35,7 +78,9 @@ use crate::models::discussion_queue::PendingFeed;
  * For example, you can not make e.g. Result<T, E> into a GraphQL type,
  * but you can make e.g. Result<User, String> into a GraphQL type.
  */
+use crate::models::user_profiles::UserProfile;
 use crate::models::users::User;
+use crate::models::webhooks::{WebhookDelivery, WebhookSubscription};
 use diesel::result::Error;
 
 #[derive(juniper::GraphQLObject)]
@@ -55,6 +100,12 @@ impl From<String> for QueryError {
     }
 }
 
+impl From<&str> for QueryError {
+    fn from(message: &str) -> Self {
+        QueryError { message: String::from(message) }
+    }
+}
+
 
 
 #[derive(juniper::GraphQLObject)]
@@ -75,11 +126,124 @@ impl ValidationError {
 
 pub struct QueryResult<T>(pub Result<T, QueryError>);
 
-#[juniper::object(name = "ProgramsResult")]
-impl QueryResult<Vec<ProgramRow>> {
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+
+const BAD_PAGE: &str = "The limit of a page should be 1 to 500 and its offset should not be negative.";
+const BAD_CURSOR: &str = "The cursor is not one we gave; please start again from the first page.";
+
+/**
+ * A page of a list: a limit with either an offset or the cursor of the previous
+ * page, the cursor winning. Without a page the first 100 rows are given.
+ */
+#[derive(juniper::GraphQLInputObject, Default)]
+pub struct PageRequest {
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Window {
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl Window {
+    pub fn of(page: Option<&PageRequest>) -> Result<Window, &'static str> {
+        let page = match page {
+            Some(value) => value,
+            None => return Ok(Window { offset: 0, limit: DEFAULT_PAGE_SIZE }),
+        };
+
+        let limit = page.limit.map(i64::from).unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(BAD_PAGE);
+        }
+
+        let offset = match &page.cursor {
+            Some(cursor) => cursor.strip_prefix('o').and_then(|value| value.parse::<i64>().ok()).filter(|value| *value >= 0).ok_or(BAD_CURSOR)?,
+            None => page.offset.map(i64::from).unwrap_or(0),
+        };
+
+        if offset < 0 {
+            return Err(BAD_PAGE);
+        }
+
+        Ok(Window { offset, limit })
+    }
+
+    /**
+     * The rows to read: one beyond the limit tells whether another page follows.
+     */
+    pub fn fetch(&self) -> i64 {
+        self.limit + 1
+    }
+}
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /**
+     * The page out of the rows read for the window, at most one beyond its limit.
+     */
+    pub fn of(mut rows: Vec<T>, window: &Window) -> Page<T> {
+        let has_more = rows.len() as i64 > window.limit;
+        rows.truncate(window.limit as usize);
+
+        Page {
+            items: rows,
+            offset: window.offset,
+            has_more,
+        }
+    }
+
+    pub fn next_cursor(&self) -> Option<String> {
+        if !self.has_more {
+            return None;
+        }
+
+        Some(format!("o{}", self.offset + self.items.len() as i64))
+    }
+}
+
+pub struct PagedResult<T>(pub Result<Page<T>, QueryError>);
+
+pub fn page_error<T>(message: &str) -> PagedResult<T> {
+    PagedResult(Err(QueryError { message: String::from(message) }))
+}
+
+#[juniper::object(name = "UsersResult")]
+impl PagedResult<User> {
+    pub fn users(&self) -> Option<&Vec<User>> {
+        self.0.as_ref().ok().map(|page| &page.items)
+    }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(Context = DBContext, name = "ProgramsResult")]
+impl PagedResult<ProgramRow> {
     pub fn programs(&self) -> Option<&Vec<ProgramRow>> {
-        self.0.as_ref().ok()
+        self.0.as_ref().ok().map(|page| &page.items)
+    }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
     }
+
     pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
@@ -176,9 +340,41 @@ impl QueryResult<Vec<Task>> {
     }
 }
 
+#[juniper::object(name = "FilteredTasksResult")]
+impl PagedResult<Task> {
+    pub fn tasks(&self) -> Option<&Vec<Task>> {
+        self.0.as_ref().ok().map(|page| &page.items)
+    }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
 #[juniper::object(name = "NotesResult")]
-impl QueryResult<Vec<Note>> {
+impl PagedResult<Note> {
     pub fn notes(&self) -> Option<&Vec<Note>> {
+        self.0.as_ref().ok().map(|page| &page.items)
+    }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "NoteFilesResult")]
+impl QueryResult<Vec<SessionFile>> {
+    pub fn files(&self) -> Option<&Vec<SessionFile>> {
         self.0.as_ref().ok()
     }
     pub fn error(&self) -> Option<&QueryError> {
@@ -187,10 +383,16 @@ impl QueryResult<Vec<Note>> {
 }
 
 #[juniper::object(name = "DiscussionsResult")]
-impl QueryResult<Vec<Discussion>> {
+impl PagedResult<Discussion> {
     pub fn discussions(&self) -> Option<&Vec<Discussion>> {
-        self.0.as_ref().ok()
+        self.0.as_ref().ok().map(|page| &page.items)
     }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
+    }
+
     pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
@@ -216,9 +418,9 @@ impl QueryResult<Vec<BoardRow>> {
     }
 }
 
-#[juniper::object(name = "EventsResult")]
-impl QueryResult<Vec<EventRow>> {
-    pub fn sessions(&self) -> Option<&Vec<EventRow>> {
+#[juniper::object(name = "BoardVersions")]
+impl QueryResult<Vec<BoardVersion>> {
+    pub fn versions(&self) -> Option<&Vec<BoardVersion>> {
         self.0.as_ref().ok()
     }
     pub fn error(&self) -> Option<&QueryError> {
@@ -226,6 +428,22 @@ impl QueryResult<Vec<EventRow>> {
     }
 }
 
+#[juniper::object(Context = DBContext, name = "EventsResult")]
+impl PagedResult<EventRow> {
+    pub fn sessions(&self) -> Option<&Vec<EventRow>> {
+        self.0.as_ref().ok().map(|page| &page.items)
+    }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
 #[juniper::object(name = "ActivitiesResult")]
 impl QueryResult<Vec<PlanRow>> {
     pub fn planRows(&self) -> Option<&Vec<PlanRow>> {
@@ -256,7 +474,7 @@ impl QueryResult<Vec<SessionPeople>> {
     }
 }
 
-#[juniper::object(name = "CoachMembers")]
+#[juniper::object(Context = DBContext, name = "CoachMembers")]
 impl QueryResult<Vec<MemberRow>> {
     pub fn members(&self) -> Option<&Vec<MemberRow>> {
         self.0.as_ref().ok()
@@ -266,221 +484,1191 @@ impl QueryResult<Vec<MemberRow>> {
     }
 }
 
-#[juniper::object(name = "Mailables")]
-impl QueryResult<Vec<Mailable>> {
-    pub fn mails(&self) -> Option<&Vec<Mailable>> {
+#[juniper::object(Context = DBContext, name = "FilteredMembersResult")]
+impl PagedResult<MemberRow> {
+    pub fn members(&self) -> Option<&Vec<MemberRow>> {
+        self.0.as_ref().ok().map(|page| &page.items)
+    }
+
+    #[graphql(description = "The cursor of the next page; none on the last page")]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.0.as_ref().ok().and_then(|page| page.next_cursor())
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SavedFiltersResult")]
+impl QueryResult<Vec<SavedFilter>> {
+    pub fn filters(&self) -> Option<&Vec<SavedFilter>> {
         self.0.as_ref().ok()
     }
+
     pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-pub fn query_error<T>(error: diesel::result::Error) -> QueryResult<T> {
-    let message: String = error.to_string();
+#[juniper::object(name = "GuestLinksResult")]
+impl QueryResult<Vec<GuestLink>> {
+    pub fn links(&self) -> Option<&Vec<GuestLink>> {
+        self.0.as_ref().ok()
+    }
 
-    let e = QueryError {message };
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
 
-    QueryResult(Err(e))
+#[juniper::object(name = "GuestAccessResult")]
+impl QueryResult<GuestAccess> {
+    pub fn access(&self) -> Option<&GuestAccess> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
 }
 
+#[juniper::object(name = "CoachDirectoryResult")]
+impl QueryResult<DirectoryPage> {
+    pub fn directory(&self) -> Option<&DirectoryPage> {
+        self.0.as_ref().ok()
+    }
 
-pub struct MutationResult<T>(pub Result<T, Vec<ValidationError>>);
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
 
-#[juniper::object(name = "SessionResult")]
-impl MutationResult<Session> {
-    pub fn session(&self) -> Option<&Session> {
+#[juniper::object(name = "AgreementStatusQueryResult")]
+impl QueryResult<AgreementStatus> {
+    pub fn status(&self) -> Option<&AgreementStatus> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "ConferenceResult")]
-impl MutationResult<Conference> {
-    pub fn conference(&self) -> Option<&Conference> {
+#[juniper::object(name = "FeeSchedulesResult")]
+impl QueryResult<Vec<FeeScheduleView>> {
+    pub fn schedules(&self) -> Option<&Vec<FeeScheduleView>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "UserResult")]
-impl MutationResult<User> {
-    pub fn user(&self) -> Option<&User> {
+#[juniper::object(name = "EarningsResult")]
+impl QueryResult<EarningsStatement> {
+    pub fn statement(&self) -> Option<&EarningsStatement> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "AbstractTaskResult")]
-impl MutationResult<AbstractTask> {
-    pub fn abstract_task(&self) -> Option<&AbstractTask> {
+#[juniper::object(name = "MemberWeekResult")]
+impl QueryResult<MemberWeek> {
+    pub fn week(&self) -> Option<&MemberWeek> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "MasterPlanResut")]
-impl MutationResult<MasterPlan> {
-    pub fn master_plan(&self) -> Option<&MasterPlan> {
+#[juniper::object(name = "ProgressQueryResult")]
+impl QueryResult<EnrollmentProgress> {
+    pub fn progress(&self) -> Option<&EnrollmentProgress> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "ProgramResult")]
-impl MutationResult<Program> {
-    pub fn program(&self) -> Option<&Program> {
+#[juniper::object(name = "LatePolicyQueryResult")]
+impl QueryResult<LatePolicy> {
+    pub fn policy(&self) -> Option<&LatePolicy> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "EnrollmentResult")]
-impl MutationResult<Enrollment> {
-    pub fn enrollment(&self) -> Option<&Enrollment> {
+#[juniper::object(name = "TaskExtensionsResult")]
+impl QueryResult<Vec<TaskExtension>> {
+    pub fn extensions(&self) -> Option<&Vec<TaskExtension>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "NoteResult")]
-impl MutationResult<Note> {
-    pub fn note(&self) -> Option<&Note> {
+#[juniper::object(name = "TicketsResult")]
+impl QueryResult<Vec<SupportTicket>> {
+    pub fn tickets(&self) -> Option<&Vec<SupportTicket>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "DiscussionResult")]
-impl MutationResult<Discussion> {
-    pub fn discussion(&self) -> Option<&Discussion> {
+#[juniper::object(name = "TicketThreadQueryResult")]
+impl QueryResult<TicketThread> {
+    pub fn thread(&self) -> Option<&TicketThread> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "ObjectiveResult")]
-impl MutationResult<Objective> {
-    pub fn objective(&self) -> Option<&Objective> {
+#[juniper::object(name = "OrganizationReportResult")]
+impl QueryResult<OrganizationReport> {
+    pub fn report(&self) -> Option<&OrganizationReport> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "OptionResult")]
-impl MutationResult<Constraint> {
-    pub fn constraint(&self) -> Option<&Constraint> {
+#[juniper::object(name = "AttendanceResult")]
+impl QueryResult<Vec<Attendee>> {
+    pub fn attendees(&self) -> Option<&Vec<Attendee>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "ObservationResult")]
-impl MutationResult<Observation> {
-    pub fn observation(&self) -> Option<&Observation> {
+#[juniper::object(name = "ProgramReviewsResult")]
+impl QueryResult<Vec<ProgramReview>> {
+    pub fn reviews(&self) -> Option<&Vec<ProgramReview>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "TaskResult")]
-impl MutationResult<Task> {
-    pub fn task(&self) -> Option<&Task> {
+#[juniper::object(name = "WaitlistResult")]
+impl QueryResult<Vec<WaitingMember>> {
+    pub fn waiting(&self) -> Option<&Vec<WaitingMember>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "MasterTaskResult")]
-impl MutationResult<MasterTask> {
-    pub fn master_task(&self) -> Option<&MasterTask> {
+#[juniper::object(name = "ScratchpadsResult")]
+impl QueryResult<Vec<SessionScratchpad>> {
+    pub fn scratchpads(&self) -> Option<&Vec<SessionScratchpad>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "Updates")]
-impl MutationResult<String> {
-    pub fn rows(&self) -> Option<&String> {
+#[juniper::object(name = "HandoversResult")]
+impl QueryResult<Vec<EnrollmentHandover>> {
+    pub fn handovers(&self) -> Option<&Vec<EnrollmentHandover>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-#[juniper::object(name = "Array")]
-impl MutationResult<Vec<String>> {
-    pub fn rows(&self) -> Option<&Vec<String>> {
+#[juniper::object(name = "AuditTrailResult")]
+impl QueryResult<Vec<AuditEntry>> {
+    pub fn entries(&self) -> Option<&Vec<AuditEntry>> {
         self.0.as_ref().ok()
     }
 
-    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+    pub fn error(&self) -> Option<&QueryError> {
         self.0.as_ref().err()
     }
 }
 
-pub fn service_error<T>(message: &str) -> MutationResult<T> {
-    let mut v: Vec<ValidationError> = Vec::new();
-    let ve = ValidationError {
-        field: String::from("service"),
-        message: String::from(message),
-    };
-    v.push(ve);
-    MutationResult(Err(v))
+#[juniper::object(name = "OnboardingQueryResult")]
+impl QueryResult<OnboardingChecklist> {
+    pub fn onboarding(&self) -> Option<&OnboardingChecklist> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
 }
 
-pub fn mutation_error<T>(error: diesel::result::Error) -> MutationResult<T> {
-    let message: String = error.to_string();
+#[juniper::object(name = "CoachProfileQueryResult")]
+impl QueryResult<CoachProfile> {
+    pub fn profile(&self) -> Option<&CoachProfile> {
+        self.0.as_ref().ok()
+    }
 
-    let mut v: Vec<ValidationError> = Vec::new();
-    let ve = ValidationError {
-        field: String::from("service"),message,
-    };
-    v.push(ve);
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
 
-    MutationResult(Err(v))
+#[juniper::object(name = "UserProfileQueryResult")]
+impl QueryResult<UserProfile> {
+    pub fn profile(&self) -> Option<&UserProfile> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AvailabilityQueryResult")]
+impl QueryResult<Availability> {
+    pub fn availability(&self) -> Option<&Availability> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SlotsResult")]
+impl QueryResult<Vec<Slot>> {
+    pub fn slots(&self) -> Option<&Vec<Slot>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "CoachStatsResult")]
+impl QueryResult<CoachStats> {
+    pub fn stats(&self) -> Option<&CoachStats> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SystemStatsResult")]
+impl QueryResult<SystemStats> {
+    pub fn stats(&self) -> Option<&SystemStats> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "CouponsResult")]
+impl QueryResult<Vec<Coupon>> {
+    pub fn coupons(&self) -> Option<&Vec<Coupon>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "InvoicesResult")]
+impl QueryResult<Vec<Invoice>> {
+    pub fn invoices(&self) -> Option<&Vec<Invoice>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramFaqsResult")]
+impl QueryResult<Vec<ProgramFaq>> {
+    pub fn faqs(&self) -> Option<&Vec<ProgramFaq>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramQuestionsResult")]
+impl QueryResult<Vec<ProgramQuestion>> {
+    pub fn questions(&self) -> Option<&Vec<ProgramQuestion>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "NotificationsResult")]
+impl QueryResult<Vec<Notification>> {
+    pub fn notifications(&self) -> Option<&Vec<Notification>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AnnouncementsResult")]
+impl QueryResult<Vec<Announcement>> {
+    pub fn announcements(&self) -> Option<&Vec<Announcement>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "WebhooksResult")]
+impl QueryResult<Vec<WebhookSubscription>> {
+    pub fn webhooks(&self) -> Option<&Vec<WebhookSubscription>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "WebhookDeliveriesResult")]
+impl QueryResult<Vec<WebhookDelivery>> {
+    pub fn deliveries(&self) -> Option<&Vec<WebhookDelivery>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramRequestsResult")]
+impl QueryResult<Vec<RequestRow>> {
+    pub fn requests(&self) -> Option<&Vec<RequestRow>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "OpenProgramRequestsResult")]
+impl QueryResult<Vec<ProgramRequest>> {
+    pub fn requests(&self) -> Option<&Vec<ProgramRequest>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "BannersResult")]
+impl QueryResult<Vec<PlatformBanner>> {
+    pub fn banners(&self) -> Option<&Vec<PlatformBanner>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "RecordingConsentsResult")]
+impl QueryResult<ConsentSheet> {
+    pub fn sheet(&self) -> Option<&ConsentSheet> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "FieldUsageReportResult")]
+impl QueryResult<Vec<FieldReport>> {
+    pub fn fields(&self) -> Option<&Vec<FieldReport>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "FileAccessResult")]
+impl QueryResult<Vec<FileAccess>> {
+    pub fn accesses(&self) -> Option<&Vec<FileAccess>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "GoalBoardResult")]
+impl QueryResult<GoalBoard> {
+    pub fn board(&self) -> Option<&GoalBoard> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "Mailables")]
+impl QueryResult<Vec<Mailable>> {
+    pub fn mails(&self) -> Option<&Vec<Mailable>> {
+        self.0.as_ref().ok()
+    }
+    pub fn error(&self) -> Option<&QueryError> {
+        self.0.as_ref().err()
+    }
+}
+
+pub fn criteria_error<T>(message: &str) -> QueryResult<T> {
+    let e = QueryError { message: String::from(message) };
+
+    QueryResult(Err(e))
+}
+
+pub fn query_error<T>(error: diesel::result::Error) -> QueryResult<T> {
+    let message: String = error.to_string();
+
+    let e = QueryError {message };
+
+    QueryResult(Err(e))
+}
+
+
+pub struct MutationResult<T>(pub Result<T, Vec<ValidationError>>);
+
+#[juniper::object(name = "SessionResult")]
+impl MutationResult<Session> {
+    pub fn session(&self) -> Option<&Session> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ConferenceResult")]
+impl MutationResult<Conference> {
+    pub fn conference(&self) -> Option<&Conference> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "UserResult")]
+impl MutationResult<User> {
+    pub fn user(&self) -> Option<&User> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AbstractTaskResult")]
+impl MutationResult<AbstractTask> {
+    pub fn abstract_task(&self) -> Option<&AbstractTask> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "MasterPlanResut")]
+impl MutationResult<MasterPlan> {
+    pub fn master_plan(&self) -> Option<&MasterPlan> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramResult")]
+impl MutationResult<Program> {
+    pub fn program(&self) -> Option<&Program> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "EnrollmentResult")]
+impl MutationResult<Enrollment> {
+    pub fn enrollment(&self) -> Option<&Enrollment> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "NoteResult")]
+impl MutationResult<Note> {
+    pub fn note(&self) -> Option<&Note> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "DiscussionResult")]
+impl MutationResult<Discussion> {
+    pub fn discussion(&self) -> Option<&Discussion> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ObjectiveResult")]
+impl MutationResult<Objective> {
+    pub fn objective(&self) -> Option<&Objective> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "OptionResult")]
+impl MutationResult<Constraint> {
+    pub fn constraint(&self) -> Option<&Constraint> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ObservationResult")]
+impl MutationResult<Observation> {
+    pub fn observation(&self) -> Option<&Observation> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "TaskResult")]
+impl MutationResult<Task> {
+    pub fn task(&self) -> Option<&Task> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "MasterTaskResult")]
+impl MutationResult<MasterTask> {
+    pub fn master_task(&self) -> Option<&MasterTask> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "Updates")]
+impl MutationResult<String> {
+    pub fn rows(&self) -> Option<&String> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "Array")]
+impl MutationResult<Vec<String>> {
+    pub fn rows(&self) -> Option<&Vec<String>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ChangedTasksResult")]
+impl MutationResult<Vec<Task>> {
+    pub fn tasks(&self) -> Option<&Vec<Task>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "FollowupsResult")]
+impl MutationResult<Followups> {
+    pub fn sessions(&self) -> Option<&Vec<Session>> {
+        self.0.as_ref().ok().map(|value| &value.sessions)
+    }
+
+    pub fn notices(&self) -> Option<&QueuedMails> {
+        self.0.as_ref().ok().map(|value| &value.notices)
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ConferenceMembersResult")]
+impl MutationResult<ConferenceMembers> {
+    #[graphql(description = "The ids of the members the request went through")]
+    pub fn rows(&self) -> Option<&Vec<String>> {
+        self.0.as_ref().ok().map(|value| &value.members)
+    }
+
+    pub fn notices(&self) -> Option<&QueuedMails> {
+        self.0.as_ref().ok().map(|value| &value.notices)
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SavedFilterResult")]
+impl MutationResult<SavedFilter> {
+    pub fn filter(&self) -> Option<&SavedFilter> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "GuestLinkResult")]
+impl MutationResult<GuestLink> {
+    pub fn link(&self) -> Option<&GuestLink> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "BoardAnnotationResult")]
+impl MutationResult<BoardAnnotation> {
+    pub fn annotation(&self) -> Option<&BoardAnnotation> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AgreementResult")]
+impl MutationResult<Agreement> {
+    pub fn agreement(&self) -> Option<&Agreement> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AgreementStatusResult")]
+impl MutationResult<AgreementStatus> {
+    pub fn status(&self) -> Option<&AgreementStatus> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "FeeScheduleResult")]
+impl MutationResult<FeeScheduleView> {
+    pub fn schedule(&self) -> Option<&FeeScheduleView> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgressResult")]
+impl MutationResult<EnrollmentProgress> {
+    pub fn progress(&self) -> Option<&EnrollmentProgress> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "LatePolicyResult")]
+impl MutationResult<LatePolicy> {
+    pub fn policy(&self) -> Option<&LatePolicy> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AbsenceResult")]
+impl MutationResult<MemberAbsence> {
+    pub fn absence(&self) -> Option<&MemberAbsence> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "TicketThreadResult")]
+impl MutationResult<TicketThread> {
+    pub fn thread(&self) -> Option<&TicketThread> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "OrganizationResult")]
+impl MutationResult<Organization> {
+    pub fn organization(&self) -> Option<&Organization> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SessionVisitResult")]
+impl MutationResult<SessionVisit> {
+    pub fn visit(&self) -> Option<&SessionVisit> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramReviewResult")]
+impl MutationResult<ProgramReview> {
+    pub fn review(&self) -> Option<&ProgramReview> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "WaitlistEntryResult")]
+impl MutationResult<ProgramWaitlist> {
+    pub fn entry(&self) -> Option<&ProgramWaitlist> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ScratchpadResult")]
+impl MutationResult<Scratchpad> {
+    pub fn scratchpad(&self) -> Option<&Scratchpad> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "EnrollmentCoachesResult")]
+impl MutationResult<Vec<EnrollmentCoach>> {
+    pub fn co_coaches(&self) -> Option<&Vec<EnrollmentCoach>> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "CouponResult")]
+impl MutationResult<Coupon> {
+    pub fn coupon(&self) -> Option<&Coupon> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "PaymentResult")]
+impl MutationResult<Payment> {
+    pub fn payment(&self) -> Option<&Payment> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SessionSummaryResult")]
+impl MutationResult<SessionSummary> {
+    pub fn summary(&self) -> Option<&SessionSummary> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "OnboardingResult")]
+impl MutationResult<OnboardingChecklist> {
+    pub fn onboarding(&self) -> Option<&OnboardingChecklist> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "CoachProfileResult")]
+impl MutationResult<CoachProfile> {
+    pub fn profile(&self) -> Option<&CoachProfile> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "UserProfileResult")]
+impl MutationResult<UserProfile> {
+    pub fn profile(&self) -> Option<&UserProfile> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AvailabilityResult")]
+impl MutationResult<Availability> {
+    pub fn availability(&self) -> Option<&Availability> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramFaqResult")]
+impl MutationResult<ProgramFaq> {
+    pub fn faq(&self) -> Option<&ProgramFaq> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramQuestionResult")]
+impl MutationResult<ProgramQuestion> {
+    pub fn question(&self) -> Option<&ProgramQuestion> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "AnnouncementResult")]
+impl MutationResult<Announcement> {
+    pub fn announcement(&self) -> Option<&Announcement> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "WebhookResult")]
+impl MutationResult<WebhookSubscription> {
+    pub fn webhook(&self) -> Option<&WebhookSubscription> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramRequestResult")]
+impl MutationResult<ProgramRequest> {
+    pub fn request(&self) -> Option<&ProgramRequest> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ProgramOfferResult")]
+impl MutationResult<ProgramOffer> {
+    pub fn offer(&self) -> Option<&ProgramOffer> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "BannerResult")]
+impl MutationResult<PlatformBanner> {
+    pub fn banner(&self) -> Option<&PlatformBanner> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "RecordingConsentResult")]
+impl MutationResult<RecordingConsent> {
+    pub fn consent(&self) -> Option<&RecordingConsent> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "ConferenceRecordingResult")]
+impl MutationResult<ConferenceRecording> {
+    pub fn recording(&self) -> Option<&ConferenceRecording> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "GoalCardResult")]
+impl MutationResult<GoalCardView> {
+    pub fn card(&self) -> Option<&GoalCardView> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "LocaleResult")]
+impl MutationResult<LocaleBundle> {
+    pub fn locale(&self) -> Option<&LocaleBundle> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "BulkCancellationResult")]
+impl MutationResult<CancellationPreview> {
+    pub fn cancellation(&self) -> Option<&CancellationPreview> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "SessionMergeResult")]
+impl MutationResult<MergePreview> {
+    pub fn merge(&self) -> Option<&MergePreview> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+#[juniper::object(name = "DataFixResult")]
+impl MutationResult<FixPreview> {
+    pub fn fix(&self) -> Option<&FixPreview> {
+        self.0.as_ref().ok()
+    }
+
+    pub fn errors(&self) -> Option<&Vec<ValidationError>> {
+        self.0.as_ref().err()
+    }
+}
+
+pub fn service_error<T>(message: &str) -> MutationResult<T> {
+    let mut v: Vec<ValidationError> = Vec::new();
+    let ve = ValidationError {
+        field: String::from("service"),
+        message: String::from(message),
+    };
+    v.push(ve);
+    MutationResult(Err(v))
+}
+
+pub fn mutation_error<T>(error: diesel::result::Error) -> MutationResult<T> {
+    let message: String = error.to_string();
+
+    let mut v: Vec<ValidationError> = Vec::new();
+    let ve = ValidationError {
+        field: String::from("service"),message,
+    };
+    v.push(ve);
+
+    MutationResult(Err(v))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn page(limit: Option<i32>, offset: Option<i32>, cursor: Option<&str>) -> PageRequest {
+        PageRequest {
+            limit,
+            offset,
+            cursor: cursor.map(String::from),
+        }
+    }
+
+    #[test]
+    fn should_window_the_page() {
+        assert_eq!(Ok(Window { offset: 0, limit: 100 }), Window::of(None));
+        assert_eq!(Ok(Window { offset: 20, limit: 10 }), Window::of(Some(&page(Some(10), Some(20), None))));
+        assert_eq!(Ok(Window { offset: 40, limit: 10 }), Window::of(Some(&page(Some(10), Some(20), Some("o40")))));
+
+        assert_eq!(Err(BAD_PAGE), Window::of(Some(&page(Some(0), None, None))));
+        assert_eq!(Err(BAD_PAGE), Window::of(Some(&page(None, Some(-1), None))));
+        assert_eq!(Err(BAD_CURSOR), Window::of(Some(&page(None, None, Some("40")))));
+    }
+
+    #[test]
+    fn should_point_to_the_next_page_while_rows_remain() {
+        let window = Window { offset: 20, limit: 2 };
+
+        let first = Page::of(vec![1, 2, 3], &window);
+        assert_eq!(vec![1, 2], first.items);
+        assert_eq!(Some(String::from("o22")), first.next_cursor());
+
+        let last = Page::of(vec![1, 2], &window);
+        assert_eq!(None, last.next_cursor());
+    }
 }
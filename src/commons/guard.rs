@@ -0,0 +1,153 @@
+/**
+ * The authorization of the mutations. The caller is the signed in user, whose
+ * signed token the clients send in the X-Session-Token header; see
+ * commons::session_tokens. A guarded mutation first checks that
+ * the caller holds one of the allowed roles on its target, so that guessing the id
+ * of an enrollment is not enough to change its tasks.
+ *
+ * The coach of a program is the coach of its enrollments and of their tasks; the
+ * member of an enrollment is the member of its tasks. The co-coaches of an
+ * enrollment hold the role of its coach on it. The admins of the platform hold
+ * every role.
+ */
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::services::users;
+
+use crate::schema::enrollment_coaches;
+use crate::schema::enrollments;
+use crate::schema::master_plans;
+use crate::schema::observations;
+use crate::schema::programs;
+use crate::schema::sessions;
+use crate::schema::tasks;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const NOT_ALLOWED: &str = "You are not allowed to do this.";
+const TARGET_NOT_FOUND: &str = "Unable to find what is to be changed.";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Coach,
+    Member,
+}
+
+pub enum Target<'a> {
+    // The coach in person, like the owner of a new master plan.
+    Coach(&'a str),
+    Program(&'a str),
+    MasterPlan(&'a str),
+    Enrollment(&'a str),
+    Task(&'a str),
+    Session(&'a str),
+    Observation(&'a str),
+}
+
+struct Parties {
+    coach_id: String,
+    member_id: Option<String>,
+    co_coach_ids: Vec<String>,
+}
+
+impl Parties {
+    fn holds(&self, caller: &str, roles: &[Role]) -> bool {
+        roles.iter().any(|role| match role {
+            Role::Coach => self.coach_id == caller || self.co_coach_ids.iter().any(|co_coach_id| co_coach_id == caller),
+            Role::Member => self.member_id.as_deref() == Some(caller),
+        })
+    }
+}
+
+pub fn authorize(connection: &MysqlConnection, caller: Option<&UserId>, target: Target, roles: &[Role]) -> Result<(), &'static str> {
+    let caller = caller.ok_or(NOT_SIGNED_IN)?;
+    let user = users::find(connection, caller).map_err(|_| NOT_SIGNED_IN)?;
+
+    if user.user_type == util::ADMIN {
+        return Ok(());
+    }
+
+    let parties = parties_of(connection, &target)?;
+
+    if !parties.holds(user.id.as_str(), roles) {
+        return Err(NOT_ALLOWED);
+    }
+
+    Ok(())
+}
+
+fn parties_of(connection: &MysqlConnection, target: &Target) -> Result<Parties, &'static str> {
+    let result: QueryResult<(String, Option<String>, Option<String>)> = match *target {
+        Target::Coach(the_coach_id) => Ok((the_coach_id.to_owned(), None, None)),
+        Target::Program(the_id) => programs::table
+            .filter(programs::id.eq(the_id))
+            .select(programs::coach_id)
+            .first(connection)
+            .map(|coach_id| (coach_id, None, None)),
+        Target::MasterPlan(the_id) => master_plans::table
+            .filter(master_plans::id.eq(the_id))
+            .select(master_plans::coach_id)
+            .first(connection)
+            .map(|coach_id| (coach_id, None, None)),
+        Target::Enrollment(the_id) => enrollments::table
+            .inner_join(programs::table)
+            .filter(enrollments::id.eq(the_id))
+            .select((programs::coach_id, enrollments::member_id, enrollments::id))
+            .first(connection)
+            .map(|(coach_id, member_id, enrollment_id)| (coach_id, Some(member_id), Some(enrollment_id))),
+        Target::Task(the_id) => tasks::table
+            .inner_join(enrollments::table.inner_join(programs::table))
+            .filter(tasks::id.eq(the_id))
+            .select((programs::coach_id, enrollments::member_id, enrollments::id))
+            .first(connection)
+            .map(|(coach_id, member_id, enrollment_id)| (coach_id, Some(member_id), Some(enrollment_id))),
+        Target::Session(the_id) => sessions::table
+            .inner_join(enrollments::table.inner_join(programs::table))
+            .filter(sessions::id.eq(the_id))
+            .select((programs::coach_id, enrollments::member_id, enrollments::id))
+            .first(connection)
+            .map(|(coach_id, member_id, enrollment_id)| (coach_id, Some(member_id), Some(enrollment_id))),
+        Target::Observation(the_id) => observations::table
+            .inner_join(enrollments::table.inner_join(programs::table))
+            .filter(observations::id.eq(the_id))
+            .select((programs::coach_id, enrollments::member_id, enrollments::id))
+            .first(connection)
+            .map(|(coach_id, member_id, enrollment_id)| (coach_id, Some(member_id), Some(enrollment_id))),
+    };
+
+    let (coach_id, member_id, enrollment_id) = result.map_err(|_| TARGET_NOT_FOUND)?;
+
+    let co_coach_ids: Vec<String> = match enrollment_id {
+        Some(the_id) => enrollment_coaches::table
+            .filter(enrollment_coaches::enrollment_id.eq(the_id))
+            .select(enrollment_coaches::coach_id)
+            .load(connection)
+            .map_err(|_| TARGET_NOT_FOUND)?,
+        None => Vec::new(),
+    };
+
+    Ok(Parties { coach_id, member_id, co_coach_ids })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_hold_only_the_roles_of_the_parties() {
+        let parties = Parties {
+            coach_id: String::from("c-1"),
+            member_id: Some(String::from("m-1")),
+            co_coach_ids: vec![String::from("c-2")],
+        };
+
+        assert!(parties.holds("c-1", &[Role::Coach]));
+        assert!(!parties.holds("c-1", &[Role::Member]));
+        assert!(parties.holds("m-1", &[Role::Coach, Role::Member]));
+        assert!(!parties.holds("m-2", &[Role::Coach, Role::Member]));
+        assert!(parties.holds("c-2", &[Role::Coach]));
+        assert!(!parties.holds("c-2", &[Role::Member]));
+    }
+}
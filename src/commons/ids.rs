@@ -0,0 +1,122 @@
+/**
+ * The ids of the entities as types of their own, so that an enrollment id given
+ * where a program id is due is caught by the compiler rather than by a lookup
+ * that finds nothing.
+ *
+ * An id is a plain string on the wire and in the database: as a GraphQL scalar
+ * it reads and writes the string as it is, and it binds to the varchar columns
+ * in the queries as a string would.
+ */
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::mysql::Mysql;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Varchar;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::io::Write;
+use std::ops::Deref;
+use std::str::FromStr;
+
+macro_rules! typed_id {
+    ($name:ident) => {
+        #[derive(juniper::GraphQLScalarValue, AsExpression, FromSqlRow, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+        #[sql_type = "Varchar"]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> $name {
+                $name(value.to_owned())
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> $name {
+                $name(value)
+            }
+        }
+
+        // The requests still hold their ids as strings, which compare with the typed ids as they are.
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<$name> for String {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(value: &str) -> Result<$name, Infallible> {
+                Ok($name(value.to_owned()))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.0.as_str())
+            }
+        }
+
+        impl ToSql<Varchar, Mysql> for $name {
+            fn to_sql<W: Write>(&self, out: &mut Output<W, Mysql>) -> serialize::Result {
+                <str as ToSql<Varchar, Mysql>>::to_sql(self.0.as_str(), out)
+            }
+        }
+
+        impl FromSql<Varchar, Mysql> for $name {
+            fn from_sql(bytes: Option<&<Mysql as Backend>::RawValue>) -> deserialize::Result<$name> {
+                <String as FromSql<Varchar, Mysql>>::from_sql(bytes).map($name)
+            }
+        }
+    };
+}
+
+typed_id!(ProgramId);
+typed_id!(EnrollmentId);
+typed_id!(SessionId);
+typed_id!(UserId);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use juniper::{DefaultScalarValue, FromInputValue, InputValue, ToInputValue};
+
+    #[test]
+    fn should_travel_as_a_plain_string() {
+        let input: InputValue<DefaultScalarValue> = InputValue::scalar("p-1");
+        let program_id = ProgramId::from_input_value(&input).unwrap();
+
+        assert_eq!(ProgramId::from("p-1"), program_id);
+        assert_eq!(input, program_id.to_input_value());
+        assert_eq!("p-1", program_id.to_string());
+    }
+}
@@ -1,2 +1,10 @@
+pub mod cache;
 pub mod chassis;
+pub mod guard;
+pub mod ids;
+pub mod password;
+pub mod plain_text;
+pub mod scheduling;
+pub mod session_tokens;
+pub mod transactions;
 pub mod util;
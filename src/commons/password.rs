@@ -0,0 +1,183 @@
+/**
+ * The passwords are hashed with Argon2id, through libsodium. The cost of a hash
+ * is in the environment, so that it can be raised as the servers grow:
+ *
+ * PASSWORD_OPS_LIMIT       the passes over the memory, 2 by default
+ * PASSWORD_MEM_LIMIT_KIB   the memory of a hash in KiB, 65536 (64 MiB) by default
+ *
+ * The defaults are the interactive limits of libsodium, which the passwords were
+ * always hashed with. The hashes of other limits, and those of Argon2i or scrypt,
+ * still verify; they are hashed again with the current limits at the next sign in.
+ */
+use sodiumoxide::crypto::pwhash::{argon2i13, argon2id13, scryptsalsa208sha256};
+use std::sync::OnceLock;
+
+const ARGON2ID_PREFIX: &str = "$argon2id$";
+const ARGON2I_PREFIX: &str = "$argon2i$";
+const SCRYPT_PREFIX: &str = "$7$";
+
+// The least libsodium takes for Argon2id.
+const MIN_OPS_LIMIT: usize = 1;
+const MIN_MEM_LIMIT_KIB: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HashLimits {
+    pub ops_limit: usize,
+    pub mem_limit_kib: usize,
+}
+
+impl HashLimits {
+    pub fn from_env() -> HashLimits {
+        let interactive = HashLimits::interactive();
+
+        HashLimits {
+            ops_limit: env_size("PASSWORD_OPS_LIMIT", interactive.ops_limit, MIN_OPS_LIMIT),
+            mem_limit_kib: env_size("PASSWORD_MEM_LIMIT_KIB", interactive.mem_limit_kib, MIN_MEM_LIMIT_KIB),
+        }
+    }
+
+    fn interactive() -> HashLimits {
+        HashLimits {
+            ops_limit: argon2id13::OPSLIMIT_INTERACTIVE.0,
+            mem_limit_kib: argon2id13::MEMLIMIT_INTERACTIVE.0 / 1024,
+        }
+    }
+
+    /**
+     * The limits a hash was made with, from its m= and t= parameters; none for a
+     * hash other than Argon2id.
+     */
+    fn of(hashed_password: &str) -> Option<HashLimits> {
+        let parameters = hashed_password.strip_prefix(ARGON2ID_PREFIX)?.split('$').nth(1)?;
+
+        let mut limits = HashLimits { ops_limit: 0, mem_limit_kib: 0 };
+        for parameter in parameters.split(',') {
+            match parameter.split_once('=')? {
+                ("m", value) => limits.mem_limit_kib = value.parse().ok()?,
+                ("t", value) => limits.ops_limit = value.parse().ok()?,
+                _ => {}
+            }
+        }
+
+        Some(limits)
+    }
+}
+
+fn limits() -> HashLimits {
+    static LIMITS: OnceLock<HashLimits> = OnceLock::new();
+    *LIMITS.get_or_init(HashLimits::from_env)
+}
+
+pub fn hash(password: &str) -> String {
+    hash_with(password, limits())
+}
+
+fn hash_with(password: &str, limits: HashLimits) -> String {
+    sodiumoxide::init().unwrap();
+
+    let ops_limit = argon2id13::OpsLimit(limits.ops_limit);
+    let mem_limit = argon2id13::MemLimit(limits.mem_limit_kib * 1024);
+
+    let hashed_password = argon2id13::pwhash(password.as_bytes(), ops_limit, mem_limit).unwrap();
+
+    std::str::from_utf8(&hashed_password.0).unwrap().to_string()
+}
+
+pub fn verify(hashed_password: &str, given_password: &str) -> bool {
+    sodiumoxide::init().unwrap();
+
+    let given = given_password.as_bytes();
+
+    if hashed_password.starts_with(ARGON2ID_PREFIX) {
+        return padded(hashed_password, argon2id13::HASHEDPASSWORDBYTES)
+            .and_then(|bytes| argon2id13::HashedPassword::from_slice(&bytes))
+            .is_some_and(|hash| argon2id13::pwhash_verify(&hash, given));
+    }
+
+    if hashed_password.starts_with(ARGON2I_PREFIX) {
+        return padded(hashed_password, argon2i13::HASHEDPASSWORDBYTES)
+            .and_then(|bytes| argon2i13::HashedPassword::from_slice(&bytes))
+            .is_some_and(|hash| argon2i13::pwhash_verify(&hash, given));
+    }
+
+    if hashed_password.starts_with(SCRYPT_PREFIX) {
+        return padded(hashed_password, scryptsalsa208sha256::HASHEDPASSWORDBYTES)
+            .and_then(|bytes| scryptsalsa208sha256::HashedPassword::from_slice(&bytes))
+            .is_some_and(|hash| scryptsalsa208sha256::pwhash_verify(&hash, given));
+    }
+
+    false
+}
+
+/**
+ * Whether a verified password is to be hashed again, its hash being of another
+ * algorithm or of other limits than the current ones.
+ */
+pub fn needs_rehash(hashed_password: &str) -> bool {
+    needs_rehash_for(hashed_password, limits())
+}
+
+fn needs_rehash_for(hashed_password: &str, limits: HashLimits) -> bool {
+    HashLimits::of(hashed_password.trim_end_matches('\0')) != Some(limits)
+}
+
+/**
+ * libsodium keeps a hash in a buffer of a fixed size, padded with zeros.
+ */
+fn padded(hashed_password: &str, size: usize) -> Option<Vec<u8>> {
+    let bytes = hashed_password.as_bytes();
+    if bytes.len() > size {
+        return None;
+    }
+
+    let mut buffer = vec![0u8; size];
+    buffer[..bytes.len()].copy_from_slice(bytes);
+
+    Some(buffer)
+}
+
+fn env_size(key: &str, default: usize, least: usize) -> usize {
+    dotenv::var(key).ok().and_then(|value| value.parse::<usize>().ok()).filter(|value| *value >= least).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_hash_and_verify_hashed_password() {
+        let hashed = hash("abcdefghijklmnopqrstuvwxyz");
+
+        assert_eq!(false, verify(hashed.as_str(), "harini"));
+        assert_eq!(true, verify(hashed.as_str(), "abcdefghijklmnopqrstuvwxyz"));
+        assert_eq!(false, verify(hashed.as_str(), "abcdefghij lmnopqrstuvwxyz"));
+    }
+
+    #[test]
+    fn gen_password() {
+        println!("{}", hash("harini"));
+        println!("{}", hash("harini"));
+    }
+
+    #[test]
+    fn should_rehash_the_hashes_of_other_limits_or_algorithms() {
+        let interactive = HashLimits::interactive();
+        let stronger = HashLimits {
+            ops_limit: interactive.ops_limit + 1,
+            mem_limit_kib: interactive.mem_limit_kib,
+        };
+
+        let hashed = hash_with("harini", interactive);
+        assert_eq!(Some(interactive), HashLimits::of(hashed.trim_end_matches('\0')));
+        assert!(!needs_rehash_for(hashed.as_str(), interactive));
+        assert!(needs_rehash_for(hashed.as_str(), stronger));
+
+        sodiumoxide::init().unwrap();
+        let legacy = argon2i13::pwhash(b"harini", argon2i13::OPSLIMIT_INTERACTIVE, argon2i13::MEMLIMIT_INTERACTIVE).unwrap();
+        let legacy = std::str::from_utf8(&legacy.0).unwrap();
+
+        assert!(verify(legacy, "harini"));
+        assert!(needs_rehash_for(legacy, interactive));
+    }
+}
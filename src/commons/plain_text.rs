@@ -0,0 +1,254 @@
+/**
+ * The program descriptions, the announcements and the mails are written in
+ * HTML or Markdown by the editors of the Web-UI. Their plain text is kept next
+ * to them when they are written, for the mail clients, the screen readers and
+ * the SMS fallbacks that have no use for the markup.
+ *
+ * The tags are dropped, a block or a line break ending the line and an item of
+ * a list starting with a dash. The Markdown markers are dropped too, a link
+ * keeping its address after the text. The entities are decoded last, so that
+ * an escaped bracket or star stays in the text.
+ */
+const SKIPPED_TAGS: [&str; 2] = ["script", "style"];
+const BLOCK_TAGS: [&str; 17] = ["br", "p", "div", "ul", "ol", "tr", "table", "blockquote", "pre", "hr", "h1", "h2", "h3", "h4", "h5", "h6", "section"];
+
+pub fn plain_text(rich: &str) -> String {
+    let text = strip_tags(rich);
+
+    let lines: Vec<String> = text.lines().map(|line| decode_entities(&strip_markdown(line))).map(|line| squeeze_spaces(&line)).collect();
+
+    let mut plain = String::new();
+    let mut paragraph_ended = false;
+
+    // The blank lines between the paragraphs come down to one.
+    for line in lines {
+        if line.is_empty() {
+            paragraph_ended = !plain.is_empty();
+            continue;
+        }
+
+        if !plain.is_empty() {
+            plain.push_str(if paragraph_ended { "\n\n" } else { "\n" });
+        }
+
+        plain.push_str(&line);
+        paragraph_ended = false;
+    }
+
+    plain
+}
+
+fn strip_tags(rich: &str) -> String {
+    let mut text = String::with_capacity(rich.len());
+    let mut rest = rich;
+
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        let close = match rest.find('>') {
+            Some(close) => close,
+            None => break,
+        };
+
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag.trim_start_matches('/').split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("").to_ascii_lowercase();
+
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            // Not a tag, say a comparison in the text.
+            text.push_str(&format!("<{}>", tag));
+            continue;
+        }
+
+        if !closing && SKIPPED_TAGS.contains(&name.as_str()) {
+            let end = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&end) {
+                Some(at) => &rest[at..],
+                None => "",
+            };
+            continue;
+        }
+
+        if name == "li" {
+            if !closing {
+                text.push_str("\n- ");
+            }
+        } else if BLOCK_TAGS.contains(&name.as_str()) {
+            text.push('\n');
+        } else if name == "td" || name == "th" {
+            text.push(' ');
+        }
+    }
+
+    text.push_str(rest);
+    text
+}
+
+fn strip_markdown(line: &str) -> String {
+    let trimmed = line.trim_start();
+
+    let trimmed = trimmed.trim_start_matches('>').trim_start();
+    let heading = trimmed.trim_start_matches('#');
+    let trimmed = if heading.len() < trimmed.len() && (heading.is_empty() || heading.starts_with(' ')) { heading.trim_start() } else { trimmed };
+
+    let (bullet, trimmed) = match trimmed.get(..2) {
+        Some("* ") | Some("+ ") | Some("- ") => ("- ", &trimmed[2..]),
+        _ => ("", trimmed),
+    };
+
+    if !trimmed.is_empty() && trimmed.chars().all(|c| c == '-' || c == '*' || c == '_' || c == '=') {
+        // A rule or the underline of a heading.
+        return String::new();
+    }
+
+    format!("{}{}", bullet, strip_inline(trimmed))
+}
+
+fn strip_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut text = String::with_capacity(line.len());
+    let mut at = 0;
+
+    while at < chars.len() {
+        let c = chars[at];
+
+        if c == '!' && chars.get(at + 1) == Some(&'[') {
+            if let Some((label, _, next)) = link(&chars, at + 1) {
+                text.push_str(&label);
+                at = next;
+                continue;
+            }
+        }
+
+        if c == '[' {
+            if let Some((label, address, next)) = link(&chars, at) {
+                text.push_str(&label);
+                if !address.is_empty() && address != label {
+                    text.push_str(&format!(" ({})", address));
+                }
+                at = next;
+                continue;
+            }
+        }
+
+        if c == '`' || ((c == '*' || c == '_' || c == '~') && chars.get(at + 1) == Some(&c)) {
+            at += if c == '`' { 1 } else { 2 };
+            continue;
+        }
+
+        if c == '*' || c == '_' {
+            let before = if at == 0 { None } else { chars.get(at - 1) };
+            let after = chars.get(at + 1);
+            let word = |other: Option<&char>| other.is_some_and(|other| other.is_alphanumeric());
+
+            // Emphasis opens or closes at the edge of a word; snake_case and 2 * 3 are left alone.
+            if word(before) != word(after) {
+                at += 1;
+                continue;
+            }
+        }
+
+        text.push(c);
+        at += 1;
+    }
+
+    text
+}
+
+// The label, the address and the position after a [label](address).
+fn link(chars: &[char], at: usize) -> Option<(String, String, usize)> {
+    let label_end = at + 1 + chars[at + 1..].iter().position(|c| *c == ']')?;
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+    let address_end = label_end + 2 + chars[label_end + 2..].iter().position(|c| *c == ')')?;
+
+    let label: String = chars[at + 1..label_end].iter().collect();
+    let address: String = chars[label_end + 2..address_end].iter().collect();
+
+    Some((strip_inline(&label), address.trim().to_owned(), address_end + 1))
+}
+
+fn decode_entities(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(amp) = rest.find('&') {
+        text.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest.find(';').filter(|semi| *semi <= 10).and_then(|semi| entity(&rest[1..semi]).map(|c| (c, semi)));
+
+        match decoded {
+            Some((c, semi)) => {
+                text.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    text.push_str(rest);
+    text
+}
+
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "ndash" => Some('–'),
+        "mdash" => Some('—'),
+        "hellip" => Some('…'),
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let value = match code.strip_prefix('x').or_else(|| code.strip_prefix('X')) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse::<u32>().ok()?,
+            };
+            std::char::from_u32(value)
+        }
+    }
+}
+
+fn squeeze_spaces(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_drop_the_tags_and_decode_the_entities() {
+        let html = "<h2>Clarity&nbsp;Program</h2><p>Six <b>weekly</b> sessions &amp; a review.</p><ul><li>Goals</li><li>Habits &lt;daily&gt;</li></ul><script>alert(1)</script>";
+
+        assert_eq!("Clarity Program\n\nSix weekly sessions & a review.\n\n- Goals\n- Habits <daily>", plain_text(html));
+    }
+
+    #[test]
+    fn should_drop_the_markdown_markers() {
+        let markdown = "# Welcome\n\nRead the **plan** at [the board](https://ferries.io/board) and _reply_ by Friday.\n\n* keep snake_case and 2 * 3\n* ![poster](poster.png)\n\n---\n\n> `See you`";
+
+        assert_eq!(
+            "Welcome\n\nRead the plan at the board (https://ferries.io/board) and reply by Friday.\n\n- keep snake_case and 2 * 3\n- poster\n\nSee you",
+            plain_text(markdown)
+        );
+    }
+
+    #[test]
+    fn should_leave_plain_text_as_it_is() {
+        assert_eq!("Meet at 5 < 6 & bring the notes.", plain_text("Meet at 5 < 6 & bring the notes."));
+        assert_eq!("", plain_text("<p> </p>"));
+    }
+}
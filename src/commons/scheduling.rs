@@ -0,0 +1,170 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+/**
+ * The platform level rules for scheduling a session.
+ *
+ * Both the rules are optional and are read from the environment:
+ *
+ * SESSION_HOURS=06:00-22:00 (the window, in the local time of the member)
+ * SESSION_HOLIDAYS=2021-12-25,2022-01-01
+ *
+ * An organization may set either rule for its coaches, in the same shape, which
+ * takes the place of the one of the platform; see organizations::scheduling_rules.
+ *
+ * A coach may still schedule a session that breaks a rule by recording a reason.
+ * The rules apply where a session is scheduled: createSession and the followups of
+ * a conference. A session is never moved in place; another one is scheduled.
+ */
+const HOURS_KEY: &str = "SESSION_HOURS";
+const HOLIDAYS_KEY: &str = "SESSION_HOLIDAYS";
+
+const TIME_PATTERN: &str = "%H:%M";
+const DATE_PATTERN: &str = "%Y-%m-%d";
+
+pub const OUTSIDE_HOURS: &str = "The session falls outside the permitted hours in the local time of the member. Please provide a reason to override.";
+pub const ON_HOLIDAY: &str = "The session falls on a holiday. Please provide a reason to override.";
+
+pub const BAD_HOURS: &str = "The hours should be like 06:00-22:00, the start before the end.";
+pub const BAD_HOLIDAYS: &str = "The holidays should be days like 2021-12-25, separated by commas.";
+
+pub struct SchedulingRules {
+    pub window: Option<(NaiveTime, NaiveTime)>,
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl SchedulingRules {
+    pub fn from_env() -> SchedulingRules {
+        let hours = std::env::var(HOURS_KEY).unwrap_or_default();
+        let holidays = std::env::var(HOLIDAYS_KEY).unwrap_or_default();
+
+        SchedulingRules::parse(hours.as_str(), holidays.as_str())
+    }
+
+    // Malformed entries are ignored rather than blocking every session.
+    pub fn parse(hours: &str, holidays: &str) -> SchedulingRules {
+        let window = match hours.split_once('-') {
+            Some((from, to)) => {
+                let from = NaiveTime::parse_from_str(from.trim(), TIME_PATTERN);
+                let to = NaiveTime::parse_from_str(to.trim(), TIME_PATTERN);
+                match (from, to) {
+                    (Ok(from), Ok(to)) if from < to => Some((from, to)),
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+
+        let holidays = holidays
+            .split(',')
+            .filter_map(|day| NaiveDate::parse_from_str(day.trim(), DATE_PATTERN).ok())
+            .collect();
+
+        SchedulingRules { window, holidays }
+    }
+
+    /**
+     * The rules set by an organization in place of these; a rule it leaves unset stays.
+     */
+    pub fn within(self, hours: Option<&str>, holidays: Option<&str>) -> SchedulingRules {
+        let own = SchedulingRules::parse(hours.unwrap_or_default(), holidays.unwrap_or_default());
+
+        SchedulingRules {
+            window: if hours.is_some() { own.window } else { self.window },
+            holidays: if holidays.is_some() { own.holidays } else { self.holidays },
+        }
+    }
+
+    /**
+     * The mistakes of the rules an organization is setting, which would otherwise be ignored.
+     */
+    pub fn mistakes(hours: &str, holidays: &str) -> Vec<&'static str> {
+        let rules = SchedulingRules::parse(hours, holidays);
+        let mut mistakes: Vec<&'static str> = Vec::new();
+
+        if !hours.trim().is_empty() && rules.window.is_none() {
+            mistakes.push(BAD_HOURS);
+        }
+
+        if holidays.split(',').filter(|day| !day.trim().is_empty()).count() != rules.holidays.len() {
+            mistakes.push(BAD_HOLIDAYS);
+        }
+
+        mistakes
+    }
+
+    /**
+     * The start and end are in UTC, while the utc_offset (in minutes) is that of the member.
+     */
+    pub fn violation(&self, start: NaiveDateTime, end: NaiveDateTime, utc_offset: i32) -> Option<&'static str> {
+        let offset = Duration::minutes(utc_offset as i64);
+        let local_start = start + offset;
+        let local_end = end + offset;
+
+        if self.holidays.contains(&local_start.date()) || self.holidays.contains(&local_end.date()) {
+            return Some(ON_HOLIDAY);
+        }
+
+        if let Some((from, to)) = self.window {
+            if local_start.date() != local_end.date() || local_start.time() < from || local_end.time() > to {
+                return Some(OUTSIDE_HOURS);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    #[test]
+    fn should_allow_when_no_rules_are_configured() {
+        let rules = SchedulingRules::parse("", "");
+        assert_eq!(None, rules.violation(at("2021-02-07T03:00"), at("2021-02-07T04:00"), 0));
+    }
+
+    #[test]
+    fn should_apply_the_window_in_member_local_time() {
+        let rules = SchedulingRules::parse("06:00-22:00", "");
+
+        assert_eq!(Some(OUTSIDE_HOURS), rules.violation(at("2021-02-07T03:00"), at("2021-02-07T04:00"), 0));
+        assert_eq!(None, rules.violation(at("2021-02-07T03:00"), at("2021-02-07T04:00"), 330));
+        assert_eq!(Some(OUTSIDE_HOURS), rules.violation(at("2021-02-07T21:30"), at("2021-02-07T22:30"), 0));
+    }
+
+    #[test]
+    fn should_reject_holidays() {
+        let rules = SchedulingRules::parse("", "2021-12-25, 2022-01-01");
+
+        assert_eq!(Some(ON_HOLIDAY), rules.violation(at("2021-12-25T10:00"), at("2021-12-25T11:00"), 0));
+        assert_eq!(None, rules.violation(at("2021-12-24T20:00"), at("2021-12-24T21:00"), 0));
+        assert_eq!(Some(ON_HOLIDAY), rules.violation(at("2021-12-24T20:00"), at("2021-12-24T21:00"), 240));
+    }
+
+    #[test]
+    fn should_take_the_rules_of_the_organization_in_place() {
+        let platform = || SchedulingRules::parse("06:00-22:00", "2021-12-25");
+
+        let rules = platform().within(Some("09:00-17:00"), None);
+        assert_eq!(Some(OUTSIDE_HOURS), rules.violation(at("2021-02-08T07:00"), at("2021-02-08T08:00"), 0));
+        assert_eq!(Some(ON_HOLIDAY), rules.violation(at("2021-12-25T10:00"), at("2021-12-25T11:00"), 0));
+
+        let rules = platform().within(Some(""), Some("2021-12-31"));
+        assert_eq!(None, rules.violation(at("2021-12-25T03:00"), at("2021-12-25T04:00"), 0));
+        assert_eq!(Some(ON_HOLIDAY), rules.violation(at("2021-12-31T10:00"), at("2021-12-31T11:00"), 0));
+    }
+
+    #[test]
+    fn should_name_the_mistakes_of_the_rules() {
+        assert!(SchedulingRules::mistakes("", "").is_empty());
+        assert!(SchedulingRules::mistakes("06:00-22:00", "2021-12-25, 2022-01-01").is_empty());
+        assert_eq!(vec![BAD_HOURS], SchedulingRules::mistakes("22:00-06:00", ""));
+        assert_eq!(vec![BAD_HOLIDAYS], SchedulingRules::mistakes("", "2021-12-25, Christmas"));
+    }
+}
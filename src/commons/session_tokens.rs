@@ -0,0 +1,94 @@
+/**
+ * The signed session of a user, issued when they sign in and sent back by the
+ * clients in the X-Session-Token header of every request. The token is the id of
+ * the user, a dot, the time it expires, a dot and the HMAC-SHA256 of the two,
+ * keyed with the sha256 of SESSION_TOKEN_SECRET; no one is signed in while it is
+ * not set.
+ */
+use chrono::{Duration, NaiveDateTime};
+use sodiumoxide::crypto::auth::hmacsha256;
+use sodiumoxide::crypto::hash::sha256;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+
+const TOKEN_SECRET: &str = "SESSION_TOKEN_SECRET";
+
+// A working day and the evening after it; the clients sign in again after that.
+pub const SESSION_HOURS: i64 = 12;
+
+const UNSIGNED: &str = "Signing in is not available at the moment.";
+
+fn token_secret() -> Option<String> {
+    dotenv::var(TOKEN_SECRET).ok().filter(|secret| !secret.trim().is_empty())
+}
+
+fn signature(secret: &str, user_id: &str, expires: i64) -> String {
+    let key = hmacsha256::Key(sha256::hash(secret.as_bytes()).0);
+    let message = format!("{}.{}", user_id, expires);
+
+    hmacsha256::authenticate(message.as_bytes(), &key).0.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn sign_with(secret: &str, user_id: &UserId, until: NaiveDateTime) -> String {
+    let expires = until.timestamp();
+
+    format!("{}.{}.{}", user_id.as_str(), expires, signature(secret, user_id.as_str(), expires))
+}
+
+/**
+ * The token of the user who has just signed in.
+ */
+pub fn issue(user_id: &UserId) -> Result<String, &'static str> {
+    match token_secret() {
+        Some(secret) => Ok(sign_with(secret.as_str(), user_id, util::now() + Duration::hours(SESSION_HOURS))),
+        None => {
+            tracing::error!("{} is not set; no session can be issued", TOKEN_SECRET);
+            Err(UNSIGNED)
+        }
+    }
+}
+
+/**
+ * The user of a token we signed, which has not expired yet.
+ */
+pub fn verify(token: &str) -> Option<UserId> {
+    let secret = token_secret()?;
+
+    verify_with(secret.as_str(), token, util::now())
+}
+
+fn verify_with(secret: &str, token: &str, now: NaiveDateTime) -> Option<UserId> {
+    // The ids are ours, yet the signature and the time are split from the right all the same.
+    let mut parts = token.trim().rsplitn(3, '.');
+    let given = parts.next()?;
+    let expires: i64 = parts.next()?.parse().ok()?;
+    let user_id = parts.next().filter(|user_id| !user_id.is_empty())?;
+
+    let expected = signature(secret, user_id, expires);
+
+    if expires > now.timestamp() && sodiumoxide::utils::memcmp(expected.as_bytes(), given.to_ascii_lowercase().as_bytes()) {
+        return Some(UserId::from(user_id));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_honour_only_the_own_unexpired_token() {
+        let now = util::as_date("2021-03-01T00:00:00Z");
+        let token = sign_with("s3cret", &UserId::from("u-1"), now + Duration::hours(1));
+
+        assert_eq!(Some(UserId::from("u-1")), verify_with("s3cret", token.as_str(), now));
+        assert_eq!(None, verify_with("other", token.as_str(), now));
+        assert_eq!(None, verify_with("s3cret", token.as_str(), now + Duration::hours(2)));
+        assert_eq!(None, verify_with("s3cret", token.replacen("u-1", "u-2", 1).as_str(), now));
+        assert_eq!(None, verify_with("s3cret", "u-1", now));
+        assert_eq!(None, verify_with("s3cret", "", now));
+    }
+}
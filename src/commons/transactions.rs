@@ -0,0 +1,37 @@
+/**
+ * The changes made of several steps, which share the connection and its
+ * transaction so that either all of them are committed or none.
+ */
+use diesel::prelude::*;
+
+/**
+ * A step of a change gone wrong. Any step failing rolls the whole change back,
+ * keeping the message of the step; a failing query is answered with the message
+ * the change was begun with.
+ */
+pub enum Failure {
+    Step(&'static str),
+    Query,
+}
+
+impl From<&'static str> for Failure {
+    fn from(message: &'static str) -> Failure {
+        Failure::Step(message)
+    }
+}
+
+impl From<diesel::result::Error> for Failure {
+    fn from(_: diesel::result::Error) -> Failure {
+        Failure::Query
+    }
+}
+
+pub fn in_transaction<T, F>(connection: &MysqlConnection, query_error: &'static str, steps: F) -> Result<T, &'static str>
+where
+    F: FnOnce() -> Result<T, Failure>,
+{
+    connection.transaction(steps).map_err(|failure| match failure {
+        Failure::Step(message) => message,
+        Failure::Query => query_error,
+    })
+}
@@ -1,6 +1,5 @@
 use chrono::format::strftime::StrftimeItems;
 use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
-use sodiumoxide::crypto::pwhash::argon2id13;
 use std::ops::Sub;
 use uuid::Uuid;
 
@@ -11,6 +10,7 @@ pub const BAD_DATE: &str = "Date format error";
 
 pub const MEMBER: &str = "member";
 pub const COACH: &str = "coach";
+pub const ADMIN: &str = "admin";
 
 pub const MONO: &str = "mono";
 pub const MULTI: &str = "multi";
@@ -76,50 +76,18 @@ pub fn fuzzy_id() -> String {
     Uuid::new_v4().to_hyphenated().to_string()
 }
 
-pub fn concat(str1: &str, str2: &str) -> String {
-    format!("{} and {}", str1, str2)
-}
-
-pub fn hash(password: &str) -> String {
-    sodiumoxide::init().unwrap();
-
-    let hashed_password = argon2id13::pwhash(password.as_bytes(), argon2id13::OPSLIMIT_INTERACTIVE, argon2id13::MEMLIMIT_INTERACTIVE).unwrap();
-
-    std::str::from_utf8(&hashed_password.0).unwrap().to_string()
-}
-
 /**
- *
- * 1. Create an array of length 128 and stuff that with 0 (unsigned byte)
- * 2. iterate the given slice and replace the array with the respective value.
- *
+ * An unguessable token for links handed out by mail; unlike the fuzzy id,
+ * it is meant to be kept secret.
  */
-fn as_byte_array(slice: &str) -> [u8; 128] {
-    let mut arr = [0u8; 128];
-
-    slice.as_bytes().iter().enumerate().for_each(|(i, val)| {
-        arr[i] = *val;
-    });
-
-    arr
+pub fn secure_token() -> String {
+    sodiumoxide::randombytes::randombytes(24).iter().map(|byte| format!("{:02x}", byte)).collect()
 }
 
-pub fn is_equal(hashed_password: &str, given_password: &str) -> bool {
-    sodiumoxide::init().unwrap();
-
-    let hashed_bytes = as_byte_array(hashed_password);
-
-    let mut status = false;
-
-    if let Some(hash) = argon2id13::HashedPassword::from_slice(&hashed_bytes) {
-        status = argon2id13::pwhash_verify(&hash, given_password.as_bytes());
-    }
-
-    status
+pub fn concat(str1: &str, str2: &str) -> String {
+    format!("{} and {}", str1, str2)
 }
 
-
-
 pub fn find_diff(current: Vec<String>, given: Vec<String>) -> Vec<String> {
     let mut diff: Vec<String> = Vec::new();
 
@@ -152,21 +120,6 @@ mod tests {
         println!("{:?}", as_end_date(start_date));
     }
 
-    #[test]
-    fn should_hash_and_verify_hashed_password() {
-        let hashed = hash("abcdefghijklmnopqrstuvwxyz");
-
-        assert_eq!(false, is_equal(hashed.as_str(), "harini"));
-        assert_eq!(true, is_equal(hashed.as_str(), "abcdefghijklmnopqrstuvwxyz"));
-        assert_eq!(false, is_equal(hashed.as_str(), "abcdefghij lmnopqrstuvwxyz"));
-    }
-
-    #[test]
-    fn gen_password() {
-        println!("{}", hash("harini"));
-        println!("{}", hash("harini"));
-    }
-
     #[test]
     fn find_diff_between_old_and_new() {
         let old = vec![String::from("1"), String::from("2"), String::from("3"), String::from("4")];
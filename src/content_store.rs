@@ -0,0 +1,188 @@
+/**
+ * The uploaded files are stored by the SHA-256 of their content, so that the
+ * same document uploaded to many sessions, programs or users takes its space
+ * once.
+ *
+ * An upload streams into a scratch file while it is hashed. The scratch file
+ * becomes the blob of its checksum, unless that blob is there already, and the
+ * path the upload asked for is made a hard link to the blob. The paths stay as
+ * they were, so the notes, the boards and the downloads see no difference.
+ *
+ *   {ASSET_ROOT}/blobs/tmp/{fuzzy_id}      an upload in progress
+ *   {ASSET_ROOT}/blobs/{ab}/{abcdef...}    the content of the checksum abcdef...
+ *
+ * A path is never written in place, as that would change every path sharing its
+ * blob; it is replaced by a new link. Where a link cannot be made, across two
+ * disks, the blob is copied instead.
+ *
+ * The blobs are of the local disk alone; a storage elsewhere keeps the paths,
+ * see storage.
+ *
+ * The blobs no path refers to any more are swept by the "blob-sweep" job, see
+ * services::file_registry. A blob is young for a while, so that an upload
+ * between its blob and its registration does not lose it.
+ */
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::commons::util::fuzzy_id;
+use crate::storage::{storage, Area};
+
+const SCRATCH: &str = "tmp";
+
+// Long enough for an upload to be registered after its blob is in place.
+const GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+impl ContentStore {
+    pub fn local() -> ContentStore {
+        ContentStore::new(storage().dir(Area::Blobs))
+    }
+
+    pub fn new<P: AsRef<Path>>(root: P) -> ContentStore {
+        ContentStore { root: root.as_ref().to_path_buf() }
+    }
+
+    /**
+     * A fresh file for an upload to stream into.
+     */
+    pub fn scratch_path(&self) -> io::Result<PathBuf> {
+        let dir = self.root.join(SCRATCH);
+        fs::create_dir_all(&dir)?;
+
+        Ok(dir.join(fuzzy_id()))
+    }
+
+    pub fn blob_path(&self, checksum: &str) -> PathBuf {
+        self.root.join(checksum.get(..2).unwrap_or(checksum)).join(checksum)
+    }
+
+    /**
+     * Turns the scratch file into the blob of the checksum and links the path to
+     * it; true when the content was there already.
+     */
+    pub fn settle(&self, scratch: &Path, checksum: &str, file_path: &Path) -> io::Result<bool> {
+        let blob = self.blob_path(checksum);
+
+        let existed = blob.is_file();
+        if existed {
+            fs::remove_file(scratch)?;
+        } else {
+            if let Some(dir) = blob.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::rename(scratch, &blob)?;
+        }
+
+        self.link(checksum, file_path)?;
+
+        Ok(existed)
+    }
+
+    /**
+     * Makes the path, one more, a link to the blob of the checksum, which should
+     * be there.
+     */
+    pub fn link(&self, checksum: &str, file_path: &Path) -> io::Result<()> {
+        let blob = self.blob_path(checksum);
+
+        match fs::remove_file(file_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        if fs::hard_link(&blob, file_path).is_err() {
+            fs::copy(&blob, file_path)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * The blobs none of the checksums refer to, and the scratch files of the
+     * uploads long gone; those of the grace period are left alone.
+     */
+    pub fn orphans(&self, referenced: &HashSet<String>) -> Vec<PathBuf> {
+        let mut orphans: Vec<PathBuf> = Vec::new();
+
+        for dir in files_in(&self.root).into_iter().filter(|path| path.is_dir()) {
+            let is_scratch = dir.file_name().map(|name| name == SCRATCH).unwrap_or(false);
+
+            for file in files_in(&dir).into_iter().filter(|path| is_old(path)) {
+                let checksum = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+                if is_scratch || !referenced.contains(checksum) {
+                    orphans.push(file);
+                }
+            }
+        }
+
+        orphans
+    }
+
+    /**
+     * Forgets every blob; the paths linked to them keep their content.
+     */
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.root) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn is_old(path: &Path) -> bool {
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+
+    match modified.map(|at| SystemTime::now().duration_since(at)) {
+        Ok(Ok(age)) => age > GRACE_PERIOD,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_keep_the_same_content_once() {
+        let root = std::env::temp_dir().join(format!("ferris-blobs-{}", fuzzy_id()));
+        let store = ContentStore::new(&root);
+        let checksum = "ab12";
+
+        let first = root.join("first.txt");
+        let scratch = store.scratch_path().unwrap();
+        fs::write(&scratch, "notes").unwrap();
+        assert!(!store.settle(&scratch, checksum, &first).unwrap());
+
+        let second = root.join("second.txt");
+        let scratch = store.scratch_path().unwrap();
+        fs::write(&scratch, "notes").unwrap();
+        assert!(store.settle(&scratch, checksum, &second).unwrap());
+
+        assert_eq!(root.join("ab").join("ab12"), store.blob_path(checksum));
+        assert_eq!("notes", fs::read_to_string(&second).unwrap());
+        assert!(!scratch.exists());
+        assert!(store.orphans(&HashSet::new()).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
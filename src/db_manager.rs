@@ -1,15 +1,275 @@
+/**
+ * The pool of the connections to MySQL.
+ *
+ * DB_POOL_SIZE                the most connections kept, 10 by default
+ * DB_CONNECTION_TIMEOUT_SECS  how long a checkout waits for a connection, 30 by default
+ * DB_STARTUP_RETRIES          how many more times the server tries to reach the database as it starts, 8 by default
+ * DB_RETRY_DELAY_MS           the wait before the first retry, doubled for each next one up to 30 seconds; 500 by default
+ * DATABASE_READ_URL           a read-only replica for the GraphQL queries, none by default; ignored in the demo mode
+ *
+ * A connection is tested as it is checked out, so the ones broken by a restart
+ * of the database are replaced rather than handed over. While the database is
+ * down a checkout fails after the timeout, and the request is answered with
+ * DB_UNAVAILABLE instead of bringing the server down.
+ */
 use diesel::mysql::MysqlConnection;
-use diesel::r2d2::{ConnectionManager, Pool, PoolError};
+use diesel::r2d2::{ConnectionManager, Pool, PoolError, PooledConnection};
 use std::env;
+use std::ops::Deref;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::Duration;
+
+use crate::demo_mode::DemoMode;
 
 pub type MySqlConnectionPool = Pool<ConnectionManager<MysqlConnection>>;
 
-fn init_pool(database_url: &str) -> Result<MySqlConnectionPool, PoolError> {
+pub type PooledMysqlConnection = PooledConnection<ConnectionManager<MysqlConnection>>;
+
+pub const DB_UNAVAILABLE: &str = "The database is not reachable now; please try again shortly.";
+pub const CONNECTION_IN_USE: &str = "The connection of the request is held by the resolver asking for it again.";
+
+const DEFAULT_POOL_SIZE: u32 = 10;
+const DEFAULT_CONNECTION_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_STARTUP_RETRIES: u32 = 8;
+const DEFAULT_RETRY_DELAY_MS: u64 = 500;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, PartialEq)]
+pub struct PoolSettings {
+    pub size: u32,
+    pub connection_timeout: Duration,
+    pub startup_retries: u32,
+    pub retry_delay: Duration,
+}
+
+impl PoolSettings {
+    pub fn from_env() -> PoolSettings {
+        PoolSettings {
+            size: setting("DB_POOL_SIZE").filter(|value| *value > 0).unwrap_or(DEFAULT_POOL_SIZE),
+            connection_timeout: Duration::from_secs(setting("DB_CONNECTION_TIMEOUT_SECS").filter(|value| *value > 0).unwrap_or(DEFAULT_CONNECTION_TIMEOUT_SECS)),
+            startup_retries: setting("DB_STARTUP_RETRIES").unwrap_or(DEFAULT_STARTUP_RETRIES),
+            retry_delay: Duration::from_millis(setting("DB_RETRY_DELAY_MS").unwrap_or(DEFAULT_RETRY_DELAY_MS)),
+        }
+    }
+
+    /**
+     * The wait before the given retry, the first being 0.
+     */
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.checked_pow(retry).unwrap_or(u32::MAX);
+
+        self.retry_delay.checked_mul(factor).map(|delay| delay.min(MAX_RETRY_DELAY)).unwrap_or(MAX_RETRY_DELAY)
+    }
+}
+
+fn setting<T: std::str::FromStr>(key: &str) -> Option<T> {
+    dotenv::var(key).ok().and_then(|value| value.trim().parse().ok())
+}
+
+fn init_pool(database_url: &str, settings: &PoolSettings) -> Result<MySqlConnectionPool, PoolError> {
     let manager = ConnectionManager::<MysqlConnection>::new(database_url);
-    Pool::builder().build(manager)
+
+    Pool::builder()
+        .max_size(settings.size)
+        .connection_timeout(settings.connection_timeout)
+        .test_on_check_out(true)
+        .build(manager)
 }
 
-pub fn establish_connection() -> MySqlConnectionPool {
+/**
+ * The pools, once the databases answer. A database still starting, like one
+ * brought up with the server, is waited for over the retries; without it after
+ * the last one the server does not start.
+ */
+pub fn establish_connection() -> ReadWritePool {
+    let demo = DemoMode::from_env();
     let database_url = env::var("DATABASE_URL").expect("The Database URL should be set");
-    init_pool(&database_url).unwrap_or_else(|_| { panic!("Error connection to {}", database_url) })
-}
\ No newline at end of file
+    let database_url = demo.database_url(database_url);
+    let settings = PoolSettings::from_env();
+
+    let primary = connect(&database_url, &settings);
+
+    let replica_url = dotenv::var("DATABASE_READ_URL").ok().filter(|value| !value.trim().is_empty() && !demo.enabled);
+    let replica = replica_url.map(|url| connect(&url, &settings));
+
+    ReadWritePool::new(primary, replica)
+}
+
+fn connect(database_url: &str, settings: &PoolSettings) -> MySqlConnectionPool {
+    let mut retry = 0;
+    loop {
+        match init_pool(database_url, settings) {
+            Ok(pool) => return pool,
+            Err(e) if retry < settings.startup_retries => {
+                let delay = settings.backoff(retry);
+                tracing::warn!(error = %e, retry = retry + 1, delay_ms = delay.as_millis() as u64, "the database is not reachable yet");
+                thread::sleep(delay);
+                retry += 1;
+            }
+            Err(e) => panic!("Unable to reach the database after {} retries: {}", settings.startup_retries, e),
+        }
+    }
+}
+
+/**
+ * The primary and, when there is one, the read-only replica. The replica lags
+ * the primary by a little, so it serves the GraphQL queries alone; the
+ * mutations, the jobs and the other handlers work on the primary, which is
+ * what the pool stands for where one is expected.
+ */
+#[derive(Clone)]
+pub struct ReadWritePool {
+    primary: MySqlConnectionPool,
+    replica: Option<MySqlConnectionPool>,
+}
+
+impl ReadWritePool {
+    pub fn new(primary: MySqlConnectionPool, replica: Option<MySqlConnectionPool>) -> ReadWritePool {
+        ReadWritePool { primary, replica }
+    }
+
+    pub fn primary(&self) -> &MySqlConnectionPool {
+        &self.primary
+    }
+
+    /**
+     * The replica, else the primary.
+     */
+    pub fn read(&self) -> &MySqlConnectionPool {
+        self.replica.as_ref().unwrap_or(&self.primary)
+    }
+}
+
+impl Deref for ReadWritePool {
+    type Target = MySqlConnectionPool;
+
+    fn deref(&self) -> &MySqlConnectionPool {
+        &self.primary
+    }
+}
+
+/**
+ * A connection for a handler working outside the GraphQL; DB_UNAVAILABLE when
+ * none could be had within the timeout.
+ */
+pub fn checkout(pool: &MySqlConnectionPool) -> Result<PooledMysqlConnection, &'static str> {
+    pool.get().map_err(|e| {
+        tracing::warn!(error = %e, "unable to check out a connection");
+        DB_UNAVAILABLE
+    })
+}
+
+/**
+ * The one connection a request works with, checked out of the pool the first
+ * time a resolver asks for it and returned when the request is done.
+ *
+ * A request that is turned away before touching the database never takes a
+ * connection; one that resolves many fields takes a single one. A clone starts
+ * empty, as a connection belongs to a single request.
+ */
+#[derive(Default)]
+pub struct RequestConnection(Mutex<Option<PooledMysqlConnection>>);
+
+impl Clone for RequestConnection {
+    fn clone(&self) -> RequestConnection {
+        RequestConnection::default()
+    }
+}
+
+impl RequestConnection {
+    /**
+     * The connection of the request, checked out now when it is not yet; DB_UNAVAILABLE
+     * when none could be had within the timeout, for the resolver to answer with.
+     *
+     * The resolvers of a request run on one thread, so a slot already locked is
+     * held further up the same call; waiting on it would never end.
+     */
+    pub fn hold<'a>(&'a self, pool: &MySqlConnectionPool) -> Result<LazyConnection<'a>, &'static str> {
+        let mut slot = match self.0.try_lock() {
+            Ok(slot) => slot,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                tracing::error!("the connection of the request was asked for while held");
+                return Err(CONNECTION_IN_USE);
+            }
+        };
+
+        if slot.is_none() {
+            *slot = Some(checkout(pool)?);
+        }
+
+        Ok(LazyConnection(slot))
+    }
+}
+
+/**
+ * A handle on the connection of the request. The resolvers run one after another,
+ * each holding the handle for as long as it runs.
+ */
+pub struct LazyConnection<'a>(MutexGuard<'a, Option<PooledMysqlConnection>>);
+
+impl<'a> Deref for LazyConnection<'a> {
+    type Target = MysqlConnection;
+
+    // The handle is only made once the connection is in the slot.
+    fn deref(&self) -> &MysqlConnection {
+        self.0.as_ref().expect("the connection of the request is checked out")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_not_take_a_connection_until_used() {
+        let pool: MySqlConnectionPool = Pool::builder().min_idle(Some(0)).build_unchecked(ConnectionManager::new("mysql://nobody@localhost/none"));
+        let request = RequestConnection::default();
+
+        let _other = request.clone();
+
+        assert_eq!(0, pool.state().connections);
+    }
+
+    #[test]
+    fn should_answer_unavailable_without_the_database() {
+        let pool = Pool::builder()
+            .min_idle(Some(0))
+            .connection_timeout(Duration::from_millis(200))
+            .build_unchecked(ConnectionManager::new("mysql://nobody@127.0.0.1:1/none"));
+        let request = RequestConnection::default();
+
+        assert_eq!(Some(DB_UNAVAILABLE), request.hold(&pool).err());
+        assert_eq!(Some(DB_UNAVAILABLE), request.hold(&pool).err());
+    }
+
+    #[test]
+    fn should_double_the_wait_up_to_a_limit() {
+        let settings = PoolSettings {
+            size: 10,
+            connection_timeout: Duration::from_secs(30),
+            startup_retries: 8,
+            retry_delay: Duration::from_millis(500),
+        };
+
+        assert_eq!(Duration::from_millis(500), settings.backoff(0));
+        assert_eq!(Duration::from_secs(2), settings.backoff(2));
+        assert_eq!(MAX_RETRY_DELAY, settings.backoff(7));
+        assert_eq!(MAX_RETRY_DELAY, settings.backoff(40));
+    }
+
+    #[test]
+    fn should_read_from_the_primary_without_a_replica() {
+        let primary = Pool::builder().min_idle(Some(0)).max_size(2).build_unchecked(ConnectionManager::new("mysql://nobody@localhost/none"));
+        let replica = Pool::builder().min_idle(Some(0)).max_size(3).build_unchecked(ConnectionManager::new("mysql://nobody@localhost/none"));
+
+        let pools = ReadWritePool::new(primary.clone(), None);
+        assert_eq!(2, pools.read().max_size());
+
+        let pools = ReadWritePool::new(primary, Some(replica));
+        assert_eq!(3, pools.read().max_size());
+        assert_eq!(2, pools.primary().max_size());
+    }
+}
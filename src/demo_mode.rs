@@ -0,0 +1,123 @@
+/**
+ * The demo deployment, for showing the platform without risking the real data.
+ *
+ * In the demo mode the server runs on a database of its own, and a visitor opens
+ * a sandbox at POST demo/sandboxes: a coach and a member seeded with a program to
+ * try things on. Every GraphQL request then carries the id of the sandbox in the
+ * X-Demo-Sandbox header, and a request without a sandbox that is still open is
+ * turned away with a 403.
+ *
+ * The visitors may run any mutation on their sandbox, but for the ones that reach
+ * beyond it, like the registrations, the banners, the webhooks and the data fixes.
+ * The "demo-purge" job empties the demo database every night, ending the sandboxes.
+ *
+ * DEMO_MODE            true for the demo deployment
+ * DEMO_DATABASE_URL    the database of the demo; it must differ from DATABASE_URL
+ */
+use actix_web::error::BlockingError;
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use crate::db_manager::checkout;
+use crate::field_usage;
+use crate::graphql_schema::DBContext;
+use crate::services::demo_sandboxes::gate_sandbox;
+
+const MODE_KEY: &str = "DEMO_MODE";
+const DATABASE_KEY: &str = "DEMO_DATABASE_URL";
+
+pub const SANDBOX_HEADER: &str = "X-Demo-Sandbox";
+
+const NO_SANDBOX: &str = "Please open a sandbox at demo/sandboxes and send its id in the X-Demo-Sandbox header.";
+
+// The mutations touching the platform as a whole, or the world outside it.
+const LOCKED_MUTATIONS: [&str; 15] = [
+    "createUser",
+    "resetPassword",
+    "requestPasswordReset",
+    "confirmPasswordReset",
+    "changeAccountState",
+    "createBanner",
+    "updateBanner",
+    "deleteBanner",
+    "createWebhook",
+    "anonymizeData",
+    "relinkEnrollment",
+    "fixSessionDates",
+    "reassignNoteAuthor",
+    "publishAgreement",
+    "publishFeeSchedule",
+];
+
+#[derive(Clone, Copy, Debug)]
+pub struct DemoMode {
+    pub enabled: bool,
+}
+
+impl DemoMode {
+    pub fn from_env() -> DemoMode {
+        DemoMode {
+            enabled: std::env::var(MODE_KEY).unwrap_or_default() == "true",
+        }
+    }
+
+    /**
+     * The database to run on. The demo refuses to start on the real database, as
+     * the nightly purge empties it.
+     */
+    pub fn database_url(&self, database_url: String) -> String {
+        if !self.enabled {
+            return database_url;
+        }
+
+        let demo_url = std::env::var(DATABASE_KEY).unwrap_or_default();
+        if demo_url.trim().is_empty() || demo_url == database_url {
+            panic!("The demo mode needs a database of its own; set {} apart from DATABASE_URL", DATABASE_KEY);
+        }
+
+        demo_url
+    }
+
+    /**
+     * Lets a GraphQL request through when it belongs to an open sandbox and asks
+     * for no locked mutation; otherwise gives the 403 to answer with.
+     */
+    pub async fn admit(&self, request: &HttpRequest, ctx: web::Data<DBContext>, query: Option<&str>) -> Result<(), HttpResponse> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if let Some(mutation) = query.and_then(|query| locked_mutation(query, &ctx)) {
+            return Err(HttpResponse::Forbidden().body(format!("The {} is not available in the demo.", mutation)));
+        }
+
+        let sandbox_id = match request.headers().get(SANDBOX_HEADER).and_then(|value| value.to_str().ok()) {
+            Some(value) if !value.trim().is_empty() => value.trim().to_owned(),
+            _ => return Err(HttpResponse::Forbidden().body(NO_SANDBOX)),
+        };
+
+        let result = web::block(move || {
+            let connection = checkout(&ctx.db)?;
+            gate_sandbox(&connection, sandbox_id.as_str())
+        })
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(BlockingError::Error(e)) => Err(HttpResponse::Forbidden().body(e)),
+            Err(BlockingError::Canceled) => Err(HttpResponse::InternalServerError().finish()),
+        }
+    }
+}
+
+fn locked_mutation(query: &str, ctx: &DBContext) -> Option<String> {
+    let catalog = &ctx.usage.catalog;
+    let selection = field_usage::select(query, catalog.query_root.as_str(), catalog.mutation_root.as_str(), |type_name, field_name| {
+        catalog.returns(type_name, field_name)
+    })?;
+
+    selection
+        .fields
+        .into_iter()
+        .find(|(type_name, field_name)| *type_name == catalog.mutation_root && LOCKED_MUTATIONS.contains(&field_name.as_str()))
+        .map(|(_, field_name)| field_name)
+}
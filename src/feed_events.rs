@@ -0,0 +1,220 @@
+/**
+ * The counts of what awaits a user, pushed to the web UI over a WebSocket so that
+ * it need not poll /feeds/{user_id}.
+ *
+ * The client opens GET /ws/feeds/{user_id} and is sent the counts as they stand,
+ * then again whenever a discussion is posted to or by the user, or a session of
+ * the user is created or changes its state. Every message is a text frame: the
+ * JSON of FeedCounts, or {"goalBoard": ...} with the JSON of a GoalBoardEvent when
+ * a card of a goal board the user shares changes. The socket answers the pings of
+ * the client and closes when asked; nothing else is read from it.
+ *
+ * As with the session events, the sockets are kept in memory, on the server the
+ * client reached; a client that reconnects is sent the counts again.
+ */
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws::{self, CloseCode, Codec, Frame, Message};
+use actix_web::web::{BytesMut, Payload};
+use actix_web::{Error, HttpRequest, HttpResponse};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::future::ready;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::models::discussion_queue::FeedCounts;
+use crate::models::goal_boards::GoalBoardEvent;
+
+// The open sockets of a server; the clients beyond are asked to retry later.
+const MAX_SUBSCRIBERS: usize = 2000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BoardMessage<'a> {
+    goal_board: &'a GoalBoardEvent,
+}
+
+struct Subscriber {
+    user_id: String,
+    sender: UnboundedSender<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct FeedEvents {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl FeedEvents {
+    /**
+     * The counts of the user as they change, opening with the given ones; none
+     * when the server is full.
+     */
+    pub fn subscribe(&self, counts: &FeedCounts) -> Option<UnboundedReceiver<String>> {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+
+        if subscribers.len() >= MAX_SUBSCRIBERS {
+            return None;
+        }
+
+        let (sender, receiver) = unbounded();
+        sender.unbounded_send(text_of(counts)).ok()?;
+
+        subscribers.push(Subscriber {
+            user_id: counts.user_id.to_owned(),
+            sender,
+        });
+
+        Some(receiver)
+    }
+
+    /**
+     * Whether the user has a socket open here; the counts of the others are not
+     * worth reading.
+     */
+    pub fn follows(&self, user_id: &str) -> bool {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|subscriber| subscriber.user_id == user_id && !subscriber.sender.is_closed())
+    }
+
+    // No socket is open here, so there is nobody to push to.
+    pub fn is_idle(&self) -> bool {
+        self.subscribers.lock().unwrap().iter().all(|subscriber| subscriber.sender.is_closed())
+    }
+
+    pub fn publish(&self, counts: &FeedCounts) {
+        self.send(counts.user_id.as_str(), text_of(counts));
+    }
+
+    pub fn publish_board(&self, user_id: &str, event: &GoalBoardEvent) {
+        self.send(user_id, serde_json::to_string(&BoardMessage { goal_board: event }).unwrap_or_default());
+    }
+
+    // The sockets of the user get the text; the ones gone are dropped.
+    fn send(&self, user_id: &str, text: String) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.user_id != user_id || subscriber.sender.unbounded_send(text.clone()).is_ok());
+    }
+}
+
+fn text_of(counts: &FeedCounts) -> String {
+    serde_json::to_string(counts).unwrap_or_default()
+}
+
+/**
+ * The response upgrading the request to the socket. The counts from the receiver
+ * go out as text frames, along with the answers to the client, until either side
+ * closes.
+ */
+pub fn socket(request: &HttpRequest, payload: Payload, receiver: UnboundedReceiver<String>) -> Result<HttpResponse, Error> {
+    let mut response = ws::handshake(request.head())?;
+
+    let (control, answers) = unbounded();
+    actix_web::rt::spawn(answer(payload, control));
+
+    let messages = stream::select(receiver.map(Message::Text), answers).scan(false, |closed, message| {
+        if *closed {
+            return ready(None);
+        }
+
+        *closed = matches!(message, Message::Close(_));
+        ready(Some(message))
+    });
+
+    let mut codec = Codec::new();
+    let frames = messages.map(move |message| {
+        let mut buffer = BytesMut::new();
+        codec.encode(message, &mut buffer).map(|_| buffer.freeze())
+    });
+
+    Ok(response.streaming(frames))
+}
+
+// Reads the frames of the client for its pings and its close; a client gone is closed too.
+async fn answer(mut payload: Payload, control: UnboundedSender<Message>) {
+    let mut codec = Codec::new();
+    let mut buffer = BytesMut::new();
+
+    while let Some(Ok(chunk)) = payload.next().await {
+        buffer.extend_from_slice(&chunk);
+
+        loop {
+            match codec.decode(&mut buffer) {
+                Ok(Some(Frame::Ping(message))) => {
+                    let _ = control.unbounded_send(Message::Pong(message));
+                }
+                Ok(Some(Frame::Close(reason))) => {
+                    let _ = control.unbounded_send(Message::Close(reason));
+                    return;
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(_) => {
+                    let _ = control.unbounded_send(Message::Close(Some(CloseCode::Protocol.into())));
+                    return;
+                }
+            }
+        }
+    }
+
+    let _ = control.unbounded_send(Message::Close(None));
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn counts(user_id: &str, pending_discussions: i64) -> FeedCounts {
+        FeedCounts {
+            user_id: user_id.to_owned(),
+            pending_discussions,
+            unread_notifications: 0,
+        }
+    }
+
+    #[test]
+    fn should_push_the_counts_only_to_their_user() {
+        let feeds = FeedEvents::default();
+
+        let mut first = feeds.subscribe(&counts("u-1", 1)).unwrap();
+        let mut second = feeds.subscribe(&counts("u-2", 4)).unwrap();
+
+        assert!(feeds.follows("u-1"));
+        assert!(!feeds.follows("u-3"));
+
+        feeds.publish(&counts("u-1", 2));
+
+        assert_eq!("{\"userId\":\"u-1\",\"pendingDiscussions\":1,\"unreadNotifications\":0}", first.try_next().unwrap().unwrap());
+        assert_eq!("{\"userId\":\"u-1\",\"pendingDiscussions\":2,\"unreadNotifications\":0}", first.try_next().unwrap().unwrap());
+        assert!(second.try_next().unwrap().is_some());
+        assert!(second.try_next().is_err());
+
+        drop(first);
+        assert!(!feeds.follows("u-1"));
+    }
+
+    #[test]
+    fn should_push_the_board_changes_beside_the_counts() {
+        let feeds = FeedEvents::default();
+        let mut member = feeds.subscribe(&counts("u-1", 0)).unwrap();
+        let mut coach = feeds.subscribe(&counts("u-2", 0)).unwrap();
+        member.try_next().unwrap();
+        coach.try_next().unwrap();
+
+        let event = GoalBoardEvent {
+            enrollment_id: String::from("e-1"),
+            card_id: String::from("g-1"),
+            change: "ADDED",
+        };
+        feeds.publish_board("u-1", &event);
+
+        assert_eq!("{\"goalBoard\":{\"enrollmentId\":\"e-1\",\"cardId\":\"g-1\",\"change\":\"ADDED\"}}", member.try_next().unwrap().unwrap());
+        assert!(coach.try_next().is_err());
+    }
+}
@@ -0,0 +1,356 @@
+/**
+ * Counts the fields of the schema that the clients select, so that we know which
+ * of the legacy fields are still in use before removing them.
+ *
+ * A query is walked on its tokens when it arrives and the fields it selects are
+ * counted in memory, by the operation and the day; the counts are saved by the
+ * "field-usage" job. The counts of a query that fails to parse are dropped, as
+ * juniper rejects such a query anyway.
+ */
+use chrono::NaiveDate;
+use diesel::mysql::MysqlConnection;
+use juniper::meta::MetaType;
+use juniper::parser::{Lexer, Token};
+use juniper::GraphQLType;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::commons::util;
+use crate::graphql_schema::{GQSchema, MutationRoot, QueryRoot};
+use crate::models::field_usage::NewFieldUsage;
+use crate::services::field_usage;
+
+const ANONYMOUS: &str = "anonymous";
+
+// The columns keeping the names are 100 characters wide.
+const NAME_LENGTH: usize = 100;
+
+/**
+ * The object types of the schema with the type each field returns and the
+ * reason a field is deprecated, if it is.
+ */
+pub struct FieldCatalog {
+    pub query_root: String,
+    pub mutation_root: String,
+    pub types: HashMap<String, Vec<CatalogField>>,
+}
+
+pub struct CatalogField {
+    pub name: String,
+    pub returns: String,
+    pub deprecation: Option<String>,
+}
+
+impl FieldCatalog {
+    pub fn of(schema: &GQSchema) -> FieldCatalog {
+        let mut types: HashMap<String, Vec<CatalogField>> = HashMap::new();
+
+        for meta_type in schema.schema.concrete_type_list() {
+            let (name, fields) = match meta_type {
+                MetaType::Object(object) => (object.name.to_string(), &object.fields),
+                MetaType::Interface(interface) => (interface.name.to_string(), &interface.fields),
+                _ => continue,
+            };
+
+            if name.starts_with("__") {
+                continue;
+            }
+
+            let catalog_fields = fields
+                .iter()
+                .map(|field| CatalogField {
+                    name: field.name.to_owned(),
+                    returns: field.field_type.innermost_name().to_owned(),
+                    deprecation: field.deprecation_status.reason().map(|reason| reason.to_owned()).or_else(|| {
+                        if field.deprecation_status.is_deprecated() {
+                            Some(String::from(""))
+                        } else {
+                            None
+                        }
+                    }),
+                })
+                .collect();
+
+            types.insert(name, catalog_fields);
+        }
+
+        FieldCatalog {
+            query_root: QueryRoot::name(&()).unwrap_or("QueryRoot").to_owned(),
+            mutation_root: MutationRoot::name(&()).unwrap_or("MutationRoot").to_owned(),
+            types,
+        }
+    }
+
+    pub fn returns(&self, type_name: &str, field_name: &str) -> Option<String> {
+        self.types.get(type_name)?.iter().find(|field| field.name == field_name).map(|field| field.returns.to_owned())
+    }
+}
+
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+pub struct UsageKey {
+    pub day: NaiveDate,
+    pub operation_name: String,
+    pub type_name: String,
+    pub field_name: String,
+}
+
+#[derive(Clone)]
+pub struct FieldUsage {
+    pub catalog: Arc<FieldCatalog>,
+    counts: Arc<Mutex<HashMap<UsageKey, i64>>>,
+}
+
+impl FieldUsage {
+    pub fn new(catalog: FieldCatalog) -> FieldUsage {
+        FieldUsage {
+            catalog: Arc::new(catalog),
+            counts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn record(&self, query: &str, operation_name: Option<&str>) {
+        let catalog = &self.catalog;
+        let selection = match select(query, catalog.query_root.as_str(), catalog.mutation_root.as_str(), |type_name, field_name| catalog.returns(type_name, field_name)) {
+            Some(value) => value,
+            None => return,
+        };
+
+        let operation = operation_name.map(|name| name.to_owned()).or(selection.operation).unwrap_or_else(|| String::from(ANONYMOUS));
+        let day = util::now().date();
+
+        let mut counts = self.counts.lock().unwrap();
+        for (type_name, field_name) in selection.fields {
+            let key = UsageKey {
+                day,
+                operation_name: clip(operation.as_str()),
+                type_name: clip(type_name.as_str()),
+                field_name: clip(field_name.as_str()),
+            };
+
+            *counts.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    /**
+     * Saves the counts so far; they are kept for the next run when the save fails.
+     */
+    pub fn flush(&self, connection: &MysqlConnection) -> Result<usize, &'static str> {
+        let drained: HashMap<UsageKey, i64> = std::mem::take(&mut *self.counts.lock().unwrap());
+
+        if drained.is_empty() {
+            return Ok(0);
+        }
+
+        let rows: Vec<NewFieldUsage> = drained.iter().map(|(key, hits)| NewFieldUsage::from(key, *hits)).collect();
+
+        match field_usage::save_counts(connection, &rows) {
+            Ok(saved) => Ok(saved),
+            Err(e) => {
+                let mut counts = self.counts.lock().unwrap();
+                for (key, hits) in drained {
+                    *counts.entry(key).or_insert(0) += hits;
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+fn clip(name: &str) -> String {
+    name.chars().take(NAME_LENGTH).collect()
+}
+
+pub struct Selection {
+    pub operation: Option<String>,
+    pub fields: Vec<(String, String)>,
+}
+
+/**
+ * The fields a document selects, as pairs of the type and the field; `returns`
+ * tells the type a field returns, for walking into its selection. A fragment is
+ * walked on its own type condition, and the fields of an unknown type and the
+ * introspection fields are left out.
+ */
+pub fn select<F>(query: &str, query_root: &str, mutation_root: &str, returns: F) -> Option<Selection>
+where
+    F: Fn(&str, &str) -> Option<String>,
+{
+    let tokens: Vec<Token> = Lexer::new(query).map(|token| token.map(|spanning| spanning.item)).collect::<Result<_, _>>().ok()?;
+
+    let mut selection = Selection { operation: None, fields: Vec::new() };
+    let mut at = 0;
+
+    loop {
+        let root: Option<String> = match tokens.get(at)? {
+            Token::EndOfFile => return Some(selection),
+            Token::CurlyOpen => Some(query_root.to_owned()),
+            Token::Name("query") | Token::Name("mutation") | Token::Name("subscription") => {
+                let root = if tokens[at] == Token::Name("mutation") { mutation_root } else { query_root };
+
+                at += 1;
+                if let Some(Token::Name(name)) = tokens.get(at) {
+                    if selection.operation.is_none() {
+                        selection.operation = Some((*name).to_owned());
+                    }
+                }
+
+                at = skip_to_selection(&tokens, at)?;
+                Some(root.to_owned())
+            }
+            Token::Name("fragment") => {
+                let type_condition = match (tokens.get(at + 2)?, tokens.get(at + 3)?) {
+                    (Token::Name("on"), Token::Name(name)) => (*name).to_owned(),
+                    _ => return None,
+                };
+
+                at = skip_to_selection(&tokens, at)?;
+                Some(type_condition)
+            }
+            _ => return None,
+        };
+
+        at = walk(&tokens, at, root, &returns, &mut selection.fields)?;
+    }
+}
+
+fn skip_to_selection(tokens: &[Token], mut at: usize) -> Option<usize> {
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyOpen => return Some(at),
+            Token::ParenOpen => at = skip_arguments(tokens, at)?,
+            _ => at += 1,
+        }
+    }
+}
+
+// The arguments may hold objects, hence the braces within are skipped too.
+fn skip_arguments(tokens: &[Token], mut at: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    loop {
+        match tokens.get(at)? {
+            Token::ParenOpen => depth += 1,
+            Token::ParenClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at + 1);
+                }
+            }
+            _ => {}
+        }
+        at += 1;
+    }
+}
+
+/**
+ * Walks the selection set opening at `at`, returning the position after its close.
+ */
+fn walk<F>(tokens: &[Token], mut at: usize, type_name: Option<String>, returns: &F, fields: &mut Vec<(String, String)>) -> Option<usize>
+where
+    F: Fn(&str, &str) -> Option<String>,
+{
+    at += 1;
+
+    // The type of the field just read, for the selection that may follow it.
+    let mut next_type: Option<String> = None;
+
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyClose => return Some(at + 1),
+            Token::CurlyOpen => at = walk(tokens, at, next_type.take(), returns, fields)?,
+            Token::ParenOpen => at = skip_arguments(tokens, at)?,
+            // A directive; its arguments are skipped as any other.
+            Token::At => at += 2,
+            Token::Ellipsis => {
+                at += 1;
+                next_type = match tokens.get(at)? {
+                    Token::Name("on") => {
+                        let condition = match tokens.get(at + 1)? {
+                            Token::Name(name) => Some((*name).to_owned()),
+                            _ => None,
+                        };
+                        at += 2;
+                        condition
+                    }
+                    // The spread of a named fragment; the fragment is walked on its own.
+                    Token::Name(_) => {
+                        at += 1;
+                        None
+                    }
+                    _ => type_name.clone(),
+                };
+            }
+            Token::Name(name) => {
+                let field = if tokens.get(at + 1) == Some(&Token::Colon) {
+                    at += 2;
+                    match tokens.get(at)? {
+                        Token::Name(field) => *field,
+                        _ => return None,
+                    }
+                } else {
+                    *name
+                };
+                at += 1;
+
+                next_type = None;
+                if let Some(parent) = &type_name {
+                    if !field.starts_with("__") {
+                        fields.push((parent.to_owned(), field.to_owned()));
+                        next_type = returns(parent.as_str(), field);
+                    }
+                }
+            }
+            _ => at += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn returns(type_name: &str, field_name: &str) -> Option<String> {
+        match (type_name, field_name) {
+            ("QueryRoot", "getSessions") => Some(String::from("SessionResult")),
+            ("SessionResult", "sessions") => Some(String::from("Session")),
+            ("MutationRoot", "createSession") => Some(String::from("Session")),
+            _ => None,
+        }
+    }
+
+    fn fields_of(query: &str) -> Vec<String> {
+        let selection = select(query, "QueryRoot", "MutationRoot", returns).unwrap();
+        selection.fields.iter().map(|(type_name, field_name)| format!("{}.{}", type_name, field_name)).collect()
+    }
+
+    #[test]
+    fn should_follow_the_types_of_the_fields() {
+        let query = r#"query Upcoming($id: String!) {
+            getSessions(criteria: { id: $id, filter: { a: "}" } }) {
+                sessions { id isClosed @include(if: true) title: name }
+                error { message }
+                __typename
+            }
+        }"#;
+
+        assert_eq!(vec!["QueryRoot.getSessions", "SessionResult.sessions", "Session.id", "Session.isClosed", "Session.name", "SessionResult.error"], fields_of(query));
+        assert_eq!(Some(String::from("Upcoming")), select(query, "QueryRoot", "MutationRoot", returns).unwrap().operation);
+    }
+
+    #[test]
+    fn should_walk_the_fragments_on_their_type() {
+        let query = r#"
+            mutation { createSession(request: {}) { ...Basics ... on Session { status } } }
+            fragment Basics on Session { name }
+        "#;
+
+        assert_eq!(vec!["MutationRoot.createSession", "Session.status", "Session.name"], fields_of(query));
+    }
+
+    #[test]
+    fn should_give_up_on_a_broken_query() {
+        assert!(select("{ getSessions { ", "QueryRoot", "MutationRoot", returns).is_none());
+        assert!(select("query ? {}", "QueryRoot", "MutationRoot", returns).is_none());
+    }
+}
@@ -1,18 +1,42 @@
+use crate::asset_policy::{respond, AssetClass};
 use crate::commons::util::fuzzy_id;
+use crate::content_store::ContentStore;
+use crate::db_manager::{checkout, MySqlConnectionPool};
+use crate::graphql_schema::DBContext;
+use crate::graphql_uploads::{self, FileMap, MAP_PART, OPERATIONS_PART};
+use crate::models::board_annotations::AnnotationCriteria;
+use crate::models::board_versions::{version_file_name, VersionEntry, VERSIONS_DIR};
+use crate::models::file_access_log::NewFileAccess;
+use crate::models::notes::FileRequest;
+use crate::models::uploads::{UploadManifest, UploadResult, MANIFEST_PART};
+use crate::models::users::LoginRequest;
+use crate::services::board_annotations::export_board;
+use crate::services::board_versions;
+use crate::services::data_exports;
+use crate::services::file_access_log::record_access;
+use crate::services::file_registry;
+use crate::services::notes::attach_file;
+use crate::services::thumbnails;
+use crate::services::users::authenticate;
+use crate::storage::{storage, Area};
+use crate::upload_policy::{FileKind, Rejection, UploadClass, UploadRule, SNIFF_BYTES, TOO_LARGE};
+use crate::upload_pool::UploadPool;
 use actix_files::NamedFile;
-use actix_multipart::Multipart;
+use actix_multipart::{Field, Multipart};
+use actix_web::error::BlockingError;
+use actix_web::http::header;
 use actix_web::{web, Error, HttpRequest, HttpResponse};
 use futures::{StreamExt, TryStreamExt};
-use std::fs;
+use serde::Deserialize;
+use serde_json::Value;
+use sodiumoxide::crypto::hash::sha256;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub const SESSION_ASSET_DIR: &str = "/Users/pmpower/assets/sessions";
-pub const PROGRAM_ASSET_DIR: &str = "/Users/pmpower/assets/programs";
-pub const USER_ASSET_DIR: &str = "/Users/pmpower/assets/users";
-pub const PLATFORM_ASSET_DIR: &str = "/Users/pmpower/assets/platform";
+const VERSION_NOT_FOUND: &str = "Unable to find the version of the board.";
 
-pub async fn manage_notes_file(mut payload: Multipart) -> Result<HttpResponse, Error> {
+pub async fn manage_notes_file(ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, mut payload: Multipart) -> Result<HttpResponse, Error> {
+    let rule = UploadClass::Notes.rule();
     let mut file_paths: Vec<String> = Vec::new();
 
     while let Ok(Some(mut field)) = payload.try_next().await {
@@ -22,22 +46,17 @@ pub async fn manage_notes_file(mut payload: Multipart) -> Result<HttpResponse, E
         let file_key = fuzzy_id();
 
         // Ensure to create a directory for the session_user.
-        let dir_path = format!("{}/{}/notes/{}", SESSION_ASSET_DIR, session_user_fuzzy_id, file_key);
-        std::fs::create_dir_all(dir_path).unwrap();
+        let dir_path = storage().dir(Area::Sessions).join(session_user_fuzzy_id).join("notes").join(file_key);
+        std::fs::create_dir_all(&dir_path).unwrap();
 
         // Now we
-        let filepath = format!("{}/{}/notes/{}/{}", SESSION_ASSET_DIR, session_user_fuzzy_id, file_key, sanitize_filename::sanitize(&filename));
-        file_paths.push(filepath.to_owned());
+        let filepath = dir_path.join(sanitize_filename::sanitize(&filename)).to_string_lossy().into_owned();
 
-        // File::create is blocking operation, use threadpool
-        let mut f = web::block(|| std::fs::File::create(filepath)).await.unwrap();
-        // Field in turn is stream of *Bytes* object
-        while let Some(chunk) = field.next().await {
-            let data = chunk.unwrap();
-
-            // filesystem operations are blocking, we have to use threadpool
-            f = web::block(move || f.write_all(&data).map(|_| f)).await?;
+        if let Stored::Rejected(rejection) = store(&uploads, &ctx.db, &mut field, filepath.as_str(), filename, &rule).await? {
+            return rejected(rejection);
         }
+
+        file_paths.push(filepath);
     }
 
     let json_response = serde_json::to_string(&file_paths)?;
@@ -45,9 +64,337 @@ pub async fn manage_notes_file(mut payload: Multipart) -> Result<HttpResponse, E
     Ok(HttpResponse::Ok().content_type("application/json").body(json_response))
 }
 
-pub async fn manage_program_content(_request: HttpRequest, mut payload: Multipart) -> Result<HttpResponse, Error> {
+/**
+ * Several files in one request. The manifest part should come first, as we store
+ * each file while it streams in. We answer with the outcome of every file; a bad
+ * file does not fail the others.
+ */
+pub async fn manage_batch_upload(ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, mut payload: Multipart) -> Result<HttpResponse, Error> {
+    let mut manifest: Option<UploadManifest> = None;
+    let mut results: Vec<UploadResult> = Vec::new();
+
+    while let Ok(Some(mut field)) = payload.try_next().await {
+        let content_type = field.content_disposition().unwrap();
+        let part = content_type.get_name().unwrap_or_default().to_owned();
+        let given_name = content_type.get_filename().unwrap_or(part.as_str()).to_owned();
+        let filename = sanitize_filename::sanitize(&given_name);
+
+        if part == MANIFEST_PART {
+            let mut bytes: Vec<u8> = Vec::new();
+            while let Some(chunk) = field.next().await {
+                bytes.extend_from_slice(&chunk?);
+            }
+
+            let parsed: UploadManifest = match serde_json::from_slice(&bytes) {
+                Ok(value) => value,
+                Err(_) => return bad_manifest(vec![String::from("The manifest is not readable.")]),
+            };
+
+            let errors = parsed.validate();
+            if !errors.is_empty() {
+                return bad_manifest(errors);
+            }
+
+            manifest = Some(parsed);
+            continue;
+        }
+
+        let entry = match &manifest {
+            Some(value) => value.entry(part.as_str()),
+            None => return bad_manifest(vec![String::from("The manifest should be the first part of the request.")]),
+        };
+
+        let entry = match entry {
+            Some(value) => value,
+            None => {
+                while field.next().await.is_some() {}
+                results.push(UploadResult::failed(part.as_str(), given_name.as_str(), "The part is not described in the manifest."));
+                continue;
+            }
+        };
+
+        // A directory we cannot make fails this file alone.
+        let dir_path = entry.directory(&storage().dir(Area::Sessions).to_string_lossy(), fuzzy_id().as_str());
+        if let Err(e) = std::fs::create_dir_all(&dir_path) {
+            eprintln!("Unable to create {}: {}", dir_path, e);
+            while field.next().await.is_some() {}
+            results.push(UploadResult::failed(part.as_str(), given_name.as_str(), "The file could not be stored."));
+            continue;
+        }
+
+        let filepath = format!("{}/{}", dir_path, filename);
+        let file_type = field.content_type().to_string();
+
+        match store(&uploads, &ctx.db, &mut field, filepath.as_str(), filename.as_str(), &entry.class().rule()).await? {
+            Stored::Written(size, _, checksum) => results.push(UploadResult::stored(entry, given_name.as_str(), filename, filepath, size as i32, file_type, checksum)),
+            Stored::Rejected(rejection) => results.push(UploadResult::failed(part.as_str(), given_name.as_str(), rejection.message.as_str())),
+        }
+    }
+
+    if let Some(value) = &manifest {
+        for entry in &value.files {
+            if !results.iter().any(|result| result.part == entry.part) {
+                results.push(UploadResult::failed(entry.part.as_str(), "", "The part described in the manifest is missing."));
+            }
+        }
+    }
+
+    let results = web::block(move || {
+        let connection = checkout(&ctx.db).map_err(std::io::Error::other)?;
+        let results = attach_to_notes(&connection, results)?;
+        keep_board_versions(&connection, &results);
+        Ok::<Vec<UploadResult>, std::io::Error>(results)
+    })
+    .await?;
+
+    let json_response = serde_json::to_string(&results)?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(json_response))
+}
+
+/**
+ * The operations of a GraphQL multipart request, with its files stored and put
+ * in place of their variables, and the paths of the stored files; see
+ * graphql_uploads. A request turned away leaves none of its files behind.
+ */
+pub async fn read_graphql_multipart(ctx: &web::Data<DBContext>, uploads: &web::Data<UploadPool>, mut payload: Multipart) -> Result<(Value, Vec<String>), Error> {
+    let rule = UploadClass::Notes.rule();
+    let mut operations: Option<Value> = None;
+    let mut map: Option<FileMap> = None;
+    let mut stored: Vec<String> = Vec::new();
+
+    while let Ok(Some(mut field)) = payload.try_next().await {
+        let content_type = field.content_disposition().unwrap();
+        let part = content_type.get_name().unwrap_or_default().to_owned();
+        let given_name = content_type.get_filename().unwrap_or(part.as_str()).to_owned();
+
+        if part == OPERATIONS_PART || part == MAP_PART {
+            let mut bytes: Vec<u8> = Vec::new();
+            while let Some(chunk) = field.next().await {
+                bytes.extend_from_slice(&chunk?);
+            }
+
+            let readable = if part == OPERATIONS_PART {
+                serde_json::from_slice(&bytes).map(|value| operations = Some(value)).is_ok()
+            } else {
+                serde_json::from_slice(&bytes).map(|value| map = Some(value)).is_ok()
+            };
+
+            if !readable {
+                return refuse_multipart(uploads, stored, format!("The {} part is not readable.", part)).await;
+            }
+            continue;
+        }
+
+        let (operations, map) = match (operations.as_mut(), map.as_ref()) {
+            (Some(operations), Some(map)) => (operations, map),
+            _ => return refuse_multipart(uploads, stored, String::from("The operations and the map should come before the files.")).await,
+        };
+
+        // A file the map does not mention is ignored, as the spec has it.
+        let paths = map.get(&part).cloned().unwrap_or_default();
+        let targets = match paths.iter().map(|path| graphql_uploads::target(operations, path)).collect::<Result<Vec<_>, _>>() {
+            Ok(targets) if !targets.is_empty() => targets,
+            Ok(_) => {
+                while field.next().await.is_some() {}
+                continue;
+            }
+            Err(e) => return refuse_multipart(uploads, stored, e.to_owned()).await,
+        };
+
+        let dir_path = storage().dir(Area::Sessions).join(targets[0].session_user_id.as_str()).join("notes").join(fuzzy_id());
+        if std::fs::create_dir_all(&dir_path).is_err() {
+            return refuse_multipart(uploads, stored, String::from("The file could not be stored.")).await;
+        }
+
+        let filename = sanitize_filename::sanitize(&given_name);
+        let filepath = dir_path.join(&filename).to_string_lossy().into_owned();
+        let file_type = field.content_type().to_string();
+
+        let size = match store(uploads, &ctx.db, &mut field, filepath.as_str(), filename.as_str(), &rule).await {
+            Ok(Stored::Written(size, _, _)) => size,
+            Ok(Stored::Rejected(rejection)) => return refuse_multipart(uploads, stored, rejection.message).await,
+            Err(e) => {
+                uploads
+                    .block(move || {
+                        graphql_uploads::discard(&stored);
+                        Ok::<(), std::io::Error>(())
+                    })
+                    .await
+                    .ok();
+                return Err(e);
+            }
+        };
+        stored.push(filepath.clone());
+
+        for target in &targets {
+            if let Err(e) = graphql_uploads::place(operations, target, filepath.as_str(), filename.as_str(), file_type.as_str(), size) {
+                return refuse_multipart(uploads, stored, e.to_owned()).await;
+            }
+        }
+    }
+
+    match operations {
+        Some(operations) => Ok((operations, stored)),
+        None => refuse_multipart(uploads, stored, String::from("The request has no operations part.")).await,
+    }
+}
+
+async fn refuse_multipart<T>(uploads: &web::Data<UploadPool>, stored: Vec<String>, message: String) -> Result<T, Error> {
+    uploads
+        .block(move || {
+            graphql_uploads::discard(&stored);
+            Ok::<(), std::io::Error>(())
+        })
+        .await?;
+
+    Err(actix_web::error::ErrorBadRequest(message))
+}
+
+fn attach_to_notes(connection: &diesel::MysqlConnection, mut results: Vec<UploadResult>) -> Result<Vec<UploadResult>, std::io::Error> {
+    for result in results.iter_mut().filter(|result| result.error.is_none()) {
+        if let (Some(note_id), Some(session_user_id), Some(path)) = (&result.note_id, &result.session_user_id, &result.path) {
+            let file = FileRequest {
+                path: path.to_owned(),
+                name: result.stored_name.to_owned().unwrap_or_default(),
+                r#type: result.file_type.to_owned(),
+                size: result.size,
+            };
+
+            if let Err(e) = attach_file(connection, note_id.as_str(), session_user_id.as_str(), &file) {
+                result.error = Some(e.to_owned());
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/**
+ * The board files of the batch become the next versions of their boards; a
+ * version we could not keep does not fail the upload, as the board is in place.
+ */
+fn keep_board_versions(connection: &diesel::MysqlConnection, results: &[UploadResult]) {
+    for result in results.iter().filter(|result| result.error.is_none()) {
+        if let (Some(board_id), Some(board_name), Some(path), Some(checksum)) = (&result.session_id, &result.stored_name, &result.path, &result.checksum) {
+            if let Err(e) = board_versions::keep_version(connection, board_id, board_name, Path::new(path), checksum, result.size as usize) {
+                eprintln!("Unable to keep the version of {}: {}", path, e);
+            }
+        }
+    }
+}
+
+fn bad_manifest(errors: Vec<String>) -> Result<HttpResponse, Error> {
+    let json_response = serde_json::to_string(&errors)?;
+
+    Ok(HttpResponse::BadRequest().content_type("application/json").body(json_response))
+}
+
+enum Stored {
+    // The size, the kind and the SHA-256 of the content, in hex.
+    Written(usize, FileKind, String),
+    Rejected(Rejection),
+}
+
+/**
+ * Stores a part once its first bytes pass the rule, counting its size and
+ * hashing it as it streams in. A part that breaks the rule is drained and leaves
+ * no file behind.
+ */
+async fn store(uploads: &web::Data<UploadPool>, db: &MySqlConnectionPool, field: &mut Field, file_path: &str, file_name: &str, rule: &UploadRule) -> Result<Stored, Error> {
+    let mut head: Vec<u8> = Vec::new();
+    while head.len() < SNIFF_BYTES {
+        match field.next().await {
+            Some(chunk) => head.extend_from_slice(&chunk?),
+            None => break,
+        }
+    }
+
+    let kind = match rule.check_type(file_name, &head).and_then(|kind| rule.check_size(file_name, head.len()).map(|_| kind)) {
+        Ok(kind) => kind,
+        Err(rejection) => {
+            while field.next().await.is_some() {}
+            return Ok(Stored::Rejected(rejection));
+        }
+    };
+
+    let mut size = head.len();
+    let mut digest = sha256::State::new();
+    digest.update(&head);
+
+    // The content streams into a scratch file, which becomes its blob; see content_store.
+    let scratch = uploads.block(|| ContentStore::local().scratch_path()).await?;
+
+    let target = scratch.clone();
+    let mut f = uploads.block(move || std::fs::File::create(target).and_then(|mut f| f.write_all(&head).map(|_| f))).await?;
+
+    while let Some(chunk) = field.next().await {
+        let data = chunk?;
+        size += data.len();
+
+        if let Err(rejection) = rule.check_size(file_name, size) {
+            drop(f);
+            let target = scratch.clone();
+            uploads.block(move || std::fs::remove_file(target)).await?;
+
+            while field.next().await.is_some() {}
+            return Ok(Stored::Rejected(rejection));
+        }
+
+        digest.update(&data);
+
+        // filesystem operations are blocking, we have to use the upload pool
+        f = uploads.block(move || f.write_all(&data).map(|_| f)).await?;
+    }
+
+    drop(f);
+
+    let checksum: String = digest.finalize().0.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    let (blob_checksum, target) = (checksum.clone(), PathBuf::from(file_path));
+    uploads.block(move || ContentStore::local().settle(&scratch, &blob_checksum, &target).and_then(|_| storage().keep(&target))).await?;
+
+    // The file is in place either way; an unregistered one merely loses its blob to the sweeper.
+    let (pool, registered_path, registered_checksum) = (db.clone(), file_path.to_owned(), checksum.clone());
+    let registered = web::block(move || file_registry::register(&pool.get().unwrap(), &registered_path, &registered_checksum, size)).await;
+    if let Err(e) = registered {
+        eprintln!("Unable to register {}: {}", file_path, e);
+    }
+
+    Ok(Stored::Written(size, kind, checksum))
+}
+
+/**
+ * The thumbnails of a stored image, made on the upload pool; an upload does not
+ * fail for want of them.
+ */
+async fn make_thumbnails(uploads: &web::Data<UploadPool>, stored: &Stored, file_path: &str) -> Result<(), Error> {
+    if let Stored::Written(_, kind, _) = stored {
+        if kind.is_image() {
+            let image_path = PathBuf::from(file_path);
+            uploads.block(move || Ok::<usize, std::io::Error>(thumbnails::generate(&image_path))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Tells the UI why the upload was turned away; the files stored before the
+ * rejected one stay.
+ */
+fn rejected(rejection: Rejection) -> Result<HttpResponse, Error> {
+    let mut response = if rejection.code == TOO_LARGE { HttpResponse::PayloadTooLarge() } else { HttpResponse::UnsupportedMediaType() };
+
+    let json_response = serde_json::to_string(&vec![rejection])?;
+
+    Ok(response.content_type("application/json").body(json_response))
+}
+
+pub async fn manage_program_content(_request: HttpRequest, ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, mut payload: Multipart) -> Result<HttpResponse, Error> {
     let program_fuzzy_id: String = _request.match_info().query("program_fuzzy_id").parse().unwrap();
     let purpose: String = _request.match_info().query("purpose").parse().unwrap();
+    let rule = UploadClass::Programs.rule();
 
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_type = field.content_disposition().unwrap();
@@ -55,21 +402,17 @@ pub async fn manage_program_content(_request: HttpRequest, mut payload: Multipar
         let filename = content_type.get_name().unwrap();
 
         // Ensure to create a directory for the program content.
-        let dir_path = format!("{}/{}/{}", PROGRAM_ASSET_DIR, program_fuzzy_id, purpose);
-        std::fs::create_dir_all(dir_path).unwrap();
-
-        let file_path = format!("{}/{}/{}/{}", PROGRAM_ASSET_DIR, program_fuzzy_id, purpose, filename);
-
-        // File::create is blocking operation, use threadpool
-        let mut f = web::block(|| std::fs::File::create(file_path)).await.unwrap();
+        let dir_path = storage().dir(Area::Programs).join(&program_fuzzy_id).join(&purpose);
+        std::fs::create_dir_all(&dir_path).unwrap();
 
-        // Field in turn is stream of *Bytes* object
-        while let Some(chunk) = field.next().await {
-            let data = chunk.unwrap();
+        let file_path = dir_path.join(filename).to_string_lossy().into_owned();
 
-            // filesystem operations are blocking, we have to use threadpool
-            f = web::block(move || f.write_all(&data).map(|_| f)).await?;
+        let stored = store(&uploads, &ctx.db, &mut field, file_path.as_str(), filename, &rule).await?;
+        if let Stored::Rejected(rejection) = stored {
+            return rejected(rejection);
         }
+
+        make_thumbnails(&uploads, &stored, file_path.as_str()).await?;
     }
 
     Ok(HttpResponse::Ok().body("Ok"))
@@ -78,15 +421,11 @@ pub async fn manage_program_content(_request: HttpRequest, mut payload: Multipar
 pub async fn fetch_list_of_boards(_request: HttpRequest) -> Result<HttpResponse, Error> {
     let session_id: PathBuf = _request.match_info().query("session_id").parse().unwrap();
 
-    let mut dir_name: PathBuf = PathBuf::from(SESSION_ASSET_DIR);
+    let mut dir_name: PathBuf = storage().dir(Area::Sessions);
     dir_name.push(session_id);
     dir_name.push("boards");
 
-    let mut entries = fs::read_dir(dir_name)?
-        .map(|res| res.map(|e| e.file_name().into_string()))
-        .collect::<Result<Vec<_>, std::io::Error>>()?;
-
-    entries.sort();
+    let entries = web::block(move || storage().list(&dir_name)).await.map_err(stored_error)?;
 
     let json_response = serde_json::to_string(&entries)?;
 
@@ -97,95 +436,295 @@ pub async fn fetch_list_of_boards(_request: HttpRequest) -> Result<HttpResponse,
  * Especially for obtaining the bord files
  */
 pub fn get_file_names(dir_name: PathBuf) -> Result<Vec<String>,std::io::Error> {
-    let mut file_names: Vec<String> = Vec::new();
+    let file_names = storage().list(&dir_name)?;
 
-    let result = fs::read_dir(dir_name)?;
-    for item in result {
-        let dir_entry: fs::DirEntry = item?;
-        if dir_entry.file_type()?.is_dir() {
-            continue;
-        }
-        let entry = dir_entry.path();
-        let name_result = entry.file_name();
-        if name_result.is_some() {
-            let file_name = format!("{:?}", name_result.unwrap());
-            file_names.push(file_name);
-        }
+    Ok(file_names.iter().map(|file_name| format!("{:?}", file_name)).collect())
+}
+
+/**
+ * The file of the path from the storage, brought to the disk first where it is
+ * kept elsewhere.
+ */
+async fn open_stored(file_name: PathBuf) -> Result<NamedFile, Error> {
+    let local = web::block(move || storage().open(&file_name)).await.map_err(stored_error)?;
+
+    Ok(NamedFile::open(local)?)
+}
+
+// A file missing from the storage is a 404, as it was from the disk.
+fn stored_error(e: BlockingError<std::io::Error>) -> Error {
+    match e {
+        BlockingError::Error(e) => e.into(),
+        BlockingError::Canceled => actix_web::error::ErrorInternalServerError("The storage could not be reached."),
     }
+}
+
+// The downloader, as the client names it in the query string.
+#[derive(Deserialize)]
+struct Accessor {
+    user_id: Option<String>,
+}
 
-    Ok(file_names)
+fn accessor_of(request: &HttpRequest) -> Option<String> {
+    web::Query::<Accessor>::from_query(request.query_string()).ok().and_then(|query| query.into_inner().user_id)
 }
 
-pub async fn fetch_board_file(_request: HttpRequest) -> Result<NamedFile, Error> {
+/**
+ * Every download of the boards, the program contents and the user contents is
+ * recorded before the file is handed over, whatever range it asks for; a
+ * download that could not be recorded is refused, as the log should account
+ * for all of them. The ranges of a video being watched are folded into one
+ * row by record_access.
+ */
+async fn audited(request: &HttpRequest, ctx: web::Data<DBContext>, file: NamedFile, class: AssetClass, file_path: String, accessor_id: Option<String>) -> Result<HttpResponse, Error> {
+    let ip = request.connection_info().realip_remote_addr().map(|value| value.to_owned());
+    let access = NewFileAccess::new(class.name(), file_path.as_str(), accessor_id.as_deref(), ip.as_deref());
+
+    web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        record_access(&connection, &access)
+    })
+    .await
+    .map_err(|e| actix_web::error::ErrorServiceUnavailable(e.to_string()))?;
+
+    respond(file, class, request)
+}
+
+pub async fn fetch_board_file(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
     let session_id: PathBuf = _request.match_info().query("session_id").parse().unwrap();
     let asset_name: PathBuf = _request.match_info().query("filename").parse().unwrap();
 
-    let mut file_name: PathBuf = PathBuf::from(SESSION_ASSET_DIR);
-    file_name.push(session_id);
-    file_name.push("boards");
-    file_name.push(asset_name);
+    let mut relative: PathBuf = session_id;
+    relative.push("boards");
+    relative.push(asset_name);
+
+    let mut file_name: PathBuf = storage().dir(Area::Sessions);
+    file_name.push(&relative);
+
+    let file = open_stored(file_name).await?;
+    let file_path = relative.to_string_lossy().into_owned();
 
-    Ok(NamedFile::open(file_name)?)
+    audited(&_request, ctx, file, AssetClass::Boards, file_path, accessor_of(&_request)).await
+}
+
+/**
+ * The board with the annotations of its people drawn over it, as a PNG.
+ */
+pub async fn fetch_flattened_board(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let criteria = AnnotationCriteria {
+        user_id: _request.match_info().query("user_id").parse().unwrap(),
+        session_id: _request.match_info().query("session_id").parse().unwrap(),
+        board_name: _request.match_info().query("filename").parse().unwrap(),
+    };
+
+    let accessor_id = criteria.user_id.to_owned();
+    let file_path = format!("{}/boards/{}", criteria.session_id, criteria.board_name);
+
+    let db = ctx.clone();
+    let file_name = web::block(move || {
+        let connection = checkout(&db.db)?;
+        export_board(&connection, &criteria)
+    })
+    .await
+    .map_err(|e| actix_web::error::ErrorBadRequest(e.to_string()))?;
+
+    let file = NamedFile::open(file_name)?;
+
+    audited(&_request, ctx, file, AssetClass::Boards, file_path, Some(accessor_id)).await
+}
+
+/**
+ * The versions of a board, the earliest first, for the client to replay.
+ */
+pub async fn fetch_list_of_board_versions(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let board_id: String = _request.match_info().query("session_id").parse().unwrap();
+    let board_name: String = _request.match_info().query("filename").parse().unwrap();
+
+    let versions = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        board_versions::list_versions(&connection, board_id.as_str(), board_name.as_str())
+    })
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?;
+
+    let entries: Vec<VersionEntry> = versions.iter().map(VersionEntry::from).collect();
+    let json_response = serde_json::to_string(&entries)?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(json_response))
 }
 
-pub async fn fetch_program_content(_request: HttpRequest) -> Result<NamedFile, Error> {
+pub async fn fetch_board_version(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let board_id: String = _request.match_info().query("session_id").parse().unwrap();
+    let board_name: String = _request.match_info().query("filename").parse().unwrap();
+    let version: i32 = _request.match_info().query("version").parse().map_err(|_| actix_web::error::ErrorNotFound(VERSION_NOT_FOUND))?;
+
+    let file_path = format!("{}/{}/{}/{}", board_id, VERSIONS_DIR, board_name, version_file_name(board_name.as_str(), version));
+
+    let db = ctx.clone();
+    let stored = web::block(move || {
+        let connection = checkout(&db.db)?;
+        board_versions::find_version(&connection, board_id.as_str(), board_name.as_str(), version)
+    })
+    .await
+    .map_err(|_| actix_web::error::ErrorNotFound(VERSION_NOT_FOUND))?;
+
+    let file = open_stored(PathBuf::from(stored.file_path)).await?;
+
+    audited(&_request, ctx, file, AssetClass::Boards, file_path, accessor_of(&_request)).await
+}
+
+pub async fn fetch_program_content(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
     let program_fuzzy_id: PathBuf = _request.match_info().query("program_fuzzy_id").parse().unwrap();
     let purpose: PathBuf = _request.match_info().query("purpose").parse().unwrap();
     let asset_name: PathBuf = _request.match_info().query("filename").parse().unwrap();
 
-    let mut file_name: PathBuf = PathBuf::from(PROGRAM_ASSET_DIR);
-    file_name.push(program_fuzzy_id);
-    file_name.push(purpose);
-    file_name.push(asset_name);
+    let mut relative: PathBuf = program_fuzzy_id;
+    relative.push(purpose);
+    relative.push(asset_name);
+
+    let mut file_name: PathBuf = storage().dir(Area::Programs);
+    file_name.push(&relative);
+
+    let file = open_stored(file_name).await?;
+    let file_path = relative.to_string_lossy().into_owned();
+
+    audited(&_request, ctx, file, AssetClass::Programs, file_path, accessor_of(&_request)).await
+}
 
-    Ok(NamedFile::open(file_name)?)
+/**
+ * The thumbnail of a program content, of the size in the query string. Until it
+ * is made, and for the contents that are not images, the original is served.
+ */
+pub async fn fetch_program_thumbnail(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let program_fuzzy_id: PathBuf = _request.match_info().query("program_fuzzy_id").parse().unwrap();
+    let purpose: PathBuf = _request.match_info().query("purpose").parse().unwrap();
+    let asset_name: PathBuf = _request.match_info().query("filename").parse().unwrap();
+
+    let mut relative: PathBuf = program_fuzzy_id;
+    relative.push(purpose);
+    relative.push(asset_name);
+
+    thumbnail(&_request, ctx, storage().dir(Area::Programs), relative, AssetClass::Programs).await
 }
 
-pub async fn fetch_platform_content(_request: HttpRequest) -> Result<NamedFile, Error> {
+pub async fn fetch_platform_content(_request: HttpRequest) -> Result<HttpResponse, Error> {
     let asset_name: PathBuf = _request.match_info().query("filename").parse().unwrap();
 
-    let mut file_name: PathBuf = PathBuf::from(PLATFORM_ASSET_DIR);
+    let mut file_name: PathBuf = storage().dir(Area::Platform);
     file_name.push(asset_name);
 
-    Ok(NamedFile::open(file_name)?)
+    respond(open_stored(file_name).await?, AssetClass::Platform, &_request)
 }
 
-pub async fn manage_user_content(_request: HttpRequest, mut payload: Multipart) -> Result<HttpResponse, Error> {
+pub async fn manage_user_content(_request: HttpRequest, ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, mut payload: Multipart) -> Result<HttpResponse, Error> {
     let user_id: String = _request.match_info().query("user_id").parse().unwrap();
- 
+    let rule = UploadClass::Users.rule();
+
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_type = field.content_disposition().unwrap();
 
         let filename = content_type.get_name().unwrap();
 
         // Ensure to create a directory for the program content.
-        let dir_path = format!("{}/{}", USER_ASSET_DIR, user_id);
-        std::fs::create_dir_all(dir_path).unwrap();
-
-        let file_path = format!("{}/{}/{}", USER_ASSET_DIR, user_id, filename);
+        let dir_path = storage().dir(Area::Users).join(&user_id);
+        std::fs::create_dir_all(&dir_path).unwrap();
 
-        // File::create is blocking operation, use threadpool
-        let mut f = web::block(|| std::fs::File::create(file_path)).await.unwrap();
+        let file_path = dir_path.join(filename).to_string_lossy().into_owned();
 
-        // Field in turn is stream of *Bytes* object
-        while let Some(chunk) = field.next().await {
-            let data = chunk.unwrap();
-
-            // filesystem operations are blocking, we have to use threadpool
-            f = web::block(move || f.write_all(&data).map(|_| f)).await?;
+        let stored = store(&uploads, &ctx.db, &mut field, file_path.as_str(), filename, &rule).await?;
+        if let Stored::Rejected(rejection) = stored {
+            return rejected(rejection);
         }
+
+        make_thumbnails(&uploads, &stored, file_path.as_str()).await?;
     }
 
     Ok(HttpResponse::Ok().body("Ok"))
 }
 
-pub async fn fetch_user_content(_request: HttpRequest) -> Result<NamedFile, Error> {
+pub async fn fetch_user_thumbnail(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
     let user_id: PathBuf = _request.match_info().query("user_id").parse().unwrap();
     let asset_name: PathBuf = _request.match_info().query("filename").parse().unwrap();
 
-    let mut file_name: PathBuf = PathBuf::from(USER_ASSET_DIR);
-    file_name.push(user_id);
-    file_name.push(asset_name);
+    let mut relative: PathBuf = user_id;
+    relative.push(asset_name);
+
+    thumbnail(&_request, ctx, storage().dir(Area::Users), relative, AssetClass::Users).await
+}
+
+#[derive(Deserialize)]
+struct ThumbnailQuery {
+    size: Option<u32>,
+}
+
+async fn thumbnail(request: &HttpRequest, ctx: web::Data<DBContext>, asset_dir: PathBuf, original: PathBuf, class: AssetClass) -> Result<HttpResponse, Error> {
+    let requested = web::Query::<ThumbnailQuery>::from_query(request.query_string()).ok().and_then(|query| query.into_inner().size);
+
+    let thumb = match thumbnails::thumbnail_path(&original, thumbnails::fitting_size(requested)) {
+        Some(value) => open_stored(asset_dir.join(&value)).await.ok().map(|file| (file, value)),
+        None => None,
+    };
+
+    let (file, relative) = match thumb {
+        Some(value) => value,
+        None => (open_stored(asset_dir.join(&original)).await?, original),
+    };
+
+    let file_path = relative.to_string_lossy().into_owned();
+
+    audited(request, ctx, file, class, file_path, accessor_of(request)).await
+}
 
-    Ok(NamedFile::open(file_name)?)
+pub async fn fetch_user_content(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let user_id: PathBuf = _request.match_info().query("user_id").parse().unwrap();
+    let asset_name: PathBuf = _request.match_info().query("filename").parse().unwrap();
+
+    let mut relative: PathBuf = user_id;
+    relative.push(asset_name);
+
+    let mut file_name: PathBuf = storage().dir(Area::Users);
+    file_name.push(&relative);
+
+    let file = open_stored(file_name).await?;
+    let file_path = relative.to_string_lossy().into_owned();
+
+    audited(&_request, ctx, file, AssetClass::Users, file_path, accessor_of(&_request)).await
+}
+
+/**
+ * The archive of the data of the user, signed in with the basic credentials as
+ * for the timeline. A copy is kept among the contents of the user.
+ */
+pub async fn export_my_data(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let credentials = _request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(LoginRequest::from_basic);
+
+    let credentials = match credentials {
+        Some(value) => value,
+        None => return Ok(HttpResponse::Unauthorized().header(header::WWW_AUTHENTICATE, "Basic realm=\"ferris\"").finish()),
+    };
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        let user = authenticate(&connection, credentials)?;
+        data_exports::export_my_data(&connection, &user)
+    })
+    .await;
+
+    let archive = match result {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::Unauthorized().header(header::WWW_AUTHENTICATE, "Basic realm=\"ferris\"").body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    let content = std::fs::read(&archive)?;
+    let file_name = archive.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name))
+        .body(content))
 }
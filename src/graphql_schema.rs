@@ -1,50 +1,249 @@
+use diesel::MysqlConnection;
 use juniper::{FieldResult, RootNode};
+use tracing::Span;
 
-use crate::db_manager::MySqlConnectionPool;
+use crate::db_manager::{LazyConnection, ReadWritePool, RequestConnection};
+use crate::loaders::Loaders;
+use crate::feed_events::FeedEvents;
+use crate::field_usage::FieldUsage;
+use crate::session_events::{SessionEvent, SessionEvents};
 
 use crate::models::abstract_tasks::{AbstractTask, AbstractTaskCriteria, NewAbstractTaskRequest};
+use crate::models::admin::{BlockUserRequest, ForceDeactivateRequest, SystemStats, UserFilter};
+use crate::models::data_fixes::{FixPreview, ReassignNoteAuthorRequest, RelinkEnrollmentRequest, SwapSessionDatesRequest};
+use crate::models::anonymizer::AnonymizeRequest;
+use crate::models::agreements::{AcceptAgreementRequest, Agreement, AgreementStatus, NewAgreementRequest};
+use crate::models::audit_events::{AuditEntry, AuditTrailCriteria};
+use crate::models::board_annotations::{AnnotationCriteria, BoardAnnotation, SaveAnnotationRequest};
+use crate::models::board_versions::BoardVersion;
+use crate::models::coach_availability::{Availability, AvailabilityRequest, Slot};
 use crate::models::coach_members::{get_coach_members, CoachCriteria, MemberRow};
-use crate::models::conferences::{Conference, MemberRequest, NewConferenceRequest};
+use crate::models::coach_onboarding::{OnboardingChecklist, PaymentDetailsRequest};
+use crate::models::coach_profiles::{CoachProfile, CoachProfileRequest, DirectoryCriteria, DirectoryPage};
+use crate::models::coach_stats::{CoachStats, CoachStatsCriteria};
+use crate::models::conferences::{Conference, ConferenceMembers, FollowupRequest, Followups, MemberRequest, NewConferenceRequest};
 use crate::models::correspondences::Mailable;
-use crate::models::discussion_queue::PendingFeed;
+use crate::models::coupons::{Coupon, NewCouponRequest};
+use crate::models::discussion_queue::{PendingFeed, ReadAllRequest, ReadDiscussionRequest};
 use crate::models::discussions::{Discussion, DiscussionCriteria, NewDiscussionRequest};
-use crate::models::enrollments::{Enrollment, EnrollmentCriteria, ManagedEnrollmentRequest, NewEnrollmentRequest, PlanCriteria};
-use crate::models::master_plans::{MasterPlan, MasterPlanCriteria, NewMasterPlanRequest, UpdateMasterPlanRequest};
+use crate::models::enrollment_coaches::{EnrollmentCoach, EnrollmentCoachRequest};
+use crate::models::enrollment_handovers::{EnrollmentHandover, TransferEnrollmentRequest};
+use crate::models::enrollments::{CancelEnrollmentRequest, Enrollment, EnrollmentCriteria, ManagedEnrollmentRequest, NewEnrollmentRequest, PlanCriteria};
+use crate::models::fee_schedules::{EarningsCriteria, EarningsStatement, FeeScheduleView, NewFeeScheduleRequest};
+use crate::models::field_usage::{FieldReport, FieldUsageCriteria};
+use crate::models::file_access_log::{FileAccess, FileAccessCriteria};
+use crate::models::goal_boards::{
+    GoalBoard, GoalBoardCriteria, GoalBoardEvent, GoalCardCriteria, GoalCardView, GoalCommentRequest, NewGoalCardRequest, UpdateGoalCardRequest, CARD_ADDED, CARD_COMMENTED, CARD_DELETED,
+    CARD_UPDATED,
+};
+use crate::models::guest_links::{GuestAccess, GuestLink, GuestLinkCriteria, NewGuestLinkRequest, RevokeGuestLinkRequest};
+use crate::models::invoices::Invoice;
+use crate::models::late_policies::{AbsenceRequest, ExtensionCriteria, LatePolicy, LatePolicyRequest, MemberAbsence, TaskExtension};
+use crate::models::master_plans::{MasterPlan, MasterPlanCriteria, NewMasterPlanRequest, SpawnPlanRequest, UpdateMasterPlanRequest};
 use crate::models::master_tasks::{MasterTask, MasterTaskCriteria, NewMasterTaskRequest, UpdateMasterTaskRequest};
-use crate::models::notes::{NewNoteRequest, Note, NoteCriteria};
+use crate::models::member_weeks::MemberWeek;
+use crate::models::notes::{NewNoteRequest, Note, NoteCriteria, NoteFileCriteria, SessionFile};
+use crate::models::notifications::{MarkReadRequest, Notification, NotificationCriteria};
 use crate::models::objectives::{NewObjectiveRequest, Objective, UpdateObjectiveRequest};
-use crate::models::observations::{NewObservationRequest, Observation, UpdateObservationRequest};
+use crate::models::observations::{ChangeObservationVisibilityRequest, NewObservationRequest, Observation, UpdateObservationRequest};
 use crate::models::options::{Constraint, NewOptionRequest, UpdateOptionRequest};
-use crate::models::programs::{AssociateCoachRequest, ChangeProgramStateRequest, NewProgramRequest, Program, ProgramCoach};
-use crate::models::sessions::{ChangeSessionStateRequest, NewSessionRequest, Session};
-use crate::models::tasks::{ChangeCoachTaskStateRequest, ChangeMemberTaskStateRequest, NewTaskRequest, Task, UpdateClosingNoteRequest, UpdateResponseRequest, UpdateTaskRequest};
+use crate::models::organizations::{NewOrganizationRequest, Organization, OrganizationMemberRequest, OrganizationReport, OrganizationReportCriteria, SchedulingRulesRequest};
+use crate::models::password_resets::{ConfirmPasswordResetRequest, PasswordResetRequest};
+use crate::models::payments::{ConfirmPaymentRequest, Payment, PaymentIntentRequest};
+use crate::models::program_announcements::{Announcement, AnnouncementCriteria, AnnouncementRequest, DeleteAnnouncementRequest, UpdateAnnouncementRequest};
+use crate::models::program_faqs::{AnswerQuestionRequest, AskQuestionRequest, FaqCriteria, NewFaqRequest, ProgramFaq, ProgramQuestion, QuestionCriteria, UpdateFaqRequest};
+use crate::models::platform_banners::{BannerCriteria, BannerRequest, DismissBannerRequest, PlatformBanner, UpdateBannerRequest};
+use crate::models::program_requests::{AcceptOfferRequest, MakeOfferRequest, ProgramOffer, ProgramRequest, RaiseProgramRequest, RequestRow, WithdrawProgramRequest};
+use crate::models::program_reviews::{ModerateReviewRequest, NewReviewRequest, ProgramReview, ReviewCriteria};
+use crate::models::program_waitlists::{CapacityRequest, ProgramWaitlist, PromoteRequest, WaitingMember, WaitlistRequest};
+use crate::models::programs::{AssociateCoachRequest, ChangeProgramStateRequest, NewProgramRequest, Program, ProgramCoach, ProgramPriceRequest, ProgramReviewRequest, ProgramTagsRequest};
+use crate::models::recording_consents::{AttachRecordingRequest, ConferenceRecording, ConsentCriteria, ConsentRequest, ConsentSheet, RecordingConsent};
+use crate::models::saved_filters::{NewSavedFilterRequest, SavedFilter, SavedFilterCriteria, UpdateSavedFilterRequest};
+use crate::models::session_cancellations::{BulkCancelRequest, CancellationPreview};
+use crate::models::session_merges::{MergePreview, MergeSessionsRequest};
+use crate::models::session_objectives::{EnrollmentProgress, ProgressCriteria, TagSessionRequest};
+use crate::models::session_scratchpads::{SaveScratchpadRequest, Scratchpad, ScratchpadCriteria, SessionScratchpad};
+use crate::models::session_summaries::{SaveSessionSummaryRequest, SessionSummary};
+use crate::models::session_visits::{Attendee, SessionVisit, VisitRequest};
+use crate::models::sessions::{ChangeSessionStateRequest, NewSessionRequest, Session, TargetState};
+use crate::models::support_tickets::{RaiseTicketRequest, SupportTicket, TicketActionRequest, TicketCriteria, TicketReplyRequest, TicketThread};
+use crate::models::tasks::{ChangeCoachTaskStateRequest, ChangeMemberTaskStateRequest, CreateTasksFromSummaryRequest, NewTaskRequest, RescheduleCascadeRequest, Task, UpdateClosingNoteRequest, UpdateResponseRequest, UpdateTaskRequest};
 use crate::models::user_artifacts::{get_boards, get_enrollment_notes, BoardRow, NoteRow};
-use crate::models::user_events::{get_events,get_plan_events, get_to_dos, EventCriteria, EventRow, PlanRow, ToDo};
+use crate::models::user_events::{get_plan_events, get_to_dos, EventCriteria, EventRow, PlanRow, ToDo};
 use crate::models::session_users::{get_people,SessionCriteria, SessionPeople};
-use crate::models::user_programs::{get_programs, ProgramCriteria, ProgramRow};
-use crate::models::users::{LoginRequest, Registration, ResetPasswordRequest, User, UserCriteria};
+use crate::models::user_programs::{ProgramCriteria, ProgramRow};
+use crate::models::user_locales::{LocaleBundle, LocaleRequest};
+use crate::models::user_profiles::{UserProfile, UserProfileRequest};
+use crate::models::users::{ChangeAccountStateRequest, DeactivateAccountRequest, LoginRequest, Registration, ResetPasswordRequest, SignedIn, User, UserCriteria};
+use crate::models::webhooks::{DeliveryCriteria, WebhookCriteria, WebhookDelivery, WebhookRequest, WebhookSubscription};
 
 use crate::services::abstract_tasks::{create_abstract_task, get_abstract_tasks};
-use crate::services::conferences::{create_conference, manage_members};
+use crate::services::anonymizer::anonymize;
+use crate::services::agreements::{accept_agreement, get_agreement_status, publish_agreement};
+use crate::services::admin::{block_user, force_deactivate_program, get_system_stats, get_users, publish_program};
+use crate::services::audit::get_audit_trail;
+use crate::services::board_annotations::{delete_annotation, save_annotation};
+use crate::services::board_versions::get_board_versions;
+use crate::services::coach_availability::{get_availability, get_available_slots, set_availability};
+use crate::services::coach_onboarding::{get_onboarding, save_payment_details};
+use crate::services::coach_profiles::{get_coach_directory, get_coach_profile, save_coach_profile};
+use crate::services::coach_stats::get_coach_stats;
+use crate::services::conferences::{create_conference, manage_members, spawn_followups};
 use crate::services::correspondences::sendable_mails;
-use crate::services::discussions::{create_new_discussion, get_discussions, get_pending_discussions};
-use crate::services::enrollments::{create_managed_enrollment, create_new_enrollment, get_active_enrollments};
-use crate::services::master_plans::{create_master_plan, get_master_plans, update_master_plan};
+use crate::services::coupons::{create_coupon, get_coupons};
+use crate::services::data_fixes::{reassign_note_author, relink_enrollment, swap_session_dates};
+use crate::services::discussions::{create_new_discussion, get_discussions, get_feed_counts, get_pending_discussions, mark_all_read, mark_discussion_read};
+use crate::services::enrollment_coaches::{add_enrollment_coach, remove_enrollment_coach};
+use crate::services::enrollments::{cancel_enrollment, create_managed_enrollment, create_new_enrollment, get_active_enrollments, get_handovers, transfer_enrollment};
+use crate::services::facades::Services;
+use crate::services::fee_schedules::{compute_earnings, get_fee_schedules, publish_fee_schedule};
+use crate::services::field_usage::get_usage_report;
+use crate::services::file_access_log::get_file_access_log;
+use crate::services::goal_boards::{comment_card, create_card, delete_card, get_goal_board, member_and_coach, update_card};
+use crate::services::guest_links::{create_guest_link, get_guest_links, redeem_guest_link, revoke_guest_link};
+use crate::services::invoices::get_invoices;
+use crate::services::late_policies::{get_late_policy, get_task_extensions, record_absence, save_late_policy};
+use crate::services::master_plans::{create_master_plan, get_master_plans, spawn_plan, update_master_plan};
 use crate::services::master_tasks::{create_master_task, get_master_tasks, update_master_task};
-use crate::services::notes::{create_new_note, get_notes};
+use crate::services::member_weeks::get_my_week;
+use crate::services::notes::{create_new_note, get_note_files, get_notes};
+use crate::services::notifications::{get_notifications, mark_read};
 use crate::services::objectives::{create_objective, get_objectives, update_objective};
-use crate::services::observations::{create_observation, get_observations, update_observation};
+use crate::services::observations::{change_observation_visibility, create_observation, get_observations, update_observation};
 use crate::services::options::{create_option, get_options, update_option};
-use crate::services::programs::{associate_coach, change_program_state, create_new_program, get_peer_coaches};
-use crate::services::sessions::{change_session_state, create_session, find};
-use crate::services::tasks::{change_coach_task_state, change_member_task_state, create_task, get_tasks, update_closing_notes, update_response, update_task};
-use crate::services::users::{authenticate, register, reset_password};
-
-use crate::commons::chassis::{mutation_error, query_error, service_error, MutationResult, QueryError, QueryResult};
+use crate::services::organizations::{create_organization, get_organization_report, save_organization_member, set_scheduling_rules};
+use crate::services::password_resets::{confirm_password_reset, request_password_reset};
+use crate::services::payments::{confirm_payment, create_payment_intent};
+use crate::services::program_announcements::{create_announcement, delete_announcement, get_announcements, update_announcement};
+use crate::services::program_faqs::{answer_question, ask_question, create_faq, delete_faq, get_faqs, get_questions, update_faq};
+use crate::services::platform_banners::{create_banner, delete_banner, dismiss_banner, get_active_banners, get_banners, update_banner};
+use crate::services::program_requests::{accept_offer, get_open_requests, get_program_requests, make_offer, raise_program_request, withdraw_program_request};
+use crate::services::program_reviews::{create_review, get_reviews, moderate_review};
+use crate::services::program_waitlists::{get_waitlist, join_waitlist, promote_from_waitlist, set_capacity};
+use crate::services::programs::{manage_tags, retire_program, set_price, submit_program};
+use crate::services::recording_consents::{attach_recording, get_consents, give_consent};
+use crate::services::saved_filters::{create_saved_filter, delete_saved_filter, get_filtered_members, get_filtered_tasks, get_saved_filters, update_saved_filter};
+use crate::services::session_cancellations::cancel_sessions;
+use crate::services::session_merges::merge_sessions;
+use crate::services::session_objectives::{get_enrollment_progress, tag_session};
+use crate::services::session_scratchpads::{get_scratchpads, save_scratchpad};
+use crate::services::session_summaries::save_session_summary;
+use crate::services::session_visits::{get_attendance, leave_session, record_session_visit};
+use crate::services::support_tickets::{act_on_ticket, get_ticket, get_tickets, raise_ticket, reply_ticket};
+use crate::services::user_locales::save_locale;
+use crate::services::user_profiles::{get_profile, update_profile};
+use crate::services::users::{authenticate, change_account_state, deactivate_account, gate_active, register, reset_password};
+use crate::services::webhooks::{create_webhook, delete_webhook, get_webhook_deliveries, get_webhooks, sendable_webhooks};
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::chassis::{criteria_error, mutation_error, page_error, query_error, service_error, MutationResult, PageRequest, PagedResult, QueryError, QueryResult, Window};
+use crate::commons::ids::{ProgramId, SessionId, UserId};
+use crate::commons::session_tokens;
 
 #[derive(Clone)]
 pub struct DBContext {
-    pub db: MySqlConnectionPool,
+    pub db: ReadWritePool,
+    pub reads_replica: bool,
+    pub usage: FieldUsage,
+    pub events: SessionEvents,
+    pub feeds: FeedEvents,
+    pub services: Services,
+    pub caller: Option<UserId>,
+    pub connection: RequestConnection,
+    pub loaders: Loaders,
+    pub span: Span,
+}
+
+// The rows of a page load what they show beside them through the loaders of the context; see loaders.
+impl juniper::Context for DBContext {}
+
+impl DBContext {
+    /**
+     * The context of a request, knowing the signed in user who sent it.
+     */
+    pub fn of_caller(&self, caller: Option<UserId>) -> DBContext {
+        DBContext { caller, ..self.clone() }
+    }
+
+    /**
+     * The context carrying the span of the request, entered while the resolvers run.
+     */
+    pub fn within(self, span: Span) -> DBContext {
+        DBContext { span, ..self }
+    }
+
+    /**
+     * The context of a request running a query, whose resolvers read from the replica.
+     */
+    pub fn on_replica(self) -> DBContext {
+        DBContext { reads_replica: true, ..self }
+    }
+
+    /**
+     * The context of a query field that writes too, on a connection of the primary.
+     */
+    pub fn on_primary(&self) -> DBContext {
+        DBContext { reads_replica: false, ..self.clone() }
+    }
+
+    pub fn caller(&self) -> Option<&UserId> {
+        self.caller.as_ref()
+    }
+
+    /**
+     * The connection of the request, taken from the pool only when first asked for;
+     * DB_UNAVAILABLE when the pool has none to give.
+     */
+    pub fn connection(&self) -> Result<LazyConnection<'_>, &'static str> {
+        let pool = if self.reads_replica { self.db.read() } else { self.db.primary() };
+
+        self.connection.hold(pool)
+    }
+
+    /**
+     * The counts of the users with a feed socket open are pushed again; see feed_events.
+     */
+    pub fn push_feed_counts(&self, connection: &MysqlConnection, user_ids: &[&str]) {
+        for user_id in user_ids.iter().filter(|user_id| self.feeds.follows(user_id)) {
+            match get_feed_counts(connection, user_id) {
+                Ok(counts) => self.feeds.publish(&counts),
+                Err(e) => tracing::warn!(user_id, error = e, "unable to push the feed counts"),
+            }
+        }
+    }
+
+    /**
+     * The people of the session, or of its conference, may see their counts change.
+     * They are read on the connection the resolver holds, as the request has no other.
+     */
+    pub fn push_session_feeds(&self, connection: &MysqlConnection, session: &Session) {
+        if self.feeds.is_idle() {
+            return;
+        }
+
+        if let Ok(people) = crate::services::sessions::users_of(connection, session) {
+            let user_ids: Vec<&str> = people.iter().map(|person| person.user_id.as_str()).collect();
+            self.push_feed_counts(connection, &user_ids);
+        }
+    }
+
+    /**
+     * The coach and the member of the enrollment are told of the change of a card of its board.
+     */
+    pub fn push_goal_board(&self, connection: &MysqlConnection, event: &GoalBoardEvent) {
+        if self.feeds.is_idle() {
+            return;
+        }
+
+        match member_and_coach(connection, event.enrollment_id.as_str()) {
+            Ok((member_id, coach_id)) => {
+                for user_id in [member_id, coach_id].iter().filter(|user_id| self.feeds.follows(user_id.as_str())) {
+                    self.feeds.publish_board(user_id.as_str(), event);
+                }
+            }
+            Err(e) => tracing::warn!(enrollment_id = event.enrollment_id.as_str(), error = e, "unable to push the goal board"),
+        }
+    }
 }
 
 
@@ -52,22 +251,42 @@ pub struct QueryRoot;
 
 #[juniper::object(Context = DBContext,description="Graph Query Root")]
 impl QueryRoot {
-    #[graphql(description = "Authenticate a user with email and password")]
-    fn authenticate(context: &DBContext, request: LoginRequest) -> FieldResult<User> {
-        let connection = context.db.get().unwrap();
+    #[graphql(description = "Authenticate a user with email and password, and sign the session to send with the later requests")]
+    fn authenticate(context: &DBContext, request: LoginRequest) -> FieldResult<SignedIn> {
+        let connection = context.connection()?;
         let user = authenticate(&connection, request)?;
-        Ok(user)
+        let token = session_tokens::issue(&user.id)?;
+        Ok(SignedIn { user, token })
+    }
+
+    #[graphql(description = "Get the latest required agreement and whether the user has accepted it")]
+    fn get_agreement_status(context: &DBContext, user_id: UserId) -> QueryResult<AgreementStatus> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_agreement_status(&connection, &user_id);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
     }
 
     #[graphql(description = "Return the basic information of a user")]
     fn get_user(context: &DBContext, criteria: UserCriteria) -> FieldResult<User> {
-        let connection = context.db.get().unwrap();
-        let user = crate::services::users::find(&connection, &criteria.id)?;
+        let connection = context.connection()?;
+        let user = crate::services::users::find_any(&connection, &criteria.id)?;
         Ok(user)
     }
 
     fn get_pending_discussions(context: &DBContext, criteria: UserCriteria) -> QueryResult<Vec<PendingFeed>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_pending_discussions(&connection, &criteria);
 
         match result {
@@ -76,31 +295,41 @@ impl QueryRoot {
         }
     }
 
-    #[graphql(description = "Get Programs of a Coach Or Member Or Latest 10.")]
-    fn get_programs(context: &DBContext, criteria: ProgramCriteria) -> QueryResult<Vec<ProgramRow>> {
-        let connection = context.db.get().unwrap();
-        let result = get_programs(&connection, &criteria);
+    #[graphql(description = "Get Programs of a Coach Or Member Or the Latest, a page at a time.")]
+    fn get_programs(context: &DBContext, criteria: ProgramCriteria, page: Option<PageRequest>) -> PagedResult<ProgramRow> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
+
+        let result = context.services.programs.get_programs(context, &criteria, &window);
 
         match result {
-            Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Ok(value) => {
+                context.loaders.peer_coaches.prime(value.items.iter().map(|row| row.program.coalesce_parent_id().to_string()));
+                PagedResult(Ok(value))
+            }
+            Err(e) => PagedResult(Err(e)),
         }
     }
 
     #[graphql(description = "Get the list of coaches associated with a Program through its parent program.")]
     fn get_program_coaches(context: &DBContext, program_id: String) -> QueryResult<Vec<ProgramCoach>> {
-        let connection = context.db.get().unwrap();
-        let result = get_peer_coaches(&connection, program_id.as_str());
+        let result = context.services.programs.get_program_coaches(context, program_id.as_str());
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Err(e) => QueryResult(Err(e)),
         }
     }
 
     #[graphql(description = "Get The List of Abstract Tasks of a Coach")]
     fn get_abstract_tasks(context: &DBContext, criteria: AbstractTaskCriteria) -> QueryResult<Vec<AbstractTask>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_abstract_tasks(&connection, &criteria);
 
         match result {
@@ -111,7 +340,11 @@ impl QueryRoot {
 
     #[graphql(description = "Get The List of Master Plans of a Coach")]
     fn get_master_plans(context: &DBContext, criteria: MasterPlanCriteria) -> QueryResult<Vec<MasterPlan>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_master_plans(&connection, &criteria);
 
         match result {
@@ -122,7 +355,11 @@ impl QueryRoot {
 
     #[graphql(description = "Get the list of tasks for an Enrollment")]
     fn get_master_tasks(context: &DBContext, criteria: MasterTaskCriteria) -> QueryResult<Vec<MasterTask>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_master_tasks(&connection, criteria);
 
         match result {
@@ -132,36 +369,55 @@ impl QueryRoot {
     }
 
     #[graphql(description = "Get the list of members enrolled into a Program")]
-    fn get_enrollments(context: &DBContext, criteria: EnrollmentCriteria) -> Vec<User> {
-        let connection = context.db.get().unwrap();
-        get_active_enrollments(&connection, criteria).unwrap()
+    fn get_enrollments(context: &DBContext, criteria: EnrollmentCriteria) -> FieldResult<Vec<User>> {
+        let connection = context.connection()?;
+        let members = get_active_enrollments(&connection, criteria)?;
+        Ok(members)
     }
 
     #[graphql(description = "Get the list of members enrolled into Programs offered by a Coach")]
     fn get_coach_members(context: &DBContext, criteria: CoachCriteria) -> QueryResult<Vec<MemberRow>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_coach_members(&connection, criteria);
 
         match result {
-            Ok(value) => QueryResult(Ok(value)),
+            Ok(value) => {
+                context.loaders.co_coaches.prime(value.iter().map(|row| row.enrollment.id.to_string()));
+                QueryResult(Ok(value))
+            }
             Err(e) => query_error(e),
         }
     }
 
-    #[graphql(description = "Get the Session Events for a User, during a period")]
-    fn get_events(context: &DBContext, criteria: EventCriteria) -> QueryResult<Vec<EventRow>> {
-        let connection = context.db.get().unwrap();
-        let result = get_events(&connection, criteria);
+    #[graphql(description = "Get the Session Events for a User, during a period, a page at a time")]
+    fn get_events(context: &DBContext, criteria: EventCriteria, page: Option<PageRequest>) -> PagedResult<EventRow> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
+
+        let result = context.services.sessions.get_events(context, criteria, &window);
 
         match result {
-            Ok(value) => QueryResult(Ok(value)),
-            Err(e) => QueryResult(Err(e)),
+            Ok(value) => {
+                context.loaders.session_people.prime(value.items.iter().map(|row| row.session.id.to_string()));
+                PagedResult(Ok(value))
+            }
+            Err(e) => PagedResult(Err(e)),
         }
     }
 
     #[graphql(description = "Get the list of Plan Events for a User")]
     fn get_plan_events(context: &DBContext, criteria: EventCriteria) -> QueryResult<Vec<PlanRow>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_plan_events(&connection, criteria);
 
         match result {
@@ -172,7 +428,11 @@ impl QueryRoot {
 
     #[graphql(description = "Get the list of events due for a user")]
     fn get_due(context: &DBContext, criteria: EventCriteria) -> QueryResult<Vec<ToDo>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_to_dos(&connection, criteria);
 
         match result {
@@ -183,7 +443,11 @@ impl QueryRoot {
 
     #[graphql(description = "Get the list of objectives for an Enrollment")]
     fn get_objectives(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<Objective>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_objectives(&connection, criteria);
 
         match result {
@@ -194,7 +458,11 @@ impl QueryRoot {
 
     #[graphql(description = "Get the list of options for an Enrollment")]
     fn get_options(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<Constraint>> {
-        let connection = context.db.get().unwrap();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
         let result = get_options(&connection, criteria);
 
         match result {
@@ -203,32 +471,39 @@ impl QueryRoot {
         }
     }
 
-    #[graphql(description = "Get the list of observations for an Enrollment")]
+    #[graphql(description = "Get the list of observations for an Enrollment that the caller may read")]
     fn get_observations(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<Observation>> {
-        let connection = context.db.get().unwrap();
-        let result = get_observations(&connection, criteria);
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_observations(&connection, context.caller(), &criteria);
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Err(e) => criteria_error(e),
         }
     }
 
     #[graphql(description = "Get the list of tasks for an Enrollment")]
     fn get_tasks(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<Task>> {
-        let connection = context.db.get().unwrap();
-        let result = get_tasks(&connection, criteria);
+        let result = context.services.tasks.get_tasks(context, criteria);
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Err(e) => QueryResult(Err(e)),
         }
     }
 
-    #[graphql(description = "Get the list of notes for a SessionUser")]
-    fn get_notes(context: &DBContext, criteria: NoteCriteria) -> QueryResult<Vec<Note>> {
-        let connection = context.db.get().unwrap();
-        let result = get_notes(&connection, criteria);
+    #[graphql(description = "Get the filters saved by a Coach")]
+    fn get_saved_filters(context: &DBContext, coach_id: String) -> QueryResult<Vec<SavedFilter>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_saved_filters(&connection, coach_id.as_str());
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
@@ -236,454 +511,3179 @@ impl QueryRoot {
         }
     }
 
-    fn get_discussions(context: &DBContext, criteria: DiscussionCriteria) -> QueryResult<Vec<Discussion>> {
-        let connection = context.db.get().unwrap();
-        let result = get_discussions(&connection, criteria);
+    #[graphql(description = "Get the members of a Coach by applying a saved filter, a page at a time")]
+    fn get_filtered_members(context: &DBContext, criteria: SavedFilterCriteria, page: Option<PageRequest>) -> PagedResult<MemberRow> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return page_error(e),
+        };
+
+        let result = get_filtered_members(&connection, &criteria, &window);
 
         match result {
-            Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Ok(value) => {
+                context.loaders.co_coaches.prime(value.items.iter().map(|row| row.enrollment.id.to_string()));
+                PagedResult(Ok(value))
+            }
+            Err(e) => page_error(e),
         }
     }
 
-    #[graphql(description = "Get the list of notes of an enrollment. Hence both the member and the coach notes directly to the member.")]
-    fn get_enrollment_notes(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<NoteRow>> {
-        let connection = context.db.get().unwrap();
-        let result = get_enrollment_notes(&connection, criteria);
+    #[graphql(description = "Get the tasks of an Enrollment by applying a saved filter, a page at a time")]
+    fn get_filtered_tasks(context: &DBContext, criteria: SavedFilterCriteria, page: Option<PageRequest>) -> PagedResult<Task> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return page_error(e),
+        };
+
+        let result = get_filtered_tasks(&connection, &criteria, &window);
 
         match result {
-            Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Ok(value) => PagedResult(Ok(value)),
+            Err(e) => page_error(e),
         }
     }
 
-    #[graphql(description = "Get the Session by its id")]
-    fn get_session(context: &DBContext, criteria: SessionCriteria) -> FieldResult<Session> {
-        let connection = context.db.get().unwrap();
-        let session = find(&connection, &criteria.id)?;
-        Ok(session)
-    }
+    #[graphql(description = "Get the onboarding steps of a coach still pending; the programs are published only after all of them are done")]
+    fn get_onboarding(context: &DBContext, coach_id: String) -> QueryResult<OnboardingChecklist> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-    #[graphql(description = "Get the People participating in an Event")]
-    fn get_session_users(context: &DBContext, criteria: SessionCriteria) -> QueryResult<Vec<SessionPeople>> {
-        let connection = context.db.get().unwrap();
-        let result = get_people(&connection, criteria);
+        let result = get_onboarding(&connection, coach_id.as_str());
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Err(e) => criteria_error(e),
         }
     }
 
-    #[graphql(description = "Top 3 mails marked as Pending")]
-    fn get_sendable_mails(context: &DBContext) -> QueryResult<Vec<Mailable>> {
-        let connection = context.db.get().unwrap();
-        let result = sendable_mails(&connection);
+    #[graphql(description = "Get the headline, the about, the experience and the social links of a user")]
+    fn get_profile(context: &DBContext, user_id: String) -> QueryResult<UserProfile> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_profile(&connection, user_id.as_str());
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Err(e) => criteria_error(e),
         }
     }
 
-    #[graphql(description = "Get the List of all the Boards of an enrolled member")]
-    fn get_boards(context: &DBContext, criteria: EventCriteria) -> QueryResult<Vec<BoardRow>> {
-        let connection = context.db.get().unwrap();
-        let result = get_boards(&connection, criteria);
+    #[graphql(description = "Get the directory profile of a coach with its completeness and the missing items")]
+    fn get_coach_profile(context: &DBContext, coach_id: String) -> QueryResult<CoachProfile> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_coach_profile(&connection, coach_id.as_str());
 
         match result {
             Ok(value) => QueryResult(Ok(value)),
-            Err(e) => query_error(e),
+            Err(e) => criteria_error(e),
         }
     }
-}
-
-pub struct MutationRoot;
 
-#[juniper::object(Context = DBContext)]
-impl MutationRoot {
-    fn create_user(context: &DBContext, registration: Registration) -> MutationResult<User> {
+    #[graphql(description = "Get the weekly windows of a coach and the exceptions to them")]
+    fn get_availability(context: &DBContext, coach_id: String) -> QueryResult<Availability> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = register(&connection, &registration);
+        let result = get_availability(&connection, coach_id.as_str());
 
         match result {
-            Ok(user) => MutationResult(Ok(user)),
-            Err(e) => MutationResult(Err(e.errors)),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn reset_password(context: &DBContext, request: ResetPasswordRequest) -> MutationResult<User> {
-        let errors = request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the free slots of a coach to book a session in, between two times like 2021-03-15T00:00:00Z up to 31 days apart")]
+    fn get_available_slots(context: &DBContext, coach_id: String, from: String, to: String) -> QueryResult<Vec<Slot>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = reset_password(&connection, &request);
+        let result = get_available_slots(&connection, coach_id.as_str(), from.as_str(), to.as_str());
 
         match result {
-            Ok(user) => MutationResult(Ok(user)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_abstract_task(context: &DBContext, request: NewAbstractTaskRequest) -> MutationResult<AbstractTask> {
-        let errors = request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get a page of the coaches who chose to be listed in the directory")]
+    fn get_coach_directory(context: &DBContext, criteria: DirectoryCriteria) -> QueryResult<DirectoryPage> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_abstract_task(&connection, &request);
+        let result = get_coach_directory(&connection, &criteria);
 
         match result {
-            Ok(abstract_task) => MutationResult(Ok(abstract_task)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_master_plan(context: &DBContext, request: NewMasterPlanRequest) -> MutationResult<MasterPlan> {
-        let errors = request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the daily activity of a coach from the summaries, for the dashboard")]
+    fn get_coach_stats(context: &DBContext, criteria: CoachStatsCriteria) -> QueryResult<CoachStats> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_master_plan(&connection, &request);
+        let result = get_coach_stats(&connection, &criteria);
 
         match result {
-            Ok(master_plan) => MutationResult(Ok(master_plan)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_master_task(context: &DBContext, new_master_task_request: NewMasterTaskRequest) -> MutationResult<MasterTask> {
-        let errors = new_master_task_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the platform fee schedules, the oldest first")]
+    fn get_fee_schedules(context: &DBContext) -> QueryResult<Vec<FeeScheduleView>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_master_task(&connection, &new_master_task_request);
+        let result = get_fee_schedules(&connection);
 
         match result {
-            Ok(master_task) => MutationResult(Ok(master_task)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn update_master_task(context: &DBContext, update_master_task_request: UpdateMasterTaskRequest) -> MutationResult<MasterTask> {
-        let errors = update_master_task_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Work out the share of a coach from the charges of a program, with the fees in effect at each charge")]
+    fn compute_earnings(context: &DBContext, criteria: EarningsCriteria) -> QueryResult<EarningsStatement> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = update_master_task(&connection, &update_master_task_request);
+        let result = compute_earnings(&connection, &criteria);
 
         match result {
-            Ok(task) => MutationResult(Ok(task)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn save_master_plan(context: &DBContext, request: UpdateMasterPlanRequest) -> MutationResult<String> {
-        let connection = context.db.get().unwrap();
-        let result = update_master_plan(&connection, &request);
+    #[graphql(description = "Get the FAQ of a program")]
+    fn get_program_faqs(context: &DBContext, program_id: String) -> QueryResult<Vec<ProgramFaq>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_faqs(&connection, program_id.as_str());
 
         match result {
-            Ok(value) => MutationResult(Ok(value)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
         }
     }
 
-    fn create_program(context: &DBContext, new_program_request: NewProgramRequest) -> MutationResult<Program> {
-        let errors = new_program_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
+    #[graphql(description = "Get the coupons of a program, the latest first; for its coach")]
+    fn get_coupons(context: &DBContext, program_id: ProgramId) -> QueryResult<Vec<Coupon>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(program_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
         }
 
-        let connection = context.db.get().unwrap();
-        let result = create_new_program(&connection, &new_program_request);
+        let result = get_coupons(&connection, &program_id);
 
         match result {
-            Ok(program) => MutationResult(Ok(program)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn associate_coach(context: &DBContext, request: AssociateCoachRequest) -> MutationResult<Program> {
-        let connection = context.db.get().unwrap();
-        let result = associate_coach(&connection, &request);
+    #[graphql(description = "Get the reviews of a program, the latest first; the hidden ones for its coach alone")]
+    fn get_program_reviews(context: &DBContext, criteria: ReviewCriteria) -> QueryResult<Vec<ProgramReview>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_reviews(&connection, &criteria);
 
         match result {
-            Ok(program) => MutationResult(Ok(program)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_enrollment(context: &DBContext, new_enrollment_request: NewEnrollmentRequest) -> MutationResult<Enrollment> {
-        let errors = new_enrollment_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
+    #[graphql(description = "Get the members waiting for a seat in a full program, first in the line first")]
+    fn get_waitlist(context: &DBContext, program_id: String) -> QueryResult<Vec<WaitingMember>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(program_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
         }
 
-        let connection = context.db.get().unwrap();
-        let result = create_new_enrollment(&connection, &new_enrollment_request);
+        let result = get_waitlist(&connection, &ProgramId::from(program_id));
 
         match result {
-            Ok(enrollment) => MutationResult(Ok(enrollment)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn managed_enrollment(context: &DBContext, managed_enrollment_request: ManagedEnrollmentRequest) -> MutationResult<Enrollment> {
-        let connection = context.db.get().unwrap();
-        let result = create_managed_enrollment(&connection, &managed_enrollment_request);
+    #[graphql(description = "Get the questions on a program; all for its coach, the own ones for the others")]
+    fn get_program_questions(context: &DBContext, criteria: QuestionCriteria) -> QueryResult<Vec<ProgramQuestion>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_questions(&connection, &criteria);
 
         match result {
-            Ok(enrollment) => MutationResult(Ok(enrollment)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_session(context: &DBContext, new_session_request: NewSessionRequest) -> MutationResult<Session> {
-        let errors = new_session_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the program requests of a member with the offers of the coaches")]
+    fn get_program_requests(context: &DBContext, member_id: String) -> QueryResult<Vec<RequestRow>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_session(&connection, &new_session_request);
+        let result = get_program_requests(&connection, member_id.as_str());
 
         match result {
-            Ok(session) => MutationResult(Ok(session)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_conference(context: &DBContext, new_conference_request: NewConferenceRequest) -> MutationResult<Conference> {
-        let errors = new_conference_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the open program requests that share a tag with the specialties of the coach")]
+    fn get_open_program_requests(context: &DBContext, coach_id: String) -> QueryResult<Vec<ProgramRequest>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_conference(&connection, &new_conference_request);
+        let result = get_open_requests(&connection, coach_id.as_str());
 
         match result {
-            Ok(conference) => MutationResult(Ok(conference)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn manage_conference(context: &DBContext, member_request: MemberRequest) -> MutationResult<Vec<String>> {
-        let connection = context.db.get().unwrap();
-        let result = manage_members(&connection, &member_request);
+    #[graphql(description = "Get the recording consents of the participants of a conference, for the hosting coach")]
+    fn get_recording_consents(context: &DBContext, criteria: ConsentCriteria) -> QueryResult<ConsentSheet> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_consents(&connection, &criteria);
 
         match result {
-            Ok(members) => MutationResult(Ok(members)),
-            Err(e) => service_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_objective(context: &DBContext, new_objective_request: NewObjectiveRequest) -> MutationResult<Objective> {
-        let errors = new_objective_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the use of the fields of the schema by the clients, for the admins deciding which deprecated fields to remove")]
+    fn get_field_usage_report(context: &DBContext, criteria: FieldUsageCriteria) -> QueryResult<Vec<FieldReport>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_objective(&connection, &new_objective_request);
+        let result = get_usage_report(&connection, context.caller(), &context.usage.catalog, &criteria);
 
         match result {
-            Ok(objective) => MutationResult(Ok(objective)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_option(context: &DBContext, new_option_request: NewOptionRequest) -> MutationResult<Constraint> {
-        let errors = new_option_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the users of the platform by type, state or a text in the name or the email, the latest to join first; for the admins")]
+    fn get_users(context: &DBContext, filter: UserFilter, page: Option<PageRequest>) -> PagedResult<User> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_option(&connection, &new_option_request);
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return page_error(e),
+        };
+
+        let result = get_users(&connection, context.caller(), &filter, &window);
 
         match result {
-            Ok(option) => MutationResult(Ok(option)),
-            Err(e) => mutation_error(e),
+            Ok(value) => PagedResult(Ok(value)),
+            Err(e) => page_error(e),
         }
     }
 
-    fn create_observation(context: &DBContext, new_observation_request: NewObservationRequest) -> MutationResult<Observation> {
-        let errors = new_observation_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the counts of the users, the programs, the enrollments and the sessions of the platform; for the admins")]
+    fn get_system_stats(context: &DBContext) -> QueryResult<SystemStats> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = create_observation(&connection, &new_observation_request);
+        let result = get_system_stats(&connection, context.caller());
 
         match result {
-            Ok(option) => MutationResult(Ok(option)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn update_observation(context: &DBContext, update_observation_request: UpdateObservationRequest) -> MutationResult<Observation> {
-        let errors = update_observation_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the downloads of a file, or of the files under a path, or by a user; the latest first, for the admins")]
+    fn get_file_access_log(context: &DBContext, criteria: FileAccessCriteria) -> QueryResult<Vec<FileAccess>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = update_observation(&connection, &update_observation_request);
+        let result = get_file_access_log(&connection, context.caller(), &criteria);
 
         match result {
-            Ok(obs) => MutationResult(Ok(obs)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn update_option(context: &DBContext, update_option_request: UpdateOptionRequest) -> MutationResult<Constraint> {
-        let errors = update_option_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the goal board of an enrollment, for its coach or member; poll the revision to follow the changes")]
+    fn get_goal_board(context: &DBContext, criteria: GoalBoardCriteria) -> QueryResult<GoalBoard> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = update_option(&connection, &update_option_request);
+        let result = get_goal_board(&connection, &criteria);
 
         match result {
-            Ok(option) => MutationResult(Ok(option)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn update_objective(context: &DBContext, update_objective_request: UpdateObjectiveRequest) -> MutationResult<Objective> {
-        let errors = update_objective_request.validate();
-        if !errors.is_empty() {
-            return MutationResult(Err(errors));
-        }
+    #[graphql(description = "Get the sessions that addressed each objective of an enrollment, for its coach or member, flagging the neglected ones")]
+    fn get_enrollment_progress(context: &DBContext, criteria: ProgressCriteria) -> QueryResult<EnrollmentProgress> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
 
-        let connection = context.db.get().unwrap();
-        let result = update_objective(&connection, &update_objective_request);
+        let result = get_enrollment_progress(&connection, &criteria);
 
         match result {
-            Ok(objective) => MutationResult(Ok(objective)),
-            Err(e) => mutation_error(e),
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
         }
     }
 
-    fn create_task(context: &DBContext, new_task_request: NewTaskRequest) -> MutationResult<Task> {
-        let errors = new_task_request.validate();
+    #[graphql(description = "Get how the late tasks of a program are extended")]
+    fn get_late_policy(context: &DBContext, program_id: String) -> QueryResult<LatePolicy> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_late_policy(&connection, program_id.as_str());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the extensions given to the late tasks of an enrollment, for its coach or member")]
+    fn get_task_extensions(context: &DBContext, criteria: ExtensionCriteria) -> QueryResult<Vec<TaskExtension>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_task_extensions(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the support tickets of the user, or every ticket for an admin, the latest updated first")]
+    fn get_tickets(context: &DBContext, criteria: TicketCriteria) -> QueryResult<Vec<SupportTicket>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_tickets(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get a support ticket with its messages, for the user who raised it or an admin")]
+    fn get_ticket(context: &DBContext, ticket_id: String, user_id: UserId) -> QueryResult<TicketThread> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_ticket(&connection, ticket_id.as_str(), &user_id);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the enrollment, session and utilization datasets of an organization, for its admins; only its own coaches are read")]
+    fn get_organization_report(context: &DBContext, criteria: OrganizationReportCriteria) -> QueryResult<OrganizationReport> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_organization_report(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the handovers of an enrollment between the peer coaches, with their notes, the latest first")]
+    fn get_handovers(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<EnrollmentHandover>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Enrollment(criteria.enrollment_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
+        }
+
+        let result = get_handovers(&connection, criteria.enrollment_id.as_str());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the monthly invoices of the coach, the latest month first, with links to download their PDFs")]
+    fn get_invoices(context: &DBContext, coach_id: String) -> QueryResult<Vec<Invoice>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(coach_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
+        }
+
+        let result = get_invoices(&connection, coach_id.as_str());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the private scratchpads of the coach over the sessions of an enrollment, the latest session first")]
+    fn get_scratchpads(context: &DBContext, criteria: ScratchpadCriteria) -> QueryResult<Vec<SessionScratchpad>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(criteria.coach_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
+        }
+
+        let result = get_scratchpads(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get who of the session joined its conference, when and for how long")]
+    fn get_attendance(context: &DBContext, session_id: String) -> QueryResult<Vec<Attendee>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Session(session_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
+        }
+
+        let result = get_attendance(&connection, &SessionId::from(session_id));
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get who changed an entity and how, the latest change first")]
+    fn get_audit_trail(context: &DBContext, criteria: AuditTrailCriteria) -> QueryResult<Vec<AuditEntry>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_audit_trail(&connection, context.caller(), &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the sessions, the tasks due, the check-ins and the announcements of the next seven days for the signed in member")]
+    fn get_my_week(context: &DBContext) -> QueryResult<MemberWeek> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_my_week(&connection, context.caller());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the platform banners in their window for the user, leaving out the dismissed ones")]
+    fn get_active_banners(context: &DBContext, user_id: UserId) -> QueryResult<Vec<PlatformBanner>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_active_banners(&connection, &user_id);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get every platform banner, for an admin to manage")]
+    fn get_banners(context: &DBContext) -> QueryResult<Vec<PlatformBanner>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_banners(&connection, context.caller());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the announcements of a program; the coach also gets the drafts and the scheduled")]
+    fn get_announcements(context: &DBContext, criteria: AnnouncementCriteria) -> QueryResult<Vec<Announcement>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_announcements(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the latest notifications of a user")]
+    fn get_notifications(context: &DBContext, criteria: NotificationCriteria) -> QueryResult<Vec<Notification>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_notifications(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the guest links shared by the coach of a session")]
+    fn get_guest_links(context: &DBContext, criteria: GuestLinkCriteria) -> QueryResult<Vec<GuestLink>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_guest_links(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the session and the board a guest link grants access to")]
+    fn redeem_guest_link(context: &DBContext, token: String) -> QueryResult<GuestAccess> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = redeem_guest_link(&connection, token.as_str());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the list of notes for a SessionUser, a page at a time")]
+    fn get_notes(context: &DBContext, criteria: NoteCriteria, page: Option<PageRequest>) -> PagedResult<Note> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return page_error(e),
+        };
+
+        let result = get_notes(&connection, criteria, &window);
+
+        match result {
+            Ok(value) => PagedResult(Ok(value)),
+            Err(e) => PagedResult(Err(e.into())),
+        }
+    }
+
+    #[graphql(description = "Get the files attached to a Note, along with the preview details of the videos")]
+    fn get_note_files(context: &DBContext, criteria: NoteFileCriteria) -> QueryResult<Vec<SessionFile>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_note_files(&connection, criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the discussions of an enrollment, the oldest first, a page at a time")]
+    fn get_discussions(context: &DBContext, criteria: DiscussionCriteria, page: Option<PageRequest>) -> PagedResult<Discussion> {
+        let window = match Window::of(page.as_ref()) {
+            Ok(value) => value,
+            Err(e) => return page_error(e),
+        };
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return page_error(e),
+        };
+
+        let result = get_discussions(&connection, criteria, &window);
+
+        match result {
+            Ok(value) => PagedResult(Ok(value)),
+            Err(e) => PagedResult(Err(e.into())),
+        }
+    }
+
+    #[graphql(description = "Get the list of notes of an enrollment. Hence both the member and the coach notes directly to the member.")]
+    fn get_enrollment_notes(context: &DBContext, criteria: PlanCriteria) -> QueryResult<Vec<NoteRow>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_enrollment_notes(&connection, criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the Session by its id")]
+    fn get_session(context: &DBContext, criteria: SessionCriteria) -> FieldResult<Session> {
+        let session = context.services.sessions.find_session(context, &criteria.id)?;
+        Ok(session)
+    }
+
+    #[graphql(description = "Get the People participating in an Event")]
+    fn get_session_users(context: &DBContext, criteria: SessionCriteria) -> QueryResult<Vec<SessionPeople>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_people(&connection, criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "The pending webhook deliveries, marked on offering; for the relay which posts them")]
+    fn get_sendable_webhooks(context: &DBContext) -> QueryResult<Vec<WebhookDelivery>> {
+        // It marks what it offers, hence the primary.
+        let context = context.on_primary();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = sendable_webhooks(&connection);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the webhooks of a Coach, with the secrets their deliveries are signed with")]
+    fn get_webhooks(context: &DBContext, coach_id: String) -> QueryResult<Vec<WebhookSubscription>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(coach_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
+        }
+
+        let result = get_webhooks(&connection, coach_id.as_str());
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the latest deliveries of the webhooks of a Coach, with the outcome of their attempts")]
+    fn get_webhook_deliveries(context: &DBContext, criteria: DeliveryCriteria) -> QueryResult<Vec<WebhookDelivery>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(criteria.coach_id.as_str()), &[Role::Coach]) {
+            return criteria_error(e);
+        }
+
+        let result = get_webhook_deliveries(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+
+    #[graphql(description = "Top 3 mails marked as Pending")]
+    fn get_sendable_mails(context: &DBContext) -> QueryResult<Vec<Mailable>> {
+        // It marks what it offers, hence the primary.
+        let context = context.on_primary();
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = sendable_mails(&connection);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the List of all the Boards of an enrolled member")]
+    fn get_boards(context: &DBContext, criteria: EventCriteria) -> QueryResult<Vec<BoardRow>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_boards(&connection, criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => query_error(e),
+        }
+    }
+
+    #[graphql(description = "Get the versions of a board, the earliest first, to replay how it evolved")]
+    fn get_board_versions(context: &DBContext, criteria: AnnotationCriteria) -> QueryResult<Vec<BoardVersion>> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return criteria_error(e),
+        };
+
+        let result = get_board_versions(&connection, &criteria);
+
+        match result {
+            Ok(value) => QueryResult(Ok(value)),
+            Err(e) => criteria_error(e),
+        }
+    }
+}
+
+pub struct MutationRoot;
+
+#[juniper::object(Context = DBContext)]
+impl MutationRoot {
+    fn create_user(context: &DBContext, registration: Registration) -> MutationResult<User> {
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = register(&connection, &registration);
+
+        match result {
+            Ok(user) => MutationResult(Ok(user)),
+            Err(e) => MutationResult(Err(e.errors)),
+        }
+    }
+
+    fn reset_password(context: &DBContext, request: ResetPasswordRequest) -> MutationResult<User> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = reset_password(&connection, &request);
+
+        match result {
+            Ok(user) => MutationResult(Ok(user)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Mail a token to reset the password of a user who forgot it; the answer is the same whether the email is registered or not")]
+    fn request_password_reset(context: &DBContext, request: PasswordResetRequest) -> MutationResult<String> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = request_password_reset(&connection, &request);
+
+        match result {
+            Ok(notice) => MutationResult(Ok(notice)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Change the password with the token of the reset mail, which is good for one change within the hour")]
+    fn confirm_password_reset(context: &DBContext, request: ConfirmPasswordResetRequest) -> MutationResult<User> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = confirm_password_reset(&connection, &request);
+
+        match result {
+            Ok(user) => MutationResult(Ok(user)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Deactivate an account, cancelling its upcoming sessions, or reactivate it; for the admins")]
+    fn change_account_state(context: &DBContext, request: ChangeAccountStateRequest) -> MutationResult<User> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = change_account_state(&connection, context.caller(), &request);
+
+        match result {
+            Ok(user) => MutationResult(Ok(user)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Block a user, cancelling the upcoming sessions, or unblock; for the admins")]
+    fn block_user(context: &DBContext, request: BlockUserRequest) -> MutationResult<User> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = block_user(&connection, context.caller(), &request);
+
+        match result {
+            Ok(user) => MutationResult(Ok(user)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Take a program and its peers off the catalog whatever their coach wants; for the admins")]
+    fn force_deactivate_program(context: &DBContext, request: ForceDeactivateRequest) -> MutationResult<String> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = force_deactivate_program(&connection, context.caller(), &request);
+
+        match result {
+            Ok(_) => MutationResult(Ok(String::from("Ok"))),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Publish a program submitted for a review to the catalog; for the admins")]
+    fn publish_program(context: &DBContext, program_id: ProgramId) -> MutationResult<Program> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = publish_program(&connection, context.caller(), &program_id);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Deactivate the own account; the user may sign in no more and is hidden from the lists of coaches")]
+    fn deactivate_account(context: &DBContext, request: DeactivateAccountRequest) -> MutationResult<User> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = deactivate_account(&connection, context.caller(), &request);
+
+        match result {
+            Ok(user) => MutationResult(Ok(user)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the locale and the time zone chosen by the user, replacing the detected defaults")]
+    fn save_locale(context: &DBContext, request: LocaleRequest) -> MutationResult<LocaleBundle> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = save_locale(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_abstract_task(context: &DBContext, request: NewAbstractTaskRequest) -> MutationResult<AbstractTask> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_abstract_task(&connection, &request);
+
+        match result {
+            Ok(abstract_task) => MutationResult(Ok(abstract_task)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn create_master_plan(context: &DBContext, request: NewMasterPlanRequest) -> MutationResult<MasterPlan> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = create_master_plan(&connection, &request);
+
+        match result {
+            Ok(master_plan) => MutationResult(Ok(master_plan)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn create_master_task(context: &DBContext, new_master_task_request: NewMasterTaskRequest) -> MutationResult<MasterTask> {
+        let errors = new_master_task_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_master_task(&connection, &new_master_task_request);
+
+        match result {
+            Ok(master_task) => MutationResult(Ok(master_task)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn update_master_task(context: &DBContext, update_master_task_request: UpdateMasterTaskRequest) -> MutationResult<MasterTask> {
+        let errors = update_master_task_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_master_task(&connection, &update_master_task_request);
+
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn save_master_plan(context: &DBContext, request: UpdateMasterPlanRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::MasterPlan(request.master_plan_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = update_master_plan(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn spawn_plan(context: &DBContext, request: SpawnPlanRequest) -> MutationResult<Vec<Task>> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Enrollment(request.enrollment_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+        if let Err(e) = authorize(&connection, context.caller(), Target::MasterPlan(request.master_plan_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = spawn_plan(&connection, &request);
+
+        match result {
+            Ok(spawned) => MutationResult(Ok(spawned)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_program(context: &DBContext, new_program_request: NewProgramRequest) -> MutationResult<Program> {
+        let errors = new_program_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let result = context.services.programs.create_program(context, &new_program_request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn associate_coach(context: &DBContext, request: AssociateCoachRequest) -> MutationResult<Program> {
+        if let Err(e) = context.services.guard.authorize(context, Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.programs.associate_coach(context, &request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Replace the tags to discover a program by in the catalog")]
+    fn manage_program_tags(context: &DBContext, request: ProgramTagsRequest) -> MutationResult<Program> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = manage_tags(&connection, &request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Submit the draft of a program for a review before it goes into the catalog")]
+    fn submit_program(context: &DBContext, request: ProgramReviewRequest) -> MutationResult<Program> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = submit_program(&connection, &request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Take a published program off the catalog for good; the members enrolled go on with it")]
+    fn retire_program(context: &DBContext, request: ProgramReviewRequest) -> MutationResult<Program> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = retire_program(&connection, &request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Price a program in a currency, or make it free; the peer programs take the same price")]
+    fn set_program_price(context: &DBContext, request: ProgramPriceRequest) -> MutationResult<Program> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = set_price(&connection, &request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Hand out a discount code for a percent or an amount off the price of a program")]
+    fn create_coupon(context: &DBContext, request: NewCouponRequest) -> MutationResult<Coupon> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = create_coupon(&connection, &request);
+
+        match result {
+            Ok(coupon) => MutationResult(Ok(coupon)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Record the payment intent opened at the payment gateway to enroll in a paid program")]
+    fn create_payment_intent(context: &DBContext, request: PaymentIntentRequest) -> MutationResult<Payment> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_payment_intent(&connection, context.caller(), &request);
+
+        match result {
+            Ok(payment) => MutationResult(Ok(payment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Confirm or fail a pending payment; for the payment gateway, with its token, and the admins")]
+    fn confirm_payment(context: &DBContext, request: ConfirmPaymentRequest) -> MutationResult<Payment> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = confirm_payment(&connection, context.caller(), &request);
+
+        match result {
+            Ok(payment) => MutationResult(Ok(payment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_enrollment(context: &DBContext, new_enrollment_request: NewEnrollmentRequest) -> MutationResult<Enrollment> {
+        let errors = new_enrollment_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_new_enrollment(&connection, &new_enrollment_request);
+
+        match result {
+            Ok(enrollment) => MutationResult(Ok(enrollment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Rate and review a program once per enrollment, after a session of it is completed")]
+    fn create_review(context: &DBContext, request: NewReviewRequest) -> MutationResult<ProgramReview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Enrollment(request.enrollment_id.as_str()), &[Role::Member]) {
+            return service_error(e);
+        }
+
+        let result = create_review(&connection, &request);
+
+        match result {
+            Ok(review) => MutationResult(Ok(review)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Hide an abusive review of a program with a reason, or show it again")]
+    fn moderate_review(context: &DBContext, request: ModerateReviewRequest) -> MutationResult<ProgramReview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = moderate_review(&connection, &request);
+
+        match result {
+            Ok(review) => MutationResult(Ok(review)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Wait for a seat in a full program, to be enrolled as one opens")]
+    fn join_waitlist(context: &DBContext, request: WaitlistRequest) -> MutationResult<ProgramWaitlist> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = join_waitlist(&connection, context.caller(), &request);
+
+        match result {
+            Ok(entry) => MutationResult(Ok(entry)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Enroll the given member of the waitlist, or else the first of it, even past the cap")]
+    fn promote_from_waitlist(context: &DBContext, request: PromoteRequest) -> MutationResult<Enrollment> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = promote_from_waitlist(&connection, &request);
+
+        match result {
+            Ok(enrollment) => MutationResult(Ok(enrollment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Cap the members of a program, or lift the cap; the seats opened go to the waitlist")]
+    fn set_program_capacity(context: &DBContext, request: CapacityRequest) -> MutationResult<Program> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Program(request.program_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = set_capacity(&connection, &request);
+
+        match result {
+            Ok(program) => MutationResult(Ok(program)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn managed_enrollment(context: &DBContext, managed_enrollment_request: ManagedEnrollmentRequest) -> MutationResult<Enrollment> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_managed_enrollment(&connection, &managed_enrollment_request);
+
+        match result {
+            Ok(enrollment) => MutationResult(Ok(enrollment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "End an enrollment with a reason, by the member or by the coach; the seat opened goes to the waitlist")]
+    fn cancel_enrollment(context: &DBContext, request: CancelEnrollmentRequest) -> MutationResult<Enrollment> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Enrollment(request.enrollment_id.as_str()), &[Role::Coach, Role::Member]) {
+            return service_error(e);
+        }
+
+        let result = cancel_enrollment(&connection, &request);
+
+        match result {
+            Ok(enrollment) => MutationResult(Ok(enrollment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Hand an enrollment over to the program of a peer coach, with a note for the coach taking over")]
+    fn transfer_enrollment(context: &DBContext, request: TransferEnrollmentRequest) -> MutationResult<Enrollment> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = transfer_enrollment(&connection, &request);
+
+        match result {
+            Ok(enrollment) => MutationResult(Ok(enrollment)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_session(context: &DBContext, new_session_request: NewSessionRequest) -> MutationResult<Session> {
+        let errors = new_session_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let result = context.services.sessions.create_session(context, &new_session_request);
+
+        match result {
+            Ok(session) => {
+                if let Ok(connection) = context.connection() {
+                    context.push_session_feeds(&connection, &session);
+                }
+                MutationResult(Ok(session))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_conference(context: &DBContext, new_conference_request: NewConferenceRequest) -> MutationResult<Conference> {
+        let errors = new_conference_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_conference(&connection, &new_conference_request);
+
+        match result {
+            Ok(conference) => MutationResult(Ok(conference)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn manage_conference(context: &DBContext, member_request: MemberRequest) -> MutationResult<ConferenceMembers> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = manage_members(&connection, &member_request);
+
+        match result {
+            Ok(members) => MutationResult(Ok(members)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Spawn a one to one follow-up for each member of a conference that is done, linked back to the conference")]
+    fn spawn_followups(context: &DBContext, request: FollowupRequest) -> MutationResult<Followups> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = spawn_followups(&connection, context.caller(), &request);
+
+        match result {
+            Ok(followups) => {
+                for session in &followups.sessions {
+                    context.push_session_feeds(&connection, session);
+                }
+                MutationResult(Ok(followups))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_objective(context: &DBContext, new_objective_request: NewObjectiveRequest) -> MutationResult<Objective> {
+        let errors = new_objective_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_objective(&connection, &new_objective_request);
+
+        match result {
+            Ok(objective) => MutationResult(Ok(objective)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn create_option(context: &DBContext, new_option_request: NewOptionRequest) -> MutationResult<Constraint> {
+        let errors = new_option_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_option(&connection, &new_option_request);
+
+        match result {
+            Ok(option) => MutationResult(Ok(option)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn create_observation(context: &DBContext, new_observation_request: NewObservationRequest) -> MutationResult<Observation> {
+        let errors = new_observation_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_observation(&connection, &new_observation_request);
+
+        match result {
+            Ok(option) => MutationResult(Ok(option)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn update_observation(context: &DBContext, update_observation_request: UpdateObservationRequest) -> MutationResult<Observation> {
+        let errors = update_observation_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_observation(&connection, &update_observation_request);
+
+        match result {
+            Ok(obs) => MutationResult(Ok(obs)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    #[graphql(description = "Keep an observation to the coach, or share it with the member or the peer coaches")]
+    fn change_observation_visibility(context: &DBContext, request: ChangeObservationVisibilityRequest) -> MutationResult<Observation> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Observation(request.id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = change_observation_visibility(&connection, &request);
+
+        match result {
+            Ok(observation) => MutationResult(Ok(observation)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn update_option(context: &DBContext, update_option_request: UpdateOptionRequest) -> MutationResult<Constraint> {
+        let errors = update_option_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_option(&connection, &update_option_request);
+
+        match result {
+            Ok(option) => MutationResult(Ok(option)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn update_objective(context: &DBContext, update_objective_request: UpdateObjectiveRequest) -> MutationResult<Objective> {
+        let errors = update_objective_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_objective(&connection, &update_objective_request);
+
+        match result {
+            Ok(objective) => MutationResult(Ok(objective)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn create_task(context: &DBContext, new_task_request: NewTaskRequest) -> MutationResult<Task> {
+        let errors = new_task_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        if let Err(e) = context.services.guard.authorize(context, Target::Enrollment(new_task_request.enrollment_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.create_task(context, &new_task_request);
+
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn update_task(context: &DBContext, update_task_request: UpdateTaskRequest) -> MutationResult<Task> {
+        let errors = update_task_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        if let Err(e) = context.services.guard.authorize(context, Target::Task(update_task_request.id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.update_task(context, &update_task_request);
+
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn reschedule_cascade(context: &DBContext, request: RescheduleCascadeRequest) -> MutationResult<Vec<Task>> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        if let Err(e) = context.services.guard.authorize(context, Target::Task(request.task_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.reschedule_cascade(context, &request);
+
+        match result {
+            Ok(moved) => MutationResult(Ok(moved)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Make the action items of a session into the tasks of its member, each due on its own date")]
+    fn create_tasks_from_summary(context: &DBContext, request: CreateTasksFromSummaryRequest) -> MutationResult<Vec<Task>> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        if let Err(e) = context.services.guard.authorize(context, Target::Session(request.session_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.create_tasks_from_summary(context, &request);
+
+        match result {
+            Ok(created) => MutationResult(Ok(created)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn update_task_closing_notes(context: &DBContext, request: UpdateClosingNoteRequest) -> MutationResult<Task> {
+        if let Err(e) = context.services.guard.authorize(context, Target::Task(request.id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.update_closing_notes(context, &request);
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn update_task_response(context: &DBContext, request: UpdateResponseRequest) -> MutationResult<Task> {
+        if let Err(e) = context.services.guard.authorize(context, Target::Task(request.id.as_str()), &[Role::Member]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.update_response(context, &request);
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => service_error(e),
+        }
+    }
+    fn alter_coach_task_state(context: &DBContext, request: ChangeCoachTaskStateRequest) -> MutationResult<Task> {
+        if let Err(e) = context.services.guard.authorize(context, Target::Task(request.id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.change_coach_task_state(context, &request);
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn alter_member_task_state(context: &DBContext, request: ChangeMemberTaskStateRequest) -> MutationResult<Task> {
+        if let Err(e) = context.services.guard.authorize(context, Target::Task(request.id.as_str()), &[Role::Member]) {
+            return service_error(e);
+        }
+
+        let result = context.services.tasks.change_member_task_state(context, &request);
+        match result {
+            Ok(task) => MutationResult(Ok(task)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn alter_session_state(context: &DBContext, request: ChangeSessionStateRequest) -> MutationResult<Session> {
+        if let Err(e) = context.services.guard.authorize(context, Target::Session(request.id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = context.services.sessions.change_session_state(context, &request);
+        match result {
+            Ok(session) => {
+                context.events.publish(&SessionEvent::changed(&session, &request.target_state));
+                if let Ok(connection) = context.connection() {
+                    context.push_session_feeds(&connection, &session);
+                }
+                MutationResult(Ok(session))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Cancel the sessions of a coach yet to start in a range of days, telling the members the reason; preview them with dryRun")]
+    fn cancel_sessions(context: &DBContext, request: BulkCancelRequest) -> MutationResult<CancellationPreview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = cancel_sessions(&connection, context.caller(), &request);
+
+        match result {
+            Ok(preview) => {
+                if preview.applied {
+                    for session in &preview.sessions {
+                        context.events.publish(&SessionEvent::changed(session, &TargetState::CANCEL));
+                        context.push_session_feeds(&connection, session);
+                    }
+                }
+                MutationResult(Ok(preview))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Merge a duplicate session into the original, moving its notes, boards and guest links and cancelling it; preview with dryRun")]
+    fn merge_sessions(context: &DBContext, request: MergeSessionsRequest) -> MutationResult<MergePreview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = merge_sessions(&connection, context.caller(), &request);
+
+        match result {
+            Ok(preview) => {
+                // The duplicate is cancelled by the merge; those following it are told so.
+                if preview.applied {
+                    if let Ok(duplicate) = crate::services::sessions::find(&connection, &SessionId::from(preview.duplicate_id.as_str())) {
+                        context.events.publish(&SessionEvent::changed(&duplicate, &TargetState::CANCEL));
+                        context.push_session_feeds(&connection, &duplicate);
+                    }
+                }
+                MutationResult(Ok(preview))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Tag a session with the objectives it addressed and an outcome rating from 1 to 5 for each")]
+    fn tag_session(context: &DBContext, request: TagSessionRequest) -> MutationResult<EnrollmentProgress> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = tag_session(&connection, context.caller(), &request);
+
+        match result {
+            Ok(progress) => MutationResult(Ok(progress)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Set the grace days and the extension on absence of the late tasks of a program")]
+    fn save_late_policy(context: &DBContext, request: LatePolicyRequest) -> MutationResult<LatePolicy> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = save_late_policy(&connection, &request);
+
+        match result {
+            Ok(policy) => MutationResult(Ok(policy)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Record a documented absence of a member; the late tasks in it are extended where the program says so")]
+    fn record_absence(context: &DBContext, request: AbsenceRequest) -> MutationResult<MemberAbsence> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = record_absence(&connection, &request);
+
+        match result {
+            Ok(absence) => MutationResult(Ok(absence)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Raise a support ticket; the admins are told by mail")]
+    fn raise_ticket(context: &DBContext, request: RaiseTicketRequest) -> MutationResult<TicketThread> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = raise_ticket(&connection, &request);
+
+        match result {
+            Ok(thread) => MutationResult(Ok(thread)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Reply on a support ticket; an admin reply answers it and a user reply opens it again")]
+    fn reply_ticket(context: &DBContext, request: TicketReplyRequest) -> MutationResult<TicketThread> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = reply_ticket(&connection, &request);
+
+        match result {
+            Ok(thread) => MutationResult(Ok(thread)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Resolve a support ticket, or reopen a resolved one as the user who raised it")]
+    fn change_ticket_status(context: &DBContext, request: TicketActionRequest) -> MutationResult<TicketThread> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = act_on_ticket(&connection, &request);
+
+        match result {
+            Ok(thread) => MutationResult(Ok(thread)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Create an organization, by an admin of the platform")]
+    fn create_organization(context: &DBContext, request: NewOrganizationRequest) -> MutationResult<Organization> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_organization(&connection, context.caller(), &request);
+
+        match result {
+            Ok(organization) => MutationResult(Ok(organization)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Add a user to an organization as an admin or a coach, or remove them without a role")]
+    fn save_organization_member(context: &DBContext, request: OrganizationMemberRequest) -> MutationResult<Organization> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = save_organization_member(&connection, context.caller(), &request);
+
+        match result {
+            Ok(organization) => MutationResult(Ok(organization)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Set the hours and the holidays the coaches of an organization schedule their sessions by, by an admin of it")]
+    fn set_scheduling_rules(context: &DBContext, request: SchedulingRulesRequest) -> MutationResult<Organization> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = set_scheduling_rules(&connection, context.caller(), &request);
+
+        match result {
+            Ok(organization) => MutationResult(Ok(organization)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Share an enrollment with a peer coach of the program, who may then stand in for its coach")]
+    fn add_enrollment_coach(context: &DBContext, request: EnrollmentCoachRequest) -> MutationResult<Vec<EnrollmentCoach>> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = add_enrollment_coach(&connection, &request);
+
+        match result {
+            Ok(co_coaches) => MutationResult(Ok(co_coaches)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Take an enrollment back from a co-coach, or step down as its co-coach")]
+    fn remove_enrollment_coach(context: &DBContext, request: EnrollmentCoachRequest) -> MutationResult<Vec<EnrollmentCoach>> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = remove_enrollment_coach(&connection, &request);
+
+        match result {
+            Ok(co_coaches) => MutationResult(Ok(co_coaches)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the private scratchpad of the coach for a session, replacing the text saved before")]
+    fn save_scratchpad(context: &DBContext, request: SaveScratchpadRequest) -> MutationResult<Scratchpad> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = save_scratchpad(&connection, &request);
+
+        match result {
+            Ok(scratchpad) => MutationResult(Ok(scratchpad)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the summary of a session for its member, filling in its closing notes from it")]
+    fn save_session_summary(context: &DBContext, request: SaveSessionSummaryRequest) -> MutationResult<SessionSummary> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = save_session_summary(&connection, &request);
+
+        match result {
+            Ok(summary) => MutationResult(Ok(summary)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Record the user joining the conference of the session")]
+    fn record_session_visit(context: &DBContext, request: VisitRequest) -> MutationResult<SessionVisit> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = record_session_visit(&connection, context.caller(), &request);
+
+        match result {
+            Ok(visit) => MutationResult(Ok(visit)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Record the user leaving the conference of the session")]
+    fn leave_session(context: &DBContext, request: VisitRequest) -> MutationResult<SessionVisit> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = leave_session(&connection, context.caller(), &request);
+
+        match result {
+            Ok(visit) => MutationResult(Ok(visit)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn alter_program_state(context: &DBContext, request: ChangeProgramStateRequest) -> MutationResult<String> {
+        let result = context.services.programs.change_program_state(context, &request);
+
+        match result {
+            Ok(rows) => MutationResult(Ok(String::from("Ok"))),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_note(context: &DBContext, new_note_request: NewNoteRequest) -> MutationResult<Note> {
+        let errors = new_note_request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_new_note(&connection, &new_note_request);
+
+        match result {
+            Ok(note) => MutationResult(Ok(note)),
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    fn create_discussion(context: &DBContext, new_discussion_request: NewDiscussionRequest) -> MutationResult<Discussion> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        // The discussion of an enrollment is locked while either side is deactivated.
+        let parties = [new_discussion_request.created_by_id.as_str(), new_discussion_request.to_id.as_str()];
+        if let Err(e) = gate_active(&connection, &parties) {
+            return service_error(e);
+        }
+
+        let result = create_new_discussion(&connection, &new_discussion_request);
+
+        match result {
+            Ok(discussion) => {
+                context.push_feed_counts(&connection, &parties);
+                MutationResult(Ok(discussion))
+            }
+            Err(e) => mutation_error(e),
+        }
+    }
+
+    #[graphql(description = "Mark the given discussions of an enrollment as read by the user. Answers the number of feeds marked.")]
+    fn mark_discussion_read(context: &DBContext, request: ReadDiscussionRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Enrollment(request.enrollment_id.as_str()), &[Role::Coach, Role::Member]) {
+            return service_error(e);
+        }
+
+        let result = mark_discussion_read(&connection, &request);
+
+        match result {
+            Ok(count) => {
+                context.push_feed_counts(&connection, &[request.user_id.as_str()]);
+                MutationResult(Ok(count.to_string()))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Mark the pending discussions of the user as read, of an enrollment or of all. Answers the number of feeds marked.")]
+    fn mark_all_read(context: &DBContext, request: ReadAllRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Some(value) = &request.enrollment_id {
+            if let Err(e) = authorize(&connection, context.caller(), Target::Enrollment(value.as_str()), &[Role::Coach, Role::Member]) {
+                return service_error(e);
+            }
+        }
+
+        let result = mark_all_read(&connection, &request);
+
+        match result {
+            Ok(count) => {
+                context.push_feed_counts(&connection, &[request.user_id.as_str()]);
+                MutationResult(Ok(count.to_string()))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_saved_filter(context: &DBContext, request: NewSavedFilterRequest) -> MutationResult<SavedFilter> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_saved_filter(&connection, &request);
+
+        match result {
+            Ok(filter) => MutationResult(Ok(filter)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn update_saved_filter(context: &DBContext, request: UpdateSavedFilterRequest) -> MutationResult<SavedFilter> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_saved_filter(&connection, &request);
+
+        match result {
+            Ok(filter) => MutationResult(Ok(filter)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn delete_saved_filter(context: &DBContext, request: SavedFilterCriteria) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_saved_filter(&connection, &request);
+
+        match result {
+            Ok(_) => MutationResult(Ok(String::from("Ok"))),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_faq(context: &DBContext, request: NewFaqRequest) -> MutationResult<ProgramFaq> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_faq(&connection, &request);
+
+        match result {
+            Ok(faq) => MutationResult(Ok(faq)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn update_faq(context: &DBContext, request: UpdateFaqRequest) -> MutationResult<ProgramFaq> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_faq(&connection, &request);
+
+        match result {
+            Ok(faq) => MutationResult(Ok(faq)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn delete_faq(context: &DBContext, criteria: FaqCriteria) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_faq(&connection, &criteria);
+
+        match result {
+            Ok(_) => MutationResult(Ok(String::from("Ok"))),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Ask the coach of a program a question; the coach is notified")]
+    fn ask_program_question(context: &DBContext, request: AskQuestionRequest) -> MutationResult<ProgramQuestion> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = ask_question(&connection, &request);
+
+        match result {
+            Ok(question) => MutationResult(Ok(question)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Answer a question, optionally publishing it to the FAQ; the asker is notified")]
+    fn answer_program_question(context: &DBContext, request: AnswerQuestionRequest) -> MutationResult<ProgramQuestion> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = answer_question(&connection, &request);
+
+        match result {
+            Ok(question) => MutationResult(Ok(question)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Describe the program a member needs; the coaches with matching specialties are notified")]
+    fn raise_program_request(context: &DBContext, request: RaiseProgramRequest) -> MutationResult<ProgramRequest> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = raise_program_request(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Withdraw an open program request; its pending offers are declined")]
+    fn withdraw_program_request(context: &DBContext, request: WithdrawProgramRequest) -> MutationResult<ProgramRequest> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = withdraw_program_request(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Offer a program of the coach for an open request; the member is notified")]
+    fn make_program_offer(context: &DBContext, request: MakeOfferRequest) -> MutationResult<ProgramOffer> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = make_offer(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Accept an offer, enrolling the member into the offered program")]
+    fn accept_program_offer(context: &DBContext, request: AcceptOfferRequest) -> MutationResult<Enrollment> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = accept_offer(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Grant or decline the recording of the conference, as a participant of its session")]
+    fn give_recording_consent(context: &DBContext, request: ConsentRequest) -> MutationResult<RecordingConsent> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = give_consent(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Attach a recording to a conference; refused until every participant has consented")]
+    fn attach_recording(context: &DBContext, request: AttachRecordingRequest) -> MutationResult<ConferenceRecording> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = attach_recording(&connection, &request);
+
+        match result {
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Add a goal card to the goal board of an enrollment; the other party is notified")]
+    fn create_goal_card(context: &DBContext, request: NewGoalCardRequest) -> MutationResult<GoalCardView> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_card(&connection, &request);
+
+        match result {
+            Ok(value) => {
+                context.push_goal_board(&connection, &GoalBoardEvent::of(&value.card, CARD_ADDED));
+                MutationResult(Ok(value))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Update or move a goal card; the other party is notified when the goal is achieved")]
+    fn update_goal_card(context: &DBContext, request: UpdateGoalCardRequest) -> MutationResult<GoalCardView> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_card(&connection, &request);
+
+        match result {
+            Ok(value) => {
+                context.push_goal_board(&connection, &GoalBoardEvent::of(&value.card, CARD_UPDATED));
+                MutationResult(Ok(value))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Delete a goal card along with its comments")]
+    fn delete_goal_card(context: &DBContext, criteria: GoalCardCriteria) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_card(&connection, &criteria);
+
+        match result {
+            Ok((card, rows)) => {
+                context.push_goal_board(&connection, &GoalBoardEvent::of(&card, CARD_DELETED));
+                MutationResult(Ok(rows.to_string()))
+            }
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Comment on a goal card; the other party is notified")]
+    fn comment_goal_card(context: &DBContext, request: GoalCommentRequest) -> MutationResult<GoalCardView> {
+        let errors = request.validate();
         if !errors.is_empty() {
             return MutationResult(Err(errors));
         }
 
-        let connection = context.db.get().unwrap();
-        let result = create_task(&connection, &new_task_request);
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = comment_card(&connection, &request);
 
         match result {
-            Ok(task) => MutationResult(Ok(task)),
-            Err(e) => mutation_error(e),
+            Ok(value) => {
+                context.push_goal_board(&connection, &GoalBoardEvent::of(&value.card, CARD_COMMENTED));
+                MutationResult(Ok(value))
+            }
+            Err(e) => service_error(e),
         }
     }
 
-    fn update_task(context: &DBContext, update_task_request: UpdateTaskRequest) -> MutationResult<Task> {
-        let errors = update_task_request.validate();
+    #[graphql(description = "Create a platform banner, shown to its audience between its start and its end")]
+    fn create_banner(context: &DBContext, request: BannerRequest) -> MutationResult<PlatformBanner> {
+        let errors = request.validate();
         if !errors.is_empty() {
             return MutationResult(Err(errors));
         }
 
-        let connection = context.db.get().unwrap();
-        let result = update_task(&connection, &update_task_request);
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_banner(&connection, context.caller(), &request);
 
         match result {
-            Ok(task) => MutationResult(Ok(task)),
-            Err(e) => mutation_error(e),
+            Ok(value) => MutationResult(Ok(value)),
+            Err(e) => service_error(e),
         }
     }
 
-    fn update_task_closing_notes(context: &DBContext, request: UpdateClosingNoteRequest) -> MutationResult<Task> {
-        let connection = context.db.get().unwrap();
-        let result = update_closing_notes(&connection, &request);
+    #[graphql(description = "Change the text, the severity, the audience or the window of a platform banner")]
+    fn update_banner(context: &DBContext, request: UpdateBannerRequest) -> MutationResult<PlatformBanner> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_banner(&connection, context.caller(), &request);
+
         match result {
-            Ok(task) => MutationResult(Ok(task)),
+            Ok(value) => MutationResult(Ok(value)),
             Err(e) => service_error(e),
         }
     }
 
-    fn update_task_response(context: &DBContext, request: UpdateResponseRequest) -> MutationResult<Task> {
-        let connection = context.db.get().unwrap();
-        let result = update_response(&connection, &request);
+    #[graphql(description = "Delete a platform banner along with its dismissals")]
+    fn delete_banner(context: &DBContext, criteria: BannerCriteria) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_banner(&connection, context.caller(), &criteria);
+
         match result {
-            Ok(task) => MutationResult(Ok(task)),
+            Ok(count) => MutationResult(Ok(count.to_string())),
             Err(e) => service_error(e),
         }
     }
-    fn alter_coach_task_state(context: &DBContext, request: ChangeCoachTaskStateRequest) -> MutationResult<Task> {
-        let connection = context.db.get().unwrap();
-        let result = change_coach_task_state(&connection, &request);
+
+    #[graphql(description = "Dismiss a platform banner for the user; a critical banner cannot be dismissed")]
+    fn dismiss_banner(context: &DBContext, request: DismissBannerRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = dismiss_banner(&connection, &request);
+
         match result {
-            Ok(task) => MutationResult(Ok(task)),
+            Ok(count) => MutationResult(Ok(count.to_string())),
             Err(e) => service_error(e),
         }
     }
 
-    fn alter_member_task_state(context: &DBContext, request: ChangeMemberTaskStateRequest) -> MutationResult<Task> {
-        let connection = context.db.get().unwrap();
-        let result = change_member_task_state(&connection, &request);
+    #[graphql(description = "Create an announcement; with a publish time it is scheduled, else kept as a draft")]
+    fn create_announcement(context: &DBContext, request: AnnouncementRequest) -> MutationResult<Announcement> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_announcement(&connection, &request);
+
         match result {
-            Ok(task) => MutationResult(Ok(task)),
+            Ok(announcement) => MutationResult(Ok(announcement)),
             Err(e) => service_error(e),
         }
     }
 
-    fn alter_session_state(context: &DBContext, request: ChangeSessionStateRequest) -> MutationResult<Session> {
-        let connection = context.db.get().unwrap();
-        let result = change_session_state(&connection, &request);
+    fn update_announcement(context: &DBContext, request: UpdateAnnouncementRequest) -> MutationResult<Announcement> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_announcement(&connection, &request);
+
         match result {
-            Ok(session) => MutationResult(Ok(session)),
+            Ok(announcement) => MutationResult(Ok(announcement)),
             Err(e) => service_error(e),
         }
     }
 
-    fn alter_program_state(context: &DBContext, request: ChangeProgramStateRequest) -> MutationResult<String> {
-        let connection = context.db.get().unwrap();
-        let result = change_program_state(&connection, &request);
+    fn delete_announcement(context: &DBContext, request: DeleteAnnouncementRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_announcement(&connection, &request);
 
         match result {
-            Ok(rows) => MutationResult(Ok(String::from("Ok"))),
+            Ok(_) => MutationResult(Ok(String::from("Ok"))),
             Err(e) => service_error(e),
         }
     }
 
-    fn create_note(context: &DBContext, new_note_request: NewNoteRequest) -> MutationResult<Note> {
-        let errors = new_note_request.validate();
+    #[graphql(description = "Post an event to a url of the coach, formatted for Slack, Discord or as plain JSON")]
+    fn create_webhook(context: &DBContext, request: WebhookRequest) -> MutationResult<WebhookSubscription> {
+        let errors = request.validate();
         if !errors.is_empty() {
             return MutationResult(Err(errors));
         }
 
-        let connection = context.db.get().unwrap();
-        let result = create_new_note(&connection, &new_note_request);
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_webhook(&connection, &request);
 
         match result {
-            Ok(note) => MutationResult(Ok(note)),
-            Err(e) => mutation_error(e),
+            Ok(webhook) => MutationResult(Ok(webhook)),
+            Err(e) => service_error(e),
         }
     }
 
-    fn create_discussion(context: &DBContext, new_discussion_request: NewDiscussionRequest) -> MutationResult<Discussion> {
-        let connection = context.db.get().unwrap();
-        let result = create_new_discussion(&connection, &new_discussion_request);
+    fn delete_webhook(context: &DBContext, criteria: WebhookCriteria) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_webhook(&connection, &criteria);
 
         match result {
-            Ok(discussion) => MutationResult(Ok(discussion)),
-            Err(e) => mutation_error(e),
+            Ok(_) => MutationResult(Ok(String::from("Ok"))),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Mark the notifications of a user as read. Answers the number of notifications marked.")]
+    fn mark_notifications_read(context: &DBContext, request: MarkReadRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = mark_read(&connection, &request);
+
+        match result {
+            Ok(count) => MutationResult(Ok(count.to_string())),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Publish a new version of an agreement; a required one must be accepted by every user before going on")]
+    fn publish_agreement(context: &DBContext, request: NewAgreementRequest) -> MutationResult<Agreement> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = publish_agreement(&connection, context.caller(), &request);
+
+        match result {
+            Ok(agreement) => MutationResult(Ok(agreement)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Publish a fee schedule taking effect after the latest one; the charges made before keep their fees")]
+    fn publish_fee_schedule(context: &DBContext, request: NewFeeScheduleRequest) -> MutationResult<FeeScheduleView> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = publish_fee_schedule(&connection, context.caller(), &request);
+
+        match result {
+            Ok(schedule) => MutationResult(Ok(schedule)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Accept an agreement on behalf of the user")]
+    fn accept_agreement(context: &DBContext, request: AcceptAgreementRequest) -> MutationResult<AgreementStatus> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = accept_agreement(&connection, &request);
+
+        match result {
+            Ok(status) => MutationResult(Ok(status)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the reference of the payout account of a coach at the payment provider")]
+    fn save_payment_details(context: &DBContext, request: PaymentDetailsRequest) -> MutationResult<OnboardingChecklist> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = save_payment_details(&connection, &request);
+
+        match result {
+            Ok(checklist) => MutationResult(Ok(checklist)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the headline, the about, the experience and the social links of the signed in user, coach or member")]
+    fn update_profile(context: &DBContext, request: UserProfileRequest) -> MutationResult<UserProfile> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = update_profile(&connection, context.caller(), &request);
+
+        match result {
+            Ok(profile) => MutationResult(Ok(profile)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the directory profile of a coach, including the choice to be listed")]
+    fn save_coach_profile(context: &DBContext, request: CoachProfileRequest) -> MutationResult<CoachProfile> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = save_coach_profile(&connection, &request);
+
+        match result {
+            Ok(profile) => MutationResult(Ok(profile)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the weekly windows of a coach and the exceptions to them, replacing the earlier ones")]
+    fn set_availability(context: &DBContext, request: AvailabilityRequest) -> MutationResult<Availability> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        if let Err(e) = authorize(&connection, context.caller(), Target::Coach(request.coach_id.as_str()), &[Role::Coach]) {
+            return service_error(e);
+        }
+
+        let result = set_availability(&connection, &request);
+
+        match result {
+            Ok(availability) => MutationResult(Ok(availability)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Save the overlay of the user over a board, replacing the earlier one")]
+    fn save_board_annotation(context: &DBContext, request: SaveAnnotationRequest) -> MutationResult<BoardAnnotation> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = save_annotation(&connection, &request);
+
+        match result {
+            Ok(annotation) => MutationResult(Ok(annotation)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn delete_board_annotation(context: &DBContext, criteria: AnnotationCriteria) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = delete_annotation(&connection, &criteria);
+
+        match result {
+            Ok(_) => MutationResult(Ok(String::from("Ok"))),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn create_guest_link(context: &DBContext, request: NewGuestLinkRequest) -> MutationResult<GuestLink> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = create_guest_link(&connection, &request);
+
+        match result {
+            Ok(link) => MutationResult(Ok(link)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn revoke_guest_link(context: &DBContext, request: RevokeGuestLinkRequest) -> MutationResult<GuestLink> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = revoke_guest_link(&connection, &request);
+
+        match result {
+            Ok(link) => MutationResult(Ok(link)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    #[graphql(description = "Scramble the personal data of a staging copy. Refused unless ALLOW_ANONYMIZE is set.")]
+    fn anonymize_data(context: &DBContext, request: AnonymizeRequest) -> MutationResult<String> {
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = anonymize(&connection, context.caller(), &request);
+
+        match result {
+            Ok(summary) => MutationResult(Ok(summary)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn relink_enrollment(context: &DBContext, request: RelinkEnrollmentRequest) -> MutationResult<FixPreview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = relink_enrollment(&connection, context.caller(), &request);
+
+        match result {
+            Ok(preview) => MutationResult(Ok(preview)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn fix_session_dates(context: &DBContext, request: SwapSessionDatesRequest) -> MutationResult<FixPreview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = swap_session_dates(&connection, context.caller(), &request);
+
+        match result {
+            Ok(preview) => MutationResult(Ok(preview)),
+            Err(e) => service_error(e),
+        }
+    }
+
+    fn reassign_note_author(context: &DBContext, request: ReassignNoteAuthorRequest) -> MutationResult<FixPreview> {
+        let errors = request.validate();
+        if !errors.is_empty() {
+            return MutationResult(Err(errors));
+        }
+
+        let connection = match context.connection() {
+            Ok(connection) => connection,
+            Err(e) => return service_error(e),
+        };
+
+        let result = reassign_note_author(&connection, context.caller(), &request);
+
+        match result {
+            Ok(preview) => MutationResult(Ok(preview)),
+            Err(e) => service_error(e),
         }
     }
 }
@@ -0,0 +1,131 @@
+/**
+ * The GraphQL multipart request, as the graphql-multipart-request-spec has it:
+ * an `operations` part with the request, its file variables left null, then a
+ * `map` part telling the variables each file part goes to, then the files.
+ *
+ *     operations  {"query": "mutation ($r: NewNoteRequest!) {...}", "variables": {"r": {"sessionUserId": "su-1", "files": [null]}}}
+ *     map         {"0": ["variables.r.files.0"]}
+ *     0           the content of the file
+ *
+ * Only the files of a note are taken this way: a path of the map should be
+ * variables.<request>.files.<n>, and the file is stored among the notes of the
+ * sessionUserId of that request, as the assets/upload route stores it. The
+ * stored file takes the place of its null as a FileRequest, so create_note
+ * attaches it in the transaction creating the note.
+ *
+ * When the response carries any errors, the stored files are removed again; the
+ * blob-sweep then forgets them. A note is thus never left without its files,
+ * nor the files without their note.
+ */
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+pub const OPERATIONS_PART: &str = "operations";
+pub const MAP_PART: &str = "map";
+
+const NOT_A_NOTE_FILE: &str = "Only the files of a note may be sent with the request, as variables.<request>.files.<n>.";
+const NO_SESSION_USER: &str = "The request of the note names no sessionUserId.";
+const NO_VARIABLE: &str = "The map points to no variable of the operations.";
+
+pub type FileMap = HashMap<String, Vec<String>>;
+
+/**
+ * Where a file part goes: the JSON pointer of its variable and the session user
+ * among whose notes it is stored.
+ */
+#[derive(Debug, PartialEq)]
+pub struct FileTarget {
+    pub pointer: String,
+    pub session_user_id: String,
+}
+
+pub fn target(operations: &Value, path: &str) -> Result<FileTarget, &'static str> {
+    let segments: Vec<&str> = path.split('.').collect();
+
+    let (request, index) = match segments.as_slice() {
+        ["variables", request, "files", index] if index.parse::<usize>().is_ok() => (*request, *index),
+        _ => return Err(NOT_A_NOTE_FILE),
+    };
+
+    let session_user_id = operations["variables"][request]["sessionUserId"]
+        .as_str()
+        .map(sanitize_filename::sanitize)
+        .filter(|value| !value.trim().is_empty())
+        .ok_or(NO_SESSION_USER)?;
+
+    Ok(FileTarget {
+        pointer: format!("/variables/{}/files/{}", request, index),
+        session_user_id,
+    })
+}
+
+/**
+ * The stored file in place of its null, in the shape of a FileRequest.
+ */
+pub fn place(operations: &mut Value, target: &FileTarget, path: &str, name: &str, file_type: &str, size: usize) -> Result<(), &'static str> {
+    let slot = operations.pointer_mut(target.pointer.as_str()).ok_or(NO_VARIABLE)?;
+
+    *slot = json!({
+        "path": path,
+        "name": name,
+        "type": file_type,
+        "size": size,
+    });
+
+    Ok(())
+}
+
+/**
+ * Errors anywhere in the response: of the request, or of a mutation result.
+ */
+pub fn failed(response: &Value) -> bool {
+    match response {
+        Value::Object(fields) => fields.iter().any(|(key, value)| (key == "errors" && has_errors(value)) || failed(value)),
+        Value::Array(items) => items.iter().any(failed),
+        _ => false,
+    }
+}
+
+fn has_errors(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Array(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+pub fn discard(paths: &[String]) {
+    for path in paths {
+        if let Err(e) = std::fs::remove_file(path) {
+            eprintln!("Unable to remove {}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_put_the_file_in_place_of_its_variable() {
+        let mut operations = json!({ "variables": { "r": { "sessionUserId": "su-1", "files": [null, null] } } });
+
+        let found = target(&operations, "variables.r.files.1").unwrap();
+        assert_eq!("su-1", found.session_user_id);
+
+        place(&mut operations, &found, "/sessions/su-1/notes/k/a.png", "a.png", "image/png", 42).unwrap();
+        assert_eq!(Value::Null, operations["variables"]["r"]["files"][0]);
+        assert_eq!(42, operations["variables"]["r"]["files"][1]["size"]);
+
+        assert_eq!(Err(NOT_A_NOTE_FILE), target(&operations, "variables.r.avatar"));
+        assert_eq!(Err(NO_SESSION_USER), target(&operations, "variables.other.files.0"));
+    }
+
+    #[test]
+    fn should_find_the_errors_of_the_response() {
+        assert!(!failed(&json!({ "data": { "createNote": { "note": { "id": "n-1" }, "errors": null } } })));
+        assert!(failed(&json!({ "data": { "createNote": { "note": null, "errors": [{ "field": "description" }] } } })));
+        assert!(failed(&json!({ "data": null, "errors": [{ "message": "Unknown field" }] })));
+    }
+}
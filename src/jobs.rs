@@ -0,0 +1,69 @@
+/**
+ * The background jobs of the platform.
+ *
+ * Each job runs on a thread of its own, outside the actix workers, and takes a
+ * connection from the shared pool on every run. A failed run is reported and
+ * retried on the next tick; it never stops the job.
+ */
+use diesel::mysql::MysqlConnection;
+use std::thread;
+use std::time::Duration;
+
+use crate::db_manager::MySqlConnectionPool;
+use crate::demo_mode::DemoMode;
+use crate::field_usage::FieldUsage;
+use crate::services::coach_stats;
+use crate::services::correspondences;
+use crate::services::demo_sandboxes;
+use crate::services::file_registry;
+use crate::services::invoices;
+use crate::services::late_policies;
+use crate::services::program_announcements;
+use crate::services::reminders;
+use crate::services::stale_drafts;
+use crate::services::video_metadata;
+use crate::services::webhooks;
+
+pub fn start(pool: &MySqlConnectionPool, usage: &FieldUsage) {
+    every(pool, "video-metadata", Duration::from_secs(30), video_metadata::process_pending);
+    every(pool, "announcements", Duration::from_secs(60), program_announcements::publish_due);
+    every(pool, "coach-stats", Duration::from_secs(15), coach_stats::refresh_pending);
+    every(pool, "coach-stats-rebuild", Duration::from_secs(24 * 60 * 60), coach_stats::rebuild);
+    every(pool, "late-policy", Duration::from_secs(10 * 60), late_policies::extend_late_tasks);
+    every(pool, "stale-drafts", Duration::from_secs(60 * 60), stale_drafts::expire_stale_drafts);
+    every(pool, "session-reminders", Duration::from_secs(60), reminders::send_reminders);
+    every(pool, "weekly-digest", Duration::from_secs(60 * 60), correspondences::send_weekly_digests);
+    every(pool, "blob-sweep", Duration::from_secs(6 * 60 * 60), file_registry::sweep_orphans);
+    every(pool, "invoices", Duration::from_secs(6 * 60 * 60), invoices::issue_invoices);
+    every(pool, "webhook-delivery", Duration::from_secs(15), webhooks::deliver_due);
+
+    let usage = usage.clone();
+    every(pool, "field-usage", Duration::from_secs(60), move |connection| usage.flush(connection));
+
+    if DemoMode::from_env().enabled {
+        every(pool, "demo-purge", Duration::from_secs(24 * 60 * 60), demo_sandboxes::purge);
+    }
+}
+
+pub fn every<F>(pool: &MySqlConnectionPool, name: &'static str, interval: Duration, job: F)
+where
+    F: Fn(&MysqlConnection) -> Result<usize, &'static str> + Send + 'static,
+{
+    let pool = pool.clone();
+
+    thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || loop {
+            match pool.get() {
+                Ok(connection) => {
+                    if let Err(e) = job(&connection) {
+                        eprintln!("The job {} failed: {}", name, e);
+                    }
+                }
+                Err(e) => eprintln!("The job {} could not obtain a connection: {}", name, e),
+            }
+
+            thread::sleep(interval);
+        })
+        .unwrap_or_else(|_| panic!("Unable to start the job {}", name));
+}
@@ -0,0 +1,161 @@
+/**
+ * The keyed loaders of a request, so that the rows of a page find what they
+ * show beside them in one query for the page rather than one for every row.
+ *
+ * The query resolver of a page primes a loader with the keys of its rows. The
+ * first row to ask for its value loads the values of every primed key at once;
+ * the other rows find theirs loaded. A key asked for without being primed is
+ * loaded on its own, and one the query finds nothing for has the default value.
+ *
+ * The loaders are kept on the DBContext of a request. A clone starts empty, like
+ * the connection of the request, so nothing loaded outlives it.
+ */
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use crate::models::coaches::Coach;
+use crate::models::users::User;
+
+struct Batch<K, V> {
+    primed: Vec<K>,
+    loaded: HashMap<K, V>,
+}
+
+pub struct Loader<K, V>(Mutex<Batch<K, V>>);
+
+impl<K, V> Default for Loader<K, V> {
+    fn default() -> Loader<K, V> {
+        Loader(Mutex::new(Batch { primed: Vec::new(), loaded: HashMap::new() }))
+    }
+}
+
+impl<K, V> Clone for Loader<K, V> {
+    fn clone(&self) -> Loader<K, V> {
+        Loader::default()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone + Default> Loader<K, V> {
+    /**
+     * The keys of the rows of a page, to be loaded together when the first of them is asked for.
+     */
+    pub fn prime<I: IntoIterator<Item = K>>(&self, keys: I) {
+        let mut batch = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for key in keys {
+            if !batch.loaded.contains_key(&key) && !batch.primed.contains(&key) {
+                batch.primed.push(key);
+            }
+        }
+    }
+
+    /**
+     * The value of the key, fetching it along with the primed keys when it is not loaded yet.
+     */
+    pub fn load<E, F>(&self, key: &K, fetch: F) -> Result<V, E>
+    where
+        F: FnOnce(&[K]) -> Result<HashMap<K, V>, E>,
+    {
+        let mut batch = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some(value) = batch.loaded.get(key) {
+            return Ok(value.clone());
+        }
+
+        let mut keys = std::mem::take(&mut batch.primed);
+        if !keys.contains(key) {
+            keys.push(key.clone());
+        }
+
+        // A failed fetch leaves the keys to the next row asking.
+        let mut values = match fetch(&keys) {
+            Ok(values) => values,
+            Err(e) => {
+                batch.primed = keys;
+                return Err(e);
+            }
+        };
+
+        for the_key in keys {
+            let value = values.remove(&the_key).unwrap_or_default();
+            batch.loaded.insert(the_key, value);
+        }
+
+        Ok(batch.loaded.get(key).cloned().unwrap_or_default())
+    }
+}
+
+/**
+ * The values of a batch query by their key, in the order the query found them.
+ */
+pub fn grouped<V>(pairs: Vec<(String, V)>) -> HashMap<String, Vec<V>> {
+    let mut groups: HashMap<String, Vec<V>> = HashMap::new();
+
+    for (key, value) in pairs {
+        groups.entry(key).or_default().push(value);
+    }
+
+    groups
+}
+
+#[derive(Clone, Default)]
+pub struct Loaders {
+    // The coaches of a program family, by the id of its parent program; see ProgramRow.
+    pub peer_coaches: Loader<String, Vec<Coach>>,
+    // The people of a session, by its id; see EventRow.
+    pub session_people: Loader<String, Vec<User>>,
+    // The co-coaches of an enrollment, by its id; see MemberRow.
+    pub co_coaches: Loader<String, Vec<User>>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::RefCell;
+
+    fn lengths(keys: &[String]) -> Result<HashMap<String, usize>, &'static str> {
+        Ok(keys.iter().filter(|key| key.as_str() != "none").map(|key| (key.to_owned(), key.len())).collect())
+    }
+
+    #[test]
+    fn should_load_the_primed_keys_at_once() {
+        let loader: Loader<String, usize> = Loader::default();
+        let fetches: RefCell<Vec<Vec<String>>> = RefCell::new(Vec::new());
+
+        let fetch = |keys: &[String]| {
+            fetches.borrow_mut().push(keys.to_vec());
+            lengths(keys)
+        };
+
+        loader.prime(vec![String::from("a"), String::from("bb"), String::from("none"), String::from("a")]);
+
+        assert_eq!(Ok(2), loader.load(&String::from("bb"), fetch));
+        assert_eq!(Ok(1), loader.load(&String::from("a"), fetch));
+        assert_eq!(Ok(0), loader.load(&String::from("none"), fetch));
+        assert_eq!(vec![vec![String::from("a"), String::from("bb"), String::from("none")]], *fetches.borrow());
+
+        assert_eq!(Ok(3), loader.load(&String::from("ccc"), fetch));
+        assert_eq!(vec![String::from("ccc")], fetches.borrow()[1]);
+    }
+
+    #[test]
+    fn should_keep_the_keys_of_a_failed_fetch() {
+        let loader: Loader<String, usize> = Loader::default();
+        loader.prime(vec![String::from("a"), String::from("bb")]);
+
+        assert_eq!(Err("down"), loader.load(&String::from("a"), |_: &[String]| Err("down")));
+        assert_eq!(Ok(1), loader.load(&String::from("a"), lengths));
+        assert_eq!(Ok(2), loader.load(&String::from("bb"), |_: &[String]| Err("fetched again")));
+    }
+
+    #[test]
+    fn should_start_a_clone_empty() {
+        let loader: Loader<String, usize> = Loader::default();
+        loader.prime(vec![String::from("a")]);
+        assert_eq!(Ok(1), loader.load(&String::from("a"), lengths));
+
+        assert_eq!(Err("fetched again"), loader.clone().load(&String::from("a"), |_: &[String]| Err("fetched again")));
+    }
+}
@@ -4,68 +4,157 @@ extern crate juniper;
 extern crate diesel;
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use actix_cors::Cors;
 use actix_multipart::Multipart;
-use actix_web::{web, App, Error, HttpRequest, HttpResponse, HttpServer};
+use actix_web::error::BlockingError;
+use actix_web::dev::RequestHead;
+use actix_web::http::header;
+use actix_web::{guard, web, App, Error, HttpRequest, HttpResponse, HttpServer};
+use asset_policy::AssetClass;
+use futures::StreamExt;
 use juniper::http::graphiql::graphiql_source;
 use juniper::http::GraphQLRequest;
+use serde::Deserialize;
 
+mod agreement_gate;
+mod asset_policy;
 mod commons;
+mod content_store;
 mod db_manager;
+mod demo_mode;
+mod feed_events;
+mod field_usage;
 mod file_manager;
 mod graphql_schema;
+mod graphql_uploads;
+mod jobs;
+mod loaders;
 mod models;
+mod mutation_audit;
+mod persisted_queries;
+mod query_limits;
+mod request_log;
 mod schema;
 mod services;
+mod session_events;
+mod storage;
+mod upload_pool;
+mod upload_policy;
 
 #[cfg(test)]
 mod service_tests;
 
-use actix_files::NamedFile;
-use db_manager::establish_connection;
+use db_manager::{checkout, establish_connection, RequestConnection};
+use demo_mode::DemoMode;
 use file_manager::{
-    fetch_board_file, fetch_list_of_boards, 
+    fetch_board_file, fetch_board_version, fetch_flattened_board, fetch_list_of_board_versions, fetch_list_of_boards, manage_batch_upload,
     fetch_program_content, fetch_user_content, fetch_platform_content,
-    manage_notes_file, manage_program_content, manage_user_content, 
-    PROGRAM_ASSET_DIR, 
-    SESSION_ASSET_DIR,
-    USER_ASSET_DIR,
-    PLATFORM_ASSET_DIR,
+    fetch_program_thumbnail, fetch_user_thumbnail,
+    manage_notes_file, manage_program_content, manage_user_content, export_my_data, read_graphql_multipart,
 };
+use feed_events::FeedEvents;
+use field_usage::{FieldCatalog, FieldUsage};
 use graphql_schema::{create_gq_schema, DBContext, GQSchema};
+use loaders::Loaders;
+use persisted_queries::PersistedQueries;
+use query_limits::QueryLimits;
+use request_log::RequestLog;
+use session_events::SessionEvents;
+use storage::{storage, Area};
+use upload_pool::UploadPool;
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::chassis::Window;
+use crate::commons::ids::{SessionId, UserId};
+use crate::commons::session_tokens;
+use crate::models::catalog_v1::{CatalogQuery, ErrorV1, ProgramPageV1};
+use crate::models::invoices::verify_link;
+use crate::models::mail_bounces::MailEvent;
+use crate::models::master_plan_imports::{self, ImportError, MASTER_PLAN_TEMPLATE};
+use crate::models::master_plans::NewMasterPlanRequest;
+use crate::models::plan_exports::{plan_file_name, PlanFormat};
+use crate::models::timeline_exports::TIMELINE_HEADER;
+use crate::models::user_locales::LocaleDetection;
+use crate::models::users::LoginRequest;
+use crate::services::demo_sandboxes::open_sandbox;
+use crate::services::facades::Services;
+use crate::services::discussions::{get_feed_counts, get_pending_feed_count};
+use crate::services::exports::{get_plan, plan_csv, plan_xlsx};
+use crate::services::invoices;
+use crate::services::mail_bounces::record_mail_events;
+use crate::services::sessions;
+use crate::services::timeline_exports::get_timeline;
+use crate::services::user_locales::settle_locale;
+use crate::services::users::authenticate;
+
+async fn upload_notes_file(ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, payload: Multipart) -> Result<HttpResponse, Error> {
+    let _permit = uploads.admit()?;
+    manage_notes_file(ctx, uploads, payload).await
+}
 
-use crate::services::discussions::get_pending_feed_count;
-
-async fn upload_notes_file(payload: Multipart) -> Result<HttpResponse, Error> {
-    manage_notes_file(payload).await
+async fn upload_batch(ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, payload: Multipart) -> Result<HttpResponse, Error> {
+    let _permit = uploads.admit()?;
+    manage_batch_upload(ctx, uploads, payload).await
 }
 
-async fn upload_program_content(_request: HttpRequest, payload: Multipart) -> Result<HttpResponse, Error> {
-    manage_program_content(_request, payload).await
+async fn upload_program_content(_request: HttpRequest, ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, payload: Multipart) -> Result<HttpResponse, Error> {
+    let _permit = uploads.admit()?;
+    manage_program_content(_request, ctx, uploads, payload).await
 }
 
 async fn list_of_boards(_request: HttpRequest) -> Result<HttpResponse, Error> {
     fetch_list_of_boards(_request).await
 }
-async fn offer_board_file(_request: HttpRequest) -> Result<NamedFile, Error> {
-    fetch_board_file(_request).await
+async fn offer_board_file(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_board_file(_request, ctx).await
+}
+
+async fn offer_flattened_board(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_flattened_board(_request, ctx).await
+}
+
+async fn list_of_board_versions(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_list_of_board_versions(_request, ctx).await
+}
+
+async fn offer_board_version(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_board_version(_request, ctx).await
+}
+
+async fn offer_program_content(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_program_content(_request, ctx).await
 }
 
-async fn offer_program_content(_request: HttpRequest) -> Result<NamedFile, Error> {
-    fetch_program_content(_request).await
+async fn offer_user_content(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_user_content(_request, ctx).await
 }
 
-async fn offer_user_content(_request: HttpRequest) -> Result<NamedFile, Error> {
-    fetch_user_content(_request).await
+async fn offer_program_thumbnail(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_program_thumbnail(_request, ctx).await
 }
 
-async fn offer_platform_content(_request: HttpRequest) -> Result<NamedFile, Error> {
+async fn offer_user_thumbnail(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    fetch_user_thumbnail(_request, ctx).await
+}
+
+async fn offer_platform_content(_request: HttpRequest) -> Result<HttpResponse, Error> {
     fetch_platform_content(_request).await
 }
 
-async fn upload_user_content(_request: HttpRequest, payload: Multipart) -> Result<HttpResponse, Error> {
-    manage_user_content(_request, payload).await
+async fn upload_user_content(_request: HttpRequest, ctx: web::Data<DBContext>, uploads: web::Data<UploadPool>, payload: Multipart) -> Result<HttpResponse, Error> {
+    let _permit = uploads.admit()?;
+    manage_user_content(_request, ctx, uploads, payload).await
+}
+
+/**
+ * The state of the upload pool, for watching the uploads against the interactive traffic.
+ */
+async fn upload_metrics(uploads: web::Data<UploadPool>) -> Result<HttpResponse, Error> {
+    let json_response = serde_json::to_string(&uploads.metrics())?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(json_response))
 }
 
 /**
@@ -76,23 +165,404 @@ async fn upload_user_content(_request: HttpRequest, payload: Multipart) -> Resul
 async fn count_feeds(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
 
     let user_id: String = _request.match_info().query("user_id").parse().unwrap();
-    
+    let request_id = request_log::request_id(&_request);
+
     let result = web::block(move || {
-        let connection = ctx.db.get().unwrap();
+        let connection = checkout(&ctx.db).map_err(serde::ser::Error::custom)?;
         let res = get_pending_feed_count(&connection, user_id.as_str());
         let json_response = serde_json::to_string(&res)?;
 
         Ok::<_, serde_json::error::Error>(json_response)
     })
     .await
-    .map_err(|e|{
-        eprintln!("{}", e);
+    .map_err(|e| {
+        tracing::error!(request_id = %request_id, error = %e, "unable to count the feeds");
         HttpResponse::InternalServerError().finish()
     })?;
 
     Ok(HttpResponse::Ok().content_type("application/json").body(result))
 }
 
+/**
+ * The mail provider posts the bounces and complaints here. The path carries the
+ * MAIL_FEEDBACK_TOKEN we shared with the provider; without it we accept nothing.
+ */
+async fn mail_feedback(_request: HttpRequest, ctx: web::Data<DBContext>, events: web::Json<Vec<MailEvent>>) -> Result<HttpResponse, Error> {
+    let token: String = _request.match_info().query("token").parse().unwrap();
+
+    let expected = dotenv::var("MAIL_FEEDBACK_TOKEN").unwrap_or_default();
+    if expected.is_empty() || token != expected {
+        return Ok(HttpResponse::Forbidden().finish());
+    }
+
+    let request_id = request_log::request_id(&_request);
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        record_mail_events(&connection, &events)
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!(request_id = %request_id, error = %e, "unable to record the mail events");
+        HttpResponse::InternalServerError().finish()
+    })?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(result.to_string()))
+}
+
+/**
+ * The sessions and tasks of the member as CSV. The member signs in with the
+ * email and password through HTTP Basic, as a download cannot carry the GraphQL.
+ */
+async fn export_timeline(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let credentials = _request
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(LoginRequest::from_basic);
+
+    let credentials = match credentials {
+        Some(value) => value,
+        None => return Ok(HttpResponse::Unauthorized().header("WWW-Authenticate", "Basic realm=\"ferris\"").finish()),
+    };
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        let member = authenticate(&connection, credentials)?;
+        get_timeline(&connection, &member)
+    })
+    .await;
+
+    let rows = match result {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::Unauthorized().header("WWW-Authenticate", "Basic realm=\"ferris\"").body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    let lines = std::iter::once(TIMELINE_HEADER.to_owned()).chain(rows.into_iter().map(|row| row.to_csv()));
+    let body = futures::stream::iter(lines.map(|line| Ok::<_, Error>(web::Bytes::from(line))));
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .header("Content-Disposition", "attachment; filename=\"timeline.csv\"")
+        .streaming(body))
+}
+
+/**
+ * The plan of an enrollment as a CSV or an Excel workbook, for its coach or its
+ * member, who sign in as for the timeline.
+ */
+async fn export_plan(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let enrollment_id: String = _request.match_info().query("enrollment_id").to_owned();
+    let format = match PlanFormat::from_extension(_request.match_info().query("format")) {
+        Some(value) => value,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+
+    let credentials = _request
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(LoginRequest::from_basic);
+
+    let credentials = match credentials {
+        Some(value) => value,
+        None => return Ok(HttpResponse::Unauthorized().header("WWW-Authenticate", "Basic realm=\"ferris\"").finish()),
+    };
+
+    let pool = ctx.clone();
+    let signed_in = web::block(move || {
+        let connection = checkout(&pool.db)?;
+        authenticate(&connection, credentials)
+    })
+    .await;
+
+    let user = match signed_in {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::Unauthorized().header("WWW-Authenticate", "Basic realm=\"ferris\"").body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    let file_name = plan_file_name(enrollment_id.as_str(), format);
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        let rows = get_plan(&connection, &user, enrollment_id.as_str())?;
+
+        match format {
+            PlanFormat::Csv => Ok(plan_csv(&rows)),
+            PlanFormat::Xlsx => plan_xlsx(&rows),
+        }
+    })
+    .await;
+
+    let content = match result {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::Forbidden().body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .header("Content-Disposition", format!("attachment; filename=\"{}\"", file_name))
+        .body(content))
+}
+
+/**
+ * The program catalog as plain JSON, for the integrators without GraphQL; see
+ * catalog_v1. It is public, as the catalog is.
+ */
+async fn list_catalog_programs(ctx: web::Data<DBContext>, query: web::Query<CatalogQuery>) -> Result<HttpResponse, Error> {
+    let window = match Window::of(Some(&query.to_page())) {
+        Ok(value) => value,
+        Err(e) => return Ok(HttpResponse::BadRequest().content_type("application/json").body(serde_json::to_string(&ErrorV1 { error: e.to_owned() })?)),
+    };
+
+    let criteria = query.to_criteria();
+    let ctx = ctx.of_caller(None).on_replica();
+
+    let result = web::block(move || ctx.services.programs.get_programs(&ctx, &criteria, &window).map_err(|e| e.message)).await;
+
+    match result {
+        Ok(page) => Ok(HttpResponse::Ok().content_type("application/json").body(serde_json::to_string(&ProgramPageV1::from(&page))?)),
+        Err(e) => {
+            tracing::error!(error = %e, "unable to list the catalog");
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct InvoiceLinkQuery {
+    expires: i64,
+    signature: String,
+}
+
+/**
+ * The PDF of an invoice, through the signed link of get_invoices or of its mail;
+ * see models::invoices. The link is the credential, as a mail cannot carry more.
+ */
+async fn download_invoice(_request: HttpRequest, ctx: web::Data<DBContext>, query: web::Query<InvoiceLinkQuery>) -> Result<HttpResponse, Error> {
+    let invoice_id: String = _request.match_info().query("invoice_id").to_owned();
+
+    if !verify_link(invoice_id.as_str(), query.expires, query.signature.as_str()) {
+        return Ok(HttpResponse::Forbidden().finish());
+    }
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        let invoice = invoices::find(&connection, invoice_id.as_str())?;
+        let local = storage().open(std::path::Path::new(invoice.file_path.as_str())).map_err(|_| "The PDF of the invoice is missing.")?;
+        let content = std::fs::read(local).map_err(|_| "The PDF of the invoice is missing.")?;
+
+        Ok::<_, &'static str>((invoice, content))
+    })
+    .await;
+
+    let (invoice, content) = match result {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::NotFound().body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .header("Cache-Control", "private, no-store")
+        .header("Content-Disposition", format!("attachment; filename=\"invoice-{}-{}.pdf\"", invoice.period_start.format("%Y-%m"), invoice.currency))
+        .body(content))
+}
+
+#[derive(Deserialize)]
+struct ImportQuery {
+    name: String,
+    description: Option<String>,
+}
+
+/**
+ * A master plan from a CSV of its tasks, posted as the body, for the coach who
+ * signs in as for the mutations; see master_plan_imports. Every mistake of the
+ * file is answered at once, with its line and column.
+ */
+async fn import_master_plan(_request: HttpRequest, ctx: web::Data<DBContext>, query: web::Query<ImportQuery>, body: web::Bytes) -> Result<HttpResponse, Error> {
+    let coach_id: String = _request.match_info().query("coach_id").to_owned();
+    let caller = agreement_gate::caller(&_request);
+
+    let plan_request = NewMasterPlanRequest {
+        name: query.name.to_owned(),
+        description: query.description.to_owned().unwrap_or_else(|| String::from("Imported from a spreadsheet.")),
+        coach_id,
+    };
+
+    let mut errors: Vec<ImportError> = plan_request.validate().iter().map(|error| ImportError::new(0, error.field.as_str(), error.message.as_str())).collect();
+
+    let rows = match String::from_utf8(body.to_vec()) {
+        Ok(content) => master_plan_imports::parse(content.as_str()).unwrap_or_else(|mistakes| {
+            errors.extend(mistakes);
+            Vec::new()
+        }),
+        Err(_) => {
+            errors.push(ImportError::new(0, "", "The file should be a CSV in UTF-8."));
+            Vec::new()
+        }
+    };
+
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().content_type("application/json").body(serde_json::to_string(&errors)?));
+    }
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db).map_err(|e| vec![ImportError::new(0, "", e)])?;
+        authorize(&connection, caller.as_ref(), Target::Coach(plan_request.coach_id.as_str()), &[Role::Coach]).map_err(|e| vec![ImportError::new(0, "", e)])?;
+        services::master_plans::import_master_plan(&connection, &plan_request, &rows)
+    })
+    .await;
+
+    match result {
+        Ok(outcome) => Ok(HttpResponse::Ok().content_type("application/json").body(serde_json::to_string(&outcome)?)),
+        Err(BlockingError::Error(errors)) => Ok(HttpResponse::BadRequest().content_type("application/json").body(serde_json::to_string(&errors)?)),
+        Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().finish()),
+    }
+}
+
+async fn master_plan_template(_request: HttpRequest) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .header("Content-Disposition", "attachment; filename=\"master-plan.csv\"")
+        .body(MASTER_PLAN_TEMPLATE)
+}
+
+#[derive(Deserialize)]
+struct FollowQuery {
+    since: Option<String>,
+    token: Option<String>,
+}
+
+fn follow_query(request: &HttpRequest) -> Option<FollowQuery> {
+    web::Query::<FollowQuery>::from_query(request.query_string()).ok().map(|query| query.into_inner())
+}
+
+// The browsers send the header on their own as they reconnect; the other clients may use the query.
+fn last_event_id(request: &HttpRequest) -> Option<String> {
+    let header = request.headers().get("Last-Event-ID").and_then(|value| value.to_str().ok()).map(String::from);
+
+    header.or_else(|| follow_query(request).and_then(|query| query.since))
+}
+
+// An EventSource of a browser cannot send the header of the session, hence the token may come as ?token=.
+fn follower(request: &HttpRequest) -> Option<UserId> {
+    agreement_gate::caller(request).or_else(|| follow_query(request).and_then(|query| query.token).and_then(|token| session_tokens::verify(token.as_str())))
+}
+
+/**
+ * The changes of the state of a session as Server-Sent Events, opening with the
+ * events missed since the last one the client got, else with its current state;
+ * see session_events. Only the coach and the members of the session may follow
+ * it. The stream stays open until the client leaves.
+ */
+async fn follow_session(_request: HttpRequest, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let session_id: SessionId = _request.match_info().query("session_id").parse().unwrap();
+    let caller = follower(&_request);
+    let events = ctx.events.clone();
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        let session = sessions::find(&connection, &session_id)?;
+        authorize(&connection, caller.as_ref(), Target::Session(session_id.as_str()), &[Role::Coach, Role::Member])?;
+        Ok::<_, &'static str>(session)
+    })
+    .await;
+
+    let session = match result {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::Forbidden().body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    let receiver = match events.subscribe(&session, last_event_id(&_request).as_deref()) {
+        Some(value) => value,
+        None => return Ok(HttpResponse::ServiceUnavailable().header("Retry-After", "30").finish()),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .streaming(receiver.map(Ok::<_, Error>)))
+}
+
+/**
+ * The counts of the feed of a user over a WebSocket, opening with the counts as
+ * they stand; see feed_events.
+ */
+async fn follow_feeds(_request: HttpRequest, payload: web::Payload, ctx: web::Data<DBContext>) -> Result<HttpResponse, Error> {
+    let user_id: String = _request.match_info().query("user_id").parse().unwrap();
+    let feeds = ctx.feeds.clone();
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        get_feed_counts(&connection, user_id.as_str())
+    })
+    .await;
+
+    let counts = match result {
+        Ok(value) => value,
+        Err(BlockingError::Error(e)) => return Ok(HttpResponse::InternalServerError().body(e)),
+        Err(BlockingError::Canceled) => return Ok(HttpResponse::InternalServerError().finish()),
+    };
+
+    let receiver = match feeds.subscribe(&counts) {
+        Some(value) => value,
+        None => return Ok(HttpResponse::ServiceUnavailable().header("Retry-After", "30").finish()),
+    };
+
+    feed_events::socket(&_request, payload, receiver)
+}
+
+/**
+ * Opens a sandbox for a visitor of the demo; see demo_mode. Not found elsewhere.
+ */
+async fn open_demo_sandbox(ctx: web::Data<DBContext>, demo: web::Data<DemoMode>) -> Result<HttpResponse, Error> {
+    if !demo.enabled {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        open_sandbox(&connection)
+    })
+    .await;
+
+    match result {
+        Ok(credentials) => Ok(HttpResponse::Ok().content_type("application/json").body(serde_json::to_string(&credentials)?)),
+        Err(BlockingError::Error(e)) => Ok(HttpResponse::ServiceUnavailable().body(e)),
+        Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().finish()),
+    }
+}
+
+/**
+ * The locale and the time zone the UI should use. Called on the login; the first
+ * call settles the Accept-Language and the zone of the client as the defaults.
+ */
+async fn detect_locale(_request: HttpRequest, ctx: web::Data<DBContext>, detection: web::Json<LocaleDetection>) -> Result<HttpResponse, Error> {
+    let user_id = UserId::from(_request.match_info().query("user_id"));
+
+    let accept_language = _request
+        .headers()
+        .get("Accept-Language")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+
+    let result = web::block(move || {
+        let connection = checkout(&ctx.db)?;
+        settle_locale(&connection, &user_id, accept_language.as_deref(), &detection)
+    })
+    .await;
+
+    match result {
+        Ok(bundle) => Ok(HttpResponse::Ok().content_type("application/json").body(serde_json::to_string(&bundle)?)),
+        Err(BlockingError::Error(e)) => Ok(HttpResponse::BadRequest().body(e)),
+        Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().finish()),
+    }
+}
 
 #[warn(unused_variables)]
 async fn index(_request: HttpRequest) -> HttpResponse {
@@ -112,16 +582,130 @@ async fn graphiql() -> HttpResponse {
  * will be blocked from accepting new connections.
  * 
  * */
-async fn graphql(ctx: web::Data<DBContext>, schema: web::Data<Arc<GQSchema>>, request: web::Json<GraphQLRequest>) -> Result<HttpResponse, Error> {
+async fn graphql(
+    http_request: HttpRequest,
+    ctx: web::Data<DBContext>,
+    schema: web::Data<Arc<GQSchema>>,
+    demo: web::Data<DemoMode>,
+    limits: web::Data<QueryLimits>,
+    persisted: web::Data<PersistedQueries>,
+    request: web::Json<serde_json::Value>,
+) -> Result<HttpResponse, Error> {
+    execute(http_request, ctx, schema, demo, limits, persisted, request.into_inner(), Vec::new()).await
+}
+
+/**
+ * The graphql-multipart-request-spec: the files of a note come with the
+ * create_note request itself; see graphql_uploads.
+ */
+#[allow(clippy::too_many_arguments)]
+async fn graphql_multipart(
+    http_request: HttpRequest,
+    ctx: web::Data<DBContext>,
+    schema: web::Data<Arc<GQSchema>>,
+    demo: web::Data<DemoMode>,
+    limits: web::Data<QueryLimits>,
+    persisted: web::Data<PersistedQueries>,
+    uploads: web::Data<UploadPool>,
+    payload: Multipart,
+) -> Result<HttpResponse, Error> {
+    let _permit = uploads.admit()?;
+    let (body, uploaded) = read_graphql_multipart(&ctx, &uploads, payload).await?;
+
+    execute(http_request, ctx, schema, demo, limits, persisted, body, uploaded).await
+}
+
+fn is_multipart(head: &RequestHead) -> bool {
+    head.headers().get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(|value| value.starts_with("multipart/form-data")).unwrap_or(false)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn execute(
+    http_request: HttpRequest,
+    ctx: web::Data<DBContext>,
+    schema: web::Data<Arc<GQSchema>>,
+    demo: web::Data<DemoMode>,
+    limits: web::Data<QueryLimits>,
+    persisted: web::Data<PersistedQueries>,
+    mut body: serde_json::Value,
+    uploaded: Vec<String>,
+) -> Result<HttpResponse, Error> {
+    // A request turned away before its execution leaves none of its files behind.
+    let refused = |response: HttpResponse| {
+        graphql_uploads::discard(&uploaded);
+        Ok(response)
+    };
+
+    // A request may name its query by a hash; see persisted_queries.
+    if let Err(refusal) = persisted.resolve(&mut body) {
+        return refused(HttpResponse::Ok().content_type("application/json").body(refusal.to_string()));
+    }
+
+    let request: GraphQLRequest = match serde_json::from_value(body.clone()) {
+        Ok(request) => request,
+        Err(_) => return refused(HttpResponse::BadRequest().finish()),
+    };
+
+    let query = body.get("query").and_then(|query| query.as_str());
+
+    // A request too deep or too large never reaches the pool; see query_limits.
+    if let Err(refusal) = query.map(|query| limits.admit(query)).unwrap_or(Ok(())) {
+        return refused(HttpResponse::Ok().content_type("application/json").body(refusal.to_string()));
+    }
+
+    // The fields are counted before the execution; see field_usage.
+    if let Some(query) = query {
+        ctx.usage.record(query, request.operation_name());
+    }
+
+    if let Err(refusal) = demo.admit(&http_request, ctx.clone(), query).await {
+        return refused(refusal);
+    }
+
+    if let Err(refusal) = agreement_gate::admit(&http_request, ctx.clone(), query).await {
+        return refused(refusal);
+    }
+
+    // The guarded mutations check what the caller may change; see commons::guard.
+    let ctx = ctx.of_caller(agreement_gate::caller(&http_request)).within(request_log::request_span(&http_request));
+    let request_id = request_log::request_id(&http_request);
+    let failed_request_id = request_id.clone();
+
+    // The mutations are read before the execution and recorded after it; see mutation_audit.
+    let mutations = match query {
+        Some(query) => mutation_audit::mutations(query, request.operation_name(), &body["variables"]),
+        None => Vec::new(),
+    };
+
+    // A query reads from the replica, when there is one; see db_manager.
+    let ctx = if mutations.is_empty() { ctx.on_replica() } else { ctx };
+
     let result = web::block(move || {
+        let _entered = ctx.span.enter();
+
+        let started = Instant::now();
         let res = request.execute(&schema, &ctx);
+
+        if request_log::is_slow(started.elapsed()) {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            tracing::warn!(request_id = %request_id, operation = request.operation_name().unwrap_or("-"), elapsed_ms, "slow graphql execution");
+        }
+
+        if !mutations.is_empty() {
+            mutation_audit::record(&ctx, &mutations, &serde_json::to_value(&res)?);
+        }
+
+        if !uploaded.is_empty() && graphql_uploads::failed(&serde_json::to_value(&res)?) {
+            graphql_uploads::discard(&uploaded);
+        }
+
         let json_response = serde_json::to_string(&res)?;
 
         Ok::<_, serde_json::error::Error>(json_response)
     })
     .await
     .map_err(|e| {
-        eprintln!("{}", e);
+        tracing::error!(request_id = %failed_request_id, error = %e, "unable to execute the graphql request");
         HttpResponse::InternalServerError().finish()
     })?;
 
@@ -130,18 +714,35 @@ async fn graphql(ctx: web::Data<DBContext>, schema: web::Data<Arc<GQSchema>>, re
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    std::env::set_var("RUST_LOG", "actix_web=info");
+    std::env::set_var("RUST_LOG", "actix_web=info,ferris=info");
     env_logger::init();
     dotenv::dotenv().ok();
 
-    std::fs::create_dir_all(SESSION_ASSET_DIR).unwrap();
-    std::fs::create_dir_all(PROGRAM_ASSET_DIR).unwrap();
-    std::fs::create_dir_all(USER_ASSET_DIR).unwrap();
-    std::fs::create_dir_all(PLATFORM_ASSET_DIR).unwrap();
+    for area in [Area::Sessions, Area::Programs, Area::Users, Area::Platform, Area::Blobs] {
+        std::fs::create_dir_all(storage().dir(area)).unwrap();
+    }
 
     let pool = establish_connection();
-    let db_context = DBContext { db: pool.clone() };
     let gq_schema = std::sync::Arc::new(create_gq_schema());
+    let usage = FieldUsage::new(FieldCatalog::of(&gq_schema));
+    jobs::start(&pool, &usage);
+
+    let db_context = DBContext {
+        db: pool.clone(),
+        reads_replica: false,
+        usage,
+        events: SessionEvents::from_env(),
+        feeds: FeedEvents::default(),
+        services: Services::backed_by_diesel(),
+        caller: None,
+        connection: RequestConnection::default(),
+        loaders: Loaders::default(),
+        span: tracing::Span::none(),
+    };
+    let upload_pool = UploadPool::from_env();
+    let demo_mode = DemoMode::from_env();
+    let query_limits = QueryLimits::from_env();
+    let persisted_queries = PersistedQueries::from_env();
 
     let bind = dotenv::var("BIND").unwrap();
     println!("Server is running at: {}", &bind);
@@ -149,22 +750,97 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         let cors = Cors::permissive();
 
+        let boards = AssetClass::Boards.policy();
+        let programs = AssetClass::Programs.policy();
+        let users = AssetClass::Users.policy();
+        let platform = AssetClass::Platform.policy();
+
+        // The assets are served with the rules of their class; see asset_policy.
+        // They are registered ahead of the API scope, which matches every other path.
         App::new()
             .data(db_context.clone())
             .data(gq_schema.clone())
-            .wrap(cors)
-            .route("graphql", web::post().to(graphql))
-            .route("graphiql", web::get().to(graphiql))
-            .route("assets/upload", web::post().to(upload_notes_file))
-            .route("assets/boards/{session_id}", web::get().to(list_of_boards))
-            .route("assets/boards/{session_id}/{filename}", web::get().to(offer_board_file))
-            .route("assets/users/{user_id}", web::post().to(upload_user_content))
-            .route("assets/users/{user_id}/{filename}", web::get().to(offer_user_content))
-            .route("assets/programs/{program_fuzzy_id}/{purpose}", web::post().to(upload_program_content))
-            .route("assets/programs/{program_fuzzy_id}/{purpose}/{filename}", web::get().to(offer_program_content))
-            .route("assets/platform/{filename}", web::get().to(offer_platform_content))
-            .route("feeds/{user_id}", web::get().to(count_feeds))
-            .route("/", web::get().to(index))
+            .data(upload_pool.clone())
+            .data(demo_mode)
+            .data(query_limits)
+            .data(persisted_queries.clone())
+            .wrap(RequestLog)
+            .service(
+                web::resource("assets/boards/{session_id}/{filename}")
+                    .wrap(boards.cors())
+                    .wrap(boards.headers())
+                    .route(web::get().to(offer_board_file)),
+            )
+            .service(
+                web::resource("assets/boards/{session_id}/{filename}/flattened/{user_id}")
+                    .wrap(boards.cors())
+                    .wrap(boards.headers())
+                    .route(web::get().to(offer_flattened_board)),
+            )
+            .service(
+                web::resource("assets/boards/{session_id}/{filename}/versions/{version}")
+                    .wrap(boards.cors())
+                    .wrap(boards.headers())
+                    .route(web::get().to(offer_board_version)),
+            )
+            .service(
+                web::resource("assets/users/{user_id}/{filename}")
+                    .wrap(users.cors())
+                    .wrap(users.headers())
+                    .route(web::get().to(offer_user_content)),
+            )
+            .service(
+                web::resource("assets/users/{user_id}/thumb/{filename}")
+                    .wrap(users.cors())
+                    .wrap(users.headers())
+                    .route(web::get().to(offer_user_thumbnail)),
+            )
+            .service(
+                web::resource("assets/programs/{program_fuzzy_id}/{purpose}/{filename}")
+                    .wrap(programs.cors())
+                    .wrap(programs.headers())
+                    .route(web::get().to(offer_program_content)),
+            )
+            .service(
+                web::resource("assets/programs/{program_fuzzy_id}/{purpose}/thumb/{filename}")
+                    .wrap(programs.cors())
+                    .wrap(programs.headers())
+                    .route(web::get().to(offer_program_thumbnail)),
+            )
+            .service(
+                web::resource("assets/platform/{filename}")
+                    .wrap(platform.cors())
+                    .wrap(platform.headers())
+                    .route(web::get().to(offer_platform_content)),
+            )
+            .service(
+                web::scope("")
+                    .wrap(cors)
+                    .route("graphql", web::post().guard(guard::fn_guard(is_multipart)).to(graphql_multipart))
+                    .route("graphql", web::post().to(graphql))
+                    .route("graphiql", web::get().to(graphiql))
+                    .route("assets/upload", web::post().to(upload_notes_file))
+                    .route("assets/uploads", web::post().to(upload_batch))
+                    .route("assets/boards/{session_id}", web::get().to(list_of_boards))
+                    .route("assets/boards/{session_id}/{filename}/versions", web::get().to(list_of_board_versions))
+                    .route("assets/users/{user_id}", web::post().to(upload_user_content))
+                    .route("assets/programs/{program_fuzzy_id}/{purpose}", web::post().to(upload_program_content))
+                    .route("feeds/{user_id}", web::get().to(count_feeds))
+                    .route("ws/feeds/{user_id}", web::get().to(follow_feeds))
+                    .route("sessions/{session_id}/events", web::get().to(follow_session))
+                    .route("metrics/uploads", web::get().to(upload_metrics))
+                    .route("exports/timeline", web::get().to(export_timeline))
+                    .route("export/plan/{enrollment_id}.{format}", web::get().to(export_plan))
+                    .route("import/master-plan/template.csv", web::get().to(master_plan_template))
+                    .route("import/master-plan/{coach_id}", web::post().to(import_master_plan))
+                    .route("exports/my-data", web::get().to(export_my_data))
+                    .route("invoices/{invoice_id}.pdf", web::get().to(download_invoice))
+                    .route("api/v1/programs", web::get().to(list_catalog_programs))
+                    .route("mails/feedback/{token}", web::post().to(mail_feedback))
+                    .route("demo/sandboxes", web::post().to(open_demo_sandbox))
+                    .route("users/{user_id}/locale", web::post().to(detect_locale))
+                    .route("/", web::get().to(index)),
+            )
     })
     .bind(&bind)?
     .run()
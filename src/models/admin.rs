@@ -0,0 +1,136 @@
+/**
+ * The surface of the platform operators: the users at large, blocking and
+ * unblocking them, taking a program off the catalog and the health of the
+ * platform in numbers. Every operation is for a signed in admin alone.
+ */
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::util;
+use crate::models::users::{AccountTargetState, ChangeAccountStateRequest};
+
+const MAX_REASON: usize = 1000;
+
+/**
+ * The filters are combined; the text is looked for in the name and the email.
+ */
+#[derive(juniper::GraphQLInputObject, Default)]
+pub struct UserFilter {
+    pub user_type: Option<String>,
+    pub blocked: Option<bool>,
+    pub text: Option<String>,
+}
+
+impl UserFilter {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match self.user_type.as_deref() {
+            None | Some(util::MEMBER) | Some(util::COACH) | Some(util::ADMIN) => Ok(()),
+            Some(_) => Err("The user type should be member, coach or admin."),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct BlockUserRequest {
+    pub user_id: UserId,
+    pub blocked: bool,
+    pub reason: String,
+}
+
+impl BlockUserRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        if self.reason.chars().count() > MAX_REASON {
+            errors.push(ValidationError::new("reason", "The reason may have at most 1000 characters."));
+        }
+
+        errors
+    }
+
+    pub fn as_state_change(&self) -> ChangeAccountStateRequest {
+        let target_state = if self.blocked { AccountTargetState::DEACTIVATE } else { AccountTargetState::REACTIVATE };
+
+        ChangeAccountStateRequest {
+            user_id: self.user_id.clone(),
+            target_state,
+            reason: self.reason.to_owned(),
+        }
+    }
+}
+
+/**
+ * The program is taken off whatever its coach wants; the reason goes to the audit trail.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ForceDeactivateRequest {
+    pub program_id: ProgramId,
+    pub reason: String,
+}
+
+impl ForceDeactivateRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        let length = self.reason.trim().chars().count();
+        if length == 0 || length > MAX_REASON {
+            errors.push(ValidationError::new("reason", "The reason should have 1 to 1000 characters."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLObject, Debug, Default, PartialEq)]
+#[graphql(description = "The health of the platform in numbers, as of now")]
+pub struct SystemStats {
+    pub users: i32,
+    pub members: i32,
+    pub coaches: i32,
+    pub blocked_users: i32,
+    pub programs: i32,
+    pub active_programs: i32,
+    pub active_enrollments: i32,
+    pub upcoming_sessions: i32,
+    pub completed_sessions: i32,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_know_the_user_types() {
+        assert!(UserFilter::default().validate().is_ok());
+
+        let filter = UserFilter {
+            user_type: Some(String::from("coach")),
+            ..UserFilter::default()
+        };
+        assert!(filter.validate().is_ok());
+
+        let filter = UserFilter {
+            user_type: Some(String::from("root")),
+            ..UserFilter::default()
+        };
+        assert!(filter.validate().is_err());
+    }
+
+    #[test]
+    fn should_need_a_reason_to_take_a_program_off() {
+        let request = ForceDeactivateRequest {
+            program_id: ProgramId::from("p-1"),
+            reason: String::from("  "),
+        };
+
+        assert_eq!(1, request.validate().len());
+    }
+}
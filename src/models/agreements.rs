@@ -0,0 +1,186 @@
+/**
+ * The terms of service and the other agreements of the platform. A new version
+ * is published as a new agreement; the latest required one must be accepted by
+ * every user before the user may do anything else, see agreement_gate.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::schema::agreement_acceptances;
+use crate::schema::agreements;
+
+const MAX_VERSION: usize = 20;
+const MAX_TITLE: usize = 255;
+
+#[derive(Queryable, Debug)]
+pub struct Agreement {
+    pub id: String,
+    pub version: String,
+    pub title: String,
+    pub body: String,
+    pub is_required: bool,
+    pub published_by_id: String,
+    pub published_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A version of the terms the users agree to")]
+impl Agreement {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn version(&self) -> &str {
+        self.version.as_str()
+    }
+
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn body(&self) -> &str {
+        self.body.as_str()
+    }
+
+    #[graphql(description = "A required agreement must be accepted before using the platform")]
+    pub fn is_required(&self) -> bool {
+        self.is_required
+    }
+
+    pub fn published_by_id(&self) -> &str {
+        self.published_by_id.as_str()
+    }
+
+    pub fn published_at(&self) -> NaiveDateTime {
+        self.published_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "agreements"]
+pub struct NewAgreement {
+    pub id: String,
+    pub version: String,
+    pub title: String,
+    pub body: String,
+    pub is_required: bool,
+    pub published_by_id: String,
+}
+
+impl NewAgreement {
+    pub fn from(request: &NewAgreementRequest, admin_id: &UserId) -> NewAgreement {
+        NewAgreement {
+            id: util::fuzzy_id(),
+            version: request.version.trim().to_owned(),
+            title: request.title.trim().to_owned(),
+            body: request.body.trim().to_owned(),
+            is_required: request.is_required,
+            published_by_id: admin_id.to_string(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "agreement_acceptances"]
+pub struct NewAcceptance {
+    pub user_id: String,
+    pub agreement_id: String,
+}
+
+// The admin publishing the agreement is the signed in user, never an id in the request.
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewAgreementRequest {
+    pub version: String,
+    pub title: String,
+    pub body: String,
+    pub is_required: bool,
+}
+
+impl NewAgreementRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        let version = self.version.trim();
+        if version.is_empty() || version.len() > MAX_VERSION {
+            errors.push(ValidationError::new("version", "The version should have 1 to 20 characters."));
+        }
+
+        let title = self.title.trim();
+        if title.is_empty() || title.chars().count() > MAX_TITLE {
+            errors.push(ValidationError::new("title", "The title should have 1 to 255 characters."));
+        }
+
+        if self.body.trim().is_empty() {
+            errors.push(ValidationError::new("body", "The text of the agreement is a must."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AcceptAgreementRequest {
+    pub user_id: UserId,
+    pub agreement_id: String,
+}
+
+impl AcceptAgreementRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        if self.agreement_id.trim().is_empty() {
+            errors.push(ValidationError::new("agreement_id", "Agreement id is a must."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The latest required agreement and whether the user has accepted it.
+ */
+pub struct AgreementStatus {
+    pub agreement: Option<Agreement>,
+    pub accepted_at: Option<NaiveDateTime>,
+}
+
+#[juniper::object(description = "The latest required agreement as it stands for a user")]
+impl AgreementStatus {
+    pub fn agreement(&self) -> Option<&Agreement> {
+        self.agreement.as_ref()
+    }
+
+    pub fn accepted_at(&self) -> Option<NaiveDateTime> {
+        self.accepted_at
+    }
+
+    #[graphql(description = "True when the user should accept the agreement before going on")]
+    pub fn is_pending(&self) -> bool {
+        self.agreement.is_some() && self.accepted_at.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_need_a_version_a_title_and_a_text() {
+        let request = NewAgreementRequest {
+            version: String::from("2021.1 and some more words"),
+            title: String::from(" "),
+            body: String::from(""),
+            is_required: true,
+        };
+
+        let fields: Vec<String> = request.validate().into_iter().map(|error| error.field).collect();
+
+        assert_eq!(vec!["version", "title", "body"], fields);
+    }
+}
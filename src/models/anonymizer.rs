@@ -0,0 +1,9 @@
+/**
+ * Anonymizing rewrites the personal data in place. It is meant for a staging
+ * database restored from production, and never for production itself. The
+ * admin is the signed in user, never an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct AnonymizeRequest {
+    pub with_files: bool,
+}
@@ -0,0 +1,119 @@
+/**
+ * An audit event is a permanent record of who changed what.
+ *
+ * The before and after states are kept as JSON text, so that we can
+ * reconstruct the change without depending on the shape of the entity.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::util;
+use crate::schema::audit_events;
+
+#[derive(Insertable)]
+#[table_name = "audit_events"]
+pub struct NewAuditEvent {
+    pub id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub actor_id: String,
+    pub before_state: Option<String>,
+    pub after_state: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl NewAuditEvent {
+    pub fn from(entity_type: &str, entity_id: &str, action: &str, actor_id: &str) -> NewAuditEvent {
+        let fuzzy_id = util::fuzzy_id();
+
+        NewAuditEvent {
+            id: fuzzy_id,
+            entity_type: entity_type.to_owned(),
+            entity_id: entity_id.to_owned(),
+            action: action.to_owned(),
+            actor_id: actor_id.to_owned(),
+            before_state: None,
+            after_state: None,
+            reason: None,
+        }
+    }
+
+    pub fn with_states(mut self, before: &serde_json::Value, after: &serde_json::Value) -> NewAuditEvent {
+        self.before_state = Some(before.to_string());
+        self.after_state = Some(after.to_string());
+        self
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> NewAuditEvent {
+        self.reason = Some(reason.to_owned());
+        self
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct AuditEvent {
+    pub id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub actor_id: String,
+    pub before_state: Option<String>,
+    pub after_state: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AuditTrailCriteria {
+    pub entity_id: String,
+}
+
+pub struct AuditEntry {
+    pub event: AuditEvent,
+    pub actor_name: String,
+}
+
+#[juniper::object(description = "A change to an entity, with who made it and the states around it")]
+impl AuditEntry {
+    pub fn id(&self) -> &str {
+        self.event.id.as_str()
+    }
+
+    pub fn entity_type(&self) -> &str {
+        self.event.entity_type.as_str()
+    }
+
+    pub fn entity_id(&self) -> &str {
+        self.event.entity_id.as_str()
+    }
+
+    pub fn action(&self) -> &str {
+        self.event.action.as_str()
+    }
+
+    pub fn actor_id(&self) -> &str {
+        self.event.actor_id.as_str()
+    }
+
+    pub fn actor_name(&self) -> &str {
+        self.actor_name.as_str()
+    }
+
+    #[graphql(description = "The state the previous change left, as JSON")]
+    pub fn before_state(&self) -> Option<&String> {
+        self.event.before_state.as_ref()
+    }
+
+    #[graphql(description = "The state this change left, as JSON")]
+    pub fn after_state(&self) -> Option<&String> {
+        self.event.after_state.as_ref()
+    }
+
+    pub fn reason(&self) -> Option<&String> {
+        self.event.reason.as_ref()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.event.created_at
+    }
+}
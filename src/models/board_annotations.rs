@@ -0,0 +1,239 @@
+/**
+ * The annotations a member or a coach draws over a board image. The image is
+ * never touched; each author keeps one overlay per board, as a JSON list of
+ * shapes in the pixel space of the image.
+ *
+ * [
+ *   {"kind": "arrow", "x1": 10, "y1": 10, "x2": 120, "y2": 80, "color": "#d0021b"},
+ *   {"kind": "text", "x": 125, "y": 90, "text": "Look here", "color": "#000000"}
+ * ]
+ */
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::commons::util;
+use crate::schema::board_annotations;
+
+const MAX_SHAPES: usize = 200;
+const MAX_TEXT: usize = 200;
+
+// The length and the half width of an arrow head, in pixels.
+const HEAD_LENGTH: f64 = 14.0;
+const HEAD_WIDTH: f64 = 6.0;
+
+const UNREADABLE_OVERLAY: &str = "The overlay should be a list of arrows and texts.";
+const TOO_MANY_SHAPES: &str = "An overlay may have at most 200 shapes.";
+const INVALID_SHAPE: &str = "Each shape needs non negative coordinates and a color like #1a2b3c.";
+const INVALID_TEXT: &str = "A text should have 1 to 200 characters.";
+
+#[derive(Queryable, Debug, Identifiable, Clone)]
+pub struct BoardAnnotation {
+    pub id: String,
+    pub board_id: String,
+    pub board_name: String,
+    pub created_by_id: String,
+    pub overlay: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The vector overlay an author keeps over a board image")]
+impl BoardAnnotation {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn board_id(&self) -> &str {
+        self.board_id.as_str()
+    }
+
+    pub fn board_name(&self) -> &str {
+        self.board_name.as_str()
+    }
+
+    pub fn created_by_id(&self) -> &str {
+        self.created_by_id.as_str()
+    }
+
+    pub fn overlay(&self) -> &str {
+        self.overlay.as_str()
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Shape {
+    Arrow { x1: f64, y1: f64, x2: f64, y2: f64, color: String },
+    Text { x: f64, y: f64, text: String, color: String },
+}
+
+fn is_color(value: &str) -> bool {
+    value.len() == 7 && value.starts_with('#') && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_position(values: &[f64]) -> bool {
+    values.iter().all(|value| value.is_finite() && *value >= 0.0)
+}
+
+impl Shape {
+    fn check(&self) -> Result<(), &'static str> {
+        let (positioned, color) = match self {
+            Shape::Arrow { x1, y1, x2, y2, color } => (is_position(&[*x1, *y1, *x2, *y2]), color),
+            Shape::Text { x, y, text, color } => {
+                let length = text.trim().chars().count();
+                if length == 0 || length > MAX_TEXT {
+                    return Err(INVALID_TEXT);
+                }
+                (is_position(&[*x, *y]), color)
+            }
+        };
+
+        if !positioned || !is_color(color) {
+            return Err(INVALID_SHAPE);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * The ImageMagick -draw primitives of the shape, used while flattening a board.
+     */
+    pub fn draw_args(&self) -> Vec<String> {
+        match self {
+            Shape::Arrow { x1, y1, x2, y2, color } => {
+                let angle = (y2 - y1).atan2(x2 - x1);
+                let (base_x, base_y) = (x2 - HEAD_LENGTH * angle.cos(), y2 - HEAD_LENGTH * angle.sin());
+                let (left_x, left_y) = (base_x + HEAD_WIDTH * angle.sin(), base_y - HEAD_WIDTH * angle.cos());
+                let (right_x, right_y) = (base_x - HEAD_WIDTH * angle.sin(), base_y + HEAD_WIDTH * angle.cos());
+
+                vec![
+                    String::from("-stroke"),
+                    color.to_owned(),
+                    String::from("-strokewidth"),
+                    String::from("3"),
+                    String::from("-fill"),
+                    color.to_owned(),
+                    String::from("-draw"),
+                    format!("line {:.0},{:.0} {:.0},{:.0}", x1, y1, base_x, base_y),
+                    String::from("-draw"),
+                    format!("polygon {:.0},{:.0} {:.0},{:.0} {:.0},{:.0}", x2, y2, left_x, left_y, right_x, right_y),
+                ]
+            }
+            Shape::Text { x, y, text, color } => vec![
+                String::from("-stroke"),
+                String::from("none"),
+                String::from("-fill"),
+                color.to_owned(),
+                String::from("-pointsize"),
+                String::from("18"),
+                String::from("-draw"),
+                format!("text {:.0},{:.0} '{}'", x, y, text.replace('\\', "\\\\").replace('\'', "\\'")),
+            ],
+        }
+    }
+}
+
+pub fn parse_overlay(overlay: &str) -> Result<Vec<Shape>, &'static str> {
+    let shapes: Vec<Shape> = serde_json::from_str(overlay).map_err(|_| UNREADABLE_OVERLAY)?;
+
+    if shapes.len() > MAX_SHAPES {
+        return Err(TOO_MANY_SHAPES);
+    }
+
+    for shape in &shapes {
+        shape.check()?;
+    }
+
+    Ok(shapes)
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SaveAnnotationRequest {
+    pub user_id: String,
+    pub session_id: SessionId,
+    pub board_name: String,
+    pub overlay: String,
+}
+
+impl SaveAnnotationRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        if self.board_name.trim().is_empty() {
+            errors.push(ValidationError::new("board_name", "Name of the board is a must."));
+        }
+
+        if let Err(e) = parse_overlay(self.overlay.as_str()) {
+            errors.push(ValidationError::new("overlay", e));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AnnotationCriteria {
+    pub user_id: String,
+    pub session_id: SessionId,
+    pub board_name: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "board_annotations"]
+pub struct NewBoardAnnotation {
+    pub id: String,
+    pub board_id: String,
+    pub board_name: String,
+    pub created_by_id: String,
+    pub overlay: String,
+}
+
+impl NewBoardAnnotation {
+    pub fn from(request: &SaveAnnotationRequest, the_board_id: &str) -> NewBoardAnnotation {
+        let fuzzy_id = util::fuzzy_id();
+
+        NewBoardAnnotation {
+            id: fuzzy_id,
+            board_id: the_board_id.to_owned(),
+            board_name: request.board_name.to_owned(),
+            created_by_id: request.user_id.to_owned(),
+            overlay: request.overlay.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_read_arrows_and_texts() {
+        let shapes = parse_overlay(r##"[{"kind": "arrow", "x1": 0, "y1": 0, "x2": 100, "y2": 0, "color": "#d0021b"}, {"kind": "text", "x": 5, "y": 5, "text": "It's here", "color": "#000000"}]"##).unwrap();
+
+        assert_eq!(2, shapes.len());
+        assert!(shapes[0].draw_args().contains(&String::from("polygon 100,0 86,-6 86,6")));
+        assert!(shapes[1].draw_args().contains(&String::from("text 5,5 'It\\'s here'")));
+    }
+
+    #[test]
+    fn should_reject_unsafe_colors_and_positions() {
+        assert_eq!(Err(INVALID_SHAPE), parse_overlay(r#"[{"kind": "text", "x": 5, "y": 5, "text": "a", "color": "red' -draw"}]"#));
+        assert_eq!(Err(INVALID_SHAPE), parse_overlay(r##"[{"kind": "arrow", "x1": -1, "y1": 0, "x2": 1, "y2": 1, "color": "#000000"}]"##));
+        assert_eq!(Err(UNREADABLE_OVERLAY), parse_overlay(r#"[{"kind": "circle"}]"#));
+    }
+}
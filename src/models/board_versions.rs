@@ -0,0 +1,143 @@
+/**
+ * Every upload of a board is kept as a version of it, numbered from 1, so that
+ * a coach may replay how the board evolved during the session. The board itself
+ * is always the latest version; the earlier ones live beside the boards.
+ *
+ *   {sessions}/{board_id}/boards/{board_name}                   the latest
+ *   {sessions}/{board_id}/board_versions/{board_name}/v{n}.png  each version
+ *
+ * A version shares the blob of its content with the board, see content_store.
+ */
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::commons::util;
+use crate::schema::board_versions;
+
+pub const VERSIONS_DIR: &str = "board_versions";
+
+#[derive(Queryable, Debug, Identifiable, Clone)]
+pub struct BoardVersion {
+    pub id: String,
+    pub board_id: String,
+    pub board_name: String,
+    pub version: i32,
+    pub file_path: String,
+    pub checksum: String,
+    pub size: i64,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "An upload of a board, in the order the board evolved")]
+impl BoardVersion {
+    pub fn board_id(&self) -> &str {
+        self.board_id.as_str()
+    }
+
+    pub fn board_name(&self) -> &str {
+        self.board_name.as_str()
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn file_name(&self) -> String {
+        version_file_name(self.board_name.as_str(), self.version)
+    }
+
+    pub fn size(&self) -> i32 {
+        self.size as i32
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+/**
+ * The version as the list_board_versions route answers it.
+ */
+#[derive(Serialize, Debug)]
+pub struct VersionEntry {
+    pub version: i32,
+    pub file_name: String,
+    pub size: i64,
+    pub created_at: String,
+}
+
+impl VersionEntry {
+    pub fn from(version: &BoardVersion) -> VersionEntry {
+        VersionEntry {
+            version: version.version,
+            file_name: version_file_name(version.board_name.as_str(), version.version),
+            size: version.size,
+            created_at: version.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }
+    }
+}
+
+/**
+ * v3.png for the third version of a png board; the extension keeps the type of
+ * the file when it is served.
+ */
+pub fn version_file_name(board_name: &str, version: i32) -> String {
+    match Path::new(board_name).extension().and_then(|value| value.to_str()) {
+        Some(extension) => format!("v{}.{}", version, extension),
+        None => format!("v{}", version),
+    }
+}
+
+/**
+ * The directory of the versions of a board, given the directory of the boards.
+ */
+pub fn versions_dir(board_dir: &Path, board_name: &str) -> PathBuf {
+    let mut dir = board_dir.to_path_buf();
+    dir.set_file_name(VERSIONS_DIR);
+    dir.push(board_name);
+
+    dir
+}
+
+#[derive(Insertable)]
+#[table_name = "board_versions"]
+pub struct NewBoardVersion {
+    pub id: String,
+    pub board_id: String,
+    pub board_name: String,
+    pub version: i32,
+    pub file_path: String,
+    pub checksum: String,
+    pub size: i64,
+}
+
+impl NewBoardVersion {
+    pub fn of(the_board_id: &str, the_board_name: &str, the_version: i32, the_file_path: &str, the_checksum: &str, the_size: usize) -> NewBoardVersion {
+        NewBoardVersion {
+            id: util::fuzzy_id(),
+            board_id: the_board_id.to_owned(),
+            board_name: the_board_name.to_owned(),
+            version: the_version,
+            file_path: the_file_path.to_owned(),
+            checksum: the_checksum.to_owned(),
+            size: the_size as i64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_keep_the_versions_beside_the_boards() {
+        assert_eq!("v3.png", version_file_name("board 1.png", 3));
+        assert_eq!("v1", version_file_name("board", 1));
+        assert_eq!(
+            PathBuf::from("/assets/sessions/s-1/board_versions/b.png"),
+            versions_dir(Path::new("/assets/sessions/s-1/boards"), "b.png")
+        );
+    }
+}
@@ -0,0 +1,152 @@
+/**
+ * The program catalog for the integrators that do not speak GraphQL, served as
+ * JSON at /api/v1/programs. It is the EXPLORE desire of get_programs: only the
+ * published, public programs of the active coaches, narrowed by a tag and a coach,
+ * a page at a time with the cursors of the GraphQL pages.
+ *
+ * The shapes below are the contract of the version 1 and are kept apart from the
+ * models, so that a change of a table never changes them; a field is only ever
+ * added here. A change that would break a client is a new version at a path of
+ * its own.
+ *
+ * The times are written in UTC, as they are kept.
+ */
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::commons::chassis::{Page, PageRequest};
+use crate::models::coach_profiles::from_tags;
+use crate::models::user_programs::{ProgramCriteria, ProgramRow};
+
+#[derive(Serialize, Debug)]
+pub struct CoachV1 {
+    pub id: String,
+    pub full_name: String,
+    pub headline: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProgramV1 {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub genre_id: Option<String>,
+    pub tags: Vec<String>,
+    pub max_members: Option<i32>,
+    pub price_cents: Option<i32>,
+    pub currency: Option<String>,
+    pub average_rating: Option<f64>,
+    pub review_count: i32,
+    pub coach: CoachV1,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl ProgramV1 {
+    pub fn from(row: &ProgramRow) -> ProgramV1 {
+        let program = &row.program;
+
+        ProgramV1 {
+            id: program.id.to_string(),
+            name: program.name.to_owned(),
+            description: program.description_text.to_owned(),
+            genre_id: program.genre_id.to_owned(),
+            tags: from_tags(program.tags.as_str()),
+            max_members: program.max_members,
+            price_cents: program.price_cents.filter(|_| program.is_paid()),
+            currency: program.currency.to_owned().filter(|_| program.is_paid()),
+            average_rating: row.average_rating,
+            review_count: row.review_count,
+            coach: CoachV1 {
+                id: row.coach.id.to_owned(),
+                full_name: row.coach.full_name.to_owned(),
+                headline: row.coach_profile.as_ref().map(|profile| profile.headline.to_owned()).filter(|headline| !headline.is_empty()),
+            },
+            created_at: stamp(program.created_at),
+            updated_at: stamp(program.updated_at),
+        }
+    }
+}
+
+/**
+ * A page of the catalog; the next_cursor, when there is one, fetches the page
+ * after it.
+ */
+#[derive(Serialize, Debug)]
+pub struct ProgramPageV1 {
+    pub programs: Vec<ProgramV1>,
+    pub next_cursor: Option<String>,
+}
+
+impl ProgramPageV1 {
+    pub fn from(page: &Page<ProgramRow>) -> ProgramPageV1 {
+        ProgramPageV1 {
+            programs: page.items.iter().map(ProgramV1::from).collect(),
+            next_cursor: page.next_cursor(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ErrorV1 {
+    pub error: String,
+}
+
+/**
+ * The query string of the catalog, like ?tag=rust&coach_id=c-1&limit=20&cursor=o20.
+ */
+#[derive(Deserialize, Debug, Default)]
+pub struct CatalogQuery {
+    pub tag: Option<String>,
+    pub coach_id: Option<String>,
+    pub limit: Option<i32>,
+    pub cursor: Option<String>,
+}
+
+impl CatalogQuery {
+    pub fn to_criteria(&self) -> ProgramCriteria {
+        let given = |value: &Option<String>| value.as_ref().map(|value| value.trim().to_owned()).filter(|value| !value.is_empty());
+
+        ProgramCriteria::explore(given(&self.tag), given(&self.coach_id))
+    }
+
+    pub fn to_page(&self) -> PageRequest {
+        PageRequest {
+            limit: self.limit,
+            offset: None,
+            cursor: self.cursor.to_owned(),
+        }
+    }
+}
+
+fn stamp(at: NaiveDateTime) -> String {
+    at.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::commons::chassis::Window;
+
+    #[test]
+    fn should_read_the_catalog_query() {
+        let query = CatalogQuery {
+            tag: Some(String::from("  ")),
+            coach_id: Some(String::from(" c-1 ")),
+            limit: Some(20),
+            cursor: Some(String::from("o40")),
+        };
+
+        assert_eq!(Ok(Window { offset: 40, limit: 20 }), Window::of(Some(&query.to_page())));
+        assert_eq!(Ok(Window { offset: 0, limit: 100 }), Window::of(Some(&CatalogQuery::default().to_page())));
+        assert!(Window::of(Some(&CatalogQuery { limit: Some(0), ..CatalogQuery::default() }.to_page())).is_err());
+    }
+
+    #[test]
+    fn should_write_the_times_in_utc() {
+        let at = chrono::NaiveDate::from_ymd(2021, 3, 11).and_hms(9, 5, 7);
+
+        assert_eq!("2021-03-11T09:05:07Z", stamp(at));
+    }
+}
@@ -0,0 +1,498 @@
+/**
+ * When a coach takes sessions: the weekly windows, in the local time of the
+ * coach by the utc_offset of the coach, and the exceptions to them in UTC, a
+ * day off or hours given besides.
+ *
+ * The slots offered to book are the windows, with the hours given added and the
+ * days off and the sessions of the coach taken away. A coach who set no
+ * availability takes sessions at any time, as before.
+ */
+use chrono::{Datelike, Duration, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::util;
+use crate::schema::{coach_availability, coach_availability_exceptions};
+
+const DATE_TIME_PATTERN: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+const DAY_MINUTES: i32 = 24 * 60;
+const MAX_WINDOWS: usize = 50;
+const MAX_EXCEPTIONS: usize = 100;
+const MAX_EXCEPTION_DAYS: i64 = 90;
+const MAX_SLOT_DAYS: i64 = 31;
+const MAX_REASON: usize = 255;
+
+const BAD_TIME: &str = "The times should be like 2021-03-15T09:00:00Z.";
+
+pub const OUTSIDE_AVAILABILITY: &str = "The session falls outside the availability of the coach or over another session of the coach. Please provide a reason to override.";
+
+#[derive(Queryable, Debug, Clone)]
+pub struct AvailabilityWindow {
+    pub id: String,
+    pub weekday: i32,
+    pub from_minute: i32,
+    pub to_minute: i32,
+}
+
+#[juniper::object(description = "A weekly window a coach takes sessions in, in the local time of the coach")]
+impl AvailabilityWindow {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    #[graphql(description = "0 for Monday to 6 for Sunday")]
+    pub fn weekday(&self) -> i32 {
+        self.weekday
+    }
+
+    #[graphql(description = "The time the window opens, like 09:00")]
+    pub fn from(&self) -> String {
+        format_minute(self.from_minute)
+    }
+
+    #[graphql(description = "The time the window closes, like 17:30; 24:00 is the end of the day")]
+    pub fn to(&self) -> String {
+        format_minute(self.to_minute)
+    }
+}
+
+#[derive(Queryable, Debug, Clone)]
+pub struct AvailabilityException {
+    pub id: String,
+    pub from_date: NaiveDateTime,
+    pub to_date: NaiveDateTime,
+    pub is_available: bool,
+    pub reason: String,
+}
+
+#[juniper::object(description = "A span departing from the weekly windows of a coach, in UTC")]
+impl AvailabilityException {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn from_date(&self) -> NaiveDateTime {
+        self.from_date
+    }
+
+    pub fn to_date(&self) -> NaiveDateTime {
+        self.to_date
+    }
+
+    #[graphql(description = "True for hours given besides the windows, false for time off")]
+    pub fn is_available(&self) -> bool {
+        self.is_available
+    }
+
+    pub fn reason(&self) -> &str {
+        self.reason.as_str()
+    }
+}
+
+pub struct Availability {
+    pub coach_id: String,
+    pub windows: Vec<AvailabilityWindow>,
+    pub exceptions: Vec<AvailabilityException>,
+}
+
+#[juniper::object(description = "The weekly windows of a coach and the exceptions to them")]
+impl Availability {
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn windows(&self) -> &Vec<AvailabilityWindow> {
+        &self.windows
+    }
+
+    pub fn exceptions(&self) -> &Vec<AvailabilityException> {
+        &self.exceptions
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct WindowRequest {
+    pub weekday: i32,
+    pub from: String,
+    pub to: String,
+}
+
+/**
+ * The times are in UTC, like 2021-03-15T09:00:00Z.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ExceptionRequest {
+    pub from_date: String,
+    pub to_date: String,
+    pub is_available: bool,
+    pub reason: Option<String>,
+}
+
+/**
+ * The whole availability is saved at once, in place of the one before.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct AvailabilityRequest {
+    pub coach_id: String,
+    pub windows: Vec<WindowRequest>,
+    pub exceptions: Vec<ExceptionRequest>,
+}
+
+impl AvailabilityRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.windows.len() > MAX_WINDOWS {
+            errors.push(ValidationError::new("windows", "A coach may have at most 50 weekly windows."));
+        }
+
+        if self.windows.iter().any(|window| window_minutes(window).is_none()) {
+            errors.push(ValidationError::new("windows", "A window should be on a weekday from 0 to 6 and close after it opens, the times being like 09:00."));
+        }
+
+        if self.exceptions.len() > MAX_EXCEPTIONS {
+            errors.push(ValidationError::new("exceptions", "A coach may have at most 100 exceptions."));
+        }
+
+        for exception in &self.exceptions {
+            match exception_range(exception) {
+                Err(e) => errors.push(ValidationError::new("exceptions", e)),
+                Ok((from, to)) if to <= from => errors.push(ValidationError::new("exceptions", "An exception should end after its start.")),
+                Ok((from, to)) if to - from > Duration::days(MAX_EXCEPTION_DAYS) => errors.push(ValidationError::new("exceptions", "An exception should be of at most 90 days.")),
+                Ok(_) => {}
+            }
+
+            if exception.reason.as_deref().unwrap_or_default().trim().chars().count() > MAX_REASON {
+                errors.push(ValidationError::new("exceptions", "The reason may have at most 255 characters."));
+            }
+        }
+
+        errors
+    }
+
+    /**
+     * The rows of a valid request; the invalid windows and exceptions are left out.
+     */
+    pub fn to_rows(&self) -> (Vec<NewAvailabilityWindow>, Vec<NewAvailabilityException>) {
+        let windows = self
+            .windows
+            .iter()
+            .filter_map(|window| window_minutes(window).map(|(from, to)| (window.weekday, from, to)))
+            .map(|(weekday, from_minute, to_minute)| NewAvailabilityWindow {
+                id: util::fuzzy_id(),
+                coach_id: self.coach_id.to_owned(),
+                weekday,
+                from_minute,
+                to_minute,
+            })
+            .collect();
+
+        let exceptions = self
+            .exceptions
+            .iter()
+            .filter_map(|exception| exception_range(exception).ok().map(|range| (exception, range)))
+            .map(|(exception, (from_date, to_date))| NewAvailabilityException {
+                id: util::fuzzy_id(),
+                coach_id: self.coach_id.to_owned(),
+                from_date,
+                to_date,
+                is_available: exception.is_available,
+                reason: exception.reason.as_deref().unwrap_or_default().trim().to_owned(),
+            })
+            .collect();
+
+        (windows, exceptions)
+    }
+}
+
+fn window_minutes(window: &WindowRequest) -> Option<(i32, i32)> {
+    if !(0..7).contains(&window.weekday) {
+        return None;
+    }
+
+    match (parse_minute(window.from.as_str()), parse_minute(window.to.as_str())) {
+        (Some(from), Some(to)) if from < to => Some((from, to)),
+        _ => None,
+    }
+}
+
+fn exception_range(exception: &ExceptionRequest) -> Result<(NaiveDateTime, NaiveDateTime), &'static str> {
+    let from = parse_time(exception.from_date.as_str()).ok_or(BAD_TIME)?;
+    let to = parse_time(exception.to_date.as_str()).ok_or(BAD_TIME)?;
+
+    Ok((from, to))
+}
+
+// From 00:00 to 24:00, the end of the day.
+fn parse_minute(value: &str) -> Option<i32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours = hours.parse::<i32>().ok().filter(|hours| (0..=24).contains(hours))?;
+    let minutes = minutes.parse::<i32>().ok().filter(|minutes| (0..60).contains(minutes))?;
+
+    Some(hours * 60 + minutes).filter(|minute| *minute <= DAY_MINUTES)
+}
+
+fn format_minute(minute: i32) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+pub fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim(), DATE_TIME_PATTERN).ok()
+}
+
+/**
+ * The span the free slots are asked for, of at most 31 days.
+ */
+pub fn slot_range(from: &str, to: &str) -> Result<(NaiveDateTime, NaiveDateTime), &'static str> {
+    let from = parse_time(from).ok_or(BAD_TIME)?;
+    let to = parse_time(to).ok_or(BAD_TIME)?;
+
+    if to <= from || to - from > Duration::days(MAX_SLOT_DAYS) {
+        return Err("The slots are given for a span of up to 31 days, ending after its start.");
+    }
+
+    Ok((from, to))
+}
+
+#[derive(Insertable)]
+#[table_name = "coach_availability"]
+pub struct NewAvailabilityWindow {
+    pub id: String,
+    pub coach_id: String,
+    pub weekday: i32,
+    pub from_minute: i32,
+    pub to_minute: i32,
+}
+
+#[derive(Insertable)]
+#[table_name = "coach_availability_exceptions"]
+pub struct NewAvailabilityException {
+    pub id: String,
+    pub coach_id: String,
+    pub from_date: NaiveDateTime,
+    pub to_date: NaiveDateTime,
+    pub is_available: bool,
+    pub reason: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slot {
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A free span of a coach to book a session in, in UTC")]
+impl Slot {
+    pub fn starts_at(&self) -> NaiveDateTime {
+        self.starts_at
+    }
+
+    pub fn ends_at(&self) -> NaiveDateTime {
+        self.ends_at
+    }
+
+    pub fn minutes(&self) -> i32 {
+        (self.ends_at - self.starts_at).num_minutes() as i32
+    }
+}
+
+type Span = (NaiveDateTime, NaiveDateTime);
+
+impl Availability {
+    /**
+     * A coach with neither a window nor hours given has not set the availability.
+     */
+    pub fn is_set(&self) -> bool {
+        !self.windows.is_empty() || self.exceptions.iter().any(|exception| exception.is_available)
+    }
+
+    /**
+     * The free slots between the two times, the busy spans taken away; the
+     * utc_offset, in minutes, is that of the coach.
+     */
+    pub fn free_slots(&self, busy: &[Span], utc_offset: i32, from: NaiveDateTime, to: NaiveDateTime) -> Vec<Slot> {
+        let offset = Duration::minutes(utc_offset as i64);
+
+        let mut open: Vec<Span> = Vec::new();
+        let mut day = (from + offset).date();
+
+        while day <= (to + offset).date() {
+            let midnight = day.and_hms(0, 0, 0) - offset;
+            let weekday = day.weekday().num_days_from_monday() as i32;
+
+            for window in self.windows.iter().filter(|window| window.weekday == weekday) {
+                open.push((midnight + Duration::minutes(window.from_minute as i64), midnight + Duration::minutes(window.to_minute as i64)));
+            }
+
+            day = day.succ();
+        }
+
+        open.extend(self.exceptions.iter().filter(|exception| exception.is_available).map(|exception| (exception.from_date, exception.to_date)));
+
+        let closed = self.exceptions.iter().filter(|exception| !exception.is_available).map(|exception| (exception.from_date, exception.to_date));
+
+        let spans = closed.chain(busy.iter().copied()).fold(merge(open), subtract);
+
+        spans
+            .into_iter()
+            .map(|(starts_at, ends_at)| (starts_at.max(from), ends_at.min(to)))
+            .filter(|(starts_at, ends_at)| starts_at < ends_at)
+            .map(|(starts_at, ends_at)| Slot { starts_at, ends_at })
+            .collect()
+    }
+
+    /**
+     * Whether a session from the start to the end fits in one free slot.
+     */
+    pub fn is_free(&self, busy: &[Span], utc_offset: i32, starts_at: NaiveDateTime, ends_at: NaiveDateTime) -> bool {
+        starts_at < ends_at && self.free_slots(busy, utc_offset, starts_at, ends_at) == vec![Slot { starts_at, ends_at }]
+    }
+}
+
+fn merge(mut spans: Vec<Span>) -> Vec<Span> {
+    spans.sort();
+
+    let mut merged: Vec<Span> = Vec::new();
+
+    for (starts_at, ends_at) in spans {
+        match merged.last_mut() {
+            Some(last) if starts_at <= last.1 => last.1 = last.1.max(ends_at),
+            _ => merged.push((starts_at, ends_at)),
+        }
+    }
+
+    merged
+}
+
+fn subtract(spans: Vec<Span>, (cut_from, cut_to): Span) -> Vec<Span> {
+    let mut left: Vec<Span> = Vec::new();
+
+    for (starts_at, ends_at) in spans {
+        if cut_to <= starts_at || cut_from >= ends_at {
+            left.push((starts_at, ends_at));
+            continue;
+        }
+
+        if starts_at < cut_from {
+            left.push((starts_at, cut_from));
+        }
+
+        if cut_to < ends_at {
+            left.push((cut_to, ends_at));
+        }
+    }
+
+    left
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn window(weekday: i32, from: &str, to: &str) -> AvailabilityWindow {
+        AvailabilityWindow {
+            id: String::from("w-1"),
+            weekday,
+            from_minute: parse_minute(from).unwrap(),
+            to_minute: parse_minute(to).unwrap(),
+        }
+    }
+
+    fn exception(from: &str, to: &str, is_available: bool) -> AvailabilityException {
+        AvailabilityException {
+            id: String::from("x-1"),
+            from_date: at(from),
+            to_date: at(to),
+            is_available,
+            reason: String::new(),
+        }
+    }
+
+    fn availability(windows: Vec<AvailabilityWindow>, exceptions: Vec<AvailabilityException>) -> Availability {
+        Availability {
+            coach_id: String::from("c-1"),
+            windows,
+            exceptions,
+        }
+    }
+
+    fn slot(from: &str, to: &str) -> Slot {
+        Slot {
+            starts_at: at(from),
+            ends_at: at(to),
+        }
+    }
+
+    #[test]
+    fn should_place_the_windows_in_the_local_time_of_the_coach() {
+        // 2021-03-15 is a Monday; the coach is 5:30 hours ahead of UTC.
+        let coach = availability(vec![window(0, "09:00", "12:00")], vec![]);
+
+        let slots = coach.free_slots(&[], 330, at("2021-03-14T00:00"), at("2021-03-22T00:00"));
+
+        assert_eq!(vec![slot("2021-03-15T03:30", "2021-03-15T06:30")], slots);
+    }
+
+    #[test]
+    fn should_take_away_the_days_off_and_the_sessions() {
+        let coach = availability(
+            vec![window(0, "09:00", "17:00"), window(1, "09:00", "17:00")],
+            vec![exception("2021-03-16T00:00", "2021-03-17T00:00", false), exception("2021-03-20T10:00", "2021-03-20T12:00", true)],
+        );
+        let busy = [(at("2021-03-15T10:00"), at("2021-03-15T11:00"))];
+
+        let slots = coach.free_slots(&busy, 0, at("2021-03-15T00:00"), at("2021-03-22T00:00"));
+
+        assert_eq!(
+            vec![slot("2021-03-15T09:00", "2021-03-15T10:00"), slot("2021-03-15T11:00", "2021-03-15T17:00"), slot("2021-03-20T10:00", "2021-03-20T12:00")],
+            slots
+        );
+        assert!(coach.is_free(&busy, 0, at("2021-03-15T11:00"), at("2021-03-15T12:00")));
+        assert!(!coach.is_free(&busy, 0, at("2021-03-15T09:30"), at("2021-03-15T10:30")));
+        assert!(!coach.is_free(&busy, 0, at("2021-03-16T09:00"), at("2021-03-16T10:00")));
+    }
+
+    #[test]
+    fn should_join_the_windows_that_meet() {
+        let coach = availability(vec![window(2, "09:00", "12:00"), window(2, "12:00", "24:00")], vec![]);
+
+        assert_eq!(vec![slot("2021-03-17T09:00", "2021-03-18T00:00")], coach.free_slots(&[], 0, at("2021-03-15T00:00"), at("2021-03-22T00:00")));
+        assert!(!availability(vec![], vec![exception("2021-03-16T00:00", "2021-03-17T00:00", false)]).is_set());
+    }
+
+    #[test]
+    fn should_check_the_windows_and_the_exceptions() {
+        let request = AvailabilityRequest {
+            coach_id: String::from("c-1"),
+            windows: vec![
+                WindowRequest { weekday: 0, from: String::from("09:00"), to: String::from("24:00") },
+                WindowRequest { weekday: 7, from: String::from("09:00"), to: String::from("10:00") },
+            ],
+            exceptions: vec![ExceptionRequest {
+                from_date: String::from("2021-03-16T00:00:00Z"),
+                to_date: String::from("2021-03-16"),
+                is_available: false,
+                reason: None,
+            }],
+        };
+
+        let errors = request.validate();
+        let (windows, exceptions) = request.to_rows();
+
+        assert_eq!(2, errors.len());
+        assert_eq!(1, windows.len());
+        assert_eq!((0, 540, 1440), (windows[0].weekday, windows[0].from_minute, windows[0].to_minute));
+        assert!(exceptions.is_empty());
+        assert_eq!(None, parse_minute("24:30"));
+        assert!(slot_range("2021-03-15T00:00:00Z", "2021-05-15T00:00:00Z").is_err());
+    }
+}
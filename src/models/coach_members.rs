@@ -1,9 +1,16 @@
 use diesel::prelude::*;
+use juniper::FieldResult;
+use std::collections::HashMap;
 
+use crate::commons::chassis::{Page, Window};
+use crate::graphql_schema::DBContext;
+use crate::loaders::grouped;
 use crate::models::enrollments::{Enrollment,EnrollmentFilter};
 use crate::models::programs::Program;
 use crate::models::users::User;
 
+use crate::schema::enrollment_coaches;
+use crate::schema::enrollments;
 use crate::schema::enrollments::dsl::*;
 use crate::schema::programs::dsl::*;
 use crate::schema::users::dsl::*;
@@ -15,13 +22,16 @@ pub struct CoachCriteria {
     pub desire: EnrollmentFilter,
 }
 
+// The enrollment, the member and the program come in the one query of the list; the co-coaches of the
+// enrollments in one more, when the first row asks for them, see loaders.
 pub struct MemberRow {
     pub enrollment: Enrollment,
     pub user: User,
     pub program: Program,
+    pub is_co_coached: bool,
 }
 
-#[juniper::object]
+#[juniper::object(Context = DBContext)]
 impl MemberRow {
     pub fn enrollment(&self) -> &Enrollment {
         &self.enrollment
@@ -34,16 +44,67 @@ impl MemberRow {
     pub fn program(&self) -> &Program {
         &self.program
     }
+
+    #[graphql(description = "The coach sees the member as a co-coach of the enrollment, not as the coach of its program")]
+    pub fn is_co_coached(&self) -> bool {
+        self.is_co_coached
+    }
+
+    #[graphql(description = "The coaches sharing the enrollment with the coach of its program")]
+    pub fn co_coaches(&self, context: &DBContext) -> FieldResult<Vec<User>> {
+        let co_coaches = context.loaders.co_coaches.load(&self.enrollment.id.to_string(), |keys| {
+            let connection = context.connection()?;
+            co_coaches_of(&connection, keys).map_err(|_| CO_COACHES_ERROR)
+        })?;
+
+        Ok(co_coaches)
+    }
+}
+
+const CO_COACHES_ERROR: &str = "Unable to find the co-coaches of the enrollments.";
+
+/**
+ * The co-coaches of the enrollments, by the id of the enrollment.
+ */
+pub fn co_coaches_of(connection: &MysqlConnection, enrollment_ids: &[String]) -> QueryResult<HashMap<String, Vec<User>>> {
+    let pairs: Vec<(String, User)> = enrollment_coaches::table
+        .inner_join(users)
+        .filter(enrollment_coaches::enrollment_id.eq_any(enrollment_ids))
+        .select((enrollment_coaches::enrollment_id, crate::schema::users::all_columns))
+        .load(connection)?;
+
+    Ok(grouped(pairs))
 }
 
 type EnrollmentType = (Enrollment, User, Program);
 
+/**
+ * The members of the programs of the coach, and the members of the enrollments
+ * the coach was made a co-coach of.
+ */
 pub fn get_coach_members(connection: &MysqlConnection, criteria: CoachCriteria) -> Result<Vec<MemberRow>, diesel::result::Error> {
+    load_members(connection, criteria, None)
+}
+
+/**
+ * The members a page at a time; the enrollment id keeps the members of the same name in one order across the pages.
+ */
+pub fn get_coach_member_page(connection: &MysqlConnection, criteria: CoachCriteria, window: &Window) -> Result<Page<MemberRow>, diesel::result::Error> {
+    let rows = load_members(connection, criteria, Some(window))?;
+
+    Ok(Page::of(rows, window))
+}
+
+fn load_members(connection: &MysqlConnection, criteria: CoachCriteria, window: Option<&Window>) -> Result<Vec<MemberRow>, diesel::result::Error> {
+    let co_coached = enrollment_coaches::table
+        .filter(enrollment_coaches::coach_id.eq(criteria.coach_id.to_owned()))
+        .select(enrollment_coaches::enrollment_id);
+
     let mut query = enrollments
         .inner_join(users)
         .inner_join(programs)
-        .filter(coach_id.eq(criteria.coach_id))
-        .order_by(full_name.asc())
+        .filter(coach_id.eq(criteria.coach_id.to_owned()).or(enrollments::id.eq_any(co_coached)))
+        .order_by((full_name.asc(), enrollments::id.asc()))
         .into_boxed();
 
     if let EnrollmentFilter::NEW = criteria.desire {
@@ -54,15 +115,22 @@ pub fn get_coach_members(connection: &MysqlConnection, criteria: CoachCriteria)
         query = query.filter(program_id.eq(criteria.program_id.unwrap()));
     }
 
+    if let Some(the_window) = window {
+        query = query.offset(the_window.offset).limit(the_window.fetch());
+    }
+
     let result: Vec<EnrollmentType> = query.load(connection)?;
 
     let mut rows: Vec<MemberRow> = Vec::new();
 
     for item in result {
+        let is_co_coached = item.2.coach_id != criteria.coach_id;
+
         let row = MemberRow {
             enrollment: item.0,
             user: item.1,
             program: item.2,
+            is_co_coached,
         };
 
         rows.push(row);
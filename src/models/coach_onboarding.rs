@@ -0,0 +1,166 @@
+/**
+ * The steps a new coach goes through before publishing a program. The steps are
+ * verified on the server against the profile, the programs and the payment
+ * account of the coach each time they are asked for; once all of them are met
+ * the onboarding is complete for good, and a later edit of the profile does not
+ * take the programs of the coach off the platform.
+ *
+ * The payment account is the reference of the payout account the coach holds
+ * with the payment provider; the card or bank details never reach us.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::models::coach_profiles::CoachProfile;
+use crate::schema::coach_onboarding;
+
+const MIN_ACCOUNT: usize = 6;
+const MAX_ACCOUNT: usize = 64;
+
+#[derive(juniper::GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OnboardingStep {
+    PROFILE,
+    PROGRAM,
+    AVAILABILITY,
+    PAYMENT,
+}
+
+#[derive(Queryable, Debug)]
+pub struct CoachOnboarding {
+    pub payment_account: Option<String>,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[table_name = "coach_onboarding"]
+pub struct NewCoachOnboarding {
+    pub coach_id: String,
+    pub payment_account: Option<String>,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+/**
+ * What the coach has done so far, as verified now.
+ */
+pub struct Progress {
+    pub has_profile: bool,
+    pub has_availability: bool,
+    pub has_program: bool,
+    pub has_payment: bool,
+}
+
+impl Progress {
+    // A profile is complete enough to be shown once it tells who the coach is.
+    pub fn of(profile: Option<&CoachProfile>, has_program: bool, onboarding: Option<&CoachOnboarding>) -> Progress {
+        Progress {
+            has_profile: profile
+                .map(|value| !value.headline.trim().is_empty() && !value.bio.trim().is_empty() && !value.avatar_url.trim().is_empty())
+                .unwrap_or(false),
+            has_availability: profile.map(|value| !value.availability.trim().is_empty()).unwrap_or(false),
+            has_program,
+            has_payment: onboarding.and_then(|value| value.payment_account.as_deref()).map(is_valid_account).unwrap_or(false),
+        }
+    }
+
+    // In the order the coach meets them.
+    pub fn pending(&self) -> Vec<OnboardingStep> {
+        let steps = vec![
+            (OnboardingStep::PROFILE, self.has_profile),
+            (OnboardingStep::PROGRAM, self.has_program),
+            (OnboardingStep::AVAILABILITY, self.has_availability),
+            (OnboardingStep::PAYMENT, self.has_payment),
+        ];
+
+        steps.into_iter().filter(|(_, done)| !done).map(|(step, _)| step).collect()
+    }
+}
+
+pub struct OnboardingChecklist {
+    pub coach_id: String,
+    pub pending: Vec<OnboardingStep>,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+#[juniper::object(description = "The onboarding of a coach, which gates the publication of the programs")]
+impl OnboardingChecklist {
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    #[graphql(description = "The steps yet to be done, in order; the first one is the current step")]
+    pub fn pending(&self) -> &Vec<OnboardingStep> {
+        &self.pending
+    }
+
+    pub fn current_step(&self) -> Option<OnboardingStep> {
+        self.pending.first().copied()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    pub fn completed_at(&self) -> Option<NaiveDateTime> {
+        self.completed_at
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct PaymentDetailsRequest {
+    pub coach_id: String,
+    pub payment_account: String,
+}
+
+impl PaymentDetailsRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if !is_valid_account(self.payment_account.trim()) {
+            errors.push(ValidationError::new("payment_account", "The account should be the reference given by the payment provider."));
+        }
+
+        errors
+    }
+}
+
+pub fn is_valid_account(account: &str) -> bool {
+    (MIN_ACCOUNT..=MAX_ACCOUNT).contains(&account.len()) && account.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_list_the_pending_steps_in_order() {
+        let progress = Progress {
+            has_profile: true,
+            has_availability: false,
+            has_program: false,
+            has_payment: false,
+        };
+
+        assert_eq!(vec![OnboardingStep::PROGRAM, OnboardingStep::AVAILABILITY, OnboardingStep::PAYMENT], progress.pending());
+
+        let done = Progress {
+            has_profile: true,
+            has_availability: true,
+            has_program: true,
+            has_payment: true,
+        };
+
+        assert!(done.pending().is_empty());
+    }
+
+    #[test]
+    fn should_take_only_a_provider_reference() {
+        assert!(is_valid_account("acct_1IMc2wF7q"));
+        assert!(!is_valid_account("4111 1111 1111 1111"));
+        assert!(!is_valid_account("abc"));
+    }
+}
@@ -0,0 +1,428 @@
+/**
+ * The public face of a coach in the directory. A coach appears in the directory
+ * only after opting in, and the directory never reveals the email of the coach.
+ *
+ * The specialties and the languages are kept as lowercase tags wrapped in commas,
+ * ",leadership,sales,", so that a single tag can be matched with LIKE.
+ *
+ * The completeness of a profile is kept along with it, so that the directory can
+ * rank by it; it is worked out again whenever the profile or a program changes.
+ *
+ * The rating of a coach is that of the visible reviews of the programs of the
+ * coach, and the next free time is the start of the first free slot given by the
+ * availability of the coach; a coach who set no availability shows none.
+ */
+use chrono::{Duration, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::models::coach_availability::parse_time;
+use crate::schema::coach_profiles;
+
+const MAX_TAGS: usize = 20;
+const MAX_PAGE_SIZE: i32 = 50;
+const MAX_FREE_DAYS: i64 = 31;
+
+// How far ahead the next free time of a coach is looked for, unless the criteria ask for longer.
+pub const FREE_HINT_DAYS: i64 = 14;
+
+const BIO: &str = "bio";
+const AVATAR: &str = "avatar";
+const AVAILABILITY: &str = "availability";
+const PROGRAM: &str = "program";
+const INTRO_VIDEO: &str = "intro_video";
+
+// Each of the five items is worth as much.
+const ITEM_SCORE: i32 = 20;
+
+#[derive(Queryable, Debug, Identifiable)]
+#[primary_key(coach_id)]
+pub struct CoachProfile {
+    pub coach_id: String,
+    pub headline: String,
+    pub specialties: String,
+    pub languages: String,
+    pub is_listed: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub bio: String,
+    pub avatar_url: String,
+    pub availability: String,
+    pub intro_video_url: String,
+    pub completeness: i32,
+    pub missing: String,
+}
+
+impl CoachProfile {
+    /**
+     * The score out of 100 with the items still missing, in the order the coach
+     * would meet them in the profile form.
+     */
+    pub fn assess(&self, has_program: bool) -> (i32, Vec<&'static str>) {
+        let items = [
+            (BIO, !self.bio.trim().is_empty()),
+            (AVATAR, !self.avatar_url.trim().is_empty()),
+            (AVAILABILITY, !self.availability.trim().is_empty()),
+            (PROGRAM, has_program),
+            (INTRO_VIDEO, !self.intro_video_url.trim().is_empty()),
+        ];
+
+        let missing: Vec<&'static str> = items.iter().filter(|(_, present)| !present).map(|(item, _)| *item).collect();
+        let score = (items.len() - missing.len()) as i32 * ITEM_SCORE;
+
+        (score, missing)
+    }
+}
+
+#[juniper::object(description = "What a coach shares about the coaching in the directory")]
+impl CoachProfile {
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn headline(&self) -> &str {
+        self.headline.as_str()
+    }
+
+    pub fn specialties(&self) -> Vec<String> {
+        from_tags(self.specialties.as_str())
+    }
+
+    pub fn languages(&self) -> Vec<String> {
+        from_tags(self.languages.as_str())
+    }
+
+    pub fn is_listed(&self) -> bool {
+        self.is_listed
+    }
+
+    pub fn bio(&self) -> &str {
+        self.bio.as_str()
+    }
+
+    pub fn avatar_url(&self) -> &str {
+        self.avatar_url.as_str()
+    }
+
+    pub fn availability(&self) -> &str {
+        self.availability.as_str()
+    }
+
+    pub fn intro_video_url(&self) -> &str {
+        self.intro_video_url.as_str()
+    }
+
+    #[graphql(description = "How complete the profile is, out of 100")]
+    pub fn completeness(&self) -> i32 {
+        self.completeness
+    }
+
+    #[graphql(description = "The items to fill for a complete profile: bio, avatar, availability, program or intro_video")]
+    pub fn missing(&self) -> Vec<String> {
+        from_tags(self.missing.as_str())
+    }
+}
+
+pub fn to_tags(values: &[String]) -> String {
+    let mut tags: Vec<String> = values
+        .iter()
+        .map(|value| value.trim().to_lowercase().replace(',', " "))
+        .filter(|value| !value.is_empty())
+        .collect();
+
+    tags.sort();
+    tags.dedup();
+
+    if tags.is_empty() {
+        return String::from("");
+    }
+
+    format!(",{},", tags.join(","))
+}
+
+pub fn from_tags(tags: &str) -> Vec<String> {
+    tags.split(',').filter(|tag| !tag.is_empty()).map(|tag| tag.to_owned()).collect()
+}
+
+/**
+ * The LIKE pattern matching one tag; the wildcards of the tag are taken literally.
+ */
+pub fn tag_pattern(tag: &str) -> String {
+    let escaped = tag.trim().to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+
+    format!("%,{},%", escaped)
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct CoachProfileRequest {
+    pub coach_id: String,
+    pub headline: String,
+    pub specialties: Vec<String>,
+    pub languages: Vec<String>,
+    pub is_listed: bool,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub availability: Option<String>,
+    pub intro_video_url: Option<String>,
+}
+
+fn optional_length(value: &Option<String>) -> usize {
+    value.as_ref().map(|text| text.trim().chars().count()).unwrap_or(0)
+}
+
+impl CoachProfileRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.headline.chars().count() > 255 {
+            errors.push(ValidationError::new("headline", "The headline may have at most 255 characters."));
+        }
+
+        if self.specialties.len() > MAX_TAGS || to_tags(&self.specialties).len() > 1024 {
+            errors.push(ValidationError::new("specialties", "A coach may list at most 20 specialties."));
+        }
+
+        if self.languages.len() > MAX_TAGS || to_tags(&self.languages).len() > 255 {
+            errors.push(ValidationError::new("languages", "A coach may list at most 20 languages."));
+        }
+
+        if optional_length(&self.bio) > 2000 {
+            errors.push(ValidationError::new("bio", "The bio may have at most 2000 characters."));
+        }
+
+        if optional_length(&self.avatar_url) > 255 || optional_length(&self.intro_video_url) > 255 {
+            errors.push(ValidationError::new("avatar_url", "The links to the avatar and the intro video may have at most 255 characters."));
+        }
+
+        if optional_length(&self.availability) > 255 {
+            errors.push(ValidationError::new("availability", "The availability may have at most 255 characters."));
+        }
+
+        errors
+    }
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "coach_profiles"]
+pub struct NewCoachProfile {
+    pub coach_id: String,
+    pub headline: String,
+    pub specialties: String,
+    pub languages: String,
+    pub is_listed: bool,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub availability: Option<String>,
+    pub intro_video_url: Option<String>,
+}
+
+/**
+ * A detail left out of the request is kept as it is.
+ */
+impl NewCoachProfile {
+    pub fn from(request: &CoachProfileRequest) -> NewCoachProfile {
+        NewCoachProfile {
+            coach_id: request.coach_id.to_owned(),
+            headline: request.headline.trim().to_owned(),
+            specialties: to_tags(&request.specialties),
+            languages: to_tags(&request.languages),
+            is_listed: request.is_listed,
+            bio: trimmed(&request.bio),
+            avatar_url: trimmed(&request.avatar_url),
+            availability: trimmed(&request.availability),
+            intro_video_url: trimmed(&request.intro_video_url),
+        }
+    }
+}
+
+fn trimmed(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|text| text.trim().to_owned())
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct DirectoryCriteria {
+    pub specialty: Option<String>,
+    pub language: Option<String>,
+    #[graphql(description = "Only the coaches rated at least this, 1 to 5")]
+    pub min_rating: Option<f64>,
+    #[graphql(description = "Only the coaches free before this time, like 2021-03-15T09:00:00Z, up to 31 days ahead")]
+    pub free_before: Option<String>,
+    pub page: i32,
+    pub page_size: i32,
+}
+
+impl DirectoryCriteria {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.page < 0 || self.page_size < 1 || self.page_size > MAX_PAGE_SIZE {
+            return Err("The page should not be negative, and the page size should be 1 to 50.");
+        }
+
+        if let Some(rating) = self.min_rating {
+            if !(1.0..=5.0).contains(&rating) {
+                return Err("The minimum rating should be 1 to 5.");
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * The time the coaches should be free before, if asked; it is after now and
+     * at most 31 days ahead.
+     */
+    pub fn free_until(&self, now: NaiveDateTime) -> Result<Option<NaiveDateTime>, &'static str> {
+        let value = match &self.free_before {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        match parse_time(value) {
+            Some(until) if until > now && until - now <= Duration::days(MAX_FREE_DAYS) => Ok(Some(until)),
+            _ => Err("The free before should be a time like 2021-03-15T09:00:00Z, after now and up to 31 days ahead."),
+        }
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page as i64) * (self.page_size as i64)
+    }
+}
+
+pub struct DirectoryEntry {
+    pub name: String,
+    pub profile: CoachProfile,
+    pub rating: Option<f64>,
+    pub reviews: i32,
+    pub free_at: Option<NaiveDateTime>,
+}
+
+#[juniper::object]
+impl DirectoryEntry {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn profile(&self) -> &CoachProfile {
+        &self.profile
+    }
+
+    #[graphql(description = "The average rating of the visible reviews of the programs of the coach")]
+    pub fn rating(&self) -> Option<f64> {
+        self.rating
+    }
+
+    pub fn reviews(&self) -> i32 {
+        self.reviews
+    }
+
+    #[graphql(description = "The start of the next free slot of the coach, in UTC, when there is one in the days looked at")]
+    pub fn free_at(&self) -> Option<NaiveDateTime> {
+        self.free_at
+    }
+}
+
+pub struct DirectoryPage {
+    pub entries: Vec<DirectoryEntry>,
+    pub total: i32,
+    pub page: i32,
+    pub page_size: i32,
+}
+
+#[juniper::object(description = "A page of the coaches listed in the directory")]
+impl DirectoryPage {
+    pub fn entries(&self) -> &Vec<DirectoryEntry> {
+        &self.entries
+    }
+
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+
+    pub fn page(&self) -> i32 {
+        self.page
+    }
+
+    pub fn page_size(&self) -> i32 {
+        self.page_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::commons::util;
+
+    #[test]
+    fn should_normalize_the_tags() {
+        let tags = to_tags(&[String::from(" Sales"), String::from("leadership"), String::from("sales"), String::from(" ")]);
+
+        assert_eq!(",leadership,sales,", tags);
+        assert_eq!(vec!["leadership", "sales"], from_tags(tags.as_str()));
+        assert_eq!("", to_tags(&[]));
+    }
+
+    #[test]
+    fn should_score_the_items_of_the_profile() {
+        let mut profile = CoachProfile {
+            coach_id: String::from("c-1"),
+            headline: String::from("Sales coach"),
+            specialties: String::from(",sales,"),
+            languages: String::from(",english,"),
+            is_listed: true,
+            created_at: util::now(),
+            updated_at: util::now(),
+            bio: String::from(" "),
+            avatar_url: String::from("assets/users/c-1/avatar.png"),
+            availability: String::from(""),
+            intro_video_url: String::from(""),
+            completeness: 0,
+            missing: String::from(""),
+        };
+
+        assert_eq!((40, vec![BIO, AVAILABILITY, INTRO_VIDEO]), profile.assess(true));
+
+        profile.bio = String::from("Twenty years in the field.");
+        profile.availability = String::from("Weekday evenings");
+        profile.intro_video_url = String::from("assets/users/c-1/intro.mp4");
+
+        assert_eq!((100, vec![]), profile.assess(true));
+        assert_eq!((80, vec![PROGRAM]), profile.assess(false));
+    }
+
+    #[test]
+    fn should_check_the_rating_and_the_free_time() {
+        let now = util::as_date("2021-03-01T00:00:00Z");
+        let mut criteria = DirectoryCriteria {
+            specialty: None,
+            language: None,
+            min_rating: Some(4.5),
+            free_before: Some(String::from("2021-03-10T09:00:00Z")),
+            page: 0,
+            page_size: 10,
+        };
+
+        assert_eq!(Ok(()), criteria.validate());
+        assert_eq!(Ok(Some(util::as_date("2021-03-10T09:00:00Z"))), criteria.free_until(now));
+
+        criteria.min_rating = Some(0.5);
+        assert!(criteria.validate().is_err());
+
+        criteria.free_before = Some(String::from("2021-04-10T09:00:00Z"));
+        assert!(criteria.free_until(now).is_err());
+
+        criteria.free_before = Some(String::from("2021-02-10T09:00:00Z"));
+        assert!(criteria.free_until(now).is_err());
+
+        criteria.free_before = None;
+        assert_eq!(Ok(None), criteria.free_until(now));
+    }
+
+    #[test]
+    fn should_match_the_wildcards_literally() {
+        assert_eq!("%,public speaking,%", tag_pattern(" Public Speaking "));
+        assert_eq!("%,100\\%\\_x,%", tag_pattern("100%_x"));
+    }
+}
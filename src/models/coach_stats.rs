@@ -0,0 +1,201 @@
+/**
+ * The daily counts of a coach, kept in a summary table for the dashboards.
+ *
+ * A change that moves a count queues the coach and the day for a refresh; the
+ * jobs recount the queued days from the source tables, so a dashboard reads a
+ * few summary rows however large the history grows.
+ */
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::schema::coach_daily_stats;
+use crate::schema::stat_refresh_queue;
+
+const DAY_PATTERN: &str = "%Y-%m-%d";
+
+const BAD_DAY: &str = "The days should be like 2021-02-14.";
+const BAD_ORDER: &str = "The from day should not be after the to day.";
+const LONG_RANGE: &str = "The range should not be longer than a year.";
+
+// A year of days is as much as a dashboard shows at once.
+const MAX_DAYS: i64 = 366;
+
+// Read without the coach, whom the dashboard already knows.
+#[derive(Queryable, Debug)]
+pub struct CoachDailyStat {
+    pub day: NaiveDate,
+    pub enrollments_created: i32,
+    pub sessions_created: i32,
+    pub sessions_done: i32,
+    pub sessions_cancelled: i32,
+    pub tasks_responded: i32,
+    pub refreshed_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The activity of a coach on a day")]
+impl CoachDailyStat {
+    pub fn day(&self) -> NaiveDate {
+        self.day
+    }
+
+    pub fn enrollments_created(&self) -> i32 {
+        self.enrollments_created
+    }
+
+    pub fn sessions_created(&self) -> i32 {
+        self.sessions_created
+    }
+
+    pub fn sessions_done(&self) -> i32 {
+        self.sessions_done
+    }
+
+    pub fn sessions_cancelled(&self) -> i32 {
+        self.sessions_cancelled
+    }
+
+    pub fn tasks_responded(&self) -> i32 {
+        self.tasks_responded
+    }
+
+    pub fn refreshed_at(&self) -> NaiveDateTime {
+        self.refreshed_at
+    }
+}
+
+/**
+ * The days of the range that had any activity, with the totals over the range.
+ */
+pub struct CoachStats {
+    pub days: Vec<CoachDailyStat>,
+}
+
+impl CoachStats {
+    fn total(&self, count: fn(&CoachDailyStat) -> i32) -> i32 {
+        self.days.iter().map(count).sum()
+    }
+}
+
+#[juniper::object(description = "The activity of a coach over a range of days")]
+impl CoachStats {
+    pub fn days(&self) -> &Vec<CoachDailyStat> {
+        &self.days
+    }
+
+    pub fn enrollments_created(&self) -> i32 {
+        self.total(|stat| stat.enrollments_created)
+    }
+
+    pub fn sessions_created(&self) -> i32 {
+        self.total(|stat| stat.sessions_created)
+    }
+
+    pub fn sessions_done(&self) -> i32 {
+        self.total(|stat| stat.sessions_done)
+    }
+
+    pub fn sessions_cancelled(&self) -> i32 {
+        self.total(|stat| stat.sessions_cancelled)
+    }
+
+    pub fn tasks_responded(&self) -> i32 {
+        self.total(|stat| stat.tasks_responded)
+    }
+}
+
+/**
+ * The days are in UTC and both ends are included, like 2021-02-01 to 2021-02-28.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct CoachStatsCriteria {
+    pub coach_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl CoachStatsCriteria {
+    pub fn range(&self) -> Result<(NaiveDate, NaiveDate), &'static str> {
+        let from = NaiveDate::parse_from_str(self.from.trim(), DAY_PATTERN).map_err(|_| BAD_DAY)?;
+        let to = NaiveDate::parse_from_str(self.to.trim(), DAY_PATTERN).map_err(|_| BAD_DAY)?;
+
+        if from > to {
+            return Err(BAD_ORDER);
+        }
+
+        if to.signed_duration_since(from) >= Duration::days(MAX_DAYS) {
+            return Err(LONG_RANGE);
+        }
+
+        Ok((from, to))
+    }
+}
+
+/**
+ * The bounds of the day, for counting the rows whose time falls on it.
+ */
+pub fn day_bounds(day: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+    let start = day.and_hms(0, 0, 0);
+    (start, start + Duration::days(1))
+}
+
+#[derive(Insertable)]
+#[table_name = "coach_daily_stats"]
+pub struct NewCoachDailyStat {
+    pub coach_id: String,
+    pub day: NaiveDate,
+    pub enrollments_created: i32,
+    pub sessions_created: i32,
+    pub sessions_done: i32,
+    pub sessions_cancelled: i32,
+    pub tasks_responded: i32,
+}
+
+#[derive(Insertable)]
+#[table_name = "stat_refresh_queue"]
+pub struct NewStatRefresh {
+    pub coach_id: String,
+    pub day: NaiveDate,
+}
+
+impl NewStatRefresh {
+    pub fn new(coach_id: &str, day: NaiveDate) -> NewStatRefresh {
+        NewStatRefresh {
+            coach_id: coach_id.to_owned(),
+            day,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn criteria(from: &str, to: &str) -> CoachStatsCriteria {
+        CoachStatsCriteria {
+            coach_id: String::from("c-1"),
+            from: from.to_owned(),
+            to: to.to_owned(),
+        }
+    }
+
+    #[test]
+    fn should_accept_a_range_of_days() {
+        assert!(criteria("2021-02-01", "2021-02-28").range().is_ok());
+        assert!(criteria("2021-02-14", "2021-02-14").range().is_ok());
+    }
+
+    #[test]
+    fn should_reject_bad_ranges() {
+        assert_eq!(Err(BAD_ORDER), criteria("2021-02-28", "2021-02-01").range());
+        assert_eq!(Err(BAD_DAY), criteria("14-02-2021", "2021-02-28").range());
+        assert_eq!(Err(LONG_RANGE), criteria("2020-01-01", "2021-02-28").range());
+    }
+
+    #[test]
+    fn should_bound_the_day() {
+        let (start, end) = day_bounds(NaiveDate::from_ymd(2021, 2, 14));
+
+        assert_eq!(NaiveDate::from_ymd(2021, 2, 14).and_hms(0, 0, 0), start);
+        assert_eq!(NaiveDate::from_ymd(2021, 2, 15).and_hms(0, 0, 0), end);
+    }
+}
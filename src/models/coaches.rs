@@ -1,6 +1,9 @@
 use chrono::NaiveDateTime;
 
-#[derive(Queryable, Debug)]
+use crate::models::users::User;
+use crate::schema::coaches;
+
+#[derive(Queryable, Debug, Clone)]
 pub struct Coach {
     pub id: String,
     pub user_id: String,
@@ -37,3 +40,24 @@ impl Coach {
         self.token
     }
 }
+
+// The id of a coach is the id of the user, so that a program may find the user of its coach.
+#[derive(Insertable)]
+#[table_name = "coaches"]
+pub struct NewCoach {
+    pub id: String,
+    pub user_id: String,
+    pub full_name: String,
+    pub email: String,
+}
+
+impl NewCoach {
+    pub fn from(user: &User) -> NewCoach {
+        NewCoach {
+            id: user.id.to_string(),
+            user_id: user.id.to_string(),
+            full_name: user.full_name.to_owned(),
+            email: user.email.to_owned(),
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, UserId};
 use crate::commons::util;
+use crate::models::correspondences::QueuedMails;
+use crate::models::sessions::Session;
 use crate::schema::conferences;
 
 use chrono::{Duration, NaiveDateTime};
@@ -9,7 +12,7 @@ pub struct Conference {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
-    pub program_id: String,
+    pub program_id: ProgramId,
     pub people: Option<String>,
     pub duration: i32,
     pub original_start_date: NaiveDateTime,
@@ -25,6 +28,7 @@ pub struct Conference {
     pub closing_notes: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub expired_at: Option<NaiveDateTime>,
 }
 
 impl Conference  {
@@ -94,11 +98,16 @@ impl Conference {
     pub fn closing_notes(&self) -> Option<String> {
         self.closing_notes.clone()
     }
+
+    #[graphql(description = "When the conference was expired, never having been made ready")]
+    pub fn expired_at(&self) -> Option<NaiveDateTime> {
+        self.expired_at
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct NewConferenceRequest {
-    pub program_id: String,
+    pub program_id: ProgramId,
     pub name: String,
     pub description: String,
     pub duration: i32,
@@ -165,7 +174,7 @@ impl NewConference {
             id: fuzzy_id,
             name: request.name.to_owned(),
             description: request.description.to_owned(),
-            program_id: request.program_id.to_owned(),
+            program_id: request.program_id.to_string(),
             people,
             duration: request.duration,
             original_start_date: start_date,
@@ -183,6 +192,145 @@ pub enum IntentionState {
 #[derive(juniper::GraphQLInputObject)]
 pub struct MemberRequest {
     pub conference_id: String,
-    pub member_ids: Vec<String>,
+    pub member_ids: Vec<UserId>,
     pub intention: IntentionState,
 }
+
+/**
+ * The members of a conference a request went through, with the mails queued
+ * to tell the newly seated ones of their sessions.
+ */
+pub struct ConferenceMembers {
+    pub members: Vec<String>,
+    pub notices: QueuedMails,
+}
+
+const MAX_GAP_MINUTES: i32 = 240;
+
+/**
+ * The one to one follow-ups of a conference that is done: a session for each of
+ * its members, or for the members named, one after the other from the start
+ * time with the gap in minutes between them.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct FollowupRequest {
+    pub conference_id: String,
+    pub member_ids: Option<Vec<UserId>>,
+    pub start_time: String,
+    pub duration: i32,
+    pub gap: Option<i32>,
+    pub override_reason: Option<String>,
+}
+
+impl FollowupRequest {
+    // A blank reason is as good as no reason.
+    pub fn override_reason(&self) -> Option<&str> {
+        match &self.override_reason {
+            Some(reason) if !reason.trim().is_empty() => Some(reason.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.conference_id.trim().is_empty() {
+            errors.push(ValidationError::new("conference_id", "Conference id is a must."));
+        }
+
+        let given_time = self.start_time.as_str();
+
+        if !util::is_valid_date(given_time) {
+            errors.push(ValidationError::new("start_time", "unparsable date."));
+        }
+
+        if util::is_past_date(util::as_date(given_time)) {
+            errors.push(ValidationError::new("start_time", "should be a future date."));
+        }
+
+        if self.duration < 15 {
+            errors.push(ValidationError::new("duration", "should be a minimum of 15 minutes"));
+        }
+
+        if !(0..=MAX_GAP_MINUTES).contains(&self.gap.unwrap_or(0)) {
+            errors.push(ValidationError::new("gap", "The gap between the follow-ups should be of 0 to 240 minutes."));
+        }
+
+        errors
+    }
+
+    /**
+     * The start and the end of each of the follow-ups, in turn.
+     */
+    pub fn schedule(&self, count: usize) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        let duration = Duration::minutes(self.duration as i64);
+        let step = duration + Duration::minutes(self.gap.unwrap_or(0) as i64);
+        let first = util::as_date(self.start_time.as_str());
+
+        (0..count as i32)
+            .map(|turn| first + step * turn)
+            .map(|starts_at| (starts_at, starts_at + duration))
+            .collect()
+    }
+}
+
+impl Conference {
+    pub fn followup_name(&self) -> String {
+        format!("Follow-up: {}", self.name)
+    }
+
+    /**
+     * The description of the conference, and its closing notes when the coach left any.
+     */
+    pub fn followup_description(&self) -> String {
+        match self.closing_notes.as_deref().map(str::trim) {
+            Some(notes) if !notes.is_empty() => format!("{}\n\nThe closing notes of the conference: {}", self.description(), notes),
+            _ => self.description().to_owned(),
+        }
+    }
+}
+
+/**
+ * The follow-ups spawned from a conference, with the mails queued to tell their
+ * members of them.
+ */
+pub struct Followups {
+    pub sessions: Vec<Session>,
+    pub notices: QueuedMails,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(gap: Option<i32>) -> FollowupRequest {
+        FollowupRequest {
+            conference_id: String::from("conf-1"),
+            member_ids: None,
+            start_time: String::from("2031-03-15T09:00:00Z"),
+            duration: 30,
+            gap,
+            override_reason: Some(String::from("  ")),
+        }
+    }
+
+    #[test]
+    fn should_schedule_the_followups_one_after_the_other() {
+        let at = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").unwrap();
+
+        let schedule = request(Some(10)).schedule(3);
+
+        assert_eq!((at("2031-03-15T09:00"), at("2031-03-15T09:30")), schedule[0]);
+        assert_eq!((at("2031-03-15T10:20"), at("2031-03-15T10:50")), schedule[2]);
+        assert_eq!(at("2031-03-15T09:30"), request(None).schedule(2)[1].0);
+    }
+
+    #[test]
+    fn should_check_the_gap_and_the_reason() {
+        assert!(request(Some(30)).validate().is_empty());
+        assert_eq!(1, request(Some(300)).validate().len());
+        assert_eq!(None, request(None).override_reason());
+    }
+}
+
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 
 use chrono::NaiveDateTime;
 
-use crate::models::enrollments::ManagedEnrollmentRequest;
+use crate::models::enrollments::{Enrollment, ManagedEnrollmentRequest};
+use crate::models::invoices::{as_money, InvoiceStatement};
+use crate::models::password_resets::NewPasswordReset;
 use crate::models::sessions::Session;
 use crate::models::users::User;
 use crate::models::programs::Program;
@@ -10,6 +12,7 @@ use crate::models::programs::Program;
 use crate::schema::correspondences;
 use crate::schema::mail_recipients;
 
+use crate::commons::plain_text;
 use crate::commons::util;
 
 const SELF_ENROLLMENT_MESSAGE :&str = "The coach will schedule a meeting to discuss with you at the earliest. Alternatively, you can converse with the coach, if required, from the discussion option available from your enrolled program. Thank you."; 
@@ -18,8 +21,8 @@ const SELF_ENROLLMENT_MESSAGE :&str = "The coach will schedule a meeting to disc
 pub struct Correspondence {
     pub id: String,
     pub from_user_id: String,
-    pub program_id: String,
-    pub enrollment_id: String,
+    pub program_id: Option<String>,
+    pub enrollment_id: Option<String>,
     pub from_email: String,
     pub subject: String,
     pub content: Option<String>,
@@ -33,6 +36,7 @@ pub struct Correspondence {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub mail_type: String,
+    pub content_text: Option<String>,
 }
 
 const SCHEDULE_SENDER_ID: &str = "schedule@krscode.com";
@@ -51,8 +55,8 @@ const EVENT: &str = "event";
 pub struct MailOut {
     pub id: String,
     pub from_user_id: String,
-    pub program_id: String,
-    pub enrollment_id: String,
+    pub program_id: Option<String>,
+    pub enrollment_id: Option<String>,
     pub from_email: String,
     pub subject: String,
     pub content: Option<String>,
@@ -62,10 +66,11 @@ pub struct MailOut {
     pub error: String,
     pub to_send_on: NaiveDateTime,
     pub mail_type: String,
+    pub content_text: Option<String>,
 }
 
 impl MailOut {
-    fn new(from_user_id: String, program_id: String, enrollment_id: String, subject: String, content: String, mail_type: &str) -> MailOut {
+    fn new(from_user_id: String, program_id: Option<String>, enrollment_id: Option<String>, subject: String, content: String, mail_type: &str) -> MailOut {
         let fuzzy_id = util::fuzzy_id();
 
         MailOut {
@@ -75,6 +80,7 @@ impl MailOut {
             program_id,
             enrollment_id,
             subject,
+            content_text: Some(plain_text::plain_text(content.as_str())),
             content: Some(content),
             in_out: OUT.to_owned(),
             status: PENDING.to_owned(),
@@ -87,9 +93,9 @@ impl MailOut {
 
     pub fn for_managed_enrollment(request: &ManagedEnrollmentRequest, enrollment_id: &str) -> MailOut {
         MailOut::new(
-            request.coach_id.to_owned(),
-            request.program_id.to_owned(),
-            enrollment_id.to_owned(),
+            request.coach_id.to_string(),
+            Some(request.program_id.to_owned()),
+            Some(enrollment_id.to_owned()),
             request.subject.to_owned(),
             request.message.to_owned(),
             NORMAL,
@@ -104,9 +110,59 @@ impl MailOut {
         let content = format!("{} {}",greetings,help);
 
         MailOut::new(
-            program.coach_id.to_owned(),
-            program.id.to_owned(),
-            enrollment_id.to_owned(),
+            program.coach_id.to_string(),
+            Some(program.id.to_string()),
+            Some(enrollment_id.to_owned()),
+            subject,
+            content,
+            NORMAL,
+        )
+    }
+
+    pub fn for_waitlist_promotion(program: &Program, enrollment_id: &str) -> MailOut {
+        let subject = format!("Enrollment in {}", program.name);
+        let content = format!("Greetings, a seat opened in {} and you are enrolled from its waitlist. {}", program.name, SELF_ENROLLMENT_MESSAGE);
+
+        MailOut::new(
+            program.coach_id.to_string(),
+            Some(program.id.to_string()),
+            Some(enrollment_id.to_owned()),
+            subject,
+            content,
+            NORMAL,
+        )
+    }
+
+    pub fn for_enrollment_cancellation(program: &Program, enrollment: &Enrollment, cancelled_by: &User) -> MailOut {
+        let subject = format!("Enrollment in {} is cancelled", program.name);
+        let reason = enrollment.end_reason.as_deref().unwrap_or_default();
+        let content = format!("Greetings, the enrollment in {} is cancelled by {}. The reason: {}", program.name, cancelled_by.full_name, reason);
+
+        MailOut::new(
+            program.coach_id.to_string(),
+            Some(program.id.to_string()),
+            Some(enrollment.id.to_string()),
+            subject,
+            content,
+            NORMAL,
+        )
+    }
+
+    /**
+     * The member is told of the coach taking over, from the new program; the note
+     * of the handover stays between the coaches.
+     */
+    pub fn for_enrollment_handover(to_program: &Program, enrollment: &Enrollment, from_coach: &User, to_coach: &User) -> MailOut {
+        let subject = format!("Your coach in {} is now {}", to_program.name, to_coach.full_name);
+        let content = format!(
+            "Greetings, {} handed your enrollment over to {}, who coaches you from now on in {}. The sessions yet to come are with {}; your plan and your notes stay as they are.",
+            from_coach.full_name, to_coach.full_name, to_program.name, to_coach.full_name
+        );
+
+        MailOut::new(
+            to_program.coach_id.to_string(),
+            Some(to_program.id.to_string()),
+            Some(enrollment.id.to_string()),
             subject,
             content,
             NORMAL,
@@ -117,9 +173,9 @@ impl MailOut {
         let content = FerrisEvent::new_session_event(session, coach, member);
 
         MailOut::new(
-            coach.id.to_owned(),
-            session.program_id.to_owned(),
-            session.enrollment_id.to_owned(),
+            coach.id.to_string(),
+            Some(session.program_id.to_string()),
+            Some(session.enrollment_id.to_string()),
             session.name.to_owned(),
             content,
             EVENT,
@@ -130,14 +186,93 @@ impl MailOut {
         let content = FerrisEvent::cancel_event(session, coach, member);
 
         MailOut::new(
-            coach.id.to_owned(),
-            session.program_id.to_owned(),
-            session.enrollment_id.to_owned(),
+            coach.id.to_string(),
+            Some(session.program_id.to_string()),
+            Some(session.enrollment_id.to_string()),
             session.name.to_owned(),
             content,
             EVENT,
         )
     }
+
+    /**
+     * A reminder of a session about to start, mailed to one of its people.
+     */
+    pub fn for_session_reminder(session: &Session, coach_id: &str, user: &User, subject: &str) -> MailOut {
+        let content = format!("Greetings {}. {}; please be on time.", user.full_name, subject);
+
+        MailOut::new(
+            coach_id.to_owned(),
+            Some(session.program_id.to_string()),
+            Some(session.enrollment_id.to_string()),
+            subject.to_owned(),
+            content,
+            NORMAL,
+        )
+    }
+
+    /**
+     * The weekly digest of an enrollment, mailed to its member from the coach.
+     */
+    pub fn for_weekly_digest(enrollment: &Enrollment, coach_id: &str, subject: String, content: String) -> MailOut {
+        MailOut::new(
+            coach_id.to_owned(),
+            Some(enrollment.program_id.to_string()),
+            Some(enrollment.id.to_string()),
+            subject,
+            content,
+            NORMAL,
+        )
+    }
+
+    /**
+     * The monthly invoice, with the link to its PDF when the links are set up;
+     * else the coach finds it among the invoices in the app.
+     */
+    pub fn for_invoice(coach: &User, statement: &InvoiceStatement, link: Option<String>) -> MailOut {
+        let subject = format!("Your invoice for {}", statement.period_name());
+        let download = match link {
+            Some(path) => format!("You may download the invoice from {} within the next 30 days.", path),
+            None => String::from("You may find the invoice among your invoices in the app."),
+        };
+        let content = format!(
+            "Greetings {}, your members paid {} {} for {} enrollments in {}. After the fee of the platform of {} {}, {} {} is due to you. {}",
+            coach.full_name,
+            as_money(statement.gross_cents()),
+            statement.currency,
+            statement.lines.len(),
+            statement.period_name(),
+            as_money(statement.fee_cents()),
+            statement.currency,
+            as_money(statement.net_cents()),
+            statement.currency,
+            download
+        );
+
+        MailOut::new(coach.id.to_string(), None, None, subject, content, NORMAL)
+    }
+
+    /**
+     * The token to reset the password with, mailed to the user only.
+     */
+    pub fn for_password_reset(user: &User, reset: &NewPasswordReset) -> MailOut {
+        let subject = String::from("Reset your password");
+        let content = format!(
+            "Greetings {}, we received a request to reset your password. Please enter the token {} on the password reset page within {} minutes. If you did not ask for it, you may ignore this mail; your password stays as it is.",
+            user.full_name,
+            reset.token,
+            reset.valid_minutes()
+        );
+
+        MailOut::new(user.id.to_string(), None, None, subject, content, NORMAL)
+    }
+
+    /**
+     * A mail about a support ticket, which belongs to no program.
+     */
+    pub fn for_ticket(from_user_id: &str, subject: String, content: String) -> MailOut {
+        MailOut::new(from_user_id.to_owned(), None, None, subject, content, NORMAL)
+    }
 }
 
 #[derive(Queryable, Debug, Associations, Identifiable, Insertable)]
@@ -156,7 +291,7 @@ impl MailRecipient {
         let to_record = MailRecipient {
             id: util::fuzzy_id(),
             correspondence_id: correspondence_id.to_owned(),
-            to_user_id: Some(member.id.to_owned()),
+            to_user_id: Some(member.id.to_string()),
             to_email: member.email.to_owned(),
             to_type: TO.to_owned(),
         };
@@ -164,13 +299,46 @@ impl MailRecipient {
         let cc_record = MailRecipient {
             id: util::fuzzy_id(),
             correspondence_id: correspondence_id.to_owned(),
-            to_user_id: Some(coach.id.to_owned()),
+            to_user_id: Some(coach.id.to_string()),
             to_email: coach.email.to_owned(),
             to_type: CC.to_owned(),
         };
 
         vec![to_record, cc_record]
     }
+
+    pub fn build_to(to_users: &[&User], correspondence_id: &str) -> Vec<MailRecipient> {
+        to_users
+            .iter()
+            .map(|user| MailRecipient {
+                id: util::fuzzy_id(),
+                correspondence_id: correspondence_id.to_owned(),
+                to_user_id: Some(user.id.to_string()),
+                to_email: user.email.to_owned(),
+                to_type: TO.to_owned(),
+            })
+            .collect()
+    }
+}
+
+/**
+ * What a batch of mails left in the queue.
+ */
+#[derive(Default)]
+pub struct QueuedMails {
+    pub mails: i32,
+    pub recipients: i32,
+}
+
+#[juniper::object(description = "The mails queued for sending")]
+impl QueuedMails {
+    pub fn mails(&self) -> i32 {
+        self.mails
+    }
+
+    pub fn recipients(&self) -> i32 {
+        self.recipients
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -200,6 +368,14 @@ impl Correspondence {
         }
     }
 
+    #[graphql(description = "The content without its markup, the text part of the mail")]
+    pub fn plain_content(&self) -> String {
+        match &self.content_text {
+            Some(value) => value.to_owned(),
+            None => plain_text::plain_text(self.content.as_deref().unwrap_or_default()),
+        }
+    }
+
     pub fn mail_type(&self) -> &str {
         self.mail_type.as_str()
     }
@@ -252,7 +428,7 @@ impl FerrisEvent {
         let end_date = session.original_end_date;
 
         let event = FerrisEvent {
-            id: session.id.to_owned(),
+            id: session.id.to_string(),
             sequence: 1,
             organizer: Some(coach.email.clone()),
             attendee: Some(member.email.clone()),
@@ -271,7 +447,7 @@ impl FerrisEvent {
         let end_date = session.original_end_date;
 
         let event = FerrisEvent {
-            id: session.id.to_owned(),
+            id: session.id.to_string(),
             sequence: 99,
             organizer: Some(coach.email.clone()),
             attendee: Some(member.email.clone()),
@@ -0,0 +1,272 @@
+/**
+ * A discount code the coach of a program hands out: a percent or an amount off
+ * the price, within a window in UTC and up to a number of uses. The coupon is
+ * kept on the parent program and holds for its peer programs too.
+ *
+ * The codes are kept in capitals; a member may type them in any case.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::schema::{coupon_redemptions, coupons};
+
+const MIN_CODE: usize = 3;
+const MAX_CODE: usize = 40;
+
+const NOT_YET_VALID: &str = "The coupon is not valid yet.";
+const EXPIRED: &str = "The coupon has expired.";
+const USED_UP: &str = "The coupon is used up.";
+
+#[derive(Queryable, Debug)]
+pub struct Coupon {
+    pub id: String,
+    pub program_id: String,
+    pub coach_id: String,
+    pub code: String,
+    pub percent_off: Option<i32>,
+    pub amount_off_cents: Option<i32>,
+    pub valid_from: NaiveDateTime,
+    pub valid_until: NaiveDateTime,
+    pub max_uses: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A discount code on the price of a program")]
+impl Coupon {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn code(&self) -> &str {
+        self.code.as_str()
+    }
+
+    pub fn percent_off(&self) -> Option<i32> {
+        self.percent_off
+    }
+
+    #[graphql(description = "The amount off in the cents of the currency of the program")]
+    pub fn amount_off_cents(&self) -> Option<i32> {
+        self.amount_off_cents
+    }
+
+    pub fn valid_from(&self) -> NaiveDateTime {
+        self.valid_from
+    }
+
+    pub fn valid_until(&self) -> NaiveDateTime {
+        self.valid_until
+    }
+
+    #[graphql(description = "The most enrollments the coupon is good for; none for any number")]
+    pub fn max_uses(&self) -> Option<i32> {
+        self.max_uses
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+impl Coupon {
+    /**
+     * The coupon may be used at the time, having been used so many times.
+     */
+    pub fn check(&self, at: NaiveDateTime, uses: i64) -> Result<(), &'static str> {
+        if at < self.valid_from {
+            return Err(NOT_YET_VALID);
+        }
+
+        if at > self.valid_until {
+            return Err(EXPIRED);
+        }
+
+        if self.max_uses.is_some_and(|max_uses| uses >= max_uses as i64) {
+            return Err(USED_UP);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * The price after the discount, never below nothing; a percent off rounds
+     * half a cent in favour of the member.
+     */
+    pub fn discounted(&self, price_cents: i32) -> i32 {
+        let discount = match (self.percent_off, self.amount_off_cents) {
+            (Some(percent), _) => ((price_cents as i64 * percent as i64 + 50) / 100) as i32,
+            (None, Some(amount)) => amount,
+            (None, None) => 0,
+        };
+
+        (price_cents - discount).max(0)
+    }
+}
+
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+#[derive(Insertable)]
+#[table_name = "coupons"]
+pub struct NewCoupon {
+    pub id: String,
+    pub program_id: String,
+    pub coach_id: String,
+    pub code: String,
+    pub percent_off: Option<i32>,
+    pub amount_off_cents: Option<i32>,
+    pub valid_from: NaiveDateTime,
+    pub valid_until: NaiveDateTime,
+    pub max_uses: Option<i32>,
+}
+
+/**
+ * A coupon takes either a percent or an amount off. The window is in UTC, like
+ * 2021-02-20T22:00:00Z.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewCouponRequest {
+    pub coach_id: String,
+    pub program_id: ProgramId,
+    pub code: String,
+    pub percent_off: Option<i32>,
+    pub amount_off_cents: Option<i32>,
+    pub valid_from: String,
+    pub valid_until: String,
+    pub max_uses: Option<i32>,
+}
+
+impl NewCouponRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        let code = normalize_code(self.code.as_str());
+        if code.len() < MIN_CODE || code.len() > MAX_CODE || !code.chars().all(|letter| letter.is_ascii_alphanumeric() || letter == '-' || letter == '_') {
+            errors.push(ValidationError::new("code", "The code should have 3 to 40 letters, digits, dashes or underscores."));
+        }
+
+        match (self.percent_off, self.amount_off_cents) {
+            (Some(percent), None) if percent > 0 && percent <= 100 => {}
+            (None, Some(amount)) if amount > 0 => {}
+            _ => errors.push(ValidationError::new("discount", "The coupon should take either 1 to 100 percent or an amount more than 0 off.")),
+        }
+
+        if !util::is_valid_date(self.valid_from.as_str()) || !util::is_valid_date(self.valid_until.as_str()) {
+            errors.push(ValidationError::new("valid_from", "The window should be like 2021-02-20T22:00:00Z."));
+        } else if util::as_date(self.valid_until.as_str()) <= util::as_date(self.valid_from.as_str()) {
+            errors.push(ValidationError::new("valid_until", "The coupon should expire after it starts."));
+        }
+
+        if self.max_uses.is_some_and(|max_uses| max_uses <= 0) {
+            errors.push(ValidationError::new("max_uses", "The coupon should be good for one use at least."));
+        }
+
+        errors
+    }
+
+    pub fn to_row(&self, parent_program_id: &str) -> NewCoupon {
+        NewCoupon {
+            id: util::fuzzy_id(),
+            program_id: parent_program_id.to_owned(),
+            coach_id: self.coach_id.to_owned(),
+            code: normalize_code(self.code.as_str()),
+            percent_off: self.percent_off,
+            amount_off_cents: self.amount_off_cents,
+            valid_from: util::as_date(self.valid_from.as_str()),
+            valid_until: util::as_date(self.valid_until.as_str()),
+            max_uses: self.max_uses,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "coupon_redemptions"]
+pub struct NewCouponRedemption {
+    pub enrollment_id: String,
+    pub coupon_id: String,
+    pub discount_cents: i32,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(percent_off: Option<i32>, amount_off_cents: Option<i32>) -> NewCouponRequest {
+        NewCouponRequest {
+            coach_id: String::from("c-1"),
+            program_id: ProgramId::from("p-1"),
+            code: String::from(" spring-21 "),
+            percent_off,
+            amount_off_cents,
+            valid_from: String::from("2021-03-01T00:00:00Z"),
+            valid_until: String::from("2021-03-31T00:00:00Z"),
+            max_uses: Some(2),
+        }
+    }
+
+    fn coupon(percent_off: Option<i32>, amount_off_cents: Option<i32>) -> Coupon {
+        let row = request(percent_off, amount_off_cents).to_row("p-1");
+
+        Coupon {
+            id: row.id,
+            program_id: row.program_id,
+            coach_id: row.coach_id,
+            code: row.code,
+            percent_off: row.percent_off,
+            amount_off_cents: row.amount_off_cents,
+            valid_from: row.valid_from,
+            valid_until: row.valid_until,
+            max_uses: row.max_uses,
+            created_at: row.valid_from,
+        }
+    }
+
+    #[test]
+    fn should_take_either_a_percent_or_an_amount_off() {
+        assert!(request(Some(20), None).validate().is_empty());
+        assert!(request(None, Some(1500)).validate().is_empty());
+        assert_eq!(1, request(Some(20), Some(1500)).validate().len());
+        assert_eq!(1, request(Some(101), None).validate().len());
+        assert_eq!(1, request(None, None).validate().len());
+        assert_eq!("SPRING-21", request(Some(20), None).to_row("p-1").code);
+    }
+
+    #[test]
+    fn should_discount_the_price_never_below_nothing() {
+        assert_eq!(3920, coupon(Some(20), None).discounted(4900));
+        assert_eq!(3400, coupon(None, Some(1500)).discounted(4900));
+        assert_eq!(0, coupon(None, Some(9900)).discounted(4900));
+        assert_eq!(0, coupon(Some(100), None).discounted(4900));
+    }
+
+    #[test]
+    fn should_hold_within_the_window_and_the_uses() {
+        let coupon = coupon(Some(20), None);
+        let at = |date: &str| util::as_date(date);
+
+        assert_eq!(Ok(()), coupon.check(at("2021-03-10T00:00:00Z"), 1));
+        assert_eq!(Err(NOT_YET_VALID), coupon.check(at("2021-02-28T00:00:00Z"), 0));
+        assert_eq!(Err(EXPIRED), coupon.check(at("2021-04-01T00:00:00Z"), 0));
+        assert_eq!(Err(USED_UP), coupon.check(at("2021-03-10T00:00:00Z"), 2));
+    }
+}
@@ -0,0 +1,167 @@
+/**
+ * Everything a user has kept with us, gathered in one JSON archive for the user
+ * to take away: the account, the enrollments, the tasks of them, the notes and
+ * the discussions the user wrote.
+ *
+ * The times are written as they are kept, in UTC.
+ */
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::models::discussions::Discussion;
+use crate::models::enrollments::Enrollment;
+use crate::models::notes::Note;
+use crate::models::programs::Program;
+use crate::models::tasks::Task;
+use crate::models::users::User;
+
+pub const EXPORTS_DIR: &str = "exports";
+
+#[derive(Serialize, Debug)]
+pub struct DataExport {
+    pub exported_at: String,
+    pub account: AccountEntry,
+    pub enrollments: Vec<EnrollmentEntry>,
+    pub tasks: Vec<TaskEntry>,
+    pub notes: Vec<NoteEntry>,
+    pub discussions: Vec<DiscussionEntry>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AccountEntry {
+    pub id: String,
+    pub full_name: String,
+    pub email: String,
+    pub user_type: String,
+    pub created_at: String,
+}
+
+impl AccountEntry {
+    pub fn from(user: &User) -> AccountEntry {
+        AccountEntry {
+            id: user.id.to_string(),
+            full_name: user.full_name.to_owned(),
+            email: user.email.to_owned(),
+            user_type: user.user_type.to_owned(),
+            created_at: stamp(user.created_at),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EnrollmentEntry {
+    pub id: String,
+    pub program: String,
+    pub enrolled_at: String,
+    pub ended_at: Option<String>,
+    pub end_reason: Option<String>,
+}
+
+impl EnrollmentEntry {
+    pub fn from(enrollment: &Enrollment, program: &Program) -> EnrollmentEntry {
+        EnrollmentEntry {
+            id: enrollment.id.to_string(),
+            program: program.name.to_owned(),
+            enrolled_at: stamp(enrollment.created_at),
+            ended_at: enrollment.ended_at.map(stamp),
+            end_reason: enrollment.end_reason.to_owned(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TaskEntry {
+    pub id: String,
+    pub enrollment_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub scheduled_start: String,
+    pub scheduled_end: String,
+    pub actual_start: Option<String>,
+    pub actual_end: Option<String>,
+    pub response: Option<String>,
+    pub closing_notes: Option<String>,
+}
+
+impl TaskEntry {
+    pub fn from(task: &Task) -> TaskEntry {
+        TaskEntry {
+            id: task.id.to_owned(),
+            enrollment_id: task.enrollment_id.to_owned(),
+            name: task.name.to_owned(),
+            description: task.description.to_owned(),
+            scheduled_start: stamp(task.revised_start_date.unwrap_or(task.original_start_date)),
+            scheduled_end: stamp(task.revised_end_date.unwrap_or(task.original_end_date)),
+            actual_start: task.actual_start_date.map(stamp),
+            actual_end: task.actual_end_date.map(stamp),
+            response: task.response.to_owned(),
+            closing_notes: task.closing_notes.to_owned(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct NoteEntry {
+    pub id: String,
+    pub session_id: String,
+    pub description: String,
+    pub is_private: bool,
+    pub created_at: String,
+}
+
+impl NoteEntry {
+    pub fn from(note: &Note) -> NoteEntry {
+        NoteEntry {
+            id: note.id.to_owned(),
+            session_id: note.session_id.to_owned(),
+            description: note.description.to_owned(),
+            is_private: note.is_private,
+            created_at: stamp(note.created_at),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DiscussionEntry {
+    pub id: String,
+    pub enrollment_id: String,
+    pub description: String,
+    pub created_at: String,
+}
+
+impl DiscussionEntry {
+    pub fn from(discussion: &Discussion) -> DiscussionEntry {
+        DiscussionEntry {
+            id: discussion.id.to_owned(),
+            enrollment_id: discussion.enrollment_id.to_owned(),
+            description: discussion.description.to_owned(),
+            created_at: stamp(discussion.created_at),
+        }
+    }
+}
+
+pub fn stamp(at: NaiveDateTime) -> String {
+    at.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/**
+ * One archive a second at most; a later export of the same second replaces it.
+ */
+pub fn export_file_name(at: NaiveDateTime) -> String {
+    format!("my-data-{}.json", at.format("%Y%m%d%H%M%S"))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn should_name_the_archive_by_its_time() {
+        let at = NaiveDate::from_ymd(2021, 3, 11).and_hms(9, 5, 7);
+
+        assert_eq!("my-data-20210311090507.json", export_file_name(at));
+        assert_eq!("2021-03-11T09:05:07", stamp(at));
+    }
+}
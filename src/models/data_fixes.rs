@@ -0,0 +1,124 @@
+/**
+ * Data fixes are the guarded repairs an admin performs instead of
+ * running raw SQL against the production database.
+ *
+ * Every fix can be previewed with dry_run, and every applied fix
+ * leaves an audit entry with the before and after states. The admin
+ * is the signed in user, never an id in the request.
+ */
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, SessionId};
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RelinkEnrollmentRequest {
+    pub enrollment_id: String,
+    pub program_id: ProgramId,
+    pub reason: String,
+    pub dry_run: bool,
+}
+
+impl RelinkEnrollmentRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "Enrollment id is a must."));
+        }
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The correct program id is a must."));
+        }
+
+        if self.reason.trim().is_empty() {
+            errors.push(ValidationError::new("reason", "Please record the reason for the fix."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SwapSessionDatesRequest {
+    pub session_id: SessionId,
+    pub reason: String,
+    pub dry_run: bool,
+}
+
+impl SwapSessionDatesRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        if self.reason.trim().is_empty() {
+            errors.push(ValidationError::new("reason", "Please record the reason for the fix."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ReassignNoteAuthorRequest {
+    pub note_id: String,
+    pub author_id: String,
+    pub reason: String,
+    pub dry_run: bool,
+}
+
+impl ReassignNoteAuthorRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.note_id.trim().is_empty() {
+            errors.push(ValidationError::new("note_id", "Note id is a must."));
+        }
+
+        if self.author_id.trim().is_empty() {
+            errors.push(ValidationError::new("author_id", "The correct author id is a must."));
+        }
+
+        if self.reason.trim().is_empty() {
+            errors.push(ValidationError::new("reason", "Please record the reason for the fix."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The outcome of a fix. When applied is false, nothing was written
+ * and the after state is what the fix would produce.
+ */
+pub struct FixPreview {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+    pub applied: bool,
+}
+
+#[juniper::object(description = "The before and after states of a data fix")]
+impl FixPreview {
+    pub fn entity_type(&self) -> &str {
+        self.entity_type.as_str()
+    }
+
+    pub fn entity_id(&self) -> &str {
+        self.entity_id.as_str()
+    }
+
+    pub fn before(&self) -> String {
+        self.before.to_string()
+    }
+
+    pub fn after(&self) -> String {
+        self.after.to_string()
+    }
+
+    pub fn applied(&self) -> bool {
+        self.applied
+    }
+}
@@ -0,0 +1,68 @@
+/**
+ * A sandbox of the demo deployment: a coach and a member of their own, seeded
+ * with a program to try the platform on. See demo_mode for the deployment.
+ */
+use serde::Serialize;
+
+use crate::commons::ids::UserId;
+use crate::commons::password;
+use crate::commons::util;
+use crate::models::users::NewUser;
+use crate::schema::demo_sandboxes;
+
+pub const DEMO_DOMAIN: &str = "demo.ferris.test";
+pub const DEMO_PASSWORD: &str = "demo";
+
+#[derive(Insertable)]
+#[table_name = "demo_sandboxes"]
+pub struct NewDemoSandbox {
+    pub id: String,
+    pub coach_user_id: String,
+    pub member_user_id: String,
+    pub program_id: String,
+}
+
+/**
+ * The users of a sandbox are namespaced by the id of the sandbox in their email,
+ * hence the sandboxes never collide on a login.
+ */
+pub fn sandbox_user(sandbox_id: &str, full_name: &str, kind: &str) -> NewUser {
+    NewUser {
+        id: UserId::from(util::fuzzy_id()),
+        full_name: full_name.to_owned(),
+        email: sandbox_email(sandbox_id, kind),
+        user_type: kind.to_owned(),
+        password: password::hash(DEMO_PASSWORD),
+        utc_offset: 0,
+    }
+}
+
+pub fn sandbox_email(sandbox_id: &str, kind: &str) -> String {
+    format!("{}@{}.{}", kind, sandbox_id, DEMO_DOMAIN)
+}
+
+// What the visitor needs to sign in; the id goes along with every GraphQL request.
+#[derive(Serialize, Debug)]
+pub struct SandboxCredentials {
+    pub sandbox_id: String,
+    pub coach_email: String,
+    pub member_email: String,
+    pub password: String,
+    pub program_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_namespace_the_users_by_the_sandbox() {
+        let coach = sandbox_user("s-1", "Demo Coach", util::COACH);
+        let member = sandbox_user("s-1", "Demo Member", util::MEMBER);
+
+        assert_eq!("coach@s-1.demo.ferris.test", coach.email);
+        assert_eq!("member@s-1.demo.ferris.test", member.email);
+        assert_ne!(sandbox_email("s-1", util::COACH), sandbox_email("s-2", util::COACH));
+    }
+}
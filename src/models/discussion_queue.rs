@@ -5,6 +5,7 @@ use crate::models::discussions::NewDiscussionRequest;
 use crate::models::users::User;
 
 use chrono::NaiveDateTime;
+use serde::Serialize;
 
 #[derive(Queryable, Debug)]
 pub struct Feed {
@@ -20,6 +21,7 @@ pub struct Feed {
     pub coach_name: String,
     pub member_id: String,
     pub member_name: String,
+    pub read_at: Option<NaiveDateTime>,
 }
 
 #[juniper::object]
@@ -64,6 +66,10 @@ impl Feed {
         self.member_name.as_str()
     }
 
+    #[graphql(description = "When the receiver read the feed; none while it is pending")]
+    pub fn read_at(&self) -> Option<NaiveDateTime> {
+        self.read_at
+    }
 }
 
 #[derive(Insertable)]
@@ -98,6 +104,26 @@ impl NewFeed {
     }
 }
 
+/**
+ * The given discussions of the enrollment are marked read for the user; the
+ * ids of other enrollments or of other users are ignored.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ReadDiscussionRequest {
+    pub user_id: String,
+    pub enrollment_id: String,
+    pub discussion_ids: Vec<String>,
+}
+
+/**
+ * Without an enrollment, every pending feed of the user is marked read.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ReadAllRequest {
+    pub user_id: String,
+    pub enrollment_id: Option<String>,
+}
+
 pub struct PendingFeed {
     pub description: String,
     pub feed: Feed,
@@ -119,3 +145,14 @@ impl PendingFeed {
         &self.user
     }
 }
+
+/**
+ * What awaits the user, pushed over the feed socket; see feed_events.
+ */
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedCounts {
+    pub user_id: String,
+    pub pending_discussions: i64,
+    pub unread_notifications: i64,
+}
@@ -0,0 +1,105 @@
+/**
+ * An enrollment belongs to the coach of its program. The coach may share it with
+ * the peer coaches of the program, so that one of them can stand in: a co-coach
+ * finds the member among their own and holds the role of the coach on the
+ * enrollment, its tasks and its sessions.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::schema::enrollment_coaches;
+
+#[derive(Queryable, Debug)]
+pub struct EnrollmentCoach {
+    pub enrollment_id: String,
+    pub coach_id: String,
+    pub added_by_id: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A peer coach sharing an enrollment with the coach of its program")]
+impl EnrollmentCoach {
+    pub fn enrollment_id(&self) -> &str {
+        self.enrollment_id.as_str()
+    }
+
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn added_by_id(&self) -> &str {
+        self.added_by_id.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "enrollment_coaches"]
+pub struct NewEnrollmentCoach {
+    pub enrollment_id: String,
+    pub coach_id: String,
+    pub added_by_id: String,
+}
+
+/**
+ * The coach of the program adds or removes the co-coach; a co-coach may also
+ * remove themselves.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct EnrollmentCoachRequest {
+    pub coach_id: String,
+    pub enrollment_id: String,
+    pub co_coach_id: String,
+}
+
+impl EnrollmentCoachRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "Enrollment id is a must."));
+        }
+
+        if self.co_coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("co_coach_id", "Co-coach id is a must."));
+        }
+
+        errors
+    }
+
+    pub fn to_row(&self) -> NewEnrollmentCoach {
+        NewEnrollmentCoach {
+            enrollment_id: self.enrollment_id.to_owned(),
+            coach_id: self.co_coach_id.to_owned(),
+            added_by_id: self.coach_id.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_add_the_co_coach_on_behalf_of_the_coach() {
+        let request = EnrollmentCoachRequest {
+            coach_id: String::from("c-1"),
+            enrollment_id: String::from("e-1"),
+            co_coach_id: String::from("c-2"),
+        };
+
+        let row = request.to_row();
+
+        assert!(request.validate().is_empty());
+        assert_eq!("c-2", row.coach_id);
+        assert_eq!("c-1", row.added_by_id);
+    }
+}
@@ -0,0 +1,154 @@
+/**
+ * A coach hands an enrollment over to a peer coach: the enrollment moves from
+ * the spawned program of the one to the spawned program of the other, along
+ * with the one to one sessions yet to start and the open tasks of the coach.
+ * What took place stays as it was.
+ *
+ * The note is for the coach taking over, not for the member, who is only told
+ * who coaches them from now on.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::schema::enrollment_handovers;
+
+const MAX_NOTE: usize = 5000;
+
+#[derive(Queryable, Debug)]
+pub struct EnrollmentHandover {
+    pub id: String,
+    pub enrollment_id: String,
+    pub from_program_id: String,
+    pub to_program_id: String,
+    pub from_coach_id: String,
+    pub to_coach_id: String,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "An enrollment handed over from one peer coach to another")]
+impl EnrollmentHandover {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn enrollment_id(&self) -> &str {
+        self.enrollment_id.as_str()
+    }
+
+    pub fn from_program_id(&self) -> &str {
+        self.from_program_id.as_str()
+    }
+
+    pub fn to_program_id(&self) -> &str {
+        self.to_program_id.as_str()
+    }
+
+    pub fn from_coach_id(&self) -> &str {
+        self.from_coach_id.as_str()
+    }
+
+    pub fn to_coach_id(&self) -> &str {
+        self.to_coach_id.as_str()
+    }
+
+    pub fn note(&self) -> &str {
+        self.note.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "enrollment_handovers"]
+pub struct NewEnrollmentHandover {
+    pub id: String,
+    pub enrollment_id: String,
+    pub from_program_id: String,
+    pub to_program_id: String,
+    pub from_coach_id: String,
+    pub to_coach_id: String,
+    pub note: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct TransferEnrollmentRequest {
+    pub coach_id: String,
+    pub enrollment_id: String,
+    pub to_program_id: ProgramId,
+    pub note: String,
+}
+
+impl TransferEnrollmentRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "Enrollment id is a must."));
+        }
+
+        if self.to_program_id.trim().is_empty() {
+            errors.push(ValidationError::new("to_program_id", "The program to hand over to is a must."));
+        }
+
+        let length = self.note.trim().chars().count();
+        if length == 0 || length > MAX_NOTE {
+            errors.push(ValidationError::new("note", "The handover note should have 1 to 5000 characters."));
+        }
+
+        errors
+    }
+
+    pub fn to_row(&self, from_program_id: &str, to_coach_id: &str) -> NewEnrollmentHandover {
+        NewEnrollmentHandover {
+            id: util::fuzzy_id(),
+            enrollment_id: self.enrollment_id.to_owned(),
+            from_program_id: from_program_id.to_owned(),
+            to_program_id: self.to_program_id.to_string(),
+            from_coach_id: self.coach_id.to_owned(),
+            to_coach_id: to_coach_id.to_owned(),
+            note: self.note.trim().to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(note: &str) -> TransferEnrollmentRequest {
+        TransferEnrollmentRequest {
+            coach_id: String::from("c-1"),
+            enrollment_id: String::from("e-1"),
+            to_program_id: ProgramId::from("p-2"),
+            note: note.to_owned(),
+        }
+    }
+
+    #[test]
+    fn should_ask_for_a_handover_note() {
+        assert_eq!(1, request("  ").validate().len());
+        assert_eq!(1, request("a".repeat(MAX_NOTE + 1).as_str()).validate().len());
+        assert!(request("Prefers the mornings").validate().is_empty());
+    }
+
+    #[test]
+    fn should_record_the_handover_between_the_coaches() {
+        let row = request(" Prefers the mornings ").to_row("p-1", "c-2");
+
+        assert_eq!("p-1", row.from_program_id);
+        assert_eq!("p-2", row.to_program_id);
+        assert_eq!("c-1", row.from_coach_id);
+        assert_eq!("c-2", row.to_coach_id);
+        assert_eq!("Prefers the mornings", row.note);
+    }
+}
@@ -4,18 +4,21 @@ use crate::models::programs::Program;
 use crate::models::users::User;
 
 use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{EnrollmentId, ProgramId, UserId};
 use crate::commons::util;
 
 use crate::schema::enrollments;
 
 #[derive(Queryable, Debug, Identifiable)]
 pub struct Enrollment {
-    pub id: String,
-    pub program_id: String,
-    pub member_id: String,
+    pub id: EnrollmentId,
+    pub program_id: ProgramId,
+    pub member_id: UserId,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub is_new: bool,
+    pub ended_at: Option<NaiveDateTime>,
+    pub end_reason: Option<String>,
 }
 
 #[juniper::object(description = "The fields we offer to the Web-UI ")]
@@ -35,13 +38,26 @@ impl Enrollment {
     pub fn created_at(&self) -> &NaiveDateTime {
         &self.created_at
     }
+    pub fn ended_at(&self) -> Option<NaiveDateTime> {
+        self.ended_at
+    }
+    pub fn end_reason(&self) -> Option<&str> {
+        self.end_reason.as_deref()
+    }
+}
+
+impl Enrollment {
+    pub fn is_cancelled(&self) -> bool {
+        self.ended_at.is_some()
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct NewEnrollmentRequest {
-    pub program_id: String,
-    pub user_id: String,
+    pub program_id: ProgramId,
+    pub user_id: UserId,
     pub coach_id: String,
+    pub coupon_code: Option<String>,
 }
 
 impl NewEnrollmentRequest {
@@ -60,6 +76,43 @@ impl NewEnrollmentRequest {
             errors.push(ValidationError::new("coach_id", "We need the coach id who offers the program."));
         }
 
+        if self.coupon_code.as_deref().is_some_and(|code| code.trim().is_empty()) {
+            errors.push(ValidationError::new("coupon_code", "The coupon code should not be blank."));
+        }
+
+        errors
+    }
+}
+
+const MAX_REASON: usize = 1000;
+
+/**
+ * Either party ends the enrollment: the member withdrawing, or the coach cancelling it.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct CancelEnrollmentRequest {
+    pub enrollment_id: String,
+    pub user_id: String,
+    pub reason: String,
+}
+
+impl CancelEnrollmentRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "The Enrollment id is invalid."));
+        }
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "The User id is invalid."));
+        }
+
+        let length = self.reason.trim().chars().count();
+        if length == 0 || length > MAX_REASON {
+            errors.push(ValidationError::new("reason", "The reason should have 1 to 1000 characters."));
+        }
+
         errors
     }
 }
@@ -94,8 +147,8 @@ impl NewEnrollment {
         let fuzzy_id = util::fuzzy_id();
         NewEnrollment {
             id: fuzzy_id,
-            program_id: program.id.to_owned(),
-            member_id: user.id.to_owned(),
+            program_id: program.id.to_string(),
+            member_id: user.id.to_string(),
         }
     }
 }
@@ -103,9 +156,32 @@ impl NewEnrollment {
 #[derive(juniper::GraphQLInputObject)]
 pub struct ManagedEnrollmentRequest {
     pub program_id: String,
-    pub coach_id: String,
+    pub coach_id: UserId,
     pub member_mail: String,
     pub subject: String,
     pub message: String
 }
 
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_need_a_reason_to_cancel() {
+        let mut request = CancelEnrollmentRequest {
+            enrollment_id: String::from("e-1"),
+            user_id: String::from("m-1"),
+            reason: String::from("  "),
+        };
+
+        assert_eq!(1, request.validate().len());
+
+        request.reason = "x".repeat(MAX_REASON + 1);
+        assert_eq!(1, request.validate().len());
+
+        request.reason = String::from("Moving to another city.");
+        assert!(request.validate().is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+/**
+ * The share the platform keeps from what a coach earns. The fees are kept as
+ * schedules: a schedule holds a default fee and, optionally, a fee for the
+ * programs of a genre, which is the tier of a program. The fees are in basis
+ * points, 1500 being 15%.
+ *
+ * A schedule applies from its effective_from until the next one; a schedule
+ * is never changed once published, so that the earnings of the past are worked
+ * out again with the fees of their time.
+ */
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::util;
+use crate::schema::{fee_rules, fee_schedules};
+
+pub const FULL_BPS: i32 = 10_000;
+
+const MAX_RULES: usize = 50;
+const MAX_NOTE: usize = 255;
+const MAX_CHARGES: usize = 500;
+
+#[derive(Queryable, Debug)]
+pub struct FeeSchedule {
+    pub id: String,
+    pub effective_from: NaiveDateTime,
+    pub note: String,
+    pub created_by_id: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Debug)]
+pub struct FeeRule {
+    pub schedule_id: String,
+    pub genre_id: Option<String>,
+    pub fee_bps: i32,
+}
+
+#[juniper::object(description = "The fee the platform keeps from the programs of a genre, or from all the others")]
+impl FeeRule {
+    #[graphql(description = "The genre of the programs; none for the default fee")]
+    pub fn genre_id(&self) -> Option<&str> {
+        self.genre_id.as_deref()
+    }
+
+    #[graphql(description = "The fee in basis points; 1500 is 15%")]
+    pub fn fee_bps(&self) -> i32 {
+        self.fee_bps
+    }
+}
+
+pub struct FeeScheduleView {
+    pub schedule: FeeSchedule,
+    pub rules: Vec<FeeRule>,
+}
+
+impl FeeScheduleView {
+    /**
+     * The fee for the programs of the genre; the default fee for the others.
+     */
+    pub fn fee_for(&self, genre_id: Option<&str>) -> i32 {
+        let of_genre = genre_id.and_then(|genre| self.rules.iter().find(|rule| rule.genre_id.as_deref() == Some(genre)));
+        let default = || self.rules.iter().find(|rule| rule.genre_id.is_none());
+
+        of_genre.or_else(default).map(|rule| rule.fee_bps).unwrap_or(0)
+    }
+}
+
+#[juniper::object(name = "FeeSchedule", description = "The platform fees from a point in time until the next schedule")]
+impl FeeScheduleView {
+    pub fn id(&self) -> &str {
+        self.schedule.id.as_str()
+    }
+
+    pub fn effective_from(&self) -> NaiveDateTime {
+        self.schedule.effective_from
+    }
+
+    pub fn note(&self) -> &str {
+        self.schedule.note.as_str()
+    }
+
+    pub fn created_by_id(&self) -> &str {
+        self.schedule.created_by_id.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.schedule.created_at
+    }
+
+    pub fn rules(&self) -> &Vec<FeeRule> {
+        &self.rules
+    }
+}
+
+/**
+ * The platform fee of an amount, rounding half a cent up; the rest is the share of the coach.
+ */
+pub fn split(amount_cents: i64, fee_bps: i32) -> (i64, i64) {
+    let fee = (amount_cents * fee_bps as i64 + (FULL_BPS / 2) as i64) / FULL_BPS as i64;
+
+    (fee, amount_cents - fee)
+}
+
+#[derive(Insertable)]
+#[table_name = "fee_schedules"]
+pub struct NewFeeSchedule {
+    pub id: String,
+    pub effective_from: NaiveDateTime,
+    pub note: String,
+    pub created_by_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "fee_rules"]
+pub struct NewFeeRule {
+    pub id: String,
+    pub schedule_id: String,
+    pub genre_id: Option<String>,
+    pub fee_bps: i32,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct FeeRuleInput {
+    pub genre_id: Option<String>,
+    pub fee_bps: i32,
+}
+
+impl FeeRuleInput {
+    fn genre_id(&self) -> Option<&str> {
+        self.genre_id.as_deref().map(|value| value.trim()).filter(|value| !value.is_empty())
+    }
+}
+
+// The admin publishing the schedule is the signed in user, never an id in the request.
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewFeeScheduleRequest {
+    pub effective_from: String,
+    pub note: String,
+    pub rules: Vec<FeeRuleInput>,
+}
+
+impl NewFeeScheduleRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if !util::is_valid_date(self.effective_from.as_str()) {
+            errors.push(ValidationError::new("effective_from", "unparsable date."));
+        } else if util::is_past_date(util::as_date(self.effective_from.as_str())) {
+            errors.push(ValidationError::new("effective_from", "A schedule may not change the fees of the past."));
+        }
+
+        if self.note.chars().count() > MAX_NOTE {
+            errors.push(ValidationError::new("note", "The note should have at most 255 characters."));
+        }
+
+        if self.rules.is_empty() || self.rules.len() > MAX_RULES {
+            errors.push(ValidationError::new("rules", "A schedule should have 1 to 50 rules."));
+        }
+
+        if self.rules.iter().filter(|rule| rule.genre_id().is_none()).count() != 1 {
+            errors.push(ValidationError::new("rules", "A schedule should have exactly one default fee, without a genre."));
+        }
+
+        let genres: HashSet<&str> = self.rules.iter().filter_map(|rule| rule.genre_id()).collect();
+        if genres.len() != self.rules.iter().filter(|rule| rule.genre_id().is_some()).count() {
+            errors.push(ValidationError::new("rules", "A genre should have one fee in a schedule."));
+        }
+
+        if self.rules.iter().any(|rule| rule.fee_bps < 0 || rule.fee_bps > FULL_BPS) {
+            errors.push(ValidationError::new("rules", "A fee should be within 0 and 10000 basis points."));
+        }
+
+        errors
+    }
+
+    pub fn to_rows(&self, admin_id: &UserId) -> (NewFeeSchedule, Vec<NewFeeRule>) {
+        let schedule = NewFeeSchedule {
+            id: util::fuzzy_id(),
+            effective_from: util::as_date(self.effective_from.as_str()),
+            note: self.note.trim().to_owned(),
+            created_by_id: admin_id.to_string(),
+        };
+
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| NewFeeRule {
+                id: util::fuzzy_id(),
+                schedule_id: schedule.id.to_owned(),
+                genre_id: rule.genre_id().map(|value| value.to_owned()),
+                fee_bps: rule.fee_bps,
+            })
+            .collect();
+
+        (schedule, rules)
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ChargeInput {
+    pub amount_cents: i32,
+    pub charged_at: String,
+}
+
+/**
+ * The charges a coach earned from in a program, as recorded by the billing.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct EarningsCriteria {
+    pub coach_id: String,
+    pub program_id: ProgramId,
+    pub charges: Vec<ChargeInput>,
+}
+
+impl EarningsCriteria {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.charges.is_empty() || self.charges.len() > MAX_CHARGES {
+            return Err("Please give 1 to 500 charges.");
+        }
+
+        if self.charges.iter().any(|charge| charge.amount_cents < 0 || !util::is_valid_date(charge.charged_at.as_str())) {
+            return Err("Each charge needs a non negative amount and a date like 2021-02-22T10:00:00Z.");
+        }
+
+        Ok(())
+    }
+}
+
+pub struct EarningLine {
+    pub charged_at: NaiveDateTime,
+    pub amount_cents: i32,
+    pub schedule_id: Option<String>,
+    pub fee_bps: i32,
+    pub fee_cents: i32,
+    pub share_cents: i32,
+}
+
+#[juniper::object(description = "A charge with the fee of the schedule of its time")]
+impl EarningLine {
+    pub fn charged_at(&self) -> NaiveDateTime {
+        self.charged_at
+    }
+
+    pub fn amount_cents(&self) -> i32 {
+        self.amount_cents
+    }
+
+    #[graphql(description = "The schedule applied; none for a charge before the first schedule, which bears no fee")]
+    pub fn schedule_id(&self) -> Option<&str> {
+        self.schedule_id.as_deref()
+    }
+
+    pub fn fee_bps(&self) -> i32 {
+        self.fee_bps
+    }
+
+    pub fn fee_cents(&self) -> i32 {
+        self.fee_cents
+    }
+
+    pub fn share_cents(&self) -> i32 {
+        self.share_cents
+    }
+}
+
+pub struct EarningsStatement {
+    pub program_id: String,
+    pub lines: Vec<EarningLine>,
+}
+
+#[juniper::object(description = "The earnings of a coach from the charges of a program")]
+impl EarningsStatement {
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn lines(&self) -> &Vec<EarningLine> {
+        &self.lines
+    }
+
+    pub fn fee_cents(&self) -> i32 {
+        self.lines.iter().map(|line| line.fee_cents as i64).sum::<i64>().min(i32::MAX as i64) as i32
+    }
+
+    pub fn share_cents(&self) -> i32 {
+        self.lines.iter().map(|line| line.share_cents as i64).sum::<i64>().min(i32::MAX as i64) as i32
+    }
+}
+
+/**
+ * The schedule in effect at the time, out of the schedules ordered by effective_from.
+ */
+pub fn schedule_at(schedules: &[FeeScheduleView], at: NaiveDateTime) -> Option<&FeeScheduleView> {
+    schedules.iter().rev().find(|view| view.schedule.effective_from <= at)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn view(id: &str, from: &str, rules: Vec<(Option<&str>, i32)>) -> FeeScheduleView {
+        FeeScheduleView {
+            schedule: FeeSchedule {
+                id: id.to_owned(),
+                effective_from: at(from),
+                note: String::from(""),
+                created_by_id: String::from("a-1"),
+                created_at: at(from),
+            },
+            rules: rules
+                .into_iter()
+                .map(|(genre, fee_bps)| FeeRule {
+                    schedule_id: id.to_owned(),
+                    genre_id: genre.map(String::from),
+                    fee_bps,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn should_round_half_a_cent_up() {
+        assert_eq!((150, 850), split(1000, 1500));
+        assert_eq!((1, 6), split(7, 1500));
+        assert_eq!((0, 3), split(3, 1500));
+        assert_eq!((0, 999), split(999, 0));
+    }
+
+    #[test]
+    fn should_apply_the_schedule_of_the_time() {
+        let schedules = vec![
+            view("s-1", "2021-01-01T00:00", vec![(None, 2000), (Some("leadership"), 1000)]),
+            view("s-2", "2021-03-01T00:00", vec![(None, 1500)]),
+        ];
+
+        assert!(schedule_at(&schedules, at("2020-12-31T23:59")).is_none());
+
+        let january = schedule_at(&schedules, at("2021-01-15T10:00")).unwrap();
+        assert_eq!(1000, january.fee_for(Some("leadership")));
+        assert_eq!(2000, january.fee_for(Some("sales")));
+        assert_eq!(2000, january.fee_for(None));
+
+        let march = schedule_at(&schedules, at("2021-03-01T00:00")).unwrap();
+        assert_eq!(1500, march.fee_for(Some("leadership")));
+    }
+}
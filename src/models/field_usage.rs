@@ -0,0 +1,117 @@
+/**
+ * The selections of the schema fields by the clients, counted by the day and the
+ * operation; see field_usage for how the counts are gathered.
+ */
+use chrono::NaiveDate;
+
+use crate::field_usage::UsageKey;
+use crate::schema::field_usage;
+
+// The report looks back at most a year.
+const MAX_DAYS: i32 = 366;
+
+#[derive(Insertable)]
+#[table_name = "field_usage"]
+pub struct NewFieldUsage {
+    pub day: NaiveDate,
+    pub operation_name: String,
+    pub type_name: String,
+    pub field_name: String,
+    pub hits: i64,
+}
+
+impl NewFieldUsage {
+    pub fn from(key: &UsageKey, hits: i64) -> NewFieldUsage {
+        NewFieldUsage {
+            day: key.day,
+            operation_name: key.operation_name.to_owned(),
+            type_name: key.type_name.to_owned(),
+            field_name: key.field_name.to_owned(),
+            hits,
+        }
+    }
+}
+
+// The report is read by the signed in admin alone.
+#[derive(juniper::GraphQLInputObject)]
+pub struct FieldUsageCriteria {
+    pub days: i32,
+    pub deprecated_only: bool,
+}
+
+impl FieldUsageCriteria {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.days < 1 || self.days > MAX_DAYS {
+            return Err("The report looks back 1 to 366 days.");
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * A field of the schema with its use over the days of the report.
+ */
+pub struct FieldReport {
+    pub type_name: String,
+    pub field_name: String,
+    pub deprecation: Option<String>,
+    pub hits: i64,
+    pub operations: Vec<String>,
+    pub last_used: Option<NaiveDate>,
+}
+
+#[juniper::object(description = "The use of a field of the schema by the clients")]
+impl FieldReport {
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_str()
+    }
+
+    pub fn field_name(&self) -> &str {
+        self.field_name.as_str()
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation.is_some()
+    }
+
+    pub fn deprecation_reason(&self) -> Option<&str> {
+        self.deprecation.as_deref()
+    }
+
+    pub fn hits(&self) -> i32 {
+        self.hits.min(i32::MAX as i64) as i32
+    }
+
+    #[graphql(description = "The operations that selected the field")]
+    pub fn operations(&self) -> &Vec<String> {
+        &self.operations
+    }
+
+    pub fn last_used(&self) -> Option<NaiveDate> {
+        self.last_used
+    }
+
+    #[graphql(description = "Deprecated and not selected over the days of the report")]
+    pub fn is_removable(&self) -> bool {
+        self.deprecation.is_some() && self.hits == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_bound_the_days() {
+        let criteria = |days| FieldUsageCriteria {
+            days,
+            deprecated_only: false,
+        };
+
+        assert!(criteria(30).validate().is_ok());
+        assert!(criteria(0).validate().is_err());
+        assert!(criteria(400).validate().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+/**
+ * Every download of a board, a program content or a user content, for the
+ * organizations that must account for who read their coaching records.
+ *
+ * The downloads carry no credentials; the accessor is the user the client names
+ * in the user_id of the query string, if any, and is kept as given.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::util;
+use crate::schema::file_access_log;
+
+// The log is for an investigation; a wider one is narrowed by the file or the user.
+pub const LOG_LIMIT: i64 = 500;
+
+const MAX_FILTER: usize = 1024;
+
+// The ranges of a video being watched come within a minute of one another.
+pub const REPEAT_SECS: i64 = 60;
+
+#[derive(Queryable, Debug)]
+pub struct FileAccess {
+    pub id: String,
+    pub asset_class: String,
+    pub file_path: String,
+    pub accessor_id: Option<String>,
+    pub ip: Option<String>,
+    pub accessed_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A download of a file of the platform")]
+impl FileAccess {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    #[graphql(description = "One of boards, programs or users")]
+    pub fn asset_class(&self) -> &str {
+        self.asset_class.as_str()
+    }
+
+    pub fn file_path(&self) -> &str {
+        self.file_path.as_str()
+    }
+
+    pub fn accessor_id(&self) -> Option<&str> {
+        self.accessor_id.as_deref()
+    }
+
+    pub fn ip(&self) -> Option<&str> {
+        self.ip.as_deref()
+    }
+
+    pub fn accessed_at(&self) -> NaiveDateTime {
+        self.accessed_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "file_access_log"]
+pub struct NewFileAccess {
+    pub id: String,
+    pub asset_class: String,
+    pub file_path: String,
+    pub accessor_id: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl NewFileAccess {
+    pub fn new(asset_class: &str, file_path: &str, accessor_id: Option<&str>, ip: Option<&str>) -> NewFileAccess {
+        NewFileAccess {
+            id: util::fuzzy_id(),
+            asset_class: asset_class.to_owned(),
+            file_path: file_path.to_owned(),
+            accessor_id: accessor_id.map(|value| value.trim()).filter(|value| !value.is_empty()).map(|value| value.to_owned()),
+            ip: ip.map(|value| value.to_owned()),
+        }
+    }
+}
+
+/**
+ * The file_path matches the files under it too, so that a whole session or
+ * program may be looked at; at least the file or the user is a must. The log
+ * is read by the signed in admin alone.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct FileAccessCriteria {
+    pub file_path: Option<String>,
+    pub user_id: Option<String>,
+}
+
+impl FileAccessCriteria {
+    pub fn file_path(&self) -> Option<&str> {
+        self.file_path.as_deref().map(|value| value.trim()).filter(|value| !value.is_empty())
+    }
+
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref().map(|value| value.trim()).filter(|value| !value.is_empty())
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.file_path().is_none() && self.user_id().is_none() {
+            return Err("Please narrow the log by a file or a user.");
+        }
+
+        if self.file_path().map(|value| value.chars().count() > MAX_FILTER).unwrap_or(false) {
+            return Err("The file path should have at most 1024 characters.");
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * The LIKE pattern of the files under the path; the wildcards of the path are taken literally.
+ */
+pub fn path_pattern(path: &str) -> String {
+    let escaped = path.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+
+    format!("{}%", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_take_the_wildcards_of_a_path_literally() {
+        assert_eq!("s-1/boards/%", path_pattern("s-1/boards/"));
+        assert_eq!("s-1/board\\_1.png%", path_pattern("s-1/board_1.png"));
+        assert_eq!("100\\%/%", path_pattern("100%/"));
+    }
+
+    #[test]
+    fn should_need_a_file_or_a_user() {
+        let criteria = |file_path: Option<&str>, user_id: Option<&str>| FileAccessCriteria {
+            file_path: file_path.map(String::from),
+            user_id: user_id.map(String::from),
+        };
+
+        assert!(criteria(None, None).validate().is_err());
+        assert!(criteria(Some("  "), Some("")).validate().is_err());
+        assert!(criteria(Some("s-1/boards"), None).validate().is_ok());
+        assert!(criteria(None, Some("u-1")).validate().is_ok());
+    }
+}
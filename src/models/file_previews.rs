@@ -0,0 +1,194 @@
+/**
+ * What the UI needs to preview a file before loading it: the pages of a PDF and the
+ * dimensions of an image. Both are read from the bytes of the file when it is
+ * attached, so the previews do not depend on its name or on the type the browser
+ * claimed; a file we cannot read simply goes without a preview.
+ *
+ * The PDF pages are counted from the page objects in the file. The PDFs that pack
+ * their objects into compressed streams hide them, and go without a page count.
+ */
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PDF_SIGNATURE: &[u8] = b"%PDF-";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Preview {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub page_count: Option<i32>,
+}
+
+pub fn is_previewable(mime_type: &str) -> bool {
+    mime_type == "application/pdf" || mime_type.starts_with("image/")
+}
+
+pub fn preview_of(mime_type: &str, bytes: &[u8]) -> Option<Preview> {
+    if mime_type == "application/pdf" {
+        return pdf_pages(bytes).map(|pages| Preview {
+            page_count: Some(pages),
+            ..Preview::default()
+        });
+    }
+
+    if mime_type.starts_with("image/") {
+        return image_size(bytes).map(|(width, height)| Preview {
+            width: Some(width),
+            height: Some(height),
+            ..Preview::default()
+        });
+    }
+
+    None
+}
+
+/**
+ * The width and height of a PNG, JPEG, GIF or WebP image.
+ */
+pub fn image_size(bytes: &[u8]) -> Option<(i32, i32)> {
+    if bytes.starts_with(PNG_SIGNATURE) {
+        return Some((be32(bytes, 16)? as i32, be32(bytes, 20)? as i32));
+    }
+
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some((le16(bytes, 6)? as i32, le16(bytes, 8)? as i32));
+    }
+
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        return jpeg_size(bytes);
+    }
+
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return webp_size(bytes);
+    }
+
+    None
+}
+
+// The dimensions are in the first start of frame segment, past the ones before it.
+fn jpeg_size(bytes: &[u8]) -> Option<(i32, i32)> {
+    let mut at = 2;
+
+    loop {
+        while *bytes.get(at)? != 0xFF {
+            at += 1;
+        }
+        while *bytes.get(at)? == 0xFF {
+            at += 1;
+        }
+
+        let marker = *bytes.get(at)?;
+        at += 1;
+
+        match marker {
+            0xD0..=0xD7 | 0x01 => continue,
+            0xC0..=0xCF if marker != 0xC4 && marker != 0xC8 && marker != 0xCC => {
+                return Some((be16(bytes, at + 5)? as i32, be16(bytes, at + 3)? as i32));
+            }
+            _ => at += be16(bytes, at)? as usize,
+        }
+    }
+}
+
+fn webp_size(bytes: &[u8]) -> Option<(i32, i32)> {
+    match bytes.get(12..16)? {
+        b"VP8X" => Some((le24(bytes, 24)? as i32 + 1, le24(bytes, 27)? as i32 + 1)),
+        b"VP8 " => Some(((le16(bytes, 26)? & 0x3FFF) as i32, (le16(bytes, 28)? & 0x3FFF) as i32)),
+        b"VP8L" => {
+            let bits = le16(bytes, 21)? as u32 | (le16(bytes, 23)? as u32) << 16;
+            Some(((bits & 0x3FFF) as i32 + 1, (bits >> 14 & 0x3FFF) as i32 + 1))
+        }
+        _ => None,
+    }
+}
+
+/**
+ * The number of "/Type /Page" objects, leaving out the "/Type /Pages" trees.
+ */
+pub fn pdf_pages(bytes: &[u8]) -> Option<i32> {
+    if !bytes.starts_with(PDF_SIGNATURE) {
+        return None;
+    }
+
+    let mut pages = 0;
+    let mut at = 0;
+
+    while let Some(found) = find(&bytes[at..], b"/Type") {
+        at += found + b"/Type".len();
+
+        let mut next = at;
+        while bytes.get(next).is_some_and(|byte| byte.is_ascii_whitespace()) {
+            next += 1;
+        }
+
+        if bytes[next..].starts_with(b"/Page") && !bytes.get(next + b"/Page".len()).is_some_and(|byte| byte.is_ascii_alphanumeric()) {
+            pages += 1;
+        }
+    }
+
+    if pages == 0 {
+        return None;
+    }
+
+    Some(pages)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn be16(bytes: &[u8], at: usize) -> Option<u16> {
+    let pair = bytes.get(at..at + 2)?;
+    Some(u16::from(pair[0]) << 8 | u16::from(pair[1]))
+}
+
+fn be32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from(be16(bytes, at)?) << 16 | u32::from(be16(bytes, at + 2)?))
+}
+
+fn le16(bytes: &[u8], at: usize) -> Option<u16> {
+    let pair = bytes.get(at..at + 2)?;
+    Some(u16::from(pair[1]) << 8 | u16::from(pair[0]))
+}
+
+fn le24(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from(le16(bytes, at)?) | u32::from(*bytes.get(at + 2)?) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_read_the_size_of_the_images() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&[0, 0, 0, 13]);
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&[0, 0, 2, 128, 0, 0, 1, 224]);
+        assert_eq!(Some((640, 480)), image_size(&png));
+
+        let gif = b"GIF89a\x20\x03\x58\x02";
+        assert_eq!(Some((800, 600)), image_size(gif));
+
+        // An APP0 segment before the start of the frame.
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0, 4, 0, 0, 0xFF, 0xC0, 0, 17, 8, 0, 100, 0, 200];
+        assert_eq!(Some((200, 100)), image_size(&jpeg));
+
+        assert_eq!(None, image_size(b"not an image"));
+    }
+
+    #[test]
+    fn should_count_the_pages_but_not_the_page_trees() {
+        let pdf = b"%PDF-1.4\n1 0 obj << /Type /Pages /Count 2 >>\n2 0 obj << /Type /Page >>\n3 0 obj <</Type/Page/Parent 1 0 R>>";
+        assert_eq!(Some(2), pdf_pages(pdf));
+
+        assert_eq!(None, pdf_pages(b"%PDF-1.5\n<< /Type /ObjStm >>"));
+        assert_eq!(
+            Some(Preview {
+                page_count: Some(2),
+                ..Preview::default()
+            }),
+            preview_of("application/pdf", pdf)
+        );
+        assert_eq!(None, preview_of("text/plain", pdf));
+    }
+}
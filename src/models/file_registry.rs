@@ -0,0 +1,27 @@
+/**
+ * The uploaded files by their path, with the SHA-256 of their content. The
+ * content of the same checksum is kept once, as a blob; the path is a link to
+ * it, see content_store. The registry tells which blobs are still in use.
+ */
+use crate::commons::util;
+use crate::schema::file_registry;
+
+#[derive(Insertable)]
+#[table_name = "file_registry"]
+pub struct NewRegisteredFile {
+    pub id: String,
+    pub file_path: String,
+    pub checksum: String,
+    pub size: i64,
+}
+
+impl NewRegisteredFile {
+    pub fn of(file_path: &str, checksum: &str, size: usize) -> NewRegisteredFile {
+        NewRegisteredFile {
+            id: util::fuzzy_id(),
+            file_path: file_path.to_owned(),
+            checksum: checksum.to_owned(),
+            size: size as i64,
+        }
+    }
+}
@@ -0,0 +1,441 @@
+/**
+ * The goal board of an enrollment, shared by the coach and the member for the
+ * visioning exercises. The board is a set of columns holding goal cards; unlike
+ * the tasks, a card has no schedule, only a status and the comments of both.
+ *
+ * The columns are named by the cards, and the default ones are always shown, even
+ * when empty. Every change of a card is pushed to the coach and the member over
+ * their feed sockets, see feed_events; the revision of the board moves with every
+ * change too, for the clients without a socket to learn whether to fetch it again.
+ */
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::schema::goal_cards;
+use crate::schema::goal_comments;
+
+pub const DEFAULT_COLUMNS: [&str; 3] = ["Someday", "This Year", "This Quarter"];
+
+const MAX_COLUMN: usize = 60;
+const MAX_TITLE: usize = 200;
+const MAX_DESCRIPTION: usize = 2000;
+const MAX_COMMENT: usize = 1000;
+
+pub const CARD_ADDED: &str = "ADDED";
+pub const CARD_UPDATED: &str = "UPDATED";
+pub const CARD_DELETED: &str = "DELETED";
+pub const CARD_COMMENTED: &str = "COMMENTED";
+
+pub const OPEN: &str = "open";
+pub const ACHIEVED: &str = "achieved";
+pub const DROPPED: &str = "dropped";
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum GoalStatus {
+    OPEN,
+    ACHIEVED,
+    DROPPED,
+}
+
+impl GoalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalStatus::OPEN => OPEN,
+            GoalStatus::ACHIEVED => ACHIEVED,
+            GoalStatus::DROPPED => DROPPED,
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct GoalCard {
+    pub id: String,
+    pub enrollment_id: String,
+    pub column_name: String,
+    pub position: i32,
+    pub title: String,
+    pub description: String,
+    pub status: String,
+    pub created_by_id: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct GoalComment {
+    pub id: String,
+    pub card_id: String,
+    pub author_id: String,
+    pub comment: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A comment of the coach or the member on a goal card")]
+impl GoalComment {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn card_id(&self) -> &str {
+        self.card_id.as_str()
+    }
+
+    pub fn author_id(&self) -> &str {
+        self.author_id.as_str()
+    }
+
+    pub fn comment(&self) -> &str {
+        self.comment.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+/**
+ * A card with its comments, the oldest first.
+ */
+pub struct GoalCardView {
+    pub card: GoalCard,
+    pub comments: Vec<GoalComment>,
+}
+
+#[juniper::object(description = "A goal on the board of an enrollment")]
+impl GoalCardView {
+    pub fn id(&self) -> &str {
+        self.card.id.as_str()
+    }
+
+    pub fn column_name(&self) -> &str {
+        self.card.column_name.as_str()
+    }
+
+    pub fn position(&self) -> i32 {
+        self.card.position
+    }
+
+    pub fn title(&self) -> &str {
+        self.card.title.as_str()
+    }
+
+    pub fn description(&self) -> &str {
+        self.card.description.as_str()
+    }
+
+    #[graphql(description = "One of open, achieved or dropped")]
+    pub fn status(&self) -> &str {
+        self.card.status.as_str()
+    }
+
+    pub fn created_by_id(&self) -> &str {
+        self.card.created_by_id.as_str()
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.card.updated_at
+    }
+
+    pub fn comments(&self) -> &Vec<GoalComment> {
+        &self.comments
+    }
+}
+
+pub struct GoalColumn {
+    pub name: String,
+    pub cards: Vec<GoalCardView>,
+}
+
+#[juniper::object(description = "A column of the goal board")]
+impl GoalColumn {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn cards(&self) -> &Vec<GoalCardView> {
+        &self.cards
+    }
+}
+
+pub struct GoalBoard {
+    pub enrollment_id: String,
+    pub columns: Vec<GoalColumn>,
+    pub revision: String,
+}
+
+impl GoalBoard {
+    /**
+     * Lays the cards out in the default columns followed by the others by name;
+     * the cards of a column are in the order of their position.
+     */
+    pub fn arrange(enrollment_id: &str, mut cards: Vec<GoalCardView>) -> GoalBoard {
+        // The latest change and the count of the cards and the comments, as a removal moves no time.
+        let latest = cards
+            .iter()
+            .flat_map(|view| std::iter::once(view.card.updated_at).chain(view.comments.iter().map(|comment| comment.created_at)))
+            .max();
+        let count: usize = cards.iter().map(|view| 1 + view.comments.len()).sum();
+        let revision = format!("{}-{}", latest.map(|time| time.timestamp()).unwrap_or(0), count);
+
+        cards.sort_by(|a, b| a.card.position.cmp(&b.card.position).then(a.card.created_at.cmp(&b.card.created_at)));
+
+        let mut names: Vec<String> = DEFAULT_COLUMNS.iter().map(|name| (*name).to_owned()).collect();
+        let mut others: Vec<String> = cards.iter().map(|view| view.card.column_name.to_owned()).filter(|name| !names.contains(name)).collect();
+        others.sort();
+        others.dedup();
+        names.extend(others);
+
+        let mut columns: Vec<GoalColumn> = names.into_iter().map(|name| GoalColumn { name, cards: Vec::new() }).collect();
+        for view in cards {
+            if let Some(column) = columns.iter_mut().find(|column| column.name == view.card.column_name) {
+                column.cards.push(view);
+            }
+        }
+
+        GoalBoard {
+            enrollment_id: enrollment_id.to_owned(),
+            columns,
+            revision,
+        }
+    }
+}
+
+#[juniper::object(description = "The goal board shared by the coach and the member of an enrollment")]
+impl GoalBoard {
+    pub fn enrollment_id(&self) -> &str {
+        self.enrollment_id.as_str()
+    }
+
+    pub fn columns(&self) -> &Vec<GoalColumn> {
+        &self.columns
+    }
+
+    #[graphql(description = "Moves with every change; fetch the board again when it does")]
+    pub fn revision(&self) -> &str {
+        self.revision.as_str()
+    }
+}
+
+/**
+ * A change of a card of the board, pushed over the feed socket; the clients fetch the card or the board again.
+ */
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalBoardEvent {
+    pub enrollment_id: String,
+    pub card_id: String,
+    pub change: &'static str,
+}
+
+impl GoalBoardEvent {
+    pub fn of(card: &GoalCard, change: &'static str) -> GoalBoardEvent {
+        GoalBoardEvent {
+            enrollment_id: card.enrollment_id.to_owned(),
+            card_id: card.id.to_owned(),
+            change,
+        }
+    }
+}
+
+fn check_text(errors: &mut Vec<ValidationError>, field: &str, value: &str, limit: usize, message: &str) {
+    let length = value.trim().chars().count();
+
+    if length == 0 || length > limit {
+        errors.push(ValidationError::new(field, message));
+    }
+}
+
+fn check_card(errors: &mut Vec<ValidationError>, column_name: &str, title: &str, description: &str) {
+    check_text(errors, "column_name", column_name, MAX_COLUMN, "The column should have 1 to 60 characters.");
+    check_text(errors, "title", title, MAX_TITLE, "The title should have 1 to 200 characters.");
+
+    if description.trim().chars().count() > MAX_DESCRIPTION {
+        errors.push(ValidationError::new("description", "The description should have at most 2000 characters."));
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewGoalCardRequest {
+    pub actor_id: UserId,
+    pub enrollment_id: String,
+    pub column_name: String,
+    pub position: i32,
+    pub title: String,
+    pub description: String,
+}
+
+impl NewGoalCardRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.actor_id.trim().is_empty() {
+            errors.push(ValidationError::new("actor_id", "Actor id is a must."));
+        }
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "Enrollment id is a must."));
+        }
+
+        check_card(&mut errors, self.column_name.as_str(), self.title.as_str(), self.description.as_str());
+
+        errors
+    }
+}
+
+/**
+ * Moving a card to another column is an update of its column and position.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateGoalCardRequest {
+    pub actor_id: UserId,
+    pub id: String,
+    pub column_name: String,
+    pub position: i32,
+    pub title: String,
+    pub description: String,
+    pub status: GoalStatus,
+}
+
+impl UpdateGoalCardRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.id.trim().is_empty() {
+            errors.push(ValidationError::new("id", "Id of the card is a must."));
+        }
+
+        check_card(&mut errors, self.column_name.as_str(), self.title.as_str(), self.description.as_str());
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct GoalCardCriteria {
+    pub actor_id: UserId,
+    pub id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct GoalCommentRequest {
+    pub actor_id: UserId,
+    pub card_id: String,
+    pub comment: String,
+}
+
+impl GoalCommentRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.card_id.trim().is_empty() {
+            errors.push(ValidationError::new("card_id", "Id of the card is a must."));
+        }
+
+        check_text(&mut errors, "comment", self.comment.as_str(), MAX_COMMENT, "The comment should have 1 to 1000 characters.");
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct GoalBoardCriteria {
+    pub user_id: UserId,
+    pub enrollment_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "goal_cards"]
+pub struct NewGoalCard {
+    pub id: String,
+    pub enrollment_id: String,
+    pub column_name: String,
+    pub position: i32,
+    pub title: String,
+    pub description: String,
+    pub created_by_id: String,
+}
+
+impl NewGoalCard {
+    pub fn from(request: &NewGoalCardRequest) -> NewGoalCard {
+        NewGoalCard {
+            id: util::fuzzy_id(),
+            enrollment_id: request.enrollment_id.to_owned(),
+            column_name: request.column_name.trim().to_owned(),
+            position: request.position,
+            title: request.title.trim().to_owned(),
+            description: request.description.trim().to_owned(),
+            created_by_id: request.actor_id.to_string(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "goal_comments"]
+pub struct NewGoalComment {
+    pub id: String,
+    pub card_id: String,
+    pub author_id: String,
+    pub comment: String,
+}
+
+impl NewGoalComment {
+    pub fn from(request: &GoalCommentRequest) -> NewGoalComment {
+        NewGoalComment {
+            id: util::fuzzy_id(),
+            card_id: request.card_id.to_owned(),
+            author_id: request.actor_id.to_string(),
+            comment: request.comment.trim().to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use chrono::Duration;
+
+    fn card(column_name: &str, position: i32, minutes: i64) -> GoalCardView {
+        let at = util::now() - Duration::minutes(minutes);
+
+        GoalCardView {
+            card: GoalCard {
+                id: util::fuzzy_id(),
+                enrollment_id: String::from("e-1"),
+                column_name: column_name.to_owned(),
+                position,
+                title: String::from("A goal"),
+                description: String::from(""),
+                status: String::from(OPEN),
+                created_by_id: String::from("u-1"),
+                created_at: at,
+                updated_at: at,
+            },
+            comments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn should_lay_out_the_cards_in_their_columns() {
+        let board = GoalBoard::arrange("e-1", vec![card("Values", 0, 5), card("This Year", 2, 10), card("This Year", 1, 20)]);
+
+        let names: Vec<&str> = board.columns.iter().map(|column| column.name.as_str()).collect();
+        assert_eq!(vec!["Someday", "This Year", "This Quarter", "Values"], names);
+
+        let positions: Vec<i32> = board.columns[1].cards.iter().map(|view| view.card.position).collect();
+        assert_eq!(vec![1, 2], positions);
+
+        assert_eq!(format!("{}-3", board.columns[3].cards[0].card.updated_at.timestamp()), board.revision);
+    }
+
+    #[test]
+    fn should_show_the_default_columns_of_an_empty_board() {
+        let board = GoalBoard::arrange("e-1", Vec::new());
+
+        assert_eq!(3, board.columns.len());
+        assert_eq!("0-0", board.revision);
+    }
+}
@@ -0,0 +1,172 @@
+/**
+ * A guest link lets an outsider, say a subject matter expert, join a single
+ * session and see its board, without registering with the platform.
+ *
+ * The token is the only credential of the guest; hence the link expires,
+ * and the coach may revoke it any time before that.
+ */
+use chrono::{Duration, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::commons::util;
+use crate::models::sessions::Session;
+use crate::schema::guest_links;
+
+// A guest link is meant for a session in the near future; a week is plenty.
+const MAX_VALID_HOURS: i32 = 24 * 7;
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct GuestLink {
+    pub id: String,
+    pub session_id: String,
+    pub created_by_id: String,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl GuestLink {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > util::now()
+    }
+}
+
+#[juniper::object(description = "A link through which a guest joins a single session")]
+impl GuestLink {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn session_id(&self) -> &str {
+        self.session_id.as_str()
+    }
+
+    pub fn guest_name(&self) -> &str {
+        self.guest_name.as_str()
+    }
+
+    pub fn guest_email(&self) -> Option<&str> {
+        self.guest_email.as_deref()
+    }
+
+    pub fn token(&self) -> &str {
+        self.token.as_str()
+    }
+
+    pub fn expires_at(&self) -> NaiveDateTime {
+        self.expires_at
+    }
+
+    pub fn revoked_at(&self) -> Option<NaiveDateTime> {
+        self.revoked_at
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn is_active(&self) -> bool {
+        GuestLink::is_active(self)
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewGuestLinkRequest {
+    pub coach_id: String,
+    pub session_id: SessionId,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub valid_hours: i32,
+}
+
+impl NewGuestLinkRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        if self.guest_name.trim().is_empty() {
+            errors.push(ValidationError::new("guest_name", "Name of the guest is a must."));
+        }
+
+        if self.valid_hours < 1 || self.valid_hours > MAX_VALID_HOURS {
+            errors.push(ValidationError::new("valid_hours", "The link should be valid for 1 hour to 7 days."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct GuestLinkCriteria {
+    pub coach_id: String,
+    pub session_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RevokeGuestLinkRequest {
+    pub coach_id: String,
+    pub id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "guest_links"]
+pub struct NewGuestLink {
+    pub id: String,
+    pub session_id: String,
+    pub created_by_id: String,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+impl NewGuestLink {
+    pub fn from(request: &NewGuestLinkRequest) -> NewGuestLink {
+        let fuzzy_id = util::fuzzy_id();
+
+        NewGuestLink {
+            id: fuzzy_id,
+            session_id: request.session_id.to_string(),
+            created_by_id: request.coach_id.to_owned(),
+            guest_name: request.guest_name.trim().to_owned(),
+            guest_email: request.guest_email.clone(),
+            token: util::secure_token(),
+            expires_at: util::now() + Duration::hours(request.valid_hours as i64),
+        }
+    }
+}
+
+/**
+ * What a guest gets on presenting the token: the session to join and its board.
+ */
+pub struct GuestAccess {
+    pub guest_name: String,
+    pub session: Session,
+    pub board_urls: Vec<String>,
+}
+
+#[juniper::object]
+impl GuestAccess {
+    pub fn guest_name(&self) -> &str {
+        self.guest_name.as_str()
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn board_urls(&self) -> &Vec<String> {
+        &self.board_urls
+    }
+}
@@ -0,0 +1,375 @@
+/**
+ * The monthly invoice of a coach: the enrollments paid for in a month, in one
+ * currency, with the fee of the platform as of the schedule in effect when each
+ * enrollment was made, and the share of the coach.
+ *
+ * The PDF is kept below the platform assets, in invoices/{coach_id}, out of the
+ * reach of the public route of the platform. It is downloaded through a signed
+ * link, which expires; the links are signed with INVOICE_LINK_SECRET, and there
+ * are none while it is not set.
+ */
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use sodiumoxide::crypto::auth::hmacsha256;
+use sodiumoxide::crypto::hash::sha256;
+
+use crate::commons::util;
+use crate::schema::invoices;
+
+const LINK_SECRET: &str = "INVOICE_LINK_SECRET";
+
+// The link of the query is fetched afresh from the UI; the one of the mail should outlive a holiday.
+pub const QUERY_LINK_HOURS: i64 = 24;
+pub const MAIL_LINK_HOURS: i64 = 30 * 24;
+
+const LINES_PER_PAGE: usize = 48;
+
+#[derive(Queryable, Debug)]
+pub struct Invoice {
+    pub id: String,
+    pub coach_id: String,
+    pub period_start: NaiveDateTime,
+    pub currency: String,
+    pub enrollment_count: i32,
+    pub gross_cents: i64,
+    pub fee_cents: i64,
+    pub net_cents: i64,
+    pub file_path: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The monthly invoice of a coach for the paid enrollments of a month, in one currency")]
+impl Invoice {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    #[graphql(description = "The first day of the month invoiced, in UTC")]
+    pub fn period_start(&self) -> NaiveDateTime {
+        self.period_start
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_str()
+    }
+
+    pub fn enrollment_count(&self) -> i32 {
+        self.enrollment_count
+    }
+
+    #[graphql(description = "The amount paid by the members, in cents")]
+    pub fn gross_cents(&self) -> i32 {
+        self.gross_cents as i32
+    }
+
+    #[graphql(description = "The fee of the platform, in cents")]
+    pub fn fee_cents(&self) -> i32 {
+        self.fee_cents as i32
+    }
+
+    #[graphql(description = "The share of the coach, in cents")]
+    pub fn net_cents(&self) -> i32 {
+        self.net_cents as i32
+    }
+
+    #[graphql(description = "The path to download the PDF from, valid for a day; none when the links are not set up")]
+    pub fn download_url(&self) -> Option<String> {
+        signed_link(self.id.as_str(), util::now() + Duration::hours(QUERY_LINK_HOURS))
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "invoices"]
+pub struct NewInvoice {
+    pub id: String,
+    pub coach_id: String,
+    pub period_start: NaiveDateTime,
+    pub currency: String,
+    pub enrollment_count: i32,
+    pub gross_cents: i64,
+    pub fee_cents: i64,
+    pub net_cents: i64,
+    pub file_path: String,
+}
+
+/**
+ * The month before the one of now, as its first moment and the first moment of
+ * the month after it.
+ */
+pub fn invoice_month(now: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+    let this_month = NaiveDate::from_ymd(now.year(), now.month(), 1);
+
+    let last_month = match now.month() {
+        1 => NaiveDate::from_ymd(now.year() - 1, 12, 1),
+        month => NaiveDate::from_ymd(now.year(), month - 1, 1),
+    };
+
+    (last_month.and_hms(0, 0, 0), this_month.and_hms(0, 0, 0))
+}
+
+/**
+ * An enrollment paid for, as the invoice lists it.
+ */
+pub struct InvoiceLine {
+    pub enrolled_at: NaiveDateTime,
+    pub program_name: String,
+    pub member_name: String,
+    pub amount_cents: i64,
+    pub fee_cents: i64,
+}
+
+pub struct InvoiceStatement {
+    pub coach_name: String,
+    pub period_start: NaiveDateTime,
+    pub currency: String,
+    pub lines: Vec<InvoiceLine>,
+}
+
+impl InvoiceStatement {
+    pub fn gross_cents(&self) -> i64 {
+        self.lines.iter().map(|line| line.amount_cents).sum()
+    }
+
+    pub fn fee_cents(&self) -> i64 {
+        self.lines.iter().map(|line| line.fee_cents).sum()
+    }
+
+    pub fn net_cents(&self) -> i64 {
+        self.gross_cents() - self.fee_cents()
+    }
+
+    pub fn period_name(&self) -> String {
+        self.period_start.format("%B %Y").to_string()
+    }
+
+    pub fn to_row(&self, coach_id: &str, file_path: &str) -> NewInvoice {
+        NewInvoice {
+            id: util::fuzzy_id(),
+            coach_id: coach_id.to_owned(),
+            period_start: self.period_start,
+            currency: self.currency.to_owned(),
+            enrollment_count: self.lines.len() as i32,
+            gross_cents: self.gross_cents(),
+            fee_cents: self.fee_cents(),
+            net_cents: self.net_cents(),
+            file_path: file_path.to_owned(),
+        }
+    }
+
+    fn text_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            String::from("Ferris - Invoice"),
+            String::new(),
+            format!("Coach: {}", self.coach_name),
+            format!("Period: {}", self.period_name()),
+            format!("Currency: {}", self.currency),
+            String::new(),
+            format!("{:<12}{:<30}{:<24}{:>12}{:>12}", "Date", "Program", "Member", "Amount", "Fee"),
+        ];
+
+        for line in &self.lines {
+            lines.push(format!(
+                "{:<12}{:<30}{:<24}{:>12}{:>12}",
+                line.enrolled_at.format("%Y-%m-%d"),
+                clip(line.program_name.as_str(), 28),
+                clip(line.member_name.as_str(), 22),
+                as_money(line.amount_cents),
+                as_money(line.fee_cents)
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Enrollments: {}", self.lines.len()));
+        lines.push(format!("Paid by the members: {} {}", as_money(self.gross_cents()), self.currency));
+        lines.push(format!("Fee of the platform: {} {}", as_money(self.fee_cents()), self.currency));
+        lines.push(format!("Due to the coach: {} {}", as_money(self.net_cents()), self.currency));
+
+        lines
+    }
+
+    /**
+     * The statement as a plain PDF of as many pages as it needs, set in Courier
+     * so that the columns line up.
+     */
+    pub fn to_pdf(&self) -> Vec<u8> {
+        render_pdf(&self.text_lines())
+    }
+}
+
+pub fn as_money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+fn clip(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// The text is kept to plain ASCII, which the standard fonts show alike everywhere; anything else is shown as a question mark.
+fn pdf_text(line: &str) -> String {
+    line.chars()
+        .map(|letter| match letter {
+            '\\' | '(' | ')' => format!("\\{}", letter),
+            ' '..='~' => letter.to_string(),
+            _ => String::from("?"),
+        })
+        .collect()
+}
+
+fn render_pdf(lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() { vec![lines] } else { lines.chunks(LINES_PER_PAGE).collect() };
+
+    // The catalog, the page tree and the font come first; then a page and its content, for each page.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|index| 4 + 2 * index).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+
+    let mut objects: Vec<String> = vec![
+        String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+        String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>"),
+    ];
+
+    for (page, page_id) in pages.iter().zip(page_ids.iter()) {
+        let mut stream = String::from("BT /F1 9 Tf 12 TL 40 800 Td\n");
+        for line in page.iter() {
+            stream.push_str(format!("({}) Tj T*\n", pdf_text(line)).as_str());
+        }
+        stream.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            page_id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", stream.len(), stream));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets: Vec<usize> = Vec::new();
+
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_str());
+    }
+
+    let xref_at = pdf.len();
+    pdf.push_str(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_str());
+    for offset in offsets {
+        pdf.push_str(format!("{:010} 00000 n \n", offset).as_str());
+    }
+    pdf.push_str(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_at).as_str());
+
+    pdf.into_bytes()
+}
+
+fn link_secret() -> Option<String> {
+    dotenv::var(LINK_SECRET).ok().filter(|secret| !secret.trim().is_empty())
+}
+
+fn signature(secret: &str, invoice_id: &str, expires: i64) -> String {
+    let key = hmacsha256::Key(sha256::hash(secret.as_bytes()).0);
+    let message = format!("{}:{}", invoice_id, expires);
+
+    hmacsha256::authenticate(message.as_bytes(), &key).0.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/**
+ * The path of the PDF of the invoice, good until the time.
+ */
+pub fn signed_link(invoice_id: &str, until: NaiveDateTime) -> Option<String> {
+    let secret = link_secret()?;
+    let expires = until.timestamp();
+
+    Some(format!("invoices/{}.pdf?expires={}&signature={}", invoice_id, expires, signature(secret.as_str(), invoice_id, expires)))
+}
+
+/**
+ * The link was signed by us for the invoice and has not expired yet.
+ */
+pub fn verify_link(invoice_id: &str, expires: i64, given: &str) -> bool {
+    match link_secret() {
+        Some(secret) => verify_with(secret.as_str(), invoice_id, expires, given, util::now()),
+        None => false,
+    }
+}
+
+fn verify_with(secret: &str, invoice_id: &str, expires: i64, given: &str, now: NaiveDateTime) -> bool {
+    let expected = signature(secret, invoice_id, expires);
+
+    expires > now.timestamp() && sodiumoxide::utils::memcmp(expected.as_bytes(), given.to_ascii_lowercase().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::models::file_previews::pdf_pages;
+
+    fn line(program_name: &str, amount_cents: i64, fee_cents: i64) -> InvoiceLine {
+        InvoiceLine {
+            enrolled_at: util::as_date("2021-02-10T09:00:00Z"),
+            program_name: program_name.to_owned(),
+            member_name: String::from("Ada (guest)"),
+            amount_cents,
+            fee_cents,
+        }
+    }
+
+    fn statement(count: usize) -> InvoiceStatement {
+        InvoiceStatement {
+            coach_name: String::from("Raja"),
+            period_start: util::as_date("2021-02-01T00:00:00Z"),
+            currency: String::from("EUR"),
+            lines: (0..count).map(|_| line("Rust in a week", 4900, 490)).collect(),
+        }
+    }
+
+    #[test]
+    fn should_invoice_the_month_before() {
+        assert_eq!(
+            (util::as_date("2021-02-01T00:00:00Z"), util::as_date("2021-03-01T00:00:00Z")),
+            invoice_month(util::as_date("2021-03-15T10:30:00Z"))
+        );
+        assert_eq!(
+            (util::as_date("2020-12-01T00:00:00Z"), util::as_date("2021-01-01T00:00:00Z")),
+            invoice_month(util::as_date("2021-01-01T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn should_total_the_lines() {
+        let statement = statement(3);
+        let row = statement.to_row("c-1", "invoices/c-1/2021-02-EUR.pdf");
+
+        assert_eq!(3, row.enrollment_count);
+        assert_eq!(14700, row.gross_cents);
+        assert_eq!(1470, row.fee_cents);
+        assert_eq!(13230, row.net_cents);
+        assert_eq!("132.30", as_money(row.net_cents));
+        assert_eq!("February 2021", statement.period_name());
+    }
+
+    #[test]
+    fn should_render_a_page_for_every_few_lines() {
+        assert_eq!(Some(1), pdf_pages(&statement(3).to_pdf()));
+        assert_eq!(Some(3), pdf_pages(&statement(100).to_pdf()));
+    }
+
+    #[test]
+    fn should_honour_only_the_own_unexpired_signature() {
+        let now = util::as_date("2021-03-01T00:00:00Z");
+        let expires = (now + Duration::hours(1)).timestamp();
+        let given = signature("s3cret", "i-1", expires);
+
+        assert!(verify_with("s3cret", "i-1", expires, given.as_str(), now));
+        assert!(!verify_with("s3cret", "i-2", expires, given.as_str(), now));
+        assert!(!verify_with("other", "i-1", expires, given.as_str(), now));
+        assert!(!verify_with("s3cret", "i-1", expires, given.as_str(), now + Duration::hours(2)));
+    }
+}
@@ -0,0 +1,404 @@
+/**
+ * What happens to a task whose end date passes without a response. A program may
+ * give such a task some grace days, once, and may extend it by the days the member
+ * was away on a documented absence within the window of the task.
+ *
+ * The extensions are made by the "late-policy" job, which revises the end date of
+ * the task, records the extension and tells both the coach and the member.
+ */
+use chrono::{Duration, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::util;
+use crate::schema::{late_policies, member_absences, task_extensions};
+
+pub const GRACE: &str = "GRACE";
+pub const ABSENCE: &str = "ABSENCE";
+
+const MAX_GRACE_DAYS: i32 = 30;
+const MAX_ABSENCE_DAYS: i64 = 90;
+const MAX_REASON: usize = 255;
+
+#[derive(Queryable, Debug)]
+pub struct LatePolicy {
+    pub program_id: String,
+    pub grace_days: i32,
+    pub extend_on_absence: bool,
+}
+
+impl LatePolicy {
+    /**
+     * The policy of a program that has none: the tasks stay late.
+     */
+    pub fn none(program_id: &str) -> LatePolicy {
+        LatePolicy {
+            program_id: program_id.to_owned(),
+            grace_days: 0,
+            extend_on_absence: false,
+        }
+    }
+}
+
+#[juniper::object(description = "How the late tasks of a program are extended")]
+impl LatePolicy {
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    #[graphql(description = "The days a late task is extended by, once")]
+    pub fn grace_days(&self) -> i32 {
+        self.grace_days
+    }
+
+    #[graphql(description = "True when a late task is extended by the days of a documented absence of the member")]
+    pub fn extend_on_absence(&self) -> bool {
+        self.extend_on_absence
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "late_policies"]
+pub struct NewLatePolicy {
+    pub program_id: String,
+    pub grace_days: i32,
+    pub extend_on_absence: bool,
+    pub updated_by_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct LatePolicyRequest {
+    pub coach_id: String,
+    pub program_id: ProgramId,
+    pub grace_days: i32,
+    pub extend_on_absence: bool,
+}
+
+impl LatePolicyRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "Program id is a must."));
+        }
+
+        if !(0..=MAX_GRACE_DAYS).contains(&self.grace_days) {
+            errors.push(ValidationError::new("grace_days", "The grace should be of 0 to 30 days."));
+        }
+
+        errors
+    }
+
+    pub fn to_row(&self) -> NewLatePolicy {
+        NewLatePolicy {
+            program_id: self.program_id.to_string(),
+            grace_days: self.grace_days,
+            extend_on_absence: self.extend_on_absence,
+            updated_by_id: self.coach_id.to_owned(),
+        }
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct MemberAbsence {
+    pub id: String,
+    pub member_id: String,
+    pub from_date: NaiveDateTime,
+    pub to_date: NaiveDateTime,
+    pub reason: String,
+    pub recorded_by_id: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A documented absence of a member, both the days included")]
+impl MemberAbsence {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn member_id(&self) -> &str {
+        self.member_id.as_str()
+    }
+
+    pub fn from_date(&self) -> NaiveDateTime {
+        self.from_date
+    }
+
+    pub fn to_date(&self) -> NaiveDateTime {
+        self.to_date
+    }
+
+    pub fn reason(&self) -> &str {
+        self.reason.as_str()
+    }
+
+    pub fn recorded_by_id(&self) -> &str {
+        self.recorded_by_id.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "member_absences"]
+pub struct NewMemberAbsence {
+    pub id: String,
+    pub member_id: String,
+    pub from_date: NaiveDateTime,
+    pub to_date: NaiveDateTime,
+    pub reason: String,
+    pub recorded_by_id: String,
+}
+
+/**
+ * The dates are whole days in UTC, like 2021-02-23.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct AbsenceRequest {
+    pub coach_id: String,
+    pub member_id: UserId,
+    pub from_date: String,
+    pub to_date: String,
+    pub reason: String,
+}
+
+impl AbsenceRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.member_id.trim().is_empty() {
+            errors.push(ValidationError::new("member_id", "Member id is a must."));
+        }
+
+        let reason = self.reason.trim();
+        if reason.is_empty() || reason.chars().count() > MAX_REASON {
+            errors.push(ValidationError::new("reason", "The reason should have 1 to 255 characters."));
+        }
+
+        match self.range() {
+            Err(e) => errors.push(ValidationError::new("from_date", e)),
+            Ok((from, to)) if to < from => errors.push(ValidationError::new("to_date", "The absence should end on or after its start.")),
+            Ok((from, to)) if to - from > Duration::days(MAX_ABSENCE_DAYS) => errors.push(ValidationError::new("to_date", "An absence should be of at most 90 days.")),
+            Ok(_) => {}
+        }
+
+        errors
+    }
+
+    pub fn range(&self) -> Result<(NaiveDateTime, NaiveDateTime), &'static str> {
+        let from = util::as_start_date(self.from_date.trim()).map_err(|_| "The dates should be like 2021-02-23.")?;
+        let to = util::as_end_date(self.to_date.trim()).map_err(|_| "The dates should be like 2021-02-23.")?;
+
+        Ok((from, to))
+    }
+
+    pub fn to_row(&self, from_date: NaiveDateTime, to_date: NaiveDateTime) -> NewMemberAbsence {
+        NewMemberAbsence {
+            id: util::fuzzy_id(),
+            member_id: self.member_id.to_string(),
+            from_date,
+            to_date,
+            reason: self.reason.trim().to_owned(),
+            recorded_by_id: self.coach_id.to_owned(),
+        }
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct TaskExtension {
+    pub id: String,
+    pub task_id: String,
+    pub absence_id: Option<String>,
+    pub reason: String,
+    pub previous_end_date: NaiveDateTime,
+    pub new_end_date: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "An extension of the end date of a late task")]
+impl TaskExtension {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn task_id(&self) -> &str {
+        self.task_id.as_str()
+    }
+
+    pub fn absence_id(&self) -> Option<&str> {
+        self.absence_id.as_deref()
+    }
+
+    #[graphql(description = "GRACE or ABSENCE")]
+    pub fn reason(&self) -> &str {
+        self.reason.as_str()
+    }
+
+    pub fn previous_end_date(&self) -> NaiveDateTime {
+        self.previous_end_date
+    }
+
+    pub fn new_end_date(&self) -> NaiveDateTime {
+        self.new_end_date
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "task_extensions"]
+pub struct NewTaskExtension {
+    pub id: String,
+    pub task_id: String,
+    pub absence_id: Option<String>,
+    pub reason: String,
+    pub previous_end_date: NaiveDateTime,
+    pub new_end_date: NaiveDateTime,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ExtensionCriteria {
+    pub user_id: String,
+    pub enrollment_id: String,
+}
+
+/**
+ * A late task as the job sees it: its window and the extensions it already had.
+ */
+pub struct LateTask<'a> {
+    pub task_id: &'a str,
+    pub start_date: NaiveDateTime,
+    pub end_date: NaiveDateTime,
+    pub applied: Vec<&'a TaskExtension>,
+}
+
+/**
+ * The next extension of a late task under the policy, if any. A documented absence
+ * overlapping the window of the task comes first and extends the task by the days
+ * of the overlap, a part of a day counting whole; the grace is given once, after
+ * the absences.
+ */
+pub fn next_extension(policy: &LatePolicy, task: &LateTask, absences: &[&MemberAbsence]) -> Option<NewTaskExtension> {
+    let extension = |absence_id: Option<String>, reason: &str, days: i64| NewTaskExtension {
+        id: util::fuzzy_id(),
+        task_id: task.task_id.to_owned(),
+        absence_id,
+        reason: reason.to_owned(),
+        previous_end_date: task.end_date,
+        new_end_date: task.end_date + Duration::days(days),
+    };
+
+    if policy.extend_on_absence {
+        let pending = absences
+            .iter()
+            .find(|absence| absence.from_date < task.end_date && absence.to_date > task.start_date && !task.applied.iter().any(|applied| applied.absence_id.as_deref() == Some(absence.id.as_str())));
+
+        if let Some(absence) = pending {
+            let overlap = absence.to_date.min(task.end_date) - absence.from_date.max(task.start_date);
+            let days = (overlap.num_seconds() + 86_399) / 86_400;
+
+            return Some(extension(Some(absence.id.to_owned()), ABSENCE, days.max(1)));
+        }
+    }
+
+    let graced = task.applied.iter().any(|applied| applied.reason == GRACE);
+    if policy.grace_days > 0 && !graced {
+        return Some(extension(None, GRACE, policy.grace_days as i64));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn absence(id: &str, from: &str, to: &str) -> MemberAbsence {
+        MemberAbsence {
+            id: id.to_owned(),
+            member_id: String::from("m-1"),
+            from_date: at(from),
+            to_date: at(to),
+            reason: String::from("Surgery"),
+            recorded_by_id: String::from("c-1"),
+            created_at: at(from),
+        }
+    }
+
+    fn applied(absence_id: Option<&str>, reason: &str) -> TaskExtension {
+        TaskExtension {
+            id: String::from("x-1"),
+            task_id: String::from("t-1"),
+            absence_id: absence_id.map(String::from),
+            reason: reason.to_owned(),
+            previous_end_date: at("2021-02-10T00:00"),
+            new_end_date: at("2021-02-13T00:00"),
+            created_at: at("2021-02-10T00:00"),
+        }
+    }
+
+    #[test]
+    fn should_extend_by_the_absence_then_by_the_grace() {
+        let policy = LatePolicy {
+            program_id: String::from("p-1"),
+            grace_days: 2,
+            extend_on_absence: true,
+        };
+        let away = absence("a-1", "2021-02-08T00:00", "2021-02-12T23:59");
+        let absences = vec![&away];
+
+        let mut task = LateTask {
+            task_id: "t-1",
+            start_date: at("2021-02-01T00:00"),
+            end_date: at("2021-02-10T00:00"),
+            applied: vec![],
+        };
+
+        let first = next_extension(&policy, &task, &absences).unwrap();
+        assert_eq!(ABSENCE, first.reason);
+        assert_eq!(at("2021-02-12T00:00"), first.new_end_date);
+
+        let by_absence = applied(Some("a-1"), ABSENCE);
+        task.applied.push(&by_absence);
+        task.end_date = first.new_end_date;
+
+        let second = next_extension(&policy, &task, &absences).unwrap();
+        assert_eq!(GRACE, second.reason);
+        assert_eq!(at("2021-02-14T00:00"), second.new_end_date);
+
+        let by_grace = applied(None, GRACE);
+        task.applied.push(&by_grace);
+
+        assert!(next_extension(&policy, &task, &absences).is_none());
+    }
+
+    #[test]
+    fn should_leave_the_task_late_without_a_policy() {
+        let away = absence("a-1", "2021-02-08T00:00", "2021-02-12T23:59");
+        let task = LateTask {
+            task_id: "t-1",
+            start_date: at("2021-02-01T00:00"),
+            end_date: at("2021-02-10T00:00"),
+            applied: vec![],
+        };
+
+        assert!(next_extension(&LatePolicy::none("p-1"), &task, &[&away]).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+/**
+ * The mail provider posts the delivery events back to us. We keep only
+ * the bounces and complaints, so that we can stop mailing the addresses
+ * that never receive our mails.
+ */
+use serde::Deserialize;
+
+use crate::commons::util;
+use crate::schema::mail_bounces;
+
+pub const HARD: &str = "hard";
+pub const SOFT: &str = "soft";
+pub const COMPLAINT: &str = "complaint";
+
+// The shape of an event as posted by the provider. The rest of the fields are ignored.
+#[derive(Deserialize, Debug)]
+pub struct MailEvent {
+    pub email: String,
+    pub event: String,
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl MailEvent {
+    pub fn bounce_type(&self) -> Option<&'static str> {
+        match (self.event.as_str(), self.event_type.as_deref()) {
+            ("bounce", Some("blocked")) => Some(SOFT),
+            ("bounce", _) => Some(HARD),
+            ("dropped", _) => Some(HARD),
+            ("spamreport", _) => Some(COMPLAINT),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "mail_bounces"]
+pub struct NewMailBounce {
+    pub id: String,
+    pub email: String,
+    pub bounce_type: String,
+    pub reason: Option<String>,
+}
+
+impl NewMailBounce {
+    pub fn from(event: &MailEvent, bounce_type: &str) -> NewMailBounce {
+        let fuzzy_id = util::fuzzy_id();
+
+        NewMailBounce {
+            id: fuzzy_id,
+            email: event.email.trim().to_lowercase(),
+            bounce_type: bounce_type.to_owned(),
+            reason: event.reason.clone(),
+        }
+    }
+}
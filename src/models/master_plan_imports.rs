@@ -0,0 +1,332 @@
+/**
+ * A master plan written in a spreadsheet: a CSV with a line for each task.
+ *
+ *     name,duration,offset_day,min,max,role
+ *     Read the chapter,4,0,2,6,member
+ *     "Review, with the coach",1,7,,,coach
+ *
+ * The duration and its min and max are in hours, as in the planner; the
+ * offset_day is the day of the plan the task starts on, the first day being 0.
+ * The min, the max and the role may be left out; the role is the member's then.
+ * The columns may come in any order, and offset-day is taken for offset_day.
+ *
+ * Every line is checked before anything is saved, and all the mistakes are told
+ * with their line and column. The tasks hang from a START of the plan, each by a
+ * forward link as long as its offset, so that a spawned plan starts them on
+ * their days.
+ */
+use serde::Serialize;
+
+use crate::commons::util;
+use crate::schema::{master_task_links, master_tasks};
+
+pub const MASTER_PLAN_TEMPLATE: &str = "name,duration,offset_day,min,max,role\r\n";
+
+const REQUIRED_COLUMNS: [&str; 3] = ["name", "duration", "offset_day"];
+
+const MAX_ROWS: usize = 500;
+const MAX_NAME: usize = 100;
+const DEFAULT_ROLE: &str = "member";
+
+pub const ACTIVITY: &str = "ACTIVITY";
+pub const START: &str = "START";
+pub const START_NAME: &str = "Start";
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ImportError {
+    pub line: usize,
+    pub column: String,
+    pub message: String,
+}
+
+impl ImportError {
+    pub fn new(line: usize, column: &str, message: &str) -> ImportError {
+        ImportError {
+            line,
+            column: column.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportRow {
+    pub line: usize,
+    pub name: String,
+    pub duration: i32,
+    pub offset_day: i32,
+    pub min: i32,
+    pub max: i32,
+    pub role: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportOutcome {
+    pub master_plan_id: String,
+    pub name: String,
+    pub tasks: usize,
+}
+
+/**
+ * The tasks of the CSV, or every mistake found in it.
+ */
+pub fn parse(content: &str) -> Result<Vec<ImportRow>, Vec<ImportError>> {
+    let mut records = records(content.trim_start_matches('\u{feff}')).into_iter();
+
+    let header: Vec<String> = match records.next() {
+        Some((_, fields)) => fields.iter().map(|field| field.trim().to_lowercase().replace('-', "_")).collect(),
+        None => return Err(vec![ImportError::new(1, "", "The file is empty; it should start with the line of the column names.")]),
+    };
+
+    let missing: Vec<ImportError> = REQUIRED_COLUMNS
+        .iter()
+        .filter(|column| !header.iter().any(|name| name == *column))
+        .map(|column| ImportError::new(1, column, "The column is missing."))
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+
+    let mut rows: Vec<ImportRow> = Vec::new();
+    let mut errors: Vec<ImportError> = Vec::new();
+
+    let tasks = records.filter(|(_, fields)| fields.iter().any(|field| !field.trim().is_empty()));
+
+    for (count, (line, fields)) in tasks.enumerate() {
+        if count >= MAX_ROWS {
+            errors.push(ImportError::new(line, "", "A plan may have at most 500 tasks."));
+            break;
+        }
+
+        let value = |column: &str| -> &str {
+            header.iter().position(|name| name == column).and_then(|at| fields.get(at)).map(|field| field.trim()).unwrap_or_default()
+        };
+
+        let errors_before = errors.len();
+
+        let name = value("name");
+        if name.is_empty() || name.chars().count() > MAX_NAME {
+            errors.push(ImportError::new(line, "name", "The name should have 1 to 100 characters."));
+        }
+
+        let mut number = |column: &str, least: i32, optional: bool| -> i32 {
+            let given = value(column);
+            if given.is_empty() && optional {
+                return 0;
+            }
+            match given.parse::<i32>() {
+                Ok(number) if number >= least => number,
+                _ => {
+                    errors.push(ImportError::new(line, column, format!("Should be a whole number of at least {}.", least).as_str()));
+                    0
+                }
+            }
+        };
+
+        let duration = number("duration", 1, false);
+        let offset_day = number("offset_day", 0, false);
+        let min = number("min", 0, true);
+        let max = number("max", 0, true);
+
+        if max > 0 && min > max {
+            errors.push(ImportError::new(line, "min", "The min should not be more than the max."));
+        }
+
+        let role = match value("role") {
+            "" => DEFAULT_ROLE,
+            given => given,
+        };
+
+        if errors.len() == errors_before {
+            rows.push(ImportRow {
+                line,
+                name: name.to_owned(),
+                duration,
+                offset_day,
+                min,
+                max,
+                role: role.to_lowercase(),
+            });
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    if rows.is_empty() {
+        return Err(vec![ImportError::new(2, "", "The file has no task.")]);
+    }
+
+    Ok(rows)
+}
+
+/**
+ * The fields of every record with the line it starts on. A quoted field may hold
+ * the separator, a doubled quote and line breaks, as RFC 4180 has it.
+ */
+fn records(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut starts_on = 1;
+
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                records.push((starts_on, std::mem::take(&mut fields)));
+                line += 1;
+                starts_on = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((starts_on, fields));
+    }
+
+    records
+}
+
+#[derive(Insertable)]
+#[table_name = "master_tasks"]
+pub struct ImportedMasterTask {
+    pub id: String,
+    pub master_plan_id: String,
+    pub abstract_task_id: String,
+    pub duration: i32,
+    pub min: i32,
+    pub max: i32,
+    pub task_type: String,
+    pub coach_id: String,
+    pub role_id: String,
+    pub coordinates: String,
+}
+
+impl ImportedMasterTask {
+    pub fn from(row: &ImportRow, plan_id: &str, abstract_task_id: &str, coach_id: &str) -> ImportedMasterTask {
+        ImportedMasterTask {
+            id: util::fuzzy_id(),
+            master_plan_id: plan_id.to_owned(),
+            abstract_task_id: abstract_task_id.to_owned(),
+            duration: row.duration,
+            min: row.min,
+            max: row.max,
+            task_type: String::from(ACTIVITY),
+            coach_id: coach_id.to_owned(),
+            role_id: row.role.to_owned(),
+            coordinates: String::from("{}"),
+        }
+    }
+
+    pub fn start(plan_id: &str, abstract_task_id: &str, coach_id: &str, role_id: &str) -> ImportedMasterTask {
+        ImportedMasterTask {
+            id: util::fuzzy_id(),
+            master_plan_id: plan_id.to_owned(),
+            abstract_task_id: abstract_task_id.to_owned(),
+            duration: 0,
+            min: 0,
+            max: 0,
+            task_type: String::from(START),
+            coach_id: coach_id.to_owned(),
+            role_id: role_id.to_owned(),
+            coordinates: String::from("{}"),
+        }
+    }
+}
+
+/**
+ * From the START of the plan to a task, the lead time being its offset in hours.
+ */
+#[derive(Insertable)]
+#[table_name = "master_task_links"]
+pub struct ImportedLink {
+    pub id: String,
+    pub master_plan_id: String,
+    pub source_task_id: String,
+    pub target_task_id: String,
+    pub lead_time: i32,
+    pub coordinates: String,
+    pub priority: i32,
+    pub is_forward: bool,
+}
+
+impl ImportedLink {
+    pub fn from(plan_id: &str, start_id: &str, task: &ImportedMasterTask, offset_day: i32, priority: i32) -> ImportedLink {
+        ImportedLink {
+            id: util::fuzzy_id(),
+            master_plan_id: plan_id.to_owned(),
+            source_task_id: start_id.to_owned(),
+            target_task_id: task.id.to_owned(),
+            lead_time: offset_day * 24,
+            coordinates: String::from("{}"),
+            priority,
+            is_forward: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_read_the_tasks_in_any_column_order() {
+        let content = "\u{feff}Offset-Day,name,duration,role\r\n0,Read the chapter,4,\r\n7,\"Review, with the \"\"coach\"\"\",1,Coach\r\n\r\n";
+
+        let rows = parse(content).unwrap();
+
+        assert_eq!(2, rows.len());
+        assert_eq!("member", rows[0].role);
+        assert_eq!(3, rows[1].line);
+        assert_eq!("Review, with the \"coach\"", rows[1].name);
+        assert_eq!(("coach", 7, 0), (rows[1].role.as_str(), rows[1].offset_day, rows[1].max));
+    }
+
+    #[test]
+    fn should_tell_every_mistake_with_its_position() {
+        let content = "name,duration,offset_day,min,max\n,4,0\nWrite,0,-1\nPlan,2,1,5,3\n";
+
+        let errors = parse(content).unwrap_err();
+
+        assert_eq!(ImportError::new(2, "name", "The name should have 1 to 100 characters."), errors[0]);
+        assert_eq!((3, "duration"), (errors[1].line, errors[1].column.as_str()));
+        assert_eq!((3, "offset_day"), (errors[2].line, errors[2].column.as_str()));
+        assert_eq!((4, "min"), (errors[3].line, errors[3].column.as_str()));
+    }
+
+    #[test]
+    fn should_need_the_columns() {
+        let errors = parse("name,duration\nRead,4\n").unwrap_err();
+
+        assert_eq!(vec![ImportError::new(1, "offset_day", "The column is missing.")], errors);
+    }
+
+    #[test]
+    fn should_count_the_lines_within_the_quotes() {
+        let content = "name,duration,offset_day\n\"Read\nthe chapter\",4,0\nWrite,x,0\n";
+
+        let errors = parse(content).unwrap_err();
+
+        assert_eq!(4, errors[0].line);
+    }
+}
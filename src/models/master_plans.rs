@@ -1,8 +1,18 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+
 use crate::commons::chassis::ValidationError;
 use crate::commons::util;
+use crate::models::master_tasks::MasterTask;
 use crate::schema::master_plans;
 use crate::schema::master_task_links;
 
+const PLAN_HAS_CYCLE: &str = "The plan loops back on itself through its forward links; please correct the plan.";
+
+// The boundaries of a plan; they take no time and are not copied as tasks.
+const MARKER_TYPES: [&str; 2] = ["START", "STOP"];
+
 #[derive(Queryable, Debug, Identifiable)]
 pub struct MasterPlan {
     pub id: String,
@@ -146,3 +156,174 @@ impl NewMasterTaskLink {
         }
     }
 }
+
+/**
+ * The tasks of a master plan copied into an enrollment, scheduled from the
+ * anchor date on.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct SpawnPlanRequest {
+    pub enrollment_id: String,
+    pub master_plan_id: String,
+    pub anchor_date: String,
+}
+
+impl SpawnPlanRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "Enrollment Id is a must."));
+        }
+
+        if self.master_plan_id.trim().is_empty() {
+            errors.push(ValidationError::new("master_plan_id", "Master Plan Id is a must."));
+        }
+
+        let given_time = self.anchor_date.as_str();
+
+        if !util::is_valid_date(given_time) {
+            errors.push(ValidationError::new("anchor_date", "unparsable date."));
+        } else if util::is_in_past(util::as_date(given_time)) {
+            errors.push(ValidationError::new("anchor_date", "should be a future date."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * A link of a plan; along a forward link, the target starts the lead time, in
+ * hours, after the source ends.
+ */
+#[derive(Queryable, Debug)]
+pub struct PlanLink {
+    pub source_task_id: String,
+    pub target_task_id: String,
+    pub lead_time: i32,
+    pub coordinates: String,
+    pub priority: i32,
+    pub is_forward: bool,
+}
+
+pub fn is_marker(master_task: &MasterTask) -> bool {
+    MARKER_TYPES.iter().any(|marker| master_task.task_type.eq_ignore_ascii_case(marker))
+}
+
+/**
+ * The start of every task of the plan. A task without a link into it starts at
+ * the anchor; any other starts when the last of its sources ends, plus the lead
+ * time. The backward links, the loops of a plan, are left out.
+ */
+pub fn schedule(master_tasks: &[MasterTask], links: &[PlanLink], anchor: NaiveDateTime) -> Result<HashMap<String, NaiveDateTime>, &'static str> {
+    let durations: HashMap<&str, i64> = master_tasks
+        .iter()
+        .map(|master_task| (master_task.id.as_str(), if is_marker(master_task) { 0 } else { master_task.duration as i64 }))
+        .collect();
+
+    let links: Vec<&PlanLink> = links
+        .iter()
+        .filter(|link| link.is_forward && durations.contains_key(link.source_task_id.as_str()) && durations.contains_key(link.target_task_id.as_str()))
+        .collect();
+
+    let mut pending: HashMap<&str, usize> = durations.keys().map(|task_id| (*task_id, 0)).collect();
+    for link in &links {
+        *pending.get_mut(link.target_task_id.as_str()).unwrap() += 1;
+    }
+
+    let mut starts: HashMap<String, NaiveDateTime> = HashMap::new();
+    let mut ready: Vec<&str> = pending.iter().filter(|(_, count)| **count == 0).map(|(task_id, _)| *task_id).collect();
+
+    for task_id in ready.iter() {
+        starts.insert(task_id.to_string(), anchor);
+    }
+
+    while let Some(task_id) = ready.pop() {
+        let ends_at = starts[task_id] + Duration::hours(durations[task_id]);
+
+        for link in links.iter().filter(|link| link.source_task_id == task_id) {
+            let target = link.target_task_id.as_str();
+            let starts_at = ends_at + Duration::hours(link.lead_time.max(0) as i64);
+
+            let start = starts.entry(target.to_owned()).or_insert(starts_at);
+            if *start < starts_at {
+                *start = starts_at;
+            }
+
+            let count = pending.get_mut(target).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(target);
+            }
+        }
+    }
+
+    if pending.values().any(|count| *count > 0) {
+        return Err(PLAN_HAS_CYCLE);
+    }
+
+    Ok(starts)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn master_task(id: &str, task_type: &str, duration: i32) -> MasterTask {
+        MasterTask {
+            id: id.to_owned(),
+            master_plan_id: String::from("plan-1"),
+            abstract_task_id: String::from("a-1"),
+            duration,
+            min: 0,
+            max: 0,
+            task_type: task_type.to_owned(),
+            created_at: at("2021-02-01T00:00"),
+            updated_at: at("2021-02-01T00:00"),
+            coach_id: String::from("c-1"),
+            role_id: String::from("member"),
+            coordinates: String::from("{}"),
+        }
+    }
+
+    fn link(source: &str, target: &str, lead_time: i32) -> PlanLink {
+        PlanLink {
+            source_task_id: source.to_owned(),
+            target_task_id: target.to_owned(),
+            lead_time,
+            coordinates: String::from("{}"),
+            priority: 0,
+            is_forward: true,
+        }
+    }
+
+    #[test]
+    fn should_start_a_task_after_the_last_of_its_sources() {
+        let master_tasks = vec![
+            master_task("start", "START", 5),
+            master_task("read", "ACTIVITY", 4),
+            master_task("write", "ACTIVITY", 10),
+            master_task("review", "ACTIVITY", 2),
+        ];
+        let links = vec![link("start", "read", 0), link("start", "write", 0), link("read", "review", 24), link("write", "review", 1)];
+
+        let starts = schedule(&master_tasks, &links, at("2021-03-01T09:00")).unwrap();
+
+        assert_eq!(at("2021-03-01T09:00"), starts["read"]);
+        assert_eq!(at("2021-03-01T09:00"), starts["write"]);
+        assert_eq!(at("2021-03-02T13:00"), starts["review"]);
+    }
+
+    #[test]
+    fn should_refuse_a_plan_looping_forward() {
+        let master_tasks = vec![master_task("a", "ACTIVITY", 1), master_task("b", "ACTIVITY", 1)];
+        let links = vec![link("a", "b", 0), link("b", "a", 0)];
+
+        assert_eq!(Err(PLAN_HAS_CYCLE), schedule(&master_tasks, &links, at("2021-03-01T09:00")));
+    }
+}
@@ -0,0 +1,273 @@
+/**
+ * The week ahead of a member at a glance: the sessions, with whether they may
+ * be joined, the tasks coming due, the check-ins on the objectives and the
+ * news of the programs. The dashboard used to gather these from the event, the
+ * due and the plan queries one by one.
+ *
+ * The week runs for seven days from the start of today. The sessions and the
+ * tasks go by their schedule, a revised date taking over the original one.
+ */
+use chrono::{Duration, NaiveDateTime};
+
+use crate::models::objectives::Objective;
+use crate::models::program_announcements::Announcement;
+use crate::models::programs::Program;
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+
+pub const WEEK_DAYS: i64 = 7;
+
+/**
+ * From the start of the day of the given time, for a week; the end is left out.
+ */
+pub fn week_of(now: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+    let starts_at = now.date().and_hms(0, 0, 0);
+
+    (starts_at, starts_at + Duration::days(WEEK_DAYS))
+}
+
+pub struct WeekSession {
+    pub session: Session,
+    pub program: Program,
+}
+
+impl WeekSession {
+    pub fn starts_at(&self) -> NaiveDateTime {
+        self.session.revised_start_date.unwrap_or(self.session.original_start_date)
+    }
+
+    /**
+     * A session may be joined once the coach made it ready, until it is over.
+     */
+    pub fn can_join(&self) -> bool {
+        if self.session.cancelled_at.is_some() || self.session.actual_end_date.is_some() {
+            return false;
+        }
+
+        self.session.is_ready || self.session.actual_start_date.is_some()
+    }
+}
+
+#[juniper::object(description = "A session of the week of a member")]
+impl WeekSession {
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    #[graphql(description = "Whether the member may join the session now")]
+    pub fn canJoin(&self) -> bool {
+        self.can_join()
+    }
+}
+
+pub struct WeekTask {
+    pub task: Task,
+    pub program: Program,
+}
+
+impl WeekTask {
+    pub fn due_at(&self) -> NaiveDateTime {
+        self.task.revised_end_date.unwrap_or(self.task.original_end_date)
+    }
+}
+
+#[juniper::object(description = "A task the member is yet to respond to, due by the end of the week")]
+impl WeekTask {
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn dueAt(&self) -> NaiveDateTime {
+        self.due_at()
+    }
+}
+
+/**
+ * An open objective of the member whose period begins in the week, when the
+ * member and the coach check in on it.
+ */
+pub struct CheckIn {
+    pub objective: Objective,
+    pub program: Program,
+}
+
+#[juniper::object(description = "An objective of the member to check in on during the week")]
+impl CheckIn {
+    pub fn objective(&self) -> &Objective {
+        &self.objective
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+}
+
+pub struct MemberWeek {
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub sessions: Vec<WeekSession>,
+    pub tasks: Vec<WeekTask>,
+    pub check_ins: Vec<CheckIn>,
+    pub announcements: Vec<Announcement>,
+}
+
+impl MemberWeek {
+    /**
+     * The rows were fetched by either of their dates; only those whose schedule
+     * falls in the week are kept, the earliest first.
+     */
+    pub fn of(starts_at: NaiveDateTime, ends_at: NaiveDateTime, sessions: Vec<WeekSession>, tasks: Vec<WeekTask>, check_ins: Vec<CheckIn>, announcements: Vec<Announcement>) -> MemberWeek {
+        let mut sessions: Vec<WeekSession> = sessions.into_iter().filter(|row| row.starts_at() >= starts_at && row.starts_at() < ends_at).collect();
+        sessions.sort_by_key(|row| row.starts_at());
+
+        let mut tasks: Vec<WeekTask> = tasks.into_iter().filter(|row| row.due_at() < ends_at).collect();
+        tasks.sort_by_key(|row| row.due_at());
+
+        MemberWeek {
+            starts_at,
+            ends_at,
+            sessions,
+            tasks,
+            check_ins,
+            announcements,
+        }
+    }
+}
+
+#[juniper::object(description = "The week ahead of a member")]
+impl MemberWeek {
+    pub fn startsAt(&self) -> NaiveDateTime {
+        self.starts_at
+    }
+
+    pub fn endsAt(&self) -> NaiveDateTime {
+        self.ends_at
+    }
+
+    pub fn sessions(&self) -> &Vec<WeekSession> {
+        &self.sessions
+    }
+
+    pub fn tasks(&self) -> &Vec<WeekTask> {
+        &self.tasks
+    }
+
+    pub fn checkIns(&self) -> &Vec<CheckIn> {
+        &self.check_ins
+    }
+
+    #[graphql(description = "The announcements of the programs of the member, published over the last week")]
+    pub fn announcements(&self) -> &Vec<Announcement> {
+        &self.announcements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::commons::ids::{EnrollmentId, ProgramId, SessionId, UserId};
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn program() -> Program {
+        Program {
+            id: ProgramId::from("p-1"),
+            name: String::from("Clarity"),
+            description: None,
+            active: true,
+            coach_name: String::from("Coach"),
+            coach_id: UserId::from("c-1"),
+            created_at: at("2021-02-01T00:00"),
+            updated_at: at("2021-02-01T00:00"),
+            is_private: false,
+            genre_id: None,
+            is_parent: true,
+            parent_program_id: None,
+            description_text: None,
+            max_members: None,
+            tags: String::from(""),
+            status: String::from("published"),
+            price_cents: None,
+            currency: None,
+        }
+    }
+
+    fn row(id: &str, start: &str) -> WeekSession {
+        let session = Session {
+            id: SessionId::from(id),
+            name: String::from("Review"),
+            description: None,
+            program_id: ProgramId::from("p-1"),
+            enrollment_id: EnrollmentId::from("e-1"),
+            people: None,
+            duration: 60,
+            original_start_date: at(start),
+            original_end_date: at(start) + Duration::hours(1),
+            revised_start_date: None,
+            revised_end_date: None,
+            offered_start_date: None,
+            offered_end_date: None,
+            is_ready: false,
+            actual_start_date: None,
+            actual_end_date: None,
+            cancelled_at: None,
+            created_at: at("2021-02-01T00:00"),
+            updated_at: at("2021-02-01T00:00"),
+            closing_notes: None,
+            is_request: false,
+            conference_id: None,
+            session_type: String::from("mono"),
+            expired_at: None,
+            parent_conference_id: None,
+        };
+
+        WeekSession { session, program: program() }
+    }
+
+    #[test]
+    fn should_run_the_week_from_the_start_of_today() {
+        assert_eq!((at("2021-02-22T00:00"), at("2021-03-01T00:00")), week_of(at("2021-02-22T15:30")));
+    }
+
+    #[test]
+    fn should_keep_the_sessions_scheduled_in_the_week() {
+        let (starts_at, ends_at) = week_of(at("2021-02-22T09:00"));
+
+        let mut moved_in = row("moved-in", "2021-03-10T10:00");
+        moved_in.session.revised_start_date = Some(at("2021-02-23T10:00"));
+
+        let mut moved_out = row("moved-out", "2021-02-24T10:00");
+        moved_out.session.revised_start_date = Some(at("2021-03-10T10:00"));
+
+        let rows = vec![row("later", "2021-02-26T10:00"), moved_out, moved_in, row("next-week", "2021-03-01T10:00")];
+
+        let week = MemberWeek::of(starts_at, ends_at, rows, Vec::new(), Vec::new(), Vec::new());
+        let kept: Vec<&str> = week.sessions.iter().map(|row| row.session.id.as_str()).collect();
+
+        assert_eq!(vec!["moved-in", "later"], kept);
+    }
+
+    #[test]
+    fn should_be_joined_from_ready_until_over() {
+        let mut session = row("s-1", "2021-02-23T10:00");
+        assert!(!session.can_join());
+
+        session.session.is_ready = true;
+        assert!(session.can_join());
+
+        session.session.actual_start_date = Some(at("2021-02-23T10:00"));
+        session.session.actual_end_date = Some(at("2021-02-23T11:00"));
+        assert!(!session.can_join());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::file_previews::{is_previewable, Preview};
 use crate::models::session_users::SessionUser;
 
 use crate::schema::session_files;
@@ -108,6 +109,117 @@ impl NewNote {
     }
 }
 
+// The Order of the fields are very important
+#[derive(Queryable, Debug)]
+pub struct SessionFile {
+    pub id: String,
+    pub session_note_id: String,
+    pub file_name: String,
+    pub file_path: String,
+    pub file_type: Option<String>,
+    pub file_size: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub duration_seconds: Option<i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub poster_path: Option<String>,
+    pub metadata_status: String,
+    pub page_count: Option<i32>,
+}
+
+// The videos wait for the metadata job; the images and PDFs are read as they are
+// attached; the rest have nothing to extract.
+pub const METADATA_NONE: &str = "none";
+pub const METADATA_PENDING: &str = "pending";
+pub const METADATA_DONE: &str = "done";
+pub const METADATA_FAILED: &str = "failed";
+
+const LAZY_LOAD_BYTES: i32 = 5 * 1024 * 1024;
+const LAZY_LOAD_PAGES: i32 = 20;
+
+impl SessionFile {
+    pub fn initial_status(mime_type: &str) -> &'static str {
+        if mime_type.starts_with("video/") {
+            return METADATA_PENDING;
+        }
+
+        METADATA_NONE
+    }
+
+    /**
+     * The files the UI should load on demand rather than along with the note.
+     */
+    fn is_large(&self) -> bool {
+        self.file_size.is_some_and(|size| size > LAZY_LOAD_BYTES) || self.page_count.is_some_and(|pages| pages > LAZY_LOAD_PAGES)
+    }
+}
+
+#[juniper::object(description = "A file attached to a note, with the preview details of the videos, images and PDFs")]
+impl SessionFile {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn note_id(&self) -> &str {
+        self.session_note_id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.file_name.as_str()
+    }
+
+    pub fn path(&self) -> &str {
+        self.file_path.as_str()
+    }
+
+    pub fn file_type(&self) -> Option<&str> {
+        self.file_type.as_deref()
+    }
+
+    pub fn size(&self) -> Option<i32> {
+        self.file_size
+    }
+
+    pub fn duration_seconds(&self) -> Option<i32> {
+        self.duration_seconds
+    }
+
+    pub fn width(&self) -> Option<i32> {
+        self.width
+    }
+
+    pub fn height(&self) -> Option<i32> {
+        self.height
+    }
+
+    pub fn poster_path(&self) -> Option<&str> {
+        self.poster_path.as_deref()
+    }
+
+    pub fn metadata_status(&self) -> &str {
+        self.metadata_status.as_str()
+    }
+
+    #[graphql(description = "The pages of a PDF")]
+    pub fn page_count(&self) -> Option<i32> {
+        self.page_count
+    }
+
+    #[graphql(description = "True when the file is large enough to be loaded only on demand")]
+    pub fn lazy_load(&self) -> bool {
+        self.is_large()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
 #[derive(Insertable)]
 #[table_name = "session_files"]
 pub struct NewNoteFile {
@@ -117,6 +229,10 @@ pub struct NewNoteFile {
     pub file_path: String,
     pub file_type: Option<String>,
     pub file_size: Option<i32>,
+    pub metadata_status: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub page_count: Option<i32>,
 }
 
 impl NewNoteFile {
@@ -130,10 +246,39 @@ impl NewNoteFile {
             file_name: request.name.to_owned(),
             file_type: Some(request.r#type.to_owned()),
             file_size: Some(request.size),
+            metadata_status: SessionFile::initial_status(request.r#type.as_str()).to_owned(),
+            width: None,
+            height: None,
+            page_count: None,
+        }
+    }
+
+    /**
+     * An image or a PDF that could not be read is marked failed, like a video.
+     */
+    pub fn with_preview(mut self, preview: Option<Preview>) -> NewNoteFile {
+        let mime_type = self.file_type.as_deref().unwrap_or_default();
+
+        match preview {
+            Some(preview) => {
+                self.width = preview.width;
+                self.height = preview.height;
+                self.page_count = preview.page_count;
+                self.metadata_status = METADATA_DONE.to_owned();
+            }
+            None if is_previewable(mime_type) => self.metadata_status = METADATA_FAILED.to_owned(),
+            None => {}
         }
+
+        self
     }
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct NoteFileCriteria {
+    pub note_id: String,
+}
+
 #[derive(juniper::GraphQLInputObject)]
 pub struct NoteCriteria {
     pub session_user_id: String,
@@ -0,0 +1,99 @@
+/**
+ * The in-app notifications of a user. A notification points to the record it is
+ * about through the ref_id, whose meaning depends on the kind; it stays unread
+ * until the user marks it read.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::util;
+use crate::schema::notifications;
+
+pub const PROGRAM_QUESTION: &str = "program_question";
+pub const QUESTION_ANSWERED: &str = "question_answered";
+pub const ANNOUNCEMENT: &str = "announcement";
+pub const PROGRAM_REQUEST: &str = "program_request";
+pub const PROGRAM_OFFER: &str = "program_offer";
+pub const OFFER_ACCEPTED: &str = "offer_accepted";
+pub const RECORDING_DECLINED: &str = "recording_declined";
+pub const GOAL_BOARD: &str = "goal_board";
+pub const SESSION_CANCELLED: &str = "session_cancelled";
+pub const TASK_EXTENDED: &str = "task_extended";
+pub const REQUEST_EXPIRED: &str = "request_expired";
+pub const CONFERENCE_EXPIRED: &str = "conference_expired";
+pub const ENROLLMENT_CREATED: &str = "enrollment_created";
+pub const ENROLLMENT_CANCELLED: &str = "enrollment_cancelled";
+pub const SESSION_READY: &str = "session_ready";
+pub const SESSION_REMINDER: &str = "session_reminder";
+pub const TASK_RESPONDED: &str = "task_responded";
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct Notification {
+    pub id: String,
+    pub user_id: String,
+    pub kind: String,
+    pub subject: String,
+    pub ref_id: String,
+    pub read_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A notice to the user about something that needs attention")]
+impl Notification {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn kind(&self) -> &str {
+        self.kind.as_str()
+    }
+
+    pub fn subject(&self) -> &str {
+        self.subject.as_str()
+    }
+
+    pub fn ref_id(&self) -> &str {
+        self.ref_id.as_str()
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NotificationCriteria {
+    pub user_id: String,
+    pub unread_only: bool,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct MarkReadRequest {
+    pub user_id: String,
+    pub ids: Vec<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "notifications"]
+pub struct NewNotification {
+    pub id: String,
+    pub user_id: String,
+    pub kind: String,
+    pub subject: String,
+    pub ref_id: String,
+}
+
+impl NewNotification {
+    pub fn new(user_id: &str, kind: &str, subject: String, ref_id: &str) -> NewNotification {
+        NewNotification {
+            id: util::fuzzy_id(),
+            user_id: user_id.to_owned(),
+            kind: kind.to_owned(),
+            subject,
+            ref_id: ref_id.to_owned(),
+        }
+    }
+}
@@ -21,7 +21,7 @@ pub struct Objective {
     pub closing_notes: Option<String>,
 }
 
-#[derive(juniper::GraphQLEnum)]
+#[derive(juniper::GraphQLEnum, Debug)]
 enum Status {
     DONE,
     PLANNED,
@@ -57,6 +57,26 @@ impl Objective {
     }
 
     pub fn status(&self) -> Status {
+        self.state()
+    }
+
+    pub fn description(&self) -> &str {
+        let value: &str = match &self.description {
+            None => "_",
+            Some(value) => value.as_str(),
+        };
+        value
+    }
+}
+
+impl Objective {
+
+    // The status as a plain word, for the exports.
+    pub fn status_label(&self) -> String {
+        format!("{:?}", self.state())
+    }
+
+    fn state(&self) -> Status {
         if self.actual_end_date.is_some() {
             return Status::DONE;
         }
@@ -78,14 +98,6 @@ impl Objective {
 
         Status::PLANNED
     }
-
-    pub fn description(&self) -> &str {
-        let value: &str = match &self.description {
-            None => "_",
-            Some(value) => value.as_str(),
-        };
-        value
-    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -4,6 +4,10 @@ use crate::schema::observations;
 
 use chrono::NaiveDateTime;
 
+const PRIVATE: &str = "private";
+const MEMBER: &str = "member";
+const ALL_COACHES: &str = "all_coaches";
+
 #[derive(Queryable, Debug, Identifiable)]
 pub struct Observation {
     pub id: String,
@@ -11,6 +15,57 @@ pub struct Observation {
     pub description: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub visibility: String,
+}
+
+/**
+ * The coach of the enrollment reads every observation; the others read only the
+ * ones shared with them: the member of the enrollment, or the peer coaches of
+ * the program.
+ */
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum Visibility {
+    PRIVATE,
+    MEMBER,
+    AllCoaches,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::PRIVATE => PRIVATE,
+            Visibility::MEMBER => MEMBER,
+            Visibility::AllCoaches => ALL_COACHES,
+        }
+    }
+
+    pub fn from_str(value: &str) -> Visibility {
+        match value {
+            MEMBER => Visibility::MEMBER,
+            ALL_COACHES => Visibility::AllCoaches,
+            _ => Visibility::PRIVATE,
+        }
+    }
+}
+
+/**
+ * How the caller stands to the enrollment whose observations are read.
+ */
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Reader {
+    Coach,
+    Member,
+    PeerCoach,
+}
+
+impl Reader {
+    pub fn visibilities(&self) -> Vec<&'static str> {
+        match self {
+            Reader::Coach => vec![PRIVATE, MEMBER, ALL_COACHES],
+            Reader::Member => vec![MEMBER],
+            Reader::PeerCoach => vec![ALL_COACHES],
+        }
+    }
 }
 
 #[juniper::object]
@@ -34,12 +89,17 @@ impl Observation {
     pub fn createdAt(&self) -> NaiveDateTime {
         self.created_at
     }
+
+    pub fn visibility(&self) -> Visibility {
+        Visibility::from_str(self.visibility.as_str())
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct NewObservationRequest {
     pub enrollment_id: String,
     pub description: String,
+    pub visibility: Option<Visibility>,
 }
 
 impl NewObservationRequest {
@@ -72,12 +132,19 @@ impl UpdateObservationRequest {
     }
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct ChangeObservationVisibilityRequest {
+    pub id: String,
+    pub visibility: Visibility,
+}
+
 #[derive(Insertable)]
 #[table_name = "observations"]
 pub struct NewObservation {
     pub id: String,
     pub enrollment_id: String,
     pub description: String,
+    pub visibility: String,
 }
 
 impl NewObservation {
@@ -88,6 +155,29 @@ impl NewObservation {
             id: fuzzy_id,
             enrollment_id: request.enrollment_id.to_owned(),
             description: request.description.to_owned(),
+            visibility: request.visibility.unwrap_or(Visibility::PRIVATE).as_str().to_owned(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_keep_the_visibility_as_a_word() {
+        for visibility in [Visibility::PRIVATE, Visibility::MEMBER, Visibility::AllCoaches].iter() {
+            assert_eq!(*visibility, Visibility::from_str(visibility.as_str()));
+        }
+
+        assert_eq!(Visibility::PRIVATE, Visibility::from_str("unknown"));
+    }
+
+    #[test]
+    fn should_show_the_others_only_what_is_shared_with_them() {
+        assert_eq!(3, Reader::Coach.visibilities().len());
+        assert_eq!(vec![MEMBER], Reader::Member.visibilities());
+        assert_eq!(vec![ALL_COACHES], Reader::PeerCoach.visibilities());
+    }
+}
@@ -0,0 +1,465 @@
+/**
+ * An organization groups the coaches of a company, so that its admins can follow
+ * their coaching in their own BI tools.
+ *
+ * The report of an organization reads only the coaches who are its members, their
+ * programs and the enrollments in them; an admin of one organization never sees the
+ * rows of another. The session and utilization rows come from the daily summaries
+ * of the coaches, the same ones the coach dashboards read.
+ */
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::scheduling::{SchedulingRules, BAD_HOURS};
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::schema::{organization_members, organizations};
+
+const DAY_PATTERN: &str = "%Y-%m-%d";
+const MAX_NAME: usize = 255;
+
+const BAD_DAY: &str = "The days should be like 2021-02-14.";
+const BAD_ORDER: &str = "The from day should not be after the to day.";
+const LONG_RANGE: &str = "The range should not be longer than a year.";
+
+// A year of days is as much as a report reads at once.
+const MAX_DAYS: i64 = 366;
+
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OrganizationRole {
+    ADMIN,
+    COACH,
+}
+
+impl OrganizationRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrganizationRole::ADMIN => "ADMIN",
+            OrganizationRole::COACH => "COACH",
+        }
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub created_by_id: String,
+    pub created_at: NaiveDateTime,
+    pub session_hours: Option<String>,
+    pub session_holidays: Option<String>,
+}
+
+#[juniper::object(description = "A company whose coaches are reported together")]
+impl Organization {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn created_by_id(&self) -> &str {
+        self.created_by_id.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    #[graphql(description = "The window of the sessions of its coaches in the local time of the member, like 06:00-22:00")]
+    pub fn session_hours(&self) -> Option<&str> {
+        self.session_hours.as_deref()
+    }
+
+    #[graphql(description = "The days without sessions for its coaches, like 2021-12-25,2022-01-01")]
+    pub fn session_holidays(&self) -> Option<&str> {
+        self.session_holidays.as_deref()
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "organizations"]
+pub struct NewOrganization {
+    pub id: String,
+    pub name: String,
+    pub created_by_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewOrganizationRequest {
+    pub name: String,
+}
+
+impl NewOrganizationRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME {
+            errors.push(ValidationError::new("name", "The name should have 1 to 255 characters."));
+        }
+
+        errors
+    }
+
+    pub fn to_row(&self, admin_id: &UserId) -> NewOrganization {
+        NewOrganization {
+            id: util::fuzzy_id(),
+            name: self.name.trim().to_owned(),
+            created_by_id: admin_id.to_string(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_members"]
+pub struct NewOrganizationMember {
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: String,
+}
+
+/**
+ * Without a role, the user leaves the organization.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct OrganizationMemberRequest {
+    pub organization_id: String,
+    pub user_id: UserId,
+    pub role: Option<OrganizationRole>,
+}
+
+impl OrganizationMemberRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.organization_id.trim().is_empty() {
+            errors.push(ValidationError::new("organization_id", "Organization id is a must."));
+        }
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The scheduling rules of the coaches of an organization, set by its admins; a
+ * rule left out falls back to the one of the platform.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct SchedulingRulesRequest {
+    pub organization_id: String,
+    pub session_hours: Option<String>,
+    pub session_holidays: Option<String>,
+}
+
+impl SchedulingRulesRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.organization_id.trim().is_empty() {
+            errors.push(ValidationError::new("organization_id", "Organization id is a must."));
+        }
+
+        let hours = self.session_hours.as_deref().unwrap_or_default();
+        let holidays = self.session_holidays.as_deref().unwrap_or_default();
+
+        for mistake in SchedulingRules::mistakes(hours, holidays) {
+            let field = if mistake == BAD_HOURS { "session_hours" } else { "session_holidays" };
+            errors.push(ValidationError::new(field, mistake));
+        }
+
+        errors
+    }
+
+    // A blank rule is left unset.
+    pub fn hours(&self) -> Option<String> {
+        self.session_hours.as_deref().map(str::trim).filter(|hours| !hours.is_empty()).map(String::from)
+    }
+
+    pub fn holidays(&self) -> Option<String> {
+        self.session_holidays.as_deref().map(str::trim).filter(|holidays| !holidays.is_empty()).map(String::from)
+    }
+}
+
+/**
+ * The days are in UTC and both ends are included, like 2021-02-01 to 2021-02-28.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct OrganizationReportCriteria {
+    pub user_id: UserId,
+    pub organization_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl OrganizationReportCriteria {
+    pub fn range(&self) -> Result<(NaiveDate, NaiveDate), &'static str> {
+        let from = NaiveDate::parse_from_str(self.from.trim(), DAY_PATTERN).map_err(|_| BAD_DAY)?;
+        let to = NaiveDate::parse_from_str(self.to.trim(), DAY_PATTERN).map_err(|_| BAD_DAY)?;
+
+        if from > to {
+            return Err(BAD_ORDER);
+        }
+
+        if to.signed_duration_since(from) >= Duration::days(MAX_DAYS) {
+            return Err(LONG_RANGE);
+        }
+
+        Ok((from, to))
+    }
+}
+
+pub struct ProgramEnrollmentRow {
+    pub program_id: String,
+    pub program_name: String,
+    pub coach_id: String,
+    pub active: bool,
+    pub enrollments: i32,
+    pub enrolled_in_range: i32,
+}
+
+#[juniper::object(description = "The enrollments of a program of the organization")]
+impl ProgramEnrollmentRow {
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn program_name(&self) -> &str {
+        self.program_name.as_str()
+    }
+
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    #[graphql(description = "The enrollments of the program till now")]
+    pub fn enrollments(&self) -> i32 {
+        self.enrollments
+    }
+
+    #[graphql(description = "The enrollments made within the range of the report")]
+    pub fn enrolled_in_range(&self) -> i32 {
+        self.enrolled_in_range
+    }
+}
+
+// The daily summary of a coach, as the report reads it.
+#[derive(Queryable, Debug)]
+pub struct SessionDayRow {
+    pub coach_id: String,
+    pub day: NaiveDate,
+    pub sessions_created: i32,
+    pub sessions_done: i32,
+    pub sessions_cancelled: i32,
+    pub tasks_responded: i32,
+}
+
+impl SessionDayRow {
+    fn is_active(&self) -> bool {
+        self.sessions_created + self.sessions_done + self.sessions_cancelled + self.tasks_responded > 0
+    }
+}
+
+#[juniper::object(description = "The sessions of a coach of the organization on a day")]
+impl SessionDayRow {
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn day(&self) -> NaiveDate {
+        self.day
+    }
+
+    pub fn sessions_created(&self) -> i32 {
+        self.sessions_created
+    }
+
+    pub fn sessions_done(&self) -> i32 {
+        self.sessions_done
+    }
+
+    pub fn sessions_cancelled(&self) -> i32 {
+        self.sessions_cancelled
+    }
+}
+
+pub struct CoachUtilizationRow {
+    pub coach_id: String,
+    pub full_name: String,
+    pub active_days: i32,
+    pub days: i32,
+    pub sessions_done: i32,
+    pub sessions_cancelled: i32,
+    pub tasks_responded: i32,
+}
+
+impl CoachUtilizationRow {
+    fn share_active(&self) -> f64 {
+        ratio(self.active_days, self.days)
+    }
+
+    fn share_done(&self) -> f64 {
+        ratio(self.sessions_done, self.sessions_done + self.sessions_cancelled)
+    }
+}
+
+#[juniper::object(description = "How much a coach of the organization coached over the range of the report")]
+impl CoachUtilizationRow {
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn full_name(&self) -> &str {
+        self.full_name.as_str()
+    }
+
+    #[graphql(description = "The days with a session or a task response")]
+    pub fn active_days(&self) -> i32 {
+        self.active_days
+    }
+
+    #[graphql(description = "The days in the range of the report")]
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    #[graphql(description = "The share of the days that were active, from 0 to 1")]
+    pub fn utilization(&self) -> f64 {
+        self.share_active()
+    }
+
+    pub fn sessions_done(&self) -> i32 {
+        self.sessions_done
+    }
+
+    pub fn sessions_cancelled(&self) -> i32 {
+        self.sessions_cancelled
+    }
+
+    #[graphql(description = "The share of the closed sessions that were done, from 0 to 1")]
+    pub fn completion(&self) -> f64 {
+        self.share_done()
+    }
+
+    pub fn tasks_responded(&self) -> i32 {
+        self.tasks_responded
+    }
+}
+
+fn ratio(part: i32, whole: i32) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+
+    part as f64 / whole as f64
+}
+
+/**
+ * The coaches of the organization with their totals over the days of the range,
+ * including the coaches without any activity.
+ */
+pub fn utilization(coaches: &[(String, String)], days: &[SessionDayRow], from: NaiveDate, to: NaiveDate) -> Vec<CoachUtilizationRow> {
+    let range_days = (to.signed_duration_since(from).num_days() + 1) as i32;
+
+    coaches
+        .iter()
+        .map(|(coach_id, full_name)| {
+            let of_coach: Vec<&SessionDayRow> = days.iter().filter(|row| row.coach_id == *coach_id).collect();
+
+            CoachUtilizationRow {
+                coach_id: coach_id.to_owned(),
+                full_name: full_name.to_owned(),
+                active_days: of_coach.iter().filter(|row| row.is_active()).count() as i32,
+                days: range_days,
+                sessions_done: of_coach.iter().map(|row| row.sessions_done).sum(),
+                sessions_cancelled: of_coach.iter().map(|row| row.sessions_cancelled).sum(),
+                tasks_responded: of_coach.iter().map(|row| row.tasks_responded).sum(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * The datasets of an organization over a range of days, flat rows for the BI tools.
+ */
+pub struct OrganizationReport {
+    pub organization: Organization,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub programs: Vec<ProgramEnrollmentRow>,
+    pub sessions: Vec<SessionDayRow>,
+    pub coaches: Vec<CoachUtilizationRow>,
+}
+
+#[juniper::object(description = "The read-only report of an organization, limited to its own coaches")]
+impl OrganizationReport {
+    pub fn organization(&self) -> &Organization {
+        &self.organization
+    }
+
+    pub fn from(&self) -> NaiveDate {
+        self.from
+    }
+
+    pub fn to(&self) -> NaiveDate {
+        self.to
+    }
+
+    #[graphql(description = "The enrollment counts by program")]
+    pub fn programs(&self) -> &Vec<ProgramEnrollmentRow> {
+        &self.programs
+    }
+
+    #[graphql(description = "The sessions by coach and day, the days without any activity left out")]
+    pub fn sessions(&self) -> &Vec<SessionDayRow> {
+        &self.sessions
+    }
+
+    #[graphql(description = "The utilization by coach")]
+    pub fn coaches(&self) -> &Vec<CoachUtilizationRow> {
+        &self.coaches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn day(coach_id: &str, date: u32, done: i32, cancelled: i32) -> SessionDayRow {
+        SessionDayRow {
+            coach_id: coach_id.to_owned(),
+            day: NaiveDate::from_ymd(2021, 2, date),
+            sessions_created: 0,
+            sessions_done: done,
+            sessions_cancelled: cancelled,
+            tasks_responded: 0,
+        }
+    }
+
+    #[test]
+    fn should_total_the_days_of_each_coach() {
+        let coaches = vec![(String::from("c-1"), String::from("Asha")), (String::from("c-2"), String::from("Ravi"))];
+        let days = vec![day("c-1", 1, 2, 0), day("c-1", 2, 1, 1), day("c-1", 3, 0, 0)];
+
+        let rows = utilization(&coaches, &days, NaiveDate::from_ymd(2021, 2, 1), NaiveDate::from_ymd(2021, 2, 10));
+
+        assert_eq!(2, rows[0].active_days);
+        assert_eq!(10, rows[0].days);
+        assert_eq!(3, rows[0].sessions_done);
+        assert_eq!(0.75, rows[0].share_done());
+
+        assert_eq!(0, rows[1].active_days);
+        assert_eq!(0.0, rows[1].share_done());
+    }
+}
@@ -0,0 +1,95 @@
+/**
+ * A user who forgot the password asks for a reset by the email address; a
+ * token is mailed to that address, and the password is changed only by
+ * presenting the token back.
+ *
+ * The token is good for an hour and for one change; asking again replaces the
+ * tokens yet to be used.
+ */
+use chrono::{Duration, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::util;
+use crate::models::users::User;
+use crate::schema::password_resets;
+
+const VALID_MINUTES: i64 = 60;
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct PasswordReset {
+    pub id: String,
+    pub user_id: String,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl PasswordReset {
+    pub fn is_active(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > util::now()
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "password_resets"]
+pub struct NewPasswordReset {
+    pub id: String,
+    pub user_id: String,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+impl NewPasswordReset {
+    pub fn from(user: &User) -> NewPasswordReset {
+        NewPasswordReset {
+            id: util::fuzzy_id(),
+            user_id: user.id.to_string(),
+            token: util::secure_token(),
+            expires_at: util::now() + Duration::minutes(VALID_MINUTES),
+        }
+    }
+
+    pub fn valid_minutes(&self) -> i64 {
+        VALID_MINUTES
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+impl PasswordResetRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.email.trim().is_empty() {
+            errors.push(ValidationError::new("email", "email is a must for password reset."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ConfirmPasswordResetRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+impl ConfirmPasswordResetRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.token.trim().is_empty() {
+            errors.push(ValidationError::new("token", "The token of the reset mail is a must."));
+        }
+
+        if self.new_password.trim().is_empty() {
+            errors.push(ValidationError::new("new_password", "New password cannot be blank."));
+        }
+
+        errors
+    }
+}
@@ -0,0 +1,224 @@
+/**
+ * A member pays for a paid program before enrolling in it. The client opens a
+ * payment intent at the payment gateway and records its reference here; the
+ * gateway, or an admin reconciling by hand, then confirms or fails it. The card
+ * details never reach us.
+ *
+ * The enrollment takes up the confirmed payment, so that a payment pays for one
+ * enrollment only.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::schema::payments;
+
+const PENDING: &str = "pending";
+const CONFIRMED: &str = "confirmed";
+const FAILED: &str = "failed";
+
+const MAX_REF: usize = 255;
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum PaymentStatus {
+    PENDING,
+    CONFIRMED,
+    FAILED,
+}
+
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::PENDING => PENDING,
+            PaymentStatus::CONFIRMED => CONFIRMED,
+            PaymentStatus::FAILED => FAILED,
+        }
+    }
+
+    pub fn from_str(value: &str) -> PaymentStatus {
+        match value {
+            CONFIRMED => PaymentStatus::CONFIRMED,
+            FAILED => PaymentStatus::FAILED,
+            _ => PaymentStatus::PENDING,
+        }
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct Payment {
+    pub id: String,
+    pub program_id: String,
+    pub member_id: String,
+    pub enrollment_id: Option<String>,
+    pub amount_cents: i32,
+    pub currency: String,
+    pub gateway_ref: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A payment of a member for a paid program, as the payment gateway reports it")]
+impl Payment {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn member_id(&self) -> &str {
+        self.member_id.as_str()
+    }
+
+    #[graphql(description = "The enrollment the payment paid for; none before the member enrolls")]
+    pub fn enrollment_id(&self) -> Option<&str> {
+        self.enrollment_id.as_deref()
+    }
+
+    #[graphql(description = "The amount in the cents of the currency")]
+    pub fn amount_cents(&self) -> i32 {
+        self.amount_cents
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_str()
+    }
+
+    #[graphql(description = "The id of the payment intent at the payment gateway")]
+    pub fn gateway_ref(&self) -> &str {
+        self.gateway_ref.as_str()
+    }
+
+    pub fn status(&self) -> PaymentStatus {
+        PaymentStatus::from_str(self.status.as_str())
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "payments"]
+pub struct NewPayment {
+    pub id: String,
+    pub program_id: String,
+    pub member_id: String,
+    pub amount_cents: i32,
+    pub currency: String,
+    pub gateway_ref: String,
+    pub status: String,
+}
+
+fn validate_ref(gateway_ref: &str, errors: &mut Vec<ValidationError>) {
+    let length = gateway_ref.chars().count();
+
+    if length == 0 || length > MAX_REF || gateway_ref.chars().any(char::is_whitespace) {
+        errors.push(ValidationError::new("gateway_ref", "The gateway reference should have 1 to 255 characters and no spaces."));
+    }
+}
+
+/**
+ * The member records the payment intent opened at the gateway; the amount is the
+ * price of the program as of now, less the discount of the coupon the member is
+ * to enroll with.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct PaymentIntentRequest {
+    pub program_id: ProgramId,
+    pub member_id: String,
+    pub gateway_ref: String,
+    pub coupon_code: Option<String>,
+}
+
+impl PaymentIntentRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        if self.member_id.trim().is_empty() {
+            errors.push(ValidationError::new("member_id", "Member id is a must."));
+        }
+
+        validate_ref(self.gateway_ref.as_str(), &mut errors);
+
+        errors
+    }
+
+    pub fn to_row(&self, amount_cents: i32, currency: &str) -> NewPayment {
+        NewPayment {
+            id: util::fuzzy_id(),
+            program_id: self.program_id.to_string(),
+            member_id: self.member_id.to_owned(),
+            amount_cents,
+            currency: currency.to_owned(),
+            gateway_ref: self.gateway_ref.to_owned(),
+            status: PaymentStatus::PENDING.as_str().to_owned(),
+        }
+    }
+}
+
+/**
+ * The outcome of a payment intent. The gateway tells it with the token we
+ * shared with it; an admin needs no token.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ConfirmPaymentRequest {
+    pub gateway_ref: String,
+    pub succeeded: bool,
+    pub token: Option<String>,
+}
+
+impl ConfirmPaymentRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        validate_ref(self.gateway_ref.as_str(), &mut errors);
+
+        errors
+    }
+
+    pub fn target_status(&self) -> PaymentStatus {
+        if self.succeeded {
+            PaymentStatus::CONFIRMED
+        } else {
+            PaymentStatus::FAILED
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_take_the_gateway_reference_as_it_is() {
+        let mut request = PaymentIntentRequest {
+            program_id: ProgramId::from("p-1"),
+            member_id: String::from("m-1"),
+            gateway_ref: String::from("pi_3Jx9"),
+            coupon_code: None,
+        };
+
+        assert!(request.validate().is_empty());
+
+        let row = request.to_row(4900, "EUR");
+        assert_eq!(4900, row.amount_cents);
+        assert_eq!("EUR", row.currency);
+        assert_eq!(PaymentStatus::PENDING, PaymentStatus::from_str(row.status.as_str()));
+
+        request.gateway_ref = String::from("pi 3Jx9");
+        assert_eq!(1, request.validate().len());
+    }
+}
@@ -0,0 +1,167 @@
+/**
+ * The plan of an enrollment as a spreadsheet, for the coaches to share with the
+ * clients who live in Excel: the objectives, the tasks with their dates and
+ * status, and the observations, one line each and in that order.
+ *
+ * The same lines make the CSV and the sheets of the workbook.
+ */
+use chrono::NaiveDateTime;
+
+use crate::models::objectives::Objective;
+use crate::models::observations::Observation;
+use crate::models::tasks::Task;
+use crate::models::timeline_exports::{csv_field, format_time};
+
+pub const PLAN_COLUMNS: [&str; 9] = ["section", "name", "description", "scheduled_start", "scheduled_end", "actual_start", "actual_end", "status", "recorded_at"];
+
+pub const OBJECTIVES: &str = "Objectives";
+pub const TASKS: &str = "Tasks";
+pub const OBSERVATIONS: &str = "Observations";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlanFormat {
+    Csv,
+    Xlsx,
+}
+
+impl PlanFormat {
+    pub fn from_extension(extension: &str) -> Option<PlanFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(PlanFormat::Csv),
+            "xlsx" => Some(PlanFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlanFormat::Csv => "csv",
+            PlanFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PlanFormat::Csv => "text/csv; charset=utf-8",
+            PlanFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+pub struct PlanRow {
+    pub section: &'static str,
+    pub name: String,
+    pub description: String,
+    pub scheduled_start: Option<NaiveDateTime>,
+    pub scheduled_end: Option<NaiveDateTime>,
+    pub actual_start: Option<NaiveDateTime>,
+    pub actual_end: Option<NaiveDateTime>,
+    pub status: String,
+    pub recorded_at: NaiveDateTime,
+}
+
+impl PlanRow {
+    pub fn from_objective(objective: &Objective) -> PlanRow {
+        PlanRow {
+            section: OBJECTIVES,
+            name: objective.description.to_owned().unwrap_or_default(),
+            description: objective.closing_notes.to_owned().unwrap_or_default(),
+            scheduled_start: Some(objective.revised_start_date.unwrap_or(objective.original_start_date)),
+            scheduled_end: Some(objective.revised_end_date.unwrap_or(objective.original_end_date)),
+            actual_start: objective.actual_start_date,
+            actual_end: objective.actual_end_date,
+            status: objective.status_label(),
+            recorded_at: objective.created_at,
+        }
+    }
+
+    pub fn from_task(task: &Task) -> PlanRow {
+        PlanRow {
+            section: TASKS,
+            name: task.name.to_owned(),
+            description: task.description.to_owned().unwrap_or_default(),
+            scheduled_start: Some(task.revised_start_date.unwrap_or(task.original_start_date)),
+            scheduled_end: Some(task.revised_end_date.unwrap_or(task.original_end_date)),
+            actual_start: task.actual_start_date,
+            actual_end: task.actual_end_date,
+            status: task.status_label(),
+            recorded_at: task.created_at,
+        }
+    }
+
+    pub fn from_observation(observation: &Observation) -> PlanRow {
+        PlanRow {
+            section: OBSERVATIONS,
+            name: String::new(),
+            description: observation.description.to_owned().unwrap_or_default(),
+            scheduled_start: None,
+            scheduled_end: None,
+            actual_start: None,
+            actual_end: None,
+            status: String::new(),
+            recorded_at: observation.created_at,
+        }
+    }
+
+    /**
+     * The values in the order of PLAN_COLUMNS, as they are.
+     */
+    pub fn cells(&self) -> Vec<String> {
+        vec![
+            self.section.to_owned(),
+            self.name.to_owned(),
+            self.description.to_owned(),
+            format_time(self.scheduled_start),
+            format_time(self.scheduled_end),
+            format_time(self.actual_start),
+            format_time(self.actual_end),
+            self.status.to_owned(),
+            format_time(Some(self.recorded_at)),
+        ]
+    }
+
+    pub fn to_csv(&self) -> String {
+        let fields: Vec<String> = self.cells().iter().map(|cell| csv_field(cell)).collect();
+
+        format!("{}\r\n", fields.join(","))
+    }
+}
+
+pub fn plan_header() -> String {
+    format!("{}\r\n", PLAN_COLUMNS.join(","))
+}
+
+pub fn plan_file_name(enrollment_id: &str, format: PlanFormat) -> String {
+    format!("plan-{}.{}", sanitize_filename::sanitize(enrollment_id), format.extension())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn should_write_an_observation_as_a_line() {
+        let observation = Observation {
+            id: String::from("o-1"),
+            enrollment_id: String::from("e-1"),
+            description: Some(String::from("Sleeps better, walks daily")),
+            created_at: NaiveDate::from_ymd(2021, 3, 11).and_hms(9, 5, 7),
+            updated_at: NaiveDate::from_ymd(2021, 3, 11).and_hms(9, 5, 7),
+            visibility: String::from("member"),
+        };
+
+        let line = PlanRow::from_observation(&observation).to_csv();
+
+        assert_eq!("Observations,,\"Sleeps better, walks daily\",,,,,,2021-03-11T09:05:07Z\r\n", line);
+        assert_eq!(PLAN_COLUMNS.len(), plan_header().split(',').count());
+    }
+
+    #[test]
+    fn should_know_the_formats_by_their_extension() {
+        assert_eq!(Some(PlanFormat::Xlsx), PlanFormat::from_extension("XLSX"));
+        assert_eq!(None, PlanFormat::from_extension("pdf"));
+        assert_eq!("plan-e-1.csv", plan_file_name("e-1", PlanFormat::Csv));
+    }
+}
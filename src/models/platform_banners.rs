@@ -0,0 +1,284 @@
+/**
+ * A banner of the platform, like a maintenance window or a new feature, shown
+ * by the UI between its start and its end to the audience it is meant for.
+ *
+ * A user may dismiss a banner, and does not get it again; a critical banner
+ * stays until its window closes.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::schema::banner_dismissals;
+use crate::schema::platform_banners;
+
+const INFO: &str = "info";
+const WARNING: &str = "warning";
+const CRITICAL: &str = "critical";
+
+const ALL: &str = "all";
+const COACHES: &str = "coaches";
+const MEMBERS: &str = "members";
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum BannerSeverity {
+    INFO,
+    WARNING,
+    CRITICAL,
+}
+
+impl BannerSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BannerSeverity::INFO => INFO,
+            BannerSeverity::WARNING => WARNING,
+            BannerSeverity::CRITICAL => CRITICAL,
+        }
+    }
+
+    pub fn from_str(value: &str) -> BannerSeverity {
+        match value {
+            WARNING => BannerSeverity::WARNING,
+            CRITICAL => BannerSeverity::CRITICAL,
+            _ => BannerSeverity::INFO,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum BannerAudience {
+    ALL,
+    COACHES,
+    MEMBERS,
+}
+
+impl BannerAudience {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BannerAudience::ALL => ALL,
+            BannerAudience::COACHES => COACHES,
+            BannerAudience::MEMBERS => MEMBERS,
+        }
+    }
+
+    pub fn from_str(value: &str) -> BannerAudience {
+        match value {
+            COACHES => BannerAudience::COACHES,
+            MEMBERS => BannerAudience::MEMBERS,
+            _ => BannerAudience::ALL,
+        }
+    }
+
+    /**
+     * The audiences a user of the type belongs to; an admin sees every banner.
+     */
+    pub fn of_user_type(user_type: &str) -> Vec<&'static str> {
+        match user_type {
+            util::COACH => vec![ALL, COACHES],
+            util::MEMBER => vec![ALL, MEMBERS],
+            _ => vec![ALL, COACHES, MEMBERS],
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct PlatformBanner {
+    pub id: String,
+    pub created_by_id: String,
+    pub title: String,
+    pub message: String,
+    pub severity: String,
+    pub audience: String,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A notice of the platform shown as a banner within its window")]
+impl PlatformBanner {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    pub fn severity(&self) -> BannerSeverity {
+        BannerSeverity::from_str(self.severity.as_str())
+    }
+
+    pub fn audience(&self) -> BannerAudience {
+        BannerAudience::from_str(self.audience.as_str())
+    }
+
+    pub fn starts_at(&self) -> NaiveDateTime {
+        self.starts_at
+    }
+
+    pub fn ends_at(&self) -> NaiveDateTime {
+        self.ends_at
+    }
+
+    pub fn is_dismissible(&self) -> bool {
+        self.severity != CRITICAL
+    }
+}
+
+/**
+ * The window is in UTC, like 2021-02-20T22:00:00Z. The banners are managed by the
+ * signed in admin, never by an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct BannerRequest {
+    pub title: String,
+    pub message: String,
+    pub severity: BannerSeverity,
+    pub audience: BannerAudience,
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateBannerRequest {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub severity: BannerSeverity,
+    pub audience: BannerAudience,
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+fn check(errors: &mut Vec<ValidationError>, title: &str, message: &str, starts_at: &str, ends_at: &str) {
+    let title_length = title.trim().chars().count();
+    if title_length == 0 || title_length > 255 {
+        errors.push(ValidationError::new("title", "The title should have 1 to 255 characters."));
+    }
+
+    let message_length = message.trim().chars().count();
+    if message_length == 0 || message_length > 2000 {
+        errors.push(ValidationError::new("message", "The message should have 1 to 2000 characters."));
+    }
+
+    if !util::is_valid_date(starts_at) || !util::is_valid_date(ends_at) {
+        errors.push(ValidationError::new("starts_at", "The window should be like 2021-02-20T22:00:00Z."));
+        return;
+    }
+
+    let (start, end) = (util::as_date(starts_at), util::as_date(ends_at));
+
+    if end <= start {
+        errors.push(ValidationError::new("ends_at", "The banner should end after it starts."));
+    } else if util::is_past_date(end) {
+        errors.push(ValidationError::new("ends_at", "The banner should not end in the past."));
+    }
+}
+
+impl BannerRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        check(&mut errors, self.title.as_str(), self.message.as_str(), self.starts_at.as_str(), self.ends_at.as_str());
+
+        errors
+    }
+}
+
+impl UpdateBannerRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.id.trim().is_empty() {
+            errors.push(ValidationError::new("id", "Id of the banner is a must."));
+        }
+
+        check(&mut errors, self.title.as_str(), self.message.as_str(), self.starts_at.as_str(), self.ends_at.as_str());
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct BannerCriteria {
+    pub id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct DismissBannerRequest {
+    pub user_id: UserId,
+    pub banner_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "platform_banners"]
+pub struct NewPlatformBanner {
+    pub id: String,
+    pub created_by_id: String,
+    pub title: String,
+    pub message: String,
+    pub severity: String,
+    pub audience: String,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+}
+
+impl NewPlatformBanner {
+    pub fn from(request: &BannerRequest, admin_id: &UserId) -> NewPlatformBanner {
+        NewPlatformBanner {
+            id: util::fuzzy_id(),
+            created_by_id: admin_id.to_string(),
+            title: request.title.trim().to_owned(),
+            message: request.message.trim().to_owned(),
+            severity: request.severity.as_str().to_owned(),
+            audience: request.audience.as_str().to_owned(),
+            starts_at: util::as_date(request.starts_at.as_str()),
+            ends_at: util::as_date(request.ends_at.as_str()),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "banner_dismissals"]
+pub struct NewBannerDismissal {
+    pub banner_id: String,
+    pub user_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(starts_at: &str, ends_at: &str) -> BannerRequest {
+        BannerRequest {
+            title: String::from("Maintenance"),
+            message: String::from("The platform is down for an upgrade."),
+            severity: BannerSeverity::WARNING,
+            audience: BannerAudience::ALL,
+            starts_at: starts_at.to_owned(),
+            ends_at: ends_at.to_owned(),
+        }
+    }
+
+    #[test]
+    fn should_check_the_window() {
+        assert!(request("2099-02-20T22:00:00Z", "2099-02-21T02:00:00Z").validate().is_empty());
+        assert_eq!("ends_at", request("2099-02-21T02:00:00Z", "2099-02-20T22:00:00Z").validate()[0].field);
+        assert_eq!("ends_at", request("2020-02-20T22:00:00Z", "2020-02-21T02:00:00Z").validate()[0].field);
+        assert_eq!("starts_at", request("tonight", "2099-02-21T02:00:00Z").validate()[0].field);
+    }
+
+    #[test]
+    fn should_target_the_audience_of_the_user() {
+        assert_eq!(vec![ALL, COACHES], BannerAudience::of_user_type(util::COACH));
+        assert_eq!(vec![ALL, MEMBERS], BannerAudience::of_user_type(util::MEMBER));
+        assert_eq!(3, BannerAudience::of_user_type(util::ADMIN).len());
+    }
+}
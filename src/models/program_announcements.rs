@@ -0,0 +1,200 @@
+/**
+ * An announcement of the coach to the members of a program. It is a draft until
+ * the coach sets the publish time, and scheduled until the jobs publish it.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::ProgramId;
+use crate::commons::plain_text;
+use crate::commons::util;
+use crate::schema::program_announcements;
+
+#[derive(juniper::GraphQLEnum, PartialEq, Debug)]
+pub enum AnnouncementState {
+    DRAFT,
+    SCHEDULED,
+    PUBLISHED,
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "program_announcements"]
+pub struct Announcement {
+    pub id: String,
+    pub program_id: String,
+    pub coach_id: String,
+    pub title: String,
+    pub content: String,
+    pub publish_at: Option<NaiveDateTime>,
+    pub published_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub content_text: Option<String>,
+}
+
+impl Announcement {
+    pub fn state(&self) -> AnnouncementState {
+        if self.published_at.is_some() {
+            return AnnouncementState::PUBLISHED;
+        }
+
+        if self.publish_at.is_some() {
+            return AnnouncementState::SCHEDULED;
+        }
+
+        AnnouncementState::DRAFT
+    }
+}
+
+#[juniper::object(description = "An announcement of the coach to the members of a program")]
+impl Announcement {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn content(&self) -> &str {
+        self.content.as_str()
+    }
+
+    #[graphql(description = "The content without its markup, for the mails, the screen readers and the SMS")]
+    pub fn plain_content(&self) -> String {
+        match &self.content_text {
+            Some(value) => value.to_owned(),
+            None => plain_text::plain_text(self.content.as_str()),
+        }
+    }
+
+    pub fn publish_at(&self) -> Option<NaiveDateTime> {
+        self.publish_at
+    }
+
+    pub fn published_at(&self) -> Option<NaiveDateTime> {
+        self.published_at
+    }
+
+    pub fn state(&self) -> AnnouncementState {
+        Announcement::state(self)
+    }
+}
+
+/**
+ * Without the publish_at, the announcement is kept as a draft. The time is in
+ * UTC, like the start of a session.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct AnnouncementRequest {
+    pub coach_id: String,
+    pub program_id: ProgramId,
+    pub title: String,
+    pub content: String,
+    pub publish_at: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateAnnouncementRequest {
+    pub coach_id: String,
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub publish_at: Option<String>,
+}
+
+fn check(errors: &mut Vec<ValidationError>, title: &str, content: &str, publish_at: &Option<String>) {
+    let title_length = title.trim().chars().count();
+    if title_length == 0 || title_length > 255 {
+        errors.push(ValidationError::new("title", "The title should have 1 to 255 characters."));
+    }
+
+    if content.trim().is_empty() {
+        errors.push(ValidationError::new("content", "The content of the announcement is a must."));
+    }
+
+    if let Some(value) = publish_at {
+        if !util::is_valid_date(value) {
+            errors.push(ValidationError::new("publish_at", "The publish time should be like 2021-02-14T09:30:00Z."));
+        } else if util::is_past_date(util::as_date(value)) {
+            errors.push(ValidationError::new("publish_at", "The publish time should not be in the past."));
+        }
+    }
+}
+
+impl AnnouncementRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "Program id is a must."));
+        }
+
+        check(&mut errors, self.title.as_str(), self.content.as_str(), &self.publish_at);
+
+        errors
+    }
+}
+
+impl UpdateAnnouncementRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.id.trim().is_empty() {
+            errors.push(ValidationError::new("id", "Id of the announcement is a must."));
+        }
+
+        check(&mut errors, self.title.as_str(), self.content.as_str(), &self.publish_at);
+
+        errors
+    }
+
+    pub fn publish_at(&self) -> Option<NaiveDateTime> {
+        self.publish_at.as_deref().map(util::as_date)
+    }
+}
+
+/**
+ * The coach of the program gets every announcement; the others only the published.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct AnnouncementCriteria {
+    pub user_id: String,
+    pub program_id: ProgramId,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct DeleteAnnouncementRequest {
+    pub coach_id: String,
+    pub id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "program_announcements"]
+pub struct NewAnnouncement {
+    pub id: String,
+    pub program_id: String,
+    pub coach_id: String,
+    pub title: String,
+    pub content: String,
+    pub publish_at: Option<NaiveDateTime>,
+    pub content_text: String,
+}
+
+impl NewAnnouncement {
+    pub fn from(request: &AnnouncementRequest) -> NewAnnouncement {
+        NewAnnouncement {
+            id: util::fuzzy_id(),
+            program_id: request.program_id.to_string(),
+            coach_id: request.coach_id.to_owned(),
+            title: request.title.trim().to_owned(),
+            content: request.content.trim().to_owned(),
+            publish_at: request.publish_at.as_deref().map(util::as_date),
+            content_text: plain_text::plain_text(request.content.trim()),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+/**
+ * The FAQ of a program and the questions behind it. The coach curates the FAQ;
+ * anyone considering the program may ask a question, which the coach answers
+ * privately or publishes to the FAQ.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::util;
+use crate::schema::program_faqs;
+use crate::schema::program_questions;
+
+const MAX_QUESTION: usize = 1000;
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct ProgramFaq {
+    pub id: String,
+    pub program_id: String,
+    pub question: String,
+    pub answer: String,
+    pub position: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A frequently asked question of a program, with the answer of the coach")]
+impl ProgramFaq {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn question(&self) -> &str {
+        self.question.as_str()
+    }
+
+    pub fn answer(&self) -> &str {
+        self.answer.as_str()
+    }
+
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct ProgramQuestion {
+    pub id: String,
+    pub program_id: String,
+    pub asked_by_id: String,
+    pub question: String,
+    pub answer: Option<String>,
+    pub answered_at: Option<NaiveDateTime>,
+    pub faq_id: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A question asked to the coach of a program")]
+impl ProgramQuestion {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn asked_by_id(&self) -> &str {
+        self.asked_by_id.as_str()
+    }
+
+    pub fn question(&self) -> &str {
+        self.question.as_str()
+    }
+
+    pub fn answer(&self) -> Option<&str> {
+        self.answer.as_deref()
+    }
+
+    pub fn answered_at(&self) -> Option<NaiveDateTime> {
+        self.answered_at
+    }
+
+    pub fn is_published(&self) -> bool {
+        self.faq_id.is_some()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+fn check_text(errors: &mut Vec<ValidationError>, field: &str, value: &str, message: &str) {
+    let length = value.trim().chars().count();
+
+    if length == 0 || length > MAX_QUESTION {
+        errors.push(ValidationError::new(field, message));
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewFaqRequest {
+    pub coach_id: String,
+    pub program_id: ProgramId,
+    pub question: String,
+    pub answer: String,
+    pub position: i32,
+}
+
+impl NewFaqRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "Program id is a must."));
+        }
+
+        check_text(&mut errors, "question", self.question.as_str(), "The question should have 1 to 1000 characters.");
+        check_text(&mut errors, "answer", self.answer.as_str(), "The answer should have 1 to 1000 characters.");
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateFaqRequest {
+    pub coach_id: String,
+    pub id: String,
+    pub question: String,
+    pub answer: String,
+    pub position: i32,
+}
+
+impl UpdateFaqRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.id.trim().is_empty() {
+            errors.push(ValidationError::new("id", "Id of the FAQ is a must."));
+        }
+
+        check_text(&mut errors, "question", self.question.as_str(), "The question should have 1 to 1000 characters.");
+        check_text(&mut errors, "answer", self.answer.as_str(), "The answer should have 1 to 1000 characters.");
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct FaqCriteria {
+    pub coach_id: String,
+    pub id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AskQuestionRequest {
+    pub user_id: UserId,
+    pub program_id: ProgramId,
+    pub question: String,
+}
+
+impl AskQuestionRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "Program id is a must."));
+        }
+
+        check_text(&mut errors, "question", self.question.as_str(), "The question should have 1 to 1000 characters.");
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AnswerQuestionRequest {
+    pub coach_id: String,
+    pub question_id: String,
+    pub answer: String,
+    pub publish: bool,
+}
+
+impl AnswerQuestionRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.question_id.trim().is_empty() {
+            errors.push(ValidationError::new("question_id", "Question id is a must."));
+        }
+
+        check_text(&mut errors, "answer", self.answer.as_str(), "The answer should have 1 to 1000 characters.");
+
+        errors
+    }
+}
+
+/**
+ * The coach of the program gets all the questions; anyone else only their own.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct QuestionCriteria {
+    pub user_id: String,
+    pub program_id: ProgramId,
+}
+
+#[derive(Insertable)]
+#[table_name = "program_faqs"]
+pub struct NewFaq {
+    pub id: String,
+    pub program_id: String,
+    pub question: String,
+    pub answer: String,
+    pub position: i32,
+}
+
+impl NewFaq {
+    pub fn from(request: &NewFaqRequest) -> NewFaq {
+        NewFaq {
+            id: util::fuzzy_id(),
+            program_id: request.program_id.to_string(),
+            question: request.question.trim().to_owned(),
+            answer: request.answer.trim().to_owned(),
+            position: request.position,
+        }
+    }
+
+    pub fn published(question: &ProgramQuestion, answer: &str, position: i32) -> NewFaq {
+        NewFaq {
+            id: util::fuzzy_id(),
+            program_id: question.program_id.to_owned(),
+            question: question.question.to_owned(),
+            answer: answer.trim().to_owned(),
+            position,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "program_questions"]
+pub struct NewQuestion {
+    pub id: String,
+    pub program_id: String,
+    pub asked_by_id: String,
+    pub question: String,
+}
+
+impl NewQuestion {
+    pub fn from(request: &AskQuestionRequest) -> NewQuestion {
+        NewQuestion {
+            id: util::fuzzy_id(),
+            program_id: request.program_id.to_string(),
+            asked_by_id: request.user_id.to_string(),
+            question: request.question.trim().to_owned(),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+/**
+ * A member who finds no fitting program describes the need; the listed coaches
+ * whose specialties meet the tags of the request are told about it, and may offer
+ * one of their programs. Accepting an offer enrolls the member into the program.
+ *
+ * The tags are kept like the specialties of the coaches, ",leadership,sales,".
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::coach_profiles::{from_tags, to_tags};
+use crate::models::programs::Program;
+use crate::schema::program_offers;
+use crate::schema::program_requests;
+
+pub const OPEN: &str = "open";
+pub const FULFILLED: &str = "fulfilled";
+pub const WITHDRAWN: &str = "withdrawn";
+
+pub const PENDING: &str = "pending";
+pub const ACCEPTED: &str = "accepted";
+pub const DECLINED: &str = "declined";
+
+const MAX_TAGS: usize = 10;
+const MAX_TEXT: usize = 2000;
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct ProgramRequest {
+    pub id: String,
+    pub member_id: String,
+    pub title: String,
+    pub needs: String,
+    pub tags: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl ProgramRequest {
+    pub fn is_open(&self) -> bool {
+        self.status == OPEN
+    }
+
+    pub fn shares_tag(&self, specialties: &str) -> bool {
+        from_tags(self.tags.as_str()).iter().any(|tag| specialties.contains(format!(",{},", tag).as_str()))
+    }
+}
+
+#[juniper::object(description = "What a member looks for in a program that is not there yet")]
+impl ProgramRequest {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn member_id(&self) -> &str {
+        self.member_id.as_str()
+    }
+
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn needs(&self) -> &str {
+        self.needs.as_str()
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        from_tags(self.tags.as_str())
+    }
+
+    pub fn status(&self) -> &str {
+        self.status.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct ProgramOffer {
+    pub id: String,
+    pub request_id: String,
+    pub coach_id: String,
+    pub program_id: String,
+    pub message: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A program a coach offers for the request of a member")]
+impl ProgramOffer {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn request_id(&self) -> &str {
+        self.request_id.as_str()
+    }
+
+    pub fn coach_id(&self) -> &str {
+        self.coach_id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    pub fn status(&self) -> &str {
+        self.status.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+pub struct OfferRow {
+    pub offer: ProgramOffer,
+    pub program: Program,
+}
+
+#[juniper::object(description = "An offer with the program on offer")]
+impl OfferRow {
+    pub fn offer(&self) -> &ProgramOffer {
+        &self.offer
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+}
+
+pub struct RequestRow {
+    pub request: ProgramRequest,
+    pub offers: Vec<OfferRow>,
+}
+
+#[juniper::object(description = "A request of the member with the offers it received")]
+impl RequestRow {
+    pub fn request(&self) -> &ProgramRequest {
+        &self.request
+    }
+
+    pub fn offers(&self) -> &Vec<OfferRow> {
+        &self.offers
+    }
+}
+
+fn check_text(errors: &mut Vec<ValidationError>, field: &str, value: &str, limit: usize, message: &str) {
+    let length = value.trim().chars().count();
+
+    if length == 0 || length > limit {
+        errors.push(ValidationError::new(field, message));
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RaiseProgramRequest {
+    pub member_id: UserId,
+    pub title: String,
+    pub needs: String,
+    pub tags: Vec<String>,
+}
+
+impl RaiseProgramRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.member_id.trim().is_empty() {
+            errors.push(ValidationError::new("member_id", "Member id is a must."));
+        }
+
+        check_text(&mut errors, "title", self.title.as_str(), 255, "The title should have 1 to 255 characters.");
+        check_text(&mut errors, "needs", self.needs.as_str(), MAX_TEXT, "The needs should have 1 to 2000 characters.");
+
+        let tags = to_tags(&self.tags);
+        if tags.is_empty() || self.tags.len() > MAX_TAGS || tags.len() > 1024 {
+            errors.push(ValidationError::new("tags", "A request needs 1 to 10 tags, for finding the coaches."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct WithdrawProgramRequest {
+    pub member_id: String,
+    pub id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct MakeOfferRequest {
+    pub coach_id: String,
+    pub request_id: String,
+    pub program_id: String,
+    pub message: String,
+}
+
+impl MakeOfferRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.request_id.trim().is_empty() {
+            errors.push(ValidationError::new("request_id", "Request id is a must."));
+        }
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "Program id is a must."));
+        }
+
+        check_text(&mut errors, "message", self.message.as_str(), MAX_TEXT, "The message should have 1 to 2000 characters.");
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AcceptOfferRequest {
+    pub member_id: String,
+    pub offer_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "program_requests"]
+pub struct NewProgramRequest {
+    pub id: String,
+    pub member_id: String,
+    pub title: String,
+    pub needs: String,
+    pub tags: String,
+}
+
+impl NewProgramRequest {
+    pub fn from(request: &RaiseProgramRequest) -> NewProgramRequest {
+        NewProgramRequest {
+            id: util::fuzzy_id(),
+            member_id: request.member_id.to_string(),
+            title: request.title.trim().to_owned(),
+            needs: request.needs.trim().to_owned(),
+            tags: to_tags(&request.tags),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "program_offers"]
+pub struct NewProgramOffer {
+    pub id: String,
+    pub request_id: String,
+    pub coach_id: String,
+    pub program_id: String,
+    pub message: String,
+}
+
+impl NewProgramOffer {
+    pub fn from(request: &MakeOfferRequest) -> NewProgramOffer {
+        NewProgramOffer {
+            id: util::fuzzy_id(),
+            request_id: request.request_id.to_owned(),
+            coach_id: request.coach_id.to_owned(),
+            program_id: request.program_id.to_owned(),
+            message: request.message.trim().to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(tags: &str) -> ProgramRequest {
+        ProgramRequest {
+            id: String::from("r-1"),
+            member_id: String::from("m-1"),
+            title: String::from("Public speaking"),
+            needs: String::from("I freeze on stage."),
+            tags: tags.to_owned(),
+            status: String::from(OPEN),
+            created_at: util::now(),
+            updated_at: util::now(),
+        }
+    }
+
+    #[test]
+    fn should_match_a_whole_tag() {
+        assert!(request(",speaking,confidence,").shares_tag(",leadership,speaking,"));
+        assert!(!request(",sales,").shares_tag(",salesforce,"));
+        assert!(!request(",sales,").shares_tag(""));
+    }
+
+    #[test]
+    fn should_need_tags() {
+        let raise = RaiseProgramRequest {
+            member_id: UserId::from("m-1"),
+            title: String::from("Public speaking"),
+            needs: String::from("I freeze on stage."),
+            tags: vec![String::from("  ")],
+        };
+
+        let errors = raise.validate();
+        assert_eq!(1, errors.len());
+        assert_eq!("tags", errors[0].field);
+    }
+}
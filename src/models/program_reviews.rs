@@ -0,0 +1,234 @@
+/**
+ * The ratings and the testimonials of a program. A member reviews a program
+ * once per enrollment, after a session of it is completed; the coach may hide
+ * an abusive review, which then counts no more in the rating of the program.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::schema::program_reviews;
+
+pub const VISIBLE: &str = "visible";
+pub const HIDDEN: &str = "hidden";
+
+const MIN_RATING: i32 = 1;
+const MAX_RATING: i32 = 5;
+const MAX_COMMENT: usize = 2000;
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct ProgramReview {
+    pub id: String,
+    pub program_id: String,
+    pub enrollment_id: String,
+    pub member_id: String,
+    pub rating: i32,
+    pub comment: String,
+    pub status: String,
+    pub hidden_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The rating and the testimonial of a member on a program")]
+impl ProgramReview {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn member_id(&self) -> &str {
+        self.member_id.as_str()
+    }
+
+    pub fn rating(&self) -> i32 {
+        self.rating
+    }
+
+    pub fn comment(&self) -> &str {
+        self.comment.as_str()
+    }
+
+    #[graphql(description = "Either visible or hidden by the coach")]
+    pub fn status(&self) -> &str {
+        self.status.as_str()
+    }
+
+    pub fn hidden_reason(&self) -> Option<&str> {
+        self.hidden_reason.as_deref()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewReviewRequest {
+    pub enrollment_id: String,
+    pub member_id: String,
+    pub rating: i32,
+    pub comment: String,
+}
+
+impl NewReviewRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.enrollment_id.trim().is_empty() {
+            errors.push(ValidationError::new("enrollment_id", "The Enrollment id is invalid."));
+        }
+
+        if self.member_id.trim().is_empty() {
+            errors.push(ValidationError::new("member_id", "The Member id is invalid."));
+        }
+
+        if self.rating < MIN_RATING || self.rating > MAX_RATING {
+            errors.push(ValidationError::new("rating", "The rating should be 1 to 5."));
+        }
+
+        let length = self.comment.trim().chars().count();
+        if length == 0 || length > MAX_COMMENT {
+            errors.push(ValidationError::new("comment", "The comment should have 1 to 2000 characters."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLEnum, PartialEq)]
+pub enum ReviewState {
+    VISIBLE,
+    HIDDEN,
+}
+
+impl ReviewState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewState::VISIBLE => VISIBLE,
+            ReviewState::HIDDEN => HIDDEN,
+        }
+    }
+}
+
+/**
+ * A review is hidden with a reason, for the member to know; showing it again clears the reason.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ModerateReviewRequest {
+    pub review_id: String,
+    pub coach_id: String,
+    pub target_state: ReviewState,
+    pub reason: Option<String>,
+}
+
+impl ModerateReviewRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.review_id.trim().is_empty() {
+            errors.push(ValidationError::new("review_id", "The Review id is invalid."));
+        }
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        let length = self.reason.as_ref().map(|value| value.trim().chars().count()).unwrap_or(0);
+        if self.target_state == ReviewState::HIDDEN && (length == 0 || length > 255) {
+            errors.push(ValidationError::new("reason", "The reason to hide should have 1 to 255 characters."));
+        }
+
+        errors
+    }
+
+    pub fn hidden_reason(&self) -> Option<String> {
+        match self.target_state {
+            ReviewState::HIDDEN => self.reason.as_ref().map(|value| value.trim().to_owned()),
+            ReviewState::VISIBLE => None,
+        }
+    }
+}
+
+/**
+ * The coach of the program sees the hidden reviews too; the others the visible ones alone.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ReviewCriteria {
+    pub program_id: ProgramId,
+    pub user_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "program_reviews"]
+pub struct NewReview {
+    pub id: String,
+    pub program_id: String,
+    pub enrollment_id: String,
+    pub member_id: String,
+    pub rating: i32,
+    pub comment: String,
+}
+
+impl NewReview {
+    pub fn from(request: &NewReviewRequest, the_program_id: &str) -> NewReview {
+        NewReview {
+            id: util::fuzzy_id(),
+            program_id: the_program_id.to_owned(),
+            enrollment_id: request.enrollment_id.to_owned(),
+            member_id: request.member_id.to_owned(),
+            rating: request.rating,
+            comment: request.comment.trim().to_owned(),
+        }
+    }
+}
+
+/**
+ * The average of the ratings, to a tenth, and their count; no average without a rating.
+ */
+pub fn summarize(ratings: &[i32]) -> (Option<f64>, i32) {
+    if ratings.is_empty() {
+        return (None, 0);
+    }
+
+    let total: i32 = ratings.iter().sum();
+    let average = total as f64 / ratings.len() as f64;
+
+    (Some((average * 10.0).round() / 10.0), ratings.len() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_average_to_a_tenth() {
+        assert_eq!((None, 0), summarize(&[]));
+        assert_eq!((Some(4.0), 1), summarize(&[4]));
+        assert_eq!((Some(4.3), 3), summarize(&[4, 4, 5]));
+    }
+
+    #[test]
+    fn should_need_a_reason_to_hide() {
+        let mut request = ModerateReviewRequest {
+            review_id: String::from("r-1"),
+            coach_id: String::from("c-1"),
+            target_state: ReviewState::HIDDEN,
+            reason: None,
+        };
+
+        assert_eq!(1, request.validate().len());
+
+        request.reason = Some(String::from(" Abusive language "));
+        assert!(request.validate().is_empty());
+        assert_eq!(Some(String::from("Abusive language")), request.hidden_reason());
+
+        request.target_state = ReviewState::VISIBLE;
+        assert_eq!(None, request.hidden_reason());
+    }
+}
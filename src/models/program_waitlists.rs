@@ -0,0 +1,198 @@
+/**
+ * A program may cap the number of its members with max_members. A member who
+ * comes to a full program joins its waitlist instead, and is enrolled, in the
+ * order of the line, as the seats open: when the coach raises the cap, or
+ * promotes a member by hand.
+ *
+ * The coach of the program is enrolled in it for the conferences; that
+ * enrollment takes no seat.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::models::users::User;
+use crate::schema::program_waitlists;
+
+// A cap is for the small groups; a larger program may as well take any number.
+const MAX_CAP: i32 = 10_000;
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct ProgramWaitlist {
+    pub id: String,
+    pub program_id: String,
+    pub member_id: String,
+    pub created_at: NaiveDateTime,
+    pub promoted_at: Option<NaiveDateTime>,
+}
+
+#[juniper::object(description = "The place of a member in the waitlist of a full program")]
+impl ProgramWaitlist {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn program_id(&self) -> &str {
+        self.program_id.as_str()
+    }
+
+    pub fn member_id(&self) -> &str {
+        self.member_id.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    #[graphql(description = "When the member was enrolled from the waitlist")]
+    pub fn promoted_at(&self) -> Option<NaiveDateTime> {
+        self.promoted_at
+    }
+}
+
+/**
+ * A member waiting for a seat, first in the line first.
+ */
+pub struct WaitingMember {
+    pub position: i32,
+    pub member: User,
+    pub joined_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A member waiting for a seat in a program")]
+impl WaitingMember {
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    pub fn member(&self) -> &User {
+        &self.member
+    }
+
+    pub fn joined_at(&self) -> NaiveDateTime {
+        self.joined_at
+    }
+}
+
+/**
+ * The member who waits is the signed in user, never an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct WaitlistRequest {
+    pub program_id: ProgramId,
+}
+
+impl WaitlistRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * Without a member, the first in the line is promoted.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct PromoteRequest {
+    pub program_id: ProgramId,
+    pub coach_id: String,
+    pub member_id: Option<String>,
+}
+
+impl PromoteRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * Without max_members the program takes any number of members.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct CapacityRequest {
+    pub program_id: ProgramId,
+    pub coach_id: String,
+    pub max_members: Option<i32>,
+}
+
+impl CapacityRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if let Some(cap) = self.max_members {
+            if !(1..=MAX_CAP).contains(&cap) {
+                errors.push(ValidationError::new("max_members", "A program may take 1 to 10000 members."));
+            }
+        }
+
+        errors
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "program_waitlists"]
+pub struct NewWaitlistEntry {
+    pub id: String,
+    pub program_id: String,
+    pub member_id: String,
+}
+
+impl NewWaitlistEntry {
+    pub fn from(request: &WaitlistRequest, member_id: &str) -> NewWaitlistEntry {
+        NewWaitlistEntry {
+            id: util::fuzzy_id(),
+            program_id: request.program_id.to_string(),
+            member_id: member_id.to_owned(),
+        }
+    }
+}
+
+/**
+ * The seats left in a program of the cap with so many members; none without a cap.
+ */
+pub fn open_seats(max_members: Option<i32>, members: i64) -> Option<i64> {
+    max_members.map(|cap| (cap as i64 - members).max(0))
+}
+
+pub fn has_seat(max_members: Option<i32>, members: i64) -> bool {
+    open_seats(max_members, members).map(|seats| seats > 0).unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_count_the_open_seats() {
+        assert!(has_seat(None, 500));
+        assert!(has_seat(Some(3), 2));
+        assert!(!has_seat(Some(3), 3));
+        assert_eq!(Some(0), open_seats(Some(3), 4));
+        assert_eq!(Some(2), open_seats(Some(5), 3));
+    }
+}
@@ -5,27 +5,45 @@
 use chrono::NaiveDateTime;
 
 use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::plain_text;
 use crate::commons::util;
+use crate::models::coach_profiles::{from_tags, to_tags};
 use crate::models::coaches::Coach;
 use crate::schema::programs;
 
+const MAX_TAGS: usize = 20;
+
+const DRAFT: &str = "draft";
+const SUBMITTED: &str = "submitted";
+const PUBLISHED: &str = "published";
+const RETIRED: &str = "retired";
+
+const MAX_PRICE_CENTS: i32 = 100_000_000;
+
 /**
  * The structure represents One row of the programs table.
  */
-#[derive(Queryable, Debug, Identifiable, Associations)]
+#[derive(Queryable, Debug, Clone, Identifiable, Associations)]
 pub struct Program {
-    pub id: String,
+    pub id: ProgramId,
     pub name: String,
     pub description: Option<String>,
     pub active: bool,
     pub coach_name: String,
-    pub coach_id: String,
+    pub coach_id: UserId,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub is_private: bool,
     pub genre_id: Option<String>,
     pub is_parent: bool,
-    pub parent_program_id: Option<String>,
+    pub parent_program_id: Option<ProgramId>,
+    pub description_text: Option<String>,
+    pub max_members: Option<i32>,
+    pub tags: String,
+    pub status: String,
+    pub price_cents: Option<i32>,
+    pub currency: Option<String>,
 }
 
 /**
@@ -53,6 +71,14 @@ impl Program {
         }
     }
 
+    #[graphql(description = "The description without its markup, for the mails, the screen readers and the SMS")]
+    pub fn plain_description(&self) -> String {
+        match &self.description_text {
+            Some(value) => value.to_owned(),
+            None => plain_text::plain_text(self.description.as_deref().unwrap_or_default()),
+        }
+    }
+
     pub fn coach_name(&self) -> &str {
         self.coach_name.as_str()
     }
@@ -69,23 +95,100 @@ impl Program {
         &self.genre_id
     }
 
-    pub fn parent_program_id(&self) -> &str {
+    pub fn parent_program_id(&self) -> &ProgramId {
        self.coalesce_parent_id()
     }
 
     pub fn is_parent(&self) -> bool {
         self.is_parent
     }
+
+    #[graphql(description = "The most members the program takes; none when it takes any number")]
+    pub fn max_members(&self) -> Option<i32> {
+        self.max_members
+    }
+
+    #[graphql(description = "The tags to discover the program by, in lowercase")]
+    pub fn tags(&self) -> Vec<String> {
+        from_tags(self.tags.as_str())
+    }
+
+    #[graphql(description = "Where the program is in its review; only a published one is in the catalog")]
+    pub fn status(&self) -> ProgramStatus {
+        ProgramStatus::from_str(self.status.as_str())
+    }
+
+    #[graphql(description = "The price in the cents of the currency; none for a free program")]
+    pub fn price_cents(&self) -> Option<i32> {
+        self.price_cents
+    }
+
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
 }
 
 impl Program {
 
-    pub fn coalesce_parent_id(&self) -> &str {
+    pub fn coalesce_parent_id(&self) -> &ProgramId {
         match &self.parent_program_id {
             None => &self.id,
             Some(value) => &value
         }
     }
+
+    pub fn is_published(&self) -> bool {
+        ProgramStatus::from_str(self.status.as_str()) == ProgramStatus::PUBLISHED
+    }
+
+    pub fn is_paid(&self) -> bool {
+        self.price_cents.unwrap_or(0) > 0
+    }
+}
+
+/**
+ * A program is drafted by its coach and submitted for a review; an admin
+ * publishes it to the catalog. The coach retires a published program, which
+ * leaves the catalog for good.
+ */
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum ProgramStatus {
+    DRAFT,
+    SUBMITTED,
+    PUBLISHED,
+    RETIRED,
+}
+
+impl ProgramStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProgramStatus::DRAFT => DRAFT,
+            ProgramStatus::SUBMITTED => SUBMITTED,
+            ProgramStatus::PUBLISHED => PUBLISHED,
+            ProgramStatus::RETIRED => RETIRED,
+        }
+    }
+
+    pub fn from_str(value: &str) -> ProgramStatus {
+        match value {
+            SUBMITTED => ProgramStatus::SUBMITTED,
+            PUBLISHED => ProgramStatus::PUBLISHED,
+            RETIRED => ProgramStatus::RETIRED,
+            _ => ProgramStatus::DRAFT,
+        }
+    }
+
+    /**
+     * The status the program is in before moving to this one.
+     */
+    pub fn preceding(&self) -> Option<ProgramStatus> {
+        match self {
+            ProgramStatus::DRAFT => None,
+            ProgramStatus::SUBMITTED => Some(ProgramStatus::DRAFT),
+            ProgramStatus::PUBLISHED => Some(ProgramStatus::SUBMITTED),
+            ProgramStatus::RETIRED => Some(ProgramStatus::PUBLISHED),
+        }
+    }
 }
 
 /**
@@ -132,7 +235,7 @@ impl NewProgramRequest {
 #[derive(Insertable)]
 #[table_name = "programs"]
 pub struct NewProgram {
-    pub id: String,
+    pub id: ProgramId,
     pub name: String,
     pub description: String,
     pub active: bool,
@@ -140,8 +243,10 @@ pub struct NewProgram {
     pub coach_name: String,
     pub coach_id: String,
     pub is_parent: bool,
-    pub parent_program_id: String,
+    pub parent_program_id: ProgramId,
     pub genre_id: Option<String>,
+    pub description_text: String,
+    pub status: String,
 }
 
 /**
@@ -153,7 +258,7 @@ impl NewProgram {
      * The parent program will have the same id as base_program_id
      */
     pub fn from_request(request: &NewProgramRequest, coach: &Coach) -> NewProgram {
-        let fuzzy_id = util::fuzzy_id();
+        let fuzzy_id = ProgramId::from(util::fuzzy_id());
 
         NewProgram {
             id: fuzzy_id.to_owned(),
@@ -161,11 +266,13 @@ impl NewProgram {
             is_parent: true,
             name: request.name.to_owned(),
             description: request.description.to_owned(),
+            description_text: plain_text::plain_text(request.description.as_str()),
             is_private: request.is_private,
             active: false,
             coach_name: coach.full_name.to_owned(),
             coach_id: coach.id.to_owned(),
             genre_id: request.genre_id.to_owned(),
+            status: ProgramStatus::DRAFT.as_str().to_owned(),
         }
     }
 
@@ -173,23 +280,67 @@ impl NewProgram {
      * We spawn a program while attaching another coach to the parent program
      */
     pub fn from_parent_program(parent_program: &Program, coach: &Coach) -> NewProgram {
-        let fuzzy_id = util::fuzzy_id();
-
         NewProgram {
-            id: fuzzy_id,
+            id: ProgramId::from(util::fuzzy_id()),
             parent_program_id: parent_program.id.to_owned(),
             is_parent: false,
             name: parent_program.name.to_owned(),
             description: String::from("-"),
+            description_text: String::from("-"),
             is_private: true,
             active: parent_program.active,
             coach_name: coach.full_name.to_owned(),
             coach_id: coach.id.to_owned(),
             genre_id: parent_program.genre_id.to_owned(),
+            status: parent_program.status.to_owned(),
         }
     }
 }
 
+/**
+ * The tags given replace the tags of the program; none clears them.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProgramTagsRequest {
+    pub program_id: ProgramId,
+    pub coach_id: String,
+    pub tags: Vec<String>,
+}
+
+impl ProgramTagsRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.tags.len() > MAX_TAGS || self.as_tags().len() > 1024 {
+            errors.push(ValidationError::new("tags", "A program may have at most 20 tags."));
+        }
+
+        errors
+    }
+
+    pub fn as_tags(&self) -> String {
+        to_tags(&self.tags)
+    }
+}
+
+/**
+ * The LIKE pattern finding the text anywhere in a value; the wildcards of the
+ * text are taken literally.
+ */
+pub fn text_pattern(text: &str) -> String {
+    let escaped = text.trim().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
+
 #[derive(juniper::GraphQLEnum, PartialEq)]
 pub enum ProgramTargetState {
     ACTIVATE,
@@ -198,15 +349,89 @@ pub enum ProgramTargetState {
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct ChangeProgramStateRequest {
-    pub id: String,
+    pub id: ProgramId,
     pub target_state: ProgramTargetState,
 }
 
 
+/**
+ * The coach of a parent program submits it for a review, or retires it.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProgramReviewRequest {
+    pub program_id: ProgramId,
+    pub coach_id: String,
+}
+
+impl ProgramReviewRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The price is kept on the parent program and its peer programs alike; a
+ * request without a price makes the program free.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProgramPriceRequest {
+    pub program_id: ProgramId,
+    pub coach_id: String,
+    pub price_cents: Option<i32>,
+    pub currency: Option<String>,
+}
+
+impl ProgramPriceRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.program_id.trim().is_empty() {
+            errors.push(ValidationError::new("program_id", "The Program id is invalid."));
+        }
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if let Some(price) = self.price_cents {
+            if price <= 0 || price > MAX_PRICE_CENTS {
+                errors.push(ValidationError::new("price_cents", "The price should be more than 0 and at most 1000000."));
+            }
+
+            if self.currency_code().is_none() {
+                errors.push(ValidationError::new("currency", "The currency should be a three letter code, like USD."));
+            }
+        }
+
+        errors
+    }
+
+    /**
+     * The ISO code of the currency in capitals; none when it is not one.
+     */
+    pub fn currency_code(&self) -> Option<String> {
+        self.currency
+            .as_deref()
+            .map(str::trim)
+            .filter(|code| code.len() == 3 && code.chars().all(|letter| letter.is_ascii_alphabetic()))
+            .map(str::to_ascii_uppercase)
+    }
+}
+
 #[derive(juniper::GraphQLInputObject)]
 pub struct AssociateCoachRequest {
     pub peer_coach_email: String,
-    pub program_id: String,
+    pub program_id: ProgramId,
     pub admin_coach_id: String,
 }
 
@@ -225,3 +450,60 @@ impl ProgramCoach {
         &self.coach
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_take_the_text_literally() {
+        assert_eq!("%public speaking%", text_pattern(" public speaking "));
+        assert_eq!("%100\\%\\_x%", text_pattern("100%_x"));
+    }
+
+    #[test]
+    fn should_limit_the_tags() {
+        let mut request = ProgramTagsRequest {
+            program_id: ProgramId::from("p-1"),
+            coach_id: String::from("c-1"),
+            tags: vec![String::from(" Leadership"), String::from("sales")],
+        };
+
+        assert!(request.validate().is_empty());
+        assert_eq!(",leadership,sales,", request.as_tags());
+
+        request.tags = (0..=MAX_TAGS).map(|index| format!("tag-{}", index)).collect();
+        assert_eq!(1, request.validate().len());
+    }
+
+    #[test]
+    fn should_move_the_program_one_status_at_a_time() {
+        assert_eq!(Some(ProgramStatus::DRAFT), ProgramStatus::SUBMITTED.preceding());
+        assert_eq!(Some(ProgramStatus::SUBMITTED), ProgramStatus::PUBLISHED.preceding());
+        assert_eq!(Some(ProgramStatus::PUBLISHED), ProgramStatus::RETIRED.preceding());
+        assert_eq!(None, ProgramStatus::DRAFT.preceding());
+
+        assert_eq!(ProgramStatus::RETIRED, ProgramStatus::from_str(ProgramStatus::RETIRED.as_str()));
+        assert_eq!(ProgramStatus::DRAFT, ProgramStatus::from_str("unknown"));
+    }
+
+    #[test]
+    fn should_price_the_program_in_a_currency() {
+        let mut request = ProgramPriceRequest {
+            program_id: ProgramId::from("p-1"),
+            coach_id: String::from("c-1"),
+            price_cents: Some(4900),
+            currency: Some(String::from(" eur ")),
+        };
+
+        assert!(request.validate().is_empty());
+        assert_eq!(Some(String::from("EUR")), request.currency_code());
+
+        request.currency = Some(String::from("euro"));
+        assert_eq!(1, request.validate().len());
+
+        request.price_cents = None;
+        assert!(request.validate().is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+/**
+ * The counts the dashboard of an enrollment shows at a glance: its tasks by
+ * status, its objectives done against those planned, its sessions held and its
+ * observations. They come together in one query, see services::progress.
+ *
+ * The status of a task is the one Task::status gives, worked out by the database.
+ */
+use diesel::sql_types::BigInt;
+
+#[derive(QueryableByName, Debug)]
+pub struct ProgressCounts {
+    #[sql_type = "BigInt"]
+    pub tasks_planned: i64,
+    #[sql_type = "BigInt"]
+    pub tasks_due: i64,
+    #[sql_type = "BigInt"]
+    pub tasks_in_progress: i64,
+    #[sql_type = "BigInt"]
+    pub tasks_delayed: i64,
+    #[sql_type = "BigInt"]
+    pub tasks_responded: i64,
+    #[sql_type = "BigInt"]
+    pub tasks_done: i64,
+    #[sql_type = "BigInt"]
+    pub tasks_cancelled: i64,
+    #[sql_type = "BigInt"]
+    pub objectives_planned: i64,
+    #[sql_type = "BigInt"]
+    pub objectives_done: i64,
+    #[sql_type = "BigInt"]
+    pub sessions_completed: i64,
+    #[sql_type = "BigInt"]
+    pub observations: i64,
+}
+
+#[juniper::object(description = "The counts of the tasks, objectives, sessions and observations of an enrollment")]
+impl ProgressCounts {
+    pub fn tasks_planned(&self) -> i32 {
+        self.tasks_planned as i32
+    }
+
+    pub fn tasks_due(&self) -> i32 {
+        self.tasks_due as i32
+    }
+
+    pub fn tasks_in_progress(&self) -> i32 {
+        self.tasks_in_progress as i32
+    }
+
+    pub fn tasks_delayed(&self) -> i32 {
+        self.tasks_delayed as i32
+    }
+
+    pub fn tasks_responded(&self) -> i32 {
+        self.tasks_responded as i32
+    }
+
+    pub fn tasks_done(&self) -> i32 {
+        self.tasks_done as i32
+    }
+
+    pub fn tasks_cancelled(&self) -> i32 {
+        self.tasks_cancelled as i32
+    }
+
+    #[graphql(description = "All the objectives of the enrollment, the done ones included")]
+    pub fn objectives_planned(&self) -> i32 {
+        self.objectives_planned as i32
+    }
+
+    pub fn objectives_done(&self) -> i32 {
+        self.objectives_done as i32
+    }
+
+    #[graphql(description = "The sessions held to their end, the cancelled ones left out")]
+    pub fn sessions_completed(&self) -> i32 {
+        self.sessions_completed as i32
+    }
+
+    pub fn observations(&self) -> i32 {
+        self.observations as i32
+    }
+}
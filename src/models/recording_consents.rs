@@ -0,0 +1,305 @@
+/**
+ * A conference is recorded only with the consent of each of its participants,
+ * given or declined on the session of the participant. The coach hosting the
+ * conference is told of a declined consent, and a recording is attached to the
+ * conference only when every participant has consented.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::session_users::SessionUser;
+use crate::models::users::User;
+use crate::schema::conference_recordings;
+use crate::schema::recording_consents;
+
+pub const GRANTED: &str = "granted";
+pub const DECLINED: &str = "declined";
+pub const PENDING: &str = "pending";
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum ConsentDecision {
+    GRANT,
+    DECLINE,
+}
+
+impl ConsentDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConsentDecision::GRANT => GRANTED,
+            ConsentDecision::DECLINE => DECLINED,
+        }
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct RecordingConsent {
+    pub session_user_id: String,
+    pub conference_id: String,
+    pub user_id: String,
+    pub decision: String,
+    pub decided_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The decision of a participant on the recording of a conference")]
+impl RecordingConsent {
+    pub fn session_user_id(&self) -> &str {
+        self.session_user_id.as_str()
+    }
+
+    pub fn conference_id(&self) -> &str {
+        self.conference_id.as_str()
+    }
+
+    pub fn user_id(&self) -> &str {
+        self.user_id.as_str()
+    }
+
+    pub fn decision(&self) -> &str {
+        self.decision.as_str()
+    }
+
+    pub fn decided_at(&self) -> NaiveDateTime {
+        self.decided_at
+    }
+}
+
+/**
+ * A participant of the conference, with the decision taken so far.
+ */
+pub struct ConsentRow {
+    pub session_user: SessionUser,
+    pub user: User,
+    pub consent: Option<RecordingConsent>,
+}
+
+impl ConsentRow {
+    pub fn state(&self) -> &str {
+        self.consent.as_ref().map(|consent| consent.decision.as_str()).unwrap_or(PENDING)
+    }
+}
+
+#[juniper::object(description = "A participant of the conference and the recording consent")]
+impl ConsentRow {
+    pub fn session_user_id(&self) -> &str {
+        self.session_user.id.as_str()
+    }
+
+    pub fn user_id(&self) -> &str {
+        self.user.id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.user.full_name.as_str()
+    }
+
+    pub fn user_type(&self) -> &str {
+        self.session_user.user_type.as_str()
+    }
+
+    #[graphql(description = "One of granted, declined or pending")]
+    pub fn status(&self) -> &str {
+        self.state()
+    }
+
+    pub fn decided_at(&self) -> Option<NaiveDateTime> {
+        self.consent.as_ref().map(|consent| consent.decided_at)
+    }
+
+    pub fn is_declined(&self) -> bool {
+        self.state() == DECLINED
+    }
+}
+
+pub struct ConsentSheet {
+    pub rows: Vec<ConsentRow>,
+}
+
+impl ConsentSheet {
+    pub fn missing_count(&self) -> usize {
+        self.rows.iter().filter(|row| row.state() != GRANTED).count()
+    }
+}
+
+#[juniper::object(description = "The recording consents of the participants of a conference")]
+impl ConsentSheet {
+    pub fn participants(&self) -> &Vec<ConsentRow> {
+        &self.rows
+    }
+
+    #[graphql(description = "The participants yet to consent, including the ones who declined")]
+    pub fn missing(&self) -> i32 {
+        self.missing_count() as i32
+    }
+
+    pub fn can_record(&self) -> bool {
+        !self.rows.is_empty() && self.missing_count() == 0
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ConsentRequest {
+    pub session_user_id: String,
+    pub decision: ConsentDecision,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ConsentCriteria {
+    pub coach_id: UserId,
+    pub conference_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "recording_consents"]
+pub struct NewRecordingConsent {
+    pub session_user_id: String,
+    pub conference_id: String,
+    pub user_id: String,
+    pub decision: String,
+    pub decided_at: NaiveDateTime,
+}
+
+impl NewRecordingConsent {
+    pub fn from(session_user: &SessionUser, conference_id: &str, decision: ConsentDecision) -> NewRecordingConsent {
+        NewRecordingConsent {
+            session_user_id: session_user.id.to_owned(),
+            conference_id: conference_id.to_owned(),
+            user_id: session_user.user_id.to_owned(),
+            decision: decision.as_str().to_owned(),
+            decided_at: util::now(),
+        }
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct ConferenceRecording {
+    pub id: String,
+    pub conference_id: String,
+    pub attached_by_id: String,
+    pub location: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A recording of a conference, attached once every participant consented")]
+impl ConferenceRecording {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn conference_id(&self) -> &str {
+        self.conference_id.as_str()
+    }
+
+    pub fn attached_by_id(&self) -> &str {
+        self.attached_by_id.as_str()
+    }
+
+    pub fn location(&self) -> &str {
+        self.location.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AttachRecordingRequest {
+    pub coach_id: UserId,
+    pub conference_id: String,
+    pub location: String,
+}
+
+impl AttachRecordingRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.conference_id.trim().is_empty() {
+            errors.push(ValidationError::new("conference_id", "Conference id is a must."));
+        }
+
+        let length = self.location.trim().chars().count();
+        if length == 0 || length > 1024 {
+            errors.push(ValidationError::new("location", "The location of the recording should have 1 to 1024 characters."));
+        }
+
+        errors
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "conference_recordings"]
+pub struct NewConferenceRecording {
+    pub id: String,
+    pub conference_id: String,
+    pub attached_by_id: String,
+    pub location: String,
+}
+
+impl NewConferenceRecording {
+    pub fn from(request: &AttachRecordingRequest) -> NewConferenceRecording {
+        NewConferenceRecording {
+            id: util::fuzzy_id(),
+            conference_id: request.conference_id.to_owned(),
+            attached_by_id: request.coach_id.to_string(),
+            location: request.location.trim().to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::commons::ids::UserId;
+
+    fn row(decision: Option<&str>) -> ConsentRow {
+        let user = User {
+            id: UserId::from("u-1"),
+            full_name: String::from("Asha"),
+            email: String::from("asha@example.com"),
+            blocked: false,
+            user_type: String::from(util::MEMBER),
+            created_at: util::now(),
+            updated_at: util::now(),
+            password: String::from(""),
+            utc_offset: 0,
+            email_invalid: false,
+        };
+
+        let session_user = SessionUser {
+            id: String::from("su-1"),
+            session_id: String::from("s-1"),
+            user_id: user.id.to_string(),
+            user_type: String::from(util::MEMBER),
+        };
+
+        let consent = decision.map(|value| RecordingConsent {
+            session_user_id: session_user.id.to_owned(),
+            conference_id: String::from("c-1"),
+            user_id: user.id.to_string(),
+            decision: value.to_owned(),
+            decided_at: util::now(),
+        });
+
+        ConsentRow { session_user, user, consent }
+    }
+
+    #[test]
+    fn should_count_the_missing_consents() {
+        let sheet = ConsentSheet {
+            rows: vec![row(Some(GRANTED)), row(Some(DECLINED)), row(None)],
+        };
+
+        assert_eq!(2, sheet.missing_count());
+        assert_eq!(PENDING, sheet.rows[2].state());
+
+        let sheet = ConsentSheet { rows: vec![row(Some(GRANTED))] };
+        assert_eq!(0, sheet.missing_count());
+    }
+}
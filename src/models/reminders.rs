@@ -0,0 +1,92 @@
+/**
+ * The reminders the "session-reminders" job sends to the people of the sessions
+ * about to start: a notification and a mail to each, once.
+ *
+ * REMINDER_LEAD_MINUTES   how long ahead of its start a session is reminded, 30 by default
+ *
+ * A person is reminded once per event, the conference for its sessions or else
+ * the session, so that the coach of a conference of many members hears of it
+ * once. A session rescheduled after its reminder is reminded again for its new
+ * start.
+ */
+use chrono::{Duration, NaiveDateTime};
+use std::env;
+
+use crate::schema::session_reminders;
+
+const LEAD_MINUTES: i64 = 30;
+
+// Longer than a day ahead is a notice of its own, not a reminder.
+const MAX_LEAD_MINUTES: i64 = 24 * 60;
+
+#[derive(Debug, PartialEq)]
+pub struct ReminderWindow {
+    pub lead_minutes: i64,
+}
+
+impl ReminderWindow {
+    pub fn from_env() -> ReminderWindow {
+        ReminderWindow::parse(env::var("REMINDER_LEAD_MINUTES").ok())
+    }
+
+    // A lead that is not a number of minutes up to a day falls back to the default.
+    fn parse(lead_minutes: Option<String>) -> ReminderWindow {
+        let lead_minutes = lead_minutes
+            .and_then(|value| value.trim().parse::<i64>().ok())
+            .filter(|minutes| *minutes > 0 && *minutes <= MAX_LEAD_MINUTES)
+            .unwrap_or(LEAD_MINUTES);
+
+        ReminderWindow { lead_minutes }
+    }
+
+    /**
+     * The sessions starting before this, and after now, are reminded.
+     */
+    pub fn horizon(&self, now: NaiveDateTime) -> NaiveDateTime {
+        now + Duration::minutes(self.lead_minutes)
+    }
+}
+
+pub fn reminder_subject(session_name: &str, starts_at: NaiveDateTime, now: NaiveDateTime) -> String {
+    let minutes = (starts_at - now).num_minutes().max(1);
+
+    match minutes {
+        1 => format!("The session {} starts in a minute", session_name),
+        _ => format!("The session {} starts in {} minutes", session_name, minutes),
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "session_reminders"]
+pub struct NewSessionReminder {
+    pub event_id: String,
+    pub user_id: String,
+    pub starts_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    #[test]
+    fn should_fall_back_to_the_default_lead() {
+        assert_eq!(LEAD_MINUTES, ReminderWindow::parse(None).lead_minutes);
+        assert_eq!(LEAD_MINUTES, ReminderWindow::parse(Some(String::from("0"))).lead_minutes);
+        assert_eq!(LEAD_MINUTES, ReminderWindow::parse(Some(String::from("2000"))).lead_minutes);
+        assert_eq!(15, ReminderWindow::parse(Some(String::from(" 15 "))).lead_minutes);
+    }
+
+    #[test]
+    fn should_tell_the_minutes_to_the_start() {
+        let now = at("2021-03-04T10:00");
+
+        assert_eq!(at("2021-03-04T10:30"), ReminderWindow { lead_minutes: 30 }.horizon(now));
+        assert_eq!("The session Review starts in 25 minutes", reminder_subject("Review", at("2021-03-04T10:25"), now));
+        assert_eq!("The session Review starts in a minute", reminder_subject("Review", now, now));
+    }
+}
@@ -0,0 +1,225 @@
+/**
+ * A saved filter lets a coach name a criteria, for the members list or
+ * for the tasks list, and apply it later without building it again.
+ *
+ * The criteria is kept as JSON text, for example
+ *
+ * members: {"program_id": "...", "desire": "NEW"}
+ * tasks:   {"enrollment_id": "..."}
+ */
+use chrono::NaiveDateTime;
+use serde_json::Value;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::util;
+use crate::models::coach_members::CoachCriteria;
+use crate::models::enrollments::{EnrollmentFilter, PlanCriteria};
+use crate::schema::saved_filters;
+
+const MEMBERS: &str = "members";
+const TASKS: &str = "tasks";
+
+const UNREADABLE_CRITERIA: &str = "The criteria of the saved filter is not readable.";
+const WRONG_TARGET: &str = "The saved filter is not meant for this list.";
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct SavedFilter {
+    pub id: String,
+    pub coach_id: String,
+    pub name: String,
+    pub target: String,
+    pub criteria: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy)]
+pub enum FilterTarget {
+    MEMBERS,
+    TASKS,
+}
+
+impl FilterTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterTarget::MEMBERS => MEMBERS,
+            FilterTarget::TASKS => TASKS,
+        }
+    }
+}
+
+#[juniper::object(description = "A named criteria a coach applies to the members or tasks list")]
+impl SavedFilter {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn target(&self) -> &str {
+        self.target.as_str()
+    }
+
+    pub fn criteria(&self) -> &str {
+        self.criteria.as_str()
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+impl SavedFilter {
+    // The coach of the filter is the owner of the list; it is never taken from the criteria.
+    pub fn as_coach_criteria(&self) -> Result<CoachCriteria, &'static str> {
+        let criteria = self.parse(MEMBERS)?;
+
+        let desire = match criteria["desire"].as_str() {
+            Some("NEW") => EnrollmentFilter::NEW,
+            _ => EnrollmentFilter::ALL,
+        };
+
+        Ok(CoachCriteria {
+            coach_id: self.coach_id.to_owned(),
+            program_id: criteria["program_id"].as_str().map(String::from),
+            desire,
+        })
+    }
+
+    pub fn as_plan_criteria(&self) -> Result<PlanCriteria, &'static str> {
+        let criteria = self.parse(TASKS)?;
+
+        match criteria["enrollment_id"].as_str() {
+            Some(value) => Ok(PlanCriteria {
+                enrollment_id: value.to_owned(),
+            }),
+            None => Err(UNREADABLE_CRITERIA),
+        }
+    }
+
+    pub fn check_criteria(&self, given_criteria: &str) -> Result<(), &'static str> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        validate_criteria(self.target_kind(), given_criteria, &mut errors);
+
+        if !errors.is_empty() {
+            return Err(UNREADABLE_CRITERIA);
+        }
+
+        Ok(())
+    }
+
+    fn target_kind(&self) -> FilterTarget {
+        if self.target == TASKS {
+            return FilterTarget::TASKS;
+        }
+
+        FilterTarget::MEMBERS
+    }
+
+    fn parse(&self, expected_target: &str) -> Result<Value, &'static str> {
+        if self.target != expected_target {
+            return Err(WRONG_TARGET);
+        }
+
+        serde_json::from_str(self.criteria.as_str()).map_err(|_| UNREADABLE_CRITERIA)
+    }
+}
+
+fn validate_criteria(target: FilterTarget, criteria: &str, errors: &mut Vec<ValidationError>) {
+    let value: Result<Value, _> = serde_json::from_str(criteria);
+
+    let value = match value {
+        Ok(value) if value.is_object() => value,
+        _ => {
+            errors.push(ValidationError::new("criteria", "The criteria should be a JSON object."));
+            return;
+        }
+    };
+
+    if target == FilterTarget::TASKS && !value["enrollment_id"].is_string() {
+        errors.push(ValidationError::new("criteria", "The enrollment_id is a must for filtering the tasks."));
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct NewSavedFilterRequest {
+    pub coach_id: String,
+    pub name: String,
+    pub target: FilterTarget,
+    pub criteria: String,
+}
+
+impl NewSavedFilterRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.name.trim().is_empty() {
+            errors.push(ValidationError::new("name", "Name of the filter is a must."));
+        }
+
+        validate_criteria(self.target, self.criteria.as_str(), &mut errors);
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateSavedFilterRequest {
+    pub id: String,
+    pub coach_id: String,
+    pub name: String,
+    pub criteria: String,
+}
+
+impl UpdateSavedFilterRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push(ValidationError::new("name", "Name of the filter is a must."));
+        }
+
+        if serde_json::from_str::<Value>(self.criteria.as_str()).is_err() {
+            errors.push(ValidationError::new("criteria", "The criteria should be a JSON object."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct SavedFilterCriteria {
+    pub id: String,
+    pub coach_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "saved_filters"]
+pub struct NewSavedFilter {
+    pub id: String,
+    pub coach_id: String,
+    pub name: String,
+    pub target: String,
+    pub criteria: String,
+}
+
+impl NewSavedFilter {
+    pub fn from(request: &NewSavedFilterRequest) -> NewSavedFilter {
+        let fuzzy_id = util::fuzzy_id();
+
+        NewSavedFilter {
+            id: fuzzy_id,
+            coach_id: request.coach_id.to_owned(),
+            name: request.name.trim().to_owned(),
+            target: request.target.as_str().to_owned(),
+            criteria: request.criteria.to_owned(),
+        }
+    }
+}
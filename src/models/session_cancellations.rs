@@ -0,0 +1,161 @@
+/**
+ * The cancellation of all the sessions of a coach in a range of dates, as on an
+ * emergency leave. The range is of whole days in UTC, both the days included.
+ *
+ * Only the sessions yet to start are cancelled; the ones in progress or closed
+ * are left as they are. The coach may preview the sessions with dry_run.
+ *
+ * The coach is the signed in user, never an id in the request.
+ */
+use chrono::{Duration, NaiveDateTime};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::util;
+use crate::models::sessions::Session;
+
+// A longer absence is better handled by closing the programs.
+pub const MAX_RANGE_DAYS: i64 = 90;
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct BulkCancelRequest {
+    pub from_date: String,
+    pub to_date: String,
+    pub reason: String,
+    pub dry_run: bool,
+}
+
+impl BulkCancelRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.reason.trim().is_empty() {
+            errors.push(ValidationError::new("reason", "Please tell the members the reason for the cancellation."));
+        }
+
+        match self.range() {
+            Err(e) => errors.push(ValidationError::new("from_date", e)),
+            Ok((from, to)) if to < from => errors.push(ValidationError::new("to_date", "The range should end on or after its start.")),
+            Ok((from, to)) if to - from > Duration::days(MAX_RANGE_DAYS) => {
+                errors.push(ValidationError::new("to_date", "The range should be of at most 90 days."))
+            }
+            Ok(_) => {}
+        }
+
+        errors
+    }
+
+    pub fn range(&self) -> Result<(NaiveDateTime, NaiveDateTime), &'static str> {
+        let from = util::as_start_date(self.from_date.trim()).map_err(|_| "The dates should be like 2021-02-21.")?;
+        let to = util::as_end_date(self.to_date.trim()).map_err(|_| "The dates should be like 2021-02-21.")?;
+
+        Ok((from, to))
+    }
+}
+
+/**
+ * The sessions in the range that are yet to start, as of now; a rescheduled
+ * session goes by its revised date.
+ */
+pub fn cancellable(candidates: Vec<Session>, from: NaiveDateTime, to: NaiveDateTime, now: NaiveDateTime) -> Vec<Session> {
+    candidates
+        .into_iter()
+        .filter(|session| session.cancelled_at.is_none() && session.actual_start_date.is_none() && session.actual_end_date.is_none())
+        .filter(|session| {
+            let start = session.revised_start_date.unwrap_or(session.original_start_date);
+            start >= from && start <= to && start > now
+        })
+        .collect()
+}
+
+/**
+ * The sessions cancelled, or to be cancelled when applied is false.
+ */
+pub struct CancellationPreview {
+    pub sessions: Vec<Session>,
+    pub notified: i32,
+    pub applied: bool,
+}
+
+#[juniper::object(description = "The sessions of a bulk cancellation")]
+impl CancellationPreview {
+    pub fn sessions(&self) -> &Vec<Session> {
+        &self.sessions
+    }
+
+    #[graphql(description = "The members told of the cancellation")]
+    pub fn notified(&self) -> i32 {
+        self.notified
+    }
+
+    pub fn applied(&self) -> bool {
+        self.applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::commons::ids::{EnrollmentId, ProgramId, SessionId};
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn session(id: &str, start: &str) -> Session {
+        Session {
+            id: SessionId::from(id),
+            name: String::from("Review"),
+            description: None,
+            program_id: ProgramId::from("p-1"),
+            enrollment_id: EnrollmentId::from("e-1"),
+            people: None,
+            duration: 60,
+            original_start_date: at(start),
+            original_end_date: at(start) + Duration::hours(1),
+            revised_start_date: None,
+            revised_end_date: None,
+            offered_start_date: None,
+            offered_end_date: None,
+            is_ready: false,
+            actual_start_date: None,
+            actual_end_date: None,
+            cancelled_at: None,
+            created_at: at("2021-02-01T00:00"),
+            updated_at: at("2021-02-01T00:00"),
+            closing_notes: None,
+            is_request: false,
+            conference_id: None,
+            session_type: String::from("mono"),
+            expired_at: None,
+            parent_conference_id: None,
+        }
+    }
+
+    #[test]
+    fn should_pick_the_sessions_yet_to_start_in_the_range() {
+        let mut started = session("started", "2021-02-22T10:00");
+        started.actual_start_date = Some(at("2021-02-22T10:00"));
+
+        let mut moved_in = session("moved-in", "2021-03-10T10:00");
+        moved_in.revised_start_date = Some(at("2021-02-23T10:00"));
+
+        let mut moved_out = session("moved-out", "2021-02-23T10:00");
+        moved_out.revised_start_date = Some(at("2021-03-10T10:00"));
+
+        let candidates = vec![
+            session("past", "2021-02-21T08:00"),
+            session("due", "2021-02-21T12:00"),
+            session("last-day", "2021-02-25T23:00"),
+            session("after", "2021-02-26T00:30"),
+            started,
+            moved_in,
+            moved_out,
+        ];
+
+        let picked = cancellable(candidates, at("2021-02-21T00:00"), at("2021-02-25T23:59"), at("2021-02-21T09:00"));
+        let ids: Vec<&str> = picked.iter().map(|session| session.id.as_str()).collect();
+
+        assert_eq!(vec!["due", "last-day", "moved-in"], ids);
+    }
+}
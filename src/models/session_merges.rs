@@ -0,0 +1,212 @@
+/**
+ * A client retrying a request may create the same session twice. Two sessions
+ * are taken as duplicates when they are of the same enrollment and name and
+ * their windows overlap; the later created one is the duplicate.
+ *
+ * The coach merges the duplicate into the original: its notes, its boards and
+ * its guest links move over, and the duplicate is cancelled. The conference
+ * sessions share their boards through the conference and are not merged.
+ *
+ * The coach is the signed in user, never an id in the request.
+ */
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, HashSet};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::models::sessions::Session;
+
+pub const DUPLICATE_WARNING: &str = "This session looks like a duplicate of another; the coach may merge them.";
+
+fn window(session: &Session) -> (NaiveDateTime, NaiveDateTime) {
+    let start = session.revised_start_date.unwrap_or(session.original_start_date);
+    let end = session.revised_end_date.unwrap_or(session.original_end_date);
+
+    (start, end)
+}
+
+pub fn is_duplicate(one: &Session, other: &Session) -> bool {
+    if one.id == other.id || one.enrollment_id != other.enrollment_id || one.cancelled_at.is_some() || other.cancelled_at.is_some() {
+        return false;
+    }
+
+    if one.name.trim().to_lowercase() != other.name.trim().to_lowercase() {
+        return false;
+    }
+
+    let (one_start, one_end) = window(one);
+    let (other_start, other_end) = window(other);
+
+    one_start < other_end && other_start < one_end
+}
+
+/**
+ * The duplicates among the sessions, each against its original.
+ */
+pub fn find_duplicates(sessions: &[Session]) -> HashMap<SessionId, SessionId> {
+    let mut duplicates: HashMap<SessionId, SessionId> = HashMap::new();
+
+    for session in sessions {
+        let original = sessions
+            .iter()
+            .filter(|other| is_duplicate(session, other))
+            .filter(|other| (other.created_at, other.id.as_str()) < (session.created_at, session.id.as_str()))
+            .min_by_key(|other| (other.created_at, other.id.as_str()));
+
+        if let Some(value) = original {
+            duplicates.insert(session.id.to_owned(), value.id.to_owned());
+        }
+    }
+
+    duplicates
+}
+
+/**
+ * The name a board of the duplicate takes among the boards of the original;
+ * a clash is settled by prefixing the id of the duplicate.
+ */
+pub fn board_target(name: &str, taken: &HashSet<String>, duplicate_id: &str) -> String {
+    if !taken.contains(name) {
+        return name.to_owned();
+    }
+
+    format!("{}-{}", duplicate_id, name)
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct MergeSessionsRequest {
+    pub session_id: SessionId,
+    pub duplicate_id: SessionId,
+    pub dry_run: bool,
+}
+
+impl MergeSessionsRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "The session to keep is a must."));
+        }
+
+        if self.duplicate_id.trim().is_empty() {
+            errors.push(ValidationError::new("duplicate_id", "The duplicate session is a must."));
+        }
+
+        if self.session_id.trim() == self.duplicate_id.trim() {
+            errors.push(ValidationError::new("duplicate_id", "A session cannot be merged into itself."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * What moves from the duplicate, or would move when applied is false.
+ */
+pub struct MergePreview {
+    pub session: Session,
+    pub duplicate_id: String,
+    pub notes: i32,
+    pub boards: Vec<String>,
+    pub guest_links: i32,
+    pub applied: bool,
+}
+
+#[juniper::object(description = "The consolidation of a duplicate session into the original")]
+impl MergePreview {
+    #[graphql(description = "The session kept")]
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn duplicate_id(&self) -> &str {
+        self.duplicate_id.as_str()
+    }
+
+    pub fn notes(&self) -> i32 {
+        self.notes
+    }
+
+    #[graphql(description = "The names the boards of the duplicate take in the session kept")]
+    pub fn boards(&self) -> &Vec<String> {
+        &self.boards
+    }
+
+    pub fn guest_links(&self) -> i32 {
+        self.guest_links
+    }
+
+    pub fn applied(&self) -> bool {
+        self.applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::commons::ids::{EnrollmentId, ProgramId};
+    use chrono::Duration;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn session(id: &str, name: &str, start: &str, created: &str) -> Session {
+        Session {
+            id: SessionId::from(id),
+            name: name.to_owned(),
+            description: None,
+            program_id: ProgramId::from("p-1"),
+            enrollment_id: EnrollmentId::from("e-1"),
+            people: None,
+            duration: 60,
+            original_start_date: at(start),
+            original_end_date: at(start) + Duration::hours(1),
+            revised_start_date: None,
+            revised_end_date: None,
+            offered_start_date: None,
+            offered_end_date: None,
+            is_ready: false,
+            actual_start_date: None,
+            actual_end_date: None,
+            cancelled_at: None,
+            created_at: at(created),
+            updated_at: at(created),
+            closing_notes: None,
+            is_request: false,
+            conference_id: None,
+            session_type: String::from("mono"),
+            expired_at: None,
+            parent_conference_id: None,
+        }
+    }
+
+    #[test]
+    fn should_flag_the_later_of_the_overlapping_sessions() {
+        let mut other_enrollment = session("s-4", "Review", "2021-02-22T10:00", "2021-02-20T09:00");
+        other_enrollment.enrollment_id = EnrollmentId::from("e-2");
+
+        let sessions = vec![
+            session("s-2", "review ", "2021-02-22T10:30", "2021-02-20T09:01"),
+            session("s-1", "Review", "2021-02-22T10:00", "2021-02-20T09:00"),
+            session("s-3", "Review", "2021-02-22T11:00", "2021-02-20T09:02"),
+            session("s-5", "Kick-off", "2021-02-22T10:00", "2021-02-20T09:03"),
+            other_enrollment,
+        ];
+
+        let duplicates = find_duplicates(&sessions);
+
+        assert_eq!(2, duplicates.len());
+        assert_eq!(Some(&SessionId::from("s-1")), duplicates.get(&SessionId::from("s-2")));
+        assert_eq!(Some(&SessionId::from("s-2")), duplicates.get(&SessionId::from("s-3")));
+    }
+
+    #[test]
+    fn should_keep_the_names_of_the_boards_apart() {
+        let taken: HashSet<String> = vec![String::from("board-1.png")].into_iter().collect();
+
+        assert_eq!("board-2.png", board_target("board-2.png", &taken, "s-2"));
+        assert_eq!("s-2-board-1.png", board_target("board-1.png", &taken, "s-2"));
+    }
+}
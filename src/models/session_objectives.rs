@@ -0,0 +1,256 @@
+/**
+ * The objectives of an enrollment a session addressed, each with the rating of
+ * the coach on how the session went for it. The progress of an enrollment is the
+ * coverage of its objectives by the sessions held for them, so that the coach and
+ * the member see the goals that are left aside. The counts and the goal board of
+ * the enrollment come along, so that its dashboard needs no other query.
+ */
+use chrono::{Duration, NaiveDateTime};
+use std::collections::HashSet;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::models::goal_boards::GoalBoard;
+use crate::models::objectives::Objective;
+use crate::models::progress::ProgressCounts;
+use crate::schema::session_objectives;
+
+pub const MIN_RATING: i32 = 1;
+pub const MAX_RATING: i32 = 5;
+
+// An open objective without a session in so many days is neglected.
+const NEGLECT_DAYS: i64 = 21;
+
+const MAX_TAGS: usize = 20;
+
+#[derive(Insertable)]
+#[table_name = "session_objectives"]
+pub struct NewSessionObjective {
+    pub session_id: String,
+    pub objective_id: String,
+    pub rating: Option<i32>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ObjectiveTag {
+    pub objective_id: String,
+    pub rating: Option<i32>,
+}
+
+/**
+ * Replaces the objectives the session addressed; no tags clears them. The
+ * coach is the signed in user, never an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct TagSessionRequest {
+    pub session_id: SessionId,
+    pub tags: Vec<ObjectiveTag>,
+}
+
+impl TagSessionRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        if self.tags.len() > MAX_TAGS {
+            errors.push(ValidationError::new("tags", "A session may address at most 20 objectives."));
+        }
+
+        let objectives: HashSet<&str> = self.tags.iter().map(|tag| tag.objective_id.as_str()).collect();
+        if objectives.len() != self.tags.len() {
+            errors.push(ValidationError::new("tags", "An objective should be tagged once."));
+        }
+
+        if self.tags.iter().filter_map(|tag| tag.rating).any(|rating| !(MIN_RATING..=MAX_RATING).contains(&rating)) {
+            errors.push(ValidationError::new("tags", "The rating should be within 1 and 5."));
+        }
+
+        errors
+    }
+
+    pub fn to_rows(&self) -> Vec<NewSessionObjective> {
+        self.tags
+            .iter()
+            .map(|tag| NewSessionObjective {
+                session_id: self.session_id.to_string(),
+                objective_id: tag.objective_id.to_owned(),
+                rating: tag.rating,
+            })
+            .collect()
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ProgressCriteria {
+    pub user_id: String,
+    pub enrollment_id: String,
+}
+
+/**
+ * A tag of a session not cancelled, with the time the session was planned for.
+ */
+pub struct SessionTag {
+    pub objective_id: String,
+    pub rating: Option<i32>,
+    pub held_at: NaiveDateTime,
+}
+
+pub struct ObjectiveCoverage {
+    pub objective: Objective,
+    pub sessions: i32,
+    pub last_session_at: Option<NaiveDateTime>,
+    pub average_rating: Option<f64>,
+    pub is_neglected: bool,
+}
+
+#[juniper::object(description = "The sessions that addressed an objective of the enrollment")]
+impl ObjectiveCoverage {
+    pub fn objective(&self) -> &Objective {
+        &self.objective
+    }
+
+    pub fn sessions(&self) -> i32 {
+        self.sessions
+    }
+
+    pub fn last_session_at(&self) -> Option<NaiveDateTime> {
+        self.last_session_at
+    }
+
+    #[graphql(description = "The average of the ratings given, from 1 to 5")]
+    pub fn average_rating(&self) -> Option<f64> {
+        self.average_rating
+    }
+
+    #[graphql(description = "True for an open objective without a session in the last 21 days")]
+    pub fn is_neglected(&self) -> bool {
+        self.is_neglected
+    }
+}
+
+pub struct EnrollmentProgress {
+    pub enrollment_id: String,
+    pub objectives: Vec<ObjectiveCoverage>,
+    pub counts: ProgressCounts,
+    pub goal_board: GoalBoard,
+}
+
+#[juniper::object(description = "The coverage of the objectives of an enrollment by its sessions")]
+impl EnrollmentProgress {
+    pub fn enrollment_id(&self) -> &str {
+        self.enrollment_id.as_str()
+    }
+
+    pub fn objectives(&self) -> &Vec<ObjectiveCoverage> {
+        &self.objectives
+    }
+
+    pub fn neglected(&self) -> i32 {
+        self.objectives.iter().filter(|coverage| coverage.is_neglected).count() as i32
+    }
+
+    pub fn counts(&self) -> &ProgressCounts {
+        &self.counts
+    }
+
+    pub fn goal_board(&self) -> &GoalBoard {
+        &self.goal_board
+    }
+}
+
+/**
+ * The coverage of each objective, in the given order. An objective is neglected
+ * when it is open and its last session, if any, is older than NEGLECT_DAYS; the
+ * sessions planned ahead count as attention too.
+ */
+pub fn coverage(objectives: Vec<Objective>, tags: &[SessionTag], now: NaiveDateTime) -> Vec<ObjectiveCoverage> {
+    let since = now - Duration::days(NEGLECT_DAYS);
+
+    objectives
+        .into_iter()
+        .map(|objective| {
+            let of_objective: Vec<&SessionTag> = tags.iter().filter(|tag| tag.objective_id == objective.id).collect();
+            let ratings: Vec<i32> = of_objective.iter().filter_map(|tag| tag.rating).collect();
+
+            let last_session_at = of_objective.iter().map(|tag| tag.held_at).max();
+            let average_rating = if ratings.is_empty() {
+                None
+            } else {
+                Some(ratings.iter().sum::<i32>() as f64 / ratings.len() as f64)
+            };
+
+            let is_open = objective.actual_end_date.is_none();
+            let is_neglected = is_open && last_session_at.map(|value| value < since).unwrap_or(true);
+
+            ObjectiveCoverage {
+                objective,
+                sessions: of_objective.len() as i32,
+                last_session_at,
+                average_rating,
+                is_neglected,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn objective(id: &str, done: bool) -> Objective {
+        Objective {
+            id: id.to_owned(),
+            enrollment_id: String::from("e-1"),
+            duration: 1,
+            original_start_date: at("2021-01-01T10:00"),
+            original_end_date: at("2021-04-01T10:00"),
+            revised_start_date: None,
+            revised_end_date: None,
+            actual_start_date: None,
+            actual_end_date: if done { Some(at("2021-02-01T10:00")) } else { None },
+            created_at: at("2021-01-01T10:00"),
+            updated_at: at("2021-01-01T10:00"),
+            description: None,
+            closing_notes: None,
+        }
+    }
+
+    fn tag(objective_id: &str, rating: Option<i32>, held_at: &str) -> SessionTag {
+        SessionTag {
+            objective_id: objective_id.to_owned(),
+            rating,
+            held_at: at(held_at),
+        }
+    }
+
+    #[test]
+    fn should_flag_the_open_objectives_left_aside() {
+        let objectives = vec![objective("o-1", false), objective("o-2", false), objective("o-3", false), objective("o-4", true)];
+        let tags = vec![
+            tag("o-1", Some(4), "2021-02-15T10:00"),
+            tag("o-1", Some(3), "2021-02-20T10:00"),
+            tag("o-1", None, "2021-02-25T10:00"),
+            tag("o-2", Some(5), "2021-01-10T10:00"),
+        ];
+
+        let result = coverage(objectives, &tags, at("2021-02-22T10:00"));
+
+        assert_eq!(3, result[0].sessions);
+        assert_eq!(Some(3.5), result[0].average_rating);
+        assert_eq!(Some(at("2021-02-25T10:00")), result[0].last_session_at);
+        assert!(!result[0].is_neglected);
+
+        assert!(result[1].is_neglected);
+        assert!(result[2].is_neglected);
+        assert_eq!(None, result[2].average_rating);
+        assert!(!result[3].is_neglected);
+    }
+}
@@ -0,0 +1,130 @@
+/**
+ * The scratchpad of a session is the private jotting of its coach: the hunches, the
+ * follow ups, the things not yet for the member. The clients save it as the coach
+ * types, each save replacing the whole text.
+ *
+ * Only the coach of the program reads it back, session by session over an
+ * enrollment. It lives apart from the notes of the session so that the timelines
+ * and exports of the member never come across it.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::models::sessions::Session;
+use crate::schema::session_scratchpads;
+
+// Well within a MEDIUMTEXT, and far more than a coach jots in a session.
+const MAX_CONTENT: usize = 100_000;
+
+#[derive(Queryable, Debug)]
+pub struct Scratchpad {
+    pub session_id: String,
+    pub content: String,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The private scratchpad of the coach for a session")]
+impl Scratchpad {
+    pub fn session_id(&self) -> &str {
+        self.session_id.as_str()
+    }
+
+    pub fn content(&self) -> &str {
+        self.content.as_str()
+    }
+
+    #[graphql(description = "When the scratchpad was last saved")]
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "session_scratchpads"]
+pub struct NewScratchpad {
+    pub session_id: String,
+    pub coach_id: String,
+    pub content: String,
+}
+
+/**
+ * An empty content clears the scratchpad but keeps it.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct SaveScratchpadRequest {
+    pub coach_id: String,
+    pub session_id: SessionId,
+    pub content: String,
+}
+
+impl SaveScratchpadRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        if self.content.chars().count() > MAX_CONTENT {
+            errors.push(ValidationError::new("content", "The scratchpad should not be longer than 100000 characters."));
+        }
+
+        errors
+    }
+
+    pub fn to_row(&self) -> NewScratchpad {
+        NewScratchpad {
+            session_id: self.session_id.to_string(),
+            coach_id: self.coach_id.to_owned(),
+            content: self.content.to_owned(),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ScratchpadCriteria {
+    pub coach_id: String,
+    pub enrollment_id: String,
+}
+
+pub struct SessionScratchpad {
+    pub session: Session,
+    pub scratchpad: Scratchpad,
+}
+
+#[juniper::object(description = "A session of the enrollment with the scratchpad of its coach")]
+impl SessionScratchpad {
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn scratchpad(&self) -> &Scratchpad {
+        &self.scratchpad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(content: String) -> SaveScratchpadRequest {
+        SaveScratchpadRequest {
+            coach_id: String::from("c-1"),
+            session_id: SessionId::from("s-1"),
+            content,
+        }
+    }
+
+    #[test]
+    fn should_allow_an_empty_but_not_an_overlong_scratchpad() {
+        assert!(request(String::new()).validate().is_empty());
+        assert!(request("a".repeat(MAX_CONTENT)).validate().is_empty());
+        assert_eq!(1, request("a".repeat(MAX_CONTENT + 1)).validate().len());
+    }
+}
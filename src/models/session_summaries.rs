@@ -0,0 +1,194 @@
+/**
+ * The summary of a session is what its coach leaves with the member once they
+ * met: what went well, the action items and the next steps. It takes the place
+ * of the single closing notes text, which is still filled in from the summary
+ * so that the exports and the older clients read the same thing.
+ *
+ * Unlike the scratchpad, the summary is for the member to see; it comes along
+ * with the notes of the enrollment.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::schema::session_summaries;
+
+// Each part is kept in a TEXT column, with room to spare for the multi-byte characters.
+const MAX_PART: usize = 10_000;
+
+#[derive(Queryable, Clone, Debug)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub went_well: String,
+    pub action_items: String,
+    pub next_steps: String,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "The structured summary of a session, as its coach left it")]
+impl SessionSummary {
+    pub fn session_id(&self) -> &str {
+        self.session_id.as_str()
+    }
+
+    pub fn went_well(&self) -> &str {
+        self.went_well.as_str()
+    }
+
+    pub fn action_items(&self) -> Vec<String> {
+        self.action_items.lines().map(String::from).collect()
+    }
+
+    pub fn next_steps(&self) -> &str {
+        self.next_steps.as_str()
+    }
+
+    #[graphql(description = "When the summary was last saved")]
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "session_summaries"]
+pub struct NewSessionSummary {
+    pub session_id: String,
+    pub coach_id: String,
+    pub went_well: String,
+    pub action_items: String,
+    pub next_steps: String,
+}
+
+/**
+ * A summary may leave out any of its parts, but not all of them. The blank action
+ * items are dropped.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct SaveSessionSummaryRequest {
+    pub coach_id: String,
+    pub session_id: SessionId,
+    pub went_well: Option<String>,
+    pub action_items: Option<Vec<String>>,
+    pub next_steps: Option<String>,
+}
+
+impl SaveSessionSummaryRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        let row = self.to_row();
+
+        if row.went_well.is_empty() && row.action_items.is_empty() && row.next_steps.is_empty() {
+            errors.push(ValidationError::new("summary", "The summary should tell what went well, an action item or the next steps."));
+        }
+
+        if row.went_well.chars().count() > MAX_PART {
+            errors.push(ValidationError::new("went_well", "What went well should not be longer than 10000 characters."));
+        }
+
+        if row.action_items.chars().count() > MAX_PART {
+            errors.push(ValidationError::new("action_items", "The action items should not be longer than 10000 characters."));
+        }
+
+        if row.next_steps.chars().count() > MAX_PART {
+            errors.push(ValidationError::new("next_steps", "The next steps should not be longer than 10000 characters."));
+        }
+
+        errors
+    }
+
+    /**
+     * The action items are kept one per line; an item spread over lines is joined
+     * back into one.
+     */
+    pub fn to_row(&self) -> NewSessionSummary {
+        let action_items: Vec<String> = self
+            .action_items
+            .as_ref()
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|item| item.split_whitespace().collect::<Vec<&str>>().join(" "))
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        NewSessionSummary {
+            session_id: self.session_id.to_string(),
+            coach_id: self.coach_id.to_owned(),
+            went_well: trimmed(&self.went_well),
+            action_items: action_items.join("\n"),
+            next_steps: trimmed(&self.next_steps),
+        }
+    }
+}
+
+fn trimmed(part: &Option<String>) -> String {
+    part.as_deref().map(str::trim).unwrap_or_default().to_owned()
+}
+
+impl NewSessionSummary {
+    /**
+     * The closing notes template: a heading for each part the summary tells,
+     * the action items as a list.
+     */
+    pub fn as_closing_notes(&self) -> String {
+        let mut paragraphs: Vec<String> = Vec::new();
+
+        if !self.went_well.is_empty() {
+            paragraphs.push(format!("What went well:\n{}", self.went_well));
+        }
+
+        if !self.action_items.is_empty() {
+            let lines: Vec<String> = self.action_items.lines().map(|item| format!("- {}", item)).collect();
+            paragraphs.push(format!("Action items:\n{}", lines.join("\n")));
+        }
+
+        if !self.next_steps.is_empty() {
+            paragraphs.push(format!("Next steps:\n{}", self.next_steps));
+        }
+
+        paragraphs.join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(went_well: Option<&str>, action_items: Option<Vec<&str>>, next_steps: Option<&str>) -> SaveSessionSummaryRequest {
+        SaveSessionSummaryRequest {
+            coach_id: String::from("c-1"),
+            session_id: SessionId::from("s-1"),
+            went_well: went_well.map(String::from),
+            action_items: action_items.map(|items| items.into_iter().map(String::from).collect()),
+            next_steps: next_steps.map(String::from),
+        }
+    }
+
+    #[test]
+    fn should_reject_a_summary_telling_nothing() {
+        assert_eq!(1, request(None, None, None).validate().len());
+        assert_eq!(1, request(Some("  "), Some(vec!["", " \n "]), Some("")).validate().len());
+        assert!(request(None, Some(vec!["Keep the journal"]), None).validate().is_empty());
+        assert_eq!(1, request(Some("a".repeat(MAX_PART + 1).as_str()), None, None).validate().len());
+    }
+
+    #[test]
+    fn should_fill_the_closing_notes_template_with_the_parts_told() {
+        let row = request(Some(" Opened up on the goals "), Some(vec!["Keep the\njournal", "", "Read  a chapter"]), None).to_row();
+
+        assert_eq!("Keep the journal\nRead a chapter", row.action_items);
+        assert_eq!("What went well:\nOpened up on the goals\n\nAction items:\n- Keep the journal\n- Read a chapter", row.as_closing_notes());
+    }
+}
@@ -1,5 +1,6 @@
 use diesel::prelude::*;
 
+use crate::commons::ids::SessionId;
 use crate::commons::util;
 
 use crate::schema::session_users;
@@ -54,8 +55,8 @@ impl NewSessionUser {
 
         NewSessionUser {
             id: fuzzy_id,
-            session_id: session.id.to_owned(),
-            user_id: user.id.to_owned(),
+            session_id: session.id.to_string(),
+            user_id: user.id.to_string(),
             user_type: String::from(session_user_type),
         }
     }
@@ -63,7 +64,7 @@ impl NewSessionUser {
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct SessionCriteria {
-    pub id: String,
+    pub id: SessionId,
 }
 
 pub struct SessionPeople {
@@ -0,0 +1,255 @@
+/**
+ * The visits of the people of a session to its conference, each from a join
+ * until the leave. A client that rejoins without leaving, after its connection
+ * dropped, goes on with the visit it had; a visit never left runs until the
+ * session is done.
+ *
+ * The attendance sums the visits of each person of the session, so that the
+ * coach of a conference of many members can tell who took part and for how
+ * long. The people who never joined are listed too, without a visit.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
+use crate::commons::util;
+use crate::schema::session_visits;
+
+#[derive(Queryable, Debug, Identifiable, Clone)]
+pub struct SessionVisit {
+    pub id: String,
+    pub session_id: String,
+    pub user_id: String,
+    pub joined_at: NaiveDateTime,
+    pub left_at: Option<NaiveDateTime>,
+}
+
+#[juniper::object(description = "A stay of a user in the conference of a session")]
+impl SessionVisit {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn session_id(&self) -> &str {
+        self.session_id.as_str()
+    }
+
+    pub fn user_id(&self) -> &str {
+        self.user_id.as_str()
+    }
+
+    pub fn joined_at(&self) -> NaiveDateTime {
+        self.joined_at
+    }
+
+    #[graphql(description = "None while the user is still in the conference")]
+    pub fn left_at(&self) -> Option<NaiveDateTime> {
+        self.left_at
+    }
+}
+
+/**
+ * The visitor is the signed in user, never an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct VisitRequest {
+    pub session_id: SessionId,
+}
+
+impl VisitRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        errors
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "session_visits"]
+pub struct NewSessionVisit {
+    pub id: String,
+    pub session_id: String,
+    pub user_id: String,
+    pub joined_at: NaiveDateTime,
+}
+
+impl NewSessionVisit {
+    pub fn from(request: &VisitRequest, user_id: &str) -> NewSessionVisit {
+        NewSessionVisit {
+            id: util::fuzzy_id(),
+            session_id: request.session_id.as_str().to_owned(),
+            user_id: user_id.to_owned(),
+            joined_at: util::now(),
+        }
+    }
+}
+
+/**
+ * A person of the session, as the attendance is taken.
+ */
+pub struct Person {
+    pub user_id: String,
+    pub full_name: String,
+    pub user_type: String,
+}
+
+pub struct Attendee {
+    pub user_id: String,
+    pub full_name: String,
+    pub user_type: String,
+    pub first_joined_at: Option<NaiveDateTime>,
+    pub last_left_at: Option<NaiveDateTime>,
+    pub visits: i32,
+    pub minutes: i32,
+    pub is_present: bool,
+}
+
+#[juniper::object(description = "How long a person of the session stayed in its conference")]
+impl Attendee {
+    pub fn user_id(&self) -> &str {
+        self.user_id.as_str()
+    }
+
+    pub fn full_name(&self) -> &str {
+        self.full_name.as_str()
+    }
+
+    pub fn user_type(&self) -> &str {
+        self.user_type.as_str()
+    }
+
+    pub fn first_joined_at(&self) -> Option<NaiveDateTime> {
+        self.first_joined_at
+    }
+
+    #[graphql(description = "The latest leave; none while the person is still in")]
+    pub fn last_left_at(&self) -> Option<NaiveDateTime> {
+        self.last_left_at
+    }
+
+    #[graphql(description = "How many times the person joined")]
+    pub fn visits(&self) -> i32 {
+        self.visits
+    }
+
+    #[graphql(description = "The minutes over all the visits, the overlapping ones counted once")]
+    pub fn minutes(&self) -> i32 {
+        self.minutes
+    }
+
+    pub fn is_present(&self) -> bool {
+        self.is_present
+    }
+}
+
+/**
+ * The attendance of the people, in their order, from the visits of the session.
+ * A visit not left counts until the given time, the end of the session or now.
+ */
+pub fn summarize(people: Vec<Person>, visits: &[SessionVisit], until: NaiveDateTime) -> Vec<Attendee> {
+    people
+        .into_iter()
+        .map(|person| {
+            let mut stays: Vec<(NaiveDateTime, NaiveDateTime)> = visits
+                .iter()
+                .filter(|visit| visit.user_id == person.user_id)
+                .map(|visit| (visit.joined_at, visit.left_at.unwrap_or(until).max(visit.joined_at)))
+                .collect();
+            stays.sort();
+
+            let is_present = visits.iter().any(|visit| visit.user_id == person.user_id && visit.left_at.is_none());
+            let last_left_at = if is_present {
+                None
+            } else {
+                visits.iter().filter(|visit| visit.user_id == person.user_id).filter_map(|visit| visit.left_at).max()
+            };
+
+            Attendee {
+                first_joined_at: stays.first().map(|(joined_at, _)| *joined_at),
+                last_left_at,
+                visits: stays.len() as i32,
+                minutes: covered_minutes(&stays),
+                is_present,
+                user_id: person.user_id,
+                full_name: person.full_name,
+                user_type: person.user_type,
+            }
+        })
+        .collect()
+}
+
+// The stays are in the order of their joins; a stay within the one before adds nothing.
+fn covered_minutes(stays: &[(NaiveDateTime, NaiveDateTime)]) -> i32 {
+    let mut seconds: i64 = 0;
+    let mut covered_until: Option<NaiveDateTime> = None;
+
+    for (joined_at, left_at) in stays {
+        let from = match covered_until {
+            Some(until) if until > *joined_at => until,
+            _ => *joined_at,
+        };
+
+        if *left_at > from {
+            seconds += (*left_at - from).num_seconds();
+            covered_until = Some(*left_at);
+        }
+    }
+
+    (seconds / 60) as i32
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn visit(user_id: &str, joined_at: &str, left_at: Option<&str>) -> SessionVisit {
+        SessionVisit {
+            id: util::fuzzy_id(),
+            session_id: String::from("s-1"),
+            user_id: user_id.to_owned(),
+            joined_at: at(joined_at),
+            left_at: left_at.map(at),
+        }
+    }
+
+    fn person(user_id: &str) -> Person {
+        Person {
+            user_id: user_id.to_owned(),
+            full_name: user_id.to_uppercase(),
+            user_type: String::from("member"),
+        }
+    }
+
+    #[test]
+    fn should_sum_the_visits_counting_the_overlaps_once() {
+        let visits = vec![
+            visit("m-1", "2021-03-03T10:00", Some("2021-03-03T10:20")),
+            visit("m-1", "2021-03-03T10:15", Some("2021-03-03T10:30")),
+            visit("m-1", "2021-03-03T10:40", Some("2021-03-03T10:50")),
+            visit("m-2", "2021-03-03T10:05", None),
+        ];
+
+        let attendance = summarize(vec![person("m-1"), person("m-2"), person("m-3")], &visits, at("2021-03-03T11:00"));
+
+        assert_eq!(40, attendance[0].minutes);
+        assert_eq!(3, attendance[0].visits);
+        assert_eq!(Some(at("2021-03-03T10:50")), attendance[0].last_left_at);
+        assert!(!attendance[0].is_present);
+
+        assert_eq!(55, attendance[1].minutes);
+        assert_eq!(None, attendance[1].last_left_at);
+        assert!(attendance[1].is_present);
+
+        assert_eq!(0, attendance[2].visits);
+        assert_eq!(None, attendance[2].first_joined_at);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::commons::chassis::ValidationError;
+use crate::commons::ids::{EnrollmentId, ProgramId, SessionId, UserId};
 use crate::commons::util;
 use crate::schema::sessions;
 
@@ -7,11 +8,11 @@ use chrono::{Duration, NaiveDateTime};
 // The Order of the fiels are very important
 #[derive(Queryable, Debug, Identifiable, Clone)]
 pub struct Session {
-    pub id: String,
+    pub id: SessionId,
     pub name: String,
     pub description: Option<String>,
-    pub program_id: String,
-    pub enrollment_id: String,
+    pub program_id: ProgramId,
+    pub enrollment_id: EnrollmentId,
     pub people: Option<String>,
     pub duration: i32,
     pub original_start_date: NaiveDateTime,
@@ -30,13 +31,16 @@ pub struct Session {
     pub is_request: bool,
     pub conference_id: Option<String>,
     pub session_type: String,
+    pub expired_at: Option<NaiveDateTime>,
+    pub parent_conference_id: Option<String>,
 }
 
-#[derive(juniper::GraphQLEnum)]
+#[derive(juniper::GraphQLEnum, Debug)]
 enum Status {
     DONE,
     PROGRESS,
     CANCELLED,
+    EXPIRED,
     READY,
     OVERDUE,
     PLANNED,
@@ -99,6 +103,7 @@ impl Session {
         self.actual_end_date
     }
 
+    #[graphql(deprecated = "Use status; a session is closed once it is done or cancelled")]
     pub fn isClosed(&self) -> bool {
         if self.cancelled_at.is_some() {
             return true;
@@ -112,28 +117,7 @@ impl Session {
     }
 
     pub fn status(&self) -> Status {
-        if self.cancelled_at.is_some() {
-            return Status::CANCELLED;
-        }
-
-        if self.actual_end_date.is_some() {
-            return Status::DONE;
-        }
-        if self.actual_start_date.is_some() {
-            return Status::PROGRESS;
-        }
-
-        if self.is_ready {
-            return Status::READY;
-        }
-
-        let rev_start_date = self.revised_start_date.unwrap_or(self.original_start_date);
-
-        if util::is_past_date(rev_start_date) {
-            return Status::OVERDUE;
-        }
-
-        Status::PLANNED
+        self.state()
     }
 
     pub fn closing_notes(&self) -> Option<String> {
@@ -147,6 +131,16 @@ impl Session {
     pub fn conference_id(&self) -> Option<String> {
         self.conference_id.clone()
     }
+
+    #[graphql(description = "When the request or the conference was expired, never having been scheduled")]
+    pub fn expired_at(&self) -> Option<NaiveDateTime> {
+        self.expired_at
+    }
+
+    #[graphql(description = "The conference a one to one follow-up came from")]
+    pub fn parent_conference_id(&self) -> Option<String> {
+        self.parent_conference_id.clone()
+    }
 }
 
 impl Session {
@@ -170,19 +164,62 @@ impl Session {
     pub fn is_conference(&self) -> bool {
         self.session_type.eq("multi")
     }
+
+    // The status as a plain word, for the exports.
+    pub fn status_label(&self) -> String {
+        format!("{:?}", self.state())
+    }
+
+    fn state(&self) -> Status {
+        if self.cancelled_at.is_some() {
+            return Status::CANCELLED;
+        }
+
+        if self.expired_at.is_some() {
+            return Status::EXPIRED;
+        }
+
+        if self.actual_end_date.is_some() {
+            return Status::DONE;
+        }
+        if self.actual_start_date.is_some() {
+            return Status::PROGRESS;
+        }
+
+        if self.is_ready {
+            return Status::READY;
+        }
+
+        let rev_start_date = self.revised_start_date.unwrap_or(self.original_start_date);
+
+        if util::is_past_date(rev_start_date) {
+            return Status::OVERDUE;
+        }
+
+        Status::PLANNED
+    }
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct NewSessionRequest {
-    pub program_id: String,
-    pub member_id: String,
+    pub program_id: ProgramId,
+    pub member_id: UserId,
     pub name: String,
     pub description: String,
     pub duration: i32,
     pub start_time: String,
+    pub override_reason: Option<String>,
 }
 
 impl NewSessionRequest {
+    // A blank reason is as good as no reason.
+    pub fn override_reason(&self) -> Option<&str> {
+        match &self.override_reason {
+            Some(reason) if !reason.trim().is_empty() => Some(reason.as_str()),
+            _ => None,
+        }
+    }
+
     pub fn validate(&self) -> Vec<ValidationError> {
         let mut errors: Vec<ValidationError> = Vec::new();
 
@@ -225,11 +262,11 @@ impl NewSessionRequest {
 #[derive(Insertable)]
 #[table_name = "sessions"]
 pub struct NewSession {
-    pub id: String,
+    pub id: SessionId,
     pub name: String,
     pub description: String,
-    pub program_id: String,
-    pub enrollment_id: String,
+    pub program_id: ProgramId,
+    pub enrollment_id: EnrollmentId,
     pub people: String,
     pub duration: i32,
     pub original_start_date: NaiveDateTime,
@@ -237,18 +274,17 @@ pub struct NewSession {
     pub conference_id: Option<String>,
     pub session_type: String,
     pub is_ready: bool,
+    pub parent_conference_id: Option<String>,
 }
 
 impl NewSession {
-    pub fn from(request: &NewSessionRequest, enrollment_id: String, people: String) -> NewSession {
+    pub fn from(request: &NewSessionRequest, enrollment_id: EnrollmentId, people: String) -> NewSession {
         let start_date = util::as_date(request.start_time.as_str());
         let duration = Duration::minutes(request.duration as i64);
         let end_date = start_date.checked_add_signed(duration);
 
-        let fuzzy_id = util::fuzzy_id();
-
         NewSession {
-            id: fuzzy_id,
+            id: SessionId::from(util::fuzzy_id()),
             name: request.name.to_owned(),
             description: request.description.to_owned(),
             program_id: request.program_id.to_owned(),
@@ -260,6 +296,7 @@ impl NewSession {
             conference_id: None,
             session_type: util::MONO.to_owned(),
             is_ready:false,
+            parent_conference_id: None,
         }
     }
 }
@@ -274,7 +311,7 @@ pub enum TargetState {
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct ChangeSessionStateRequest {
-    pub id: String,
+    pub id: SessionId,
     pub target_state: TargetState,
     pub closing_notes: Option<String>,
 }
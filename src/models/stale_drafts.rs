@@ -0,0 +1,68 @@
+/**
+ * The drafts that were never scheduled: the sessions a member requested and the
+ * conferences a coach set up but never made ready. The "stale-drafts" job expires
+ * them after a while, tells the one who asked for them, and the calendars leave
+ * them out from then on.
+ *
+ * STALE_REQUEST_DAYS counts from the request; STALE_CONFERENCE_DAYS counts from the
+ * start of the conference.
+ */
+use chrono::{Duration, NaiveDateTime};
+use std::env;
+
+const REQUEST_DAYS: i64 = 14;
+const CONFERENCE_DAYS: i64 = 7;
+
+#[derive(Debug, PartialEq)]
+pub struct StalePeriods {
+    pub request_days: i64,
+    pub conference_days: i64,
+}
+
+impl StalePeriods {
+    pub fn from_env() -> StalePeriods {
+        StalePeriods::parse(env::var("STALE_REQUEST_DAYS").ok(), env::var("STALE_CONFERENCE_DAYS").ok())
+    }
+
+    // A period that is not a positive number of days falls back to the default.
+    fn parse(request_days: Option<String>, conference_days: Option<String>) -> StalePeriods {
+        let days = |value: Option<String>, default: i64| value.and_then(|value| value.trim().parse::<i64>().ok()).filter(|days| *days > 0).unwrap_or(default);
+
+        StalePeriods {
+            request_days: days(request_days, REQUEST_DAYS),
+            conference_days: days(conference_days, CONFERENCE_DAYS),
+        }
+    }
+
+    /**
+     * The requests made before this are stale.
+     */
+    pub fn request_cutoff(&self, now: NaiveDateTime) -> NaiveDateTime {
+        now - Duration::days(self.request_days)
+    }
+
+    /**
+     * The conferences due to start before this are stale.
+     */
+    pub fn conference_cutoff(&self, now: NaiveDateTime) -> NaiveDateTime {
+        now - Duration::days(self.conference_days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_fall_back_to_the_default_periods() {
+        let defaults = StalePeriods {
+            request_days: REQUEST_DAYS,
+            conference_days: CONFERENCE_DAYS,
+        };
+
+        assert_eq!(defaults, StalePeriods::parse(None, None));
+        assert_eq!(defaults, StalePeriods::parse(Some(String::from("0")), Some(String::from("a week"))));
+        assert_eq!(30, StalePeriods::parse(Some(String::from(" 30 ")), None).request_days);
+    }
+}
@@ -0,0 +1,377 @@
+/**
+ * The help desk of the platform. A member or a coach raises a ticket, like "I
+ * cannot join my session", and the admins answer it in the thread of the ticket.
+ *
+ * A ticket is OPEN while it waits for the admins, ANSWERED while it waits for the
+ * user who raised it and RESOLVED when either side closes it; the user may reopen
+ * it. The attachments are the files the user uploaded to their own assets, named
+ * by the file name.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::schema::{support_tickets, ticket_messages};
+
+const MAX_SUBJECT: usize = 255;
+const MAX_BODY: usize = 10_000;
+const MAX_ATTACHMENTS: usize = 5;
+const MAX_FILE_NAME: usize = 255;
+
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TicketCategory {
+    SESSION,
+    ACCOUNT,
+    PAYMENT,
+    CONTENT,
+    OTHER,
+}
+
+impl TicketCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketCategory::SESSION => "SESSION",
+            TicketCategory::ACCOUNT => "ACCOUNT",
+            TicketCategory::PAYMENT => "PAYMENT",
+            TicketCategory::CONTENT => "CONTENT",
+            TicketCategory::OTHER => "OTHER",
+        }
+    }
+}
+
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TicketStatus {
+    OPEN,
+    ANSWERED,
+    RESOLVED,
+}
+
+impl TicketStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketStatus::OPEN => "OPEN",
+            TicketStatus::ANSWERED => "ANSWERED",
+            TicketStatus::RESOLVED => "RESOLVED",
+        }
+    }
+
+    pub fn parse(value: &str) -> TicketStatus {
+        match value {
+            "ANSWERED" => TicketStatus::ANSWERED,
+            "RESOLVED" => TicketStatus::RESOLVED,
+            _ => TicketStatus::OPEN,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TicketAction {
+    RESOLVE,
+    REOPEN,
+}
+
+/**
+ * The status after a reply or an action on a ticket, or the reason it is not allowed.
+ * A reply from the admins answers the ticket and a reply from the user opens it
+ * again; only the user may reopen a resolved ticket.
+ */
+pub fn next_status(current: TicketStatus, by_admin: bool, action: Option<TicketAction>) -> Result<TicketStatus, &'static str> {
+    match (current, action) {
+        (TicketStatus::RESOLVED, None) => Err("The ticket is resolved; please reopen it to write again."),
+        (_, None) if by_admin => Ok(TicketStatus::ANSWERED),
+        (_, None) => Ok(TicketStatus::OPEN),
+        (TicketStatus::RESOLVED, Some(TicketAction::RESOLVE)) => Err("The ticket is already resolved."),
+        (_, Some(TicketAction::RESOLVE)) => Ok(TicketStatus::RESOLVED),
+        (TicketStatus::RESOLVED, Some(TicketAction::REOPEN)) if !by_admin => Ok(TicketStatus::OPEN),
+        (TicketStatus::RESOLVED, Some(TicketAction::REOPEN)) => Err("Only the user who raised the ticket may reopen it."),
+        (_, Some(TicketAction::REOPEN)) => Err("Only a resolved ticket may be reopened."),
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct SupportTicket {
+    pub id: String,
+    pub raised_by_id: String,
+    pub category: String,
+    pub subject: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>,
+}
+
+impl SupportTicket {
+    pub fn state(&self) -> TicketStatus {
+        TicketStatus::parse(self.status.as_str())
+    }
+}
+
+#[juniper::object(description = "A request for help raised by a member or a coach")]
+impl SupportTicket {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn raised_by_id(&self) -> &str {
+        self.raised_by_id.as_str()
+    }
+
+    pub fn category(&self) -> &str {
+        self.category.as_str()
+    }
+
+    pub fn subject(&self) -> &str {
+        self.subject.as_str()
+    }
+
+    pub fn status(&self) -> TicketStatus {
+        self.state()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+
+    pub fn resolved_at(&self) -> Option<NaiveDateTime> {
+        self.resolved_at
+    }
+}
+
+#[derive(Queryable, Debug)]
+pub struct TicketMessage {
+    pub id: String,
+    pub ticket_id: String,
+    pub author_id: String,
+    pub body: String,
+    pub attachments: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A message in the thread of a support ticket")]
+impl TicketMessage {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn ticket_id(&self) -> &str {
+        self.ticket_id.as_str()
+    }
+
+    pub fn author_id(&self) -> &str {
+        self.author_id.as_str()
+    }
+
+    pub fn body(&self) -> &str {
+        self.body.as_str()
+    }
+
+    #[graphql(description = "The paths of the attached files under the assets, like assets/users/{authorId}/{fileName}")]
+    pub fn attachments(&self) -> Vec<String> {
+        let names: Vec<String> = self.attachments.as_deref().and_then(|value| serde_json::from_str(value).ok()).unwrap_or_default();
+
+        names.iter().map(|name| format!("assets/users/{}/{}", self.author_id, name)).collect()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+pub struct TicketThread {
+    pub ticket: SupportTicket,
+    pub messages: Vec<TicketMessage>,
+}
+
+#[juniper::object(description = "A support ticket with its messages, the oldest first")]
+impl TicketThread {
+    pub fn ticket(&self) -> &SupportTicket {
+        &self.ticket
+    }
+
+    pub fn messages(&self) -> &Vec<TicketMessage> {
+        &self.messages
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "support_tickets"]
+pub struct NewSupportTicket {
+    pub id: String,
+    pub raised_by_id: String,
+    pub category: String,
+    pub subject: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "ticket_messages"]
+pub struct NewTicketMessage {
+    pub id: String,
+    pub ticket_id: String,
+    pub author_id: String,
+    pub body: String,
+    pub attachments: Option<String>,
+}
+
+impl NewTicketMessage {
+    pub fn new(ticket_id: &str, author_id: &str, body: &str, attachments: &[String]) -> NewTicketMessage {
+        let attachments = if attachments.is_empty() { None } else { serde_json::to_string(attachments).ok() };
+
+        NewTicketMessage {
+            id: util::fuzzy_id(),
+            ticket_id: ticket_id.to_owned(),
+            author_id: author_id.to_owned(),
+            body: body.trim().to_owned(),
+            attachments,
+        }
+    }
+}
+
+fn validate_message(body: &str, attachments: &[String], errors: &mut Vec<ValidationError>) {
+    let body = body.trim();
+    if body.is_empty() || body.chars().count() > MAX_BODY {
+        errors.push(ValidationError::new("body", "The message should have 1 to 10000 characters."));
+    }
+
+    if attachments.len() > MAX_ATTACHMENTS {
+        errors.push(ValidationError::new("attachments", "Please attach at most 5 files."));
+    }
+
+    // The names point into the assets of the author; a path could reach the files of others.
+    let unsafe_name = |name: &String| name.is_empty() || name.len() > MAX_FILE_NAME || name.contains('/') || name.contains('\\') || name.contains("..");
+    if attachments.iter().any(unsafe_name) {
+        errors.push(ValidationError::new("attachments", "Please give the plain names of the files you uploaded."));
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RaiseTicketRequest {
+    pub user_id: UserId,
+    pub category: TicketCategory,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<String>,
+}
+
+impl RaiseTicketRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        let subject = self.subject.trim();
+        if subject.is_empty() || subject.chars().count() > MAX_SUBJECT {
+            errors.push(ValidationError::new("subject", "The subject should have 1 to 255 characters."));
+        }
+
+        validate_message(self.body.as_str(), &self.attachments, &mut errors);
+
+        errors
+    }
+
+    pub fn to_ticket(&self) -> NewSupportTicket {
+        NewSupportTicket {
+            id: util::fuzzy_id(),
+            raised_by_id: self.user_id.to_string(),
+            category: self.category.as_str().to_owned(),
+            subject: self.subject.trim().to_owned(),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct TicketReplyRequest {
+    pub ticket_id: String,
+    pub author_id: UserId,
+    pub body: String,
+    pub attachments: Vec<String>,
+}
+
+impl TicketReplyRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.ticket_id.trim().is_empty() {
+            errors.push(ValidationError::new("ticket_id", "Ticket id is a must."));
+        }
+
+        if self.author_id.trim().is_empty() {
+            errors.push(ValidationError::new("author_id", "Author id is a must."));
+        }
+
+        validate_message(self.body.as_str(), &self.attachments, &mut errors);
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct TicketActionRequest {
+    pub ticket_id: String,
+    pub actor_id: UserId,
+    pub action: TicketAction,
+}
+
+impl TicketActionRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.ticket_id.trim().is_empty() {
+            errors.push(ValidationError::new("ticket_id", "Ticket id is a must."));
+        }
+
+        if self.actor_id.trim().is_empty() {
+            errors.push(ValidationError::new("actor_id", "Actor id is a must."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The admins see every ticket, the others only their own.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct TicketCriteria {
+    pub user_id: UserId,
+    pub status: Option<TicketStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_move_the_ticket_between_the_sides() {
+        assert_eq!(Ok(TicketStatus::ANSWERED), next_status(TicketStatus::OPEN, true, None));
+        assert_eq!(Ok(TicketStatus::OPEN), next_status(TicketStatus::ANSWERED, false, None));
+        assert!(next_status(TicketStatus::RESOLVED, true, None).is_err());
+
+        assert_eq!(Ok(TicketStatus::RESOLVED), next_status(TicketStatus::ANSWERED, false, Some(TicketAction::RESOLVE)));
+        assert_eq!(Ok(TicketStatus::OPEN), next_status(TicketStatus::RESOLVED, false, Some(TicketAction::REOPEN)));
+        assert!(next_status(TicketStatus::RESOLVED, true, Some(TicketAction::REOPEN)).is_err());
+        assert!(next_status(TicketStatus::OPEN, false, Some(TicketAction::REOPEN)).is_err());
+    }
+
+    #[test]
+    fn should_refuse_the_paths_as_attachments() {
+        let request = RaiseTicketRequest {
+            user_id: UserId::from("u-1"),
+            category: TicketCategory::SESSION,
+            subject: String::from("I cannot join my session"),
+            body: String::from("The join button stays grey."),
+            attachments: vec![String::from("screen.png"), String::from("../u-2/notes.pdf")],
+        };
+
+        let fields: Vec<String> = request.validate().into_iter().map(|error| error.field).collect();
+
+        assert_eq!(vec!["attachments"], fields);
+    }
+}
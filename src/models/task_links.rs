@@ -0,0 +1,289 @@
+/**
+ * The links between the tasks of an enrollment, copied from the links of the
+ * master plan the tasks were spawned from. A forward link makes its target wait
+ * for its source to end, and for the lead time, in hours, after that.
+ *
+ * When a task slips, its later tasks are pushed along the forward links as far
+ * as the new end asks for. A task that has started, or is over, keeps its dates;
+ * the tasks after it are pushed from its dates as they are.
+ */
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDateTime};
+
+use crate::commons::util;
+use crate::models::master_plans::PlanLink;
+use crate::models::tasks::Task;
+use crate::schema::task_links;
+
+const SLIPPED_TASK_CLOSED: &str = "A task that is done or cancelled cannot be rescheduled.";
+const END_BEFORE_START: &str = "The new end date should be after the start of the task.";
+const LINKS_HAVE_CYCLE: &str = "The tasks loop back on themselves through their links; please correct the links.";
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct TaskLink {
+    pub id: String,
+    pub source_task_id: String,
+    pub target_task_id: String,
+    pub lead_time: i32,
+    pub coordinates: String,
+    pub priority: i32,
+    pub is_forward: bool,
+    pub enrollment_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "task_links"]
+pub struct NewTaskLink {
+    pub id: String,
+    pub source_task_id: String,
+    pub target_task_id: String,
+    pub lead_time: i32,
+    pub coordinates: String,
+    pub priority: i32,
+    pub is_forward: bool,
+    pub enrollment_id: String,
+}
+
+impl NewTaskLink {
+    pub fn of_plan_link(link: &PlanLink, the_enrollment_id: &str, source_id: &str, target_id: &str) -> NewTaskLink {
+        NewTaskLink {
+            id: util::fuzzy_id(),
+            source_task_id: source_id.to_owned(),
+            target_task_id: target_id.to_owned(),
+            lead_time: link.lead_time,
+            coordinates: link.coordinates.to_owned(),
+            priority: link.priority,
+            is_forward: link.is_forward,
+            enrollment_id: the_enrollment_id.to_owned(),
+        }
+    }
+}
+
+/**
+ * The revised dates of a task moved by a slip.
+ */
+#[derive(Debug, PartialEq)]
+pub struct Revision {
+    pub task_id: String,
+    pub start_date: NaiveDateTime,
+    pub end_date: NaiveDateTime,
+}
+
+/**
+ * The revisions a slip of the task to the new end makes, the slipped task first.
+ * The later tasks are taken in the order of their links, so that a task waiting
+ * on two slipped ones moves once, after the later of the two.
+ */
+pub fn cascade(tasks: &[Task], links: &[TaskLink], slipped_id: &str, new_end: NaiveDateTime) -> Result<Vec<Revision>, &'static str> {
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|task| (task.id.as_str(), task)).collect();
+
+    let slipped = match by_id.get(slipped_id) {
+        Some(task) => *task,
+        None => return Ok(Vec::new()),
+    };
+
+    if !slipped.can_cancel() {
+        return Err(SLIPPED_TASK_CLOSED);
+    }
+
+    let (slipped_start, _) = schedule_of(slipped);
+    if new_end <= slipped_start {
+        return Err(END_BEFORE_START);
+    }
+
+    let links: Vec<&TaskLink> = links
+        .iter()
+        .filter(|link| link.is_forward && by_id.contains_key(link.source_task_id.as_str()) && by_id.contains_key(link.target_task_id.as_str()))
+        .collect();
+
+    let later = later_tasks(&links, slipped_id);
+
+    // Only the links among the slipped task and the later ones hold a task back.
+    let mut pending: HashMap<&str, usize> = later.iter().map(|task_id| (*task_id, 0)).collect();
+    for link in &links {
+        if later.contains(link.source_task_id.as_str()) || link.source_task_id == slipped_id {
+            if let Some(count) = pending.get_mut(link.target_task_id.as_str()) {
+                *count += 1;
+            }
+        }
+    }
+
+    let mut dates: HashMap<&str, (NaiveDateTime, NaiveDateTime)> = tasks.iter().map(|task| (task.id.as_str(), schedule_of(task))).collect();
+    dates.insert(slipped.id.as_str(), (slipped_start, new_end));
+
+    let mut revisions = vec![Revision {
+        task_id: slipped_id.to_owned(),
+        start_date: slipped_start,
+        end_date: new_end,
+    }];
+
+    let mut ready: Vec<&str> = vec![slipped_id];
+
+    while let Some(task_id) = ready.pop() {
+        for link in links.iter().filter(|link| link.source_task_id == task_id) {
+            let target = link.target_task_id.as_str();
+
+            let count = match pending.get_mut(target) {
+                Some(count) => count,
+                None => continue,
+            };
+            *count -= 1;
+            if *count > 0 {
+                continue;
+            }
+
+            ready.push(target);
+
+            if !by_id[target].can_start() {
+                continue;
+            }
+
+            let earliest = links
+                .iter()
+                .filter(|link| link.target_task_id == target)
+                .map(|link| dates[link.source_task_id.as_str()].1 + Duration::hours(link.lead_time.max(0) as i64))
+                .max()
+                .unwrap_or(slipped_start);
+
+            let (start_date, end_date) = dates[target];
+            if earliest > start_date {
+                let moved = (earliest, earliest + (end_date - start_date));
+                dates.insert(target, moved);
+                revisions.push(Revision {
+                    task_id: target.to_owned(),
+                    start_date: moved.0,
+                    end_date: moved.1,
+                });
+            }
+        }
+    }
+
+    if pending.values().any(|count| *count > 0) {
+        return Err(LINKS_HAVE_CYCLE);
+    }
+
+    Ok(revisions)
+}
+
+// The revised dates of the task, else the original ones.
+fn schedule_of(task: &Task) -> (NaiveDateTime, NaiveDateTime) {
+    (task.revised_start_date.unwrap_or(task.original_start_date), task.revised_end_date.unwrap_or(task.original_end_date))
+}
+
+// The tasks reachable from the slipped one along the forward links.
+fn later_tasks<'a>(links: &[&'a TaskLink], slipped_id: &str) -> HashSet<&'a str> {
+    let mut later: HashSet<&str> = HashSet::new();
+    let mut next: Vec<&str> = vec![slipped_id];
+
+    while let Some(task_id) = next.pop() {
+        for link in links.iter().filter(|link| link.source_task_id == task_id) {
+            if later.insert(link.target_task_id.as_str()) {
+                next.push(link.target_task_id.as_str());
+            }
+        }
+    }
+
+    later
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn task(id: &str, start: &str, end: &str) -> Task {
+        Task {
+            id: id.to_owned(),
+            enrollment_id: String::from("e-1"),
+            actor_id: String::from("u-1"),
+            name: id.to_owned(),
+            duration: 0,
+            min: 0,
+            max: 0,
+            original_start_date: at(start),
+            original_end_date: at(end),
+            revised_start_date: None,
+            revised_end_date: None,
+            offered_start_date: None,
+            offered_end_date: None,
+            actual_start_date: None,
+            actual_end_date: None,
+            locked: false,
+            created_at: at(start),
+            updated_at: at(start),
+            description: None,
+            closing_notes: None,
+            response: None,
+            approved_at: None,
+            cancelled_at: None,
+            responded_date: None,
+            session_id: None,
+        }
+    }
+
+    fn link(source: &str, target: &str, lead_time: i32) -> TaskLink {
+        TaskLink {
+            id: format!("{}-{}", source, target),
+            source_task_id: source.to_owned(),
+            target_task_id: target.to_owned(),
+            lead_time,
+            coordinates: String::from("{}"),
+            priority: 0,
+            is_forward: true,
+            enrollment_id: String::from("e-1"),
+        }
+    }
+
+    #[test]
+    fn should_push_the_later_tasks_by_the_slip() {
+        let tasks = vec![
+            task("read", "2021-03-01T09:00", "2021-03-01T13:00"),
+            task("write", "2021-03-01T14:00", "2021-03-01T18:00"),
+            task("review", "2021-03-03T09:00", "2021-03-03T11:00"),
+            task("apart", "2021-03-01T09:00", "2021-03-01T10:00"),
+        ];
+        let links = vec![link("read", "write", 1), link("write", "review", 24), link("read", "review", 0)];
+
+        let revisions = cascade(&tasks, &links, "read", at("2021-03-02T13:00")).unwrap();
+
+        assert_eq!(
+            vec![
+                Revision {
+                    task_id: String::from("read"),
+                    start_date: at("2021-03-01T09:00"),
+                    end_date: at("2021-03-02T13:00"),
+                },
+                Revision {
+                    task_id: String::from("write"),
+                    start_date: at("2021-03-02T14:00"),
+                    end_date: at("2021-03-02T18:00"),
+                },
+                Revision {
+                    task_id: String::from("review"),
+                    start_date: at("2021-03-03T18:00"),
+                    end_date: at("2021-03-03T20:00"),
+                },
+            ],
+            revisions
+        );
+    }
+
+    #[test]
+    fn should_leave_a_started_task_and_the_roomy_ones_as_they_are() {
+        let mut write = task("write", "2021-03-01T14:00", "2021-03-01T18:00");
+        write.actual_start_date = Some(at("2021-03-01T14:00"));
+
+        let tasks = vec![task("read", "2021-03-01T09:00", "2021-03-01T13:00"), write, task("review", "2021-03-05T09:00", "2021-03-05T11:00")];
+        let links = vec![link("read", "write", 0), link("write", "review", 0)];
+
+        let revisions = cascade(&tasks, &links, "read", at("2021-03-01T16:00")).unwrap();
+
+        assert_eq!(1, revisions.len());
+        assert_eq!(Err(END_BEFORE_START), cascade(&tasks, &links, "read", at("2021-03-01T08:00")));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::commons::chassis::ValidationError;
+use crate::commons::ids::SessionId;
 use crate::commons::util;
+use crate::models::master_tasks::MasterTask;
 use crate::schema::tasks;
 
 use chrono::{Duration, NaiveDateTime};
@@ -30,9 +32,13 @@ pub struct Task {
     pub approved_at: Option<NaiveDateTime>,
     pub cancelled_at: Option<NaiveDateTime>,
     pub responded_date: Option<NaiveDateTime>,
+    pub session_id: Option<String>,
 }
 
-#[derive(juniper::GraphQLEnum)]
+// The action items of a session are few; a longer list belongs to a plan.
+const MAX_ACTION_ITEMS: usize = 20;
+
+#[derive(juniper::GraphQLEnum, Debug)]
 enum Status {
     PLANNED,
     CANCELLED,
@@ -125,13 +131,53 @@ impl Task {
         self.cancelled_at
     }
 
+    #[graphql(description = "The session whose action item the task was made from, if any")]
+    pub fn sessionId(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
    
     pub fn status(&self) -> Status {
+        self.state()
+    }
+
+    pub fn canStart(&self) -> bool {
+        self.can_start()
+    }
+
+    pub fn canRespond(&self) -> bool {
+        self.can_respond()
+    }
+
+    pub fn canFinish(&self) -> bool {
+        self.can_finish()
+    }
+
+    pub fn canComplete(&self) -> bool {
+        self.can_complete()
+    }
+
+    pub fn canCancel(&self) -> bool {
+        self.can_cancel()
+    }
+
+    pub fn canReopen(&self) -> bool {
+        self.can_reopen()
+    }
+}
+
+impl Task {
+
+    // The status as a plain word, for the exports.
+    pub fn status_label(&self) -> String {
+        format!("{:?}", self.state())
+    }
 
+    fn state(&self) -> Status {
         if self.cancelled_at.is_some() {
             return Status::CANCELLED;
         }
-    
+
         if self.actual_end_date.is_some() {
             return Status::DONE;
         }
@@ -156,34 +202,6 @@ impl Task {
 
         Status::PLANNED
     }
- 
- 
-    pub fn canStart(&self) -> bool {
-        self.can_start()
-    }
-
-    pub fn canRespond(&self) -> bool {
-        self.can_respond()
-    }
-
-    pub fn canFinish(&self) -> bool {
-        self.can_finish()
-    }
-
-    pub fn canComplete(&self) -> bool {
-        self.can_complete()
-    }
-
-    pub fn canCancel(&self) -> bool {
-        self.can_cancel()
-    }
-
-    pub fn canReopen(&self) -> bool {
-        self.can_reopen()
-    }
-}
-
-impl Task {
 
     pub fn can_start(&self) -> bool {
         self.actual_start_date.is_none() && self.responded_date.is_none() && self.cancelled_at.is_none() && self.actual_end_date.is_none()
@@ -282,6 +300,86 @@ impl UpdateTaskRequest {
     }
 }
 
+/**
+ * The task ends on the new end date, and the tasks waiting on it are pushed
+ * along its links.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct RescheduleCascadeRequest {
+    pub task_id: String,
+    pub end_date: String,
+}
+
+impl RescheduleCascadeRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+        let given_time = self.end_date.as_str();
+
+        if self.task_id.trim().is_empty() {
+            errors.push(ValidationError::new("task_id", "Task Id is a must."));
+        }
+
+        if !util::is_valid_date(given_time) {
+            errors.push(ValidationError::new("end_date", "unparsable date."));
+        } else if util::is_in_past(util::as_date(given_time)) {
+            errors.push(ValidationError::new("end_date", "should be a future date."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The action items of a session made into the tasks of its member, each due on
+ * its own date.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct CreateTasksFromSummaryRequest {
+    pub session_id: SessionId,
+    pub action_items: Vec<ActionItemRequest>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ActionItemRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub due_date: String,
+}
+
+impl CreateTasksFromSummaryRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.session_id.trim().is_empty() {
+            errors.push(ValidationError::new("session_id", "Session id is a must."));
+        }
+
+        if self.action_items.is_empty() {
+            errors.push(ValidationError::new("action_items", "At least one action item is a must."));
+        }
+
+        if self.action_items.len() > MAX_ACTION_ITEMS {
+            errors.push(ValidationError::new("action_items", "A session should not make more than 20 tasks at once."));
+        }
+
+        for item in &self.action_items {
+            if item.name.trim().is_empty() {
+                errors.push(ValidationError::new("name", "Every action item should have a name."));
+            }
+
+            let given_time = item.due_date.as_str();
+
+            if !util::is_valid_date(given_time) {
+                errors.push(ValidationError::new("due_date", "unparsable date."));
+            } else if util::is_past_date(util::as_date(given_time)) {
+                errors.push(ValidationError::new("due_date", "should be a future time."));
+            }
+        }
+
+        errors
+    }
+}
+
 #[derive(Insertable)]
 #[table_name = "tasks"]
 pub struct NewTask {
@@ -293,6 +391,7 @@ pub struct NewTask {
     pub original_end_date: NaiveDateTime,
     pub description: String,
     pub name: String,
+    pub session_id: Option<String>,
 }
 
 impl NewTask {
@@ -312,6 +411,54 @@ impl NewTask {
             original_end_date: end_date.unwrap_or(start_date),
             description: request.description.to_owned(),
             name: request.name.to_owned(),
+            session_id: None,
+        }
+    }
+
+    /**
+     * A task of the enrollment copied from a task of a master plan, done by the
+     * actor its role points to.
+     */
+    pub fn of_master_task(the_enrollment_id: &str, actor_id: &str, master_task: &MasterTask, task_name: &str, plan_name: &str, start_date: NaiveDateTime) -> NewTask {
+        let end_date = start_date.checked_add_signed(Duration::hours(master_task.duration as i64));
+
+        NewTask {
+            id: util::fuzzy_id(),
+            enrollment_id: the_enrollment_id.to_owned(),
+            actor_id: actor_id.to_owned(),
+            duration: master_task.duration,
+            original_start_date: start_date,
+            original_end_date: end_date.unwrap_or(start_date),
+            description: format!("{} of the plan {}", task_name, plan_name),
+            name: task_name.to_owned(),
+            session_id: None,
+        }
+    }
+
+    /**
+     * A task of the member made of an action item of the session. It is open from
+     * now until the item is due, in whole hours.
+     */
+    pub fn of_action_item(the_enrollment_id: &str, member_id: &str, session_id: &str, session_name: &str, item: &ActionItemRequest, now: NaiveDateTime) -> NewTask {
+        let due_date = util::as_date(item.due_date.as_str());
+        let minutes = (due_date - now).num_minutes().max(1);
+        let hours = (minutes + 59) / 60;
+
+        let description = match item.description.as_deref().map(str::trim) {
+            Some(value) if !value.is_empty() => value.to_owned(),
+            _ => format!("An action item of the session {}", session_name),
+        };
+
+        NewTask {
+            id: util::fuzzy_id(),
+            enrollment_id: the_enrollment_id.to_owned(),
+            actor_id: member_id.to_owned(),
+            duration: hours as i32,
+            original_start_date: now,
+            original_end_date: due_date,
+            description,
+            name: item.name.trim().to_owned(),
+            session_id: Some(session_id.to_owned()),
         }
     }
 }
@@ -361,4 +508,41 @@ pub enum MemberTargetState {
 pub struct ChangeMemberTaskStateRequest {
     pub id: String,
     pub target_state: MemberTargetState,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn item(description: Option<&str>) -> ActionItemRequest {
+        ActionItemRequest {
+            name: String::from(" Keep the journal "),
+            description: description.map(String::from),
+            due_date: String::from("2021-03-17T10:30:00Z"),
+        }
+    }
+
+    #[test]
+    fn should_make_a_task_of_the_member_due_with_the_action_item() {
+        let task = NewTask::of_action_item("e-1", "m-1", "s-1", "Clarity", &item(None), at("2021-03-15T08:00"));
+
+        assert_eq!("m-1", task.actor_id);
+        assert_eq!(Some(String::from("s-1")), task.session_id);
+        assert_eq!("Keep the journal", task.name);
+        assert_eq!("An action item of the session Clarity", task.description);
+        assert_eq!(at("2021-03-17T10:30"), task.original_end_date);
+        assert_eq!(51, task.duration);
+    }
+
+    #[test]
+    fn should_keep_the_description_given_to_the_action_item() {
+        let task = NewTask::of_action_item("e-1", "m-1", "s-1", "Clarity", &item(Some("A page a day")), at("2021-03-15T08:00"));
+
+        assert_eq!("A page a day", task.description);
+    }
+}
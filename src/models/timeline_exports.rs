@@ -0,0 +1,134 @@
+/**
+ * The sessions and the tasks of a member, one line each, for the CSV export of
+ * the timeline. The lines of an enrollment come together, the sessions first.
+ */
+use chrono::NaiveDateTime;
+
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+
+pub const TIMELINE_HEADER: &str = "program,enrollment_id,kind,name,scheduled_start,scheduled_end,duration_minutes,actual_start,actual_end,status,responded_at,notes\r\n";
+
+const SESSION: &str = "session";
+const TASK: &str = "task";
+
+// A title is the first line of the note, kept short.
+const TITLE_LENGTH: usize = 80;
+
+pub struct TimelineRow {
+    pub program: String,
+    pub enrollment_id: String,
+    pub kind: &'static str,
+    pub name: String,
+    pub scheduled_start: NaiveDateTime,
+    pub scheduled_end: NaiveDateTime,
+    pub duration_minutes: i32,
+    pub actual_start: Option<NaiveDateTime>,
+    pub actual_end: Option<NaiveDateTime>,
+    pub status: String,
+    pub responded_at: Option<NaiveDateTime>,
+    pub notes: Vec<String>,
+}
+
+impl TimelineRow {
+    pub fn from_session(program: &str, session: &Session, note_titles: Vec<String>) -> TimelineRow {
+        TimelineRow {
+            program: program.to_owned(),
+            enrollment_id: session.enrollment_id.to_string(),
+            kind: SESSION,
+            name: session.name.to_owned(),
+            scheduled_start: session.revised_start_date.unwrap_or(session.original_start_date),
+            scheduled_end: session.revised_end_date.unwrap_or(session.original_end_date),
+            duration_minutes: session.duration,
+            actual_start: session.actual_start_date,
+            actual_end: session.actual_end_date,
+            status: session.status_label(),
+            responded_at: None,
+            notes: note_titles,
+        }
+    }
+
+    pub fn from_task(program: &str, task: &Task) -> TimelineRow {
+        TimelineRow {
+            program: program.to_owned(),
+            enrollment_id: task.enrollment_id.to_owned(),
+            kind: TASK,
+            name: task.name.to_owned(),
+            scheduled_start: task.revised_start_date.unwrap_or(task.original_start_date),
+            scheduled_end: task.revised_end_date.unwrap_or(task.original_end_date),
+            duration_minutes: task.duration * 60,
+            actual_start: task.actual_start_date,
+            actual_end: task.actual_end_date,
+            status: task.status_label(),
+            responded_at: task.responded_date,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let fields = vec![
+            csv_field(self.program.as_str()),
+            csv_field(self.enrollment_id.as_str()),
+            self.kind.to_owned(),
+            csv_field(self.name.as_str()),
+            format_time(Some(self.scheduled_start)),
+            format_time(Some(self.scheduled_end)),
+            self.duration_minutes.to_string(),
+            format_time(self.actual_start),
+            format_time(self.actual_end),
+            self.status.to_owned(),
+            format_time(self.responded_at),
+            csv_field(self.notes.join("; ").as_str()),
+        ];
+
+        format!("{}\r\n", fields.join(","))
+    }
+}
+
+pub fn note_title(description: &str) -> String {
+    let first_line = description.trim().lines().next().unwrap_or_default();
+    first_line.chars().take(TITLE_LENGTH).collect::<String>().trim().to_owned()
+}
+
+/**
+ * Quoted when it holds a separator, a quote or a line break, as RFC 4180 asks.
+ * A leading =, +, - or @ is escaped, so that a spreadsheet does not take the
+ * cell for a formula.
+ */
+pub fn csv_field(value: &str) -> String {
+    let value = match value.chars().next() {
+        Some('=') | Some('+') | Some('-') | Some('@') => format!("'{}", value),
+        _ => value.to_owned(),
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+
+    value
+}
+
+pub fn format_time(value: Option<NaiveDateTime>) -> String {
+    value.map(|time| time.format("%Y-%m-%dT%H:%M:%SZ").to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_escape_the_fields() {
+        assert_eq!("Goals", csv_field("Goals"));
+        assert_eq!("\"Goals, and habits\"", csv_field("Goals, and habits"));
+        assert_eq!("\"Read \"\"Deep Work\"\"\"", csv_field("Read \"Deep Work\""));
+        assert_eq!("'=SUM(A1:A2)", csv_field("=SUM(A1:A2)"));
+    }
+
+    #[test]
+    fn should_title_a_note_by_its_first_line() {
+        assert_eq!("Agreed on the plan", note_title("  Agreed on the plan\nDetails follow"));
+        assert_eq!(TITLE_LENGTH, note_title("x".repeat(200).as_str()).len());
+        assert_eq!("", note_title(""));
+    }
+}
@@ -0,0 +1,188 @@
+/**
+ * A batch upload carries a JSON manifest part ahead of the file parts.
+ * Each entry of the manifest names a file part and tells where it belongs.
+ *
+ * {"files": [
+ *   {"part": "file1", "purpose": "notes", "session_user_id": "..", "note_id": ".."},
+ *   {"part": "file2", "purpose": "boards", "session_id": ".."}
+ * ]}
+ *
+ * A note file with a note_id is attached to that note; without it, the client
+ * passes the returned path along while creating the note, as before.
+ *
+ * Each file gets a result of its own: the name the client gave, the name it is
+ * stored under, its size and SHA-256 checksum, or else the error that failed it.
+ *
+ * A board file becomes the next version of its board, see board_versions.
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::upload_policy::UploadClass;
+
+pub const MANIFEST_PART: &str = "manifest";
+
+const NOTES: &str = "notes";
+const BOARDS: &str = "boards";
+
+// Guards the directory names we build from the manifest.
+fn is_safe_id(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ManifestEntry {
+    pub part: String,
+    pub purpose: String,
+    pub session_user_id: Option<String>,
+    pub session_id: Option<String>,
+    pub note_id: Option<String>,
+}
+
+impl ManifestEntry {
+    fn validate(&self) -> Option<String> {
+        match self.purpose.as_str() {
+            NOTES => match &self.session_user_id {
+                Some(value) if is_safe_id(value) => None,
+                _ => Some(format!("{}: a valid session_user_id is a must for the notes.", self.part)),
+            },
+            BOARDS => {
+                if self.note_id.is_some() {
+                    return Some(format!("{}: a board file cannot be attached to a note.", self.part));
+                }
+                match &self.session_id {
+                    Some(value) if is_safe_id(value) => None,
+                    _ => Some(format!("{}: a valid session_id is a must for the boards.", self.part)),
+                }
+            }
+            _ => Some(format!("{}: the purpose should be either notes or boards.", self.part)),
+        }
+    }
+
+    pub fn class(&self) -> UploadClass {
+        if self.purpose == BOARDS {
+            return UploadClass::Boards;
+        }
+
+        UploadClass::Notes
+    }
+
+    /**
+     * The same layout the single file uploads and the board listing use.
+     */
+    pub fn directory(&self, asset_dir: &str, file_key: &str) -> String {
+        if self.purpose == BOARDS {
+            return format!("{}/{}/boards", asset_dir, self.session_id.as_deref().unwrap_or_default());
+        }
+
+        format!("{}/{}/notes/{}", asset_dir, self.session_user_id.as_deref().unwrap_or_default(), file_key)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UploadManifest {
+    pub files: Vec<ManifestEntry>,
+}
+
+impl UploadManifest {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        if self.files.is_empty() {
+            errors.push(String::from("The manifest should describe at least one file."));
+        }
+
+        let mut parts: HashSet<&str> = HashSet::new();
+
+        for entry in &self.files {
+            if entry.part == MANIFEST_PART || !parts.insert(entry.part.as_str()) {
+                errors.push(format!("{}: the part name should be unique.", entry.part));
+            }
+
+            if let Some(error) = entry.validate() {
+                errors.push(error);
+            }
+        }
+
+        errors
+    }
+
+    pub fn entry(&self, part: &str) -> Option<&ManifestEntry> {
+        self.files.iter().find(|entry| entry.part == part)
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct UploadResult {
+    pub part: String,
+    pub file_name: String,
+    pub stored_name: Option<String>,
+    pub path: Option<String>,
+    pub size: i32,
+    pub file_type: String,
+    pub checksum: Option<String>,
+    pub note_id: Option<String>,
+    pub session_user_id: Option<String>,
+    pub session_id: Option<String>,
+    pub error: Option<String>,
+}
+
+impl UploadResult {
+    pub fn failed(part: &str, file_name: &str, error: &str) -> UploadResult {
+        UploadResult {
+            part: part.to_owned(),
+            file_name: file_name.to_owned(),
+            stored_name: None,
+            path: None,
+            size: 0,
+            file_type: String::from(""),
+            checksum: None,
+            note_id: None,
+            session_user_id: None,
+            session_id: None,
+            error: Some(error.to_owned()),
+        }
+    }
+
+    pub fn stored(entry: &ManifestEntry, file_name: &str, stored_name: String, path: String, size: i32, file_type: String, checksum: String) -> UploadResult {
+        UploadResult {
+            part: entry.part.to_owned(),
+            file_name: file_name.to_owned(),
+            stored_name: Some(stored_name),
+            path: Some(path),
+            size,
+            file_type,
+            checksum: Some(checksum),
+            note_id: entry.note_id.clone(),
+            session_user_id: entry.session_user_id.clone(),
+            session_id: entry.session_id.clone().filter(|_| entry.purpose == BOARDS),
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn manifest(json: &str) -> UploadManifest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn should_accept_notes_and_boards() {
+        let given = manifest(r#"{"files": [{"part": "a", "purpose": "notes", "session_user_id": "su-1"}, {"part": "b", "purpose": "boards", "session_id": "s-1"}]}"#);
+
+        assert!(given.validate().is_empty());
+        assert_eq!("/assets/s-1/boards", given.entry("b").unwrap().directory("/assets", "k"));
+        assert_eq!("/assets/su-1/notes/k", given.entry("a").unwrap().directory("/assets", "k"));
+    }
+
+    #[test]
+    fn should_reject_duplicate_parts_and_unsafe_ids() {
+        let given = manifest(r#"{"files": [{"part": "a", "purpose": "notes", "session_user_id": "../etc"}, {"part": "a", "purpose": "boards", "session_id": "s-1"}]}"#);
+
+        assert_eq!(2, given.validate().len());
+    }
+}
@@ -2,13 +2,19 @@ use diesel::prelude::*;
 
 use crate::commons::util;
 
-use crate::file_manager::{get_file_names, SESSION_ASSET_DIR};
+use crate::file_manager::get_file_names;
+use crate::storage::{storage, Area};
 
+use crate::models::board_annotations::BoardAnnotation;
 use crate::models::enrollments::{Enrollment, PlanCriteria};
 use crate::models::notes::Note;
+use crate::models::session_summaries::SessionSummary;
 use crate::models::sessions::Session;
 use crate::models::user_events::EventCriteria;
 
+use crate::services::board_annotations::get_annotations;
+use crate::services::session_summaries::get_enrollment_summaries;
+
 use crate::schema::enrollments;
 use crate::schema::session_notes;
 use crate::schema::sessions;
@@ -23,6 +29,7 @@ pub struct NoteRow {
     pub session: Session,
     pub note: Note,
     pub by: String,
+    pub summary: Option<SessionSummary>,
 }
 
 #[juniper::object]
@@ -36,6 +43,9 @@ impl NoteRow {
     pub fn by(&self) -> &String {
         &self.by
     }
+    pub fn summary(&self) -> Option<&SessionSummary> {
+        self.summary.as_ref()
+    }
 }
 
 pub fn get_enrollment_notes(connection: &MysqlConnection, criteria: PlanCriteria) -> Result<Vec<NoteRow>, diesel::result::Error> {
@@ -47,6 +57,9 @@ pub fn get_enrollment_notes(connection: &MysqlConnection, criteria: PlanCriteria
         .order_by(session_notes::updated_at.asc())
         .load(connection)?;
 
+    // The summary of a session comes along with each of its notes.
+    let summaries = get_enrollment_summaries(connection, &criteria.enrollment_id)?;
+
     let mut rows: Vec<NoteRow> = Vec::new();
     for item in artifact_rows {
         let enrollment = item.0;
@@ -55,7 +68,14 @@ pub fn get_enrollment_notes(connection: &MysqlConnection, criteria: PlanCriteria
 
         let by = if enrollment.member_id == note.created_by_id { util::MEMBER } else { util::COACH };
 
-        let note_row = NoteRow { session, note, by: String::from(by) };
+        let summary = summaries.iter().find(|summary| summary.session_id == session.id).cloned();
+
+        let note_row = NoteRow {
+            session,
+            note,
+            by: String::from(by),
+            summary,
+        };
 
         rows.push(note_row);
     }
@@ -66,6 +86,7 @@ pub fn get_enrollment_notes(connection: &MysqlConnection, criteria: PlanCriteria
 pub struct BoardRow {
     pub session: Session,
     pub urls: Vec<String>,
+    pub annotations: Vec<BoardAnnotation>,
 }
 
 #[juniper::object]
@@ -77,6 +98,10 @@ impl BoardRow {
     pub fn urls(&self) -> &Vec<String> {
         &self.urls
     }
+
+    pub fn annotations(&self) -> &Vec<BoardAnnotation> {
+        &self.annotations
+    }
 }
 
 type Row = (Enrollment, Session);
@@ -91,37 +116,57 @@ pub fn get_boards(connection: &MysqlConnection, criteria: EventCriteria) -> Resu
         .order_by(sessions::updated_at.asc())
         .load(connection)?;
 
-    Ok(get_session_boards(&rows))
+    let mut board_rows = get_session_boards(&rows);
+
+    let board_ids: Vec<String> = board_rows.iter().map(|row| artifact_id(&row.session)).collect();
+    let annotations = get_annotations(connection, &board_ids)?;
+
+    // The sessions of a conference share the boards, and so the annotations.
+    for row in board_rows.iter_mut() {
+        let the_board_id = artifact_id(&row.session);
+        row.annotations = annotations.iter().filter(|annotation| annotation.board_id == the_board_id).cloned().collect();
+    }
+
+    Ok(board_rows)
 }
 
 /**
  * Sessions without any boards will not be returned.
- * 
- * We store the boards against the conference id if the session is part
- * of a conference, So the url should be constructed with the conference id 
- * instead of session id for conference sessions.
  */
 fn get_session_boards(rows: &[Row]) -> Vec<BoardRow> {
     let mut board_rows: Vec<BoardRow> = Vec::new();
 
     for row in rows {
-        let mut dir_name: PathBuf = PathBuf::from(SESSION_ASSET_DIR);
-
-        let artifact_id = match &row.1.conference_id {
-            Some(value) => value.to_owned(),
-            None => row.1.id.to_owned()
-        };
-        
-        dir_name.push(artifact_id);
-        dir_name.push("boards");
-
-        if let Ok(urls) = get_file_names(dir_name) {
+        let urls = get_file_names(board_dir(&row.1)).unwrap_or_default();
+        if !urls.is_empty() {
             board_rows.push(BoardRow {
                 session: row.1.clone(),
                 urls,
+                annotations: Vec::new(),
             });
         }
     }
 
     board_rows
 }
+
+/**
+ * We store the boards against the conference id if the session is part
+ * of a conference, So the url should be constructed with the conference id
+ * instead of session id for conference sessions.
+ */
+pub fn artifact_id(session: &Session) -> String {
+    match &session.conference_id {
+        Some(value) => value.to_owned(),
+        None => session.id.to_string(),
+    }
+}
+
+pub fn board_dir(session: &Session) -> PathBuf {
+    let mut dir_name: PathBuf = storage().dir(Area::Sessions);
+
+    dir_name.push(artifact_id(session));
+    dir_name.push("boards");
+
+    dir_name
+}
@@ -1,12 +1,18 @@
 use diesel::prelude::*;
+use juniper::FieldResult;
+use std::collections::HashMap;
 
 use crate::commons::util;
-use crate::commons::chassis::QueryError;
+use crate::commons::chassis::{Page, QueryError, Window};
+use crate::commons::ids::SessionId;
+use crate::graphql_schema::DBContext;
+use crate::loaders::grouped;
 
 use crate::models::enrollments::Enrollment;
 use crate::models::notes::Note;
 use crate::models::objectives::Objective;
 use crate::models::programs::Program;
+use crate::models::session_merges::{find_duplicates, DUPLICATE_WARNING};
 use crate::models::session_users::SessionUser;
 use crate::models::sessions::Session;
 use crate::models::tasks::Task;
@@ -38,13 +44,16 @@ pub struct EventCriteria {
     pub end_date: Option<String>,
 }
 
+// The session, the program and the place of the user come in the one query of the page; the people of
+// the sessions in one more, when the first row asks for them, see loaders.
 pub struct EventRow {
     pub session: Session,
     pub program: Program,
     pub session_user: SessionUser,
+    pub duplicate_of: Option<SessionId>,
 }
 
-#[juniper::object]
+#[juniper::object(Context = DBContext)]
 impl EventRow {
     pub fn session(&self) -> &Session {
         &self.session
@@ -57,15 +66,50 @@ impl EventRow {
     pub fn sessionUser(&self) -> &SessionUser {
         &self.session_user
     }
+
+    #[graphql(description = "The original session when this one looks like a duplicate of it")]
+    pub fn duplicateOf(&self) -> Option<&str> {
+        self.duplicate_of.as_ref().map(|the_id| the_id.as_str())
+    }
+
+    pub fn warnings(&self) -> Vec<&str> {
+        self.duplicate_of.iter().map(|_| DUPLICATE_WARNING).collect()
+    }
+
+    #[graphql(description = "The coach and the members taking part in the session")]
+    pub fn people(&self, context: &DBContext) -> FieldResult<Vec<User>> {
+        let the_people = context.loaders.session_people.load(&self.session.id.to_string(), |keys| {
+            let connection = context.connection()?;
+            people_of(&connection, keys).map_err(|_| PEOPLE_ERROR)
+        })?;
+
+        Ok(the_people)
+    }
+}
+
+const PEOPLE_ERROR: &str = "Unable to find the people of the sessions.";
+
+/**
+ * The users taking part in the sessions, by the id of the session.
+ */
+pub fn people_of(connection: &MysqlConnection, session_ids: &[String]) -> QueryResult<HashMap<String, Vec<User>>> {
+    let pairs: Vec<(String, User)> = session_users
+        .inner_join(users)
+        .filter(session_users::session_id.eq_any(session_ids))
+        .select((session_users::session_id, crate::schema::users::all_columns))
+        .load(connection)?;
+
+    Ok(grouped(pairs))
 }
 
 type SessionProgram = (Session, Program, SessionUser);
 
-pub fn get_events(connection: &MysqlConnection, criteria: EventCriteria) -> Result<Vec<EventRow>, QueryError> {
+pub fn get_events(connection: &MysqlConnection, criteria: EventCriteria, window: &Window) -> Result<Page<EventRow>, QueryError> {
     let mut query = sessions
         .inner_join(programs)
         .inner_join(session_users)
         .filter(session_users::user_id.eq(criteria.user_id))
+        .filter(sessions::expired_at.is_null())
         .order_by(sessions::original_start_date.asc())
         .into_boxed();
 
@@ -83,17 +127,22 @@ pub fn get_events(connection: &MysqlConnection, criteria: EventCriteria) -> Resu
         query = query.filter(sessions::original_start_date.le(end_date));
     }
 
-    let rows: Vec<EventRow> = query
-        .load::<SessionProgram>(connection)?
+    let tuples = query.offset(window.offset).limit(window.fetch()).load::<SessionProgram>(connection)?;
+
+    let listed: Vec<Session> = tuples.iter().map(|tuple| tuple.0.clone()).collect();
+    let mut duplicates = find_duplicates(&listed);
+
+    let rows: Vec<EventRow> = tuples
         .into_iter()
         .map(|tuple| EventRow {
+            duplicate_of: duplicates.remove(&tuple.0.id),
             session: tuple.0,
             program: tuple.1,
             session_user: tuple.2,
         })
         .collect();
 
-    Ok(rows)
+    Ok(Page::of(rows, window))
 }
 
 pub struct PlanRow {
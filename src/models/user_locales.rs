@@ -0,0 +1,207 @@
+/**
+ * The locale and the time zone of a user, detected from the client on the first
+ * login and kept as the defaults of the user afterwards. The time zone is the IANA
+ * name given by the client; the scheduling rules and the mails still go by the
+ * utc_offset of the user, which is settled along with it.
+ */
+use serde::{Deserialize, Serialize};
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::ids::UserId;
+use crate::schema::user_locales;
+
+pub const DEFAULT_LOCALE: &str = "en";
+pub const DEFAULT_TIME_ZONE: &str = "UTC";
+
+const MAX_LOCALE: usize = 35;
+const MAX_TIME_ZONE: usize = 64;
+
+// Minutes ahead of UTC, from Baker Island to the Line Islands.
+const MIN_OFFSET: i32 = -12 * 60;
+const MAX_OFFSET: i32 = 14 * 60;
+
+#[derive(Queryable, Debug)]
+pub struct UserLocale {
+    pub user_id: String,
+    pub locale: String,
+    pub time_zone: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "user_locales"]
+pub struct NewUserLocale {
+    pub user_id: String,
+    pub locale: String,
+    pub time_zone: String,
+}
+
+// What the client tells of itself; the locale comes from the Accept-Language.
+#[derive(Deserialize, Debug)]
+pub struct LocaleDetection {
+    pub time_zone: Option<String>,
+    pub utc_offset: Option<i32>,
+}
+
+/**
+ * The locale and the time zone the UI should use.
+ */
+#[derive(Serialize, Debug)]
+pub struct LocaleBundle {
+    pub user_id: String,
+    pub locale: String,
+    pub time_zone: String,
+    pub utc_offset: i32,
+    pub first_detection: bool,
+}
+
+#[juniper::object(description = "The locale and the time zone of a user")]
+impl LocaleBundle {
+    pub fn user_id(&self) -> &str {
+        self.user_id.as_str()
+    }
+
+    pub fn locale(&self) -> &str {
+        self.locale.as_str()
+    }
+
+    pub fn time_zone(&self) -> &str {
+        self.time_zone.as_str()
+    }
+
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct LocaleRequest {
+    pub user_id: UserId,
+    pub locale: String,
+    pub time_zone: String,
+    pub utc_offset: i32,
+}
+
+impl LocaleRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        if !is_valid_locale(self.locale.as_str()) {
+            errors.push(ValidationError::new("locale", "The locale should be a language tag like en-US."));
+        }
+
+        if !is_valid_time_zone(self.time_zone.as_str()) {
+            errors.push(ValidationError::new("time_zone", "The time zone should be an IANA zone like Asia/Kolkata."));
+        }
+
+        if !is_valid_offset(self.utc_offset) {
+            errors.push(ValidationError::new("utc_offset", "The offset should be within -720 and 840 minutes."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * The language of the highest weight in an Accept-Language header; a language
+ * with no weight weighs 1. The wildcard and the malformed tags are skipped.
+ */
+pub fn preferred_locale(header: &str) -> Option<String> {
+    let mut best: Option<(String, f32)> = None;
+
+    for part in header.split(',') {
+        let mut pieces = part.split(';');
+        let tag = pieces.next().unwrap_or("").trim();
+
+        let weight = pieces
+            .filter_map(|piece| piece.trim().strip_prefix("q="))
+            .next()
+            .map(|value| value.trim().parse::<f32>().unwrap_or(0.0))
+            .unwrap_or(1.0);
+
+        if tag == "*" || weight <= 0.0 || !is_valid_locale(tag) {
+            continue;
+        }
+
+        if best.as_ref().map(|(_, top)| weight > *top).unwrap_or(true) {
+            best = Some((tag.to_owned(), weight));
+        }
+    }
+
+    best.map(|(tag, _)| normalize_locale(tag.as_str()))
+}
+
+// en-us becomes en-US, as the UI expects.
+fn normalize_locale(tag: &str) -> String {
+    let mut parts = tag.split('-');
+    let language = parts.next().unwrap_or("").to_lowercase();
+
+    let rest: Vec<String> = parts.map(|part| if part.len() == 2 { part.to_uppercase() } else { part.to_owned() }).collect();
+
+    std::iter::once(language).chain(rest).collect::<Vec<String>>().join("-")
+}
+
+pub fn is_valid_locale(tag: &str) -> bool {
+    let tag = tag.trim();
+
+    if tag.is_empty() || tag.len() > MAX_LOCALE {
+        return false;
+    }
+
+    let mut parts = tag.split('-');
+    let language = parts.next().unwrap_or("");
+
+    let language_ok = (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic());
+    language_ok && parts.all(|part| (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/**
+ * The shape of an IANA name, like Asia/Kolkata or America/Argentina/Buenos_Aires;
+ * we carry no zone database to check the name against.
+ */
+pub fn is_valid_time_zone(zone: &str) -> bool {
+    if zone == DEFAULT_TIME_ZONE {
+        return true;
+    }
+
+    if zone.is_empty() || zone.len() > MAX_TIME_ZONE || zone.starts_with('/') || zone.ends_with('/') || !zone.contains('/') {
+        return false;
+    }
+
+    zone.split('/').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+'))
+}
+
+pub fn is_valid_offset(utc_offset: i32) -> bool {
+    (MIN_OFFSET..=MAX_OFFSET).contains(&utc_offset)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_prefer_the_heaviest_language() {
+        assert_eq!(Some(String::from("ta-IN")), preferred_locale("en;q=0.8, ta-in, *;q=0.1"));
+        assert_eq!(Some(String::from("fr")), preferred_locale("de;q=0.5,fr;q=0.9"));
+        assert_eq!(Some(String::from("de")), preferred_locale("de,fr"));
+        assert_eq!(None, preferred_locale("*"));
+        assert_eq!(None, preferred_locale("en;q=0"));
+        assert_eq!(None, preferred_locale(""));
+    }
+
+    #[test]
+    fn should_check_the_shape_of_a_zone() {
+        assert!(is_valid_time_zone("Asia/Kolkata"));
+        assert!(is_valid_time_zone("America/Argentina/Buenos_Aires"));
+        assert!(is_valid_time_zone("Etc/GMT+5"));
+        assert!(is_valid_time_zone("UTC"));
+
+        assert!(!is_valid_time_zone("Kolkata"));
+        assert!(!is_valid_time_zone("Asia//Kolkata"));
+        assert!(!is_valid_time_zone("Asia/Kolkata; drop"));
+    }
+}
@@ -0,0 +1,171 @@
+/**
+ * What a user tells about oneself: a headline, a few words about, the
+ * experience and the links to the social profiles. The coaches show it as
+ * their credentials in the catalog; the members to their coaches.
+ *
+ * The social links are kept one per line. A member may turn off the weekly
+ * digest of the enrollments here.
+ */
+use chrono::NaiveDateTime;
+
+use crate::commons::chassis::ValidationError;
+use crate::schema::user_profiles;
+
+const MAX_HEADLINE: usize = 255;
+const MAX_TEXT: usize = 2000;
+const MAX_LINKS: usize = 5;
+const MAX_LINK: usize = 255;
+
+#[derive(Queryable, Debug, Clone, Identifiable)]
+#[primary_key(user_id)]
+pub struct UserProfile {
+    pub user_id: String,
+    pub headline: String,
+    pub about: String,
+    pub experience: String,
+    pub social_links: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub weekly_digest: bool,
+}
+
+#[juniper::object(description = "What a user tells about oneself")]
+impl UserProfile {
+    pub fn user_id(&self) -> &str {
+        self.user_id.as_str()
+    }
+
+    pub fn headline(&self) -> &str {
+        self.headline.as_str()
+    }
+
+    pub fn about(&self) -> &str {
+        self.about.as_str()
+    }
+
+    pub fn experience(&self) -> &str {
+        self.experience.as_str()
+    }
+
+    pub fn social_links(&self) -> Vec<String> {
+        from_lines(self.social_links.as_str())
+    }
+
+    pub fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+
+    #[graphql(description = "Whether the weekly digest of the enrollments is mailed")]
+    pub fn weekly_digest(&self) -> bool {
+        self.weekly_digest
+    }
+}
+
+/**
+ * The whole profile is saved at once; a field left empty is cleared. The weekly
+ * digest stays as it is unless told. The profile is the one of the signed in
+ * user, never of an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct UserProfileRequest {
+    pub headline: String,
+    pub about: String,
+    pub experience: String,
+    pub social_links: Vec<String>,
+    pub weekly_digest: Option<bool>,
+}
+
+impl UserProfileRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.headline.trim().chars().count() > MAX_HEADLINE {
+            errors.push(ValidationError::new("headline", "The headline may have at most 255 characters."));
+        }
+
+        if self.about.trim().chars().count() > MAX_TEXT {
+            errors.push(ValidationError::new("about", "The about may have at most 2000 characters."));
+        }
+
+        if self.experience.trim().chars().count() > MAX_TEXT {
+            errors.push(ValidationError::new("experience", "The experience may have at most 2000 characters."));
+        }
+
+        let links = self.links();
+        if links.len() > MAX_LINKS || links.iter().any(|link| !is_valid_link(link)) {
+            errors.push(ValidationError::new("social_links", "A user may share at most 5 links, each an http or https address of at most 255 characters."));
+        }
+
+        errors
+    }
+
+    fn links(&self) -> Vec<&str> {
+        self.social_links.iter().map(|link| link.trim()).filter(|link| !link.is_empty()).collect()
+    }
+}
+
+fn is_valid_link(link: &str) -> bool {
+    let has_scheme = link.starts_with("https://") || link.starts_with("http://");
+
+    has_scheme && link.chars().count() <= MAX_LINK && !link.chars().any(char::is_whitespace)
+}
+
+fn from_lines(value: &str) -> Vec<String> {
+    value.lines().filter(|line| !line.is_empty()).map(|line| line.to_owned()).collect()
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "user_profiles"]
+pub struct NewUserProfile {
+    pub user_id: String,
+    pub headline: String,
+    pub about: String,
+    pub experience: String,
+    pub social_links: String,
+    pub weekly_digest: Option<bool>,
+}
+
+impl NewUserProfile {
+    pub fn from(request: &UserProfileRequest, user_id: &str) -> NewUserProfile {
+        NewUserProfile {
+            user_id: user_id.to_owned(),
+            headline: request.headline.trim().to_owned(),
+            about: request.about.trim().to_owned(),
+            experience: request.experience.trim().to_owned(),
+            social_links: request.links().join("\n"),
+            weekly_digest: request.weekly_digest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn request(links: &[&str]) -> UserProfileRequest {
+        UserProfileRequest {
+            headline: String::from("Leadership coach"),
+            about: String::from("Ten years with the first time managers."),
+            experience: String::from("ICF certified."),
+            social_links: links.iter().map(|link| link.to_string()).collect(),
+            weekly_digest: None,
+        }
+    }
+
+    #[test]
+    fn should_keep_the_links_one_per_line() {
+        let profile = NewUserProfile::from(&request(&[" https://example.com/a ", "", "http://example.com/b"]), "u-1");
+
+        assert_eq!("https://example.com/a\nhttp://example.com/b", profile.social_links);
+        assert_eq!(vec!["https://example.com/a", "http://example.com/b"], from_lines(profile.social_links.as_str()));
+    }
+
+    #[test]
+    fn should_take_only_the_web_links() {
+        assert!(request(&["https://example.com/a"]).validate().is_empty());
+        assert_eq!(1, request(&["javascript:alert(1)"]).validate().len());
+        assert_eq!(1, request(&["https://example.com/a b"]).validate().len());
+        assert_eq!(1, request(&["https://example.com"; 6]).validate().len());
+    }
+}
@@ -1,13 +1,27 @@
+use diesel::dsl::sql;
 use diesel::prelude::*;
-
+use diesel::sql_types::BigInt;
+use juniper::FieldResult;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::commons::cache::TtlCache;
+use crate::commons::chassis::{Page, Window};
+use crate::graphql_schema::DBContext;
+use crate::loaders::grouped;
+use crate::models::coach_profiles::tag_pattern;
 use crate::models::coaches::Coach;
 use crate::models::enrollments::Enrollment;
-use crate::models::programs::Program;
+use crate::models::programs::{text_pattern, Program, ProgramStatus};
+use crate::models::user_profiles::UserProfile;
+use crate::services::program_reviews::rating_summaries;
+use crate::services::user_profiles::profiles_of;
 
 use crate::schema::coaches::dsl::*;
 use crate::schema::enrollments::dsl::*;
 use crate::schema::programs;
 use crate::schema::programs::dsl::*;
+use crate::schema::users;
 
 #[derive(juniper::GraphQLEnum)]
 pub enum Desire {
@@ -17,28 +31,81 @@ pub enum Desire {
     SINGLE,
 }
 
+/**
+ * Without a sort, the programs are explored in the order of their last change.
+ */
+#[derive(juniper::GraphQLEnum)]
+pub enum ProgramSort {
+    NEWEST,
+    POPULAR,
+}
+
+/**
+ * The tag, the genre, the coach, the text and the sort narrow the EXPLORE desire alone.
+ * Only the published programs are explored; the coach finds the drafts among
+ * YOURS.
+ */
 #[derive(juniper::GraphQLInputObject)]
 pub struct ProgramCriteria {
     user_id: String,
     program_id: String,
     desire: Desire,
+    tag: Option<String>,
+    genre_id: Option<String>,
+    text: Option<String>,
+    sort: Option<ProgramSort>,
+    coach_id: Option<String>,
 }
 
-#[derive(juniper::GraphQLEnum)]
+impl ProgramCriteria {
+    /**
+     * The catalog as the visitors see it, narrowed by a tag and a coach; see
+     * catalog_v1.
+     */
+    pub fn explore(tag: Option<String>, the_coach_id: Option<String>) -> ProgramCriteria {
+        ProgramCriteria {
+            user_id: String::new(),
+            program_id: String::new(),
+            desire: Desire::EXPLORE,
+            tag,
+            genre_id: None,
+            text: None,
+            sort: None,
+            coach_id: the_coach_id,
+        }
+    }
+}
+
+// The members in the program and in its peer programs, leaving out the coaches and the cancelled enrollments.
+const MEMBER_COUNT: &str = "(SELECT COUNT(*) FROM enrollments e INNER JOIN programs p ON p.id = e.program_id \
+    WHERE p.parent_program_id = programs.id AND e.member_id <> p.coach_id AND e.ended_at IS NULL)";
+
+#[derive(juniper::GraphQLEnum, Clone)]
 pub enum EnrollmentStatus {
     UNKNOWN,
     YES,
     NO,
 }
 
+/**
+ * A row comes out of the query of its page: the coach with the join, the
+ * ratings and the profile of the coach with one lookup for the page, see
+ * with_ratings. The peer coaches are loaded for the page at once when the
+ * first row asks for them, see loaders, so a page of programs costs the same
+ * few queries whatever its size.
+ */
+#[derive(Clone)]
 pub struct ProgramRow {
     pub program: Program,
     pub coach: Coach,
     pub enrollment_id: String,
     pub enrollment_status: EnrollmentStatus,
+    pub average_rating: Option<f64>,
+    pub review_count: i32,
+    pub coach_profile: Option<UserProfile>,
 }
 
-#[juniper::object]
+#[juniper::object(Context = DBContext)]
 impl ProgramRow {
     pub fn program(&self) -> &Program {
         &self.program
@@ -55,19 +122,131 @@ impl ProgramRow {
     pub fn enrollment_id(&self) -> &str {
         &self.enrollment_id
     }
+
+    #[graphql(description = "The average of the visible ratings, to a tenth; none before the first review")]
+    pub fn average_rating(&self) -> Option<f64> {
+        self.average_rating
+    }
+
+    pub fn review_count(&self) -> i32 {
+        self.review_count
+    }
+
+    #[graphql(description = "The headline, the experience and the links of the coach, for the credentials in the catalog")]
+    pub fn coach_profile(&self) -> Option<&UserProfile> {
+        self.coach_profile.as_ref()
+    }
+
+    #[graphql(description = "The coaches of the program and of its peer programs")]
+    pub fn peer_coaches(&self, context: &DBContext) -> FieldResult<Vec<Coach>> {
+        let key = self.program.coalesce_parent_id().to_string();
+
+        let peers = context.loaders.peer_coaches.load(&key, |keys| {
+            let connection = context.connection()?;
+            peer_coaches_of(&connection, keys).map_err(|_| PEER_COACHES_ERROR)
+        })?;
+
+        Ok(peers)
+    }
+}
+
+const PEER_COACHES_ERROR: &str = "Unable to find the coaches of the programs.";
+
+/**
+ * The coaches of the program families, active ones alone, by the id of their parent program.
+ */
+pub fn peer_coaches_of(connection: &MysqlConnection, parent_ids: &[String]) -> QueryResult<HashMap<String, Vec<Coach>>> {
+    let pairs: Vec<(Option<String>, Coach)> = programs
+        .inner_join(coaches)
+        .filter(parent_program_id.eq_any(parent_ids))
+        .filter(user_id.eq_any(users::table.filter(users::blocked.eq(false)).select(users::id)))
+        .select((parent_program_id, crate::schema::coaches::all_columns))
+        .load(connection)?;
+
+    Ok(grouped(pairs.into_iter().filter_map(|(parent_id, coach)| parent_id.map(|parent_id| (parent_id, coach))).collect()))
 }
 
 type ProgramType = (Program, Coach);
 
 pub type ProgramResult = Result<Vec<ProgramRow>, diesel::result::Error>;
 
-pub fn get_programs(connection: &MysqlConnection, criteria: &ProgramCriteria) -> ProgramResult {
-    match &criteria.desire {
-        Desire::EXPLORE => get_latest_programs(connection),
-        Desire::ENROLLED => get_enrolled_programs(connection, criteria),
-        Desire::YOURS => get_coach_programs(connection, criteria),
-        Desire::SINGLE => find_program(connection, criteria),
+/**
+ * The lists are read a window at a time; a single program is a page of one.
+ */
+pub fn get_programs(connection: &MysqlConnection, criteria: &ProgramCriteria, window: &Window) -> Result<Page<ProgramRow>, diesel::result::Error> {
+    let load = || -> ProgramResult {
+        let rows = match &criteria.desire {
+            Desire::EXPLORE => get_latest_programs(connection, criteria, window),
+            Desire::ENROLLED => get_enrolled_programs(connection, criteria, window),
+            Desire::YOURS => get_coach_programs(connection, criteria, window),
+            Desire::SINGLE => find_program(connection, criteria),
+        }?;
+
+        let rows = with_ratings(connection, rows)?;
+        with_coach_profiles(connection, rows)
+    };
+
+    // The catalog is the same for everyone exploring it; see forget_catalog.
+    let rows = match &criteria.desire {
+        Desire::EXPLORE => catalog().get_or_load(&catalog_key(criteria, window), load)?,
+        _ => load()?,
+    };
+
+    Ok(Page::of(rows, window))
+}
+
+type CatalogKey = (Option<String>, Option<String>, Option<String>, Option<String>, Option<&'static str>, i64, i64);
+
+const CATALOG_PAGES: usize = 256;
+
+fn catalog() -> &'static TtlCache<CatalogKey, Vec<ProgramRow>> {
+    static CATALOG: OnceLock<TtlCache<CatalogKey, Vec<ProgramRow>>> = OnceLock::new();
+    CATALOG.get_or_init(|| TtlCache::from_env(CATALOG_PAGES))
+}
+
+fn catalog_key(criteria: &ProgramCriteria, window: &Window) -> CatalogKey {
+    let sort = criteria.sort.as_ref().map(|sort| match sort {
+        ProgramSort::NEWEST => "newest",
+        ProgramSort::POPULAR => "popular",
+    });
+
+    (criteria.tag.clone(), criteria.genre_id.clone(), criteria.coach_id.clone(), criteria.text.clone(), sort, window.offset, window.limit)
+}
+
+/**
+ * The pages of the catalog are read afresh; called on the changes that show in
+ * it: the programs, their reviews, the profiles and the accounts of the coaches.
+ */
+pub fn forget_catalog() {
+    catalog().clear();
+}
+
+/**
+ * The reviews are kept on the parent program, so a peer program shows the rating of its parent.
+ */
+fn with_ratings(connection: &MysqlConnection, mut rows: Vec<ProgramRow>) -> ProgramResult {
+    let parent_ids: Vec<&str> = rows.iter().map(|row| row.program.coalesce_parent_id().as_str()).collect();
+    let summaries = rating_summaries(connection, &parent_ids)?;
+
+    for row in rows.iter_mut() {
+        if let Some((average, count)) = summaries.get(row.program.coalesce_parent_id().as_str()) {
+            row.average_rating = *average;
+            row.review_count = *count;
+        }
     }
+
+    Ok(rows)
+}
+
+fn with_coach_profiles(connection: &MysqlConnection, mut rows: Vec<ProgramRow>) -> ProgramResult {
+    let coach_ids: Vec<&str> = rows.iter().map(|row| row.coach.id.as_str()).collect();
+    let mut profiles = profiles_of(connection, &coach_ids)?;
+
+    for row in rows.iter_mut() {
+        row.coach_profile = profiles.remove(row.coach.id.as_str());
+    }
+
+    Ok(rows)
 }
 
 /**
@@ -117,6 +296,9 @@ fn find_program(connection: &MysqlConnection, criteria: &ProgramCriteria) -> Pro
         coach,
         enrollment_id: String::from(""),
         enrollment_status: EnrollmentStatus::NO,
+        average_rating: None,
+        review_count: 0,
+        coach_profile: None,
     };
 
     Ok(vec![program_row])
@@ -131,17 +313,26 @@ fn find_enrolled_program(connection: &MysqlConnection, enrollment: &Enrollment)
     let program_row = ProgramRow {
         program:result.0,
         coach:result.1,
-        enrollment_id: enrollment.id.to_owned(),
+        enrollment_id: enrollment.id.to_string(),
         enrollment_status: EnrollmentStatus::YES,
+        average_rating: None,
+        review_count: 0,
+        coach_profile: None,
     };
 
     Ok(vec![program_row])
 }
 
-fn get_enrolled_programs(connection: &MysqlConnection, criteria: &ProgramCriteria) -> ProgramResult {
+fn get_enrolled_programs(connection: &MysqlConnection, criteria: &ProgramCriteria, window: &Window) -> ProgramResult {
     type Row = (Enrollment, ProgramType);
 
-    let data: Vec<Row> = enrollments.inner_join(programs.inner_join(coaches)).filter(member_id.eq(&criteria.user_id)).load(connection)?;
+    let data: Vec<Row> = enrollments
+        .inner_join(programs.inner_join(coaches))
+        .filter(member_id.eq(&criteria.user_id))
+        .order_by(crate::schema::enrollments::created_at.desc())
+        .offset(window.offset)
+        .limit(window.fetch())
+        .load(connection)?;
 
     let mut rows: Vec<ProgramRow> = Vec::new();
 
@@ -151,33 +342,67 @@ fn get_enrolled_programs(connection: &MysqlConnection, criteria: &ProgramCriteri
         rows.push(ProgramRow {
             program: pc.0,
             coach: pc.1,
-            enrollment_id: enrollment.id,
+            enrollment_id: enrollment.id.to_string(),
             enrollment_status: EnrollmentStatus::YES,
+            average_rating: None,
+            review_count: 0,
+            coach_profile: None,
         });
     }
 
     Ok(rows)
 }
 
-fn get_coach_programs(connection: &MysqlConnection, criteria: &ProgramCriteria) -> ProgramResult {
+fn get_coach_programs(connection: &MysqlConnection, criteria: &ProgramCriteria, window: &Window) -> ProgramResult {
     use crate::schema::coaches::dsl::id;
 
-    let data: Vec<ProgramType> = programs.inner_join(coaches).filter(id.eq(&criteria.user_id)).order_by(name.asc()).load(connection)?;
+    let data: Vec<ProgramType> = programs
+        .inner_join(coaches)
+        .filter(id.eq(&criteria.user_id))
+        .order_by(name.asc())
+        .offset(window.offset)
+        .limit(window.fetch())
+        .load(connection)?;
 
     Ok(to_program_rows(data))
 }
 
-fn get_latest_programs(connection: &MysqlConnection) -> ProgramResult {
-    use crate::schema::programs::dsl::updated_at;
+fn get_latest_programs(connection: &MysqlConnection, criteria: &ProgramCriteria, window: &Window) -> ProgramResult {
+    use crate::schema::programs::dsl::{created_at, updated_at};
 
-    let data: Vec<ProgramType> = programs
+    let mut query = programs
         .inner_join(coaches)
-        .order_by(updated_at.asc())
         .filter(active.eq(true))
+        .filter(status.eq(ProgramStatus::PUBLISHED.as_str()))
         .filter(is_private.eq(false))
         .filter(is_parent.eq(true))
-        .limit(10)
-        .load(connection)?;
+        .filter(user_id.eq_any(users::table.filter(users::blocked.eq(false)).select(users::id)))
+        .into_boxed();
+
+    if let Some(value) = &criteria.tag {
+        query = query.filter(tags.like(tag_pattern(value)));
+    }
+
+    if let Some(value) = &criteria.genre_id {
+        query = query.filter(genre_id.eq(value));
+    }
+
+    if let Some(value) = &criteria.coach_id {
+        query = query.filter(programs::coach_id.eq(value));
+    }
+
+    if let Some(value) = criteria.text.as_ref().filter(|value| !value.trim().is_empty()) {
+        let pattern = text_pattern(value);
+        query = query.filter(name.like(pattern.to_owned()).or(description_text.like(pattern)));
+    }
+
+    query = match criteria.sort {
+        None => query.order_by(updated_at.asc()),
+        Some(ProgramSort::NEWEST) => query.order_by((created_at.desc(), programs::id.asc())),
+        Some(ProgramSort::POPULAR) => query.order_by((sql::<BigInt>(MEMBER_COUNT).desc(), created_at.desc(), programs::id.asc())),
+    };
+
+    let data: Vec<ProgramType> = query.offset(window.offset).limit(window.fetch()).load(connection)?;
 
     Ok(to_program_rows(data))
 }
@@ -191,6 +416,9 @@ fn to_program_rows(data: Vec<ProgramType>) -> Vec<ProgramRow> {
             coach: pc.1,
             enrollment_id: String::from(""),
             enrollment_status: EnrollmentStatus::UNKNOWN,
+            average_rating: None,
+            review_count: 0,
+            coach_profile: None,
         });
     }
 
@@ -7,6 +7,8 @@ use chrono::NaiveDateTime;
 use super::ferror::{Ferror};
 
 use crate::commons::chassis::{ValidationError};
+use crate::commons::ids::UserId;
+use crate::commons::password;
 use crate::commons::util;
 use crate::schema::users;
 
@@ -15,7 +17,7 @@ use crate::schema::users;
 // See the Juniper:object for the fields we exposed to outside
 #[derive(Clone, Queryable, Debug, Identifiable)]
 pub struct User {
-    pub id: String,
+    pub id: UserId,
     pub full_name: String,
     pub email: String,
     pub blocked: bool,
@@ -23,6 +25,8 @@ pub struct User {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub password: String,
+    pub utc_offset: i32,
+    pub email_invalid: bool,
 }
 
 // Fields that we can safely expose to APIs
@@ -43,6 +47,38 @@ impl User {
     pub fn user_type(&self) -> &str {
         self.user_type.as_str()
     }
+
+    // Minutes ahead of UTC; the scheduling rules use it to find the local time of the member.
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset
+    }
+
+    // The mail provider keeps rejecting this address, hence we have stopped mailing it.
+    pub fn email_invalid(&self) -> bool {
+        self.email_invalid
+    }
+
+    // A deactivated account can neither login nor act, and is not mailed.
+    pub fn blocked(&self) -> bool {
+        self.blocked
+    }
+}
+
+// The signed in user with the token to send in the X-Session-Token header; see commons::session_tokens.
+pub struct SignedIn {
+    pub user: User,
+    pub token: String,
+}
+
+#[juniper::object(description = "The signed in user and the token of the session")]
+impl SignedIn {
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    pub fn token(&self) -> &str {
+        self.token.as_str()
+    }
 }
 
 // Registration represents the fields we obtain from user
@@ -52,6 +88,7 @@ pub struct Registration {
     pub full_name: String,
     pub email: String,
     pub password: String,
+    pub utc_offset: Option<i32>,
 }
 
 impl Registration {
@@ -82,11 +119,12 @@ impl Registration {
 #[derive(Insertable)]
 #[table_name = "users"]
 pub struct NewUser {
-    pub id: String,
+    pub id: UserId,
     pub full_name: String,
     pub email: String,
     pub user_type: String,
     pub password: String,
+    pub utc_offset: i32,
 }
 
 // A way to transform the inbound registration request into the persistable
@@ -99,11 +137,12 @@ impl NewUser {
         let fuzzy_id = util::fuzzy_id();
 
         NewUser {
-            id: fuzzy_id,
+            id: UserId::from(fuzzy_id),
             full_name: registration.full_name.to_owned(),
             email: registration.email.to_owned(),
             user_type: String::from(util::MEMBER),
-            password: util::hash(registration.password.as_str()),
+            password: password::hash(registration.password.as_str()),
+            utc_offset: registration.utc_offset.unwrap_or(0),
         }
     }
 }
@@ -115,6 +154,24 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+impl LoginRequest {
+    /**
+     * The credentials of an HTTP Basic Authorization header, for the endpoints
+     * outside the GraphQL, like the exports.
+     */
+    pub fn from_basic(header: &str) -> Option<LoginRequest> {
+        let encoded = header.strip_prefix("Basic ")?;
+        let decoded = sodiumoxide::base64::decode(encoded.trim(), sodiumoxide::base64::Variant::Original).ok()?;
+        let credentials = String::from_utf8(decoded).ok()?;
+        let (email, password) = credentials.split_once(':')?;
+
+        Some(LoginRequest {
+            email: email.to_owned(),
+            password: password.to_owned(),
+        })
+    }
+}
+
 #[derive(juniper::GraphQLInputObject)]
 pub struct ResetPasswordRequest {
     pub email: String,
@@ -144,5 +201,81 @@ impl ResetPasswordRequest {
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct UserCriteria {
-    pub id: String,
-}
\ No newline at end of file
+    pub id: UserId,
+}
+
+#[derive(juniper::GraphQLEnum, PartialEq)]
+pub enum AccountTargetState {
+    DEACTIVATE,
+    REACTIVATE,
+}
+
+// Only an admin, the signed in user, may deactivate an account or bring a
+// deactivated one back; a user closes the own account with DeactivateAccountRequest.
+#[derive(juniper::GraphQLInputObject)]
+pub struct ChangeAccountStateRequest {
+    pub user_id: UserId,
+    pub target_state: AccountTargetState,
+    pub reason: String,
+}
+
+impl ChangeAccountStateRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.user_id.trim().is_empty() {
+            errors.push(ValidationError::new("user_id", "User id is a must."));
+        }
+
+        if self.reason.chars().count() > 1000 {
+            errors.push(ValidationError::new("reason", "The reason may have at most 1000 characters."));
+        }
+
+        errors
+    }
+}
+
+/**
+ * A user closing the own account; it is the self-service deactivation. The
+ * account is the one of the signed in user, never an id in the request.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct DeactivateAccountRequest {
+    pub reason: String,
+}
+
+impl DeactivateAccountRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.reason.chars().count() > 1000 {
+            errors.push(ValidationError::new("reason", "The reason may have at most 1000 characters."));
+        }
+
+        errors
+    }
+
+    pub fn as_state_change(&self, user_id: &UserId) -> ChangeAccountStateRequest {
+        ChangeAccountStateRequest {
+            user_id: user_id.clone(),
+            target_state: AccountTargetState::DEACTIVATE,
+            reason: self.reason.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_read_basic_credentials() {
+        let request = LoginRequest::from_basic("Basic YXNoYUBleGFtcGxlLmNvbTpzM2NyZXQ6eA==").unwrap();
+        assert_eq!("asha@example.com", request.email);
+        assert_eq!("s3cret:x", request.password);
+
+        assert!(LoginRequest::from_basic("Bearer abc").is_none());
+        assert!(LoginRequest::from_basic("Basic !!").is_none());
+    }
+}
@@ -0,0 +1,439 @@
+/**
+ * A webhook subscription posts an event of the platform to a url of the coach,
+ * in one of the pre-baked formats, so that the message lands as is in a Slack
+ * or a Discord channel, or reaches any other tool as plain JSON.
+ *
+ * Like the mails, the deliveries wait in an outbox. With the webhook-delivery
+ * feature the server posts them itself, signed with the secret of the webhook,
+ * and tries a failed one again later, waiting twice as long each time; else the
+ * relay picks them through the sendableWebhooks query and posts the body to the url.
+ *
+ * The signature is in the X-Ferris-Signature header, as t=<unix time>,v1=<hex>,
+ * the HMAC-SHA256 of the time, a dot and the body, keyed with the sha256 of the
+ * secret; the coach checks it and turns away the old times.
+ */
+use chrono::{Duration, NaiveDateTime};
+use serde_json::{json, Value};
+use sodiumoxide::crypto::auth::hmacsha256;
+use sodiumoxide::crypto::hash::sha256;
+
+use crate::commons::chassis::ValidationError;
+use crate::commons::util;
+use crate::schema::webhook_deliveries;
+use crate::schema::webhook_subscriptions;
+
+pub const PENDING: &str = "pending";
+pub const MARKED: &str = "marked";
+pub const SENDING: &str = "sending";
+pub const DELIVERED: &str = "delivered";
+pub const FAILED: &str = "failed";
+
+const TASK_RESPONDED: &str = "task.responded";
+const ENROLLMENT_CREATED: &str = "enrollment.created";
+const SESSION_COMPLETED: &str = "session.completed";
+
+// The first retry waits half a minute, the last some hours; then the delivery is given up.
+pub const MAX_ATTEMPTS: i32 = 12;
+const FIRST_RETRY_SECONDS: i64 = 30;
+const LONGEST_RETRY_SECONDS: i64 = 6 * 60 * 60;
+
+// The latest deliveries are enough to see what went wrong.
+pub const MAX_DELIVERIES: i64 = 100;
+
+const SLACK: &str = "slack";
+const DISCORD: &str = "discord";
+const JSON: &str = "json";
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum WebhookEvent {
+    TaskResponse,
+    EnrollmentCreated,
+    SessionCompleted,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::TaskResponse => TASK_RESPONDED,
+            WebhookEvent::EnrollmentCreated => ENROLLMENT_CREATED,
+            WebhookEvent::SessionCompleted => SESSION_COMPLETED,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum DeliveryStatus {
+    PENDING,
+    MARKED,
+    SENDING,
+    DELIVERED,
+    FAILED,
+}
+
+impl DeliveryStatus {
+    pub fn from_str(value: &str) -> DeliveryStatus {
+        match value {
+            MARKED => DeliveryStatus::MARKED,
+            SENDING => DeliveryStatus::SENDING,
+            DELIVERED => DeliveryStatus::DELIVERED,
+            FAILED => DeliveryStatus::FAILED,
+            _ => DeliveryStatus::PENDING,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLEnum, PartialEq, Clone, Copy, Debug)]
+pub enum WebhookFormat {
+    SLACK,
+    DISCORD,
+    JSON,
+}
+
+impl WebhookFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookFormat::SLACK => SLACK,
+            WebhookFormat::DISCORD => DISCORD,
+            WebhookFormat::JSON => JSON,
+        }
+    }
+
+    pub fn from_str(value: &str) -> WebhookFormat {
+        match value {
+            SLACK => WebhookFormat::SLACK,
+            DISCORD => WebhookFormat::DISCORD,
+            _ => WebhookFormat::JSON,
+        }
+    }
+}
+
+/**
+ * What happened, told once for people and once for programs.
+ */
+pub struct OutboundEvent {
+    pub event: WebhookEvent,
+    pub summary: String,
+    pub data: Value,
+}
+
+impl OutboundEvent {
+    pub fn task_response(member_name: &str, task_name: &str, task_id: &str, enrollment_id: &str) -> OutboundEvent {
+        OutboundEvent {
+            event: WebhookEvent::TaskResponse,
+            summary: format!("{} responded to the task {}", member_name, task_name),
+            data: json!({
+                "task_id": task_id,
+                "task_name": task_name,
+                "enrollment_id": enrollment_id,
+                "member_name": member_name,
+            }),
+        }
+    }
+
+    pub fn enrollment_created(member_name: &str, program_name: &str, enrollment_id: &str, program_id: &str) -> OutboundEvent {
+        OutboundEvent {
+            event: WebhookEvent::EnrollmentCreated,
+            summary: format!("{} enrolled in {}", member_name, program_name),
+            data: json!({
+                "enrollment_id": enrollment_id,
+                "program_id": program_id,
+                "program_name": program_name,
+                "member_name": member_name,
+            }),
+        }
+    }
+
+    pub fn session_completed(session_name: &str, session_id: &str, enrollment_id: &str, program_id: &str) -> OutboundEvent {
+        OutboundEvent {
+            event: WebhookEvent::SessionCompleted,
+            summary: format!("The session {} is completed", session_name),
+            data: json!({
+                "session_id": session_id,
+                "session_name": session_name,
+                "enrollment_id": enrollment_id,
+                "program_id": program_id,
+            }),
+        }
+    }
+
+    pub fn render(&self, format: WebhookFormat) -> String {
+        let body = match format {
+            WebhookFormat::SLACK => json!({ "text": self.summary }),
+            WebhookFormat::DISCORD => json!({ "content": self.summary }),
+            WebhookFormat::JSON => json!({
+                "event": self.event.as_str(),
+                "summary": self.summary,
+                "data": self.data,
+                "occurred_at": util::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            }),
+        };
+
+        body.to_string()
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub coach_id: String,
+    pub event: String,
+    pub format: String,
+    pub url: String,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub secret: String,
+}
+
+#[juniper::object(description = "A url of the coach which receives an event of the platform")]
+impl WebhookSubscription {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn event(&self) -> &str {
+        self.event.as_str()
+    }
+
+    pub fn format(&self) -> WebhookFormat {
+        WebhookFormat::from_str(self.format.as_str())
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    #[graphql(description = "The secret the deliveries are signed with; see X-Ferris-Signature")]
+    pub fn secret(&self) -> &str {
+        self.secret.as_str()
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct WebhookRequest {
+    pub coach_id: String,
+    pub event: WebhookEvent,
+    pub format: WebhookFormat,
+    pub url: String,
+}
+
+impl WebhookRequest {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = Vec::new();
+
+        if self.coach_id.trim().is_empty() {
+            errors.push(ValidationError::new("coach_id", "Coach id is a must."));
+        }
+
+        let url = self.url.trim();
+        if !url.starts_with("https://") || url.len() > 512 || url.chars().any(char::is_whitespace) {
+            errors.push(ValidationError::new("url", "The url should be an https address of at most 512 characters."));
+        }
+
+        errors
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct WebhookCriteria {
+    pub coach_id: String,
+    pub id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "webhook_subscriptions"]
+pub struct NewWebhookSubscription {
+    pub id: String,
+    pub coach_id: String,
+    pub event: String,
+    pub format: String,
+    pub url: String,
+    pub secret: String,
+}
+
+impl NewWebhookSubscription {
+    pub fn from(request: &WebhookRequest) -> NewWebhookSubscription {
+        NewWebhookSubscription {
+            id: util::fuzzy_id(),
+            coach_id: request.coach_id.to_owned(),
+            event: request.event.as_str().to_owned(),
+            format: request.format.as_str().to_owned(),
+            url: request.url.trim().to_owned(),
+            secret: util::secure_token(),
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "webhook_deliveries"]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub url: String,
+    pub body: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub event: String,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub response_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[juniper::object(description = "A message posted, or waiting to be posted, to a webhook")]
+impl WebhookDelivery {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    pub fn content_type(&self) -> &str {
+        "application/json"
+    }
+
+    pub fn body(&self) -> &str {
+        self.body.as_str()
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn subscription_id(&self) -> &str {
+        self.subscription_id.as_str()
+    }
+
+    pub fn event(&self) -> &str {
+        self.event.as_str()
+    }
+
+    pub fn status(&self) -> DeliveryStatus {
+        DeliveryStatus::from_str(self.status.as_str())
+    }
+
+    pub fn attempts(&self) -> i32 {
+        self.attempts
+    }
+
+    #[graphql(description = "When a pending delivery is tried next")]
+    pub fn next_attempt_at(&self) -> NaiveDateTime {
+        self.next_attempt_at
+    }
+
+    #[graphql(description = "The HTTP status the url answered the last attempt with; none when it could not be reached")]
+    pub fn response_code(&self) -> Option<i32> {
+        self.response_code
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    pub fn delivered_at(&self) -> Option<NaiveDateTime> {
+        self.delivered_at
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "webhook_deliveries"]
+pub struct NewWebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub url: String,
+    pub body: String,
+    pub event: String,
+}
+
+impl NewWebhookDelivery {
+    pub fn from(subscription: &WebhookSubscription, event: &OutboundEvent) -> NewWebhookDelivery {
+        NewWebhookDelivery {
+            id: util::fuzzy_id(),
+            subscription_id: subscription.id.to_owned(),
+            url: subscription.url.to_owned(),
+            body: event.render(WebhookFormat::from_str(subscription.format.as_str())),
+            event: event.event.as_str().to_owned(),
+        }
+    }
+}
+
+/**
+ * The deliveries of the webhooks of the coach, or of one of them, the latest first.
+ */
+#[derive(juniper::GraphQLInputObject)]
+pub struct DeliveryCriteria {
+    pub coach_id: String,
+    pub webhook_id: Option<String>,
+}
+
+/**
+ * The value of the X-Ferris-Signature header of the body posted at the time.
+ */
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmacsha256::Key(sha256::hash(secret.as_bytes()).0);
+    let message = format!("{}.{}", timestamp, body);
+    let tag: String = hmacsha256::authenticate(message.as_bytes(), &key).0.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    format!("t={},v1={}", timestamp, tag)
+}
+
+/**
+ * How long to wait after the attempts failed so far; none when it is time to
+ * give up.
+ */
+pub fn retry_delay(attempts: i32) -> Option<Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+
+    let doublings = (attempts.max(1) - 1) as u32;
+    let seconds = FIRST_RETRY_SECONDS.saturating_mul(2_i64.saturating_pow(doublings)).min(LONGEST_RETRY_SECONDS);
+
+    Some(Duration::seconds(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_render_each_format() {
+        let event = OutboundEvent::task_response("Asha", "Read \"Deep Work\"", "t-1", "e-1");
+
+        assert_eq!(r#"{"text":"Asha responded to the task Read \"Deep Work\""}"#, event.render(WebhookFormat::SLACK));
+        assert_eq!(r#"{"content":"Asha responded to the task Read \"Deep Work\""}"#, event.render(WebhookFormat::DISCORD));
+
+        let json: Value = serde_json::from_str(event.render(WebhookFormat::JSON).as_str()).unwrap();
+        assert_eq!("task.responded", json["event"]);
+        assert_eq!("t-1", json["data"]["task_id"]);
+    }
+
+    #[test]
+    fn should_wait_twice_as_long_each_time_and_then_give_up() {
+        assert_eq!(Some(Duration::seconds(30)), retry_delay(1));
+        assert_eq!(Some(Duration::seconds(60)), retry_delay(2));
+        assert_eq!(Some(Duration::seconds(3840)), retry_delay(8));
+        assert_eq!(Some(Duration::hours(6)), retry_delay(MAX_ATTEMPTS - 1));
+        assert_eq!(None, retry_delay(MAX_ATTEMPTS));
+    }
+
+    #[test]
+    fn should_sign_the_time_and_the_body_with_the_secret() {
+        let signed = signature("s3cret", 1614556800, r#"{"event":"session.completed"}"#);
+
+        assert!(signed.starts_with("t=1614556800,v1="));
+        assert_eq!(16 + 64, signed.len());
+        assert_eq!(signed, signature("s3cret", 1614556800, r#"{"event":"session.completed"}"#));
+        assert_ne!(signed, signature("other", 1614556800, r#"{"event":"session.completed"}"#));
+        assert_ne!(signed, signature("s3cret", 1614556801, r#"{"event":"session.completed"}"#));
+    }
+}
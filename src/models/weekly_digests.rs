@@ -0,0 +1,181 @@
+/**
+ * The weekly digest of an enrollment, mailed to its member by the
+ * "weekly-digest" job: the sessions coming up in the week, the tasks due by its
+ * end, the late ones included, and the notes the coach shared over the last
+ * week.
+ *
+ * The week begins on Monday. An enrollment is marked for the Monday of the week
+ * its digest went out, so that it gets one a week however often the job runs;
+ * a week with nothing to tell is marked and not mailed. A member turns the
+ * digest off on the profile, for all the enrollments at once.
+ */
+use chrono::{Datelike, Duration, NaiveDateTime};
+
+use crate::models::member_weeks::WEEK_DAYS;
+use crate::models::notes::Note;
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+use crate::schema::weekly_digests;
+
+// A note is told in its first few words; the rest waits on the platform.
+const NOTE_WORDS: usize = 30;
+
+/**
+ * From the Monday of the week of the given time, for a week; the end is left out.
+ */
+pub fn digest_week(now: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+    let monday = now.date() - Duration::days(now.weekday().num_days_from_monday() as i64);
+    let starts_at = monday.and_hms(0, 0, 0);
+
+    (starts_at, starts_at + Duration::days(WEEK_DAYS))
+}
+
+pub struct WeeklyDigest {
+    pub program_name: String,
+    pub sessions: Vec<Session>,
+    pub tasks: Vec<Task>,
+    pub notes: Vec<Note>,
+}
+
+impl WeeklyDigest {
+    /**
+     * The rows were fetched by either of their dates; the sessions yet to start
+     * and the tasks due by the end of the week are kept, the earliest first.
+     */
+    pub fn of(program_name: &str, now: NaiveDateTime, ends_at: NaiveDateTime, sessions: Vec<Session>, tasks: Vec<Task>, notes: Vec<Note>) -> WeeklyDigest {
+        let mut sessions: Vec<Session> = sessions.into_iter().filter(|session| starts_at(session) >= now && starts_at(session) < ends_at).collect();
+        sessions.sort_by_key(starts_at);
+
+        let mut tasks: Vec<Task> = tasks.into_iter().filter(|task| due_at(task) < ends_at).collect();
+        tasks.sort_by_key(due_at);
+
+        let mut notes = notes;
+        notes.sort_by_key(|note| note.created_at);
+
+        WeeklyDigest {
+            program_name: program_name.to_owned(),
+            sessions,
+            tasks,
+            notes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty() && self.tasks.is_empty() && self.notes.is_empty()
+    }
+
+    pub fn subject(&self) -> String {
+        format!("Your week in {}", self.program_name)
+    }
+
+    pub fn content(&self, member_name: &str, now: NaiveDateTime) -> String {
+        let mut paragraphs: Vec<String> = vec![format!("Greetings {}, here is your week in {}.", member_name, self.program_name)];
+
+        if !self.sessions.is_empty() {
+            let lines = self.sessions.iter().map(|session| format!("- {}, on {}", session.name, format_day(starts_at(session))));
+            paragraphs.push(section("The sessions coming up:", lines));
+        }
+
+        if !self.tasks.is_empty() {
+            let lines = self.tasks.iter().map(|task| {
+                if due_at(task) < now {
+                    format!("- {}, late since {}", task.name, format_day(due_at(task)))
+                } else {
+                    format!("- {}, due on {}", task.name, format_day(due_at(task)))
+                }
+            });
+            paragraphs.push(section("The tasks due:", lines));
+        }
+
+        if !self.notes.is_empty() {
+            let lines = self.notes.iter().map(|note| format!("- {}", first_words(note.description.as_str())));
+            paragraphs.push(section("The new notes of your coach:", lines));
+        }
+
+        paragraphs.push(String::from("You may turn off this digest from your profile."));
+
+        paragraphs.join("\n\n")
+    }
+}
+
+fn section(title: &str, lines: impl Iterator<Item = String>) -> String {
+    std::iter::once(title.to_owned()).chain(lines).collect::<Vec<String>>().join("\n")
+}
+
+fn starts_at(session: &Session) -> NaiveDateTime {
+    session.revised_start_date.unwrap_or(session.original_start_date)
+}
+
+fn due_at(task: &Task) -> NaiveDateTime {
+    task.revised_end_date.unwrap_or(task.original_end_date)
+}
+
+fn format_day(time: NaiveDateTime) -> String {
+    time.format("%a %d %b, %H:%M UTC").to_string()
+}
+
+fn first_words(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    if words.len() > NOTE_WORDS {
+        return format!("{}...", words[..NOTE_WORDS].join(" "));
+    }
+
+    words.join(" ")
+}
+
+#[derive(Insertable)]
+#[table_name = "weekly_digests"]
+pub struct NewWeeklyDigest {
+    pub enrollment_id: String,
+    pub week_of: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn at(date_time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn note(description: &str) -> Note {
+        Note {
+            id: String::from("n-1"),
+            session_id: String::from("s-1"),
+            created_by_id: String::from("c-1"),
+            session_user_id: String::from("su-1"),
+            description: description.to_owned(),
+            remind_at: None,
+            is_private: false,
+            created_at: at("2021-03-12T10:00"),
+            updated_at: at("2021-03-12T10:00"),
+        }
+    }
+
+    #[test]
+    fn should_begin_the_week_on_monday() {
+        assert_eq!((at("2021-03-08T00:00"), at("2021-03-15T00:00")), digest_week(at("2021-03-11T17:45")));
+        assert_eq!(at("2021-03-15T00:00"), digest_week(at("2021-03-15T00:00")).0);
+        assert_eq!(at("2021-03-08T00:00"), digest_week(at("2021-03-14T23:59")).0);
+    }
+
+    #[test]
+    fn should_tell_the_notes_in_their_first_words() {
+        let long = vec!["word"; 40].join(" ");
+        let digest = WeeklyDigest::of("Clarity", at("2021-03-15T06:00"), at("2021-03-22T00:00"), vec![], vec![], vec![note("Keep  the\njournal"), note(long.as_str())]);
+
+        let content = digest.content("Mani", at("2021-03-15T06:00"));
+
+        assert!(!digest.is_empty());
+        assert_eq!("Your week in Clarity", digest.subject());
+        assert!(content.starts_with("Greetings Mani, here is your week in Clarity.\n\nThe new notes of your coach:\n- Keep the journal\n"));
+        assert!(content.contains(format!("- {}...\n", vec!["word"; 30].join(" ")).as_str()));
+    }
+
+    #[test]
+    fn should_have_nothing_to_tell_of_an_empty_week() {
+        assert!(WeeklyDigest::of("Clarity", at("2021-03-15T06:00"), at("2021-03-22T00:00"), vec![], vec![], vec![]).is_empty());
+    }
+}
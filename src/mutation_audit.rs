@@ -0,0 +1,409 @@
+/**
+ * Records every mutation in the audit trail: who ran it, on which entity, and
+ * the state it left next to the state the previous change left.
+ *
+ * The root fields of a mutation are read from the tokens of the document when it
+ * arrives, with their arguments and the variables the arguments refer to, and are
+ * recorded once juniper ran them. The entity is the object a mutation returned,
+ * or else the one its arguments point at by an id. A mutation answering with
+ * errors changed nothing and is left out, as is one sent by nobody signed in but
+ * for a user signing up.
+ *
+ * The recording follows the change on its own connection; a failure to record is
+ * logged and the change stands. The passwords in the arguments are never written.
+ */
+use juniper::parser::{Lexer, ScalarToken, Token};
+use serde_json::{json, Map, Value};
+
+use crate::commons::ids::UserId;
+use crate::field_usage::FieldCatalog;
+use crate::graphql_schema::DBContext;
+use crate::models::audit_events::NewAuditEvent;
+use crate::services::audit;
+
+const REDACTED: &str = "[redacted]";
+
+const USER: &str = "user";
+
+/**
+ * A root field of a mutation; the key is its alias, if it has one, as the
+ * response holds it by that.
+ */
+#[derive(Debug, PartialEq)]
+pub struct Mutation {
+    pub key: String,
+    pub field: String,
+    pub arguments: Map<String, Value>,
+}
+
+/**
+ * The mutations of the operation the request runs, none when the document does
+ * not parse.
+ */
+pub fn mutations(query: &str, operation_name: Option<&str>, variables: &Value) -> Vec<Mutation> {
+    let tokens: Vec<Token> = match Lexer::new(query).map(|token| token.map(|spanning| spanning.item)).collect::<Result<_, _>>() {
+        Ok(tokens) => tokens,
+        Err(_) => return Vec::new(),
+    };
+
+    read_operations(&tokens, operation_name, variables).unwrap_or_default()
+}
+
+fn read_operations(tokens: &[Token], operation_name: Option<&str>, variables: &Value) -> Option<Vec<Mutation>> {
+    let mut at = 0;
+
+    loop {
+        match tokens.get(at)? {
+            Token::EndOfFile => return Some(Vec::new()),
+            Token::Name("mutation") => {
+                let name = match tokens.get(at + 1)? {
+                    Token::Name(name) => Some(*name),
+                    _ => None,
+                };
+
+                at = skip_to_selection(tokens, at)?;
+                if operation_name.is_none() || operation_name == name {
+                    return read_root_fields(tokens, at, variables);
+                }
+                at = skip_selection(tokens, at)?;
+            }
+            Token::CurlyOpen => at = skip_selection(tokens, at)?,
+            _ => {
+                at = skip_to_selection(tokens, at)?;
+                at = skip_selection(tokens, at)?;
+            }
+        }
+    }
+}
+
+fn skip_to_selection(tokens: &[Token], mut at: usize) -> Option<usize> {
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyOpen => return Some(at),
+            Token::ParenOpen => at = skip_arguments(tokens, at)?,
+            _ => at += 1,
+        }
+    }
+}
+
+// The default values of the variables may hold objects, hence the braces within are skipped too.
+fn skip_arguments(tokens: &[Token], mut at: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    loop {
+        match tokens.get(at)? {
+            Token::ParenOpen => depth += 1,
+            Token::ParenClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at + 1);
+                }
+            }
+            _ => {}
+        }
+        at += 1;
+    }
+}
+
+// The selection set opening at `at`, with the ones within it.
+fn skip_selection(tokens: &[Token], mut at: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyOpen => depth += 1,
+            Token::CurlyClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at + 1);
+                }
+            }
+            _ => {}
+        }
+        at += 1;
+    }
+}
+
+fn read_root_fields(tokens: &[Token], mut at: usize, variables: &Value) -> Option<Vec<Mutation>> {
+    let mut mutations = Vec::new();
+    at += 1;
+
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyClose => return Some(mutations),
+            Token::Name(name) => {
+                let (key, field) = if tokens.get(at + 1) == Some(&Token::Colon) {
+                    at += 2;
+                    match tokens.get(at)? {
+                        Token::Name(field) => (*name, *field),
+                        _ => return None,
+                    }
+                } else {
+                    (*name, *name)
+                };
+                at += 1;
+
+                let mut arguments = Map::new();
+                if tokens.get(at) == Some(&Token::ParenOpen) {
+                    at += 1;
+                    while let Token::Name(argument) = tokens.get(at)? {
+                        let (value, next) = read_value(tokens, at + 2, variables)?;
+                        arguments.insert((*argument).to_owned(), value);
+                        at = next;
+                    }
+                    at += 1;
+                }
+
+                mutations.push(Mutation {
+                    key: key.to_owned(),
+                    field: field.to_owned(),
+                    arguments,
+                });
+            }
+            Token::CurlyOpen => at = skip_selection(tokens, at)?,
+            Token::ParenOpen => at = skip_arguments(tokens, at)?,
+            // A directive, whose name is not a field.
+            Token::At => at += 2,
+            _ => at += 1,
+        }
+    }
+}
+
+/**
+ * The value at `at` as JSON, a variable standing for its own value, with the
+ * position after it.
+ */
+fn read_value(tokens: &[Token], at: usize, variables: &Value) -> Option<(Value, usize)> {
+    match tokens.get(at)? {
+        Token::Dollar => match tokens.get(at + 1)? {
+            Token::Name(name) => Some((variables.get(*name).cloned().unwrap_or(Value::Null), at + 2)),
+            _ => None,
+        },
+        Token::Scalar(ScalarToken::String(text)) => Some((Value::String(unescape(text)), at + 1)),
+        Token::Scalar(ScalarToken::Int(text)) | Token::Scalar(ScalarToken::Float(text)) => Some((text.parse().map(Value::Number).unwrap_or(Value::Null), at + 1)),
+        Token::Name("true") => Some((Value::Bool(true), at + 1)),
+        Token::Name("false") => Some((Value::Bool(false), at + 1)),
+        Token::Name("null") => Some((Value::Null, at + 1)),
+        // The values of an enum.
+        Token::Name(name) => Some((Value::String((*name).to_owned()), at + 1)),
+        Token::BracketOpen => {
+            let mut items = Vec::new();
+            let mut at = at + 1;
+            while tokens.get(at)? != &Token::BracketClose {
+                let (item, next) = read_value(tokens, at, variables)?;
+                items.push(item);
+                at = next;
+            }
+            Some((Value::Array(items), at + 1))
+        }
+        Token::CurlyOpen => {
+            let mut fields = Map::new();
+            let mut at = at + 1;
+            while let Token::Name(name) = tokens.get(at)? {
+                let (item, next) = read_value(tokens, at + 2, variables)?;
+                fields.insert((*name).to_owned(), item);
+                at = next;
+            }
+            Some((Value::Object(fields), at + 1))
+        }
+        _ => None,
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some(other) => value.push(other),
+            None => {}
+        }
+    }
+
+    value
+}
+
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| {
+                    if name.to_lowercase().contains("password") {
+                        (name.to_owned(), Value::String(String::from(REDACTED)))
+                    } else {
+                        (name.to_owned(), redact(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        _ => value.clone(),
+    }
+}
+
+/**
+ * Records the mutations that went through, by the response juniper gave.
+ */
+pub fn record(ctx: &DBContext, mutations: &[Mutation], response: &Value) {
+    let connection = match ctx.connection() {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Unable to record the mutations: {}", e);
+            return;
+        }
+    };
+
+    for mutation in mutations {
+        if failed(mutation, response) {
+            continue;
+        }
+
+        let (entity_type, entity_id, entity) = match locate(&ctx.usage.catalog, mutation, &response["data"][mutation.key.as_str()]) {
+            Some(located) => located,
+            None => continue,
+        };
+
+        let actor_id = match ctx.caller() {
+            Some(caller) => caller.to_owned(),
+            None if entity_type == USER => UserId::from(entity_id.as_str()),
+            None => continue,
+        };
+
+        let after = json!({
+            "arguments": redact(&Value::Object(mutation.arguments.clone())),
+            "result": redact(&entity),
+        });
+
+        let mut event = NewAuditEvent::from(entity_type.as_str(), entity_id.as_str(), mutation.field.as_str(), actor_id.as_str());
+        event.after_state = Some(after.to_string());
+
+        if let Err(e) = audit::record_change(&connection, event) {
+            eprintln!("Unable to record the mutation {} of {} {}: {}", mutation.field, entity_type, entity_id, e);
+        }
+    }
+}
+
+fn failed(mutation: &Mutation, response: &Value) -> bool {
+    let result = &response["data"][mutation.key.as_str()];
+
+    if result.is_null() {
+        return true;
+    }
+
+    if result["errors"].as_array().is_some_and(|errors| !errors.is_empty()) {
+        return true;
+    }
+
+    response["errors"]
+        .as_array()
+        .is_some_and(|errors| errors.iter().any(|error| error["path"][0].as_str() == Some(mutation.key.as_str())))
+}
+
+/**
+ * The type, the id and the state of the entity the mutation changed: the object
+ * it returned when that came back with an id, or else the one named by an id in
+ * its arguments; `id` is of the entity the mutation returns, an `xyzId` of an xyz.
+ */
+fn locate(catalog: &FieldCatalog, mutation: &Mutation, result: &Value) -> Option<(String, String, Value)> {
+    let wrapper = catalog.returns(catalog.mutation_root.as_str(), mutation.field.as_str())?;
+    let entity_field = catalog.types.get(wrapper.as_str())?.iter().find(|field| field.name != "errors")?;
+
+    let returns_object = catalog.types.contains_key(entity_field.returns.as_str());
+    let returned_type = if returns_object { entity_field.returns.to_owned() } else { verbless(mutation.field.as_str()) };
+
+    let entity = result[entity_field.name.as_str()].clone();
+
+    if let Some(the_id) = entity["id"].as_str() {
+        return Some((snake_case(returned_type.as_str()), the_id.to_owned(), entity));
+    }
+
+    // The ids in the input objects and the ones given as arguments.
+    let mut ids: Vec<(&String, &str)> = Vec::new();
+    for (name, value) in &mutation.arguments {
+        match value {
+            Value::Object(fields) => ids.extend(fields.iter().filter_map(|(name, value)| value.as_str().map(|value| (name, value)))),
+            Value::String(value) => ids.push((name, value.as_str())),
+            _ => {}
+        }
+    }
+
+    if let Some((_, the_id)) = ids.iter().find(|(name, _)| name.as_str() == "id") {
+        return Some((snake_case(returned_type.as_str()), (*the_id).to_owned(), entity));
+    }
+
+    ids.iter()
+        .find(|(name, _)| name.len() > 2 && name.ends_with("Id"))
+        .map(|(name, the_id)| (snake_case(&name[..name.len() - 2]), (*the_id).to_owned(), entity))
+}
+
+// The thing a mutation acts on by its name, like the Faq of deleteFaq.
+fn verbless(field: &str) -> String {
+    field.trim_start_matches(|c: char| c.is_ascii_lowercase()).to_owned()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut value = String::with_capacity(name.len() + 4);
+
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if index > 0 {
+                value.push('_');
+            }
+            value.push(c.to_ascii_lowercase());
+        } else {
+            value.push(c);
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_read_the_mutations_with_their_variables() {
+        let query = r#"query Plans { getPlans { id } }
+            mutation Save($plan: UpdateMasterPlanRequest!) {
+                saved: saveMasterPlan(request: $plan) { rows errors { field message } }
+                updateTask(updateTaskRequest: { id: "t-1", name: "Read \"Dune\"", duration: 30, tags: [A, B] }) { task { id } }
+            }"#;
+
+        let variables = json!({ "plan": { "masterPlanId": "p-1", "tasks": [] } });
+        let read = mutations(query, Some("Save"), &variables);
+
+        assert_eq!(2, read.len());
+        assert_eq!(("saved", "saveMasterPlan"), (read[0].key.as_str(), read[0].field.as_str()));
+        assert_eq!(variables["plan"], read[0].arguments["request"]);
+        assert_eq!(
+            json!({ "id": "t-1", "name": "Read \"Dune\"", "duration": 30, "tags": ["A", "B"] }),
+            read[1].arguments["updateTaskRequest"]
+        );
+
+        assert!(mutations(query, Some("Other"), &variables).is_empty());
+        assert!(mutations("{ getPlans { id } }", None, &variables).is_empty());
+    }
+
+    #[test]
+    fn should_never_keep_a_password() {
+        let registration = json!({ "registration": { "email": "a@b.c", "password": "secret", "confirmPassword": "secret" } });
+        let redacted = redact(&registration);
+
+        assert_eq!("a@b.c", redacted["registration"]["email"]);
+        assert_eq!(REDACTED, redacted["registration"]["password"]);
+        assert_eq!(REDACTED, redacted["registration"]["confirmPassword"]);
+        assert_eq!("master_plan", snake_case(verbless("saveMasterPlan").as_str()));
+    }
+}
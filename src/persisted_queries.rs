@@ -0,0 +1,159 @@
+/**
+ * The automatic persisted queries, as the Apollo clients send them: the client
+ * sends the sha256 of its query in extensions.persistedQuery and leaves the query
+ * out. A hash we do not know is answered with PERSISTED_QUERY_NOT_FOUND, and the
+ * client sends the query along with its hash once, for it to be kept. The mobile
+ * client then sends a few bytes for a request instead of the whole document.
+ *
+ * GRAPHQL_PERSISTED_CAPACITY   the queries kept, 2000 by default
+ * GRAPHQL_PRODUCTION           true turns away the large ad-hoc queries
+ * GRAPHQL_MAX_ADHOC_BYTES      the largest ad-hoc query in production, 8192 by default
+ *
+ * An ad-hoc query is one sent without a hash. The queries are kept in the process
+ * for a day at most; a client missing one after a restart or on another instance
+ * sends it again, as the protocol has it. The query resolved from a hash goes
+ * through the query_limits as any other.
+ */
+use serde_json::Value;
+use sodiumoxide::crypto::hash::sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::commons::cache::TtlCache;
+use crate::query_limits::refusal;
+
+const DEFAULT_CAPACITY: usize = 2000;
+const DEFAULT_MAX_ADHOC_BYTES: usize = 8192;
+const KEPT_FOR: Duration = Duration::from_secs(24 * 60 * 60);
+const SUPPORTED_VERSION: i64 = 1;
+
+const PERSISTED_QUERY_NOT_FOUND: &str = "PERSISTED_QUERY_NOT_FOUND";
+const PERSISTED_QUERY_NOT_SUPPORTED: &str = "PERSISTED_QUERY_NOT_SUPPORTED";
+const PERSISTED_QUERY_MISMATCH: &str = "PERSISTED_QUERY_MISMATCH";
+const ADHOC_QUERY_TOO_LARGE: &str = "ADHOC_QUERY_TOO_LARGE";
+
+#[derive(Clone)]
+pub struct PersistedQueries {
+    queries: Arc<TtlCache<String, String>>,
+    production: bool,
+    max_adhoc_bytes: usize,
+}
+
+impl PersistedQueries {
+    pub fn new(capacity: usize, production: bool, max_adhoc_bytes: usize) -> PersistedQueries {
+        PersistedQueries {
+            queries: Arc::new(TtlCache::new(KEPT_FOR, capacity)),
+            production,
+            max_adhoc_bytes,
+        }
+    }
+
+    pub fn from_env() -> PersistedQueries {
+        PersistedQueries::new(
+            env_size("GRAPHQL_PERSISTED_CAPACITY", DEFAULT_CAPACITY),
+            std::env::var("GRAPHQL_PRODUCTION").unwrap_or_default() == "true",
+            env_size("GRAPHQL_MAX_ADHOC_BYTES", DEFAULT_MAX_ADHOC_BYTES),
+        )
+    }
+
+    /**
+     * Puts the query of the hash into the body of the request, or keeps the query
+     * sent with its hash; otherwise gives the GraphQL error to answer with.
+     */
+    pub fn resolve(&self, body: &mut Value) -> Result<(), Value> {
+        let persisted = match body.pointer("/extensions/persistedQuery") {
+            Some(persisted) => persisted,
+            None => return self.admit_adhoc(body),
+        };
+
+        if persisted.get("version").and_then(Value::as_i64) != Some(SUPPORTED_VERSION) {
+            return Err(refusal(String::from("Only the version 1 of the persisted queries is supported."), PERSISTED_QUERY_NOT_SUPPORTED));
+        }
+
+        let hash = match persisted.get("sha256Hash").and_then(Value::as_str) {
+            Some(hash) => hash.to_ascii_lowercase(),
+            None => return Err(refusal(String::from("The persisted query has no sha256Hash."), PERSISTED_QUERY_NOT_FOUND)),
+        };
+
+        if let Some(query) = body.get("query").and_then(Value::as_str) {
+            if digest(query) != hash {
+                return Err(refusal(String::from("The sha256Hash is not of the query sent with it."), PERSISTED_QUERY_MISMATCH));
+            }
+
+            let query = query.to_owned();
+            return self.queries.get_or_load(&hash, || Ok(query)).map(|_| ());
+        }
+
+        let query = self
+            .queries
+            .get_or_load(&hash, || Err(()))
+            .map_err(|_| refusal(String::from("PersistedQueryNotFound"), PERSISTED_QUERY_NOT_FOUND))?;
+
+        body["query"] = Value::String(query);
+
+        Ok(())
+    }
+
+    fn admit_adhoc(&self, body: &Value) -> Result<(), Value> {
+        let size = body.get("query").and_then(Value::as_str).map(str::len).unwrap_or(0);
+
+        if self.production && size > self.max_adhoc_bytes {
+            let message = format!("The query has {} bytes; at most {} are allowed unless it is persisted.", size, self.max_adhoc_bytes);
+            return Err(refusal(message, ADHOC_QUERY_TOO_LARGE));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn digest(query: &str) -> String {
+    sha256::hash(query.as_bytes()).as_ref().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn env_size(key: &str, default: usize) -> usize {
+    dotenv::var(key).ok().and_then(|value| value.parse::<usize>().ok()).filter(|value| *value > 0).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_json::json;
+
+    const QUERY: &str = "{ getPlatformContent { error { message } } }";
+
+    fn code(refusal: Value) -> String {
+        refusal["errors"][0]["extensions"]["code"].as_str().unwrap_or_default().to_owned()
+    }
+
+    #[test]
+    fn should_keep_the_query_sent_with_its_hash() {
+        let queries = PersistedQueries::new(10, true, 1024);
+        let hash = digest(QUERY);
+
+        let mut by_hash = json!({ "extensions": { "persistedQuery": { "version": 1, "sha256Hash": hash } } });
+        assert_eq!(PERSISTED_QUERY_NOT_FOUND, code(queries.resolve(&mut by_hash.clone()).unwrap_err()));
+
+        let mut registration = json!({ "query": QUERY, "extensions": { "persistedQuery": { "version": 1, "sha256Hash": hash } } });
+        assert!(queries.resolve(&mut registration).is_ok());
+
+        assert!(queries.resolve(&mut by_hash).is_ok());
+        assert_eq!(QUERY, by_hash["query"]);
+    }
+
+    #[test]
+    fn should_refuse_a_hash_of_another_query() {
+        let queries = PersistedQueries::new(10, false, 1024);
+
+        let mut body = json!({ "query": QUERY, "extensions": { "persistedQuery": { "version": 1, "sha256Hash": digest("{ other }") } } });
+        assert_eq!(PERSISTED_QUERY_MISMATCH, code(queries.resolve(&mut body).unwrap_err()));
+    }
+
+    #[test]
+    fn should_limit_the_adhoc_queries_in_production() {
+        let mut body = json!({ "query": QUERY });
+
+        assert!(PersistedQueries::new(10, false, 8).resolve(&mut body).is_ok());
+        assert_eq!(ADHOC_QUERY_TOO_LARGE, code(PersistedQueries::new(10, true, 8).resolve(&mut body).unwrap_err()));
+    }
+}
@@ -0,0 +1,301 @@
+/**
+ * The size of a GraphQL request is checked before it runs, so that a client
+ * cannot tie up the workers and the connections of the pool with a document
+ * nesting its selections deep or fanning them out wide.
+ *
+ * The depth is the longest chain of nested fields; the nodes are all the fields
+ * the request would resolve, with every fragment counted each time it is spread.
+ * Every operation of the document is measured, the largest one counting.
+ *
+ * GRAPHQL_MAX_DEPTH    the deepest a request may go, 15 by default
+ * GRAPHQL_MAX_NODES    the fields a request may ask for, 1000 by default
+ *
+ * The defaults leave room for the introspection of GraphiQL. A request past a
+ * limit is answered with a GraphQL error coded QUERY_TOO_DEEP or QUERY_TOO_LARGE
+ * and is never executed. A document that does not parse is left to juniper.
+ */
+use juniper::parser::{Lexer, Token};
+use serde_json::json;
+use std::collections::HashMap;
+
+const DEFAULT_MAX_DEPTH: usize = 15;
+const DEFAULT_MAX_NODES: usize = 1000;
+
+const QUERY_TOO_DEEP: &str = "QUERY_TOO_DEEP";
+const QUERY_TOO_LARGE: &str = "QUERY_TOO_LARGE";
+
+#[derive(Clone, Copy, Debug)]
+pub struct QueryLimits {
+    pub max_depth: usize,
+    pub max_nodes: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Measure {
+    pub depth: usize,
+    pub nodes: usize,
+}
+
+impl Measure {
+    fn widest(self, other: Measure) -> Measure {
+        Measure {
+            depth: self.depth.max(other.depth),
+            nodes: self.nodes.max(other.nodes),
+        }
+    }
+}
+
+impl QueryLimits {
+    pub fn from_env() -> QueryLimits {
+        QueryLimits {
+            max_depth: env_size("GRAPHQL_MAX_DEPTH", DEFAULT_MAX_DEPTH),
+            max_nodes: env_size("GRAPHQL_MAX_NODES", DEFAULT_MAX_NODES),
+        }
+    }
+
+    /**
+     * Lets the request through when it is within the limits; otherwise gives the
+     * GraphQL error to answer with.
+     */
+    pub fn admit(&self, query: &str) -> Result<(), serde_json::Value> {
+        let measure = match measure(query) {
+            Some(measure) => measure,
+            None => return Ok(()),
+        };
+
+        if measure.depth > self.max_depth {
+            let message = format!("The query nests {} levels deep; at most {} are allowed.", measure.depth, self.max_depth);
+            return Err(refusal(message, QUERY_TOO_DEEP));
+        }
+
+        if measure.nodes > self.max_nodes {
+            let message = format!("The query asks for {} fields; at most {} are allowed.", measure.nodes, self.max_nodes);
+            return Err(refusal(message, QUERY_TOO_LARGE));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn refusal(message: String, code: &str) -> serde_json::Value {
+    json!({
+        "data": null,
+        "errors": [{
+            "message": message,
+            "extensions": { "code": code }
+        }]
+    })
+}
+
+/**
+ * The largest operation of the document, none when it does not parse.
+ */
+pub fn measure(query: &str) -> Option<Measure> {
+    let tokens: Vec<Token> = Lexer::new(query).map(|token| token.map(|spanning| spanning.item)).collect::<Result<_, _>>().ok()?;
+
+    let mut operations: Vec<usize> = Vec::new();
+    let mut fragments: HashMap<&str, usize> = HashMap::new();
+
+    let mut at = 0;
+    loop {
+        match tokens.get(at)? {
+            Token::EndOfFile => break,
+            Token::Name("fragment") => {
+                let name = match tokens.get(at + 1)? {
+                    Token::Name(name) => *name,
+                    _ => return None,
+                };
+                at = skip_to_selection(&tokens, at)?;
+                fragments.insert(name, at);
+            }
+            _ => {
+                at = skip_to_selection(&tokens, at)?;
+                operations.push(at);
+            }
+        }
+        at = skip_selection(&tokens, at)?;
+    }
+
+    let mut meter = Meter {
+        tokens: &tokens,
+        fragments,
+        measured: HashMap::new(),
+        spreading: Vec::new(),
+    };
+
+    let mut largest = Measure::default();
+    for start in operations {
+        let (measure, _) = meter.selection(start)?;
+        largest = largest.widest(measure);
+    }
+
+    Some(largest)
+}
+
+struct Meter<'a, 'b> {
+    tokens: &'b [Token<'a>],
+    fragments: HashMap<&'a str, usize>,
+    // A fragment is measured once however many times it is spread.
+    measured: HashMap<&'a str, Measure>,
+    spreading: Vec<&'a str>,
+}
+
+impl<'a, 'b> Meter<'a, 'b> {
+    /**
+     * The selection set opening at `at`, with the position after it.
+     */
+    fn selection(&mut self, mut at: usize) -> Option<(Measure, usize)> {
+        let mut measure = Measure::default();
+        at += 1;
+
+        loop {
+            match self.tokens.get(at)? {
+                Token::CurlyClose => return Some((measure, at + 1)),
+                Token::Ellipsis => {
+                    let (inner, next) = match self.tokens.get(at + 1)? {
+                        Token::Name(name) if *name != "on" => (self.fragment(name)?, at + 2),
+                        // An inline fragment, with or without a type condition.
+                        _ => self.selection(skip_to_selection(self.tokens, at)?)?,
+                    };
+                    measure.depth = measure.depth.max(inner.depth);
+                    measure.nodes = measure.nodes.saturating_add(inner.nodes);
+                    at = next;
+                }
+                Token::Name(_) => {
+                    at += 1;
+                    if self.tokens.get(at) == Some(&Token::Colon) {
+                        at += 2;
+                    }
+
+                    let mut inner = Measure::default();
+                    loop {
+                        match self.tokens.get(at)? {
+                            Token::ParenOpen => at = skip_arguments(self.tokens, at)?,
+                            Token::At => at += 2,
+                            Token::CurlyOpen => {
+                                let (nested, next) = self.selection(at)?;
+                                inner = nested;
+                                at = next;
+                                break;
+                            }
+                            _ => break,
+                        }
+                    }
+
+                    measure.depth = measure.depth.max(inner.depth + 1);
+                    measure.nodes = measure.nodes.saturating_add(inner.nodes).saturating_add(1);
+                }
+                // The directives of a fragment spread.
+                Token::At => at += 2,
+                Token::ParenOpen => at = skip_arguments(self.tokens, at)?,
+                _ => return None,
+            }
+        }
+    }
+
+    // A fragment spreading itself is refused by juniper; here it counts for nothing more.
+    fn fragment(&mut self, name: &'a str) -> Option<Measure> {
+        if let Some(measure) = self.measured.get(name) {
+            return Some(*measure);
+        }
+
+        if self.spreading.contains(&name) {
+            return Some(Measure::default());
+        }
+
+        let start = *self.fragments.get(name)?;
+
+        self.spreading.push(name);
+        let (measure, _) = self.selection(start)?;
+        self.spreading.pop();
+
+        self.measured.insert(name, measure);
+
+        Some(measure)
+    }
+}
+
+fn skip_to_selection(tokens: &[Token], mut at: usize) -> Option<usize> {
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyOpen => return Some(at),
+            Token::ParenOpen => at = skip_arguments(tokens, at)?,
+            _ => at += 1,
+        }
+    }
+}
+
+// The default values of the variables may hold objects, hence the braces within are skipped too.
+fn skip_arguments(tokens: &[Token], mut at: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    loop {
+        match tokens.get(at)? {
+            Token::ParenOpen => depth += 1,
+            Token::ParenClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at + 1);
+                }
+            }
+            _ => {}
+        }
+        at += 1;
+    }
+}
+
+fn skip_selection(tokens: &[Token], mut at: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    loop {
+        match tokens.get(at)? {
+            Token::CurlyOpen => depth += 1,
+            Token::CurlyClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(at + 1);
+                }
+            }
+            _ => {}
+        }
+        at += 1;
+    }
+}
+
+fn env_size(key: &str, default: usize) -> usize {
+    dotenv::var(key).ok().and_then(|value| value.parse::<usize>().ok()).filter(|value| *value > 0).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_measure_the_fields_and_the_fragments() {
+        let query = r#"
+            query Week($id: String!) {
+                me: getUser(criteria: { id: $id }) { id ...Names }
+                getEvents(criteria: { userId: $id }) @include(if: true) {
+                    rows { session { id } ... on EventRow { program { ...Names } } }
+                }
+            }
+            fragment Names on User { fullName email }
+        "#;
+
+        assert_eq!(Some(Measure { depth: 4, nodes: 11 }), measure(query));
+    }
+
+    #[test]
+    fn should_turn_away_past_the_limits() {
+        let limits = QueryLimits { max_depth: 3, max_nodes: 6 };
+
+        let deep = "{ a { b { c { d } } } }";
+        let wide = "{ a { ...F ...F } } fragment F on A { b c d }";
+
+        assert!(limits.admit("{ a { b { c } } }").is_ok());
+        assert_eq!(QUERY_TOO_DEEP, limits.admit(deep).unwrap_err()["errors"][0]["extensions"]["code"]);
+        assert_eq!(QUERY_TOO_LARGE, limits.admit(wide).unwrap_err()["errors"][0]["extensions"]["code"]);
+        assert!(limits.admit("{ a { ").is_ok());
+    }
+}
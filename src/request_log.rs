@@ -0,0 +1,179 @@
+/**
+ * Every request gets an id, the one in its X-Request-Id header when the proxy
+ * sent a sane one, else a fresh one. The id goes back in the X-Request-Id header
+ * of the response, and a line with the method, the path, the status and the
+ * latency is logged as the response leaves; a slow request is logged as a warning.
+ *
+ * The GraphQL handler carries the span of the request into the context and enters
+ * it while the resolvers run, so that the events of the services fall under the
+ * request; it names the operation of a slow execution with the id of the request.
+ *
+ * SLOW_REQUEST_MS      a request taking longer is slow, 500 by default
+ *
+ * The events reach the env_logger through the log feature of tracing, under the
+ * ferris target.
+ */
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage, HttpRequest};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use tracing::{Instrument, Span};
+
+use crate::commons::util;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const DEFAULT_SLOW_MS: u64 = 500;
+const MAX_ID_LENGTH: usize = 64;
+
+/**
+ * The id and the span of a request, kept in its extensions.
+ */
+#[derive(Clone, Debug)]
+pub struct RequestTrace {
+    pub id: String,
+    pub span: Span,
+}
+
+/**
+ * The id of the request, empty for a request that did not pass the middleware.
+ */
+pub fn request_id(request: &HttpRequest) -> String {
+    request.extensions().get::<RequestTrace>().map(|trace| trace.id.clone()).unwrap_or_default()
+}
+
+pub fn request_span(request: &HttpRequest) -> Span {
+    request.extensions().get::<RequestTrace>().map(|trace| trace.span.clone()).unwrap_or_else(Span::none)
+}
+
+pub fn is_slow(elapsed: Duration) -> bool {
+    static SLOW: OnceLock<Duration> = OnceLock::new();
+
+    let threshold = SLOW.get_or_init(|| {
+        let millis = dotenv::var("SLOW_REQUEST_MS").ok().and_then(|value| value.parse::<u64>().ok()).filter(|value| *value > 0);
+        Duration::from_millis(millis.unwrap_or(DEFAULT_SLOW_MS))
+    });
+
+    elapsed > *threshold
+}
+
+// An id of the proxy is kept only when it cannot break the header or the log line.
+fn given_id(request: &ServiceRequest) -> Option<String> {
+    let value = request.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+
+    let sane = !value.is_empty() && value.len() <= MAX_ID_LENGTH && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if sane {
+        Some(value.to_owned())
+    } else {
+        None
+    }
+}
+
+pub struct RequestLog;
+
+impl<S, B> Transform<S> for RequestLog
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestLogMiddleware { service })
+    }
+}
+
+pub struct RequestLogMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RequestLogMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: ServiceRequest) -> Self::Future {
+        let id = given_id(&request).unwrap_or_else(util::fuzzy_id);
+        let method = request.method().to_string();
+        let path = request.path().to_owned();
+
+        let span = tracing::info_span!("request", id = %id, method = %method, path = %path);
+        request.extensions_mut().insert(RequestTrace { id: id.clone(), span: span.clone() });
+
+        let started = Instant::now();
+        let future = self.service.call(request);
+
+        let logged = async move {
+            let result = future.await;
+            let elapsed = started.elapsed();
+            let elapsed_ms = elapsed.as_millis() as u64;
+
+            let mut response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::error!(request_id = %id, method = %method, path = %path, elapsed_ms, error = %e, "request failed");
+                    return Err(e);
+                }
+            };
+
+            if let Ok(value) = HeaderValue::from_str(&id) {
+                response.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+
+            let status = response.status().as_u16();
+            if is_slow(elapsed) {
+                tracing::warn!(request_id = %id, method = %method, path = %path, status, elapsed_ms, "slow request");
+            } else {
+                tracing::info!(request_id = %id, method = %method, path = %path, status, elapsed_ms, "request");
+            }
+
+            Ok(response)
+        };
+
+        Box::pin(logged.instrument(span))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use actix_web::{test, web, App, HttpResponse};
+
+    #[actix_rt::test]
+    async fn should_give_every_request_an_id() {
+        let mut app = test::init_service(App::new().wrap(RequestLog).route("/", web::get().to(HttpResponse::Ok))).await;
+
+        let fresh = test::call_service(&mut app, test::TestRequest::get().uri("/").to_request()).await;
+        assert!(!fresh.headers().get(REQUEST_ID_HEADER).unwrap().is_empty());
+
+        let given = test::TestRequest::get().uri("/").header(REQUEST_ID_HEADER, "lb-42").to_request();
+        let given = test::call_service(&mut app, given).await;
+        assert_eq!("lb-42", given.headers().get(REQUEST_ID_HEADER).unwrap());
+
+        let forged = test::TestRequest::get().uri("/").header(REQUEST_ID_HEADER, "a b\tc").to_request();
+        let forged = test::call_service(&mut app, forged).await;
+        assert_ne!("a b\tc", forged.headers().get(REQUEST_ID_HEADER).unwrap());
+    }
+}
@@ -6,6 +6,137 @@ table! {
     }
 }
 
+table! {
+    agreement_acceptances (user_id, agreement_id) {
+        user_id -> Varchar,
+        agreement_id -> Varchar,
+        accepted_at -> Datetime,
+    }
+}
+
+table! {
+    agreements (id) {
+        id -> Varchar,
+        version -> Varchar,
+        title -> Varchar,
+        body -> Text,
+        is_required -> Bool,
+        published_by_id -> Varchar,
+        published_at -> Datetime,
+    }
+}
+
+table! {
+    audit_events (id) {
+        id -> Varchar,
+        entity_type -> Varchar,
+        entity_id -> Varchar,
+        action -> Varchar,
+        actor_id -> Varchar,
+        before_state -> Nullable<Text>,
+        after_state -> Nullable<Text>,
+        reason -> Nullable<Text>,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    banner_dismissals (banner_id, user_id) {
+        banner_id -> Varchar,
+        user_id -> Varchar,
+        dismissed_at -> Datetime,
+    }
+}
+
+table! {
+    board_annotations (id) {
+        id -> Varchar,
+        board_id -> Varchar,
+        board_name -> Varchar,
+        created_by_id -> Varchar,
+        overlay -> Text,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    board_versions (id) {
+        id -> Varchar,
+        board_id -> Varchar,
+        board_name -> Varchar,
+        version -> Integer,
+        file_path -> Varchar,
+        checksum -> Char,
+        size -> Bigint,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    coach_availability (id) {
+        id -> Varchar,
+        coach_id -> Varchar,
+        weekday -> Integer,
+        from_minute -> Integer,
+        to_minute -> Integer,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    coach_availability_exceptions (id) {
+        id -> Varchar,
+        coach_id -> Varchar,
+        from_date -> Datetime,
+        to_date -> Datetime,
+        is_available -> Bool,
+        reason -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    coach_daily_stats (coach_id, day) {
+        coach_id -> Varchar,
+        day -> Date,
+        enrollments_created -> Integer,
+        sessions_created -> Integer,
+        sessions_done -> Integer,
+        sessions_cancelled -> Integer,
+        tasks_responded -> Integer,
+        refreshed_at -> Datetime,
+    }
+}
+
+table! {
+    coach_onboarding (coach_id) {
+        coach_id -> Varchar,
+        payment_account -> Nullable<Varchar>,
+        completed_at -> Nullable<Datetime>,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    coach_profiles (coach_id) {
+        coach_id -> Varchar,
+        headline -> Varchar,
+        specialties -> Varchar,
+        languages -> Varchar,
+        is_listed -> Bool,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+        bio -> Varchar,
+        avatar_url -> Varchar,
+        availability -> Varchar,
+        intro_video_url -> Varchar,
+        completeness -> Integer,
+        missing -> Varchar,
+    }
+}
+
 table! {
     coaches (id) {
         id -> Varchar,
@@ -18,6 +149,16 @@ table! {
     }
 }
 
+table! {
+    conference_recordings (id) {
+        id -> Varchar,
+        conference_id -> Varchar,
+        attached_by_id -> Varchar,
+        location -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
 table! {
     conferences (id) {
         id -> Varchar,
@@ -39,6 +180,31 @@ table! {
         closing_notes -> Nullable<Text>,
         created_at -> Datetime,
         updated_at -> Datetime,
+        expired_at -> Nullable<Datetime>,
+    }
+}
+
+table! {
+    coupon_redemptions (enrollment_id) {
+        enrollment_id -> Varchar,
+        coupon_id -> Varchar,
+        discount_cents -> Integer,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    coupons (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        coach_id -> Varchar,
+        code -> Varchar,
+        percent_off -> Nullable<Integer>,
+        amount_off_cents -> Nullable<Integer>,
+        valid_from -> Datetime,
+        valid_until -> Datetime,
+        max_uses -> Nullable<Integer>,
+        created_at -> Datetime,
     }
 }
 
@@ -46,8 +212,8 @@ table! {
     correspondences (id) {
         id -> Varchar,
         from_user_id -> Varchar,
-        program_id -> Varchar,
-        enrollment_id -> Varchar,
+        program_id -> Nullable<Varchar>,
+        enrollment_id -> Nullable<Varchar>,
         from_email -> Varchar,
         subject -> Varchar,
         content -> Nullable<Text>,
@@ -61,6 +227,17 @@ table! {
         created_at -> Datetime,
         updated_at -> Datetime,
         mail_type -> Varchar,
+        content_text -> Nullable<Text>,
+    }
+}
+
+table! {
+    demo_sandboxes (id) {
+        id -> Varchar,
+        coach_user_id -> Varchar,
+        member_user_id -> Varchar,
+        program_id -> Varchar,
+        created_at -> Datetime,
     }
 }
 
@@ -78,6 +255,7 @@ table! {
         coach_name -> Varchar,
         member_id -> Varchar,
         member_name -> Varchar,
+        read_at -> Nullable<Datetime>,
     }
 }
 
@@ -92,6 +270,28 @@ table! {
     }
 }
 
+table! {
+    enrollment_coaches (enrollment_id, coach_id) {
+        enrollment_id -> Varchar,
+        coach_id -> Varchar,
+        added_by_id -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    enrollment_handovers (id) {
+        id -> Varchar,
+        enrollment_id -> Varchar,
+        from_program_id -> Varchar,
+        to_program_id -> Varchar,
+        from_coach_id -> Varchar,
+        to_coach_id -> Varchar,
+        note -> Text,
+        created_at -> Datetime,
+    }
+}
+
 table! {
     enrollments (id) {
         id -> Varchar,
@@ -100,6 +300,134 @@ table! {
         created_at -> Datetime,
         updated_at -> Datetime,
         is_new -> Bool,
+        ended_at -> Nullable<Datetime>,
+        end_reason -> Nullable<Text>,
+    }
+}
+
+table! {
+    fee_rules (id) {
+        id -> Varchar,
+        schedule_id -> Varchar,
+        genre_id -> Nullable<Varchar>,
+        fee_bps -> Integer,
+    }
+}
+
+table! {
+    fee_schedules (id) {
+        id -> Varchar,
+        effective_from -> Datetime,
+        note -> Varchar,
+        created_by_id -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    field_usage (day, operation_name, type_name, field_name) {
+        day -> Date,
+        operation_name -> Varchar,
+        type_name -> Varchar,
+        field_name -> Varchar,
+        hits -> Bigint,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    file_access_log (id) {
+        id -> Varchar,
+        asset_class -> Varchar,
+        file_path -> Varchar,
+        accessor_id -> Nullable<Varchar>,
+        ip -> Nullable<Varchar>,
+        accessed_at -> Datetime,
+    }
+}
+
+table! {
+    file_registry (id) {
+        id -> Varchar,
+        file_path -> Varchar,
+        checksum -> Char,
+        size -> Bigint,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    goal_cards (id) {
+        id -> Varchar,
+        enrollment_id -> Varchar,
+        column_name -> Varchar,
+        position -> Integer,
+        title -> Varchar,
+        description -> Varchar,
+        status -> Varchar,
+        created_by_id -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    goal_comments (id) {
+        id -> Varchar,
+        card_id -> Varchar,
+        author_id -> Varchar,
+        comment -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    guest_links (id) {
+        id -> Varchar,
+        session_id -> Varchar,
+        created_by_id -> Varchar,
+        guest_name -> Varchar,
+        guest_email -> Nullable<Varchar>,
+        token -> Varchar,
+        expires_at -> Datetime,
+        revoked_at -> Nullable<Datetime>,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    invoices (id) {
+        id -> Varchar,
+        coach_id -> Varchar,
+        period_start -> Datetime,
+        currency -> Varchar,
+        enrollment_count -> Integer,
+        gross_cents -> Bigint,
+        fee_cents -> Bigint,
+        net_cents -> Bigint,
+        file_path -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    late_policies (program_id) {
+        program_id -> Varchar,
+        grace_days -> Integer,
+        extend_on_absence -> Bool,
+        updated_by_id -> Varchar,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    mail_bounces (id) {
+        id -> Varchar,
+        email -> Varchar,
+        bounce_type -> Varchar,
+        reason -> Nullable<Text>,
+        created_at -> Datetime,
     }
 }
 
@@ -152,6 +480,30 @@ table! {
     }
 }
 
+table! {
+    member_absences (id) {
+        id -> Varchar,
+        member_id -> Varchar,
+        from_date -> Datetime,
+        to_date -> Datetime,
+        reason -> Varchar,
+        recorded_by_id -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    notifications (id) {
+        id -> Varchar,
+        user_id -> Varchar,
+        kind -> Varchar,
+        subject -> Varchar,
+        ref_id -> Varchar,
+        read_at -> Nullable<Datetime>,
+        created_at -> Datetime,
+    }
+}
+
 table! {
     objectives (id) {
         id -> Varchar,
@@ -177,6 +529,7 @@ table! {
         description -> Nullable<Text>,
         created_at -> Datetime,
         updated_at -> Datetime,
+        visibility -> Varchar,
     }
 }
 
@@ -190,18 +543,119 @@ table! {
     }
 }
 
+table! {
+    organization_members (organization_id, user_id) {
+        organization_id -> Varchar,
+        user_id -> Varchar,
+        role -> Varchar,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    organizations (id) {
+        id -> Varchar,
+        name -> Varchar,
+        created_by_id -> Varchar,
+        created_at -> Datetime,
+        session_hours -> Nullable<Varchar>,
+        session_holidays -> Nullable<Text>,
+    }
+}
+
+table! {
+    password_resets (id) {
+        id -> Varchar,
+        user_id -> Varchar,
+        token -> Varchar,
+        expires_at -> Datetime,
+        used_at -> Nullable<Datetime>,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    payments (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        member_id -> Varchar,
+        enrollment_id -> Nullable<Varchar>,
+        amount_cents -> Integer,
+        currency -> Varchar,
+        gateway_ref -> Varchar,
+        status -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    platform_banners (id) {
+        id -> Varchar,
+        created_by_id -> Varchar,
+        title -> Varchar,
+        message -> Text,
+        severity -> Varchar,
+        audience -> Varchar,
+        starts_at -> Datetime,
+        ends_at -> Datetime,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
 table! {
     platform_roles (id) {
         id -> Varchar,
     }
 }
 
+table! {
+    program_announcements (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        coach_id -> Varchar,
+        title -> Varchar,
+        content -> Text,
+        publish_at -> Nullable<Datetime>,
+        published_at -> Nullable<Datetime>,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+        content_text -> Nullable<Text>,
+    }
+}
+
+table! {
+    program_faqs (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        question -> Text,
+        answer -> Text,
+        position -> Integer,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
 table! {
     program_genres (id) {
         id -> Varchar,
     }
 }
 
+table! {
+    program_offers (id) {
+        id -> Varchar,
+        request_id -> Varchar,
+        coach_id -> Varchar,
+        program_id -> Varchar,
+        message -> Text,
+        status -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
 table! {
     program_plans (id) {
         id -> Varchar,
@@ -212,6 +666,57 @@ table! {
     }
 }
 
+table! {
+    program_questions (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        asked_by_id -> Varchar,
+        question -> Text,
+        answer -> Nullable<Text>,
+        answered_at -> Nullable<Datetime>,
+        faq_id -> Nullable<Varchar>,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    program_requests (id) {
+        id -> Varchar,
+        member_id -> Varchar,
+        title -> Varchar,
+        needs -> Text,
+        tags -> Varchar,
+        status -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    program_reviews (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        enrollment_id -> Varchar,
+        member_id -> Varchar,
+        rating -> Integer,
+        comment -> Text,
+        status -> Varchar,
+        hidden_reason -> Nullable<Varchar>,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    program_waitlists (id) {
+        id -> Varchar,
+        program_id -> Varchar,
+        member_id -> Varchar,
+        created_at -> Datetime,
+        promoted_at -> Nullable<Datetime>,
+    }
+}
+
 table! {
     programs (id) {
         id -> Varchar,
@@ -226,6 +731,34 @@ table! {
         genre_id -> Nullable<Varchar>,
         is_parent -> Bool,
         parent_program_id -> Nullable<Varchar>,
+        description_text -> Nullable<Text>,
+        max_members -> Nullable<Integer>,
+        tags -> Varchar,
+        status -> Varchar,
+        price_cents -> Nullable<Integer>,
+        currency -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    recording_consents (session_user_id) {
+        session_user_id -> Varchar,
+        conference_id -> Varchar,
+        user_id -> Varchar,
+        decision -> Varchar,
+        decided_at -> Datetime,
+    }
+}
+
+table! {
+    saved_filters (id) {
+        id -> Varchar,
+        coach_id -> Varchar,
+        name -> Varchar,
+        target -> Varchar,
+        criteria -> Text,
+        created_at -> Datetime,
+        updated_at -> Datetime,
     }
 }
 
@@ -239,6 +772,12 @@ table! {
         file_size -> Nullable<Integer>,
         created_at -> Datetime,
         updated_at -> Datetime,
+        duration_seconds -> Nullable<Integer>,
+        width -> Nullable<Integer>,
+        height -> Nullable<Integer>,
+        poster_path -> Nullable<Varchar>,
+        metadata_status -> Varchar,
+        page_count -> Nullable<Integer>,
     }
 }
 
@@ -256,6 +795,44 @@ table! {
     }
 }
 
+table! {
+    session_objectives (session_id, objective_id) {
+        session_id -> Varchar,
+        objective_id -> Varchar,
+        rating -> Nullable<Integer>,
+        tagged_at -> Datetime,
+    }
+}
+
+table! {
+    session_reminders (event_id, user_id, starts_at) {
+        event_id -> Varchar,
+        user_id -> Varchar,
+        starts_at -> Datetime,
+        reminded_at -> Datetime,
+    }
+}
+
+table! {
+    session_scratchpads (session_id) {
+        session_id -> Varchar,
+        coach_id -> Varchar,
+        content -> Text,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    session_summaries (session_id) {
+        session_id -> Varchar,
+        coach_id -> Varchar,
+        went_well -> Text,
+        action_items -> Text,
+        next_steps -> Text,
+        updated_at -> Datetime,
+    }
+}
+
 table! {
     session_users (id) {
         id -> Varchar,
@@ -265,6 +842,16 @@ table! {
     }
 }
 
+table! {
+    session_visits (id) {
+        id -> Varchar,
+        session_id -> Varchar,
+        user_id -> Varchar,
+        joined_at -> Datetime,
+        left_at -> Nullable<Datetime>,
+    }
+}
+
 table! {
     sessions (id) {
         id -> Varchar,
@@ -290,6 +877,41 @@ table! {
         is_request -> Bool,
         conference_id -> Nullable<Varchar>,
         session_type -> Char,
+        expired_at -> Nullable<Datetime>,
+        parent_conference_id -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    stat_refresh_queue (coach_id, day) {
+        coach_id -> Varchar,
+        day -> Date,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    support_tickets (id) {
+        id -> Varchar,
+        raised_by_id -> Varchar,
+        category -> Varchar,
+        subject -> Varchar,
+        status -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+        resolved_at -> Nullable<Datetime>,
+    }
+}
+
+table! {
+    task_extensions (id) {
+        id -> Varchar,
+        task_id -> Varchar,
+        absence_id -> Nullable<Varchar>,
+        reason -> Varchar,
+        previous_end_date -> Datetime,
+        new_end_date -> Datetime,
+        created_at -> Datetime,
     }
 }
 
@@ -332,6 +954,41 @@ table! {
         approved_at -> Nullable<Datetime>,
         cancelled_at -> Nullable<Datetime>,
         responded_date -> Nullable<Datetime>,
+        session_id -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    ticket_messages (id) {
+        id -> Varchar,
+        ticket_id -> Varchar,
+        author_id -> Varchar,
+        body -> Text,
+        attachments -> Nullable<Text>,
+        created_at -> Datetime,
+    }
+}
+
+table! {
+    user_locales (user_id) {
+        user_id -> Varchar,
+        locale -> Varchar,
+        time_zone -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    user_profiles (user_id) {
+        user_id -> Varchar,
+        headline -> Varchar,
+        about -> Text,
+        experience -> Text,
+        social_links -> Varchar,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+        weekly_digest -> Bool,
     }
 }
 
@@ -345,22 +1002,98 @@ table! {
         created_at -> Datetime,
         updated_at -> Datetime,
         password -> Varchar,
+        utc_offset -> Integer,
+        email_invalid -> Bool,
+    }
+}
+
+table! {
+    webhook_deliveries (id) {
+        id -> Varchar,
+        subscription_id -> Varchar,
+        url -> Varchar,
+        body -> Text,
+        status -> Varchar,
+        created_at -> Datetime,
+        event -> Varchar,
+        attempts -> Integer,
+        next_attempt_at -> Datetime,
+        response_code -> Nullable<Integer>,
+        last_error -> Nullable<Varchar>,
+        delivered_at -> Nullable<Datetime>,
+        updated_at -> Datetime,
+    }
+}
+
+table! {
+    webhook_subscriptions (id) {
+        id -> Varchar,
+        coach_id -> Varchar,
+        event -> Varchar,
+        format -> Varchar,
+        url -> Varchar,
+        is_active -> Bool,
+        created_at -> Datetime,
+        updated_at -> Datetime,
+        secret -> Varchar,
+    }
+}
+
+table! {
+    weekly_digests (enrollment_id, week_of) {
+        enrollment_id -> Varchar,
+        week_of -> Datetime,
+        sent_at -> Datetime,
     }
 }
 
 joinable!(abstract_tasks -> coaches (coach_id));
+joinable!(agreement_acceptances -> agreements (agreement_id));
+joinable!(agreement_acceptances -> users (user_id));
+joinable!(agreements -> users (published_by_id));
+joinable!(audit_events -> users (actor_id));
+joinable!(banner_dismissals -> platform_banners (banner_id));
+joinable!(banner_dismissals -> users (user_id));
+joinable!(board_annotations -> users (created_by_id));
+joinable!(coach_availability -> users (coach_id));
+joinable!(coach_availability_exceptions -> users (coach_id));
+joinable!(coach_daily_stats -> coaches (coach_id));
+joinable!(coach_onboarding -> coaches (coach_id));
+joinable!(coach_profiles -> coaches (coach_id));
 joinable!(coaches -> users (user_id));
+joinable!(conference_recordings -> conferences (conference_id));
+joinable!(conference_recordings -> users (attached_by_id));
 joinable!(conferences -> programs (program_id));
+joinable!(coupon_redemptions -> coupons (coupon_id));
+joinable!(coupon_redemptions -> enrollments (enrollment_id));
+joinable!(coupons -> programs (program_id));
+joinable!(coupons -> users (coach_id));
 joinable!(correspondences -> enrollments (enrollment_id));
 joinable!(correspondences -> programs (program_id));
 joinable!(correspondences -> users (from_user_id));
+joinable!(demo_sandboxes -> programs (program_id));
 joinable!(discussion_queue -> discussions (discussion_id));
 joinable!(discussion_queue -> enrollments (enrollment_id));
 joinable!(discussion_queue -> users (to_id));
 joinable!(discussions -> enrollments (enrollment_id));
 joinable!(discussions -> users (created_by_id));
+joinable!(enrollment_coaches -> enrollments (enrollment_id));
+joinable!(enrollment_coaches -> users (coach_id));
+joinable!(enrollment_handovers -> enrollments (enrollment_id));
 joinable!(enrollments -> programs (program_id));
 joinable!(enrollments -> users (member_id));
+joinable!(fee_rules -> fee_schedules (schedule_id));
+joinable!(fee_rules -> program_genres (genre_id));
+joinable!(fee_schedules -> users (created_by_id));
+joinable!(goal_cards -> enrollments (enrollment_id));
+joinable!(goal_cards -> users (created_by_id));
+joinable!(goal_comments -> goal_cards (card_id));
+joinable!(goal_comments -> users (author_id));
+joinable!(guest_links -> sessions (session_id));
+joinable!(guest_links -> users (created_by_id));
+joinable!(invoices -> users (coach_id));
+joinable!(late_policies -> programs (program_id));
+joinable!(late_policies -> users (updated_by_id));
 joinable!(mail_recipients -> correspondences (correspondence_id));
 joinable!(mail_recipients -> users (to_user_id));
 joinable!(master_plans -> coaches (coach_id));
@@ -369,50 +1102,156 @@ joinable!(master_tasks -> abstract_tasks (abstract_task_id));
 joinable!(master_tasks -> coaches (coach_id));
 joinable!(master_tasks -> master_plans (master_plan_id));
 joinable!(master_tasks -> platform_roles (role_id));
+joinable!(notifications -> users (user_id));
 joinable!(objectives -> enrollments (enrollment_id));
 joinable!(observations -> enrollments (enrollment_id));
 joinable!(options -> enrollments (enrollment_id));
+joinable!(organization_members -> organizations (organization_id));
+joinable!(organization_members -> users (user_id));
+joinable!(organizations -> users (created_by_id));
+joinable!(password_resets -> users (user_id));
+joinable!(payments -> enrollments (enrollment_id));
+joinable!(payments -> programs (program_id));
+joinable!(payments -> users (member_id));
+joinable!(platform_banners -> users (created_by_id));
+joinable!(program_announcements -> programs (program_id));
+joinable!(program_announcements -> users (coach_id));
+joinable!(program_faqs -> programs (program_id));
+joinable!(program_offers -> coaches (coach_id));
+joinable!(program_offers -> program_requests (request_id));
+joinable!(program_offers -> programs (program_id));
 joinable!(program_plans -> master_plans (master_plan_id));
 joinable!(program_plans -> programs (program_id));
+joinable!(program_questions -> program_faqs (faq_id));
+joinable!(program_questions -> programs (program_id));
+joinable!(program_questions -> users (asked_by_id));
+joinable!(program_requests -> users (member_id));
+joinable!(program_reviews -> enrollments (enrollment_id));
+joinable!(program_reviews -> programs (program_id));
+joinable!(program_reviews -> users (member_id));
+joinable!(program_waitlists -> programs (program_id));
+joinable!(program_waitlists -> users (member_id));
 joinable!(programs -> coaches (coach_id));
 joinable!(programs -> program_genres (genre_id));
+joinable!(recording_consents -> conferences (conference_id));
+joinable!(recording_consents -> session_users (session_user_id));
+joinable!(recording_consents -> users (user_id));
+joinable!(saved_filters -> users (coach_id));
 joinable!(session_files -> session_notes (session_note_id));
 joinable!(session_notes -> session_users (session_user_id));
 joinable!(session_notes -> sessions (session_id));
 joinable!(session_notes -> users (created_by_id));
+joinable!(session_objectives -> objectives (objective_id));
+joinable!(session_objectives -> sessions (session_id));
+joinable!(session_reminders -> users (user_id));
+joinable!(session_scratchpads -> sessions (session_id));
+joinable!(session_scratchpads -> users (coach_id));
+joinable!(session_summaries -> sessions (session_id));
+joinable!(session_summaries -> users (coach_id));
 joinable!(session_users -> sessions (session_id));
 joinable!(session_users -> users (user_id));
+joinable!(session_visits -> sessions (session_id));
+joinable!(session_visits -> users (user_id));
 joinable!(sessions -> conferences (conference_id));
 joinable!(sessions -> enrollments (enrollment_id));
 joinable!(sessions -> programs (program_id));
+joinable!(stat_refresh_queue -> coaches (coach_id));
+joinable!(support_tickets -> users (raised_by_id));
+joinable!(task_extensions -> member_absences (absence_id));
+joinable!(task_extensions -> tasks (task_id));
 joinable!(task_links -> enrollments (enrollment_id));
 joinable!(tasks -> enrollments (enrollment_id));
 joinable!(tasks -> users (actor_id));
+joinable!(ticket_messages -> support_tickets (ticket_id));
+joinable!(ticket_messages -> users (author_id));
+joinable!(user_locales -> users (user_id));
+joinable!(user_profiles -> users (user_id));
+joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
+joinable!(webhook_subscriptions -> users (coach_id));
+joinable!(weekly_digests -> enrollments (enrollment_id));
 
 allow_tables_to_appear_in_same_query!(
     abstract_tasks,
+    agreement_acceptances,
+    agreements,
+    audit_events,
+    banner_dismissals,
+    board_annotations,
+    board_versions,
+    coach_availability,
+    coach_availability_exceptions,
+    coach_daily_stats,
+    coach_onboarding,
+    coach_profiles,
     coaches,
+    conference_recordings,
     conferences,
+    coupon_redemptions,
+    coupons,
     correspondences,
+    demo_sandboxes,
     discussion_queue,
     discussions,
+    enrollment_coaches,
+    enrollment_handovers,
     enrollments,
+    fee_rules,
+    fee_schedules,
+    field_usage,
+    file_access_log,
+    file_registry,
+    goal_cards,
+    goal_comments,
+    guest_links,
+    invoices,
+    late_policies,
+    mail_bounces,
     mail_recipients,
     master_plans,
     master_task_links,
     master_tasks,
+    member_absences,
+    notifications,
     objectives,
     observations,
     options,
+    organization_members,
+    organizations,
+    password_resets,
+    payments,
+    platform_banners,
     platform_roles,
+    program_announcements,
+    program_faqs,
     program_genres,
+    program_offers,
     program_plans,
+    program_questions,
+    program_requests,
+    program_reviews,
+    program_waitlists,
     programs,
+    recording_consents,
+    saved_filters,
     session_files,
     session_notes,
+    session_objectives,
+    session_reminders,
+    session_scratchpads,
+    session_summaries,
     session_users,
+    session_visits,
     sessions,
+    stat_refresh_queue,
+    support_tickets,
+    task_extensions,
     task_links,
     tasks,
+    ticket_messages,
+    user_locales,
+    user_profiles,
     users,
+    webhook_deliveries,
+    webhook_subscriptions,
+    weekly_digests,
 );
@@ -1,12 +1,16 @@
 use diesel::prelude::*;
 use super::prelude::*;
 
+use crate::commons::ids::UserId;
+
 use crate::models::users::Registration;
 use crate::models::users::LoginRequest;
+use crate::models::users::{AccountTargetState, ChangeAccountStateRequest, DeactivateAccountRequest};
 
 use crate::services::users::register;
 use crate::services::users::authenticate;
-use crate::services::users::INVALID_CREDENTIAL;
+use crate::services::users::{change_account_state, deactivate_account};
+use crate::services::users::{ACCOUNT_DEACTIVATED, INVALID_ADMIN, INVALID_CREDENTIAL};
 
 #[test]
 pub fn should_authenticate_valid_user() {
@@ -48,6 +52,63 @@ pub fn should_defend_invalid_login() {
     });
 }
 
+#[test]
+pub fn should_turn_away_a_deactivated_user() {
+    let connection = connection_without_transaction();
+
+    connection.test_transaction::<_,String,_>(||{
+
+        let reg_request = build_registration_request();
+        let user = register(&connection,&reg_request).unwrap();
+
+        let request = DeactivateAccountRequest { reason: String::from("Taking a break") };
+        let result = deactivate_account(&connection, Some(&user.id), &request);
+        assert_eq!(result.unwrap().blocked,true);
+
+        let result = authenticate(&connection, build_known_login_request());
+        assert_eq!(result.unwrap_err(),ACCOUNT_DEACTIVATED);
+
+        // Only an admin may bring the account back.
+        let request = build_account_state_request(user.id.as_str(), AccountTargetState::REACTIVATE);
+        let result = change_account_state(&connection, Some(&user.id), &request);
+        assert_eq!(result.unwrap_err(),INVALID_ADMIN);
+
+        Ok(())
+    });
+}
+
+#[test]
+pub fn should_let_only_an_admin_deactivate_others() {
+    let connection = connection_without_transaction();
+
+    connection.test_transaction::<_,String,_>(||{
+
+        let reg_request = build_registration_request();
+        let user = register(&connection,&reg_request).unwrap();
+
+        let request = build_account_state_request("someone-else", AccountTargetState::DEACTIVATE);
+        let result = change_account_state(&connection, Some(&user.id), &request);
+        assert_eq!(result.unwrap_err(),INVALID_ADMIN);
+
+        // Not even the own account; a user closes it with deactivate_account.
+        let request = build_account_state_request(user.id.as_str(), AccountTargetState::DEACTIVATE);
+        let result = change_account_state(&connection, Some(&user.id), &request);
+        assert_eq!(result.unwrap_err(),INVALID_ADMIN);
+
+        assert!(change_account_state(&connection, None, &request).is_err());
+
+        Ok(())
+    });
+}
+
+fn build_account_state_request(user_id: &str, target_state: AccountTargetState) -> ChangeAccountStateRequest {
+    ChangeAccountStateRequest {
+        user_id: UserId::from(user_id),
+        target_state,
+        reason: String::from("Taking a break"),
+    }
+}
+
 /**
  * Used to precreate a user with a credential.
  */
@@ -56,6 +117,7 @@ fn build_registration_request() -> Registration {
         full_name: String::from("Full_Name-1"),
         email: String::from("email3@krscode.com"),
         password: String::from("password"),
+        utc_offset: None,
     }
 }
 
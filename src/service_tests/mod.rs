@@ -1,6 +1,16 @@
 pub mod prelude {
 
     use diesel::prelude::*;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use std::time::Duration;
+
+    use crate::db_manager::{ReadWritePool, RequestConnection};
+    use crate::loaders::Loaders;
+    use crate::feed_events::FeedEvents;
+    use crate::field_usage::{FieldCatalog, FieldUsage};
+    use crate::graphql_schema::{create_gq_schema, DBContext};
+    use crate::services::facades::Services;
+    use crate::session_events::SessionEvents;
 
     fn get_test_database_url() -> String {
         dotenv::dotenv().ok();
@@ -11,6 +21,28 @@ pub mod prelude {
         let db_url = get_test_database_url();
         MysqlConnection::establish(db_url.as_str()).unwrap()
     }
+
+    /**
+     * The context of a GraphQL request on the test database, with no caller.
+     */
+    pub fn context_without_transaction() -> DBContext {
+        let manager = ConnectionManager::<MysqlConnection>::new(get_test_database_url());
+        let pool = Pool::builder().max_size(2).build(manager).unwrap();
+        let schema = create_gq_schema();
+
+        DBContext {
+            db: ReadWritePool::new(pool.clone(), None),
+            reads_replica: false,
+            usage: FieldUsage::new(FieldCatalog::of(&schema)),
+            events: SessionEvents::new(1, Duration::from_secs(1)),
+            feeds: FeedEvents::default(),
+            services: Services::backed_by_diesel(),
+            caller: None,
+            connection: RequestConnection::default(),
+            loaders: Loaders::default(),
+            span: tracing::Span::none(),
+        }
+    }
 }
 
 pub mod authentication_feature;
@@ -20,3 +52,5 @@ pub mod password_reset_feature;
 pub mod program_creation_feature;
 
 pub mod session_tests;
+
+pub mod session_feeds_feature;
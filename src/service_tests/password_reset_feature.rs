@@ -1,12 +1,16 @@
 use diesel::prelude::*;
 use super::prelude::*;
 
+use crate::services::password_resets::{confirm_password_reset, request_password_reset};
 use crate::services::users::register;
 use crate::services::users::reset_password;
 
+use crate::models::password_resets::{ConfirmPasswordResetRequest, PasswordResetRequest};
 use crate::models::users::Registration;
 use crate::models::users::ResetPasswordRequest;
 
+use crate::schema::password_resets;
+
 #[test]
 pub fn should_reset_password() {
 
@@ -53,6 +57,54 @@ pub fn should_deny_if_wrong_request() {
     });
 }
 
+/**
+ * The token mailed for a forgotten password changes it once, and only once.
+ */
+#[test]
+pub fn should_reset_forgotten_password_with_the_token() {
+
+    let connection = connection_without_transaction();
+
+    connection.test_transaction::<_,String,_>(||{
+
+        let reg_request = build_registration_request();
+        let reg_result = register(&connection,&reg_request);
+
+        assert!(reg_result.is_ok());
+
+        let request = PasswordResetRequest { email: String::from("email1@krscode.com") };
+        assert!(request_password_reset(&connection, &request).is_ok());
+
+        let token: String = password_resets::table.select(password_resets::token).first(&connection).unwrap();
+
+        let confirm = ConfirmPasswordResetRequest { token, new_password: String::from("new_password") };
+        assert!(confirm_password_reset(&connection, &confirm).is_ok());
+        assert!(confirm_password_reset(&connection, &confirm).is_err());
+
+        Ok(())
+    });
+}
+
+/**
+ * An unknown email gets the same answer, and no token.
+ */
+#[test]
+pub fn should_not_tell_an_unknown_email() {
+
+    let connection = connection_without_transaction();
+
+    connection.test_transaction::<_,String,_>(||{
+
+        let request = PasswordResetRequest { email: String::from("nobody@krscode.com") };
+        assert!(request_password_reset(&connection, &request).is_ok());
+
+        let tokens: i64 = password_resets::table.count().get_result(&connection).unwrap();
+        assert_eq!(tokens,0);
+
+        Ok(())
+    });
+}
+
 fn build_reset_password_request() -> ResetPasswordRequest{
 
     ResetPasswordRequest{
@@ -79,5 +131,6 @@ fn build_registration_request() -> Registration {
         full_name: String::from("Full_Name-1"),
         email: String::from("email1@krscode.com"),
         password: String::from("password"),
+        utc_offset: None,
     }
 }
\ No newline at end of file
@@ -69,6 +69,7 @@ fn build_registration_request() -> Registration {
         full_name: String::from("Full_Name-1"),
         email: String::from("email_reg@krscode.com"),
         password: String::from("password"),
+        utc_offset: None,
     }
 }
 
@@ -78,6 +79,7 @@ fn build_blank_registration_request() -> Registration {
         full_name: String::from(""),
         email: String::from(""),
         password: String::from(""),
+        utc_offset: None,
     }
 }
 
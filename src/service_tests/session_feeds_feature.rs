@@ -0,0 +1,68 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+use super::prelude::context_without_transaction;
+
+use crate::commons::ids::{EnrollmentId, ProgramId, SessionId};
+use crate::db_manager::CONNECTION_IN_USE;
+use crate::models::discussion_queue::FeedCounts;
+use crate::models::sessions::Session;
+
+fn session() -> Session {
+    let at = NaiveDate::from_ymd(2021, 3, 1).and_hms(9, 0, 0);
+
+    Session {
+        id: SessionId::from("no-such-session"),
+        name: String::from("Followup"),
+        description: None,
+        program_id: ProgramId::from("p-1"),
+        enrollment_id: EnrollmentId::from("e-1"),
+        people: None,
+        duration: 60,
+        original_start_date: at,
+        original_end_date: at,
+        revised_start_date: None,
+        revised_end_date: None,
+        offered_start_date: None,
+        offered_end_date: None,
+        is_ready: false,
+        actual_start_date: None,
+        actual_end_date: None,
+        cancelled_at: None,
+        created_at: at,
+        updated_at: at,
+        closing_notes: None,
+        is_request: false,
+        conference_id: None,
+        session_type: String::from("mono"),
+        expired_at: None,
+        parent_conference_id: None,
+    }
+}
+
+#[test]
+pub fn should_push_the_feeds_on_the_connection_the_resolver_holds() {
+    let context = context_without_transaction();
+
+    let counts = FeedCounts {
+        user_id: String::from("u-1"),
+        pending_discussions: 0,
+        unread_notifications: 0,
+    };
+    let _feed = context.feeds.subscribe(&counts).unwrap();
+
+    let (done, finished) = mpsc::channel();
+
+    thread::spawn(move || {
+        let connection = context.connection().unwrap();
+        context.push_session_feeds(&connection, &session());
+
+        done.send(context.connection().err()).unwrap();
+    });
+
+    let asked_again = finished.recv_timeout(Duration::from_secs(30)).expect("the feeds were not pushed");
+    assert_eq!(Some(CONNECTION_IN_USE), asked_again);
+}
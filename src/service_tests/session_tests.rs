@@ -2,6 +2,8 @@
 use diesel::prelude::*;
 use super::prelude::*;
 
+use crate::commons::ids::{ProgramId, UserId};
+
 use crate::models::users::{Registration};
 use crate::models::programs::{NewProgramRequest};
 use crate::models::sessions::{NewSessionRequest};
@@ -21,11 +23,12 @@ fn program_request(coach_id: String) -> NewProgramRequest {
 }
 fn session_request() -> NewSessionRequest{
     NewSessionRequest{
-        program_id:String::from("1"),
-        member_id:String::from("1"),
+        program_id:ProgramId::from("1"),
+        member_id:UserId::from("1"),
         name: String::from("name"),
         description: String::from("name"),
         duration: 14,
         start_time: String::from("12"),
+        override_reason: None,
     }
 }
\ No newline at end of file
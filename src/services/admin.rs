@@ -0,0 +1,127 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::commons::chassis::{Page, Window};
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::util;
+use crate::models::admin::{BlockUserRequest, ForceDeactivateRequest, SystemStats, UserFilter};
+use crate::models::programs::{text_pattern, Program};
+use crate::models::users::User;
+
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::enrollments;
+use crate::schema::programs as programs_table;
+use crate::schema::sessions;
+use crate::schema::users as users_table;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const USERS_ERROR: &str = "Unable to list the users.";
+const STATS_ERROR: &str = "Unable to gather the stats of the platform.";
+
+/**
+ * The admin behind the request; the operations of the operators trust the
+ * signed in user alone, never an id in the request.
+ */
+pub fn admin_of(connection: &MysqlConnection, caller: Option<&UserId>) -> Result<User, &'static str> {
+    users::find_admin(connection, caller.ok_or(NOT_SIGNED_IN)?)
+}
+
+/**
+ * The users, the latest to join first, blocked or not.
+ */
+pub fn get_users(connection: &MysqlConnection, caller: Option<&UserId>, filter: &UserFilter, window: &Window) -> Result<Page<User>, &'static str> {
+    admin_of(connection, caller)?;
+    filter.validate()?;
+
+    let mut query = users_table::table.into_boxed();
+
+    if let Some(value) = &filter.user_type {
+        query = query.filter(users_table::user_type.eq(value));
+    }
+
+    if let Some(value) = filter.blocked {
+        query = query.filter(users_table::blocked.eq(value));
+    }
+
+    if let Some(value) = filter.text.as_ref().filter(|value| !value.trim().is_empty()) {
+        let pattern = text_pattern(value);
+        query = query.filter(users_table::full_name.like(pattern.to_owned()).or(users_table::email.like(pattern)));
+    }
+
+    let rows: Vec<User> = query
+        .order_by((users_table::created_at.desc(), users_table::id.asc()))
+        .offset(window.offset)
+        .limit(window.fetch())
+        .load(connection)
+        .map_err(|_| USERS_ERROR)?;
+
+    Ok(Page::of(rows, window))
+}
+
+/**
+ * Blocking is the deactivation of the account, with its upcoming sessions cancelled.
+ */
+pub fn block_user(connection: &MysqlConnection, caller: Option<&UserId>, request: &BlockUserRequest) -> Result<User, &'static str> {
+    users::change_account_state(connection, caller, &request.as_state_change())
+}
+
+pub fn force_deactivate_program(connection: &MysqlConnection, caller: Option<&UserId>, request: &ForceDeactivateRequest) -> Result<usize, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    programs::force_deactivate(connection, &admin, request)
+}
+
+/**
+ * The program submitted by its coach goes into the catalog once an admin
+ * reviewed it.
+ */
+pub fn publish_program(connection: &MysqlConnection, caller: Option<&UserId>, the_program_id: &ProgramId) -> Result<Program, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    programs::publish(connection, &admin, the_program_id)
+}
+
+/**
+ * The programs are the parent ones, as they are in the catalog.
+ */
+pub fn get_system_stats(connection: &MysqlConnection, caller: Option<&UserId>) -> Result<SystemStats, &'static str> {
+    admin_of(connection, caller)?;
+
+    let now = Utc::now().naive_utc();
+
+    let count_users = |the_type: Option<&str>, only_blocked: bool| -> QueryResult<i64> {
+        let mut query = users_table::table.into_boxed();
+        if let Some(value) = the_type {
+            query = query.filter(users_table::user_type.eq(value));
+        }
+        if only_blocked {
+            query = query.filter(users_table::blocked.eq(true));
+        }
+        query.count().get_result(connection)
+    };
+
+    let gather = || -> QueryResult<SystemStats> {
+        let parents = programs_table::table.filter(programs_table::is_parent.eq(true));
+        let open_sessions = sessions::table.filter(sessions::cancelled_at.is_null());
+
+        Ok(SystemStats {
+            users: count_users(None, false)? as i32,
+            members: count_users(Some(util::MEMBER), false)? as i32,
+            coaches: count_users(Some(util::COACH), false)? as i32,
+            blocked_users: count_users(None, true)? as i32,
+            programs: parents.count().get_result::<i64>(connection)? as i32,
+            active_programs: parents.filter(programs_table::active.eq(true)).count().get_result::<i64>(connection)? as i32,
+            active_enrollments: enrollments::table.filter(enrollments::ended_at.is_null()).count().get_result::<i64>(connection)? as i32,
+            upcoming_sessions: open_sessions
+                .filter(sessions::actual_end_date.is_null())
+                .filter(sessions::original_start_date.gt(now))
+                .count()
+                .get_result::<i64>(connection)? as i32,
+            completed_sessions: open_sessions.filter(sessions::actual_end_date.is_not_null()).count().get_result::<i64>(connection)? as i32,
+        })
+    };
+
+    gather().map_err(|_| STATS_ERROR)
+}
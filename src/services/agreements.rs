@@ -0,0 +1,121 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::models::agreements::{AcceptAgreementRequest, Agreement, AgreementStatus, NewAcceptance, NewAgreement, NewAgreementRequest};
+
+use crate::services::admin::admin_of;
+use crate::services::users;
+
+use crate::schema::agreement_acceptances;
+use crate::schema::agreements;
+
+const AGREEMENT_NOT_FOUND: &str = "Unable to find the agreement.";
+const VERSION_TAKEN: &str = "The version is already published.";
+const PUBLISH_ERROR: &str = "Unable to publish the agreement.";
+const ACCEPT_ERROR: &str = "Unable to record the acceptance of the agreement.";
+const AGREEMENT_FETCH_ERROR: &str = "Unable to fetch the agreement.";
+
+pub fn publish_agreement(connection: &MysqlConnection, caller: Option<&UserId>, request: &NewAgreementRequest) -> Result<Agreement, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let taken: i64 = agreements::table
+        .filter(agreements::version.eq(request.version.trim()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| PUBLISH_ERROR)?;
+
+    if taken > 0 {
+        return Err(VERSION_TAKEN);
+    }
+
+    let new_agreement = NewAgreement::from(request, &admin.id);
+
+    diesel::insert_into(agreements::table).values(&new_agreement).execute(connection).map_err(|_| PUBLISH_ERROR)?;
+
+    find(connection, new_agreement.id.as_str())
+}
+
+pub fn get_agreement_status(connection: &MysqlConnection, the_user_id: &UserId) -> Result<AgreementStatus, &'static str> {
+    let user = users::find(connection, the_user_id)?;
+
+    let agreement = latest_required(connection).map_err(|_| AGREEMENT_FETCH_ERROR)?;
+
+    let accepted_at = match &agreement {
+        Some(value) => accepted_at(connection, user.id.as_str(), value.id.as_str()).map_err(|_| AGREEMENT_FETCH_ERROR)?,
+        None => None,
+    };
+
+    Ok(AgreementStatus { agreement, accepted_at })
+}
+
+/**
+ * Accepting again keeps the time of the first acceptance.
+ */
+pub fn accept_agreement(connection: &MysqlConnection, request: &AcceptAgreementRequest) -> Result<AgreementStatus, &'static str> {
+    let user = users::find(connection, &request.user_id)?;
+    let agreement = find(connection, request.agreement_id.as_str())?;
+
+    let acceptance = NewAcceptance {
+        user_id: user.id.to_string(),
+        agreement_id: agreement.id.to_owned(),
+    };
+
+    diesel::insert_or_ignore_into(agreement_acceptances::table)
+        .values(&acceptance)
+        .execute(connection)
+        .map_err(|_| ACCEPT_ERROR)?;
+
+    let accepted_at = accepted_at(connection, user.id.as_str(), agreement.id.as_str()).map_err(|_| ACCEPT_ERROR)?;
+
+    Ok(AgreementStatus {
+        agreement: Some(agreement),
+        accepted_at,
+    })
+}
+
+/**
+ * The latest required agreement when the user is yet to accept it.
+ */
+pub fn pending_agreement(connection: &MysqlConnection, the_user_id: &UserId) -> Result<Option<Agreement>, &'static str> {
+    let agreement = match latest_required(connection).map_err(|_| AGREEMENT_FETCH_ERROR)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let accepted = accepted_at(connection, the_user_id, agreement.id.as_str()).map_err(|_| AGREEMENT_FETCH_ERROR)?;
+
+    if accepted.is_some() {
+        return Ok(None);
+    }
+
+    Ok(Some(agreement))
+}
+
+/**
+ * The latest required agreement, for the requests of no one in particular.
+ */
+pub fn latest_agreement(connection: &MysqlConnection) -> Result<Option<Agreement>, &'static str> {
+    latest_required(connection).map_err(|_| AGREEMENT_FETCH_ERROR)
+}
+
+fn latest_required(connection: &MysqlConnection) -> QueryResult<Option<Agreement>> {
+    agreements::table
+        .filter(agreements::is_required.eq(true))
+        .order_by(agreements::published_at.desc())
+        .first(connection)
+        .optional()
+}
+
+fn accepted_at(connection: &MysqlConnection, the_user_id: &str, the_agreement_id: &str) -> QueryResult<Option<NaiveDateTime>> {
+    agreement_acceptances::table
+        .filter(agreement_acceptances::user_id.eq(the_user_id))
+        .filter(agreement_acceptances::agreement_id.eq(the_agreement_id))
+        .select(agreement_acceptances::accepted_at)
+        .first(connection)
+        .optional()
+}
+
+fn find(connection: &MysqlConnection, the_id: &str) -> Result<Agreement, &'static str> {
+    agreements::table.filter(agreements::id.eq(the_id)).first(connection).map_err(|_| AGREEMENT_NOT_FOUND)
+}
@@ -0,0 +1,210 @@
+use diesel::prelude::*;
+use sodiumoxide::crypto::hash::sha256;
+use std::fs;
+use std::path::Path;
+
+use crate::commons::ids::UserId;
+use crate::commons::password;
+use crate::models::anonymizer::AnonymizeRequest;
+use crate::services::admin::admin_of;
+use crate::services::file_registry;
+use crate::storage::{storage, Area};
+
+use crate::schema::{
+    audit_events, coaches, conferences, correspondences, discussions, mail_bounces, mail_recipients, objectives, observations, options, session_notes, session_scratchpads, session_summaries,
+    sessions, tasks, users,
+};
+
+const ANONYMIZE_KEY: &str = "ALLOW_ANONYMIZE";
+const PASSWORD_KEY: &str = "ANONYMIZED_PASSWORD";
+const DEFAULT_PASSWORD: &str = "staging";
+
+const STAGING_DOMAIN: &str = "staging.ferris.test";
+const PLACEHOLDER: &str = "This text was replaced while anonymizing the data.";
+const FILE_PLACEHOLDER: &str = "This file was replaced while anonymizing the data.";
+
+const NOT_ALLOWED: &str = "Anonymizing is not enabled on this instance. Set ALLOW_ANONYMIZE=true on a staging instance only.";
+const ANONYMIZE_ERROR: &str = "Unable to anonymize the data. Nothing was changed in the database.";
+
+/**
+ * The same id always yields the same pseudonym, so the relations among the
+ * rows survive, and a second refresh produces the same names and emails.
+ */
+pub fn pseudonym(value: &str) -> String {
+    let digest = sha256::hash(value.as_bytes());
+    digest.0.iter().take(5).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn anonymize(connection: &MysqlConnection, caller: Option<&UserId>, request: &AnonymizeRequest) -> Result<String, &'static str> {
+    if std::env::var(ANONYMIZE_KEY).unwrap_or_default() != "true" {
+        return Err(NOT_ALLOWED);
+    }
+
+    admin_of(connection, caller)?;
+
+    let staging_password = std::env::var(PASSWORD_KEY).unwrap_or_else(|_| String::from(DEFAULT_PASSWORD));
+    let hashed_password = password::hash(staging_password.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let user_count = scramble_users(connection, hashed_password.as_str())?;
+        let text_count = replace_texts(connection)?;
+        Ok((user_count, text_count))
+    });
+
+    if result.is_err() {
+        return Err(ANONYMIZE_ERROR);
+    }
+
+    let (user_count, text_count) = result.unwrap();
+
+    let file_count = if request.with_files { forget_and_replace_files(connection) } else { 0 };
+
+    Ok(format!("Users: {}, Texts: {}, Files: {}", user_count, text_count, file_count))
+}
+
+fn staging_email(user_id: &str) -> String {
+    format!("{}@{}", pseudonym(user_id), STAGING_DOMAIN)
+}
+
+fn scramble_users(connection: &MysqlConnection, hashed_password: &str) -> QueryResult<usize> {
+    let people: Vec<(String, String)> = users::table.select((users::id, users::user_type)).load(connection)?;
+
+    for (user_id, kind) in &people {
+        let name = format!("{} {}", kind, pseudonym(user_id));
+        let email = staging_email(user_id);
+
+        diesel::update(users::table.filter(users::id.eq(user_id)))
+            .set((users::full_name.eq(&name), users::email.eq(&email), users::password.eq(hashed_password), users::email_invalid.eq(false)))
+            .execute(connection)?;
+
+        diesel::update(coaches::table.filter(coaches::user_id.eq(user_id)))
+            .set((coaches::full_name.eq(&name), coaches::email.eq(&email), coaches::token.eq(None::<i32>)))
+            .execute(connection)?;
+
+        diesel::update(mail_recipients::table.filter(mail_recipients::to_user_id.eq(user_id)))
+            .set(mail_recipients::to_email.eq(&email))
+            .execute(connection)?;
+    }
+
+    // Recipients without a user are outsiders we mailed once; their addresses go too.
+    let outsiders: Vec<(String, String)> = mail_recipients::table
+        .filter(mail_recipients::to_user_id.is_null())
+        .select((mail_recipients::id, mail_recipients::to_email))
+        .load(connection)?;
+
+    for (recipient_id, address) in &outsiders {
+        diesel::update(mail_recipients::table.filter(mail_recipients::id.eq(recipient_id)))
+            .set(mail_recipients::to_email.eq(staging_email(address)))
+            .execute(connection)?;
+    }
+
+    diesel::delete(mail_bounces::table).execute(connection)?;
+
+    Ok(people.len())
+}
+
+fn replace_texts(connection: &MysqlConnection) -> QueryResult<usize> {
+    let mut count: usize = 0;
+
+    count += diesel::update(session_notes::table).set(session_notes::description.eq(PLACEHOLDER)).execute(connection)?;
+    count += diesel::update(discussions::table).set(discussions::description.eq(PLACEHOLDER)).execute(connection)?;
+    count += diesel::update(session_scratchpads::table).set(session_scratchpads::content.eq(PLACEHOLDER)).execute(connection)?;
+    count += diesel::update(session_summaries::table)
+        .set((
+            session_summaries::went_well.eq(PLACEHOLDER),
+            session_summaries::action_items.eq(PLACEHOLDER),
+            session_summaries::next_steps.eq(PLACEHOLDER),
+        ))
+        .execute(connection)?;
+    count += diesel::update(audit_events::table.filter(audit_events::after_state.is_not_null().or(audit_events::before_state.is_not_null())))
+        .set((audit_events::before_state.eq(None::<String>), audit_events::after_state.eq(None::<String>)))
+        .execute(connection)?;
+
+    count += diesel::update(correspondences::table.filter(correspondences::content.is_not_null()))
+        .set((correspondences::content.eq(PLACEHOLDER), correspondences::content_text.eq(PLACEHOLDER)))
+        .execute(connection)?;
+
+    count += diesel::update(objectives::table.filter(objectives::description.is_not_null()))
+        .set(objectives::description.eq(PLACEHOLDER))
+        .execute(connection)?;
+    count += diesel::update(observations::table.filter(observations::description.is_not_null()))
+        .set(observations::description.eq(PLACEHOLDER))
+        .execute(connection)?;
+    count += diesel::update(options::table.filter(options::description.is_not_null()))
+        .set(options::description.eq(PLACEHOLDER))
+        .execute(connection)?;
+
+    count += diesel::update(tasks::table.filter(tasks::response.is_not_null()))
+        .set(tasks::response.eq(PLACEHOLDER))
+        .execute(connection)?;
+    count += diesel::update(tasks::table.filter(tasks::closing_notes.is_not_null()))
+        .set(tasks::closing_notes.eq(PLACEHOLDER))
+        .execute(connection)?;
+
+    // The people column caches the names of the participants.
+    count += diesel::update(sessions::table.filter(sessions::people.is_not_null()))
+        .set((sessions::people.eq(PLACEHOLDER), sessions::closing_notes.eq(None::<String>)))
+        .execute(connection)?;
+    count += diesel::update(conferences::table.filter(conferences::people.is_not_null()))
+        .set(conferences::people.eq(PLACEHOLDER))
+        .execute(connection)?;
+
+    Ok(count)
+}
+
+// The blobs hold the contents too; they go before the files are replaced.
+fn forget_and_replace_files(connection: &MysqlConnection) -> usize {
+    if let Err(e) = file_registry::forget_all(connection) {
+        eprintln!("Unable to forget the uploaded files: {}", e);
+    }
+
+    replace_files()
+}
+
+/**
+ * The files keep their names and places, so the links from the notes and
+ * boards still resolve, but not their contents.
+ */
+fn replace_files() -> usize {
+    [Area::Sessions, Area::Users, Area::Programs].iter().map(|area| replace_files_in(&storage().dir(*area))).sum()
+}
+
+fn replace_files_in(dir: &Path) -> usize {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    let mut count: usize = 0;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            count += replace_files_in(&path);
+        } else if fs::write(&path, FILE_PLACEHOLDER).is_ok() {
+            if let Err(e) = storage().keep(&path) {
+                eprintln!("Unable to keep the replaced file {:?}: {}", path, e);
+            }
+            count += 1;
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_give_the_same_pseudonym_for_the_same_id() {
+        let first = pseudonym("4f6c7c1e-5a6f-4b7e-9c7d-2d8e1f3a9b10");
+        let second = pseudonym("4f6c7c1e-5a6f-4b7e-9c7d-2d8e1f3a9b10");
+
+        assert_eq!(first, second);
+        assert_eq!(10, first.len());
+        assert_ne!(first, pseudonym("another-id"));
+    }
+}
@@ -0,0 +1,83 @@
+use diesel::prelude::*;
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::ids::UserId;
+use crate::models::audit_events::{AuditEntry, AuditEvent, AuditTrailCriteria, NewAuditEvent};
+use crate::services::users;
+
+use crate::schema::audit_events;
+use crate::schema::users as users_table;
+
+const TRAIL_SIZE: i64 = 200;
+
+const AUDIT_TRAIL_ERROR: &str = "Unable to read the audit trail.";
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+
+/**
+ * The audit entry is written on the same connection as the change it describes.
+ * When the caller is inside a transaction, a failure here rolls back the change too.
+ */
+pub fn record(connection: &MysqlConnection, event: &NewAuditEvent) -> QueryResult<usize> {
+    diesel::insert_into(audit_events::table).values(event).execute(connection)
+}
+
+/**
+ * Records a change whose before state is the after state the last change of the
+ * same entity left; the first change we know of has none.
+ */
+pub fn record_change(connection: &MysqlConnection, mut event: NewAuditEvent) -> QueryResult<usize> {
+    let last_state: Option<Option<String>> = audit_events::table
+        .filter(audit_events::entity_type.eq(event.entity_type.as_str()))
+        .filter(audit_events::entity_id.eq(event.entity_id.as_str()))
+        .filter(audit_events::after_state.is_not_null())
+        .order_by(audit_events::created_at.desc())
+        .select(audit_events::after_state)
+        .first(connection)
+        .optional()?;
+
+    event.before_state = last_state.flatten();
+
+    record(connection, &event)
+}
+
+/**
+ * The changes of an entity, the latest first. The coach of a program, a master
+ * plan, an enrollment or a task may follow its changes; the changes of anything
+ * else are for the admins.
+ */
+pub fn get_audit_trail(connection: &MysqlConnection, caller: Option<&UserId>, criteria: &AuditTrailCriteria) -> Result<Vec<AuditEntry>, &'static str> {
+    let rows: Vec<(AuditEvent, String)> = audit_events::table
+        .inner_join(users_table::table)
+        .filter(audit_events::entity_id.eq(criteria.entity_id.as_str()))
+        .order_by(audit_events::created_at.desc())
+        .limit(TRAIL_SIZE)
+        .select((audit_events::all_columns, users_table::full_name))
+        .load(connection)
+        .map_err(|_| AUDIT_TRAIL_ERROR)?;
+
+    let entity_type = match rows.first() {
+        Some((event, _)) => event.entity_type.to_owned(),
+        None => return Ok(Vec::new()),
+    };
+
+    match coached_target(entity_type.as_str(), criteria.entity_id.as_str()) {
+        Some(target) => authorize(connection, caller, target, &[Role::Coach])?,
+        None => {
+            users::find_admin(connection, caller.ok_or(NOT_SIGNED_IN)?)?;
+        }
+    }
+
+    let entries = rows.into_iter().map(|(event, actor_name)| AuditEntry { event, actor_name }).collect();
+
+    Ok(entries)
+}
+
+fn coached_target<'a>(entity_type: &str, the_id: &'a str) -> Option<Target<'a>> {
+    match entity_type {
+        "program" => Some(Target::Program(the_id)),
+        "master_plan" => Some(Target::MasterPlan(the_id)),
+        "enrollment" => Some(Target::Enrollment(the_id)),
+        "task" => Some(Target::Task(the_id)),
+        _ => None,
+    }
+}
@@ -0,0 +1,155 @@
+use diesel::prelude::*;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::models::board_annotations::{parse_overlay, AnnotationCriteria, BoardAnnotation, NewBoardAnnotation, SaveAnnotationRequest};
+use crate::models::sessions::Session;
+use crate::models::user_artifacts::{artifact_id, board_dir};
+
+use crate::services::sessions::find;
+use crate::storage::storage;
+
+use crate::schema::board_annotations;
+use crate::schema::board_annotations::dsl::*;
+use crate::schema::session_users;
+
+const NOT_SESSION_USER: &str = "Only the people of the session may annotate its boards.";
+const BOARD_NOT_FOUND: &str = "Unable to find the board.";
+const ANNOTATION_SAVE_ERROR: &str = "Unable to save the annotations.";
+const ANNOTATION_DELETE_ERROR: &str = "Unable to delete the annotations.";
+const ANNOTATIONS_NOT_FOUND: &str = "Unable to find the annotations of the board.";
+const EXPORT_UNAVAILABLE: &str = "Flattening the boards is not enabled on this server.";
+const EXPORT_ERROR: &str = "Unable to flatten the annotations over the board.";
+
+/**
+ * An author keeps one overlay per board; saving again replaces it.
+ */
+pub fn save_annotation(connection: &MysqlConnection, request: &SaveAnnotationRequest) -> Result<BoardAnnotation, &'static str> {
+    let criteria = AnnotationCriteria {
+        user_id: request.user_id.to_owned(),
+        session_id: request.session_id.to_owned(),
+        board_name: request.board_name.to_owned(),
+    };
+
+    let (session, _) = find_board(connection, &criteria)?;
+    let the_board_id = artifact_id(&session);
+
+    let existing: QueryResult<BoardAnnotation> = board_annotations
+        .filter(board_id.eq(the_board_id.as_str()))
+        .filter(board_name.eq(request.board_name.as_str()))
+        .filter(created_by_id.eq(request.user_id.as_str()))
+        .first(connection);
+
+    let result = match &existing {
+        Ok(annotation) => diesel::update(board_annotations.filter(board_annotations::id.eq(annotation.id.as_str())))
+            .set(overlay.eq(request.overlay.as_str()))
+            .execute(connection),
+        Err(_) => diesel::insert_into(board_annotations)
+            .values(&NewBoardAnnotation::from(request, the_board_id.as_str()))
+            .execute(connection),
+    };
+
+    if result.is_err() {
+        return Err(ANNOTATION_SAVE_ERROR);
+    }
+
+    board_annotations
+        .filter(board_id.eq(the_board_id.as_str()))
+        .filter(board_name.eq(request.board_name.as_str()))
+        .filter(created_by_id.eq(request.user_id.as_str()))
+        .first(connection)
+        .map_err(|_| ANNOTATION_SAVE_ERROR)
+}
+
+pub fn delete_annotation(connection: &MysqlConnection, criteria: &AnnotationCriteria) -> Result<usize, &'static str> {
+    let (session, _) = find_board(connection, criteria)?;
+
+    let target = board_annotations
+        .filter(board_id.eq(artifact_id(&session)))
+        .filter(board_name.eq(criteria.board_name.as_str()))
+        .filter(created_by_id.eq(criteria.user_id.as_str()));
+
+    diesel::delete(target).execute(connection).map_err(|_| ANNOTATION_DELETE_ERROR)
+}
+
+/**
+ * The overlays of all the boards stored against the given board ids.
+ */
+pub fn get_annotations(connection: &MysqlConnection, board_ids: &[String]) -> QueryResult<Vec<BoardAnnotation>> {
+    board_annotations
+        .filter(board_id.eq_any(board_ids))
+        .order_by((board_name.asc(), created_at.asc()))
+        .load(connection)
+}
+
+/**
+ * Draws the overlays of every author over a copy of the board and returns the path
+ * of the PNG. The drawing needs ImageMagick on the host, hence it runs only with
+ * the board-export feature.
+ */
+pub fn export_board(connection: &MysqlConnection, criteria: &AnnotationCriteria) -> Result<PathBuf, &'static str> {
+    if !cfg!(feature = "board-export") {
+        return Err(EXPORT_UNAVAILABLE);
+    }
+
+    let (session, source) = find_board(connection, criteria)?;
+
+    let annotations: Vec<BoardAnnotation> = board_annotations
+        .filter(board_id.eq(artifact_id(&session)))
+        .filter(board_name.eq(criteria.board_name.as_str()))
+        .order_by(created_at.asc())
+        .load(connection)
+        .map_err(|_| ANNOTATIONS_NOT_FOUND)?;
+
+    let mut args: Vec<String> = vec![source.to_string_lossy().into_owned()];
+    for annotation in &annotations {
+        for shape in parse_overlay(annotation.overlay.as_str())? {
+            args.extend(shape.draw_args());
+        }
+    }
+
+    // The exports live beside the boards, not among them, so the listing stays as is.
+    let mut target = board_dir(&session);
+    target.set_file_name("board_exports");
+    std::fs::create_dir_all(&target).map_err(|_| EXPORT_ERROR)?;
+    target.push(format!("{}.png", criteria.board_name));
+
+    args.push(target.to_string_lossy().into_owned());
+
+    match Command::new("convert").args(&args).status() {
+        Ok(status) if status.success() => Ok(target),
+        _ => Err(EXPORT_ERROR),
+    }
+}
+
+/**
+ * The board should be a file of the session, and the user one of its people.
+ */
+pub fn find_board(connection: &MysqlConnection, criteria: &AnnotationCriteria) -> Result<(Session, PathBuf), &'static str> {
+    let count: i64 = session_users::table
+        .filter(session_users::session_id.eq(criteria.session_id.as_str()))
+        .filter(session_users::user_id.eq(criteria.user_id.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| NOT_SESSION_USER)?;
+
+    if count == 0 {
+        return Err(NOT_SESSION_USER);
+    }
+
+    let session = find(connection, &criteria.session_id)?;
+
+    let name = criteria.board_name.as_str();
+    if name.is_empty() || sanitize_filename::sanitize(name) != name {
+        return Err(BOARD_NOT_FOUND);
+    }
+
+    let mut path = board_dir(&session);
+    path.push(name);
+
+    if storage().open(&path).is_err() {
+        return Err(BOARD_NOT_FOUND);
+    }
+
+    Ok((session, path))
+}
@@ -0,0 +1,83 @@
+use diesel::prelude::*;
+use std::path::Path;
+
+use crate::content_store::ContentStore;
+use crate::models::board_annotations::AnnotationCriteria;
+use crate::models::board_versions::{version_file_name, versions_dir, BoardVersion, NewBoardVersion};
+use crate::models::user_artifacts::artifact_id;
+
+use crate::services::board_annotations::find_board;
+use crate::services::file_registry;
+use crate::storage::storage;
+
+use crate::schema::board_versions::dsl::*;
+
+const VERSION_SAVE_ERROR: &str = "Unable to keep the version of the board.";
+const VERSIONS_NOT_FOUND: &str = "Unable to find the versions of the board.";
+const VERSION_NOT_FOUND: &str = "Unable to find the version of the board.";
+
+/**
+ * The board just uploaded to the path becomes its next version. An upload of
+ * the same content as the latest version is no new version.
+ */
+pub fn keep_version(connection: &MysqlConnection, the_board_id: &str, the_board_name: &str, board_path: &Path, the_checksum: &str, the_size: usize) -> Result<BoardVersion, &'static str> {
+    let versions = list_versions(connection, the_board_id, the_board_name)?;
+
+    if let Some(latest) = versions.last() {
+        if latest.checksum == the_checksum {
+            return Ok(latest.clone());
+        }
+    }
+
+    let next = versions.last().map(|latest| latest.version + 1).unwrap_or(1);
+
+    let board_dir = board_path.parent().ok_or(VERSION_SAVE_ERROR)?;
+    let target = versions_dir(board_dir, the_board_name).join(version_file_name(the_board_name, next));
+
+    if let Err(e) = ContentStore::local().link(the_checksum, &target).and_then(|_| storage().keep(&target)) {
+        eprintln!("Unable to keep {:?}: {}", target, e);
+        return Err(VERSION_SAVE_ERROR);
+    }
+
+    let target_path = target.to_string_lossy().into_owned();
+
+    // Without its registration the version would lose its blob to the sweeper.
+    file_registry::register(connection, target_path.as_str(), the_checksum, the_size)?;
+
+    diesel::insert_into(board_versions)
+        .values(&NewBoardVersion::of(the_board_id, the_board_name, next, target_path.as_str(), the_checksum, the_size))
+        .execute(connection)
+        .map_err(|_| VERSION_SAVE_ERROR)?;
+
+    find_version(connection, the_board_id, the_board_name, next)
+}
+
+/**
+ * The versions of a board, the earliest first.
+ */
+pub fn list_versions(connection: &MysqlConnection, the_board_id: &str, the_board_name: &str) -> Result<Vec<BoardVersion>, &'static str> {
+    board_versions
+        .filter(board_id.eq(the_board_id))
+        .filter(board_name.eq(the_board_name))
+        .order_by(version.asc())
+        .load(connection)
+        .map_err(|_| VERSIONS_NOT_FOUND)
+}
+
+pub fn find_version(connection: &MysqlConnection, the_board_id: &str, the_board_name: &str, the_version: i32) -> Result<BoardVersion, &'static str> {
+    board_versions
+        .filter(board_id.eq(the_board_id))
+        .filter(board_name.eq(the_board_name))
+        .filter(version.eq(the_version))
+        .first(connection)
+        .map_err(|_| VERSION_NOT_FOUND)
+}
+
+/**
+ * The history of a board, for the people of its session to replay.
+ */
+pub fn get_board_versions(connection: &MysqlConnection, criteria: &AnnotationCriteria) -> Result<Vec<BoardVersion>, &'static str> {
+    let (session, _) = find_board(connection, criteria)?;
+
+    list_versions(connection, artifact_id(&session).as_str(), criteria.board_name.as_str())
+}
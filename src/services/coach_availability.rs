@@ -0,0 +1,210 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use crate::commons::ids::UserId;
+use crate::loaders::grouped;
+use crate::models::coach_availability::{slot_range, Availability, AvailabilityException, AvailabilityRequest, AvailabilityWindow, Slot, OUTSIDE_AVAILABILITY};
+use crate::models::sessions::Session;
+use crate::models::users::User;
+
+use crate::services::users;
+
+use crate::schema::coach_availability;
+use crate::schema::coach_availability_exceptions;
+use crate::schema::programs;
+use crate::schema::sessions;
+use crate::schema::users as users_table;
+
+const AVAILABILITY_SAVE_ERROR: &str = "Unable to save the availability.";
+const AVAILABILITY_FETCH_ERROR: &str = "Unable to fetch the availability.";
+const SLOTS_ERROR: &str = "Unable to gather the free slots of the coach.";
+
+/**
+ * The windows and the exceptions of the coach are replaced by those of the request.
+ */
+pub fn set_availability(connection: &MysqlConnection, request: &AvailabilityRequest) -> Result<Availability, &'static str> {
+    let coach = users::find_coach_by_id(connection, request.coach_id.as_str())?;
+
+    let (windows, exceptions) = request.to_rows();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::delete(coach_availability::table.filter(coach_availability::coach_id.eq(request.coach_id.as_str()))).execute(connection)?;
+        diesel::delete(coach_availability_exceptions::table.filter(coach_availability_exceptions::coach_id.eq(request.coach_id.as_str()))).execute(connection)?;
+
+        diesel::insert_into(coach_availability::table).values(&windows).execute(connection)?;
+        diesel::insert_into(coach_availability_exceptions::table).values(&exceptions).execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(AVAILABILITY_SAVE_ERROR);
+    }
+
+    get_availability(connection, coach.id.as_str())
+}
+
+pub fn get_availability(connection: &MysqlConnection, the_coach_id: &str) -> Result<Availability, &'static str> {
+    let windows: Vec<AvailabilityWindow> = coach_availability::table
+        .filter(coach_availability::coach_id.eq(the_coach_id))
+        .select((coach_availability::id, coach_availability::weekday, coach_availability::from_minute, coach_availability::to_minute))
+        .order_by((coach_availability::weekday.asc(), coach_availability::from_minute.asc()))
+        .load(connection)
+        .map_err(|_| AVAILABILITY_FETCH_ERROR)?;
+
+    let exceptions: Vec<AvailabilityException> = coach_availability_exceptions::table
+        .filter(coach_availability_exceptions::coach_id.eq(the_coach_id))
+        .select((
+            coach_availability_exceptions::id,
+            coach_availability_exceptions::from_date,
+            coach_availability_exceptions::to_date,
+            coach_availability_exceptions::is_available,
+            coach_availability_exceptions::reason,
+        ))
+        .order_by(coach_availability_exceptions::from_date.asc())
+        .load(connection)
+        .map_err(|_| AVAILABILITY_FETCH_ERROR)?;
+
+    Ok(Availability {
+        coach_id: the_coach_id.to_owned(),
+        windows,
+        exceptions,
+    })
+}
+
+/**
+ * The free slots of the coach between the two times, in UTC. A coach who set no
+ * availability has none to offer.
+ */
+pub fn get_available_slots(connection: &MysqlConnection, the_coach_id: &str, from: &str, to: &str) -> Result<Vec<Slot>, &'static str> {
+    let (from, to) = slot_range(from, to)?;
+
+    let coach = users::find(connection, &UserId::from(the_coach_id))?;
+    let availability = get_availability(connection, coach.id.as_str())?;
+
+    let busy = busy_spans(connection, coach.id.as_str(), from, to).map_err(|_| SLOTS_ERROR)?;
+
+    Ok(availability.free_slots(&busy, coach.utc_offset, from, to))
+}
+
+/**
+ * Why a session of the coach may not take place from the start to the end, if
+ * the coach set an availability: it falls outside, or over another session.
+ */
+pub fn violation(connection: &MysqlConnection, coach: &User, starts_at: NaiveDateTime, ends_at: NaiveDateTime) -> Result<Option<&'static str>, &'static str> {
+    let availability = get_availability(connection, coach.id.as_str())?;
+
+    if !availability.is_set() {
+        return Ok(None);
+    }
+
+    let busy = busy_spans(connection, coach.id.as_str(), starts_at, ends_at).map_err(|_| SLOTS_ERROR)?;
+
+    if availability.is_free(&busy, coach.utc_offset, starts_at, ends_at) {
+        return Ok(None);
+    }
+
+    Ok(Some(OUTSIDE_AVAILABILITY))
+}
+
+/**
+ * The start of the first free slot of each of the coaches between the two
+ * times, in a few round trips for all of them. A coach who set no availability,
+ * or is not free then, is left out.
+ */
+pub fn first_free_times(connection: &MysqlConnection, coach_ids: &[String], from: NaiveDateTime, to: NaiveDateTime) -> QueryResult<HashMap<String, NaiveDateTime>> {
+    let offsets: Vec<(String, i32)> = users_table::table
+        .filter(users_table::id.eq_any(coach_ids))
+        .select((users_table::id, users_table::utc_offset))
+        .load(connection)?;
+
+    let windows: Vec<(String, AvailabilityWindow)> = coach_availability::table
+        .filter(coach_availability::coach_id.eq_any(coach_ids))
+        .select((
+            coach_availability::coach_id,
+            (coach_availability::id, coach_availability::weekday, coach_availability::from_minute, coach_availability::to_minute),
+        ))
+        .load(connection)?;
+
+    let exceptions: Vec<(String, AvailabilityException)> = coach_availability_exceptions::table
+        .filter(coach_availability_exceptions::coach_id.eq_any(coach_ids))
+        .filter(coach_availability_exceptions::from_date.lt(to))
+        .filter(coach_availability_exceptions::to_date.gt(from))
+        .select((
+            coach_availability_exceptions::coach_id,
+            (
+                coach_availability_exceptions::id,
+                coach_availability_exceptions::from_date,
+                coach_availability_exceptions::to_date,
+                coach_availability_exceptions::is_available,
+                coach_availability_exceptions::reason,
+            ),
+        ))
+        .load(connection)?;
+
+    let mut windows = grouped(windows);
+    let mut exceptions = grouped(exceptions);
+    let mut busy = busy_spans_of(connection, coach_ids, from, to)?;
+
+    let mut free_times: HashMap<String, NaiveDateTime> = HashMap::new();
+
+    for (the_coach_id, utc_offset) in offsets {
+        let availability = Availability {
+            windows: windows.remove(&the_coach_id).unwrap_or_default(),
+            exceptions: exceptions.remove(&the_coach_id).unwrap_or_default(),
+            coach_id: the_coach_id,
+        };
+
+        if !availability.is_set() {
+            continue;
+        }
+
+        let spans = busy.remove(&availability.coach_id).unwrap_or_default();
+
+        if let Some(slot) = availability.free_slots(&spans, utc_offset, from, to).first() {
+            free_times.insert(availability.coach_id.to_owned(), slot.starts_at);
+        }
+    }
+
+    Ok(free_times)
+}
+
+/**
+ * The spans of the sessions of the coach meeting the two times, on their
+ * schedule; the cancelled, the expired and the requested ones hold no time.
+ */
+fn busy_spans(connection: &MysqlConnection, the_coach_id: &str, from: NaiveDateTime, to: NaiveDateTime) -> QueryResult<Vec<(NaiveDateTime, NaiveDateTime)>> {
+    let mut spans = busy_spans_of(connection, &[the_coach_id.to_owned()], from, to)?;
+
+    Ok(spans.remove(the_coach_id).unwrap_or_default())
+}
+
+/**
+ * The busy spans of each of the coaches, by the id of the coach.
+ */
+fn busy_spans_of(connection: &MysqlConnection, coach_ids: &[String], from: NaiveDateTime, to: NaiveDateTime) -> QueryResult<HashMap<String, Vec<(NaiveDateTime, NaiveDateTime)>>> {
+    let coach_sessions: Vec<(String, Session)> = sessions::table
+        .inner_join(programs::table)
+        .filter(programs::coach_id.eq_any(coach_ids))
+        .filter(sessions::is_request.eq(false))
+        .filter(sessions::cancelled_at.is_null())
+        .filter(sessions::expired_at.is_null())
+        .filter(
+            sessions::original_start_date
+                .lt(to)
+                .and(sessions::original_end_date.gt(from))
+                .or(sessions::revised_start_date.lt(to).and(sessions::revised_end_date.gt(from))),
+        )
+        .select((programs::coach_id, sessions::all_columns))
+        .load(connection)?;
+
+    let spans = coach_sessions
+        .iter()
+        .map(|(the_coach_id, session)| {
+            let span = (session.revised_start_date.unwrap_or(session.original_start_date), session.revised_end_date.unwrap_or(session.original_end_date));
+            (the_coach_id.to_owned(), span)
+        })
+        .filter(|(_, (starts_at, ends_at))| *starts_at < to && *ends_at > from)
+        .collect();
+
+    Ok(grouped(spans))
+}
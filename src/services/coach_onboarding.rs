@@ -0,0 +1,94 @@
+use diesel::prelude::*;
+
+use crate::commons::util;
+
+use crate::models::coach_onboarding::{CoachOnboarding, NewCoachOnboarding, OnboardingChecklist, PaymentDetailsRequest, Progress};
+use crate::models::coach_profiles::CoachProfile;
+
+use crate::services::users::find_coach_by_id;
+
+use crate::schema::coach_onboarding;
+use crate::schema::coach_profiles;
+use crate::schema::programs;
+
+const ONBOARDING_ERROR: &str = "Unable to verify the onboarding of the coach.";
+const PAYMENT_SAVE_ERROR: &str = "Unable to save the payment details.";
+const ONBOARDING_PENDING: &str = "Please complete the onboarding before publishing a program.";
+
+pub fn get_onboarding(connection: &MysqlConnection, the_coach_id: &str) -> Result<OnboardingChecklist, &'static str> {
+    let coach = find_coach_by_id(connection, the_coach_id)?;
+
+    verify(connection, coach.id.as_str()).map_err(|_| ONBOARDING_ERROR)
+}
+
+pub fn save_payment_details(connection: &MysqlConnection, request: &PaymentDetailsRequest) -> Result<OnboardingChecklist, &'static str> {
+    let coach = find_coach_by_id(connection, request.coach_id.as_str())?;
+
+    let fresh = NewCoachOnboarding {
+        coach_id: coach.id.to_owned(),
+        payment_account: None,
+        completed_at: None,
+    };
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_or_ignore_into(coach_onboarding::table).values(&fresh).execute(connection)?;
+
+        diesel::update(coach_onboarding::table.filter(coach_onboarding::coach_id.eq(coach.id.as_str())))
+            .set(coach_onboarding::payment_account.eq(request.payment_account.trim()))
+            .execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(PAYMENT_SAVE_ERROR);
+    }
+
+    verify(connection, coach.id.as_str()).map_err(|_| ONBOARDING_ERROR)
+}
+
+/**
+ * A program is published only by a coach who completed the onboarding.
+ */
+pub fn gate_publication(connection: &MysqlConnection, the_coach_id: &str) -> Result<(), &'static str> {
+    let checklist = verify(connection, the_coach_id).map_err(|_| ONBOARDING_ERROR)?;
+
+    if checklist.completed_at.is_none() {
+        return Err(ONBOARDING_PENDING);
+    }
+
+    Ok(())
+}
+
+/**
+ * The steps are checked against the records of the coach; the onboarding is
+ * marked complete the first time none is pending.
+ */
+fn verify(connection: &MysqlConnection, the_coach_id: &str) -> QueryResult<OnboardingChecklist> {
+    let profile: Option<CoachProfile> = coach_profiles::table.filter(coach_profiles::coach_id.eq(the_coach_id)).first(connection).optional()?;
+
+    let programs_created: i64 = programs::table.filter(programs::coach_id.eq(the_coach_id)).count().get_result(connection)?;
+
+    let onboarding: Option<CoachOnboarding> = coach_onboarding::table
+        .filter(coach_onboarding::coach_id.eq(the_coach_id))
+        .select((coach_onboarding::payment_account, coach_onboarding::completed_at))
+        .first(connection)
+        .optional()?;
+
+    let pending = Progress::of(profile.as_ref(), programs_created > 0, onboarding.as_ref()).pending();
+    let mut completed_at = onboarding.and_then(|value| value.completed_at);
+
+    if completed_at.is_none() && pending.is_empty() {
+        let now = util::now();
+
+        diesel::update(coach_onboarding::table.filter(coach_onboarding::coach_id.eq(the_coach_id)))
+            .set(coach_onboarding::completed_at.eq(now))
+            .execute(connection)?;
+
+        completed_at = Some(now);
+    }
+
+    Ok(OnboardingChecklist {
+        coach_id: the_coach_id.to_owned(),
+        pending,
+        completed_at,
+    })
+}
@@ -0,0 +1,163 @@
+use chrono::Duration;
+use diesel::prelude::*;
+
+use crate::commons::util;
+use crate::models::coach_profiles::{tag_pattern, to_tags, CoachProfile, CoachProfileRequest, DirectoryCriteria, DirectoryEntry, DirectoryPage, NewCoachProfile, FREE_HINT_DAYS};
+use crate::models::coaches::Coach;
+
+use crate::services::coach_availability::first_free_times;
+use crate::services::program_reviews::coach_ratings;
+use crate::services::users::find_coach_by_id;
+
+use crate::schema::coach_profiles;
+use crate::schema::coach_profiles::dsl::*;
+use crate::schema::coaches;
+use crate::schema::programs;
+use crate::schema::users;
+
+const PROFILE_SAVE_ERROR: &str = "Unable to save the profile of the coach.";
+const PROFILE_NOT_FOUND: &str = "Unable to find the profile of the coach.";
+const DIRECTORY_ERROR: &str = "Unable to list the coaches of the directory.";
+
+pub fn save_coach_profile(connection: &MysqlConnection, request: &CoachProfileRequest) -> Result<CoachProfile, &'static str> {
+    let coach = find_coach_by_id(connection, request.coach_id.as_str())?;
+
+    let profile = NewCoachProfile::from(request);
+
+    let existing: QueryResult<i64> = coach_profiles.filter(coach_id.eq(coach.id.as_str())).count().get_result(connection);
+
+    let result = match existing {
+        Ok(count) if count > 0 => diesel::update(coach_profiles.filter(coach_id.eq(coach.id.as_str()))).set(&profile).execute(connection),
+        _ => diesel::insert_into(coach_profiles).values(&profile).execute(connection),
+    };
+
+    if result.is_err() {
+        return Err(PROFILE_SAVE_ERROR);
+    }
+
+    refresh_completeness(connection, coach.id.as_str()).map_err(|_| PROFILE_SAVE_ERROR)?;
+
+    get_coach_profile(connection, coach.id.as_str())
+}
+
+/**
+ * Works out the completeness of the profile again; a coach without a profile
+ * has nothing to refresh. An active program of the coach counts as a program.
+ */
+pub fn refresh_completeness(connection: &MysqlConnection, the_coach_id: &str) -> QueryResult<usize> {
+    let profile: CoachProfile = match coach_profiles.filter(coach_id.eq(the_coach_id)).first(connection).optional()? {
+        Some(value) => value,
+        None => return Ok(0),
+    };
+
+    let active_programs: i64 = programs::table
+        .filter(programs::coach_id.eq(the_coach_id))
+        .filter(programs::active.eq(true))
+        .count()
+        .get_result(connection)?;
+
+    let (score, items) = profile.assess(active_programs > 0);
+    let item_tags: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+
+    diesel::update(coach_profiles.filter(coach_id.eq(the_coach_id)))
+        .set((completeness.eq(score), missing.eq(to_tags(&item_tags))))
+        .execute(connection)
+}
+
+pub fn get_coach_profile(connection: &MysqlConnection, the_coach_id: &str) -> Result<CoachProfile, &'static str> {
+    coach_profiles.filter(coach_id.eq(the_coach_id)).first(connection).map_err(|_| PROFILE_NOT_FOUND)
+}
+
+/**
+ * Only the coaches who opted in, and are not deactivated, are listed, the more
+ * complete profiles first and then by name. The rating and the free time are
+ * worked out before the page is queried, so that the page and the total count
+ * only the coaches meeting them.
+ */
+pub fn get_coach_directory(connection: &MysqlConnection, criteria: &DirectoryCriteria) -> Result<DirectoryPage, &'static str> {
+    criteria.validate()?;
+
+    let now = util::now();
+    let free_until = criteria.free_until(now)?;
+
+    let rated: Option<Vec<String>> = match criteria.min_rating {
+        Some(min_rating) => {
+            let ratings = coach_ratings(connection, None).map_err(|_| DIRECTORY_ERROR)?;
+            Some(ratings.into_iter().filter(|(_, (average, _))| average.is_some_and(|value| value >= min_rating)).map(|(the_coach_id, _)| the_coach_id).collect())
+        }
+        None => None,
+    };
+
+    let free: Option<Vec<String>> = match free_until {
+        Some(until) => {
+            let listed_ids: Vec<String> = coach_profiles.filter(is_listed.eq(true)).select(coach_id).load(connection).map_err(|_| DIRECTORY_ERROR)?;
+            let free_times = first_free_times(connection, &listed_ids, now, until).map_err(|_| DIRECTORY_ERROR)?;
+            Some(free_times.into_keys().collect())
+        }
+        None => None,
+    };
+
+    let listed = || {
+        let mut query = coach_profiles
+            .inner_join(coaches::table)
+            .filter(is_listed.eq(true))
+            .filter(coaches::user_id.eq_any(users::table.filter(users::blocked.eq(false)).select(users::id)))
+            .into_boxed();
+
+        if let Some(value) = &criteria.specialty {
+            query = query.filter(specialties.like(tag_pattern(value)));
+        }
+
+        if let Some(value) = &criteria.language {
+            query = query.filter(languages.like(tag_pattern(value)));
+        }
+
+        if let Some(ids) = &rated {
+            query = query.filter(coach_profiles::coach_id.eq_any(ids));
+        }
+
+        if let Some(ids) = &free {
+            query = query.filter(coach_profiles::coach_id.eq_any(ids));
+        }
+
+        query
+    };
+
+    let total: i64 = listed().count().get_result(connection).map_err(|_| DIRECTORY_ERROR)?;
+
+    let rows: Vec<(CoachProfile, Coach)> = listed()
+        .order_by((completeness.desc(), coaches::full_name.asc(), coach_profiles::coach_id.asc()))
+        .offset(criteria.offset())
+        .limit(criteria.page_size as i64)
+        .load(connection)
+        .map_err(|_| DIRECTORY_ERROR)?;
+
+    let page_ids: Vec<String> = rows.iter().map(|(profile, _)| profile.coach_id.to_owned()).collect();
+
+    let mut ratings = coach_ratings(connection, Some(&page_ids)).map_err(|_| DIRECTORY_ERROR)?;
+    let hint_until = free_until.unwrap_or_else(|| now + Duration::days(FREE_HINT_DAYS));
+    let mut free_times = first_free_times(connection, &page_ids, now, hint_until).map_err(|_| DIRECTORY_ERROR)?;
+
+    let entries = rows
+        .into_iter()
+        .map(|(profile, coach)| {
+            let (rating, reviews) = ratings.remove(&profile.coach_id).unwrap_or((None, 0));
+            let free_at = free_times.remove(&profile.coach_id);
+
+            DirectoryEntry {
+                name: coach.full_name,
+                profile,
+                rating,
+                reviews,
+                free_at,
+            }
+        })
+        .collect();
+
+    Ok(DirectoryPage {
+        entries,
+        total: total as i32,
+        page: criteria.page,
+        page_size: criteria.page_size,
+    })
+}
@@ -0,0 +1,185 @@
+use chrono::{Duration, NaiveDate};
+use diesel::prelude::*;
+use std::env;
+
+use crate::commons::util;
+use crate::models::coach_stats::{day_bounds, CoachDailyStat, CoachStats, CoachStatsCriteria, NewCoachDailyStat, NewStatRefresh};
+use crate::models::programs::Program;
+
+use crate::services::users::find_coach_by_id;
+
+use crate::schema::coach_daily_stats;
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::sessions;
+use crate::schema::stat_refresh_queue;
+use crate::schema::tasks;
+
+const BATCH_SIZE: i64 = 100;
+
+// How far back the nightly rebuild recounts, unless STATS_REBUILD_DAYS says otherwise.
+const REBUILD_DAYS: i64 = 30;
+
+const STATS_ERROR: &str = "Unable to fetch the activity of the coach.";
+const QUEUE_ERROR: &str = "Unable to read the queue of the summaries.";
+const REFRESH_ERROR: &str = "Unable to refresh the summary of the coach.";
+const REBUILD_ERROR: &str = "Unable to queue the rebuild of the summaries.";
+
+/**
+ * Queues today of the coach for a refresh. A day already waiting in the queue
+ * is left as it is; the refresh counts everything that happened till then.
+ */
+pub fn mark(connection: &MysqlConnection, the_coach_id: &str) -> QueryResult<usize> {
+    let refresh = NewStatRefresh::new(the_coach_id, util::now().date());
+
+    diesel::insert_or_ignore_into(stat_refresh_queue::table).values(&refresh).execute(connection)
+}
+
+pub fn mark_program(connection: &MysqlConnection, the_program_id: &str) -> QueryResult<usize> {
+    let program: Program = programs::table.filter(programs::id.eq(the_program_id)).first(connection)?;
+
+    mark(connection, program.coach_id.as_str())
+}
+
+pub fn mark_enrollment(connection: &MysqlConnection, the_enrollment_id: &str) -> QueryResult<usize> {
+    let the_program_id: String = enrollments::table.filter(enrollments::id.eq(the_enrollment_id)).select(enrollments::program_id).first(connection)?;
+
+    mark_program(connection, the_program_id.as_str())
+}
+
+/**
+ * Recounts the queued days. The entry leaves the queue before the count, so that
+ * a change arriving during the count queues the day again instead of being lost.
+ */
+pub fn refresh_pending(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let pending: Vec<(String, NaiveDate)> = stat_refresh_queue::table
+        .select((stat_refresh_queue::coach_id, stat_refresh_queue::day))
+        .order_by(stat_refresh_queue::created_at.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| QUEUE_ERROR)?;
+
+    for (the_coach_id, the_day) in &pending {
+        let the_entry = stat_refresh_queue::table
+            .filter(stat_refresh_queue::coach_id.eq(the_coach_id.as_str()))
+            .filter(stat_refresh_queue::day.eq(the_day));
+
+        diesel::delete(the_entry).execute(connection).map_err(|_| QUEUE_ERROR)?;
+
+        let result = count_day(connection, the_coach_id.as_str(), *the_day)
+            .and_then(|stat| diesel::replace_into(coach_daily_stats::table).values(&stat).execute(connection));
+
+        if result.is_err() {
+            let requeue = NewStatRefresh::new(the_coach_id.as_str(), *the_day);
+            let _ = diesel::insert_or_ignore_into(stat_refresh_queue::table).values(&requeue).execute(connection);
+            return Err(REFRESH_ERROR);
+        }
+    }
+
+    Ok(pending.len())
+}
+
+/**
+ * Queues the recent days of every coach with a program, correcting whatever the
+ * marks may have missed; on the first start it fills the summaries as well.
+ */
+pub fn rebuild(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let days = env::var("STATS_REBUILD_DAYS").ok().and_then(|value| value.parse::<i64>().ok()).unwrap_or(REBUILD_DAYS);
+
+    let coach_ids: Vec<String> = programs::table.select(programs::coach_id).distinct().load(connection).map_err(|_| REBUILD_ERROR)?;
+
+    let today = util::now().date();
+    let mut queued = 0;
+
+    for the_coach_id in &coach_ids {
+        let refreshes: Vec<NewStatRefresh> = (0..days).map(|back| NewStatRefresh::new(the_coach_id.as_str(), today - Duration::days(back))).collect();
+
+        queued += diesel::insert_or_ignore_into(stat_refresh_queue::table)
+            .values(&refreshes)
+            .execute(connection)
+            .map_err(|_| REBUILD_ERROR)?;
+    }
+
+    Ok(queued)
+}
+
+/**
+ * Reads only the summaries; the days without any activity are absent.
+ */
+pub fn get_coach_stats(connection: &MysqlConnection, criteria: &CoachStatsCriteria) -> Result<CoachStats, &'static str> {
+    let coach = find_coach_by_id(connection, criteria.coach_id.as_str())?;
+    let (from, to) = criteria.range()?;
+
+    let days: Vec<CoachDailyStat> = coach_daily_stats::table
+        .select((
+            coach_daily_stats::day,
+            coach_daily_stats::enrollments_created,
+            coach_daily_stats::sessions_created,
+            coach_daily_stats::sessions_done,
+            coach_daily_stats::sessions_cancelled,
+            coach_daily_stats::tasks_responded,
+            coach_daily_stats::refreshed_at,
+        ))
+        .filter(coach_daily_stats::coach_id.eq(coach.id.as_str()))
+        .filter(coach_daily_stats::day.between(from, to))
+        .order_by(coach_daily_stats::day.asc())
+        .load(connection)
+        .map_err(|_| STATS_ERROR)?;
+
+    let days = days.into_iter().filter(has_activity).collect();
+
+    Ok(CoachStats { days })
+}
+
+fn has_activity(stat: &CoachDailyStat) -> bool {
+    stat.enrollments_created + stat.sessions_created + stat.sessions_done + stat.sessions_cancelled + stat.tasks_responded > 0
+}
+
+/**
+ * The coach enrolls in her own conferences; those enrollments are not counted.
+ */
+fn count_day(connection: &MysqlConnection, the_coach_id: &str, the_day: NaiveDate) -> QueryResult<NewCoachDailyStat> {
+    let (start, end) = day_bounds(the_day);
+
+    let enrollments_created: i64 = enrollments::table
+        .inner_join(programs::table)
+        .filter(programs::coach_id.eq(the_coach_id))
+        .filter(enrollments::member_id.ne(the_coach_id))
+        .filter(enrollments::created_at.ge(start).and(enrollments::created_at.lt(end)))
+        .count()
+        .get_result(connection)?;
+
+    let coach_sessions = || sessions::table.inner_join(programs::table).filter(programs::coach_id.eq(the_coach_id));
+
+    let sessions_created: i64 = coach_sessions()
+        .filter(sessions::created_at.ge(start).and(sessions::created_at.lt(end)))
+        .count()
+        .get_result(connection)?;
+
+    let sessions_done: i64 = coach_sessions()
+        .filter(sessions::actual_end_date.ge(start).and(sessions::actual_end_date.lt(end)))
+        .count()
+        .get_result(connection)?;
+
+    let sessions_cancelled: i64 = coach_sessions()
+        .filter(sessions::cancelled_at.ge(start).and(sessions::cancelled_at.lt(end)))
+        .count()
+        .get_result(connection)?;
+
+    let tasks_responded: i64 = tasks::table
+        .inner_join(enrollments::table.inner_join(programs::table))
+        .filter(programs::coach_id.eq(the_coach_id))
+        .filter(tasks::responded_date.ge(start).and(tasks::responded_date.lt(end)))
+        .count()
+        .get_result(connection)?;
+
+    Ok(NewCoachDailyStat {
+        coach_id: the_coach_id.to_owned(),
+        day: the_day,
+        enrollments_created: enrollments_created as i32,
+        sessions_created: sessions_created as i32,
+        sessions_done: sessions_done as i32,
+        sessions_cancelled: sessions_cancelled as i32,
+        tasks_responded: tasks_responded as i32,
+    })
+}
@@ -1,26 +1,43 @@
 use diesel::prelude::*;
 
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::ids::{EnrollmentId, SessionId, UserId};
+use crate::commons::transactions::in_transaction;
 use crate::commons::util;
 
+use crate::services::coach_availability;
+use crate::services::coach_stats;
+use crate::services::correspondences::queue_mails;
 use crate::services::enrollments;
+use crate::services::organizations;
 use crate::services::programs;
-use crate::services::sessions::{find_by_conference, insert_session, insert_session_member, remove_conference_session,create_session_mail};
+use crate::services::sessions::{find_by_conference, insert_session, insert_session_member, record_override, remove_conference_session, session_mail};
 use crate::services::users;
 
-use crate::models::conferences::{Conference, IntentionState, MemberRequest, NewConference, NewConferenceRequest};
+use crate::models::conferences::{Conference, ConferenceMembers, FollowupRequest, Followups, IntentionState, MemberRequest, NewConference, NewConferenceRequest};
+use crate::models::correspondences::QueuedMails;
 use crate::models::programs::Program;
+use crate::models::session_objectives::NewSessionObjective;
+use crate::models::session_users::NewSessionUser;
 use crate::models::sessions::{ChangeSessionStateRequest, NewSession, Session, TargetState};
 use crate::models::users::User;
 use crate::schema::conferences::dsl::*;
+use crate::schema::session_objectives;
+use crate::schema::session_users;
+use crate::schema::sessions;
+use crate::schema::users as users_table;
 
 const CONFERENCE_CREATION_ERROR: &str = "Unable to create conference.";
 const FINDER_ERROR: &str = "Unable to find the conference.";
 const CONFERENCE_STATE_UPDATE_ERROR: &str = "Unable to complete the requested action on the state of the conference";
+const CONFERENCE_NOT_DONE: &str = "The follow-ups are spawned once the conference is done.";
+const NO_MEMBERS_TO_FOLLOW: &str = "There is no member of the conference left without a follow-up.";
+const FOLLOWUP_ERROR: &str = "Unable to spawn the follow-ups of the conference.";
 
 pub fn create_conference(connection: &MysqlConnection, request: &NewConferenceRequest) -> Result<Conference, &'static str> {
-    let program = programs::find(connection, request.program_id.as_str())?;
+    let program = programs::find(connection, &request.program_id)?;
 
-    let coach = users::find(connection, &program.coach_id.as_str())?;
+    let coach = users::find(connection, &program.coach_id)?;
 
     let people_involved = coach.full_name.to_owned();
 
@@ -33,7 +50,7 @@ pub fn create_conference(connection: &MysqlConnection, request: &NewConferenceRe
     Ok(conference)
 }
 
-pub fn manage_members(connection: &MysqlConnection, member_request: &MemberRequest) -> Result<Vec<String>, &'static str> {
+pub fn manage_members(connection: &MysqlConnection, member_request: &MemberRequest) -> Result<ConferenceMembers, &'static str> {
     if let IntentionState::ADD = member_request.intention {
         return add_members(connection, member_request);
     }
@@ -61,32 +78,46 @@ fn find(connection: &MysqlConnection, conf_id: &str) -> Result<Conference, &'sta
     Ok(result.unwrap())
 }
 
-fn add_members(connection: &MysqlConnection, member_request: &MemberRequest) -> Result<Vec<String>, &'static str> {
+/**
+ * The new members are seated one by one; the mails telling them of their
+ * sessions are queued together once all are seated.
+ */
+fn add_members(connection: &MysqlConnection, member_request: &MemberRequest) -> Result<ConferenceMembers, &'static str> {
     let conf_id = member_request.conference_id.as_str();
 
     let conference = find(connection, conf_id)?;
 
-    let program = programs::find(connection, conference.program_id.as_str())?;
-    let coach = users::find(connection, program.coach_id.as_str())?;
+    let program = programs::find(connection, &conference.program_id)?;
+    let coach = users::find(connection, &program.coach_id)?;
 
     let mut added_members: Vec<String> = Vec::new();
+    let mut mails = Vec::new();
     for member_id in &member_request.member_ids {
-        let result = find_or_create_session(connection, &conference, member_id, &program, &coach);
-        if result.is_ok() {
-            added_members.push(member_id.to_owned());
+        if let Ok((session, newcomer)) = find_or_create_session(connection, &conference, member_id, &program, &coach) {
+            added_members.push(member_id.to_string());
+
+            if let Some(member) = newcomer {
+                mails.push(session_mail(&session, &member, &coach));
+            }
         }
     }
 
-    Ok(added_members)
+    let notices = queue_mails(connection, mails)?;
+
+    Ok(ConferenceMembers { members: added_members, notices })
 }
 
-fn find_or_create_session(connection: &MysqlConnection, conference: &Conference, member_id: &str, program: &Program, coach: &User) -> Result<Session, &'static str> {
+/**
+ * The session of the member in the conference, along with the member when the
+ * session is new and the member is to be told of it; the coach never is.
+ */
+fn find_or_create_session(connection: &MysqlConnection, conference: &Conference, member_id: &UserId, program: &Program, coach: &User) -> Result<(Session, Option<User>), &'static str> {
     if let Ok(session) = find_by_conference(connection, conference.id.as_str(), member_id) {
-        return Ok(session);
+        return Ok((session, None));
     }
 
     let member = users::find(connection, member_id)?;
-    let enrollment = enrollments::find(connection, &program, &member)?;
+    let enrollment = enrollments::find_active(connection, &program, &member)?;
 
     let is_coach_session = coach.id.as_str().eq(member.id.as_str());
 
@@ -98,10 +129,8 @@ fn find_or_create_session(connection: &MysqlConnection, conference: &Conference,
         user_type = util::MEMBER;
     }
 
-    let fuzzy_id = util::fuzzy_id();
-
     let new_session = NewSession {
-        id: fuzzy_id,
+        id: SessionId::from(util::fuzzy_id()),
         name: conference.name.to_owned(),
         description: conference.description().to_owned(),
         program_id: conference.program_id.to_owned(),
@@ -113,6 +142,7 @@ fn find_or_create_session(connection: &MysqlConnection, conference: &Conference,
         conference_id: Some(conference.id.to_owned()),
         session_type: util::MULTI.to_owned(),
         is_ready: conference.is_ready,
+        parent_conference_id: None,
     };
 
     let session = insert_session(connection, &new_session)?;
@@ -120,14 +150,14 @@ fn find_or_create_session(connection: &MysqlConnection, conference: &Conference,
     
     enrollments::mark_as_old(connection, enrollment.id())?;
 
-    if !is_coach_session {
-        create_session_mail(connection, &session, &member, &coach)?;
+    if is_coach_session {
+        return Ok((session, None));
     }
 
-    Ok(session)
+    Ok((session, Some(member)))
 }
 
-fn remove_members(connection: &MysqlConnection, member_request: &MemberRequest) -> Result<Vec<String>, &'static str> {
+fn remove_members(connection: &MysqlConnection, member_request: &MemberRequest) -> Result<ConferenceMembers, &'static str> {
     let conf_id = member_request.conference_id.as_str();
 
     find(connection, conf_id)?;
@@ -136,18 +166,23 @@ fn remove_members(connection: &MysqlConnection, member_request: &MemberRequest)
     for member_id in &member_request.member_ids {
         let result = remove_conference_session(connection, conf_id, &member_id);
         if result.is_ok() {
-            _members.push(member_id.to_owned());
+            _members.push(member_id.to_string());
         }
     }
 
-    Ok(_members)
+    Ok(ConferenceMembers {
+        members: _members,
+        notices: QueuedMails::default(),
+    })
 }
 
 // Coach Session is a special entry with a self enrollment id
 fn create_coach_session(connection: &MysqlConnection, conference: &Conference, program: &Program, coach: &User) -> Result<Session, &'static str> {
-    enrollments::find_or_create_coach_enrollment(connection, conference.program_id.as_str())?;
+    enrollments::find_or_create_coach_enrollment(connection, &conference.program_id)?;
+
+    let (session, _) = find_or_create_session(connection, &conference, &coach.id, &program, &coach)?;
 
-    find_or_create_session(connection, &conference, &coach.id.to_owned(), &program, &coach)
+    Ok(session)
 }
 
 // To keep the state of the conference in sync with the coach's session state.
@@ -175,3 +210,129 @@ pub fn sync_conference_state(connection: &MysqlConnection, request: &ChangeSessi
 
     Ok(result.unwrap())
 }
+
+/**
+ * A mono session for each member of the conference, or for each member named,
+ * who has no follow-up of it yet. A follow-up keeps the name, the description
+ * and the closing notes of the conference and the objectives the session of the
+ * member addressed, and links back to the conference.
+ *
+ * The follow-ups are held to the scheduling rules and to the availability of the
+ * coach as a new session is; either all of them are created or none.
+ */
+pub fn spawn_followups(connection: &MysqlConnection, caller: Option<&UserId>, request: &FollowupRequest) -> Result<Followups, &'static str> {
+    let conference = find(connection, request.conference_id.as_str())?;
+
+    authorize(connection, caller, Target::Program(conference.program_id.as_str()), &[Role::Coach])?;
+
+    if conference.actual_end_date.is_none() || conference.cancelled_at.is_some() {
+        return Err(CONFERENCE_NOT_DONE);
+    }
+
+    let program = programs::find(connection, &conference.program_id)?;
+    let coach = users::find(connection, &program.coach_id)?;
+
+    let attendees: Vec<(Session, User)> = sessions::table
+        .inner_join(session_users::table.inner_join(users_table::table))
+        .filter(sessions::conference_id.eq(conference.id.as_str()))
+        .filter(sessions::cancelled_at.is_null())
+        .filter(session_users::user_type.eq(util::MEMBER))
+        .order_by(users_table::full_name.asc())
+        .select((sessions::all_columns, users_table::all_columns))
+        .load(connection)
+        .map_err(|_| FOLLOWUP_ERROR)?;
+
+    let followed: Vec<EnrollmentId> = sessions::table
+        .filter(sessions::parent_conference_id.eq(conference.id.as_str()))
+        .filter(sessions::cancelled_at.is_null())
+        .select(sessions::enrollment_id)
+        .load(connection)
+        .map_err(|_| FOLLOWUP_ERROR)?;
+
+    let members: Vec<(Session, User)> = attendees
+        .into_iter()
+        .filter(|(_, member)| request.member_ids.as_ref().map(|ids| ids.contains(&member.id)).unwrap_or(true))
+        .filter(|(session, _)| !followed.contains(&session.enrollment_id))
+        .collect();
+
+    if members.is_empty() {
+        return Err(NO_MEMBERS_TO_FOLLOW);
+    }
+
+    let schedule = request.schedule(members.len());
+
+    let rules = organizations::scheduling_rules(connection, coach.id.as_str())?;
+    let mut overridden: Vec<bool> = Vec::new();
+
+    for ((_, member), (starts_at, ends_at)) in members.iter().zip(&schedule) {
+        let violation = match rules.violation(*starts_at, *ends_at, member.utc_offset) {
+            Some(rule) => Some(rule),
+            None => coach_availability::violation(connection, &coach, *starts_at, *ends_at)?,
+        };
+
+        if let (Some(rule), None) = (violation, request.override_reason()) {
+            return Err(rule);
+        }
+
+        overridden.push(violation.is_some());
+    }
+
+    let spawned = in_transaction(connection, FOLLOWUP_ERROR, || {
+        let mut spawned: Vec<Session> = Vec::new();
+
+        for (((member_session, member), (starts_at, ends_at)), overridden) in members.iter().zip(&schedule).zip(&overridden) {
+            let new_session = NewSession {
+                id: SessionId::from(util::fuzzy_id()),
+                name: conference.followup_name(),
+                description: conference.followup_description(),
+                program_id: conference.program_id.to_owned(),
+                enrollment_id: member_session.enrollment_id.to_owned(),
+                people: util::concat(coach.full_name.as_str(), member.full_name.as_str()),
+                duration: request.duration,
+                original_start_date: *starts_at,
+                original_end_date: *ends_at,
+                conference_id: None,
+                session_type: util::MONO.to_owned(),
+                is_ready: false,
+                parent_conference_id: Some(conference.id.to_owned()),
+            };
+
+            diesel::insert_into(sessions::table).values(&new_session).execute(connection)?;
+            let session: Session = sessions::table.filter(sessions::id.eq(&new_session.id)).first(connection)?;
+
+            let seats = vec![NewSessionUser::from(&session, &coach, util::COACH), NewSessionUser::from(&session, member, util::MEMBER)];
+            diesel::insert_into(session_users::table).values(&seats).execute(connection)?;
+
+            let tags: Vec<NewSessionObjective> = session_objectives::table
+                .filter(session_objectives::session_id.eq(member_session.id.as_str()))
+                .select(session_objectives::objective_id)
+                .load::<String>(connection)?
+                .into_iter()
+                .map(|the_objective_id| NewSessionObjective {
+                    session_id: session.id.to_string(),
+                    objective_id: the_objective_id,
+                    rating: None,
+                })
+                .collect();
+            diesel::insert_into(session_objectives::table).values(&tags).execute(connection)?;
+
+            if let (true, Some(reason)) = (*overridden, request.override_reason()) {
+                record_override(connection, &session, &coach, reason)?;
+            }
+
+            spawned.push(session);
+        }
+
+        Ok(spawned)
+    })?;
+
+    if let Err(e) = coach_stats::mark(connection, program.coach_id.as_str()) {
+        eprintln!("Unable to queue the summary of the coach {}: {}", program.coach_id, e);
+    }
+
+    let mails = spawned.iter().zip(&members).map(|(session, (_, member))| session_mail(session, member, &coach)).collect();
+    let notices = queue_mails(connection, mails)?;
+
+    Ok(Followups { sessions: spawned, notices })
+}
+
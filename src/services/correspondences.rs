@@ -1,11 +1,40 @@
+use chrono::{Duration, NaiveDateTime};
 use diesel::prelude::*;
 
 use crate::schema::correspondences::dsl::*;
 use crate::schema::mail_recipients::dsl::*;
 
-use crate::models::correspondences::{Correspondence, MailCriteria, MailOut, MailRecipient, Mailable};
+use crate::commons::util;
+use crate::models::correspondences::{Correspondence, MailCriteria, MailOut, MailRecipient, Mailable, QueuedMails};
+use crate::models::enrollments::Enrollment;
+use crate::models::member_weeks::WEEK_DAYS;
+use crate::models::notes::Note;
+use crate::models::programs::Program;
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+use crate::models::users::User;
+use crate::models::weekly_digests::{digest_week, NewWeeklyDigest, WeeklyDigest};
+
+use crate::services::mail_bounces::invalid_addresses;
+
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::session_notes;
+use crate::schema::sessions;
+use crate::schema::tasks;
+use crate::schema::user_profiles;
+use crate::schema::users;
+use crate::schema::weekly_digests;
 
 const MAIL_CREATION_ERROR: &str = "Error in creating the invitation mail. But enrollment is done.";
+const MAIL_QUEUE_ERROR: &str = "Unable to queue the mails.";
+const DIGEST_ERROR: &str = "Unable to read the enrollments to digest.";
+
+// The rows of a single insert when queueing many mails at once.
+const MAIL_CHUNK: usize = 100;
+
+// The enrollments digested in a round of the weekly digest.
+const DIGEST_BATCH: i64 = 200;
 
 pub type MailType = (Correspondence, Vec<MailRecipient>);
 pub type MailResult = Result<Vec<MailType>, diesel::result::Error>;
@@ -22,13 +51,23 @@ pub fn sendable_mails(connection: &MysqlConnection) -> MailableResult {
         in_out: "out".to_owned(),
     };
 
-    let mailables: Vec<Mailable> = get_mails(connection, &criteria)?
+    let mails = get_mails(connection, &criteria)?;
+
+    let addresses: Vec<&str> = mails.iter().flat_map(|item| item.1.iter().map(|r| r.to_email.as_str())).collect();
+    let invalid = invalid_addresses(connection, addresses)?;
+
+    // Skip the addresses that keep bouncing; a mail left with no one to send to is not offered at all.
+    let (mailables, undeliverables): (Vec<Mailable>, Vec<Mailable>) = mails
         .into_iter()
         .map(|item| Mailable {
             correspondence: item.0,
-            receipients: item.1,
+            receipients: item.1.into_iter().filter(|r| !invalid.contains(&r.to_email.to_lowercase())).collect(),
         })
-        .collect();
+        .partition(|item| !item.receipients.is_empty());
+
+    let ids: Vec<&str> = undeliverables.iter().map(|item| item.correspondence.id.as_str()).collect();
+    let query = correspondences.filter(crate::schema::correspondences::id.eq_any(ids));
+    diesel::update(query).set(status.eq("undeliverable")).execute(connection)?;
 
     let ids: Vec<&str> = mailables.iter().map(|item| item.correspondence.id.as_str()).collect();
     let query = correspondences.filter(crate::schema::correspondences::id.eq_any(ids));
@@ -66,3 +105,135 @@ pub fn create_mail(connection: &MysqlConnection, mail_out: MailOut, recipients:
 
     Ok(result.unwrap())
 }
+
+/**
+ * Queues many mails at once. The mails and then their recipients are inserted
+ * in chunks, all in one transaction, so either every mail is queued or none is.
+ */
+pub fn queue_mails(connection: &MysqlConnection, mails: Vec<(MailOut, Vec<MailRecipient>)>) -> Result<QueuedMails, &'static str> {
+    let (mail_outs, recipients): (Vec<MailOut>, Vec<Vec<MailRecipient>>) = mails.into_iter().unzip();
+    let recipients: Vec<MailRecipient> = recipients.into_iter().flatten().collect();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        for chunk in mail_outs.chunks(MAIL_CHUNK) {
+            diesel::insert_into(correspondences).values(chunk).execute(connection)?;
+        }
+
+        for chunk in recipients.chunks(MAIL_CHUNK) {
+            diesel::insert_into(mail_recipients).values(chunk).execute(connection)?;
+        }
+
+        Ok(())
+    });
+
+    if result.is_err() {
+        return Err(MAIL_QUEUE_ERROR);
+    }
+
+    Ok(QueuedMails {
+        mails: mail_outs.len() as i32,
+        recipients: recipients.len() as i32,
+    })
+}
+
+/**
+ * The "weekly-digest" job. The active enrollments not yet digested this week,
+ * of the members who did not turn the digest off, get their digest; see
+ * weekly_digests. The rounds go on until every enrollment is told, or a round
+ * tells none.
+ */
+pub fn send_weekly_digests(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let now = util::now();
+    let (week_of, ends_at) = digest_week(now);
+
+    let mut digested = 0;
+
+    loop {
+        let marked = weekly_digests::table.filter(weekly_digests::week_of.eq(week_of)).select(weekly_digests::enrollment_id);
+        let opted_out = user_profiles::table.filter(user_profiles::weekly_digest.eq(false)).select(user_profiles::user_id);
+
+        let due: Vec<(Enrollment, Program, User)> = enrollments::table
+            .inner_join(programs::table)
+            .inner_join(users::table)
+            .filter(enrollments::ended_at.is_null())
+            .filter(enrollments::id.ne_all(marked))
+            .filter(enrollments::member_id.ne_all(opted_out))
+            .select((enrollments::all_columns, programs::all_columns, users::all_columns))
+            .limit(DIGEST_BATCH)
+            .load(connection)
+            .map_err(|_| DIGEST_ERROR)?;
+
+        let mut told = 0;
+
+        for (enrollment, program, member) in &due {
+            match digest(connection, enrollment, program, member, week_of, ends_at, now) {
+                Ok(_) => told += 1,
+                Err(e) => eprintln!("Unable to digest the week of the enrollment {}: {}", enrollment.id, e),
+            }
+        }
+
+        digested += told;
+
+        if told == 0 || (due.len() as i64) < DIGEST_BATCH {
+            return Ok(digested);
+        }
+    }
+}
+
+/**
+ * The enrollment is marked before its digest is queued, in one transaction, so
+ * that a digest is mailed once a week however many instances run the job.
+ */
+fn digest(connection: &MysqlConnection, enrollment: &Enrollment, program: &Program, member: &User, week_of: NaiveDateTime, ends_at: NaiveDateTime, now: NaiveDateTime) -> QueryResult<usize> {
+    let the_enrollment_id = enrollment.id.as_str();
+
+    let the_sessions: Vec<Session> = sessions::table
+        .filter(sessions::enrollment_id.eq(the_enrollment_id))
+        .filter(sessions::is_request.eq(false))
+        .filter(sessions::cancelled_at.is_null())
+        .filter(sessions::expired_at.is_null())
+        .filter(
+            sessions::original_start_date
+                .ge(now)
+                .and(sessions::original_start_date.lt(ends_at))
+                .or(sessions::revised_start_date.ge(now).and(sessions::revised_start_date.lt(ends_at))),
+        )
+        .load(connection)?;
+
+    let the_tasks: Vec<Task> = tasks::table
+        .filter(tasks::enrollment_id.eq(the_enrollment_id))
+        .filter(tasks::responded_date.is_null())
+        .filter(tasks::cancelled_at.is_null())
+        .filter(tasks::actual_end_date.is_null())
+        .filter(tasks::original_end_date.lt(ends_at).or(tasks::revised_end_date.lt(ends_at)))
+        .load(connection)?;
+
+    let the_notes: Vec<Note> = session_notes::table
+        .inner_join(sessions::table)
+        .filter(sessions::enrollment_id.eq(the_enrollment_id))
+        .filter(session_notes::created_by_id.eq(program.coach_id.as_str()))
+        .filter(session_notes::is_private.eq(false))
+        .filter(session_notes::created_at.ge(now - Duration::days(WEEK_DAYS)))
+        .select(session_notes::all_columns)
+        .load(connection)?;
+
+    let digest = WeeklyDigest::of(program.name.as_str(), now, ends_at, the_sessions, the_tasks, the_notes);
+
+    let mark = NewWeeklyDigest {
+        enrollment_id: the_enrollment_id.to_owned(),
+        week_of,
+    };
+
+    connection.transaction::<_, diesel::result::Error, _>(|| {
+        if diesel::insert_or_ignore_into(weekly_digests::table).values(&mark).execute(connection)? == 0 || digest.is_empty() {
+            return Ok(0);
+        }
+
+        let mail_out = MailOut::for_weekly_digest(enrollment, program.coach_id.as_str(), digest.subject(), digest.content(member.full_name.as_str(), now));
+        let recipients = MailRecipient::build_to(&[member], mail_out.id.as_str());
+
+        queue_mails(connection, vec![(mail_out, recipients)]).map_err(|_| diesel::result::Error::RollbackTransaction)?;
+
+        Ok(1)
+    })
+}
@@ -0,0 +1,89 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::models::coupons::{normalize_code, Coupon, NewCouponRedemption, NewCouponRequest};
+use crate::models::programs::Program;
+
+use crate::services::programs;
+
+use crate::schema::coupon_redemptions;
+use crate::schema::coupons;
+
+const NOT_THE_COACH: &str = "Only the coach of the program may hand out its coupons.";
+const NOT_THE_PARENT: &str = "The coupons are kept on the parent program; please create it there.";
+const COUPON_EXISTS: &str = "The program has a coupon with this code already.";
+const COUPON_SAVE_ERROR: &str = "Unable to save the coupon.";
+const COUPONS_FETCH_ERROR: &str = "Unable to fetch the coupons of the program.";
+const UNKNOWN_COUPON: &str = "The coupon code is not known for this program.";
+const REDEEM_ERROR: &str = "Unable to record the coupon against the enrollment.";
+
+pub fn create_coupon(connection: &MysqlConnection, request: &NewCouponRequest) -> Result<Coupon, &'static str> {
+    let program = programs::find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    if !program.is_parent {
+        return Err(NOT_THE_PARENT);
+    }
+
+    let row = request.to_row(program.id.as_str());
+
+    if find_by_code(connection, program.id.as_str(), row.code.as_str()).is_ok() {
+        return Err(COUPON_EXISTS);
+    }
+
+    diesel::insert_into(coupons::table).values(&row).execute(connection).map_err(|_| COUPON_SAVE_ERROR)?;
+
+    coupons::table.filter(coupons::id.eq(row.id.as_str())).first(connection).map_err(|_| COUPON_SAVE_ERROR)
+}
+
+/**
+ * The coupons of the program, the latest first.
+ */
+pub fn get_coupons(connection: &MysqlConnection, the_program_id: &ProgramId) -> Result<Vec<Coupon>, &'static str> {
+    let program = programs::find(connection, the_program_id)?;
+
+    coupons::table
+        .filter(coupons::program_id.eq(program.coalesce_parent_id().as_str()))
+        .order_by(coupons::created_at.desc())
+        .load(connection)
+        .map_err(|_| COUPONS_FETCH_ERROR)
+}
+
+/**
+ * The coupon of the program, or of its parent, the member may use now.
+ */
+pub fn find_usable(connection: &MysqlConnection, program: &Program, code: &str) -> Result<Coupon, &'static str> {
+    let coupon = find_by_code(connection, program.coalesce_parent_id().as_str(), normalize_code(code).as_str()).map_err(|_| UNKNOWN_COUPON)?;
+
+    let uses: i64 = coupon_redemptions::table
+        .filter(coupon_redemptions::coupon_id.eq(coupon.id.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| UNKNOWN_COUPON)?;
+
+    coupon.check(util::now(), uses)?;
+
+    Ok(coupon)
+}
+
+/**
+ * The coupon is recorded against the enrollment it was used for, with the
+ * discount it gave.
+ */
+pub fn redeem(connection: &MysqlConnection, coupon: &Coupon, the_enrollment_id: &str, discount_cents: i32) -> Result<usize, &'static str> {
+    let redemption = NewCouponRedemption {
+        enrollment_id: the_enrollment_id.to_owned(),
+        coupon_id: coupon.id.to_owned(),
+        discount_cents,
+    };
+
+    diesel::insert_into(coupon_redemptions::table).values(&redemption).execute(connection).map_err(|_| REDEEM_ERROR)
+}
+
+fn find_by_code(connection: &MysqlConnection, the_program_id: &str, the_code: &str) -> QueryResult<Coupon> {
+    coupons::table.filter(coupons::program_id.eq(the_program_id)).filter(coupons::code.eq(the_code)).first(connection)
+}
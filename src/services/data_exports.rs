@@ -0,0 +1,79 @@
+use diesel::prelude::*;
+use std::path::PathBuf;
+
+use crate::models::data_exports::{export_file_name, stamp, AccountEntry, DataExport, DiscussionEntry, EnrollmentEntry, NoteEntry, TaskEntry, EXPORTS_DIR};
+use crate::models::discussions::Discussion;
+use crate::models::enrollments::Enrollment;
+use crate::models::notes::Note;
+use crate::models::programs::Program;
+use crate::models::tasks::Task;
+use crate::models::users::User;
+
+use crate::storage::{storage, Area};
+
+use crate::schema::discussions;
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::session_notes;
+use crate::schema::tasks;
+
+const EXPORT_ERROR: &str = "Unable to prepare the data of the user.";
+const ARCHIVE_ERROR: &str = "Unable to keep the archive of the data.";
+
+/**
+ * Gathers the data of the user into a JSON archive kept among the contents of
+ * the user, and returns its path. The tasks are of the enrollments of the user
+ * as a member; the notes and the discussions are the ones the user wrote, in
+ * any role.
+ */
+pub fn export_my_data(connection: &MysqlConnection, user: &User) -> Result<PathBuf, &'static str> {
+    let archive = gather(connection, user)?;
+    let content = serde_json::to_vec_pretty(&archive).map_err(|_| EXPORT_ERROR)?;
+
+    let dir = storage().dir(Area::Users).join(user.id.as_str()).join(EXPORTS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|_| ARCHIVE_ERROR)?;
+
+    let path = dir.join(export_file_name(chrono::Utc::now().naive_utc()));
+    std::fs::write(&path, content).map_err(|_| ARCHIVE_ERROR)?;
+    storage().keep(&path).map_err(|_| ARCHIVE_ERROR)?;
+
+    Ok(path)
+}
+
+fn gather(connection: &MysqlConnection, user: &User) -> Result<DataExport, &'static str> {
+    let enrolled: Vec<(Enrollment, Program)> = enrollments::table
+        .inner_join(programs::table)
+        .filter(enrollments::member_id.eq(user.id.as_str()))
+        .order_by(enrollments::created_at.asc())
+        .load(connection)
+        .map_err(|_| EXPORT_ERROR)?;
+
+    let enrollment_ids: Vec<&str> = enrolled.iter().map(|(enrollment, _)| enrollment.id.as_str()).collect();
+
+    let the_tasks: Vec<Task> = tasks::table
+        .filter(tasks::enrollment_id.eq_any(enrollment_ids))
+        .order_by(tasks::original_start_date.asc())
+        .load(connection)
+        .map_err(|_| EXPORT_ERROR)?;
+
+    let notes: Vec<Note> = session_notes::table
+        .filter(session_notes::created_by_id.eq(user.id.as_str()))
+        .order_by(session_notes::created_at.asc())
+        .load(connection)
+        .map_err(|_| EXPORT_ERROR)?;
+
+    let the_discussions: Vec<Discussion> = discussions::table
+        .filter(discussions::created_by_id.eq(user.id.as_str()))
+        .order_by(discussions::created_at.asc())
+        .load(connection)
+        .map_err(|_| EXPORT_ERROR)?;
+
+    Ok(DataExport {
+        exported_at: stamp(chrono::Utc::now().naive_utc()),
+        account: AccountEntry::from(user),
+        enrollments: enrolled.iter().map(|(enrollment, program)| EnrollmentEntry::from(enrollment, program)).collect(),
+        tasks: the_tasks.iter().map(TaskEntry::from).collect(),
+        notes: notes.iter().map(NoteEntry::from).collect(),
+        discussions: the_discussions.iter().map(DiscussionEntry::from).collect(),
+    })
+}
@@ -0,0 +1,209 @@
+use diesel::prelude::*;
+use serde_json::json;
+
+use crate::commons::ids::UserId;
+use crate::models::audit_events::NewAuditEvent;
+use crate::models::data_fixes::{FixPreview, ReassignNoteAuthorRequest, RelinkEnrollmentRequest, SwapSessionDatesRequest};
+use crate::models::enrollments::Enrollment;
+use crate::models::notes::Note;
+use crate::models::session_users::SessionUser;
+
+use crate::services::admin::admin_of;
+use crate::services::audit;
+use crate::services::programs;
+use crate::services::sessions;
+
+use crate::schema::enrollments;
+use crate::schema::session_notes;
+use crate::schema::session_users;
+use crate::schema::sessions as sessions_table;
+
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const NOTE_NOT_FOUND: &str = "Unable to find the note.";
+const ALREADY_LINKED: &str = "The enrollment is already linked to the given program.";
+const MEMBER_ENROLLED_IN_TARGET: &str = "The member is already enrolled in the given program.";
+const DATES_NOT_SWAPPED: &str = "The session ends after it starts. There is nothing to fix.";
+const SAME_AUTHOR: &str = "The note is already authored by the given user.";
+const AUTHOR_NOT_IN_SESSION: &str = "The given author is not a participant of the session of the note.";
+const FIX_FAILED: &str = "Unable to apply the fix. Nothing was changed.";
+
+/**
+ * Move an enrollment, together with its sessions, to the program it should have been created in.
+ */
+pub fn relink_enrollment(connection: &MysqlConnection, caller: Option<&UserId>, request: &RelinkEnrollmentRequest) -> Result<FixPreview, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let enrollment = find_enrollment(connection, request.enrollment_id.as_str())?;
+    let program = programs::find(connection, &request.program_id)?;
+
+    if enrollment.program_id == program.id {
+        return Err(ALREADY_LINKED);
+    }
+
+    let prior: QueryResult<Enrollment> = enrollments::table
+        .filter(enrollments::program_id.eq(program.id.as_str()))
+        .filter(enrollments::member_id.eq(enrollment.member_id.as_str()))
+        .first(connection);
+
+    if prior.is_ok() {
+        return Err(MEMBER_ENROLLED_IN_TARGET);
+    }
+
+    let preview = FixPreview {
+        entity_type: String::from("enrollment"),
+        entity_id: enrollment.id.to_string(),
+        before: json!({ "program_id": enrollment.program_id }),
+        after: json!({ "program_id": program.id }),
+        applied: false,
+    };
+
+    if request.dry_run {
+        return Ok(preview);
+    }
+
+    let event = audit_event(&preview, "relink", admin.id.as_str(), request.reason.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(enrollments::table.filter(enrollments::id.eq(enrollment.id.as_str())))
+            .set(enrollments::program_id.eq(program.id.as_str()))
+            .execute(connection)?;
+
+        diesel::update(sessions_table::table.filter(sessions_table::enrollment_id.eq(enrollment.id.as_str())))
+            .set(sessions_table::program_id.eq(program.id.as_str()))
+            .execute(connection)?;
+
+        audit::record(connection, &event)
+    });
+
+    applied(result, preview)
+}
+
+/**
+ * A session whose end date precedes its start date was created with the dates swapped.
+ */
+pub fn swap_session_dates(connection: &MysqlConnection, caller: Option<&UserId>, request: &SwapSessionDatesRequest) -> Result<FixPreview, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let session = sessions::find(connection, &request.session_id)?;
+
+    if session.original_end_date >= session.original_start_date {
+        return Err(DATES_NOT_SWAPPED);
+    }
+
+    let preview = FixPreview {
+        entity_type: String::from("session"),
+        entity_id: session.id.to_string(),
+        before: json!({
+            "original_start_date": session.original_start_date.to_string(),
+            "original_end_date": session.original_end_date.to_string(),
+        }),
+        after: json!({
+            "original_start_date": session.original_end_date.to_string(),
+            "original_end_date": session.original_start_date.to_string(),
+        }),
+        applied: false,
+    };
+
+    if request.dry_run {
+        return Ok(preview);
+    }
+
+    let event = audit_event(&preview, "swap_dates", admin.id.as_str(), request.reason.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(sessions_table::table.filter(sessions_table::id.eq(session.id.as_str())))
+            .set((
+                sessions_table::original_start_date.eq(session.original_end_date),
+                sessions_table::original_end_date.eq(session.original_start_date),
+            ))
+            .execute(connection)?;
+
+        audit::record(connection, &event)
+    });
+
+    applied(result, preview)
+}
+
+/**
+ * The new author should be one of the participants of the session the note belongs to.
+ */
+pub fn reassign_note_author(connection: &MysqlConnection, caller: Option<&UserId>, request: &ReassignNoteAuthorRequest) -> Result<FixPreview, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let note = find_note(connection, request.note_id.as_str())?;
+
+    if note.created_by_id == request.author_id {
+        return Err(SAME_AUTHOR);
+    }
+
+    let session_user_result: QueryResult<SessionUser> = session_users::table
+        .filter(session_users::session_id.eq(note.session_id.as_str()))
+        .filter(session_users::user_id.eq(request.author_id.as_str()))
+        .first(connection);
+
+    if session_user_result.is_err() {
+        return Err(AUTHOR_NOT_IN_SESSION);
+    }
+
+    let session_user = session_user_result.unwrap();
+
+    let preview = FixPreview {
+        entity_type: String::from("note"),
+        entity_id: note.id.to_owned(),
+        before: json!({ "created_by_id": note.created_by_id, "session_user_id": note.session_user_id }),
+        after: json!({ "created_by_id": session_user.user_id, "session_user_id": session_user.id }),
+        applied: false,
+    };
+
+    if request.dry_run {
+        return Ok(preview);
+    }
+
+    let event = audit_event(&preview, "reassign_author", admin.id.as_str(), request.reason.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(session_notes::table.filter(session_notes::id.eq(note.id.as_str())))
+            .set((session_notes::created_by_id.eq(session_user.user_id.as_str()), session_notes::session_user_id.eq(session_user.id.as_str())))
+            .execute(connection)?;
+
+        audit::record(connection, &event)
+    });
+
+    applied(result, preview)
+}
+
+fn audit_event(preview: &FixPreview, action: &str, admin_id: &str, reason: &str) -> NewAuditEvent {
+    NewAuditEvent::from(preview.entity_type.as_str(), preview.entity_id.as_str(), action, admin_id)
+        .with_states(&preview.before, &preview.after)
+        .with_reason(reason)
+}
+
+fn applied(result: QueryResult<usize>, mut preview: FixPreview) -> Result<FixPreview, &'static str> {
+    if result.is_err() {
+        return Err(FIX_FAILED);
+    }
+
+    preview.applied = true;
+
+    Ok(preview)
+}
+
+fn find_enrollment(connection: &MysqlConnection, the_id: &str) -> Result<Enrollment, &'static str> {
+    let result = enrollments::table.filter(enrollments::id.eq(the_id)).first(connection);
+
+    if result.is_err() {
+        return Err(ENROLLMENT_NOT_FOUND);
+    }
+
+    Ok(result.unwrap())
+}
+
+fn find_note(connection: &MysqlConnection, the_id: &str) -> Result<Note, &'static str> {
+    let result = session_notes::table.filter(session_notes::id.eq(the_id)).first(connection);
+
+    if result.is_err() {
+        return Err(NOTE_NOT_FOUND);
+    }
+
+    Ok(result.unwrap())
+}
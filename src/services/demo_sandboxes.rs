@@ -0,0 +1,279 @@
+use chrono::Duration;
+use diesel::prelude::*;
+
+use crate::commons::util;
+use crate::demo_mode::DemoMode;
+use crate::models::coach_onboarding::NewCoachOnboarding;
+use crate::models::coaches::NewCoach;
+use crate::models::demo_sandboxes::{sandbox_user, NewDemoSandbox, SandboxCredentials, DEMO_PASSWORD};
+use crate::models::enrollments::NewEnrollmentRequest;
+use crate::models::objectives::NewObjectiveRequest;
+use crate::models::programs::{ChangeProgramStateRequest, NewProgramRequest, ProgramReviewRequest, ProgramTargetState};
+use crate::models::sessions::NewSessionRequest;
+use crate::models::tasks::NewTaskRequest;
+use crate::models::users::{NewUser, User};
+
+use crate::services::enrollments;
+use crate::services::objectives;
+use crate::services::programs;
+use crate::services::sessions;
+use crate::services::tasks;
+use crate::services::users;
+
+use crate::schema::{
+    abstract_tasks, agreement_acceptances, agreements, audit_events, banner_dismissals, board_annotations, coach_availability, coach_availability_exceptions, coach_daily_stats, coach_onboarding, coach_profiles, coaches, conference_recordings,
+    conferences, correspondences, coupon_redemptions, coupons, demo_sandboxes, discussion_queue, discussions, enrollment_coaches, enrollment_handovers, enrollments as enrollments_table, fee_rules, fee_schedules, file_access_log, goal_cards, goal_comments, guest_links,
+    invoices, late_policies, mail_bounces, mail_recipients, master_plans, master_task_links, master_tasks, member_absences, notifications, objectives as objectives_table, observations, options,
+    organization_members, organizations, payments, platform_banners, program_announcements, program_faqs, program_offers, program_plans, program_questions, program_requests, program_reviews, program_waitlists,
+    programs as programs_table, recording_consents, saved_filters, session_files, session_notes, session_objectives, session_reminders, session_scratchpads, session_summaries, session_users, session_visits,
+    sessions as sessions_table, stat_refresh_queue, support_tickets, task_extensions, task_links, tasks as tasks_table, ticket_messages, user_profiles, users as users_table, webhook_deliveries,
+    webhook_subscriptions, weekly_digests,
+};
+
+const NOT_A_DEMO: &str = "The sandboxes are available only in the demo mode.";
+const SANDBOX_ERROR: &str = "Unable to open a sandbox.";
+const SANDBOX_SEED_ERROR: &str = "Unable to seed the sandbox.";
+const SANDBOX_NOT_FOUND: &str = "The sandbox has ended; please open another.";
+const PURGE_ERROR: &str = "Unable to purge the demo data. Nothing was removed.";
+
+const TIME_PATTERN: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/**
+ * A coach and a member of their own, with an active program, an enrollment, a
+ * session for tomorrow, an objective and a task. A sandbox left half seeded by
+ * a failure is removed with the others by the nightly purge.
+ */
+pub fn open_sandbox(connection: &MysqlConnection) -> Result<SandboxCredentials, &'static str> {
+    if !DemoMode::from_env().enabled {
+        return Err(NOT_A_DEMO);
+    }
+
+    let sandbox_id = util::fuzzy_id();
+
+    let coach = insert_user(connection, &sandbox_user(sandbox_id.as_str(), "Demo Coach", util::COACH))?;
+    let member = insert_user(connection, &sandbox_user(sandbox_id.as_str(), "Demo Member", util::MEMBER))?;
+
+    diesel::insert_into(coaches::table).values(&NewCoach::from(&coach)).execute(connection).map_err(|_| SANDBOX_ERROR)?;
+
+    // The demo coach may publish right away.
+    let onboarded = NewCoachOnboarding {
+        coach_id: coach.id.to_string(),
+        payment_account: None,
+        completed_at: Some(util::now()),
+    };
+    diesel::insert_into(coach_onboarding::table).values(&onboarded).execute(connection).map_err(|_| SANDBOX_ERROR)?;
+
+    let program = programs::create_new_program(
+        connection,
+        &NewProgramRequest {
+            name: String::from("Leading with Clarity"),
+            coach_id: coach.id.to_string(),
+            description: String::from("A sample program of the demo. Feel free to change anything; the sandbox is purged tonight."),
+            is_private: false,
+            genre_id: None,
+        },
+    )?;
+
+    programs::change_program_state(
+        connection,
+        &ChangeProgramStateRequest {
+            id: program.id.to_owned(),
+            target_state: ProgramTargetState::ACTIVATE,
+        },
+    )?;
+
+    // The demo program skips the review of the admins.
+    programs::submit_program(
+        connection,
+        &ProgramReviewRequest {
+            program_id: program.id.to_owned(),
+            coach_id: coach.id.to_string(),
+        },
+    )?;
+    programs::publish(connection, &coach, &program.id)?;
+
+    let enrollment = enrollments::create_new_enrollment(
+        connection,
+        &NewEnrollmentRequest {
+            program_id: program.id.to_owned(),
+            user_id: member.id.to_owned(),
+            coach_id: coach.id.to_string(),
+            coupon_code: None,
+        },
+    )?;
+
+    let tomorrow = util::now().date().succ().and_hms(10, 0, 0);
+
+    sessions::create_session(
+        connection,
+        &NewSessionRequest {
+            program_id: program.id.to_owned(),
+            member_id: member.id.to_owned(),
+            name: String::from("Kick-off"),
+            description: String::from("Getting to know each other and setting the goals."),
+            duration: 60,
+            start_time: tomorrow.format(TIME_PATTERN).to_string(),
+            override_reason: Some(String::from("Seeded for the demo")),
+        },
+    )?;
+
+    objectives::create_objective(
+        connection,
+        &NewObjectiveRequest {
+            enrollment_id: enrollment.id.to_string(),
+            start_time: tomorrow.format(TIME_PATTERN).to_string(),
+            end_time: (tomorrow + Duration::days(30)).format(TIME_PATTERN).to_string(),
+            description: String::from("Run the weekly team meeting without notes."),
+        },
+    )
+    .map_err(|_| SANDBOX_SEED_ERROR)?;
+
+    tasks::create_task(
+        connection,
+        &NewTaskRequest {
+            enrollment_id: enrollment.id.to_string(),
+            actor_id: member.id.to_string(),
+            start_time: tomorrow.format(TIME_PATTERN).to_string(),
+            duration: 2,
+            description: String::from("List the three decisions you postponed this month."),
+            name: String::from("Postponed decisions"),
+        },
+    )
+    .map_err(|_| SANDBOX_SEED_ERROR)?;
+
+    let sandbox = NewDemoSandbox {
+        id: sandbox_id,
+        coach_user_id: coach.id.to_string(),
+        member_user_id: member.id.to_string(),
+        program_id: program.id.to_string(),
+    };
+
+    diesel::insert_into(demo_sandboxes::table).values(&sandbox).execute(connection).map_err(|_| SANDBOX_ERROR)?;
+
+    Ok(SandboxCredentials {
+        sandbox_id: sandbox.id,
+        coach_email: coach.email,
+        member_email: member.email,
+        password: String::from(DEMO_PASSWORD),
+        program_id: program.id.to_string(),
+    })
+}
+
+// A sandbox is open until the nightly purge.
+pub fn gate_sandbox(connection: &MysqlConnection, the_id: &str) -> Result<(), &'static str> {
+    let result: QueryResult<String> = demo_sandboxes::table.filter(demo_sandboxes::id.eq(the_id)).select(demo_sandboxes::id).first(connection);
+
+    result.map(|_| ()).map_err(|_| SANDBOX_NOT_FOUND)
+}
+
+fn insert_user(connection: &MysqlConnection, new_user: &NewUser) -> Result<User, &'static str> {
+    diesel::insert_into(users_table::table).values(new_user).execute(connection).map_err(|_| SANDBOX_ERROR)?;
+
+    users::find(connection, &new_user.id)
+}
+
+/**
+ * Ends every sandbox by removing all the data of the demo database, but for the
+ * genres, the roles and the field usage, which are not owned by any sandbox.
+ * The tables are emptied from the dependents up, in one transaction.
+ *
+ * This is safe only because the demo mode runs on a database of its own; see
+ * demo_mode. Outside the demo mode nothing is removed.
+ */
+pub fn purge(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    if !DemoMode::from_env().enabled {
+        return Err(NOT_A_DEMO);
+    }
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::delete(webhook_deliveries::table).execute(connection)?;
+        diesel::delete(webhook_subscriptions::table).execute(connection)?;
+        diesel::delete(recording_consents::table).execute(connection)?;
+        diesel::delete(conference_recordings::table).execute(connection)?;
+        diesel::delete(session_files::table).execute(connection)?;
+        diesel::delete(session_notes::table).execute(connection)?;
+        diesel::delete(guest_links::table).execute(connection)?;
+        diesel::delete(board_annotations::table).execute(connection)?;
+        diesel::delete(session_objectives::table).execute(connection)?;
+        diesel::delete(session_reminders::table).execute(connection)?;
+        diesel::delete(session_scratchpads::table).execute(connection)?;
+        diesel::delete(session_summaries::table).execute(connection)?;
+        diesel::delete(session_visits::table).execute(connection)?;
+        diesel::delete(session_users::table).execute(connection)?;
+        diesel::delete(discussion_queue::table).execute(connection)?;
+        diesel::delete(discussions::table).execute(connection)?;
+        diesel::delete(mail_recipients::table).execute(connection)?;
+        diesel::delete(correspondences::table).execute(connection)?;
+        diesel::delete(ticket_messages::table).execute(connection)?;
+        diesel::delete(support_tickets::table).execute(connection)?;
+        diesel::delete(task_extensions::table).execute(connection)?;
+        diesel::delete(member_absences::table).execute(connection)?;
+        diesel::delete(task_links::table).execute(connection)?;
+        diesel::delete(tasks_table::table).execute(connection)?;
+        diesel::delete(goal_comments::table).execute(connection)?;
+        diesel::delete(goal_cards::table).execute(connection)?;
+        diesel::delete(objectives_table::table).execute(connection)?;
+        diesel::delete(observations::table).execute(connection)?;
+        diesel::delete(options::table).execute(connection)?;
+        diesel::delete(sessions_table::table).execute(connection)?;
+        diesel::delete(conferences::table).execute(connection)?;
+        diesel::delete(program_questions::table).execute(connection)?;
+        diesel::delete(program_faqs::table).execute(connection)?;
+        diesel::delete(program_announcements::table).execute(connection)?;
+        diesel::delete(program_offers::table).execute(connection)?;
+        diesel::delete(program_requests::table).execute(connection)?;
+        diesel::delete(program_reviews::table).execute(connection)?;
+        diesel::delete(program_waitlists::table).execute(connection)?;
+        diesel::delete(program_plans::table).execute(connection)?;
+        diesel::delete(master_task_links::table).execute(connection)?;
+        diesel::delete(master_tasks::table).execute(connection)?;
+        diesel::delete(master_plans::table).execute(connection)?;
+        diesel::delete(abstract_tasks::table).execute(connection)?;
+        diesel::delete(weekly_digests::table).execute(connection)?;
+
+        let sandboxes = diesel::delete(demo_sandboxes::table).execute(connection)?;
+
+        diesel::delete(enrollment_coaches::table).execute(connection)?;
+        diesel::delete(enrollment_handovers::table).execute(connection)?;
+        diesel::delete(coupon_redemptions::table).execute(connection)?;
+        diesel::delete(coupons::table).execute(connection)?;
+        diesel::delete(payments::table).execute(connection)?;
+        diesel::delete(invoices::table).execute(connection)?;
+        diesel::delete(enrollments_table::table).execute(connection)?;
+        diesel::delete(late_policies::table).execute(connection)?;
+        diesel::delete(programs_table::table).execute(connection)?;
+        diesel::delete(coach_daily_stats::table).execute(connection)?;
+        diesel::delete(stat_refresh_queue::table).execute(connection)?;
+        diesel::delete(coach_availability_exceptions::table).execute(connection)?;
+        diesel::delete(coach_availability::table).execute(connection)?;
+        diesel::delete(coach_profiles::table).execute(connection)?;
+        diesel::delete(coach_onboarding::table).execute(connection)?;
+        diesel::delete(coaches::table).execute(connection)?;
+        diesel::delete(banner_dismissals::table).execute(connection)?;
+        diesel::delete(platform_banners::table).execute(connection)?;
+        diesel::delete(saved_filters::table).execute(connection)?;
+        diesel::delete(notifications::table).execute(connection)?;
+        diesel::delete(audit_events::table).execute(connection)?;
+        diesel::delete(agreement_acceptances::table).execute(connection)?;
+        diesel::delete(agreements::table).execute(connection)?;
+        diesel::delete(fee_rules::table).execute(connection)?;
+        diesel::delete(fee_schedules::table).execute(connection)?;
+        diesel::delete(organization_members::table).execute(connection)?;
+        diesel::delete(organizations::table).execute(connection)?;
+        diesel::delete(mail_bounces::table).execute(connection)?;
+        diesel::delete(file_access_log::table).execute(connection)?;
+        diesel::delete(user_profiles::table).execute(connection)?;
+        diesel::delete(users_table::table).execute(connection)?;
+
+        Ok(sandboxes)
+    });
+
+    match result {
+        Ok(sandboxes) => {
+            programs::forget_programs();
+            eprintln!("The demo purge ended {} sandboxes", sandboxes);
+            Ok(sandboxes)
+        }
+        Err(_) => Err(PURGE_ERROR),
+    }
+}
@@ -8,13 +8,18 @@ use crate::schema::discussion_queue::dsl::*;
 use crate::schema::discussions::dsl::*;
 use crate::schema::users::dsl::*;
 
-use crate::models::discussion_queue::{Feed, NewFeed, PendingFeed};
+use crate::commons::chassis::{Page, Window};
+use crate::commons::util;
+use crate::models::discussion_queue::{Feed, FeedCounts, NewFeed, PendingFeed, ReadAllRequest, ReadDiscussionRequest};
 use crate::models::discussions::{Discussion, DiscussionCriteria, NewDiscussion, NewDiscussionRequest};
 use crate::models::users::User;
 
 use crate::models::users::UserCriteria;
 
+use crate::services::notifications;
+
 const FEED_COUNT_ERROR: &str = "Error while counting pending feeds.";
+const MARK_READ_ERROR: &str = "Unable to mark the discussions as read.";
 
 pub fn create_new_discussion(connection: &MysqlConnection, request: &NewDiscussionRequest) -> QueryResult<Discussion> {
     let new_discussion = NewDiscussion::from(request);
@@ -33,11 +38,15 @@ pub fn create_new_discussion(connection: &MysqlConnection, request: &NewDiscussi
     Ok(discussion)
 }
 
-pub fn get_discussions(connection: &MysqlConnection, criteria: DiscussionCriteria) -> Result<Vec<Discussion>, diesel::result::Error> {
-    discussions
+pub fn get_discussions(connection: &MysqlConnection, criteria: DiscussionCriteria, window: &Window) -> Result<Page<Discussion>, diesel::result::Error> {
+    let rows: Vec<Discussion> = discussions
         .filter(discussions::enrollment_id.eq(criteria.enrollment_id))
         .order_by(discussions::created_at.asc())
-        .load(connection)
+        .offset(window.offset)
+        .limit(window.fetch())
+        .load(connection)?;
+
+    Ok(Page::of(rows, window))
 }
 
 /**
@@ -84,6 +93,17 @@ pub fn get_pending_feed_count(connection: &MysqlConnection, user_id: &str) -> Re
     Ok(result.unwrap())
 }
 
+pub fn get_feed_counts(connection: &MysqlConnection, the_user_id: &str) -> Result<FeedCounts, &'static str> {
+    let pending_discussions = get_pending_feed_count(connection, the_user_id)?;
+    let unread_notifications = notifications::count_unread(connection, the_user_id).map_err(|_| FEED_COUNT_ERROR)?;
+
+    Ok(FeedCounts {
+        user_id: the_user_id.to_owned(),
+        pending_discussions,
+        unread_notifications,
+    })
+}
+
 /**
  * When a user respond or typed a message, it is understood that the user read all
  * those prior feeds, hence he will be marked as read.
@@ -96,5 +116,41 @@ fn mark_as_read(connection: &MysqlConnection, to_user_id: &str, for_enrollment_i
         .filter(to_id.eq(to_user_id))
         .filter(discussion_queue::enrollment_id.eq(for_enrollment_id));
 
-    let _ = diesel::update(target_feeds).set(is_pending.eq(false)).execute(connection);
+    let _ = diesel::update(target_feeds).set((is_pending.eq(false), read_at.eq(util::now()))).execute(connection);
+}
+
+/**
+ * The user read some of the discussions of an enrollment without responding.
+ * Answers the number of feeds marked.
+ */
+pub fn mark_discussion_read(connection: &MysqlConnection, request: &ReadDiscussionRequest) -> Result<usize, &'static str> {
+    let target_feeds = discussion_queue
+        .filter(is_pending.eq(true))
+        .filter(to_id.eq(request.user_id.as_str()))
+        .filter(discussion_queue::enrollment_id.eq(request.enrollment_id.as_str()))
+        .filter(discussion_id.eq_any(&request.discussion_ids));
+
+    diesel::update(target_feeds)
+        .set((is_pending.eq(false), read_at.eq(util::now())))
+        .execute(connection)
+        .map_err(|_| MARK_READ_ERROR)
+}
+
+pub fn mark_all_read(connection: &MysqlConnection, request: &ReadAllRequest) -> Result<usize, &'static str> {
+    let mut target_ids = discussion_queue
+        .filter(is_pending.eq(true))
+        .filter(to_id.eq(request.user_id.as_str()))
+        .select(discussion_queue::id)
+        .into_boxed();
+
+    if let Some(value) = &request.enrollment_id {
+        target_ids = target_ids.filter(discussion_queue::enrollment_id.eq(value.as_str()));
+    }
+
+    let the_ids: Vec<String> = target_ids.load(connection).map_err(|_| MARK_READ_ERROR)?;
+
+    diesel::update(discussion_queue.filter(discussion_queue::id.eq_any(&the_ids)))
+        .set((is_pending.eq(false), read_at.eq(util::now())))
+        .execute(connection)
+        .map_err(|_| MARK_READ_ERROR)
 }
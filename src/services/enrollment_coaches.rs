@@ -0,0 +1,77 @@
+use diesel::prelude::*;
+
+use crate::models::enrollment_coaches::{EnrollmentCoach, EnrollmentCoachRequest};
+
+use crate::services::enrollments;
+use crate::services::programs;
+
+use crate::schema::enrollment_coaches;
+
+const NOT_THE_COACH: &str = "Only the coach of the program may share the enrollment.";
+const NOT_A_PEER: &str = "The enrollment may be shared only with a peer coach of the program.";
+const CANCELLED_ENROLLMENT: &str = "The enrollment is cancelled.";
+const CO_COACH_SAVE_ERROR: &str = "Unable to add the co-coach.";
+const CO_COACH_REMOVE_ERROR: &str = "Unable to remove the co-coach.";
+const CO_COACHES_FETCH_ERROR: &str = "Unable to fetch the co-coaches.";
+
+/**
+ * Adding a co-coach twice is as good as adding them once. The co-coaches of the
+ * enrollment are answered.
+ */
+pub fn add_enrollment_coach(connection: &MysqlConnection, request: &EnrollmentCoachRequest) -> Result<Vec<EnrollmentCoach>, &'static str> {
+    let enrollment = enrollments::find_by_id(connection, request.enrollment_id.as_str())?;
+    let program = programs::find(connection, &enrollment.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    if enrollment.is_cancelled() {
+        return Err(CANCELLED_ENROLLMENT);
+    }
+
+    let peers = programs::get_peer_coaches(connection, program.id.as_str()).map_err(|_| CO_COACH_SAVE_ERROR)?;
+    let is_peer = peers.iter().any(|peer| peer.program.coach_id == request.co_coach_id && peer.program.coach_id != program.coach_id);
+
+    if !is_peer {
+        return Err(NOT_A_PEER);
+    }
+
+    diesel::insert_or_ignore_into(enrollment_coaches::table)
+        .values(&request.to_row())
+        .execute(connection)
+        .map_err(|_| CO_COACH_SAVE_ERROR)?;
+
+    get_enrollment_coaches(connection, request.enrollment_id.as_str())
+}
+
+/**
+ * The coach of the program takes the enrollment back from a co-coach, or the
+ * co-coach steps down.
+ */
+pub fn remove_enrollment_coach(connection: &MysqlConnection, request: &EnrollmentCoachRequest) -> Result<Vec<EnrollmentCoach>, &'static str> {
+    let enrollment = enrollments::find_by_id(connection, request.enrollment_id.as_str())?;
+    let program = programs::find(connection, &enrollment.program_id)?;
+
+    if program.coach_id != request.coach_id && request.co_coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    diesel::delete(
+        enrollment_coaches::table
+            .filter(enrollment_coaches::enrollment_id.eq(request.enrollment_id.as_str()))
+            .filter(enrollment_coaches::coach_id.eq(request.co_coach_id.as_str())),
+    )
+    .execute(connection)
+    .map_err(|_| CO_COACH_REMOVE_ERROR)?;
+
+    get_enrollment_coaches(connection, request.enrollment_id.as_str())
+}
+
+pub fn get_enrollment_coaches(connection: &MysqlConnection, the_enrollment_id: &str) -> Result<Vec<EnrollmentCoach>, &'static str> {
+    enrollment_coaches::table
+        .filter(enrollment_coaches::enrollment_id.eq(the_enrollment_id))
+        .order_by(enrollment_coaches::created_at.asc())
+        .load(connection)
+        .map_err(|_| CO_COACHES_FETCH_ERROR)
+}
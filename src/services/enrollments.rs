@@ -1,16 +1,34 @@
 use diesel::prelude::*;
 
+use crate::commons::ids::{EnrollmentId, ProgramId};
+use crate::commons::transactions::{in_transaction, Failure};
+use crate::commons::util;
 use crate::models::programs::Program;
 use crate::models::users::User;
 
 use crate::models::correspondences::{MailOut, MailRecipient};
-use crate::models::enrollments::{Enrollment, EnrollmentCriteria, EnrollmentFilter, ManagedEnrollmentRequest, NewEnrollment, NewEnrollmentRequest};
+use crate::models::enrollment_handovers::{EnrollmentHandover, TransferEnrollmentRequest};
+use crate::models::enrollments::{CancelEnrollmentRequest, Enrollment, EnrollmentCriteria, EnrollmentFilter, ManagedEnrollmentRequest, NewEnrollment, NewEnrollmentRequest};
+use crate::models::notifications::{NewNotification, ENROLLMENT_CANCELLED, ENROLLMENT_CREATED};
+use crate::models::program_waitlists::has_seat;
 
+use crate::services::coach_stats;
 use crate::services::correspondences::create_mail;
+use crate::services::coupons;
+use crate::services::notifications::notify;
+use crate::services::payments;
+use crate::services::program_waitlists;
 use crate::services::programs;
+use crate::services::sessions;
 use crate::services::users;
+use crate::services::webhooks;
 
+use crate::schema::enrollment_coaches;
+use crate::schema::enrollment_handovers;
 use crate::schema::enrollments::dsl::*;
+use crate::schema::objectives;
+use crate::schema::program_waitlists as waitlists_table;
+use crate::schema::tasks;
 use crate::schema::programs::dsl::*;
 use crate::schema::users::dsl::*;
 
@@ -19,19 +37,64 @@ const ERROR_002: &str = "Error in creating enrollment. Error-002.";
 const ERROR_003: &str = "Error in finding enrollment for the program and member. Error-003.";
 const ERROR_004: &str = "Error in marking the enrollment as Old";
 const QUERY_ERROR: &str = "Error in fetching enrolled members";
+const ERROR_005: &str = "Error in creating the enrollment mail. The enrollment is not made. Error-005.";
+const NOT_PUBLISHED: &str = "The program is not open to enroll in yet.";
+const PROGRAM_FULL: &str = "The program is full. Please join its waitlist to be enrolled as a seat opens.";
+const MEMBER_COUNT_ERROR: &str = "Unable to count the members of the program.";
+const PROMOTION_ERROR: &str = "Unable to enroll the member from the waitlist.";
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const CANCELLED_ENROLLMENT: &str = "The enrollment is cancelled. Please enroll again to continue with the program.";
+const ALREADY_CANCELLED: &str = "The enrollment is cancelled already.";
+const CANCEL_ERROR: &str = "Unable to cancel the enrollment.";
+const CANCEL_MAIL_ERROR: &str = "Error in creating the cancellation mail. The enrollment is not cancelled.";
+const NOT_THE_COACH: &str = "Only the coach of the program may hand the enrollment over.";
+const NOT_A_PEER_PROGRAM: &str = "The enrollment may be handed over only to the program of a peer coach.";
+const INACTIVE_PROGRAM: &str = "The program to hand the enrollment over to is not active.";
+const HANDOVER_ERROR: &str = "Unable to hand the enrollment over.";
+const HANDOVERS_FETCH_ERROR: &str = "Unable to fetch the handovers of the enrollment.";
+const HANDOVER_MAIL_ERROR: &str = "Error in creating the handover mail. The enrollment is not handed over.";
 
 pub fn create_new_enrollment(connection: &MysqlConnection, request: &NewEnrollmentRequest) -> Result<Enrollment, &'static str> {
-    let user: User = users::find(connection, request.user_id.as_str())?;
-    let program: Program = programs::find(connection, request.program_id.as_str())?;
+    let (program, enrollment, user) = in_transaction(connection, ERROR_002, || {
+        let user: User = users::find(connection, &request.user_id)?;
+        let program: Program = programs::find(connection, &request.program_id)?;
 
-    gate_prior_enrollment(connection, &program, &user)?;
-    insert_enrollment(connection, &program, &user)?;
+        if !program.is_published() {
+            return Err(Failure::Step(NOT_PUBLISHED));
+        }
+
+        let coupon = match request.coupon_code.as_deref() {
+            Some(code) => Some(coupons::find_usable(connection, &program, code)?),
+            None => None,
+        };
+
+        gate_prior_enrollment(connection, &program, &user)?;
+        gate_capacity(connection, &program)?;
+        insert_enrollment(connection, &program, &user)?;
+
+        let enrollment = find(connection, &program, &user)?;
+
+        let price = program.price_cents.unwrap_or(0);
+        let amount_due = coupon.as_ref().map_or(price, |coupon| coupon.discounted(price));
+
+        payments::take_payment(connection, &program, amount_due, user.id.as_str(), enrollment.id.as_str())?;
 
-    let enrollment = find(connection, &program, &user)?;
+        if let Some(coupon) = &coupon {
+            coupons::redeem(connection, coupon, enrollment.id.as_str(), price - amount_due)?;
+        }
 
-    let coach = users::find(connection, program.coach_id.as_str())?;
+        let coach = users::find(connection, &program.coach_id)?;
 
-    create_self_enrollment_mail(connection, enrollment.id.as_str(), &program, &user, &coach)?;
+        create_self_enrollment_mail(connection, enrollment.id.as_str(), &program, &user, &coach)?;
+
+        let subject = format!("{} enrolled in {}", user.full_name, program.name);
+        notify(connection, &[NewNotification::new(coach.id.as_str(), ENROLLMENT_CREATED, subject, enrollment.id.as_str())])?;
+
+        Ok((program, enrollment, user))
+    })?;
+
+    mark_coach_stats(connection, &program);
+    publish_enrollment(connection, &program, &enrollment, &user);
 
     Ok(enrollment)
 }
@@ -52,9 +115,9 @@ fn insert_enrollment(connection: &MysqlConnection, program: &Program, user: &Use
  * This is because, the notes and other artifacts are tied to the session_user.
  * In order to create a session user we need a session that needs an enrollment.
  */
-pub fn find_or_create_coach_enrollment(connection: &MysqlConnection, given_program_id: &str) -> Result<Enrollment, &'static str> {
+pub fn find_or_create_coach_enrollment(connection: &MysqlConnection, given_program_id: &ProgramId) -> Result<Enrollment, &'static str> {
     let program = programs::find(connection, given_program_id)?;
-    let given_coach_id = program.coach_id.as_str();
+    let given_coach_id = &program.coach_id;
 
     let enrollment_result: Result<Enrollment, diesel::result::Error> = enrollments.filter(program_id.eq(given_program_id)).filter(member_id.eq(given_coach_id)).first(connection);
 
@@ -72,10 +135,14 @@ pub fn find_or_create_coach_enrollment(connection: &MysqlConnection, given_progr
  * Check if the User is enrolled into a Spawned or Root Program already.
  *
  */
-fn gate_prior_enrollment(connection: &MysqlConnection, program: &Program, user: &User) -> Result<bool, &'static str> {
+pub fn gate_prior_enrollment(connection: &MysqlConnection, program: &Program, user: &User) -> Result<bool, &'static str> {
     let prog_query = programs.filter(parent_program_id.eq(program.coalesce_parent_id())).select(crate::schema::programs::id);
 
-    let prior_enrollments: QueryResult<Enrollment> = enrollments.filter(member_id.eq(user.id.as_str())).filter(program_id.eq_any(prog_query)).first(connection);
+    let prior_enrollments: QueryResult<Enrollment> = enrollments
+        .filter(member_id.eq(user.id.as_str()))
+        .filter(program_id.eq_any(prog_query))
+        .filter(ended_at.is_null())
+        .first(connection);
 
     if prior_enrollments.is_err() {
         return Ok(true);
@@ -84,8 +151,94 @@ fn gate_prior_enrollment(connection: &MysqlConnection, program: &Program, user:
     Err(WARNING)
 }
 
+/**
+ * The members of a program, leaving out the coach enrolled for the conferences.
+ */
+pub fn count_members(connection: &MysqlConnection, program: &Program) -> Result<i64, &'static str> {
+    enrollments
+        .filter(program_id.eq(program.id.as_str()))
+        .filter(member_id.ne(program.coach_id.as_str()))
+        .filter(ended_at.is_null())
+        .count()
+        .get_result(connection)
+        .map_err(|_| MEMBER_COUNT_ERROR)
+}
+
+/**
+ * The cap of the program and the count of its members, read with the row of the
+ * program locked until the transaction ends, so that two members may not both
+ * take its last seat.
+ */
+pub fn lock_seats(connection: &MysqlConnection, program: &Program) -> Result<(Option<i32>, i64), &'static str> {
+    let cap: Option<i32> = programs
+        .filter(crate::schema::programs::id.eq(program.id.as_str()))
+        .select(max_members)
+        .for_update()
+        .first(connection)
+        .map_err(|_| MEMBER_COUNT_ERROR)?;
+
+    Ok((cap, count_members(connection, program)?))
+}
+
+fn gate_capacity(connection: &MysqlConnection, program: &Program) -> Result<bool, &'static str> {
+    let (cap, members) = lock_seats(connection, program)?;
+
+    if has_seat(cap, members) {
+        return Ok(true);
+    }
+
+    Err(PROGRAM_FULL)
+}
+
+/**
+ * The member waiting in the line of the program takes a seat. Within the cap,
+ * the seat is checked as the member is enrolled; a coach may promote a member
+ * past it.
+ */
+pub fn enroll_from_waitlist(connection: &MysqlConnection, program: &Program, member: &User, entry_id: &str, within_cap: bool) -> Result<Enrollment, &'static str> {
+    let enrollment = in_transaction(connection, ERROR_002, || {
+        gate_prior_enrollment(connection, program, member)?;
+
+        if within_cap {
+            gate_capacity(connection, program)?;
+        }
+
+        insert_enrollment(connection, program, member)?;
+
+        let enrollment = find(connection, program, member)?;
+
+        diesel::update(waitlists_table::table.filter(waitlists_table::id.eq(entry_id)))
+            .set(waitlists_table::promoted_at.eq(util::now()))
+            .execute(connection)
+            .map_err(|_| PROMOTION_ERROR)?;
+
+        let coach = users::find(connection, &program.coach_id)?;
+
+        let mail_out = MailOut::for_waitlist_promotion(program, enrollment.id.as_str());
+        let recipients = MailRecipient::build_recipients(member, &coach, mail_out.id.as_str());
+        create_mail(connection, mail_out, recipients).map_err(|_| ERROR_005)?;
+
+        let subject = format!("A seat opened in {}; you are enrolled", program.name);
+        notify(connection, &[NewNotification::new(member.id.as_str(), ENROLLMENT_CREATED, subject, enrollment.id.as_str())])?;
+
+        Ok(enrollment)
+    })?;
+
+    mark_coach_stats(connection, program);
+
+    Ok(enrollment)
+}
+
+/**
+ * The latest enrollment of the member in the program; a member may enroll
+ * again after a cancellation.
+ */
 pub fn find(connection: &MysqlConnection, program: &Program, user: &User) -> Result<Enrollment, &'static str> {
-    let result = enrollments.filter(program_id.eq(program.id.to_owned())).filter(member_id.eq(user.id.to_owned())).first(connection);
+    let result = enrollments
+        .filter(program_id.eq(program.id.to_owned()))
+        .filter(member_id.eq(user.id.to_owned()))
+        .order_by(crate::schema::enrollments::created_at.desc())
+        .first(connection);
 
     if result.is_err() {
         return Err(ERROR_003);
@@ -94,7 +247,189 @@ pub fn find(connection: &MysqlConnection, program: &Program, user: &User) -> Res
     Ok(result.unwrap())
 }
 
-pub fn mark_as_old(connection: &MysqlConnection, enrollment_id: &str) -> Result<usize, &'static str> {
+/**
+ * As find, for the steps that need the enrollment to be going on, like a new session.
+ */
+pub fn find_active(connection: &MysqlConnection, program: &Program, user: &User) -> Result<Enrollment, &'static str> {
+    let enrollment = find(connection, program, user)?;
+
+    if enrollment.is_cancelled() {
+        return Err(CANCELLED_ENROLLMENT);
+    }
+
+    Ok(enrollment)
+}
+
+pub fn find_by_id(connection: &MysqlConnection, the_id: &str) -> Result<Enrollment, &'static str> {
+    enrollments.filter(crate::schema::enrollments::id.eq(the_id)).first(connection).map_err(|_| ENROLLMENT_NOT_FOUND)
+}
+
+/**
+ * Ends the enrollment with the reason given by the member or the coach. The
+ * open tasks are cancelled, the open objectives closed and the sessions yet to
+ * start cancelled; the row stays for the history. The seat opened goes to the
+ * first in the waitlist of the program.
+ */
+pub fn cancel_enrollment(connection: &MysqlConnection, request: &CancelEnrollmentRequest) -> Result<Enrollment, &'static str> {
+    let (program, enrollment, cancelled_sessions) = in_transaction(connection, ERROR_002, || {
+        let enrollment = find_by_id(connection, request.enrollment_id.as_str())?;
+
+        if enrollment.is_cancelled() {
+            return Err(Failure::Step(ALREADY_CANCELLED));
+        }
+
+        let program = programs::find(connection, &enrollment.program_id)?;
+        let now = util::now();
+
+        diesel::update(enrollments.filter(crate::schema::enrollments::id.eq(enrollment.id.as_str())))
+            .set((ended_at.eq(now), end_reason.eq(request.reason.trim())))
+            .execute(connection)
+            .map_err(|_| CANCEL_ERROR)?;
+
+        diesel::update(
+            tasks::table
+                .filter(tasks::enrollment_id.eq(enrollment.id.as_str()))
+                .filter(tasks::cancelled_at.is_null())
+                .filter(tasks::actual_end_date.is_null()),
+        )
+        .set(tasks::cancelled_at.eq(now))
+        .execute(connection)
+        .map_err(|_| CANCEL_ERROR)?;
+
+        diesel::update(
+            objectives::table
+                .filter(objectives::enrollment_id.eq(enrollment.id.as_str()))
+                .filter(objectives::actual_end_date.is_null()),
+        )
+        .set(objectives::actual_end_date.eq(now))
+        .execute(connection)
+        .map_err(|_| CANCEL_ERROR)?;
+
+        let cancelled_sessions = sessions::cancel_enrollment_sessions(connection, enrollment.id.as_str()).map_err(|_| CANCEL_ERROR)?;
+
+        let enrollment = find_by_id(connection, enrollment.id.as_str())?;
+
+        let member = users::find(connection, &enrollment.member_id)?;
+        let coach = users::find(connection, &program.coach_id)?;
+        let cancelled_by = if request.user_id == coach.id.as_str() { &coach } else { &member };
+
+        let mail_out = MailOut::for_enrollment_cancellation(&program, &enrollment, cancelled_by);
+        let recipients = MailRecipient::build_recipients(&member, &coach, mail_out.id.as_str());
+        create_mail(connection, mail_out, recipients).map_err(|_| CANCEL_MAIL_ERROR)?;
+
+        // The other party is told.
+        let told = if cancelled_by.id == coach.id { &member } else { &coach };
+        let subject = format!("The enrollment of {} in {} is cancelled", member.full_name, program.name);
+        notify(connection, &[NewNotification::new(told.id.as_str(), ENROLLMENT_CANCELLED, subject, enrollment.id.as_str())])?;
+
+        Ok((program, enrollment, cancelled_sessions))
+    })?;
+
+    sessions::follow_cancellations(connection, &cancelled_sessions);
+    mark_coach_stats(connection, &program);
+
+    if let Err(e) = program_waitlists::promote_waiting(connection, &program) {
+        eprintln!("Unable to promote the waitlist of the program {}: {}", program.id, e);
+    }
+
+    Ok(enrollment)
+}
+
+/**
+ * Hands the enrollment over from the spawned program of its coach to the one of a
+ * peer coach. The sessions yet to start and the open tasks of the coach go along,
+ * the new coach is no longer a co-coach of it, and the member is mailed.
+ */
+pub fn transfer_enrollment(connection: &MysqlConnection, request: &TransferEnrollmentRequest) -> Result<Enrollment, &'static str> {
+    let (from_program, to_program, enrollment) = in_transaction(connection, ERROR_002, || {
+        let enrollment = find_by_id(connection, request.enrollment_id.as_str())?;
+
+        if enrollment.is_cancelled() {
+            return Err(Failure::Step(ALREADY_CANCELLED));
+        }
+
+        let from_program = programs::find(connection, &enrollment.program_id)?;
+        let to_program = programs::find(connection, &request.to_program_id)?;
+
+        if from_program.coach_id != request.coach_id {
+            return Err(Failure::Step(NOT_THE_COACH));
+        }
+
+        if from_program.id == to_program.id || from_program.coalesce_parent_id() != to_program.coalesce_parent_id() {
+            return Err(Failure::Step(NOT_A_PEER_PROGRAM));
+        }
+
+        if !to_program.active {
+            return Err(Failure::Step(INACTIVE_PROGRAM));
+        }
+
+        let member = users::find(connection, &enrollment.member_id)?;
+        let from_coach = users::find(connection, &from_program.coach_id)?;
+        let to_coach = users::find(connection, &to_program.coach_id)?;
+
+        diesel::update(enrollments.filter(crate::schema::enrollments::id.eq(enrollment.id.as_str())))
+            .set(program_id.eq(to_program.id.as_str()))
+            .execute(connection)
+            .map_err(|_| HANDOVER_ERROR)?;
+
+        sessions::hand_over_enrollment_sessions(connection, enrollment.id.as_str(), &to_program, &to_coach, &member).map_err(|_| HANDOVER_ERROR)?;
+
+        diesel::update(
+            tasks::table
+                .filter(tasks::enrollment_id.eq(enrollment.id.as_str()))
+                .filter(tasks::actor_id.eq(from_coach.id.as_str()))
+                .filter(tasks::cancelled_at.is_null())
+                .filter(tasks::actual_end_date.is_null()),
+        )
+        .set(tasks::actor_id.eq(to_coach.id.as_str()))
+        .execute(connection)
+        .map_err(|_| HANDOVER_ERROR)?;
+
+        diesel::delete(
+            enrollment_coaches::table
+                .filter(enrollment_coaches::enrollment_id.eq(enrollment.id.as_str()))
+                .filter(enrollment_coaches::coach_id.eq(to_coach.id.as_str())),
+        )
+        .execute(connection)
+        .map_err(|_| HANDOVER_ERROR)?;
+
+        diesel::insert_into(enrollment_handovers::table)
+            .values(&request.to_row(from_program.id.as_str(), to_coach.id.as_str()))
+            .execute(connection)
+            .map_err(|_| HANDOVER_ERROR)?;
+
+        let enrollment = find_by_id(connection, enrollment.id.as_str())?;
+
+        let mail_out = MailOut::for_enrollment_handover(&to_program, &enrollment, &from_coach, &to_coach);
+        let recipients = MailRecipient::build_recipients(&member, &to_coach, mail_out.id.as_str());
+        create_mail(connection, mail_out, recipients).map_err(|_| HANDOVER_MAIL_ERROR)?;
+
+        Ok((from_program, to_program, enrollment))
+    })?;
+
+    mark_coach_stats(connection, &from_program);
+    mark_coach_stats(connection, &to_program);
+
+    // The seat the member left may go to the first in the line.
+    if let Err(e) = program_waitlists::promote_waiting(connection, &from_program) {
+        eprintln!("Unable to promote the waitlist of the program {}: {}", from_program.id, e);
+    }
+
+    Ok(enrollment)
+}
+
+/**
+ * The handovers of the enrollment with their notes, the latest first.
+ */
+pub fn get_handovers(connection: &MysqlConnection, the_enrollment_id: &str) -> Result<Vec<EnrollmentHandover>, &'static str> {
+    enrollment_handovers::table
+        .filter(enrollment_handovers::enrollment_id.eq(the_enrollment_id))
+        .order_by(enrollment_handovers::created_at.desc())
+        .load(connection)
+        .map_err(|_| HANDOVERS_FETCH_ERROR)
+}
+
+pub fn mark_as_old(connection: &MysqlConnection, enrollment_id: &EnrollmentId) -> Result<usize, &'static str> {
     let query = enrollments.filter(crate::schema::enrollments::id.eq(enrollment_id));
 
     let result = diesel::update(query).set(is_new.eq(false)).execute(connection);
@@ -112,6 +447,7 @@ pub fn get_active_enrollments(connection: &MysqlConnection, criteria: Enrollment
     let mut query = enrollments
         .inner_join(users)
         .filter(program_id.eq(criteria.program_id))
+        .filter(ended_at.is_null())
         .select(users::all_columns())
         .order_by(full_name.asc())
         .into_boxed();
@@ -136,35 +472,59 @@ const CONFLICT_PROGRAM_OWNER_MAIL: &str = "The coach does not have rights to enr
  * When a coach enrolls a member into her program
  */
 pub fn create_managed_enrollment(connection: &MysqlConnection, request: &ManagedEnrollmentRequest) -> Result<Enrollment, &'static str> {
-    let user_result: QueryResult<User> = users.filter(email.eq(request.member_mail.as_str())).first(connection);
+    let (program, enrollment, member) = in_transaction(connection, ERROR_002, || {
+        let user_result: QueryResult<User> = users.filter(email.eq(request.member_mail.as_str())).first(connection);
 
-    if user_result.is_err() {
-        return Err(INVALID_MEMBER_MAIL);
-    }
+        if user_result.is_err() {
+            return Err(Failure::Step(INVALID_MEMBER_MAIL));
+        }
 
-    let program_result: QueryResult<Program> = programs
-        .filter(crate::schema::programs::id.eq(request.program_id.as_str()))
-        .filter(coach_id.eq(request.coach_id.as_str()))
-        .first(connection);
+        let program_result: QueryResult<Program> = programs
+            .filter(crate::schema::programs::id.eq(request.program_id.as_str()))
+            .filter(coach_id.eq(request.coach_id.as_str()))
+            .first(connection);
 
-    if program_result.is_err() {
-        return Err(CONFLICT_PROGRAM_OWNER_MAIL);
-    }
+        if program_result.is_err() {
+            return Err(Failure::Step(CONFLICT_PROGRAM_OWNER_MAIL));
+        }
+
+        let member = user_result.unwrap();
+        let program = program_result.unwrap();
+        let coach = users::find(connection, &request.coach_id)?;
+
+        gate_prior_enrollment(connection, &program, &member)?;
+        insert_enrollment(connection, &program, &member)?;
 
-    let member = user_result.unwrap();
-    let program = program_result.unwrap();
-    let coach = users::find(connection, request.coach_id.as_str())?;
+        let enrollment = find(connection, &program, &member)?;
 
-    gate_prior_enrollment(connection, &program, &member)?;
-    insert_enrollment(connection, &program, &member)?;
+        create_managed_enrollment_mail(connection, request, enrollment.id.as_str(), &member, &coach)?;
 
-    let enrollment = find(connection, &program, &member)?;
+        let subject = format!("{} enrolled you in {}", coach.full_name, program.name);
+        notify(connection, &[NewNotification::new(member.id.as_str(), ENROLLMENT_CREATED, subject, enrollment.id.as_str())])?;
 
-    create_managed_enrollment_mail(connection, request, enrollment.id.as_str(), &member, &coach)?;
+        Ok((program, enrollment, member))
+    })?;
+
+    mark_coach_stats(connection, &program);
+    publish_enrollment(connection, &program, &enrollment, &member);
 
     Ok(enrollment)
 }
 
+// The enrollment stands; a summary that could not be queued is caught by the rebuild.
+fn mark_coach_stats(connection: &MysqlConnection, program: &Program) {
+    if let Err(e) = coach_stats::mark(connection, program.coach_id.as_str()) {
+        eprintln!("Unable to queue the summary of the coach {}: {}", program.coach_id, e);
+    }
+}
+
+// The enrollment stands too when its webhooks could not be queued.
+fn publish_enrollment(connection: &MysqlConnection, program: &Program, enrollment: &Enrollment, member: &User) {
+    if let Err(e) = webhooks::enrollment_created(connection, program, enrollment, member) {
+        eprintln!("Unable to queue the webhooks of the enrollment {}: {}", enrollment.id, e);
+    }
+}
+
 /**
  * Mail when a coach enrolls a member into his program
  */
@@ -172,7 +532,7 @@ fn create_managed_enrollment_mail(connection: &MysqlConnection, request: &Manage
     let mail_out = MailOut::for_managed_enrollment(request, new_enroll_id);
     let recipients = MailRecipient::build_recipients(member, coach, mail_out.id.as_str());
 
-    create_mail(connection, mail_out, recipients)
+    create_mail(connection, mail_out, recipients).map_err(|_| ERROR_005)
 }
 
 /**
@@ -182,5 +542,5 @@ fn create_self_enrollment_mail(connection: &MysqlConnection, enrollment_id: &str
     let mail_out = MailOut::for_self_enrollment(program, enrollment_id);
     let recipients = MailRecipient::build_recipients(member, coach, mail_out.id.as_str());
 
-    create_mail(connection, mail_out, recipients)
+    create_mail(connection, mail_out, recipients).map_err(|_| ERROR_005)
 }
@@ -0,0 +1,90 @@
+use diesel::prelude::*;
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::models::objectives::Objective;
+use crate::models::observations::Observation;
+use crate::models::plan_exports::{plan_header, PlanRow};
+use crate::models::tasks::Task;
+use crate::models::users::User;
+use crate::services::observations::get_readable_observations;
+
+use crate::schema::objectives;
+use crate::schema::tasks;
+
+const PLAN_ERROR: &str = "Unable to gather the plan of the enrollment.";
+
+/**
+ * The plan of the enrollment, for its coach or its member alone.
+ */
+pub fn get_plan(connection: &MysqlConnection, user: &User, the_enrollment_id: &str) -> Result<Vec<PlanRow>, &'static str> {
+    authorize(connection, Some(&user.id), Target::Enrollment(the_enrollment_id), &[Role::Coach, Role::Member])?;
+
+    let the_objectives: Vec<Objective> = objectives::table
+        .filter(objectives::enrollment_id.eq(the_enrollment_id))
+        .order_by(objectives::original_start_date.asc())
+        .load(connection)
+        .map_err(|_| PLAN_ERROR)?;
+
+    let the_tasks: Vec<Task> = tasks::table
+        .filter(tasks::enrollment_id.eq(the_enrollment_id))
+        .order_by(tasks::original_start_date.asc())
+        .load(connection)
+        .map_err(|_| PLAN_ERROR)?;
+
+    // The member finds only the observations shared with them.
+    let the_observations: Vec<Observation> = get_readable_observations(connection, user, the_enrollment_id)?;
+
+    let rows = the_objectives
+        .iter()
+        .map(PlanRow::from_objective)
+        .chain(the_tasks.iter().map(PlanRow::from_task))
+        .chain(the_observations.iter().map(PlanRow::from_observation))
+        .collect();
+
+    Ok(rows)
+}
+
+pub fn plan_csv(rows: &[PlanRow]) -> Vec<u8> {
+    let lines = std::iter::once(plan_header()).chain(rows.iter().map(PlanRow::to_csv));
+
+    lines.collect::<String>().into_bytes()
+}
+
+/**
+ * A sheet for each section, with the header on its first line kept in view.
+ */
+#[cfg(feature = "xlsx-export")]
+pub fn plan_xlsx(rows: &[PlanRow]) -> Result<Vec<u8>, &'static str> {
+    use crate::models::plan_exports::{OBJECTIVES, OBSERVATIONS, PLAN_COLUMNS, TASKS};
+    use rust_xlsxwriter::{Format, Workbook};
+
+    const WORKBOOK_ERROR: &str = "Unable to write the workbook of the plan.";
+
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+
+    for section in [OBJECTIVES, TASKS, OBSERVATIONS].iter() {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(*section).map_err(|_| WORKBOOK_ERROR)?;
+
+        for (column, title) in PLAN_COLUMNS.iter().enumerate() {
+            sheet.write_string_with_format(0, column as u16, *title, &bold).map_err(|_| WORKBOOK_ERROR)?;
+        }
+
+        for (line, row) in rows.iter().filter(|row| row.section == *section).enumerate() {
+            for (column, cell) in row.cells().iter().enumerate() {
+                sheet.write_string(line as u32 + 1, column as u16, cell).map_err(|_| WORKBOOK_ERROR)?;
+            }
+        }
+
+        sheet.set_freeze_panes(1, 0).map_err(|_| WORKBOOK_ERROR)?;
+        sheet.autofit();
+    }
+
+    workbook.save_to_buffer().map_err(|_| WORKBOOK_ERROR)
+}
+
+#[cfg(not(feature = "xlsx-export"))]
+pub fn plan_xlsx(_rows: &[PlanRow]) -> Result<Vec<u8>, &'static str> {
+    Err("The Excel export is not built into this server; ask for the CSV instead.")
+}
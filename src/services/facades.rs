@@ -0,0 +1,463 @@
+/**
+ * The service groups the resolvers reach through the context, rather than calling
+ * the service functions with a connection of their own. The diesel-backed ones
+ * below are the default; the tests put their own in the context, so that the
+ * GraphQL layer can be tried without a database.
+ *
+ * A group works on the connection of the request, taken from the context it is
+ * handed, so that a guarded mutation holds one connection and a query reads from
+ * the replica like any other resolver. The guard is a group of its own, as the
+ * guarded mutations check the caller before calling any other.
+ */
+use std::sync::Arc;
+
+use crate::commons::chassis::{Page, QueryError, Window};
+use crate::commons::guard::{self, Role, Target};
+use crate::commons::ids::SessionId;
+use crate::graphql_schema::DBContext;
+use crate::models::enrollments::PlanCriteria;
+use crate::models::programs::{AssociateCoachRequest, ChangeProgramStateRequest, NewProgramRequest, Program, ProgramCoach};
+use crate::models::sessions::{ChangeSessionStateRequest, NewSessionRequest, Session};
+use crate::models::tasks::{
+    ChangeCoachTaskStateRequest, ChangeMemberTaskStateRequest, CreateTasksFromSummaryRequest, NewTaskRequest, RescheduleCascadeRequest, Task, UpdateClosingNoteRequest, UpdateResponseRequest,
+    UpdateTaskRequest,
+};
+use crate::models::user_events::{self, EventCriteria, EventRow};
+use crate::models::user_programs::{self, ProgramCriteria, ProgramRow};
+use crate::services::{programs, sessions, tasks};
+
+const TASK_CREATE_ERROR: &str = "Unable to create the task.";
+const TASK_UPDATE_ERROR: &str = "Unable to update the task.";
+
+pub trait GuardService: Send + Sync {
+    fn authorize(&self, context: &DBContext, target: Target, roles: &[Role]) -> Result<(), &'static str>;
+}
+
+pub trait ProgramsService: Send + Sync {
+    fn get_programs(&self, context: &DBContext, criteria: &ProgramCriteria, window: &Window) -> Result<Page<ProgramRow>, QueryError>;
+    fn get_program_coaches(&self, context: &DBContext, program_id: &str) -> Result<Vec<ProgramCoach>, QueryError>;
+    fn create_program(&self, context: &DBContext, request: &NewProgramRequest) -> Result<Program, &'static str>;
+    fn associate_coach(&self, context: &DBContext, request: &AssociateCoachRequest) -> Result<Program, &'static str>;
+    fn change_program_state(&self, context: &DBContext, request: &ChangeProgramStateRequest) -> Result<usize, &'static str>;
+}
+
+pub trait SessionsService: Send + Sync {
+    fn get_events(&self, context: &DBContext, criteria: EventCriteria, window: &Window) -> Result<Page<EventRow>, QueryError>;
+    fn find_session(&self, context: &DBContext, session_id: &SessionId) -> Result<Session, &'static str>;
+    fn create_session(&self, context: &DBContext, request: &NewSessionRequest) -> Result<Session, &'static str>;
+    fn change_session_state(&self, context: &DBContext, request: &ChangeSessionStateRequest) -> Result<Session, &'static str>;
+}
+
+pub trait TasksService: Send + Sync {
+    fn get_tasks(&self, context: &DBContext, criteria: PlanCriteria) -> Result<Vec<Task>, QueryError>;
+    fn create_task(&self, context: &DBContext, request: &NewTaskRequest) -> Result<Task, &'static str>;
+    fn update_task(&self, context: &DBContext, request: &UpdateTaskRequest) -> Result<Task, &'static str>;
+    fn update_closing_notes(&self, context: &DBContext, request: &UpdateClosingNoteRequest) -> Result<Task, &'static str>;
+    fn update_response(&self, context: &DBContext, request: &UpdateResponseRequest) -> Result<Task, &'static str>;
+    fn change_coach_task_state(&self, context: &DBContext, request: &ChangeCoachTaskStateRequest) -> Result<Task, &'static str>;
+    fn change_member_task_state(&self, context: &DBContext, request: &ChangeMemberTaskStateRequest) -> Result<Task, &'static str>;
+    fn reschedule_cascade(&self, context: &DBContext, request: &RescheduleCascadeRequest) -> Result<Vec<Task>, &'static str>;
+    fn create_tasks_from_summary(&self, context: &DBContext, request: &CreateTasksFromSummaryRequest) -> Result<Vec<Task>, &'static str>;
+}
+
+#[derive(Clone)]
+pub struct Services {
+    pub guard: Arc<dyn GuardService>,
+    pub programs: Arc<dyn ProgramsService>,
+    pub sessions: Arc<dyn SessionsService>,
+    pub tasks: Arc<dyn TasksService>,
+}
+
+impl Services {
+    pub fn backed_by_diesel() -> Services {
+        let backend = Arc::new(Diesel);
+
+        Services {
+            guard: backend.clone(),
+            programs: backend.clone(),
+            sessions: backend.clone(),
+            tasks: backend,
+        }
+    }
+}
+
+struct Diesel;
+
+impl GuardService for Diesel {
+    fn authorize(&self, context: &DBContext, target: Target, roles: &[Role]) -> Result<(), &'static str> {
+        let connection = context.connection()?;
+        guard::authorize(&connection, context.caller(), target, roles)
+    }
+}
+
+impl ProgramsService for Diesel {
+    fn get_programs(&self, context: &DBContext, criteria: &ProgramCriteria, window: &Window) -> Result<Page<ProgramRow>, QueryError> {
+        let connection = context.connection()?;
+        Ok(user_programs::get_programs(&connection, criteria, window)?)
+    }
+
+    fn get_program_coaches(&self, context: &DBContext, program_id: &str) -> Result<Vec<ProgramCoach>, QueryError> {
+        let connection = context.connection()?;
+        Ok(programs::get_peer_coaches(&connection, program_id)?)
+    }
+
+    fn create_program(&self, context: &DBContext, request: &NewProgramRequest) -> Result<Program, &'static str> {
+        let connection = context.connection()?;
+        programs::create_new_program(&connection, request)
+    }
+
+    fn associate_coach(&self, context: &DBContext, request: &AssociateCoachRequest) -> Result<Program, &'static str> {
+        let connection = context.connection()?;
+        programs::associate_coach(&connection, request)
+    }
+
+    fn change_program_state(&self, context: &DBContext, request: &ChangeProgramStateRequest) -> Result<usize, &'static str> {
+        let connection = context.connection()?;
+        programs::change_program_state(&connection, request)
+    }
+}
+
+impl SessionsService for Diesel {
+    fn get_events(&self, context: &DBContext, criteria: EventCriteria, window: &Window) -> Result<Page<EventRow>, QueryError> {
+        let connection = context.connection()?;
+        user_events::get_events(&connection, criteria, window)
+    }
+
+    fn find_session(&self, context: &DBContext, session_id: &SessionId) -> Result<Session, &'static str> {
+        let connection = context.connection()?;
+        sessions::find(&connection, session_id)
+    }
+
+    fn create_session(&self, context: &DBContext, request: &NewSessionRequest) -> Result<Session, &'static str> {
+        let connection = context.connection()?;
+        sessions::create_session(&connection, request)
+    }
+
+    fn change_session_state(&self, context: &DBContext, request: &ChangeSessionStateRequest) -> Result<Session, &'static str> {
+        let connection = context.connection()?;
+        sessions::change_session_state(&connection, request)
+    }
+}
+
+impl TasksService for Diesel {
+    fn get_tasks(&self, context: &DBContext, criteria: PlanCriteria) -> Result<Vec<Task>, QueryError> {
+        let connection = context.connection()?;
+        Ok(tasks::get_tasks(&connection, criteria)?)
+    }
+
+    fn create_task(&self, context: &DBContext, request: &NewTaskRequest) -> Result<Task, &'static str> {
+        let connection = context.connection()?;
+        tasks::create_task(&connection, request).map_err(|_| TASK_CREATE_ERROR)
+    }
+
+    fn update_task(&self, context: &DBContext, request: &UpdateTaskRequest) -> Result<Task, &'static str> {
+        let connection = context.connection()?;
+        tasks::update_task(&connection, request).map_err(|_| TASK_UPDATE_ERROR)
+    }
+
+    fn update_closing_notes(&self, context: &DBContext, request: &UpdateClosingNoteRequest) -> Result<Task, &'static str> {
+        let connection = context.connection()?;
+        tasks::update_closing_notes(&connection, request)
+    }
+
+    fn update_response(&self, context: &DBContext, request: &UpdateResponseRequest) -> Result<Task, &'static str> {
+        let connection = context.connection()?;
+        tasks::update_response(&connection, request)
+    }
+
+    fn change_coach_task_state(&self, context: &DBContext, request: &ChangeCoachTaskStateRequest) -> Result<Task, &'static str> {
+        let connection = context.connection()?;
+        tasks::change_coach_task_state(&connection, request)
+    }
+
+    fn change_member_task_state(&self, context: &DBContext, request: &ChangeMemberTaskStateRequest) -> Result<Task, &'static str> {
+        let connection = context.connection()?;
+        tasks::change_member_task_state(&connection, request)
+    }
+
+    fn reschedule_cascade(&self, context: &DBContext, request: &RescheduleCascadeRequest) -> Result<Vec<Task>, &'static str> {
+        let connection = context.connection()?;
+        tasks::reschedule_cascade(&connection, request)
+    }
+
+    fn create_tasks_from_summary(&self, context: &DBContext, request: &CreateTasksFromSummaryRequest) -> Result<Vec<Task>, &'static str> {
+        let connection = context.connection()?;
+        tasks::create_tasks_from_summary(&connection, request)
+    }
+}
+
+#[cfg(test)]
+pub mod mocks {
+
+    use super::*;
+    use std::sync::Mutex;
+
+    const UNAVAILABLE: &str = "Not available in the test.";
+
+    /**
+     * Lets the callers in or turns them all away.
+     */
+    pub struct MockGuard {
+        pub allows: bool,
+    }
+
+    impl GuardService for MockGuard {
+        fn authorize(&self, _context: &DBContext, _target: Target, _roles: &[Role]) -> Result<(), &'static str> {
+            if self.allows {
+                return Ok(());
+            }
+
+            Err("You are not allowed to do this.")
+        }
+    }
+
+    /**
+     * Finds nothing and changes nothing, noting what it was asked for.
+     */
+    #[derive(Default)]
+    pub struct MockBackend {
+        pub calls: Mutex<Vec<&'static str>>,
+    }
+
+    impl MockBackend {
+        fn called(&self, name: &'static str) {
+            self.calls.lock().unwrap().push(name);
+        }
+    }
+
+    impl ProgramsService for MockBackend {
+        fn get_programs(&self, _context: &DBContext, _criteria: &ProgramCriteria, window: &Window) -> Result<Page<ProgramRow>, QueryError> {
+            self.called("get_programs");
+            Ok(Page::of(Vec::new(), window))
+        }
+
+        fn get_program_coaches(&self, _context: &DBContext, _program_id: &str) -> Result<Vec<ProgramCoach>, QueryError> {
+            self.called("get_program_coaches");
+            Ok(Vec::new())
+        }
+
+        fn create_program(&self, _context: &DBContext, _request: &NewProgramRequest) -> Result<Program, &'static str> {
+            self.called("create_program");
+            Err(UNAVAILABLE)
+        }
+
+        fn associate_coach(&self, _context: &DBContext, _request: &AssociateCoachRequest) -> Result<Program, &'static str> {
+            self.called("associate_coach");
+            Err(UNAVAILABLE)
+        }
+
+        fn change_program_state(&self, _context: &DBContext, _request: &ChangeProgramStateRequest) -> Result<usize, &'static str> {
+            self.called("change_program_state");
+            Ok(1)
+        }
+    }
+
+    impl SessionsService for MockBackend {
+        fn get_events(&self, _context: &DBContext, _criteria: EventCriteria, window: &Window) -> Result<Page<EventRow>, QueryError> {
+            self.called("get_events");
+            Ok(Page::of(Vec::new(), window))
+        }
+
+        fn find_session(&self, _context: &DBContext, _session_id: &SessionId) -> Result<Session, &'static str> {
+            self.called("find_session");
+            Err(UNAVAILABLE)
+        }
+
+        fn create_session(&self, _context: &DBContext, _request: &NewSessionRequest) -> Result<Session, &'static str> {
+            self.called("create_session");
+            Err(UNAVAILABLE)
+        }
+
+        fn change_session_state(&self, _context: &DBContext, _request: &ChangeSessionStateRequest) -> Result<Session, &'static str> {
+            self.called("change_session_state");
+            Err(UNAVAILABLE)
+        }
+    }
+
+    impl TasksService for MockBackend {
+        fn get_tasks(&self, _context: &DBContext, _criteria: PlanCriteria) -> Result<Vec<Task>, QueryError> {
+            self.called("get_tasks");
+            Ok(Vec::new())
+        }
+
+        fn create_task(&self, _context: &DBContext, _request: &NewTaskRequest) -> Result<Task, &'static str> {
+            self.called("create_task");
+            Err(UNAVAILABLE)
+        }
+
+        fn update_task(&self, _context: &DBContext, _request: &UpdateTaskRequest) -> Result<Task, &'static str> {
+            self.called("update_task");
+            Err(UNAVAILABLE)
+        }
+
+        fn update_closing_notes(&self, _context: &DBContext, _request: &UpdateClosingNoteRequest) -> Result<Task, &'static str> {
+            self.called("update_closing_notes");
+            Err(UNAVAILABLE)
+        }
+
+        fn update_response(&self, _context: &DBContext, _request: &UpdateResponseRequest) -> Result<Task, &'static str> {
+            self.called("update_response");
+            Err(UNAVAILABLE)
+        }
+
+        fn change_coach_task_state(&self, _context: &DBContext, _request: &ChangeCoachTaskStateRequest) -> Result<Task, &'static str> {
+            self.called("change_coach_task_state");
+            Err(UNAVAILABLE)
+        }
+
+        fn change_member_task_state(&self, _context: &DBContext, _request: &ChangeMemberTaskStateRequest) -> Result<Task, &'static str> {
+            self.called("change_member_task_state");
+            Err(UNAVAILABLE)
+        }
+
+        fn reschedule_cascade(&self, _context: &DBContext, _request: &RescheduleCascadeRequest) -> Result<Vec<Task>, &'static str> {
+            self.called("reschedule_cascade");
+            Err(UNAVAILABLE)
+        }
+
+        fn create_tasks_from_summary(&self, _context: &DBContext, _request: &CreateTasksFromSummaryRequest) -> Result<Vec<Task>, &'static str> {
+            self.called("create_tasks_from_summary");
+            Err(UNAVAILABLE)
+        }
+    }
+
+    pub fn services(guard: MockGuard, backend: Arc<MockBackend>) -> Services {
+        Services {
+            guard: Arc::new(guard),
+            programs: backend.clone(),
+            sessions: backend.clone(),
+            tasks: backend,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::mocks::{services, MockBackend, MockGuard};
+    use super::*;
+    use crate::commons::ids::UserId;
+    use crate::db_manager::{ReadWritePool, RequestConnection, DB_UNAVAILABLE};
+    use crate::loaders::Loaders;
+    use crate::feed_events::FeedEvents;
+    use crate::field_usage::{FieldCatalog, FieldUsage};
+    use crate::graphql_schema::{create_gq_schema, DBContext};
+    use crate::session_events::SessionEvents;
+    use crate::db_manager::MySqlConnectionPool;
+    use diesel::r2d2::{ConnectionManager, HandleError, Pool};
+    use juniper::Variables;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    // Never connects; the resolvers under test reach the database only through the services.
+    fn context(guard: MockGuard, backend: Arc<MockBackend>) -> DBContext {
+        let schema = create_gq_schema();
+        let pool = Pool::builder()
+            .min_idle(Some(0))
+            .connection_timeout(Duration::from_millis(200))
+            .build_unchecked(ConnectionManager::new("mysql://nobody@127.0.0.1:1/none"));
+
+        DBContext {
+            db: ReadWritePool::new(pool, None),
+            reads_replica: false,
+            usage: FieldUsage::new(FieldCatalog::of(&schema)),
+            events: SessionEvents::new(1, Duration::from_secs(1)),
+            feeds: FeedEvents::default(),
+            services: services(guard, backend),
+            caller: Some(UserId::from("u-1")),
+            connection: RequestConnection::default(),
+            loaders: Loaders::default(),
+            span: tracing::Span::none(),
+        }
+    }
+
+    fn run(query: &str, context: &DBContext) -> serde_json::Value {
+        let schema = create_gq_schema();
+        let (value, errors) = juniper::execute(query, None, &schema, &Variables::new(), context).unwrap();
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        serde_json::to_value(&value).unwrap()
+    }
+
+    #[test]
+    fn should_stop_a_guarded_mutation_before_the_service() {
+        let backend = Arc::new(MockBackend::default());
+        let context = context(MockGuard { allows: false }, backend.clone());
+
+        let result = run(r#"mutation { alterCoachTaskState(request: {id: "t-1", targetState: DONE}) { errors { message } } }"#, &context);
+
+        assert_eq!("You are not allowed to do this.", result["alterCoachTaskState"]["errors"][0]["message"]);
+
+        let result = run(r#"mutation { alterSessionState(request: {id: "s-1", targetState: CANCEL}) { errors { message } } }"#, &context);
+        assert_eq!("You are not allowed to do this.", result["alterSessionState"]["errors"][0]["message"]);
+
+        assert!(backend.calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_answer_from_the_services() {
+        let backend = Arc::new(MockBackend::default());
+        let context = context(MockGuard { allows: true }, backend.clone());
+
+        let result = run(r#"{ getTasks(criteria: {enrollmentId: "e-1"}) { tasks { id } error { message } } }"#, &context);
+        assert_eq!(0, result["getTasks"]["tasks"].as_array().unwrap().len());
+
+        let result = run(r#"mutation { alterCoachTaskState(request: {id: "t-1", targetState: DONE}) { errors { message } } }"#, &context);
+        assert_eq!("Not available in the test.", result["alterCoachTaskState"]["errors"][0]["message"]);
+
+        assert_eq!(vec!["get_tasks", "change_coach_task_state"], *backend.calls.lock().unwrap());
+    }
+
+    #[test]
+    fn should_answer_unavailable_without_the_database() {
+        let context = context(MockGuard { allows: true }, Arc::new(MockBackend::default()));
+
+        let result = run(r#"{ getWebhooks(coachId: "c-1") { error { message } } }"#, &context);
+        assert_eq!(DB_UNAVAILABLE, result["getWebhooks"]["error"]["message"]);
+
+        let result = run(r#"mutation { deleteWebhook(criteria: {coachId: "c-1", id: "w-1"}) { errors { message } } }"#, &context);
+        assert_eq!(DB_UNAVAILABLE, result["deleteWebhook"]["errors"][0]["message"]);
+    }
+
+    // Counts the tries of a pool to connect, none of which get through.
+    #[derive(Debug, Default, Clone)]
+    struct Tries(Arc<AtomicUsize>);
+
+    impl HandleError<diesel::r2d2::Error> for Tries {
+        fn handle_error(&self, _error: diesel::r2d2::Error) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl Tries {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+
+        fn pool(&self) -> MySqlConnectionPool {
+            Pool::builder()
+                .min_idle(Some(0))
+                .connection_timeout(Duration::from_millis(500))
+                .error_handler(Box::new(self.clone()))
+                .build_unchecked(ConnectionManager::new("mysql://nobody@127.0.0.1:1/none"))
+        }
+    }
+
+    #[test]
+    fn should_read_the_queries_from_the_replica() {
+        let (primary, replica) = (Tries::default(), Tries::default());
+
+        let mut context = context(MockGuard { allows: true }, Arc::new(MockBackend::default()));
+        context.db = ReadWritePool::new(primary.pool(), Some(replica.pool()));
+        context.services = Services::backed_by_diesel();
+
+        let result = run(r#"{ getPrograms(criteria: {userId: "u-1", programId: "", desire: YOURS}) { error { message } } }"#, &context.clone().on_replica());
+        assert_eq!(DB_UNAVAILABLE, result["getPrograms"]["error"]["message"]);
+        assert!(replica.count() > 0);
+        assert_eq!(0, primary.count());
+
+        let result = run(r#"mutation { createProgram(newProgramRequest: {name: "Rust", coachId: "c-1", description: "Rust", isPrivate: false}) { errors { message } } }"#, &context);
+        assert_eq!(DB_UNAVAILABLE, result["createProgram"]["errors"][0]["message"]);
+        assert!(primary.count() > 0);
+    }
+}
@@ -0,0 +1,114 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::fee_schedules::{schedule_at, split, EarningLine, EarningsCriteria, EarningsStatement, FeeRule, FeeSchedule, FeeScheduleView, NewFeeScheduleRequest};
+
+use crate::services::admin::admin_of;
+use crate::services::programs;
+
+use crate::schema::fee_rules;
+use crate::schema::fee_schedules;
+
+const SCHEDULE_FETCH_ERROR: &str = "Unable to fetch the fee schedules.";
+const PUBLISH_ERROR: &str = "Unable to publish the fee schedule.";
+const SCHEDULE_BEHIND: &str = "A schedule should take effect after the latest one.";
+const NOT_THE_COACH: &str = "The program is not coached by the given coach.";
+
+/**
+ * A schedule takes effect after the latest one, so the fees of the charges already
+ * made never change.
+ */
+pub fn publish_fee_schedule(connection: &MysqlConnection, caller: Option<&UserId>, request: &NewFeeScheduleRequest) -> Result<FeeScheduleView, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let (schedule, rules) = request.to_rows(&admin.id);
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let latest: Option<chrono::NaiveDateTime> = fee_schedules::table
+            .select(fee_schedules::effective_from)
+            .order_by(fee_schedules::effective_from.desc())
+            .first(connection)
+            .optional()?;
+
+        if latest.map(|value| value >= schedule.effective_from).unwrap_or(false) {
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+
+        diesel::insert_into(fee_schedules::table).values(&schedule).execute(connection)?;
+        diesel::insert_into(fee_rules::table).values(&rules).execute(connection)?;
+
+        Ok(())
+    });
+
+    match result {
+        Ok(_) => {}
+        Err(diesel::result::Error::RollbackTransaction) => return Err(SCHEDULE_BEHIND),
+        Err(_) => return Err(PUBLISH_ERROR),
+    }
+
+    let views = load_schedules(connection).map_err(|_| SCHEDULE_FETCH_ERROR)?;
+
+    views.into_iter().find(|view| view.schedule.id == schedule.id).ok_or(SCHEDULE_FETCH_ERROR)
+}
+
+pub fn get_fee_schedules(connection: &MysqlConnection) -> Result<Vec<FeeScheduleView>, &'static str> {
+    load_schedules(connection).map_err(|_| SCHEDULE_FETCH_ERROR)
+}
+
+/**
+ * Each charge bears the fee of the schedule in effect when it was made, for the genre of the program.
+ */
+pub fn compute_earnings(connection: &MysqlConnection, criteria: &EarningsCriteria) -> Result<EarningsStatement, &'static str> {
+    criteria.validate()?;
+
+    let program = programs::find(connection, &criteria.program_id)?;
+    if program.coach_id != criteria.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    let schedules = load_schedules(connection).map_err(|_| SCHEDULE_FETCH_ERROR)?;
+
+    let lines = criteria
+        .charges
+        .iter()
+        .map(|charge| {
+            let charged_at = util::as_date(charge.charged_at.as_str());
+            let schedule = schedule_at(&schedules, charged_at);
+            let fee_bps = schedule.map(|view| view.fee_for(program.genre_id.as_deref())).unwrap_or(0);
+            let (fee, share) = split(charge.amount_cents as i64, fee_bps);
+
+            EarningLine {
+                charged_at,
+                amount_cents: charge.amount_cents,
+                schedule_id: schedule.map(|view| view.schedule.id.to_owned()),
+                fee_bps,
+                fee_cents: fee as i32,
+                share_cents: share as i32,
+            }
+        })
+        .collect();
+
+    Ok(EarningsStatement { program_id: program.id.to_string(), lines })
+}
+
+// The schedules ordered by effective_from, with their rules.
+pub fn load_schedules(connection: &MysqlConnection) -> QueryResult<Vec<FeeScheduleView>> {
+    let schedules: Vec<FeeSchedule> = fee_schedules::table.order_by(fee_schedules::effective_from.asc()).load(connection)?;
+
+    let ids: Vec<&str> = schedules.iter().map(|schedule| schedule.id.as_str()).collect();
+    let rules: Vec<FeeRule> = fee_rules::table
+        .filter(fee_rules::schedule_id.eq_any(ids))
+        .select((fee_rules::schedule_id, fee_rules::genre_id, fee_rules::fee_bps))
+        .load(connection)?;
+
+    let mut views: Vec<FeeScheduleView> = schedules.into_iter().map(|schedule| FeeScheduleView { schedule, rules: vec![] }).collect();
+
+    for rule in rules {
+        if let Some(view) = views.iter_mut().find(|view| view.schedule.id == rule.schedule_id) {
+            view.rules.push(rule);
+        }
+    }
+
+    Ok(views)
+}
@@ -0,0 +1,99 @@
+use chrono::{Duration, NaiveDate};
+use diesel::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::field_usage::FieldCatalog;
+use crate::models::field_usage::{FieldReport, FieldUsageCriteria, NewFieldUsage};
+
+use crate::services::admin::admin_of;
+
+use crate::schema::field_usage;
+
+const SAVE_ERROR: &str = "Unable to save the field usage.";
+const REPORT_ERROR: &str = "Unable to prepare the field usage report.";
+
+// The hits of a field, the operations that asked for it and the last day it was asked for.
+type Usage = (i64, BTreeSet<String>, Option<NaiveDate>);
+
+/**
+ * Adds the hits to the counts of the day; a count is created on its first hits.
+ */
+pub fn save_counts(connection: &MysqlConnection, rows: &[NewFieldUsage]) -> Result<usize, &'static str> {
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        for row in rows {
+            let inserted = diesel::insert_or_ignore_into(field_usage::table).values(row).execute(connection)?;
+
+            if inserted == 0 {
+                let the_count = field_usage::table
+                    .filter(field_usage::day.eq(row.day))
+                    .filter(field_usage::operation_name.eq(row.operation_name.as_str()))
+                    .filter(field_usage::type_name.eq(row.type_name.as_str()))
+                    .filter(field_usage::field_name.eq(row.field_name.as_str()));
+
+                diesel::update(the_count).set(field_usage::hits.eq(field_usage::hits + row.hits)).execute(connection)?;
+            }
+        }
+
+        Ok(rows.len())
+    });
+
+    result.map_err(|_| SAVE_ERROR)
+}
+
+/**
+ * Every field of the schema with its hits over the days, the deprecated ones
+ * first and then the least used.
+ */
+pub fn get_usage_report(connection: &MysqlConnection, caller: Option<&UserId>, catalog: &FieldCatalog, criteria: &FieldUsageCriteria) -> Result<Vec<FieldReport>, &'static str> {
+    admin_of(connection, caller)?;
+    criteria.validate()?;
+
+    let since = util::now().date() - Duration::days(criteria.days as i64 - 1);
+
+    let counts: Vec<(NaiveDate, String, String, String, i64)> = field_usage::table
+        .filter(field_usage::day.ge(since))
+        .select((field_usage::day, field_usage::operation_name, field_usage::type_name, field_usage::field_name, field_usage::hits))
+        .load(connection)
+        .map_err(|_| REPORT_ERROR)?;
+
+    let mut usage_of: HashMap<(String, String), Usage> = HashMap::new();
+    for (day, operation_name, type_name, field_name, hits) in counts {
+        let usage = usage_of.entry((type_name, field_name)).or_default();
+        usage.0 += hits;
+        usage.1.insert(operation_name);
+        usage.2 = usage.2.max(Some(day));
+    }
+
+    let mut reports: Vec<FieldReport> = Vec::new();
+    for (type_name, fields) in &catalog.types {
+        for field in fields {
+            if criteria.deprecated_only && field.deprecation.is_none() {
+                continue;
+            }
+
+            let (hits, operations, last_used) = usage_of.remove(&(type_name.to_owned(), field.name.to_owned())).unwrap_or_default();
+
+            reports.push(FieldReport {
+                type_name: type_name.to_owned(),
+                field_name: field.name.to_owned(),
+                deprecation: field.deprecation.clone(),
+                hits,
+                operations: operations.into_iter().collect(),
+                last_used,
+            });
+        }
+    }
+
+    reports.sort_by(|a, b| {
+        b.deprecation
+            .is_some()
+            .cmp(&a.deprecation.is_some())
+            .then(a.hits.cmp(&b.hits))
+            .then(a.type_name.cmp(&b.type_name))
+            .then(a.field_name.cmp(&b.field_name))
+    });
+
+    Ok(reports)
+}
@@ -0,0 +1,66 @@
+use chrono::Duration;
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::file_access_log::{path_pattern, FileAccess, FileAccessCriteria, NewFileAccess, LOG_LIMIT, REPEAT_SECS};
+
+use crate::services::admin::admin_of;
+
+use crate::schema::file_access_log;
+
+const LOG_ERROR: &str = "Unable to record the access to the file.";
+const QUERY_ERROR: &str = "Unable to fetch the file access log.";
+
+/**
+ * A download of the same file by the same accessor from the same address within
+ * REPEAT_SECS of the last one recorded is taken as a part of it, like the ranges
+ * of a video being watched, and is not recorded again.
+ */
+pub fn record_access(connection: &MysqlConnection, access: &NewFileAccess) -> Result<usize, &'static str> {
+    let mut recent = file_access_log::table
+        .filter(file_access_log::file_path.eq(access.file_path.as_str()))
+        .filter(file_access_log::accessed_at.ge(util::now() - Duration::seconds(REPEAT_SECS)))
+        .into_boxed();
+
+    recent = match &access.accessor_id {
+        Some(value) => recent.filter(file_access_log::accessor_id.eq(value.as_str())),
+        None => recent.filter(file_access_log::accessor_id.is_null()),
+    };
+
+    recent = match &access.ip {
+        Some(value) => recent.filter(file_access_log::ip.eq(value.as_str())),
+        None => recent.filter(file_access_log::ip.is_null()),
+    };
+
+    let repeats: i64 = recent.count().get_result(connection).map_err(|_| LOG_ERROR)?;
+    if repeats > 0 {
+        return Ok(0);
+    }
+
+    diesel::insert_into(file_access_log::table).values(access).execute(connection).map_err(|_| LOG_ERROR)
+}
+
+/**
+ * The latest accesses first.
+ */
+pub fn get_file_access_log(connection: &MysqlConnection, caller: Option<&UserId>, criteria: &FileAccessCriteria) -> Result<Vec<FileAccess>, &'static str> {
+    admin_of(connection, caller)?;
+    criteria.validate()?;
+
+    let mut query = file_access_log::table.into_boxed();
+
+    if let Some(path) = criteria.file_path() {
+        query = query.filter(file_access_log::file_path.like(path_pattern(path)));
+    }
+
+    if let Some(the_user_id) = criteria.user_id() {
+        query = query.filter(file_access_log::accessor_id.eq(the_user_id.to_owned()));
+    }
+
+    query
+        .order_by(file_access_log::accessed_at.desc())
+        .limit(LOG_LIMIT)
+        .load(connection)
+        .map_err(|_| QUERY_ERROR)
+}
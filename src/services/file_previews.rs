@@ -0,0 +1,34 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::models::file_previews::{is_previewable, preview_of, Preview};
+
+// The dimensions are in the head of an image; the pages are all over a PDF.
+const IMAGE_HEAD_BYTES: u64 = 1024 * 1024;
+const MAX_PDF_BYTES: u64 = 64 * 1024 * 1024;
+
+/**
+ * The preview of a stored file, read as it is attached. The larger PDFs are left
+ * without a page count rather than holding up the upload.
+ */
+pub fn preview(file_path: &str, mime_type: &str) -> Option<Preview> {
+    if !is_previewable(mime_type) {
+        return None;
+    }
+
+    let file = File::open(file_path).ok()?;
+
+    let limit = if mime_type.starts_with("image/") {
+        IMAGE_HEAD_BYTES
+    } else {
+        if file.metadata().ok()?.len() > MAX_PDF_BYTES {
+            return None;
+        }
+        MAX_PDF_BYTES
+    };
+
+    let mut bytes: Vec<u8> = Vec::new();
+    file.take(limit).read_to_end(&mut bytes).ok()?;
+
+    preview_of(mime_type, &bytes)
+}
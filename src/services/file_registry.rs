@@ -0,0 +1,71 @@
+use diesel::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::content_store::ContentStore;
+use crate::models::file_registry::NewRegisteredFile;
+
+use crate::schema::file_registry;
+
+const REGISTER_ERROR: &str = "Unable to register the uploaded file.";
+const SWEEP_ERROR: &str = "Unable to read the file registry.";
+
+/**
+ * The path now holds the content of the checksum; a path uploaded again is
+ * registered again.
+ */
+pub fn register(connection: &MysqlConnection, file_path: &str, checksum: &str, size: usize) -> Result<usize, &'static str> {
+    diesel::replace_into(file_registry::table)
+        .values(&NewRegisteredFile::of(file_path, checksum, size))
+        .execute(connection)
+        .map_err(|_| REGISTER_ERROR)
+}
+
+/**
+ * The "blob-sweep" job. The paths removed from the disk, by a merge of the
+ * sessions or by hand, leave the registry first; then the blobs none of the
+ * remaining paths refer to are removed.
+ */
+pub fn sweep_orphans(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let files: Vec<(String, String, String)> = file_registry::table
+        .select((file_registry::id, file_registry::file_path, file_registry::checksum))
+        .load(connection)
+        .map_err(|_| SWEEP_ERROR)?;
+
+    let (present, missing): (Vec<_>, Vec<_>) = files.into_iter().partition(|(_, file_path, _)| Path::new(file_path).is_file());
+
+    let missing_ids: Vec<String> = missing.into_iter().map(|(id, _, _)| id).collect();
+    if !missing_ids.is_empty() {
+        diesel::delete(file_registry::table.filter(file_registry::id.eq_any(&missing_ids)))
+            .execute(connection)
+            .map_err(|_| SWEEP_ERROR)?;
+    }
+
+    let referenced: HashSet<String> = present.into_iter().map(|(_, _, checksum)| checksum).collect();
+
+    let mut swept: usize = 0;
+
+    for orphan in ContentStore::local().orphans(&referenced) {
+        match fs::remove_file(&orphan) {
+            Ok(_) => swept += 1,
+            Err(e) => eprintln!("Unable to sweep the blob {:?}: {}", orphan, e),
+        }
+    }
+
+    Ok(swept)
+}
+
+/**
+ * For the anonymizer: the files lose their contents, so their checksums and
+ * blobs go too.
+ */
+pub fn forget_all(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let count = diesel::delete(file_registry::table).execute(connection).map_err(|_| REGISTER_ERROR)?;
+
+    if let Err(e) = ContentStore::local().clear() {
+        eprintln!("Unable to remove the blobs: {}", e);
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,210 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use crate::commons::ids::UserId;
+use crate::models::enrollments::Enrollment;
+use crate::models::goal_boards::{
+    GoalBoard, GoalBoardCriteria, GoalCard, GoalCardCriteria, GoalCardView, GoalComment, GoalCommentRequest, GoalStatus, NewGoalCard, NewGoalCardRequest, NewGoalComment,
+    UpdateGoalCardRequest,
+};
+use crate::models::notifications::{NewNotification, GOAL_BOARD};
+use crate::models::users::User;
+
+use crate::services::notifications::notify;
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::enrollments;
+use crate::schema::goal_cards;
+use crate::schema::goal_comments;
+
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const NOT_A_PARTY: &str = "Only the coach and the member of the enrollment may use its goal board.";
+const CARD_NOT_FOUND: &str = "Unable to find the goal card.";
+const CARD_SAVE_ERROR: &str = "Unable to save the goal card.";
+const CARD_DELETE_ERROR: &str = "Unable to delete the goal card.";
+const COMMENT_SAVE_ERROR: &str = "Unable to save the comment.";
+const BOARD_ERROR: &str = "Unable to fetch the goal board.";
+
+/**
+ * The actor and the other party of the enrollment, who is told of the changes.
+ */
+struct Parties {
+    actor: User,
+    other_id: UserId,
+}
+
+fn parties(connection: &MysqlConnection, the_enrollment_id: &str, the_actor_id: &UserId) -> Result<Parties, &'static str> {
+    let (member_id, the_coach_id) = member_and_coach(connection, the_enrollment_id)?;
+    let actor = users::find(connection, the_actor_id)?;
+
+    let other_id = if actor.id == member_id {
+        the_coach_id
+    } else if actor.id == the_coach_id {
+        member_id
+    } else {
+        return Err(NOT_A_PARTY);
+    };
+
+    Ok(Parties { actor, other_id })
+}
+
+/**
+ * The member and the coach of the enrollment, the ones sharing its board.
+ */
+pub fn member_and_coach(connection: &MysqlConnection, the_enrollment_id: &str) -> Result<(UserId, UserId), &'static str> {
+    let enrollment: Enrollment = enrollments::table
+        .filter(enrollments::id.eq(the_enrollment_id))
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    let program = programs::find(connection, &enrollment.program_id)?;
+
+    Ok((enrollment.member_id, program.coach_id))
+}
+
+pub fn create_card(connection: &MysqlConnection, request: &NewGoalCardRequest) -> Result<GoalCardView, &'static str> {
+    let parties = parties(connection, request.enrollment_id.as_str(), &request.actor_id)?;
+
+    let new_card = NewGoalCard::from(request);
+    let subject = format!("{} added the goal {}", parties.actor.full_name, new_card.title);
+    let notice = NewNotification::new(parties.other_id.as_str(), GOAL_BOARD, subject, new_card.enrollment_id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(goal_cards::table).values(&new_card).execute(connection)?;
+
+        notify(connection, &[notice])
+    });
+
+    if result.is_err() {
+        return Err(CARD_SAVE_ERROR);
+    }
+
+    card_view(connection, new_card.id.as_str())
+}
+
+/**
+ * The other party is told only when the goal is achieved.
+ */
+pub fn update_card(connection: &MysqlConnection, request: &UpdateGoalCardRequest) -> Result<GoalCardView, &'static str> {
+    let card = find_card(connection, request.id.as_str())?;
+    let parties = parties(connection, card.enrollment_id.as_str(), &request.actor_id)?;
+
+    let mut notices: Vec<NewNotification> = Vec::new();
+    if request.status == GoalStatus::ACHIEVED && card.status != GoalStatus::ACHIEVED.as_str() {
+        let subject = format!("{} achieved the goal {}", parties.actor.full_name, request.title.trim());
+        notices.push(NewNotification::new(parties.other_id.as_str(), GOAL_BOARD, subject, card.enrollment_id.as_str()));
+    }
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(goal_cards::table.filter(goal_cards::id.eq(card.id.as_str())))
+            .set((
+                goal_cards::column_name.eq(request.column_name.trim()),
+                goal_cards::position.eq(request.position),
+                goal_cards::title.eq(request.title.trim()),
+                goal_cards::description.eq(request.description.trim()),
+                goal_cards::status.eq(request.status.as_str()),
+            ))
+            .execute(connection)?;
+
+        notify(connection, &notices)
+    });
+
+    if result.is_err() {
+        return Err(CARD_SAVE_ERROR);
+    }
+
+    card_view(connection, card.id.as_str())
+}
+
+/**
+ * The comments of the card go along with it. The card is answered along with the
+ * count of the rows deleted, for the change to be pushed to its board.
+ */
+pub fn delete_card(connection: &MysqlConnection, criteria: &GoalCardCriteria) -> Result<(GoalCard, usize), &'static str> {
+    let card = find_card(connection, criteria.id.as_str())?;
+    parties(connection, card.enrollment_id.as_str(), &criteria.actor_id)?;
+
+    let rows = diesel::delete(goal_cards::table.filter(goal_cards::id.eq(card.id.as_str())))
+        .execute(connection)
+        .map_err(|_| CARD_DELETE_ERROR)?;
+
+    Ok((card, rows))
+}
+
+pub fn comment_card(connection: &MysqlConnection, request: &GoalCommentRequest) -> Result<GoalCardView, &'static str> {
+    let card = find_card(connection, request.card_id.as_str())?;
+    let parties = parties(connection, card.enrollment_id.as_str(), &request.actor_id)?;
+
+    let new_comment = NewGoalComment::from(request);
+    let subject = format!("{} commented on the goal {}", parties.actor.full_name, card.title);
+    let notice = NewNotification::new(parties.other_id.as_str(), GOAL_BOARD, subject, card.enrollment_id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(goal_comments::table).values(&new_comment).execute(connection)?;
+
+        notify(connection, &[notice])
+    });
+
+    if result.is_err() {
+        return Err(COMMENT_SAVE_ERROR);
+    }
+
+    card_view(connection, card.id.as_str())
+}
+
+pub fn get_goal_board(connection: &MysqlConnection, criteria: &GoalBoardCriteria) -> Result<GoalBoard, &'static str> {
+    parties(connection, criteria.enrollment_id.as_str(), &criteria.user_id)?;
+
+    board_of(connection, criteria.enrollment_id.as_str())
+}
+
+/**
+ * The board of the enrollment, for a caller already known to be one of its parties.
+ */
+pub fn board_of(connection: &MysqlConnection, the_enrollment_id: &str) -> Result<GoalBoard, &'static str> {
+    let cards: Vec<GoalCard> = goal_cards::table
+        .filter(goal_cards::enrollment_id.eq(the_enrollment_id))
+        .load(connection)
+        .map_err(|_| BOARD_ERROR)?;
+
+    let views = with_comments(connection, cards).map_err(|_| BOARD_ERROR)?;
+
+    Ok(GoalBoard::arrange(the_enrollment_id, views))
+}
+
+fn with_comments(connection: &MysqlConnection, cards: Vec<GoalCard>) -> QueryResult<Vec<GoalCardView>> {
+    let card_ids: Vec<&str> = cards.iter().map(|card| card.id.as_str()).collect();
+
+    let comments: Vec<GoalComment> = goal_comments::table
+        .filter(goal_comments::card_id.eq_any(&card_ids))
+        .order_by(goal_comments::created_at.asc())
+        .load(connection)?;
+
+    let mut comments_of: HashMap<String, Vec<GoalComment>> = HashMap::new();
+    for comment in comments {
+        comments_of.entry(comment.card_id.to_owned()).or_default().push(comment);
+    }
+
+    let views = cards
+        .into_iter()
+        .map(|card| {
+            let comments = comments_of.remove(&card.id).unwrap_or_default();
+            GoalCardView { card, comments }
+        })
+        .collect();
+
+    Ok(views)
+}
+
+fn find_card(connection: &MysqlConnection, the_id: &str) -> Result<GoalCard, &'static str> {
+    goal_cards::table.filter(goal_cards::id.eq(the_id)).first(connection).map_err(|_| CARD_NOT_FOUND)
+}
+
+fn card_view(connection: &MysqlConnection, the_id: &str) -> Result<GoalCardView, &'static str> {
+    let card = find_card(connection, the_id)?;
+
+    let mut views = with_comments(connection, vec![card]).map_err(|_| CARD_NOT_FOUND)?;
+
+    views.pop().ok_or(CARD_NOT_FOUND)
+}
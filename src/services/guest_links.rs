@@ -0,0 +1,111 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::SessionId;
+use crate::commons::util;
+use crate::file_manager::get_file_names;
+
+use crate::models::guest_links::{GuestAccess, GuestLink, GuestLinkCriteria, NewGuestLink, NewGuestLinkRequest, RevokeGuestLinkRequest};
+use crate::models::user_artifacts::board_dir;
+
+use crate::services::sessions::find;
+
+use crate::schema::guest_links;
+use crate::schema::guest_links::dsl::*;
+use crate::schema::session_users;
+
+const NOT_SESSION_COACH: &str = "Only the coach of the session may share it with a guest.";
+const SESSION_CLOSED: &str = "The session is either cancelled or completed. Hence it cannot be shared.";
+const LINK_NOT_FOUND: &str = "Unable to find the guest link.";
+const LINK_INACTIVE: &str = "The guest link is either expired or revoked.";
+const LINK_SAVE_ERROR: &str = "Unable to create the guest link.";
+const LINK_REVOKE_ERROR: &str = "Unable to revoke the guest link.";
+
+pub fn create_guest_link(connection: &MysqlConnection, request: &NewGuestLinkRequest) -> Result<GuestLink, &'static str> {
+    is_session_coach(connection, request.session_id.as_str(), request.coach_id.as_str())?;
+
+    let session = find(connection, &request.session_id)?;
+
+    if session.cancelled_at.is_some() || session.actual_end_date.is_some() {
+        return Err(SESSION_CLOSED);
+    }
+
+    let new_link = NewGuestLink::from(request);
+
+    let result = diesel::insert_into(guest_links).values(&new_link).execute(connection);
+
+    if result.is_err() {
+        return Err(LINK_SAVE_ERROR);
+    }
+
+    find_link(connection, new_link.id.as_str())
+}
+
+pub fn revoke_guest_link(connection: &MysqlConnection, request: &RevokeGuestLinkRequest) -> Result<GuestLink, &'static str> {
+    let link = find_link(connection, request.id.as_str())?;
+
+    is_session_coach(connection, link.session_id.as_str(), request.coach_id.as_str())?;
+
+    let result = diesel::update(guest_links.filter(guest_links::id.eq(link.id.as_str())))
+        .set(revoked_at.eq(util::now()))
+        .execute(connection);
+
+    if result.is_err() {
+        return Err(LINK_REVOKE_ERROR);
+    }
+
+    find_link(connection, link.id.as_str())
+}
+
+pub fn get_guest_links(connection: &MysqlConnection, criteria: &GuestLinkCriteria) -> Result<Vec<GuestLink>, &'static str> {
+    is_session_coach(connection, criteria.session_id.as_str(), criteria.coach_id.as_str())?;
+
+    guest_links
+        .filter(session_id.eq(criteria.session_id.as_str()))
+        .order_by(created_at.desc())
+        .load(connection)
+        .map_err(|_| LINK_NOT_FOUND)
+}
+
+/**
+ * The guest holds nothing but the token; an unknown token and an inactive
+ * link are reported alike, so that the tokens cannot be probed.
+ */
+pub fn redeem_guest_link(connection: &MysqlConnection, the_token: &str) -> Result<GuestAccess, &'static str> {
+    let link: GuestLink = guest_links.filter(token.eq(the_token)).first(connection).map_err(|_| LINK_INACTIVE)?;
+
+    if !link.is_active() {
+        return Err(LINK_INACTIVE);
+    }
+
+    let session = find(connection, &SessionId::from(link.session_id.as_str()))?;
+
+    if session.cancelled_at.is_some() || session.actual_end_date.is_some() {
+        return Err(SESSION_CLOSED);
+    }
+
+    let board_urls = get_file_names(board_dir(&session)).unwrap_or_default();
+
+    Ok(GuestAccess {
+        guest_name: link.guest_name,
+        session,
+        board_urls,
+    })
+}
+
+fn find_link(connection: &MysqlConnection, the_id: &str) -> Result<GuestLink, &'static str> {
+    guest_links.filter(guest_links::id.eq(the_id)).first(connection).map_err(|_| LINK_NOT_FOUND)
+}
+
+fn is_session_coach(connection: &MysqlConnection, the_session_id: &str, the_coach_id: &str) -> Result<(), &'static str> {
+    let result: QueryResult<i64> = session_users::table
+        .filter(session_users::session_id.eq(the_session_id))
+        .filter(session_users::user_id.eq(the_coach_id))
+        .filter(session_users::user_type.eq(util::COACH))
+        .count()
+        .get_result(connection);
+
+    match result {
+        Ok(count) if count > 0 => Ok(()),
+        _ => Err(NOT_SESSION_COACH),
+    }
+}
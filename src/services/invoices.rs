@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::correspondences::{MailOut, MailRecipient};
+use crate::models::fee_schedules::{schedule_at, split, FeeScheduleView};
+use crate::models::invoices::{invoice_month, signed_link, Invoice, InvoiceLine, InvoiceStatement, MAIL_LINK_HOURS};
+use crate::models::payments::{Payment, PaymentStatus};
+use crate::models::programs::Program;
+use crate::models::users::User;
+
+use crate::services::correspondences::queue_mails;
+use crate::services::fee_schedules::load_schedules;
+use crate::services::users;
+
+use crate::schema::enrollments;
+use crate::schema::invoices;
+use crate::schema::payments;
+use crate::schema::programs;
+
+use crate::storage::{storage, Area};
+
+const PAYMENTS_FETCH_ERROR: &str = "Unable to fetch the paid enrollments of the month.";
+const SCHEDULE_FETCH_ERROR: &str = "Unable to fetch the fee schedules.";
+const INVOICES_FETCH_ERROR: &str = "Unable to fetch the invoices.";
+const INVOICE_NOT_FOUND: &str = "Unable to find the invoice.";
+const INVOICE_WRITE_ERROR: &str = "Unable to write the PDF of the invoice.";
+const INVOICE_SAVE_ERROR: &str = "Unable to record the invoice.";
+
+// A payment with its program, the member who enrolled and the time of the enrollment.
+type PaidLine = (Payment, Program, String, NaiveDateTime);
+
+/**
+ * Invoices every coach for the enrollments paid for in the month before, once
+ * a currency. A coach already invoiced for the month is left alone, so the job
+ * may run as often as it likes and on as many instances.
+ */
+pub fn issue_invoices(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let (period_start, period_end) = invoice_month(util::now());
+
+    let invoiced: Vec<(String, String)> = invoices::table
+        .filter(invoices::period_start.eq(period_start))
+        .select((invoices::coach_id, invoices::currency))
+        .load(connection)
+        .map_err(|_| INVOICES_FETCH_ERROR)?;
+
+    let paid: Vec<PaidLine> = payments::table
+        .inner_join(programs::table)
+        .inner_join(enrollments::table)
+        .filter(payments::status.eq(PaymentStatus::CONFIRMED.as_str()))
+        .filter(enrollments::created_at.ge(period_start))
+        .filter(enrollments::created_at.lt(period_end))
+        .select((payments::all_columns, programs::all_columns, enrollments::member_id, enrollments::created_at))
+        .order_by(enrollments::created_at.asc())
+        .load(connection)
+        .map_err(|_| PAYMENTS_FETCH_ERROR)?;
+
+    let schedules = load_schedules(connection).map_err(|_| SCHEDULE_FETCH_ERROR)?;
+
+    let mut due: BTreeMap<(String, String), Vec<PaidLine>> = BTreeMap::new();
+    for row in paid {
+        let key = (row.1.coach_id.to_string(), row.0.currency.to_owned());
+        if !invoiced.contains(&key) {
+            due.entry(key).or_default().push(row);
+        }
+    }
+
+    let mut issued = 0;
+
+    for ((coach_id, currency), rows) in due {
+        match issue(connection, &schedules, coach_id.as_str(), currency.as_str(), period_start, rows) {
+            Ok(count) => issued += count,
+            Err(e) => eprintln!("Unable to invoice the coach {} in {}: {}", coach_id, currency, e),
+        }
+    }
+
+    Ok(issued)
+}
+
+/**
+ * The invoice is recorded before its mail is queued, in one transaction, so that
+ * the coach is mailed once for the month. The PDF is written ahead, to a path of
+ * the coach, the month and the currency, which a second run writes over alike.
+ */
+fn issue(
+    connection: &MysqlConnection,
+    schedules: &[FeeScheduleView],
+    coach_id: &str,
+    currency: &str,
+    period_start: NaiveDateTime,
+    rows: Vec<PaidLine>,
+) -> Result<usize, &'static str> {
+    let coach = users::find_any(connection, &UserId::from(coach_id))?;
+
+    let mut lines: Vec<InvoiceLine> = Vec::new();
+    for (payment, program, member_id, enrolled_at) in rows {
+        let member_name = users::find_any(connection, &UserId::from(member_id.as_str())).map(|member| member.full_name).unwrap_or(member_id);
+        let fee_bps = schedule_at(schedules, enrolled_at).map(|view| view.fee_for(program.genre_id.as_deref())).unwrap_or(0);
+        let (fee_cents, _) = split(payment.amount_cents as i64, fee_bps);
+
+        lines.push(InvoiceLine {
+            enrolled_at,
+            program_name: program.name,
+            member_name,
+            amount_cents: payment.amount_cents as i64,
+            fee_cents,
+        });
+    }
+
+    let statement = InvoiceStatement {
+        coach_name: coach.full_name.to_owned(),
+        period_start,
+        currency: currency.to_owned(),
+        lines,
+    };
+
+    let file_path = write_pdf(&coach, &statement)?;
+    let row = statement.to_row(coach_id, file_path.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        if diesel::insert_or_ignore_into(invoices::table).values(&row).execute(connection)? == 0 {
+            return Ok(0);
+        }
+
+        let link = signed_link(row.id.as_str(), util::now() + Duration::hours(MAIL_LINK_HOURS));
+        let mail_out = MailOut::for_invoice(&coach, &statement, link);
+        let recipients = MailRecipient::build_to(&[&coach], mail_out.id.as_str());
+
+        queue_mails(connection, vec![(mail_out, recipients)]).map_err(|_| diesel::result::Error::RollbackTransaction)?;
+
+        Ok(1)
+    });
+
+    result.map_err(|_| INVOICE_SAVE_ERROR)
+}
+
+fn write_pdf(coach: &User, statement: &InvoiceStatement) -> Result<String, &'static str> {
+    let dir = storage().dir(Area::Platform).join("invoices").join(coach.id.as_str());
+    fs::create_dir_all(&dir).map_err(|_| INVOICE_WRITE_ERROR)?;
+
+    let path = dir.join(format!("{}-{}.pdf", statement.period_start.format("%Y-%m"), statement.currency));
+    fs::write(&path, statement.to_pdf()).map_err(|_| INVOICE_WRITE_ERROR)?;
+    storage().keep(&path).map_err(|_| INVOICE_WRITE_ERROR)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/**
+ * The invoices of the coach, the latest month first.
+ */
+pub fn get_invoices(connection: &MysqlConnection, the_coach_id: &str) -> Result<Vec<Invoice>, &'static str> {
+    invoices::table
+        .filter(invoices::coach_id.eq(the_coach_id))
+        .order_by((invoices::period_start.desc(), invoices::currency.asc()))
+        .load(connection)
+        .map_err(|_| INVOICES_FETCH_ERROR)
+}
+
+pub fn find(connection: &MysqlConnection, the_invoice_id: &str) -> Result<Invoice, &'static str> {
+    invoices::table.filter(invoices::id.eq(the_invoice_id)).first(connection).map_err(|_| INVOICE_NOT_FOUND)
+}
@@ -0,0 +1,216 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::commons::util;
+use crate::models::enrollments::Enrollment;
+use crate::models::late_policies::{next_extension, AbsenceRequest, ExtensionCriteria, LatePolicy, LatePolicyRequest, LateTask, MemberAbsence, TaskExtension, GRACE};
+use crate::models::notifications::{NewNotification, TASK_EXTENDED};
+
+use crate::services::notifications::notify;
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::enrollments;
+use crate::schema::late_policies;
+use crate::schema::member_absences;
+use crate::schema::programs as programs_table;
+use crate::schema::task_extensions;
+use crate::schema::tasks;
+
+const BATCH_SIZE: i64 = 200;
+
+const NOT_THE_COACH: &str = "Only the coach of the program may change its late policy.";
+const NOT_A_COACH_OF_MEMBER: &str = "Only a coach of the member may record an absence.";
+const NOT_A_PARTY: &str = "Only the coach and the member of the enrollment may see the extensions of its tasks.";
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const POLICY_SAVE_ERROR: &str = "Unable to save the late policy.";
+const POLICY_FETCH_ERROR: &str = "Unable to fetch the late policy.";
+const ABSENCE_SAVE_ERROR: &str = "Unable to record the absence.";
+const EXTENSIONS_ERROR: &str = "Unable to fetch the extensions of the tasks.";
+const LATE_TASKS_ERROR: &str = "Unable to read the late tasks.";
+
+pub fn save_late_policy(connection: &MysqlConnection, request: &LatePolicyRequest) -> Result<LatePolicy, &'static str> {
+    let program = programs::find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    diesel::replace_into(late_policies::table)
+        .values(&request.to_row())
+        .execute(connection)
+        .map_err(|_| POLICY_SAVE_ERROR)?;
+
+    get_late_policy(connection, program.id.as_str())
+}
+
+pub fn get_late_policy(connection: &MysqlConnection, the_program_id: &str) -> Result<LatePolicy, &'static str> {
+    let policy: Option<LatePolicy> = late_policies::table
+        .filter(late_policies::program_id.eq(the_program_id))
+        .select((late_policies::program_id, late_policies::grace_days, late_policies::extend_on_absence))
+        .first(connection)
+        .optional()
+        .map_err(|_| POLICY_FETCH_ERROR)?;
+
+    Ok(policy.unwrap_or_else(|| LatePolicy::none(the_program_id)))
+}
+
+/**
+ * A coach records the absence of a member of any of their programs; it counts for
+ * the tasks of all the enrollments of the member.
+ */
+pub fn record_absence(connection: &MysqlConnection, request: &AbsenceRequest) -> Result<MemberAbsence, &'static str> {
+    let member = users::find(connection, &request.member_id)?;
+    let (from_date, to_date) = request.range()?;
+
+    let coached: i64 = enrollments::table
+        .inner_join(programs_table::table)
+        .filter(enrollments::member_id.eq(member.id.as_str()))
+        .filter(programs_table::coach_id.eq(request.coach_id.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| ABSENCE_SAVE_ERROR)?;
+
+    if coached == 0 {
+        return Err(NOT_A_COACH_OF_MEMBER);
+    }
+
+    let absence = request.to_row(from_date, to_date);
+
+    diesel::insert_into(member_absences::table).values(&absence).execute(connection).map_err(|_| ABSENCE_SAVE_ERROR)?;
+
+    member_absences::table
+        .filter(member_absences::id.eq(absence.id.as_str()))
+        .first(connection)
+        .map_err(|_| ABSENCE_SAVE_ERROR)
+}
+
+pub fn get_task_extensions(connection: &MysqlConnection, criteria: &ExtensionCriteria) -> Result<Vec<TaskExtension>, &'static str> {
+    let enrollment: Enrollment = enrollments::table
+        .filter(enrollments::id.eq(criteria.enrollment_id.as_str()))
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    let program = programs::find(connection, &enrollment.program_id)?;
+
+    if criteria.user_id != enrollment.member_id && criteria.user_id != program.coach_id {
+        return Err(NOT_A_PARTY);
+    }
+
+    task_extensions::table
+        .inner_join(tasks::table)
+        .filter(tasks::enrollment_id.eq(enrollment.id.as_str()))
+        .select(task_extensions::all_columns)
+        .order_by(task_extensions::created_at.desc())
+        .load(connection)
+        .map_err(|_| EXTENSIONS_ERROR)
+}
+
+type LateRow = (
+    String,
+    String,
+    NaiveDateTime,
+    NaiveDateTime,
+    Option<NaiveDateTime>,
+    Option<NaiveDateTime>,
+    String,
+    String,
+    String,
+    i32,
+    bool,
+);
+
+/**
+ * The "late-policy" job. The tasks past their end date without a response, in the
+ * programs with a late policy, are given their next extension; both the coach and
+ * the member are told. A task is extended once per run, and is left out once it
+ * had its grace, which comes last. The latest deadlines are taken first.
+ */
+pub fn extend_late_tasks(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let now = util::now();
+
+    let rows: Vec<LateRow> = tasks::table
+        .inner_join(enrollments::table.inner_join(programs_table::table.inner_join(late_policies::table)))
+        .filter(tasks::cancelled_at.is_null())
+        .filter(tasks::actual_end_date.is_null())
+        .filter(tasks::responded_date.is_null())
+        .filter(tasks::revised_end_date.lt(now).or(tasks::revised_end_date.is_null().and(tasks::original_end_date.lt(now))))
+        .filter(late_policies::grace_days.gt(0).or(late_policies::extend_on_absence.eq(true)))
+        .filter(tasks::id.ne_all(task_extensions::table.filter(task_extensions::reason.eq(GRACE)).select(task_extensions::task_id)))
+        .select((
+            tasks::id,
+            tasks::name,
+            tasks::original_start_date,
+            tasks::original_end_date,
+            tasks::revised_start_date,
+            tasks::revised_end_date,
+            enrollments::member_id,
+            programs_table::coach_id,
+            late_policies::program_id,
+            late_policies::grace_days,
+            late_policies::extend_on_absence,
+        ))
+        .order_by(tasks::original_end_date.desc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| LATE_TASKS_ERROR)?;
+
+    let task_ids: Vec<&str> = rows.iter().map(|row| row.0.as_str()).collect();
+    let applied: Vec<TaskExtension> = task_extensions::table
+        .filter(task_extensions::task_id.eq_any(task_ids))
+        .load(connection)
+        .map_err(|_| LATE_TASKS_ERROR)?;
+
+    let member_ids: Vec<&str> = rows.iter().map(|row| row.6.as_str()).collect();
+    let absences: Vec<MemberAbsence> = member_absences::table
+        .filter(member_absences::member_id.eq_any(member_ids))
+        .order_by(member_absences::from_date.asc())
+        .load(connection)
+        .map_err(|_| LATE_TASKS_ERROR)?;
+
+    let mut extended = 0;
+
+    for (task_id, name, original_start, original_end, revised_start, revised_end, member_id, coach_id, program_id, grace_days, extend_on_absence) in &rows {
+        let policy = LatePolicy {
+            program_id: program_id.to_owned(),
+            grace_days: *grace_days,
+            extend_on_absence: *extend_on_absence,
+        };
+
+        let task = LateTask {
+            task_id: task_id.as_str(),
+            start_date: revised_start.unwrap_or(*original_start),
+            end_date: revised_end.unwrap_or(*original_end),
+            applied: applied.iter().filter(|extension| extension.task_id == *task_id).collect(),
+        };
+
+        let of_member: Vec<&MemberAbsence> = absences.iter().filter(|absence| absence.member_id == *member_id).collect();
+
+        let extension = match next_extension(&policy, &task, &of_member) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        let subject = format!("The task {} is extended to {} ({})", name, extension.new_end_date.format("%Y-%m-%d"), extension.reason.to_lowercase());
+        let notices = vec![
+            NewNotification::new(member_id.as_str(), TASK_EXTENDED, subject.to_owned(), task_id.as_str()),
+            NewNotification::new(coach_id.as_str(), TASK_EXTENDED, subject, task_id.as_str()),
+        ];
+
+        let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::update(tasks::table.filter(tasks::id.eq(task_id.as_str())))
+                .set(tasks::revised_end_date.eq(extension.new_end_date))
+                .execute(connection)?;
+            diesel::insert_into(task_extensions::table).values(&extension).execute(connection)?;
+
+            notify(connection, &notices)
+        });
+
+        match result {
+            Ok(_) => extended += 1,
+            Err(e) => eprintln!("Unable to extend the task {}: {}", task_id, e),
+        }
+    }
+
+    Ok(extended)
+}
@@ -0,0 +1,82 @@
+use diesel::dsl::count;
+use diesel::prelude::*;
+
+use crate::models::mail_bounces::{MailEvent, NewMailBounce, COMPLAINT, HARD};
+
+use crate::schema::mail_bounces::dsl::*;
+use crate::schema::users;
+
+const FEEDBACK_ERROR: &str = "Unable to record the delivery feedback.";
+
+// A single hard bounce can be a passing problem at the receiving end.
+const HARD_BOUNCE_LIMIT: i64 = 2;
+
+/**
+ * Record the bounces and complaints among the given events, and mark the address
+ * invalid on a complaint or on repeated hard bounces. Other events are ignored.
+ */
+pub fn record_mail_events(connection: &MysqlConnection, events: &[MailEvent]) -> Result<usize, &'static str> {
+    let mut recorded: usize = 0;
+
+    for event in events {
+        let the_type = match event.bounce_type() {
+            Some(value) => value,
+            None => continue,
+        };
+
+        let bounce = NewMailBounce::from(event, the_type);
+
+        let result = diesel::insert_into(mail_bounces).values(&bounce).execute(connection);
+        if result.is_err() {
+            return Err(FEEDBACK_ERROR);
+        }
+
+        if the_type == COMPLAINT || hard_bounces(connection, bounce.email.as_str())? >= HARD_BOUNCE_LIMIT {
+            mark_invalid(connection, bounce.email.as_str())?;
+        }
+
+        recorded += 1;
+    }
+
+    Ok(recorded)
+}
+
+fn hard_bounces(connection: &MysqlConnection, address: &str) -> Result<i64, &'static str> {
+    let result = mail_bounces
+        .filter(email.eq(address))
+        .filter(bounce_type.eq(HARD))
+        .select(count(id))
+        .first(connection);
+
+    if result.is_err() {
+        return Err(FEEDBACK_ERROR);
+    }
+
+    Ok(result.unwrap())
+}
+
+fn mark_invalid(connection: &MysqlConnection, address: &str) -> Result<usize, &'static str> {
+    let result = diesel::update(users::table.filter(users::email.eq(address)))
+        .set(users::email_invalid.eq(true))
+        .execute(connection);
+
+    if result.is_err() {
+        return Err(FEEDBACK_ERROR);
+    }
+
+    Ok(result.unwrap())
+}
+
+/**
+ * The addresses, among the given, that we should not mail anymore; the mails to
+ * a deactivated account are held back as well.
+ */
+pub fn invalid_addresses(connection: &MysqlConnection, addresses: Vec<&str>) -> QueryResult<Vec<String>> {
+    let found: Vec<String> = users::table
+        .filter(users::email_invalid.eq(true).or(users::blocked.eq(true)))
+        .filter(users::email.eq_any(addresses))
+        .select(users::email)
+        .load(connection)?;
+
+    Ok(found.into_iter().map(|address| address.to_lowercase()).collect())
+}
@@ -1,17 +1,41 @@
 use diesel::prelude::*;
+use std::collections::HashMap;
 
 use crate::commons::util;
-use crate::models::master_plans::UpdateMasterPlanRequest;
+use crate::models::abstract_tasks::{NewAbstractTask, NewAbstractTaskRequest};
+use crate::models::enrollments::Enrollment;
+use crate::models::master_plan_imports::{ImportError, ImportOutcome, ImportRow, ImportedLink, ImportedMasterTask, START_NAME};
+use crate::models::master_plans::{self as plans, PlanLink, SpawnPlanRequest, UpdateMasterPlanRequest};
 use crate::models::master_plans::{MasterPlan, MasterPlanCriteria, NewMasterPlan, NewMasterPlanRequest, TaskUnit};
+use crate::models::master_tasks::MasterTask;
+use crate::models::task_links::NewTaskLink;
+use crate::models::tasks::{NewTask, Task};
 
+use crate::services::programs;
+
+use crate::schema::abstract_tasks;
+use crate::schema::enrollments;
 use crate::schema::master_plans;
 use crate::schema::master_task_links;
 use crate::schema::master_tasks;
+use crate::schema::platform_roles;
+use crate::schema::task_links;
+use crate::schema::tasks;
 
 use crate::schema::master_plans::dsl::*;
 use crate::schema::master_task_links::dsl::*;
 use crate::schema::master_tasks::dsl::*;
 
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const PLAN_NOT_FOUND: &str = "Unable to find the master plan.";
+const PLAN_FETCH_ERROR: &str = "Unable to fetch the tasks of the master plan.";
+const EMPTY_PLAN: &str = "The master plan has no task to spawn.";
+const SPAWN_ERROR: &str = "Unable to spawn the tasks of the master plan.";
+const IMPORT_ERROR: &str = "Unable to save the imported plan. Nothing was saved.";
+const UNKNOWN_ROLE: &str = "The role is not one of the platform.";
+
+const COACH_ROLE: &str = "coach";
+
 pub fn create_master_plan(connection: &MysqlConnection, request: &NewMasterPlanRequest) -> Result<MasterPlan, diesel::result::Error> {
     let new_master_plan = NewMasterPlan::from(request);
 
@@ -62,3 +86,159 @@ pub fn update_master_plan(connection: &MysqlConnection, request: &UpdateMasterPl
 
     Ok(String::from("Ok"))
 }
+
+/**
+ * Every task of the plan, other than its START and STOP, becomes a task of the
+ * enrollment, scheduled from the anchor date along the forward links, and the
+ * links between them are copied along. The tasks of the coach role go to the
+ * coach of the program and the rest to the member. Either all the tasks are
+ * saved or none.
+ */
+pub fn spawn_plan(connection: &MysqlConnection, request: &SpawnPlanRequest) -> Result<Vec<Task>, &'static str> {
+    let enrollment: Enrollment = enrollments::table
+        .filter(enrollments::id.eq(request.enrollment_id.as_str()))
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    let program = programs::find(connection, &enrollment.program_id)?;
+
+    let plan: MasterPlan = master_plans
+        .filter(master_plans::id.eq(request.master_plan_id.as_str()))
+        .first(connection)
+        .map_err(|_| PLAN_NOT_FOUND)?;
+
+    let plan_tasks: Vec<(MasterTask, String)> = master_tasks
+        .inner_join(abstract_tasks::table)
+        .filter(master_tasks::master_plan_id.eq(plan.id.as_str()))
+        .select((master_tasks::all_columns, abstract_tasks::name))
+        .load(connection)
+        .map_err(|_| PLAN_FETCH_ERROR)?;
+
+    let links: Vec<PlanLink> = master_task_links
+        .filter(master_task_links::master_plan_id.eq(plan.id.as_str()))
+        .select((source_task_id, target_task_id, lead_time, master_task_links::coordinates, priority, is_forward))
+        .load(connection)
+        .map_err(|_| PLAN_FETCH_ERROR)?;
+
+    let (the_master_tasks, task_names): (Vec<MasterTask>, Vec<String>) = plan_tasks.into_iter().unzip();
+    let starts = plans::schedule(&the_master_tasks, &links, util::as_date(request.anchor_date.as_str()))?;
+
+    let mut new_tasks: Vec<NewTask> = Vec::new();
+    let mut spawned_ids: HashMap<&str, String> = HashMap::new();
+
+    for (master_task, task_name) in the_master_tasks.iter().zip(task_names.iter()) {
+        if plans::is_marker(master_task) {
+            continue;
+        }
+
+        let actor_id = if master_task.role_id.eq_ignore_ascii_case(COACH_ROLE) {
+            program.coach_id.as_str()
+        } else {
+            enrollment.member_id.as_str()
+        };
+
+        let new_task = NewTask::of_master_task(enrollment.id.as_str(), actor_id, master_task, task_name, plan.name.as_str(), starts[&master_task.id]);
+        spawned_ids.insert(master_task.id.as_str(), new_task.id.to_owned());
+        new_tasks.push(new_task);
+    }
+
+    if new_tasks.is_empty() {
+        return Err(EMPTY_PLAN);
+    }
+
+    // The links of the markers have no task to hold on to.
+    let new_links: Vec<NewTaskLink> = links
+        .iter()
+        .filter_map(|link| {
+            let source_id = spawned_ids.get(link.source_task_id.as_str())?;
+            let target_id = spawned_ids.get(link.target_task_id.as_str())?;
+            Some(NewTaskLink::of_plan_link(link, enrollment.id.as_str(), source_id, target_id))
+        })
+        .collect();
+
+    let new_ids: Vec<&str> = new_tasks.iter().map(|new_task| new_task.id.as_str()).collect();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(tasks::table).values(&new_tasks).execute(connection)?;
+        if !new_links.is_empty() {
+            diesel::insert_into(task_links::table).values(&new_links).execute(connection)?;
+        }
+
+        tasks::table.filter(tasks::id.eq_any(&new_ids)).order_by(tasks::original_start_date.asc()).load(connection)
+    });
+
+    result.map_err(|_| SPAWN_ERROR)
+}
+
+/**
+ * The plan of a CSV with its tasks; either all of it is saved or none. The tasks
+ * are named by the abstract tasks of the coach, the missing ones created along.
+ */
+pub fn import_master_plan(connection: &MysqlConnection, request: &NewMasterPlanRequest, rows: &[ImportRow]) -> Result<ImportOutcome, Vec<ImportError>> {
+    let import_error = |_| vec![ImportError::new(0, "", IMPORT_ERROR)];
+
+    let roles: Vec<String> = platform_roles::table.select(platform_roles::id).load(connection).map_err(import_error)?;
+
+    let unknown: Vec<ImportError> = rows
+        .iter()
+        .filter(|row| !roles.iter().any(|role| role.eq_ignore_ascii_case(row.role.as_str())))
+        .map(|row| ImportError::new(row.line, "role", UNKNOWN_ROLE))
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(unknown);
+    }
+
+    let new_master_plan = NewMasterPlan::from(request);
+    let plan_id = new_master_plan.id.as_str();
+    let the_coach_id = request.coach_id.as_str();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(master_plans).values(&new_master_plan).execute(connection)?;
+
+        let mut named: HashMap<String, String> = abstract_tasks::table
+            .filter(abstract_tasks::coach_id.eq(the_coach_id))
+            .select((abstract_tasks::name, abstract_tasks::id))
+            .load::<(String, String)>(connection)?
+            .into_iter()
+            .collect();
+
+        let mut abstract_task_of = |task_name: &str| -> QueryResult<String> {
+            if let Some(the_id) = named.get(task_name) {
+                return Ok(the_id.to_owned());
+            }
+
+            let new_abstract_task = NewAbstractTask::from(&NewAbstractTaskRequest {
+                name: task_name.to_owned(),
+                coach_id: the_coach_id.to_owned(),
+            });
+            diesel::insert_into(abstract_tasks::table).values(&new_abstract_task).execute(connection)?;
+            named.insert(task_name.to_owned(), new_abstract_task.id.to_owned());
+
+            Ok(new_abstract_task.id)
+        };
+
+        let start = ImportedMasterTask::start(plan_id, abstract_task_of(START_NAME)?.as_str(), the_coach_id, COACH_ROLE);
+
+        let mut new_tasks: Vec<ImportedMasterTask> = Vec::new();
+        let mut new_links: Vec<ImportedLink> = Vec::new();
+
+        for (at, row) in rows.iter().enumerate() {
+            let new_task = ImportedMasterTask::from(row, plan_id, abstract_task_of(row.name.as_str())?.as_str(), the_coach_id);
+            new_links.push(ImportedLink::from(plan_id, start.id.as_str(), &new_task, row.offset_day, at as i32));
+            new_tasks.push(new_task);
+        }
+
+        diesel::insert_into(master_tasks).values(&start).execute(connection)?;
+        diesel::insert_into(master_tasks).values(&new_tasks).execute(connection)?;
+        diesel::insert_into(master_task_links).values(&new_links).execute(connection)?;
+
+        Ok(ImportOutcome {
+            master_plan_id: plan_id.to_owned(),
+            name: new_master_plan.name.to_owned(),
+            tasks: new_tasks.len(),
+        })
+    });
+
+    result.map_err(import_error)
+}
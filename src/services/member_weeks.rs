@@ -0,0 +1,85 @@
+use chrono::Duration;
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::member_weeks::{week_of, CheckIn, MemberWeek, WeekSession, WeekTask, WEEK_DAYS};
+use crate::models::objectives::Objective;
+use crate::models::program_announcements::Announcement;
+use crate::models::programs::Program;
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+
+use crate::schema::enrollments;
+use crate::schema::objectives;
+use crate::schema::program_announcements;
+use crate::schema::programs;
+use crate::schema::session_users;
+use crate::schema::sessions;
+use crate::schema::tasks;
+
+const NOT_SIGNED_IN: &str = "Please sign in to see your week.";
+const WEEK_ERROR: &str = "Unable to gather the week.";
+
+/**
+ * The week of the signed in member, in four range queries. The sessions and
+ * the tasks are fetched by either of their dates and settled on their schedule
+ * by the model.
+ */
+pub fn get_my_week(connection: &MysqlConnection, caller: Option<&UserId>) -> Result<MemberWeek, &'static str> {
+    let member_id = caller.ok_or(NOT_SIGNED_IN)?.as_str();
+    let (starts_at, ends_at) = week_of(util::now());
+
+    let sessions: Vec<(Session, Program)> = sessions::table
+        .inner_join(programs::table)
+        .inner_join(session_users::table)
+        .filter(session_users::user_id.eq(member_id))
+        .filter(sessions::cancelled_at.is_null())
+        .filter(sessions::expired_at.is_null())
+        .filter(
+            sessions::original_start_date
+                .ge(starts_at)
+                .and(sessions::original_start_date.lt(ends_at))
+                .or(sessions::revised_start_date.ge(starts_at).and(sessions::revised_start_date.lt(ends_at))),
+        )
+        .select((sessions::all_columns, programs::all_columns))
+        .load(connection)
+        .map_err(|_| WEEK_ERROR)?;
+
+    let tasks: Vec<(Task, Program)> = tasks::table
+        .inner_join(enrollments::table.inner_join(programs::table))
+        .filter(enrollments::member_id.eq(member_id))
+        .filter(tasks::responded_date.is_null())
+        .filter(tasks::cancelled_at.is_null())
+        .filter(tasks::actual_end_date.is_null())
+        .filter(tasks::original_end_date.lt(ends_at).or(tasks::revised_end_date.lt(ends_at)))
+        .select((tasks::all_columns, programs::all_columns))
+        .load(connection)
+        .map_err(|_| WEEK_ERROR)?;
+
+    let check_ins: Vec<(Objective, Program)> = objectives::table
+        .inner_join(enrollments::table.inner_join(programs::table))
+        .filter(enrollments::member_id.eq(member_id))
+        .filter(objectives::actual_end_date.is_null())
+        .filter(objectives::original_start_date.ge(starts_at))
+        .filter(objectives::original_start_date.lt(ends_at))
+        .order_by(objectives::original_start_date.asc())
+        .select((objectives::all_columns, programs::all_columns))
+        .load(connection)
+        .map_err(|_| WEEK_ERROR)?;
+
+    let enrolled_programs = enrollments::table.filter(enrollments::member_id.eq(member_id)).select(enrollments::program_id);
+
+    let announcements: Vec<Announcement> = program_announcements::table
+        .filter(program_announcements::program_id.eq_any(enrolled_programs))
+        .filter(program_announcements::published_at.ge(starts_at - Duration::days(WEEK_DAYS)))
+        .order_by(program_announcements::published_at.desc())
+        .load(connection)
+        .map_err(|_| WEEK_ERROR)?;
+
+    let sessions = sessions.into_iter().map(|(session, program)| WeekSession { session, program }).collect();
+    let tasks = tasks.into_iter().map(|(task, program)| WeekTask { task, program }).collect();
+    let check_ins = check_ins.into_iter().map(|(objective, program)| CheckIn { objective, program }).collect();
+
+    Ok(MemberWeek::of(starts_at, ends_at, sessions, tasks, check_ins, announcements))
+}
@@ -1,15 +1,71 @@
 pub mod abstract_tasks;
+pub mod admin;
+pub mod agreements;
+pub mod anonymizer;
+pub mod audit;
+pub mod board_annotations;
+pub mod board_versions;
+pub mod coach_availability;
+pub mod coach_onboarding;
+pub mod coach_profiles;
+pub mod coach_stats;
+pub mod coupons;
+pub mod data_exports;
+pub mod data_fixes;
+pub mod demo_sandboxes;
+pub mod enrollment_coaches;
 pub mod enrollments;
+pub mod exports;
+pub mod facades;
+pub mod fee_schedules;
+pub mod field_usage;
+pub mod file_access_log;
+pub mod file_previews;
+pub mod file_registry;
+pub mod goal_boards;
+pub mod guest_links;
+pub mod invoices;
+pub mod mail_bounces;
+pub mod late_policies;
 pub mod master_plans;
 pub mod master_tasks;
+pub mod member_weeks;
 pub mod notes;
+pub mod notifications;
 pub mod objectives;
 pub mod observations;
+pub mod organizations;
 pub mod options;
+pub mod password_resets;
+pub mod payments;
+pub mod platform_banners;
+pub mod program_announcements;
+pub mod program_faqs;
+pub mod program_requests;
+pub mod program_reviews;
+pub mod program_waitlists;
 pub mod programs;
+pub mod progress;
+pub mod recording_consents;
+pub mod reminders;
+pub mod saved_filters;
+pub mod session_cancellations;
+pub mod session_merges;
+pub mod session_objectives;
+pub mod session_scratchpads;
+pub mod session_summaries;
+pub mod session_visits;
 pub mod sessions;
+pub mod support_tickets;
+pub mod stale_drafts;
 pub mod tasks;
+pub mod thumbnails;
+pub mod timeline_exports;
+pub mod user_locales;
+pub mod user_profiles;
 pub mod users;
+pub mod video_metadata;
+pub mod webhooks;
 pub mod correspondences;
 pub mod discussions;
 pub mod conferences;
\ No newline at end of file
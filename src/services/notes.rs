@@ -1,7 +1,9 @@
 use diesel::prelude::*;
 
-use crate::models::notes::{NewNote, NewNoteFile, NewNoteRequest, Note, NoteCriteria};
+use crate::commons::chassis::{Page, Window};
+use crate::models::notes::{FileRequest, NewNote, NewNoteFile, NewNoteRequest, Note, NoteCriteria, NoteFileCriteria, SessionFile};
 
+use crate::services::file_previews::preview;
 use crate::services::sessions::find_session_user;
 
 use crate::schema::session_files::dsl::*;
@@ -14,13 +16,16 @@ pub fn create_new_note(connection: &MysqlConnection, request: &NewNoteRequest) -
 
     let new_note = NewNote::from(request, session_user);
 
-    diesel::insert_into(session_notes).values(&new_note).execute(connection)?;
+    // The note comes with all of its files or not at all; see graphql_uploads.
+    connection.transaction(|| {
+        diesel::insert_into(session_notes).values(&new_note).execute(connection)?;
 
-    let note: Note = find(connection, &new_note.id.as_str())?;
+        let note: Note = find(connection, &new_note.id.as_str())?;
 
-    insert_files(connection, request, &note)?;
+        insert_files(connection, request, &note)?;
 
-    Ok(note)
+        Ok(note)
+    })
 }
 
 fn insert_files(connection: &MysqlConnection, request: &NewNoteRequest, note: &Note) -> QueryResult<usize> {
@@ -28,13 +33,60 @@ fn insert_files(connection: &MysqlConnection, request: &NewNoteRequest, note: &N
         return Ok(0);
     }
 
-    let insert_files: Vec<NewNoteFile> = request.files.as_ref().unwrap().iter().map(|file| NewNoteFile::from(file, note.id.to_owned())).collect();
+    let insert_files: Vec<NewNoteFile> = request
+        .files
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|file| NewNoteFile::from(file, note.id.to_owned()).with_preview(preview(file.path.as_str(), file.r#type.as_str())))
+        .collect();
 
     diesel::insert_into(session_files).values(insert_files).execute(connection)
 }
 
-pub fn get_notes(connection: &MysqlConnection, criteria: NoteCriteria) -> Result<Vec<Note>, diesel::result::Error> {
-    session_notes.filter(session_user_id.eq(criteria.session_user_id)).load(connection)
+const NOTE_NOT_FOUND: &str = "Unable to find the note of the session user.";
+const ATTACH_ERROR: &str = "Unable to attach the file to the note.";
+
+/**
+ * Attach an uploaded file to a note. The note should belong to the session user
+ * in whose directory the file was stored.
+ */
+pub fn attach_file(connection: &MysqlConnection, note_id: &str, the_session_user_id: &str, file: &FileRequest) -> Result<usize, &'static str> {
+    let result: QueryResult<Note> = session_notes
+        .filter(crate::schema::session_notes::id.eq(note_id))
+        .filter(session_user_id.eq(the_session_user_id))
+        .first(connection);
+
+    if result.is_err() {
+        return Err(NOTE_NOT_FOUND);
+    }
+
+    let new_file = NewNoteFile::from(file, note_id.to_owned()).with_preview(preview(file.path.as_str(), file.r#type.as_str()));
+
+    let result = diesel::insert_into(session_files).values(&new_file).execute(connection);
+
+    if result.is_err() {
+        return Err(ATTACH_ERROR);
+    }
+
+    Ok(result.unwrap())
+}
+
+pub fn get_notes(connection: &MysqlConnection, criteria: NoteCriteria, window: &Window) -> Result<Page<Note>, diesel::result::Error> {
+    use crate::schema::session_notes::dsl::created_at;
+
+    let rows: Vec<Note> = session_notes
+        .filter(session_user_id.eq(criteria.session_user_id))
+        .order_by(created_at.asc())
+        .offset(window.offset)
+        .limit(window.fetch())
+        .load(connection)?;
+
+    Ok(Page::of(rows, window))
+}
+
+pub fn get_note_files(connection: &MysqlConnection, criteria: NoteFileCriteria) -> Result<Vec<SessionFile>, diesel::result::Error> {
+    session_files.filter(session_note_id.eq(criteria.note_id)).load(connection)
 }
 
 fn find(connection: &MysqlConnection, the_id: &str) -> QueryResult<Note> {
@@ -0,0 +1,49 @@
+use diesel::prelude::*;
+
+use crate::commons::util;
+use crate::models::notifications::{MarkReadRequest, NewNotification, Notification, NotificationCriteria};
+
+use crate::schema::notifications::dsl::*;
+
+const MARK_READ_ERROR: &str = "Unable to mark the notifications as read.";
+
+// The list is for a glance; older notices are reached through their records.
+const LIST_LIMIT: i64 = 100;
+
+/**
+ * Like the audit, the notices are written on the connection of the change they tell
+ * about, so that a rolled back change notifies nobody.
+ */
+pub fn notify(connection: &MysqlConnection, notices: &[NewNotification]) -> QueryResult<usize> {
+    if notices.is_empty() {
+        return Ok(0);
+    }
+
+    diesel::insert_into(notifications).values(notices).execute(connection)
+}
+
+pub fn get_notifications(connection: &MysqlConnection, criteria: &NotificationCriteria) -> QueryResult<Vec<Notification>> {
+    let mut query = notifications.filter(user_id.eq(criteria.user_id.as_str())).into_boxed();
+
+    if criteria.unread_only {
+        query = query.filter(read_at.is_null());
+    }
+
+    query.order_by(created_at.desc()).limit(LIST_LIMIT).load(connection)
+}
+
+pub fn count_unread(connection: &MysqlConnection, the_user_id: &str) -> QueryResult<i64> {
+    notifications.filter(user_id.eq(the_user_id)).filter(read_at.is_null()).count().get_result(connection)
+}
+
+/**
+ * Only the notifications of the given user are marked; the ids of others are ignored.
+ */
+pub fn mark_read(connection: &MysqlConnection, request: &MarkReadRequest) -> Result<usize, &'static str> {
+    let target = notifications
+        .filter(user_id.eq(request.user_id.as_str()))
+        .filter(id.eq_any(&request.ids))
+        .filter(read_at.is_null());
+
+    diesel::update(target).set(read_at.eq(util::now())).execute(connection).map_err(|_| MARK_READ_ERROR)
+}
@@ -1,8 +1,23 @@
 use diesel::prelude::*;
 
+use crate::commons::ids::UserId;
+use crate::commons::util;
 use crate::models::enrollments::PlanCriteria;
-use crate::models::observations::{NewObservation, NewObservationRequest, Observation, UpdateObservationRequest};
+use crate::models::observations::{ChangeObservationVisibilityRequest, NewObservation, NewObservationRequest, Observation, Reader, UpdateObservationRequest};
+use crate::models::users::User;
+use crate::schema::enrollments;
 use crate::schema::observations::dsl::*;
+use crate::schema::programs;
+
+use crate::services::enrollment_coaches::get_enrollment_coaches;
+use crate::services::programs::get_peer_coaches;
+use crate::services::users;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const NOT_ALLOWED: &str = "You are not allowed to read the observations of this enrollment.";
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const OBSERVATIONS_FETCH_ERROR: &str = "Unable to fetch the observations.";
+const VISIBILITY_CHANGE_ERROR: &str = "Unable to change the visibility of the observation.";
 
 pub fn create_observation(connection: &MysqlConnection, request: &NewObservationRequest) -> Result<Observation, diesel::result::Error> {
     let new_observation = NewObservation::from(request);
@@ -23,6 +38,73 @@ pub fn update_observation(connection: &MysqlConnection, request: &UpdateObservat
     observations.filter(id.eq(the_id)).first(connection)
 }
 
-pub fn get_observations(connection: &MysqlConnection, criteria: PlanCriteria) -> Result<Vec<Observation>, diesel::result::Error> {
-    observations.filter(enrollment_id.eq(criteria.enrollment_id)).order_by(created_at.asc()).load(connection)
+pub fn change_observation_visibility(connection: &MysqlConnection, request: &ChangeObservationVisibilityRequest) -> Result<Observation, &'static str> {
+    let the_id = request.id.as_str();
+
+    diesel::update(observations.filter(id.eq(the_id)))
+        .set(visibility.eq(request.visibility.as_str()))
+        .execute(connection)
+        .map_err(|_| VISIBILITY_CHANGE_ERROR)?;
+
+    observations.filter(id.eq(the_id)).first(connection).map_err(|_| VISIBILITY_CHANGE_ERROR)
+}
+
+/**
+ * The observations of the enrollment that the caller may read.
+ */
+pub fn get_observations(connection: &MysqlConnection, caller: Option<&UserId>, criteria: &PlanCriteria) -> Result<Vec<Observation>, &'static str> {
+    let caller = caller.ok_or(NOT_SIGNED_IN)?;
+    let user = users::find(connection, caller).map_err(|_| NOT_SIGNED_IN)?;
+
+    get_readable_observations(connection, &user, criteria.enrollment_id.as_str())
+}
+
+pub fn get_readable_observations(connection: &MysqlConnection, user: &User, the_enrollment_id: &str) -> Result<Vec<Observation>, &'static str> {
+    let reader = reader_of(connection, user, the_enrollment_id)?;
+
+    observations
+        .filter(enrollment_id.eq(the_enrollment_id))
+        .filter(visibility.eq_any(reader.visibilities()))
+        .order_by(created_at.asc())
+        .load(connection)
+        .map_err(|_| OBSERVATIONS_FETCH_ERROR)
+}
+
+/**
+ * The admins and the co-coaches read as the coach of the enrollment does. A peer
+ * coach is the coach of a program under the same parent.
+ */
+fn reader_of(connection: &MysqlConnection, user: &User, the_enrollment_id: &str) -> Result<Reader, &'static str> {
+    if user.user_type == util::ADMIN {
+        return Ok(Reader::Coach);
+    }
+
+    let (the_program_id, the_coach_id, the_member_id): (String, String, String) = enrollments::table
+        .inner_join(programs::table)
+        .filter(enrollments::id.eq(the_enrollment_id))
+        .select((programs::id, programs::coach_id, enrollments::member_id))
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    if user.id == the_coach_id || is_co_coach(connection, user, the_enrollment_id)? {
+        return Ok(Reader::Coach);
+    }
+
+    if user.id == the_member_id {
+        return Ok(Reader::Member);
+    }
+
+    let peers = get_peer_coaches(connection, the_program_id.as_str()).map_err(|_| OBSERVATIONS_FETCH_ERROR)?;
+
+    if peers.iter().any(|peer| user.id == peer.program.coach_id) {
+        return Ok(Reader::PeerCoach);
+    }
+
+    Err(NOT_ALLOWED)
+}
+
+fn is_co_coach(connection: &MysqlConnection, user: &User, the_enrollment_id: &str) -> Result<bool, &'static str> {
+    let co_coaches = get_enrollment_coaches(connection, the_enrollment_id)?;
+
+    Ok(co_coaches.iter().any(|co_coach| user.id == co_coach.coach_id))
 }
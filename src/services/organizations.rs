@@ -0,0 +1,227 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::scheduling::SchedulingRules;
+use crate::commons::util;
+use crate::models::organizations::{
+    utilization, NewOrganizationMember, NewOrganizationRequest, Organization, OrganizationMemberRequest, OrganizationReport, OrganizationReportCriteria, OrganizationRole, ProgramEnrollmentRow,
+    SchedulingRulesRequest, SessionDayRow,
+};
+
+use crate::services::admin::admin_of;
+use crate::services::users;
+
+use crate::schema::coach_daily_stats;
+use crate::schema::coaches;
+use crate::schema::enrollments;
+use crate::schema::organization_members;
+use crate::schema::organizations;
+use crate::schema::programs;
+
+const ORGANIZATION_NOT_FOUND: &str = "Unable to find the organization.";
+const NOT_AN_ADMIN: &str = "Only an admin of the organization may read its report.";
+const ORGANIZATION_SAVE_ERROR: &str = "Unable to save the organization.";
+const MEMBER_SAVE_ERROR: &str = "Unable to save the member of the organization.";
+const REPORT_ERROR: &str = "Unable to read the report of the organization.";
+const RULES_ERROR: &str = "Unable to read the scheduling rules of the organization.";
+
+/**
+ * The organizations are set up by the admins of the platform; the admin is the
+ * signed in user, never an id in the request.
+ */
+pub fn create_organization(connection: &MysqlConnection, caller: Option<&UserId>, request: &NewOrganizationRequest) -> Result<Organization, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let organization = request.to_row(&admin.id);
+
+    diesel::insert_into(organizations::table)
+        .values(&organization)
+        .execute(connection)
+        .map_err(|_| ORGANIZATION_SAVE_ERROR)?;
+
+    find(connection, organization.id.as_str())
+}
+
+/**
+ * Adds the user to the organization or changes their role; without a role the user leaves it.
+ */
+pub fn save_organization_member(connection: &MysqlConnection, caller: Option<&UserId>, request: &OrganizationMemberRequest) -> Result<Organization, &'static str> {
+    admin_of(connection, caller)?;
+
+    let organization = find(connection, request.organization_id.as_str())?;
+    let user = users::find(connection, &request.user_id)?;
+
+    let the_member = organization_members::table
+        .filter(organization_members::organization_id.eq(organization.id.as_str()))
+        .filter(organization_members::user_id.eq(user.id.as_str()));
+
+    let result = match request.role {
+        Some(role) => {
+            let member = NewOrganizationMember {
+                organization_id: organization.id.to_owned(),
+                user_id: user.id.to_string(),
+                role: role.as_str().to_owned(),
+            };
+            diesel::replace_into(organization_members::table).values(&member).execute(connection)
+        }
+        None => diesel::delete(the_member).execute(connection),
+    };
+
+    result.map_err(|_| MEMBER_SAVE_ERROR)?;
+
+    Ok(organization)
+}
+
+/**
+ * The scheduling rules of the coaches of the organization, by an admin of it or of the platform.
+ */
+pub fn set_scheduling_rules(connection: &MysqlConnection, caller: Option<&UserId>, request: &SchedulingRulesRequest) -> Result<Organization, &'static str> {
+    let organization = find(connection, request.organization_id.as_str())?;
+    let user = users::find(connection, caller.ok_or(NOT_AN_ADMIN)?).map_err(|_| NOT_AN_ADMIN)?;
+
+    if user.user_type != util::ADMIN && !has_role(connection, organization.id.as_str(), user.id.as_str(), OrganizationRole::ADMIN)? {
+        return Err(NOT_AN_ADMIN);
+    }
+
+    diesel::update(organizations::table.filter(organizations::id.eq(organization.id.as_str())))
+        .set((organizations::session_hours.eq(request.hours()), organizations::session_holidays.eq(request.holidays())))
+        .execute(connection)
+        .map_err(|_| ORGANIZATION_SAVE_ERROR)?;
+
+    find(connection, organization.id.as_str())
+}
+
+/**
+ * The rules a coach schedules the sessions by: those of the platform, with the
+ * ones set by the organization of the coach in their place. A coach in several
+ * organizations follows the one joined first.
+ */
+pub fn scheduling_rules(connection: &MysqlConnection, the_coach_id: &str) -> Result<SchedulingRules, &'static str> {
+    let own: Option<(Option<String>, Option<String>)> = organizations::table
+        .inner_join(organization_members::table)
+        .filter(organization_members::user_id.eq(the_coach_id))
+        .filter(organizations::session_hours.is_not_null().or(organizations::session_holidays.is_not_null()))
+        .select((organizations::session_hours, organizations::session_holidays))
+        .order_by(organization_members::created_at.asc())
+        .first(connection)
+        .optional()
+        .map_err(|_| RULES_ERROR)?;
+
+    let rules = SchedulingRules::from_env();
+
+    Ok(match own {
+        Some((hours, holidays)) => rules.within(hours.as_deref(), holidays.as_deref()),
+        None => rules,
+    })
+}
+
+/**
+ * Every row of the report is scoped to the coaches who are members of the
+ * organization; the scope is resolved once, after the admin is checked, and each
+ * dataset filters by it.
+ */
+pub fn get_organization_report(connection: &MysqlConnection, criteria: &OrganizationReportCriteria) -> Result<OrganizationReport, &'static str> {
+    let (from, to) = criteria.range()?;
+    let organization = find(connection, criteria.organization_id.as_str())?;
+    let user = users::find(connection, &criteria.user_id)?;
+
+    if !has_role(connection, organization.id.as_str(), user.id.as_str(), OrganizationRole::ADMIN)? {
+        return Err(NOT_AN_ADMIN);
+    }
+
+    let scope: Vec<(String, String)> = coaches::table
+        .inner_join(organization_members::table.on(organization_members::user_id.eq(coaches::user_id)))
+        .filter(organization_members::organization_id.eq(organization.id.as_str()))
+        .filter(organization_members::role.eq(OrganizationRole::COACH.as_str()))
+        .select((coaches::id, coaches::full_name))
+        .order_by(coaches::full_name.asc())
+        .load(connection)
+        .map_err(|_| REPORT_ERROR)?;
+
+    let coach_ids: Vec<&str> = scope.iter().map(|(coach_id, _)| coach_id.as_str()).collect();
+
+    let sessions: Vec<SessionDayRow> = coach_daily_stats::table
+        .filter(coach_daily_stats::coach_id.eq_any(&coach_ids))
+        .filter(coach_daily_stats::day.between(from, to))
+        .select((
+            coach_daily_stats::coach_id,
+            coach_daily_stats::day,
+            coach_daily_stats::sessions_created,
+            coach_daily_stats::sessions_done,
+            coach_daily_stats::sessions_cancelled,
+            coach_daily_stats::tasks_responded,
+        ))
+        .order_by((coach_daily_stats::day.asc(), coach_daily_stats::coach_id.asc()))
+        .load(connection)
+        .map_err(|_| REPORT_ERROR)?;
+
+    let programs = program_rows(connection, &coach_ids, from.and_hms(0, 0, 0), to.succ().and_hms(0, 0, 0))?;
+    let coaches = utilization(&scope, &sessions, from, to);
+    let sessions = sessions.into_iter().filter(|row| row.sessions_created + row.sessions_done + row.sessions_cancelled > 0).collect();
+
+    Ok(OrganizationReport {
+        organization,
+        from,
+        to,
+        programs,
+        sessions,
+        coaches,
+    })
+}
+
+/**
+ * The coach enrolls in her own conferences; those enrollments are not counted.
+ */
+fn program_rows(connection: &MysqlConnection, coach_ids: &[&str], start: NaiveDateTime, end: NaiveDateTime) -> Result<Vec<ProgramEnrollmentRow>, &'static str> {
+    let owned: Vec<(String, String, String, bool)> = programs::table
+        .filter(programs::coach_id.eq_any(coach_ids))
+        .select((programs::id, programs::name, programs::coach_id, programs::active))
+        .order_by(programs::name.asc())
+        .load(connection)
+        .map_err(|_| REPORT_ERROR)?;
+
+    let program_ids: Vec<&str> = owned.iter().map(|row| row.0.as_str()).collect();
+
+    let enrolled: Vec<(String, NaiveDateTime)> = enrollments::table
+        .inner_join(programs::table)
+        .filter(enrollments::program_id.eq_any(&program_ids))
+        .filter(enrollments::member_id.ne(programs::coach_id))
+        .select((enrollments::program_id, enrollments::created_at))
+        .load(connection)
+        .map_err(|_| REPORT_ERROR)?;
+
+    let rows = owned
+        .into_iter()
+        .map(|(program_id, program_name, coach_id, active)| {
+            let of_program: Vec<NaiveDateTime> = enrolled.iter().filter(|row| row.0 == program_id).map(|row| row.1).collect();
+
+            ProgramEnrollmentRow {
+                enrollments: of_program.len() as i32,
+                enrolled_in_range: of_program.iter().filter(|created_at| **created_at >= start && **created_at < end).count() as i32,
+                program_id,
+                program_name,
+                coach_id,
+                active,
+            }
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn find(connection: &MysqlConnection, the_id: &str) -> Result<Organization, &'static str> {
+    organizations::table.filter(organizations::id.eq(the_id)).first(connection).map_err(|_| ORGANIZATION_NOT_FOUND)
+}
+
+fn has_role(connection: &MysqlConnection, the_organization_id: &str, the_user_id: &str, role: OrganizationRole) -> Result<bool, &'static str> {
+    let count: i64 = organization_members::table
+        .filter(organization_members::organization_id.eq(the_organization_id))
+        .filter(organization_members::user_id.eq(the_user_id))
+        .filter(organization_members::role.eq(role.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| REPORT_ERROR)?;
+
+    Ok(count > 0)
+}
@@ -0,0 +1,111 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::password;
+use crate::commons::util;
+
+use crate::models::correspondences::{MailOut, MailRecipient};
+use crate::models::password_resets::{ConfirmPasswordResetRequest, NewPasswordReset, PasswordReset, PasswordResetRequest};
+use crate::models::users::User;
+
+use crate::services::correspondences::create_mail;
+use crate::services::users;
+
+use crate::schema::password_resets;
+use crate::schema::password_resets::dsl::*;
+use crate::schema::users::dsl as user_dsl;
+
+// The same answer, registered or not, so that the addresses cannot be probed through the reset.
+const RESET_MAIL_QUEUED: &str = "If the email is registered with us, a mail to reset the password is on its way.";
+
+const RESET_REQUEST_ERROR: &str = "Unable to process the password reset request.";
+const TOKEN_INVALID: &str = "The token is either invalid or already used.";
+const TOKEN_EXPIRED: &str = "The token has expired. Please ask for a reset again.";
+
+/**
+ * The tokens of the user yet to be used are spent, and the new token and its
+ * mail are saved together. A deactivated account, or one whose mails bounce,
+ * gets nothing.
+ */
+pub fn request_password_reset(connection: &MysqlConnection, request: &PasswordResetRequest) -> Result<String, &'static str> {
+    let result: QueryResult<User> = user_dsl::users.filter(user_dsl::email.eq(request.email.trim())).first(connection);
+
+    let user = match result {
+        Ok(user) if !user.blocked && !user.email_invalid => user,
+        _ => return Ok(String::from(RESET_MAIL_QUEUED)),
+    };
+
+    let new_reset = NewPasswordReset::from(&user);
+    let mail_out = MailOut::for_password_reset(&user, &new_reset);
+    let recipients = MailRecipient::build_to(&[&user], mail_out.id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        spend_open_tokens(connection, user.id.as_str())?;
+
+        diesel::insert_into(password_resets).values(&new_reset).execute(connection)?;
+
+        create_mail(connection, mail_out, recipients).map_err(|_| diesel::result::Error::RollbackTransaction)?;
+
+        Ok(())
+    });
+
+    if result.is_err() {
+        return Err(RESET_REQUEST_ERROR);
+    }
+
+    Ok(String::from(RESET_MAIL_QUEUED))
+}
+
+/**
+ * The token is spent with the change of the password, along with any other
+ * token of the user yet to be used.
+ */
+pub fn confirm_password_reset(connection: &MysqlConnection, request: &ConfirmPasswordResetRequest) -> Result<User, &'static str> {
+    let result: QueryResult<PasswordReset> = password_resets.filter(token.eq(request.token.trim())).first(connection);
+
+    let reset = match result {
+        Ok(reset) => reset,
+        Err(_) => return Err(TOKEN_INVALID),
+    };
+
+    if reset.used_at.is_some() {
+        return Err(TOKEN_INVALID);
+    }
+
+    if !reset.is_active() {
+        return Err(TOKEN_EXPIRED);
+    }
+
+    let user = users::find(connection, &UserId::from(reset.user_id.as_str()))?;
+
+    let hashed_password = password::hash(request.new_password.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        // A token spent in the meantime leaves the password as it is.
+        let spent = diesel::update(password_resets.filter(password_resets::id.eq(reset.id.as_str())).filter(used_at.is_null()))
+            .set(used_at.eq(util::now()))
+            .execute(connection)?;
+
+        if spent == 0 {
+            return Err(diesel::result::Error::NotFound);
+        }
+
+        diesel::update(user_dsl::users.filter(user_dsl::id.eq(&user.id))).set(user_dsl::password.eq(hashed_password)).execute(connection)?;
+
+        spend_open_tokens(connection, user.id.as_str())?;
+
+        Ok(())
+    });
+
+    match result {
+        Ok(_) => Ok(user),
+        Err(diesel::result::Error::NotFound) => Err(TOKEN_INVALID),
+        Err(_) => Err(users::PASSWORD_RESET_FAILED),
+    }
+}
+
+fn spend_open_tokens(connection: &MysqlConnection, the_user_id: &str) -> QueryResult<usize> {
+    diesel::update(password_resets.filter(user_id.eq(the_user_id)).filter(used_at.is_null()))
+        .set(used_at.eq(util::now()))
+        .execute(connection)
+}
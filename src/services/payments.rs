@@ -0,0 +1,124 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::payments::{ConfirmPaymentRequest, Payment, PaymentIntentRequest, PaymentStatus};
+use crate::models::programs::Program;
+
+use crate::services::coupons;
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::payments;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const NOT_THE_MEMBER: &str = "Only the member may pay for the own enrollment.";
+const NOT_PAID: &str = "There is nothing to pay for the program.";
+const NOT_PUBLISHED: &str = "The program is not open to enroll in yet.";
+const NOT_THE_GATEWAY: &str = "The payment may be confirmed only by the payment gateway or an admin.";
+const PAYMENT_EXISTS: &str = "The payment intent is recorded already.";
+const PAYMENT_NOT_FOUND: &str = "Unable to find the payment.";
+const PAYMENT_SETTLED: &str = "The payment is confirmed or failed already.";
+const PAYMENT_SAVE_ERROR: &str = "Unable to record the payment.";
+const PAYMENT_REQUIRED: &str = "The program is a paid one. Please complete the payment to enroll.";
+
+/**
+ * The payment intent opened by the member at the gateway, for the price of the
+ * program as of now, less the discount of the coupon if any.
+ */
+pub fn create_payment_intent(connection: &MysqlConnection, caller: Option<&UserId>, request: &PaymentIntentRequest) -> Result<Payment, &'static str> {
+    let caller = caller.ok_or(NOT_SIGNED_IN)?;
+
+    if caller.as_str() != request.member_id {
+        return Err(NOT_THE_MEMBER);
+    }
+
+    let program = programs::find(connection, &request.program_id)?;
+
+    if !program.is_published() {
+        return Err(NOT_PUBLISHED);
+    }
+
+    let (price, currency) = match (program.price_cents, program.currency.as_deref()) {
+        (Some(price), Some(code)) if program.is_paid() => (price, code),
+        _ => return Err(NOT_PAID),
+    };
+
+    let amount_cents = match request.coupon_code.as_deref() {
+        Some(code) => coupons::find_usable(connection, &program, code)?.discounted(price),
+        None => price,
+    };
+
+    if amount_cents <= 0 {
+        return Err(NOT_PAID);
+    }
+
+    if find_by_ref(connection, request.gateway_ref.as_str()).is_ok() {
+        return Err(PAYMENT_EXISTS);
+    }
+
+    let row = request.to_row(amount_cents, currency);
+
+    diesel::insert_into(payments::table).values(&row).execute(connection).map_err(|_| PAYMENT_SAVE_ERROR)?;
+
+    find_by_ref(connection, row.gateway_ref.as_str())
+}
+
+/**
+ * The gateway tells the outcome of a pending payment with the token we shared
+ * with it, PAYMENT_GATEWAY_TOKEN; an admin reconciling by hand needs none.
+ */
+pub fn confirm_payment(connection: &MysqlConnection, caller: Option<&UserId>, request: &ConfirmPaymentRequest) -> Result<Payment, &'static str> {
+    let expected = dotenv::var("PAYMENT_GATEWAY_TOKEN").unwrap_or_default();
+    let from_gateway = !expected.is_empty() && request.token.as_deref() == Some(expected.as_str());
+
+    if !from_gateway {
+        let caller = caller.ok_or(NOT_THE_GATEWAY)?;
+        users::find_admin(connection, caller).map_err(|_| NOT_THE_GATEWAY)?;
+    }
+
+    let payment = find_by_ref(connection, request.gateway_ref.as_str())?;
+
+    if PaymentStatus::from_str(payment.status.as_str()) != PaymentStatus::PENDING {
+        return Err(PAYMENT_SETTLED);
+    }
+
+    diesel::update(payments::table.filter(payments::id.eq(payment.id.as_str())))
+        .set((payments::status.eq(request.target_status().as_str()), payments::updated_at.eq(util::now())))
+        .execute(connection)
+        .map_err(|_| PAYMENT_SAVE_ERROR)?;
+
+    find_by_ref(connection, request.gateway_ref.as_str())
+}
+
+/**
+ * An amount due takes a confirmed payment of the member, of the amount at
+ * least, not used up by an enrollment yet; the payment is linked to the new enrollment. A free program,
+ * or one made free by a coupon, takes none.
+ */
+pub fn take_payment(connection: &MysqlConnection, program: &Program, amount_due: i32, the_member_id: &str, the_enrollment_id: &str) -> Result<(), &'static str> {
+    if amount_due <= 0 {
+        return Ok(());
+    }
+
+    let payment: Payment = payments::table
+        .filter(payments::program_id.eq(program.id.as_str()))
+        .filter(payments::member_id.eq(the_member_id))
+        .filter(payments::status.eq(PaymentStatus::CONFIRMED.as_str()))
+        .filter(payments::enrollment_id.is_null())
+        .filter(payments::amount_cents.ge(amount_due))
+        .order_by(payments::created_at.asc())
+        .first(connection)
+        .map_err(|_| PAYMENT_REQUIRED)?;
+
+    diesel::update(payments::table.filter(payments::id.eq(payment.id.as_str())))
+        .set((payments::enrollment_id.eq(the_enrollment_id), payments::updated_at.eq(util::now())))
+        .execute(connection)
+        .map_err(|_| PAYMENT_SAVE_ERROR)?;
+
+    Ok(())
+}
+
+fn find_by_ref(connection: &MysqlConnection, the_ref: &str) -> Result<Payment, &'static str> {
+    payments::table.filter(payments::gateway_ref.eq(the_ref)).first(connection).map_err(|_| PAYMENT_NOT_FOUND)
+}
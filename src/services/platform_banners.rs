@@ -0,0 +1,118 @@
+use diesel::prelude::*;
+use std::cmp::Reverse;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::platform_banners::{BannerAudience, BannerCriteria, BannerRequest, BannerSeverity, DismissBannerRequest, NewBannerDismissal, NewPlatformBanner, PlatformBanner, UpdateBannerRequest};
+
+use crate::services::admin::admin_of;
+use crate::services::users;
+
+use crate::schema::banner_dismissals;
+use crate::schema::platform_banners;
+
+const BANNER_NOT_FOUND: &str = "Unable to find the banner.";
+const BANNER_SAVE_ERROR: &str = "Unable to save the banner.";
+const BANNER_DELETE_ERROR: &str = "Unable to delete the banner.";
+const BANNERS_ERROR: &str = "Unable to fetch the banners.";
+const NOT_DISMISSIBLE: &str = "A critical banner stays until its window closes.";
+const DISMISS_ERROR: &str = "Unable to dismiss the banner.";
+
+pub fn create_banner(connection: &MysqlConnection, caller: Option<&UserId>, request: &BannerRequest) -> Result<PlatformBanner, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    let new_banner = NewPlatformBanner::from(request, &admin.id);
+
+    diesel::insert_into(platform_banners::table).values(&new_banner).execute(connection).map_err(|_| BANNER_SAVE_ERROR)?;
+
+    find_banner(connection, new_banner.id.as_str())
+}
+
+pub fn update_banner(connection: &MysqlConnection, caller: Option<&UserId>, request: &UpdateBannerRequest) -> Result<PlatformBanner, &'static str> {
+    admin_of(connection, caller)?;
+
+    let banner = find_banner(connection, request.id.as_str())?;
+
+    diesel::update(&banner)
+        .set((
+            platform_banners::title.eq(request.title.trim()),
+            platform_banners::message.eq(request.message.trim()),
+            platform_banners::severity.eq(request.severity.as_str()),
+            platform_banners::audience.eq(request.audience.as_str()),
+            platform_banners::starts_at.eq(util::as_date(request.starts_at.as_str())),
+            platform_banners::ends_at.eq(util::as_date(request.ends_at.as_str())),
+            platform_banners::updated_at.eq(util::now()),
+        ))
+        .execute(connection)
+        .map_err(|_| BANNER_SAVE_ERROR)?;
+
+    find_banner(connection, banner.id.as_str())
+}
+
+/**
+ * The dismissals of the banner go along with it.
+ */
+pub fn delete_banner(connection: &MysqlConnection, caller: Option<&UserId>, criteria: &BannerCriteria) -> Result<usize, &'static str> {
+    admin_of(connection, caller)?;
+
+    let banner = find_banner(connection, criteria.id.as_str())?;
+
+    diesel::delete(&banner).execute(connection).map_err(|_| BANNER_DELETE_ERROR)
+}
+
+/**
+ * Every banner, the past and the scheduled ones included, for the admins to manage.
+ */
+pub fn get_banners(connection: &MysqlConnection, caller: Option<&UserId>) -> Result<Vec<PlatformBanner>, &'static str> {
+    admin_of(connection, caller)?;
+
+    platform_banners::table.order_by(platform_banners::starts_at.desc()).load(connection).map_err(|_| BANNERS_ERROR)
+}
+
+/**
+ * The banners in their window for the audience of the user, leaving out the ones
+ * the user has dismissed. The UI polls this, so it is kept to a single query.
+ */
+pub fn get_active_banners(connection: &MysqlConnection, the_user_id: &UserId) -> Result<Vec<PlatformBanner>, &'static str> {
+    let user = users::find(connection, the_user_id)?;
+    let now = util::now();
+
+    let dismissed = banner_dismissals::table.filter(banner_dismissals::user_id.eq(the_user_id)).select(banner_dismissals::banner_id);
+
+    let mut banners: Vec<PlatformBanner> = platform_banners::table
+        .filter(platform_banners::starts_at.le(now))
+        .filter(platform_banners::ends_at.gt(now))
+        .filter(platform_banners::audience.eq_any(BannerAudience::of_user_type(user.user_type.as_str())))
+        .filter(platform_banners::id.ne_all(dismissed))
+        .order_by(platform_banners::starts_at.desc())
+        .load(connection)
+        .map_err(|_| BANNERS_ERROR)?;
+
+    banners.sort_by_key(|banner| Reverse(BannerSeverity::from_str(banner.severity.as_str()) as u8));
+
+    Ok(banners)
+}
+
+/**
+ * Dismissing a banner twice is harmless.
+ */
+pub fn dismiss_banner(connection: &MysqlConnection, request: &DismissBannerRequest) -> Result<usize, &'static str> {
+    users::find(connection, &request.user_id)?;
+
+    let banner = find_banner(connection, request.banner_id.as_str())?;
+
+    if BannerSeverity::from_str(banner.severity.as_str()) == BannerSeverity::CRITICAL {
+        return Err(NOT_DISMISSIBLE);
+    }
+
+    let dismissal = NewBannerDismissal {
+        banner_id: banner.id.to_owned(),
+        user_id: request.user_id.to_string(),
+    };
+
+    diesel::insert_or_ignore_into(banner_dismissals::table).values(&dismissal).execute(connection).map_err(|_| DISMISS_ERROR)
+}
+
+fn find_banner(connection: &MysqlConnection, the_id: &str) -> Result<PlatformBanner, &'static str> {
+    platform_banners::table.filter(platform_banners::id.eq(the_id)).first(connection).map_err(|_| BANNER_NOT_FOUND)
+}
@@ -0,0 +1,162 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::ProgramId;
+use crate::commons::plain_text;
+use crate::commons::util;
+use crate::models::notifications::{NewNotification, ANNOUNCEMENT};
+use crate::models::program_announcements::{Announcement, AnnouncementCriteria, AnnouncementRequest, DeleteAnnouncementRequest, NewAnnouncement, UpdateAnnouncementRequest};
+use crate::models::programs::Program;
+
+use crate::services::notifications::notify;
+use crate::services::programs;
+
+use crate::schema::enrollments;
+use crate::schema::program_announcements::dsl::*;
+
+const BATCH_SIZE: i64 = 20;
+
+const NOT_PROGRAM_COACH: &str = "Only the coach of the program may make its announcements.";
+const ALREADY_PUBLISHED: &str = "The announcement is already published; it cannot be changed.";
+const ANNOUNCEMENT_NOT_FOUND: &str = "Unable to find the announcement.";
+const ANNOUNCEMENT_SAVE_ERROR: &str = "Unable to save the announcement.";
+const ANNOUNCEMENT_DELETE_ERROR: &str = "Unable to delete the announcement.";
+const DUE_ANNOUNCEMENTS_ERROR: &str = "Unable to find the announcements due for publishing.";
+const PUBLISH_ERROR: &str = "Unable to publish the announcement.";
+
+pub fn create_announcement(connection: &MysqlConnection, request: &AnnouncementRequest) -> Result<Announcement, &'static str> {
+    coached_program(connection, &request.program_id, request.coach_id.as_str())?;
+
+    let new_announcement = NewAnnouncement::from(request);
+
+    let result = diesel::insert_into(program_announcements).values(&new_announcement).execute(connection);
+
+    if result.is_err() {
+        return Err(ANNOUNCEMENT_SAVE_ERROR);
+    }
+
+    find(connection, new_announcement.id.as_str())
+}
+
+/**
+ * A draft or a scheduled announcement may be changed, or moved back to draft.
+ */
+pub fn update_announcement(connection: &MysqlConnection, request: &UpdateAnnouncementRequest) -> Result<Announcement, &'static str> {
+    let announcement = find_unpublished(connection, request.id.as_str(), request.coach_id.as_str())?;
+
+    let result = diesel::update(program_announcements.filter(id.eq(announcement.id.as_str())).filter(published_at.is_null()))
+        .set((
+            title.eq(request.title.trim()),
+            content.eq(request.content.trim()),
+            content_text.eq(plain_text::plain_text(request.content.trim())),
+            publish_at.eq(request.publish_at()),
+        ))
+        .execute(connection);
+
+    if result.is_err() {
+        return Err(ANNOUNCEMENT_SAVE_ERROR);
+    }
+
+    find(connection, announcement.id.as_str())
+}
+
+pub fn delete_announcement(connection: &MysqlConnection, request: &DeleteAnnouncementRequest) -> Result<usize, &'static str> {
+    let announcement = find_unpublished(connection, request.id.as_str(), request.coach_id.as_str())?;
+
+    diesel::delete(program_announcements.filter(id.eq(announcement.id.as_str())).filter(published_at.is_null()))
+        .execute(connection)
+        .map_err(|_| ANNOUNCEMENT_DELETE_ERROR)
+}
+
+pub fn get_announcements(connection: &MysqlConnection, criteria: &AnnouncementCriteria) -> Result<Vec<Announcement>, &'static str> {
+    let program = programs::find(connection, &criteria.program_id)?;
+
+    let mut query = program_announcements.filter(program_id.eq(program.id.as_str())).into_boxed();
+
+    if program.coach_id != criteria.user_id {
+        query = query.filter(published_at.is_not_null());
+    }
+
+    query
+        .order_by((published_at.desc(), publish_at.desc(), created_at.desc()))
+        .load(connection)
+        .map_err(|_| ANNOUNCEMENT_NOT_FOUND)
+}
+
+/**
+ * Publishes the scheduled announcements whose time has come and notifies the
+ * members of their programs. An announcement claimed by another instance in
+ * the meantime is left alone.
+ */
+pub fn publish_due(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let now = util::now();
+
+    let due: Vec<Announcement> = program_announcements
+        .filter(published_at.is_null())
+        .filter(publish_at.le(now))
+        .order_by(publish_at.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| DUE_ANNOUNCEMENTS_ERROR)?;
+
+    let mut published = 0;
+
+    for announcement in &due {
+        let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+            let claimed = diesel::update(program_announcements.filter(id.eq(announcement.id.as_str())).filter(published_at.is_null()))
+                .set(published_at.eq(now))
+                .execute(connection)?;
+
+            if claimed == 0 {
+                return Ok(0);
+            }
+
+            let members: Vec<String> = enrollments::table
+                .filter(enrollments::program_id.eq(announcement.program_id.as_str()))
+                .select(enrollments::member_id)
+                .distinct()
+                .load(connection)?;
+
+            let notices: Vec<NewNotification> = members
+                .iter()
+                .map(|member| NewNotification::new(member.as_str(), ANNOUNCEMENT, announcement.title.to_owned(), announcement.id.as_str()))
+                .collect();
+
+            notify(connection, &notices)?;
+
+            Ok(claimed)
+        });
+
+        match result {
+            Ok(count) => published += count,
+            Err(_) => return Err(PUBLISH_ERROR),
+        }
+    }
+
+    Ok(published)
+}
+
+fn find(connection: &MysqlConnection, the_id: &str) -> Result<Announcement, &'static str> {
+    program_announcements.filter(id.eq(the_id)).first(connection).map_err(|_| ANNOUNCEMENT_NOT_FOUND)
+}
+
+fn find_unpublished(connection: &MysqlConnection, the_id: &str, the_coach_id: &str) -> Result<Announcement, &'static str> {
+    let announcement = find(connection, the_id)?;
+
+    coached_program(connection, &ProgramId::from(announcement.program_id.as_str()), the_coach_id)?;
+
+    if announcement.published_at.is_some() {
+        return Err(ALREADY_PUBLISHED);
+    }
+
+    Ok(announcement)
+}
+
+fn coached_program(connection: &MysqlConnection, the_program_id: &ProgramId, the_coach_id: &str) -> Result<Program, &'static str> {
+    let program = programs::find(connection, the_program_id)?;
+
+    if program.coach_id != the_coach_id {
+        return Err(NOT_PROGRAM_COACH);
+    }
+
+    Ok(program)
+}
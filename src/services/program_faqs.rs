@@ -0,0 +1,187 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::ProgramId;
+use crate::commons::util;
+use crate::models::notifications::{NewNotification, PROGRAM_QUESTION, QUESTION_ANSWERED};
+use crate::models::program_faqs::{AnswerQuestionRequest, AskQuestionRequest, FaqCriteria, NewFaq, NewFaqRequest, NewQuestion, ProgramFaq, ProgramQuestion, QuestionCriteria, UpdateFaqRequest};
+use crate::models::programs::Program;
+
+use crate::services::notifications::notify;
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::program_faqs;
+use crate::schema::program_questions;
+
+const NOT_PROGRAM_COACH: &str = "Only the coach of the program may manage its FAQ and answer its questions.";
+const INACTIVE_PROGRAM: &str = "The program is not open for questions.";
+const FAQ_NOT_FOUND: &str = "Unable to find the FAQ.";
+const FAQ_SAVE_ERROR: &str = "Unable to save the FAQ.";
+const FAQ_DELETE_ERROR: &str = "Unable to delete the FAQ.";
+const QUESTION_NOT_FOUND: &str = "Unable to find the question.";
+const QUESTION_SAVE_ERROR: &str = "Unable to save the question.";
+const ANSWER_SAVE_ERROR: &str = "Unable to save the answer.";
+
+pub fn create_faq(connection: &MysqlConnection, request: &NewFaqRequest) -> Result<ProgramFaq, &'static str> {
+    coached_program(connection, &request.program_id, request.coach_id.as_str())?;
+
+    let new_faq = NewFaq::from(request);
+
+    let result = diesel::insert_into(program_faqs::table).values(&new_faq).execute(connection);
+
+    if result.is_err() {
+        return Err(FAQ_SAVE_ERROR);
+    }
+
+    find_faq(connection, new_faq.id.as_str())
+}
+
+pub fn update_faq(connection: &MysqlConnection, request: &UpdateFaqRequest) -> Result<ProgramFaq, &'static str> {
+    let faq = find_faq(connection, request.id.as_str())?;
+    coached_program(connection, &ProgramId::from(faq.program_id.as_str()), request.coach_id.as_str())?;
+
+    let result = diesel::update(program_faqs::table.filter(program_faqs::id.eq(faq.id.as_str())))
+        .set((
+            program_faqs::question.eq(request.question.trim()),
+            program_faqs::answer.eq(request.answer.trim()),
+            program_faqs::position.eq(request.position),
+        ))
+        .execute(connection);
+
+    if result.is_err() {
+        return Err(FAQ_SAVE_ERROR);
+    }
+
+    find_faq(connection, faq.id.as_str())
+}
+
+/**
+ * The question behind a deleted FAQ stays answered; it is just no more published.
+ */
+pub fn delete_faq(connection: &MysqlConnection, criteria: &FaqCriteria) -> Result<usize, &'static str> {
+    let faq = find_faq(connection, criteria.id.as_str())?;
+    coached_program(connection, &ProgramId::from(faq.program_id.as_str()), criteria.coach_id.as_str())?;
+
+    diesel::delete(program_faqs::table.filter(program_faqs::id.eq(faq.id.as_str())))
+        .execute(connection)
+        .map_err(|_| FAQ_DELETE_ERROR)
+}
+
+pub fn get_faqs(connection: &MysqlConnection, the_program_id: &str) -> QueryResult<Vec<ProgramFaq>> {
+    program_faqs::table
+        .filter(program_faqs::program_id.eq(the_program_id))
+        .order_by((program_faqs::position.asc(), program_faqs::created_at.asc()))
+        .load(connection)
+}
+
+pub fn ask_question(connection: &MysqlConnection, request: &AskQuestionRequest) -> Result<ProgramQuestion, &'static str> {
+    let user = users::find(connection, &request.user_id)?;
+    let program = programs::find(connection, &request.program_id)?;
+
+    if !program.active {
+        return Err(INACTIVE_PROGRAM);
+    }
+
+    let new_question = NewQuestion::from(request);
+    let subject = format!("{} asked a question on {}", user.full_name, program.name);
+    let notice = NewNotification::new(program.coach_id.as_str(), PROGRAM_QUESTION, subject, new_question.id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(program_questions::table).values(&new_question).execute(connection)?;
+
+        notify(connection, &[notice])
+    });
+
+    if result.is_err() {
+        return Err(QUESTION_SAVE_ERROR);
+    }
+
+    find_question(connection, new_question.id.as_str())
+}
+
+/**
+ * Answering again replaces the earlier answer, and the published FAQ with it.
+ * A published question is published once, at the end of the FAQ.
+ */
+pub fn answer_question(connection: &MysqlConnection, request: &AnswerQuestionRequest) -> Result<ProgramQuestion, &'static str> {
+    let question = find_question(connection, request.question_id.as_str())?;
+    let program = coached_program(connection, &ProgramId::from(question.program_id.as_str()), request.coach_id.as_str())?;
+
+    let answer = request.answer.trim();
+    let subject = format!("Your question on {} is answered", program.name);
+    let notice = NewNotification::new(question.asked_by_id.as_str(), QUESTION_ANSWERED, subject, question.id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let published_id = match (&question.faq_id, request.publish) {
+            (Some(the_faq_id), _) => {
+                diesel::update(program_faqs::table.filter(program_faqs::id.eq(the_faq_id.as_str())))
+                    .set(program_faqs::answer.eq(answer))
+                    .execute(connection)?;
+                Some(the_faq_id.to_owned())
+            }
+            (None, true) => {
+                let last: Option<i32> = program_faqs::table
+                    .filter(program_faqs::program_id.eq(program.id.as_str()))
+                    .select(diesel::dsl::max(program_faqs::position))
+                    .first(connection)?;
+
+                let new_faq = NewFaq::published(&question, answer, last.map_or(0, |value| value + 1));
+                diesel::insert_into(program_faqs::table).values(&new_faq).execute(connection)?;
+                Some(new_faq.id)
+            }
+            (None, false) => None,
+        };
+
+        diesel::update(program_questions::table.filter(program_questions::id.eq(question.id.as_str())))
+            .set((
+                program_questions::answer.eq(answer),
+                program_questions::answered_at.eq(util::now()),
+                program_questions::faq_id.eq(published_id),
+            ))
+            .execute(connection)?;
+
+        notify(connection, &[notice])
+    });
+
+    if result.is_err() {
+        return Err(ANSWER_SAVE_ERROR);
+    }
+
+    find_question(connection, question.id.as_str())
+}
+
+/**
+ * The unanswered questions come first, the oldest at the top.
+ */
+pub fn get_questions(connection: &MysqlConnection, criteria: &QuestionCriteria) -> Result<Vec<ProgramQuestion>, &'static str> {
+    let program = programs::find(connection, &criteria.program_id)?;
+
+    let mut query = program_questions::table.filter(program_questions::program_id.eq(program.id.as_str())).into_boxed();
+
+    if program.coach_id != criteria.user_id {
+        query = query.filter(program_questions::asked_by_id.eq(criteria.user_id.as_str()));
+    }
+
+    query
+        .order_by((program_questions::answered_at.is_not_null().asc(), program_questions::created_at.asc()))
+        .load(connection)
+        .map_err(|_| QUESTION_NOT_FOUND)
+}
+
+fn coached_program(connection: &MysqlConnection, the_program_id: &ProgramId, the_coach_id: &str) -> Result<Program, &'static str> {
+    let program = programs::find(connection, the_program_id)?;
+
+    if program.coach_id != the_coach_id {
+        return Err(NOT_PROGRAM_COACH);
+    }
+
+    Ok(program)
+}
+
+fn find_faq(connection: &MysqlConnection, the_id: &str) -> Result<ProgramFaq, &'static str> {
+    program_faqs::table.filter(program_faqs::id.eq(the_id)).first(connection).map_err(|_| FAQ_NOT_FOUND)
+}
+
+fn find_question(connection: &MysqlConnection, the_id: &str) -> Result<ProgramQuestion, &'static str> {
+    program_questions::table.filter(program_questions::id.eq(the_id)).first(connection).map_err(|_| QUESTION_NOT_FOUND)
+}
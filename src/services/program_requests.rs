@@ -0,0 +1,294 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use crate::commons::ids::{ProgramId, UserId};
+use crate::models::coach_profiles::{from_tags, tag_pattern};
+use crate::models::enrollments::{Enrollment, NewEnrollmentRequest};
+use crate::models::notifications::{NewNotification, OFFER_ACCEPTED, PROGRAM_OFFER, PROGRAM_REQUEST};
+use crate::models::program_requests::{
+    AcceptOfferRequest, MakeOfferRequest, NewProgramOffer, NewProgramRequest, OfferRow, ProgramOffer, ProgramRequest, RaiseProgramRequest, RequestRow, WithdrawProgramRequest, ACCEPTED, DECLINED, FULFILLED, OPEN,
+    PENDING, WITHDRAWN,
+};
+use crate::models::programs::Program;
+
+use crate::services::enrollments;
+use crate::services::notifications::notify;
+use crate::services::users;
+
+use crate::schema::coach_profiles;
+use crate::schema::program_offers;
+use crate::schema::program_requests;
+use crate::schema::programs;
+
+// The coaches told about a request, and the open requests a coach browses.
+const MAX_NOTIFIED: i64 = 50;
+const OPEN_REQUESTS_LIMIT: i64 = 200;
+
+const REQUEST_NOT_FOUND: &str = "Unable to find the program request.";
+const REQUEST_NOT_OPEN: &str = "The program request is no longer open.";
+const REQUEST_SAVE_ERROR: &str = "Unable to save the program request.";
+const REQUESTS_ERROR: &str = "Unable to fetch the program requests.";
+const PROGRAM_NOT_FOUND: &str = "Unable to find the program.";
+const NOT_PROGRAM_COACH: &str = "Only the coach of the program may offer it.";
+const INACTIVE_PROGRAM: &str = "An inactive program cannot be offered.";
+const OFFER_NOT_FOUND: &str = "Unable to find the offer.";
+const OFFER_NOT_PENDING: &str = "The offer is no longer on the table.";
+const OFFER_SAVE_ERROR: &str = "Unable to save the offer; the program may have been offered for this request already.";
+const ACCEPT_ERROR: &str = "The member is enrolled, but the offers could not be closed.";
+
+/**
+ * The listed coaches whose specialties meet any tag of the request are notified.
+ */
+pub fn raise_program_request(connection: &MysqlConnection, request: &RaiseProgramRequest) -> Result<ProgramRequest, &'static str> {
+    let member = users::find(connection, &request.member_id)?;
+
+    let new_request = NewProgramRequest::from(request);
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(program_requests::table).values(&new_request).execute(connection)?;
+
+        let coaches = matching_coaches(connection, new_request.tags.as_str(), member.id.as_str())?;
+
+        let notices: Vec<NewNotification> = coaches
+            .iter()
+            .map(|coach| {
+                let subject = format!("{} is looking for a program on {}", member.full_name, new_request.title);
+                NewNotification::new(coach.as_str(), PROGRAM_REQUEST, subject, new_request.id.as_str())
+            })
+            .collect();
+
+        notify(connection, &notices)
+    });
+
+    if result.is_err() {
+        return Err(REQUEST_SAVE_ERROR);
+    }
+
+    find_request(connection, new_request.id.as_str())
+}
+
+/**
+ * The pending offers are declined along with the request.
+ */
+pub fn withdraw_program_request(connection: &MysqlConnection, request: &WithdrawProgramRequest) -> Result<ProgramRequest, &'static str> {
+    let program_request = find_owned_open(connection, request.id.as_str(), request.member_id.as_str())?;
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(program_requests::table.filter(program_requests::id.eq(program_request.id.as_str())))
+            .set(program_requests::status.eq(WITHDRAWN))
+            .execute(connection)?;
+
+        decline_pending(connection, program_request.id.as_str())
+    });
+
+    if result.is_err() {
+        return Err(REQUEST_SAVE_ERROR);
+    }
+
+    find_request(connection, program_request.id.as_str())
+}
+
+pub fn get_program_requests(connection: &MysqlConnection, the_member_id: &str) -> Result<Vec<RequestRow>, &'static str> {
+    let requests: Vec<ProgramRequest> = program_requests::table
+        .filter(program_requests::member_id.eq(the_member_id))
+        .order_by(program_requests::created_at.desc())
+        .load(connection)
+        .map_err(|_| REQUESTS_ERROR)?;
+
+    let request_ids: Vec<&str> = requests.iter().map(|request| request.id.as_str()).collect();
+
+    let offers: Vec<(ProgramOffer, Program)> = program_offers::table
+        .inner_join(programs::table)
+        .filter(program_offers::request_id.eq_any(request_ids))
+        .order_by(program_offers::created_at.asc())
+        .load(connection)
+        .map_err(|_| REQUESTS_ERROR)?;
+
+    let mut offers_of: HashMap<String, Vec<OfferRow>> = HashMap::new();
+    for (offer, program) in offers {
+        offers_of.entry(offer.request_id.to_owned()).or_default().push(OfferRow { offer, program });
+    }
+
+    let rows = requests
+        .into_iter()
+        .map(|request| {
+            let offers = offers_of.remove(&request.id).unwrap_or_default();
+            RequestRow { request, offers }
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+/**
+ * The recent open requests sharing a tag with the specialties of the coach.
+ */
+pub fn get_open_requests(connection: &MysqlConnection, the_coach_id: &str) -> Result<Vec<ProgramRequest>, &'static str> {
+    let specialties: String = coach_profiles::table
+        .filter(coach_profiles::coach_id.eq(the_coach_id))
+        .select(coach_profiles::specialties)
+        .first(connection)
+        .unwrap_or_default();
+
+    if specialties.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let requests: Vec<ProgramRequest> = program_requests::table
+        .filter(program_requests::status.eq(OPEN))
+        .filter(program_requests::member_id.ne(the_coach_id))
+        .order_by(program_requests::created_at.desc())
+        .limit(OPEN_REQUESTS_LIMIT)
+        .load(connection)
+        .map_err(|_| REQUESTS_ERROR)?;
+
+    Ok(requests.into_iter().filter(|request| request.shares_tag(specialties.as_str())).collect())
+}
+
+pub fn make_offer(connection: &MysqlConnection, request: &MakeOfferRequest) -> Result<ProgramOffer, &'static str> {
+    let program_request = find_request(connection, request.request_id.as_str())?;
+
+    if !program_request.is_open() {
+        return Err(REQUEST_NOT_OPEN);
+    }
+
+    let program: Program = programs::table.filter(programs::id.eq(request.program_id.as_str())).first(connection).map_err(|_| PROGRAM_NOT_FOUND)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_PROGRAM_COACH);
+    }
+
+    if !program.active {
+        return Err(INACTIVE_PROGRAM);
+    }
+
+    let new_offer = NewProgramOffer::from(request);
+    let subject = format!("{} offers the program {} for your request", program.coach_name, program.name);
+    let notice = NewNotification::new(program_request.member_id.as_str(), PROGRAM_OFFER, subject, program_request.id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(program_offers::table).values(&new_offer).execute(connection)?;
+
+        notify(connection, &[notice])
+    });
+
+    if result.is_err() {
+        return Err(OFFER_SAVE_ERROR);
+    }
+
+    find_offer(connection, new_offer.id.as_str())
+}
+
+/**
+ * The request is claimed first, so that two offers cannot both be accepted; the
+ * claim is given up when the enrollment fails, for instance on a prior enrollment.
+ */
+pub fn accept_offer(connection: &MysqlConnection, request: &AcceptOfferRequest) -> Result<Enrollment, &'static str> {
+    let offer = find_offer(connection, request.offer_id.as_str())?;
+
+    if offer.status != PENDING {
+        return Err(OFFER_NOT_PENDING);
+    }
+
+    let program_request = find_owned_open(connection, offer.request_id.as_str(), request.member_id.as_str())?;
+
+    let claimed = diesel::update(program_requests::table.filter(program_requests::id.eq(program_request.id.as_str())).filter(program_requests::status.eq(OPEN)))
+        .set(program_requests::status.eq(FULFILLED))
+        .execute(connection)
+        .map_err(|_| REQUEST_SAVE_ERROR)?;
+
+    if claimed == 0 {
+        return Err(REQUEST_NOT_OPEN);
+    }
+
+    let enrollment_request = NewEnrollmentRequest {
+        program_id: ProgramId::from(offer.program_id.as_str()),
+        user_id: UserId::from(program_request.member_id.as_str()),
+        coach_id: offer.coach_id.to_owned(),
+        coupon_code: None,
+    };
+
+    let enrollment = match enrollments::create_new_enrollment(connection, &enrollment_request) {
+        Ok(value) => value,
+        Err(e) => {
+            let reopened = diesel::update(program_requests::table.filter(program_requests::id.eq(program_request.id.as_str())))
+                .set(program_requests::status.eq(OPEN))
+                .execute(connection);
+
+            if let Err(reopen_error) = reopened {
+                eprintln!("Unable to reopen the program request {}: {}", program_request.id, reopen_error);
+            }
+
+            return Err(e);
+        }
+    };
+
+    let subject = format!("Your offer for {} is accepted", program_request.title);
+    let notice = NewNotification::new(offer.coach_id.as_str(), OFFER_ACCEPTED, subject, offer.id.as_str());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(program_offers::table.filter(program_offers::id.eq(offer.id.as_str())))
+            .set(program_offers::status.eq(ACCEPTED))
+            .execute(connection)?;
+
+        decline_pending(connection, program_request.id.as_str())?;
+
+        notify(connection, &[notice])
+    });
+
+    if result.is_err() {
+        return Err(ACCEPT_ERROR);
+    }
+
+    Ok(enrollment)
+}
+
+fn matching_coaches(connection: &MysqlConnection, tags: &str, the_member_id: &str) -> QueryResult<Vec<String>> {
+    let mut coaches: Vec<String> = Vec::new();
+
+    for tag in from_tags(tags) {
+        let found: Vec<String> = coach_profiles::table
+            .filter(coach_profiles::is_listed.eq(true))
+            .filter(coach_profiles::specialties.like(tag_pattern(tag.as_str())))
+            .filter(coach_profiles::coach_id.ne(the_member_id))
+            .select(coach_profiles::coach_id)
+            .limit(MAX_NOTIFIED)
+            .load(connection)?;
+
+        coaches.extend(found);
+    }
+
+    coaches.sort();
+    coaches.dedup();
+    coaches.truncate(MAX_NOTIFIED as usize);
+
+    Ok(coaches)
+}
+
+fn decline_pending(connection: &MysqlConnection, the_request_id: &str) -> QueryResult<usize> {
+    diesel::update(program_offers::table.filter(program_offers::request_id.eq(the_request_id)).filter(program_offers::status.eq(PENDING)))
+        .set(program_offers::status.eq(DECLINED))
+        .execute(connection)
+}
+
+fn find_request(connection: &MysqlConnection, the_id: &str) -> Result<ProgramRequest, &'static str> {
+    program_requests::table.filter(program_requests::id.eq(the_id)).first(connection).map_err(|_| REQUEST_NOT_FOUND)
+}
+
+fn find_owned_open(connection: &MysqlConnection, the_id: &str, the_member_id: &str) -> Result<ProgramRequest, &'static str> {
+    let program_request: ProgramRequest = program_requests::table
+        .filter(program_requests::id.eq(the_id))
+        .filter(program_requests::member_id.eq(the_member_id))
+        .first(connection)
+        .map_err(|_| REQUEST_NOT_FOUND)?;
+
+    if !program_request.is_open() {
+        return Err(REQUEST_NOT_OPEN);
+    }
+
+    Ok(program_request)
+}
+
+fn find_offer(connection: &MysqlConnection, the_id: &str) -> Result<ProgramOffer, &'static str> {
+    program_offers::table.filter(program_offers::id.eq(the_id)).first(connection).map_err(|_| OFFER_NOT_FOUND)
+}
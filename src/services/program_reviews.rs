@@ -0,0 +1,148 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use crate::commons::ids::ProgramId;
+use crate::models::program_reviews::{summarize, ModerateReviewRequest, NewReview, NewReviewRequest, ProgramReview, ReviewCriteria, VISIBLE};
+use crate::models::user_programs::forget_catalog;
+
+use crate::services::programs;
+
+use crate::schema::enrollments;
+use crate::schema::program_reviews;
+use crate::schema::programs as programs_table;
+use crate::schema::sessions;
+
+const NOT_THE_MEMBER: &str = "Only the member of the enrollment may review the program.";
+const NOT_THE_COACH: &str = "Only the coach of the program may moderate its reviews.";
+const NOT_COMPLETED: &str = "The program may be reviewed once a session of the enrollment is completed.";
+const ALREADY_REVIEWED: &str = "The program is reviewed for this enrollment already.";
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const REVIEW_NOT_FOUND: &str = "Unable to find the review.";
+const REVIEW_SAVE_ERROR: &str = "Unable to save the review.";
+const REVIEWS_ERROR: &str = "Unable to fetch the reviews of the program.";
+
+/**
+ * The enrollment has no end of its own; a member who completed a session of
+ * it has been through the program enough to review it. The review is kept
+ * on the parent program, the one explored in the catalog.
+ */
+pub fn create_review(connection: &MysqlConnection, request: &NewReviewRequest) -> Result<ProgramReview, &'static str> {
+    let (the_program_id, the_member_id): (String, String) = enrollments::table
+        .filter(enrollments::id.eq(request.enrollment_id.as_str()))
+        .select((enrollments::program_id, enrollments::member_id))
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    if the_member_id != request.member_id {
+        return Err(NOT_THE_MEMBER);
+    }
+
+    let completed: i64 = sessions::table
+        .filter(sessions::enrollment_id.eq(request.enrollment_id.as_str()))
+        .filter(sessions::actual_end_date.is_not_null())
+        .filter(sessions::cancelled_at.is_null())
+        .count()
+        .get_result(connection)
+        .map_err(|_| REVIEW_SAVE_ERROR)?;
+
+    if completed == 0 {
+        return Err(NOT_COMPLETED);
+    }
+
+    let program = programs::find(connection, &ProgramId::from(the_program_id))?;
+    let new_review = NewReview::from(request, program.coalesce_parent_id().as_str());
+
+    let inserted = diesel::insert_or_ignore_into(program_reviews::table)
+        .values(&new_review)
+        .execute(connection)
+        .map_err(|_| REVIEW_SAVE_ERROR)?;
+
+    if inserted == 0 {
+        return Err(ALREADY_REVIEWED);
+    }
+
+    forget_catalog();
+
+    find(connection, new_review.id.as_str())
+}
+
+pub fn moderate_review(connection: &MysqlConnection, request: &ModerateReviewRequest) -> Result<ProgramReview, &'static str> {
+    let review = find(connection, request.review_id.as_str())?;
+    let program = programs::find(connection, &ProgramId::from(review.program_id.as_str()))?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    diesel::update(program_reviews::table.filter(program_reviews::id.eq(review.id.as_str())))
+        .set((program_reviews::status.eq(request.target_state.as_str()), program_reviews::hidden_reason.eq(request.hidden_reason())))
+        .execute(connection)
+        .map_err(|_| REVIEW_SAVE_ERROR)?;
+
+    forget_catalog();
+
+    find(connection, review.id.as_str())
+}
+
+/**
+ * The latest reviews first.
+ */
+pub fn get_reviews(connection: &MysqlConnection, criteria: &ReviewCriteria) -> Result<Vec<ProgramReview>, &'static str> {
+    let program = programs::find(connection, &criteria.program_id)?;
+
+    let mut query = program_reviews::table.filter(program_reviews::program_id.eq(program.coalesce_parent_id().as_str())).into_boxed();
+
+    if program.coach_id != criteria.user_id {
+        query = query.filter(program_reviews::status.eq(VISIBLE));
+    }
+
+    query.order_by(program_reviews::created_at.desc()).load(connection).map_err(|_| REVIEWS_ERROR)
+}
+
+/**
+ * The average rating and the count of the visible reviews of each of the
+ * parent programs, in one round trip for a page of programs.
+ */
+pub fn rating_summaries(connection: &MysqlConnection, parent_ids: &[&str]) -> QueryResult<HashMap<String, (Option<f64>, i32)>> {
+    let rows: Vec<(String, i32)> = program_reviews::table
+        .filter(program_reviews::program_id.eq_any(parent_ids))
+        .filter(program_reviews::status.eq(VISIBLE))
+        .select((program_reviews::program_id, program_reviews::rating))
+        .load(connection)?;
+
+    let mut ratings: HashMap<String, Vec<i32>> = HashMap::new();
+    for (the_program_id, rating) in rows {
+        ratings.entry(the_program_id).or_default().push(rating);
+    }
+
+    Ok(ratings.into_iter().map(|(the_program_id, values)| (the_program_id, summarize(&values))).collect())
+}
+
+/**
+ * The average rating and the count of the visible reviews of the programs of
+ * each coach; of every rated coach when no ids are given.
+ */
+pub fn coach_ratings(connection: &MysqlConnection, coach_ids: Option<&[String]>) -> QueryResult<HashMap<String, (Option<f64>, i32)>> {
+    let mut query = program_reviews::table
+        .inner_join(programs_table::table)
+        .filter(program_reviews::status.eq(VISIBLE))
+        .select((programs_table::coach_id, program_reviews::rating))
+        .into_boxed();
+
+    if let Some(ids) = coach_ids {
+        query = query.filter(programs_table::coach_id.eq_any(ids));
+    }
+
+    let rows: Vec<(String, i32)> = query.load(connection)?;
+
+    let mut ratings: HashMap<String, Vec<i32>> = HashMap::new();
+    for (the_coach_id, rating) in rows {
+        ratings.entry(the_coach_id).or_default().push(rating);
+    }
+
+    Ok(ratings.into_iter().map(|(the_coach_id, values)| (the_coach_id, summarize(&values))).collect())
+}
+
+fn find(connection: &MysqlConnection, the_id: &str) -> Result<ProgramReview, &'static str> {
+    program_reviews::table.filter(program_reviews::id.eq(the_id)).first(connection).map_err(|_| REVIEW_NOT_FOUND)
+}
@@ -0,0 +1,177 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::{ProgramId, UserId};
+use crate::commons::transactions::{in_transaction, Failure};
+use crate::models::enrollments::Enrollment;
+use crate::models::program_waitlists::{has_seat, open_seats, CapacityRequest, NewWaitlistEntry, ProgramWaitlist, PromoteRequest, WaitingMember, WaitlistRequest};
+use crate::models::programs::Program;
+use crate::models::users::User;
+
+use crate::services::enrollments;
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::program_waitlists;
+use crate::schema::programs as programs_table;
+use crate::schema::users as users_table;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const NOT_THE_COACH: &str = "Only the coach of the program may manage its seats.";
+const SEATS_OPEN: &str = "The program has seats open. Please enroll in it.";
+const ALREADY_WAITING: &str = "The member is in the waitlist of the program already.";
+const NOBODY_WAITING: &str = "Nobody is waiting for a seat in the program.";
+const WAITLIST_SAVE_ERROR: &str = "Unable to add the member to the waitlist.";
+const WAITLIST_FETCH_ERROR: &str = "Unable to fetch the waitlist of the program.";
+const CAPACITY_SAVE_ERROR: &str = "Unable to change the number of members of the program.";
+
+/**
+ * Only a full program has a waitlist; a member enrolled in it, or in a peer
+ * program, may not wait for it. The seats are counted with the program locked,
+ * as an enrollment counts them.
+ */
+pub fn join_waitlist(connection: &MysqlConnection, caller: Option<&UserId>, request: &WaitlistRequest) -> Result<ProgramWaitlist, &'static str> {
+    let user = users::find(connection, caller.ok_or(NOT_SIGNED_IN)?)?;
+    let program = programs::find(connection, &request.program_id)?;
+
+    enrollments::gate_prior_enrollment(connection, &program, &user)?;
+
+    in_transaction(connection, WAITLIST_SAVE_ERROR, || {
+        let (cap, members) = enrollments::lock_seats(connection, &program)?;
+
+        if has_seat(cap, members) {
+            return Err(Failure::Step(SEATS_OPEN));
+        }
+
+        let inserted = diesel::insert_or_ignore_into(program_waitlists::table)
+            .values(&NewWaitlistEntry::from(request, user.id.as_str()))
+            .execute(connection)?;
+
+        if inserted == 0 {
+            return Err(Failure::Step(ALREADY_WAITING));
+        }
+
+        Ok(())
+    })?;
+
+    program_waitlists::table
+        .filter(program_waitlists::program_id.eq(request.program_id.as_str()))
+        .filter(program_waitlists::member_id.eq(user.id.as_str()))
+        .first(connection)
+        .map_err(|_| WAITLIST_SAVE_ERROR)
+}
+
+/**
+ * The coach enrolls the given member of the line, or else the first of it,
+ * even past the cap.
+ */
+pub fn promote_from_waitlist(connection: &MysqlConnection, request: &PromoteRequest) -> Result<Enrollment, &'static str> {
+    let program = programs::find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    let mut query = program_waitlists::table
+        .filter(program_waitlists::program_id.eq(program.id.as_str()))
+        .filter(program_waitlists::promoted_at.is_null())
+        .into_boxed();
+
+    if let Some(member_id) = &request.member_id {
+        query = query.filter(program_waitlists::member_id.eq(member_id.as_str()));
+    }
+
+    let entry: ProgramWaitlist = query.order_by(program_waitlists::created_at.asc()).first(connection).map_err(|_| NOBODY_WAITING)?;
+
+    promote(connection, &program, &entry, false)
+}
+
+/**
+ * The "automatic" promotion: the members first in the line take the seats open
+ * in the program. A member who could not be enrolled is passed over, and stays
+ * in the line for the coach to see.
+ */
+pub fn promote_waiting(connection: &MysqlConnection, program: &Program) -> Result<usize, &'static str> {
+    // Without a cap, the whole line takes a seat.
+    let seats = open_seats(program.max_members, enrollments::count_members(connection, program)?).unwrap_or(i64::MAX);
+
+    if seats == 0 {
+        return Ok(0);
+    }
+
+    let waiting: Vec<ProgramWaitlist> = program_waitlists::table
+        .filter(program_waitlists::program_id.eq(program.id.as_str()))
+        .filter(program_waitlists::promoted_at.is_null())
+        .order_by(program_waitlists::created_at.asc())
+        .load(connection)
+        .map_err(|_| WAITLIST_FETCH_ERROR)?;
+
+    let mut promoted = 0;
+
+    for entry in &waiting {
+        if promoted as i64 >= seats {
+            break;
+        }
+
+        match promote(connection, program, entry, true) {
+            Ok(_) => promoted += 1,
+            Err(e) => eprintln!("Unable to promote {} in the program {}: {}", entry.member_id, program.id, e),
+        }
+    }
+
+    Ok(promoted)
+}
+
+fn promote(connection: &MysqlConnection, program: &Program, entry: &ProgramWaitlist, within_cap: bool) -> Result<Enrollment, &'static str> {
+    let member = users::find(connection, &UserId::from(entry.member_id.as_str()))?;
+
+    enrollments::enroll_from_waitlist(connection, program, &member, entry.id.as_str(), within_cap)
+}
+
+/**
+ * A raised cap, or none, opens seats, which the line takes at once.
+ */
+pub fn set_capacity(connection: &MysqlConnection, request: &CapacityRequest) -> Result<Program, &'static str> {
+    let program = programs::find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    diesel::update(programs_table::table.filter(programs_table::id.eq(program.id.as_str())))
+        .set(programs_table::max_members.eq(request.max_members))
+        .execute(connection)
+        .map_err(|_| CAPACITY_SAVE_ERROR)?;
+
+    programs::forget_programs();
+
+    let program = programs::find(connection, &request.program_id)?;
+
+    if let Err(e) = promote_waiting(connection, &program) {
+        eprintln!("Unable to promote the waitlist of the program {}: {}", program.id, e);
+    }
+
+    Ok(program)
+}
+
+/**
+ * The members still waiting, first in the line first.
+ */
+pub fn get_waitlist(connection: &MysqlConnection, the_program_id: &ProgramId) -> Result<Vec<WaitingMember>, &'static str> {
+    let rows: Vec<(ProgramWaitlist, User)> = program_waitlists::table
+        .inner_join(users_table::table)
+        .filter(program_waitlists::program_id.eq(the_program_id.as_str()))
+        .filter(program_waitlists::promoted_at.is_null())
+        .order_by(program_waitlists::created_at.asc())
+        .load(connection)
+        .map_err(|_| WAITLIST_FETCH_ERROR)?;
+
+    Ok(rows
+        .into_iter()
+        .enumerate()
+        .map(|(index, (entry, member))| WaitingMember {
+            position: index as i32 + 1,
+            member,
+            joined_at: entry.created_at,
+        })
+        .collect())
+}
@@ -1,15 +1,30 @@
 use diesel::prelude::*;
+use serde_json::json;
+use std::sync::OnceLock;
 
+use crate::commons::cache::TtlCache;
+use crate::commons::ids::ProgramId;
+use crate::models::admin::ForceDeactivateRequest;
+use crate::models::audit_events::NewAuditEvent;
 use crate::models::coaches::Coach;
 use crate::models::enrollments::Enrollment;
-use crate::models::programs::{AssociateCoachRequest, ChangeProgramStateRequest, NewProgram, NewProgramRequest, Program, ProgramCoach, ProgramTargetState};
-
+use crate::models::programs::{
+    AssociateCoachRequest, ChangeProgramStateRequest, NewProgram, NewProgramRequest, Program, ProgramCoach, ProgramPriceRequest, ProgramReviewRequest, ProgramStatus, ProgramTagsRequest,
+    ProgramTargetState,
+};
+use crate::models::user_programs::forget_catalog;
+use crate::models::users::User;
+
+use crate::services::audit;
+use crate::services::coach_onboarding::gate_publication;
+use crate::services::coach_profiles::refresh_completeness;
 use crate::services::users::{find_coach_by_email, find_coach_by_id};
 
 use crate::schema::coaches::dsl::*;
 use crate::schema::enrollments::dsl::*;
 use crate::schema::programs;
 use crate::schema::programs::dsl::*;
+use crate::schema::users;
 
 const INVALID_PROGRAM: &str = "Invalid Program Id. Error:001.";
 const PROGRAM_CREATION_ERROR: &str = "Program Creation. Error:002";
@@ -18,17 +33,43 @@ const PROGRAM_STATE_CHANGE_ERROR: &str = "Unable to change the state of the prog
 const PROGRAM_SAME_STATE_ERROR: &str = "Program is already in the target state.";
 
 const COACH_WAS_ASSOCIATED: &str = "The coach is already associated";
+const NOT_THE_COACH: &str = "Only the coach of the program may tag it.";
+const NOT_THE_PARENT: &str = "The tags are kept on the parent program; please tag it instead.";
+const TAGS_SAVE_ERROR: &str = "Unable to save the tags of the program.";
+
+const NOT_THE_REVIEWED_COACH: &str = "Only the coach of the program may submit or retire it.";
+const NOT_THE_REVIEWED_PARENT: &str = "The review is of the parent program; its peer programs follow it.";
+const NOT_IN_REVIEW_ORDER: &str = "The program is not in the status to move to the one asked for.";
+const STATUS_CHANGE_ERROR: &str = "Unable to change the status of the program.";
+
+const NOT_THE_PRICING_COACH: &str = "Only the coach of the program may price it.";
+const NOT_THE_PRICED_PARENT: &str = "The price is kept on the parent program; please price it instead.";
+const PRICE_SAVE_ERROR: &str = "Unable to save the price of the program.";
+
 const COACH_WAS_A_MEMBER: &str = "The coach was a member of this program in the past. To avoid conflict in roles, please use a different credential.";
 
 
-pub fn find(connection: &MysqlConnection, the_id: &str) -> Result<Program, &'static str> {
-    let result = programs.filter(programs::id.eq(the_id)).first(connection);
+const CACHED_PROGRAMS: usize = 1024;
 
-    if result.is_err() {
-        return Err(INVALID_PROGRAM);
-    }
+fn cached_programs() -> &'static TtlCache<ProgramId, Program> {
+    static PROGRAMS: OnceLock<TtlCache<ProgramId, Program>> = OnceLock::new();
+    PROGRAMS.get_or_init(|| TtlCache::from_env(CACHED_PROGRAMS))
+}
 
-    Ok(result.unwrap())
+/**
+ * The program, kept a while as it is looked up on most of the requests; a
+ * change of the programs goes through forget_programs.
+ */
+pub fn find(connection: &MysqlConnection, the_id: &ProgramId) -> Result<Program, &'static str> {
+    cached_programs().get_or_load(the_id, || programs.filter(programs::id.eq(the_id)).first(connection).map_err(|_| INVALID_PROGRAM))
+}
+
+/**
+ * The programs and the catalog are read afresh after a change of a program.
+ */
+pub fn forget_programs() {
+    cached_programs().clear();
+    forget_catalog();
 }
 
 /**
@@ -63,7 +104,7 @@ pub fn create_new_program(connection: &MysqlConnection, request: &NewProgramRequ
 pub fn associate_coach(connection: &MysqlConnection, request: &AssociateCoachRequest) -> Result<Program, &'static str> {
     let coach = find_coach_by_email(connection, request.peer_coach_email.as_str())?;
 
-    let given_program = find(connection, request.program_id.as_str())?;
+    let given_program = find(connection, &request.program_id)?;
 
     gate_past_member(connection, &given_program, &coach)?;
 
@@ -73,7 +114,13 @@ pub fn associate_coach(connection: &MysqlConnection, request: &AssociateCoachReq
 
     let new_program = NewProgram::from_parent_program(&parent_program, &coach);
 
-    insert_program(connection, &new_program)
+    let program = insert_program(connection, &new_program)?;
+
+    if let Err(e) = refresh_completeness(connection, coach.id.as_str()) {
+        eprintln!("Unable to refresh the profile completeness of the coach {}: {}", coach.id, e);
+    }
+
+    Ok(program)
 }
 
 fn gate_past_member(connection: &MysqlConnection, given_program: &Program, coach: &Coach) -> Result<(), &'static str> {
@@ -106,7 +153,8 @@ fn gate_already_associated(connection: &MysqlConnection, given_program: &Program
  *
  * The given program_id may either a parent or a spawned one.
  *
- * Return the list of all the associated coaches for the program.
+ * Return the list of all the associated coaches for the program; a deactivated
+ * coach is left out.
  */
 
 pub fn get_peer_coaches(connection: &MysqlConnection, the_program_id: &str) -> Result<Vec<ProgramCoach>, diesel::result::Error> {
@@ -115,6 +163,7 @@ pub fn get_peer_coaches(connection: &MysqlConnection, the_program_id: &str) -> R
     let peer_coaches: Vec<ProgramCoach> = programs
         .inner_join(coaches)
         .filter(parent_program_id.eq(root_program_id))
+        .filter(user_id.eq_any(users::table.filter(users::blocked.eq(false)).select(users::id)))
         .load(connection)?
         .into_iter()
         .map(|tuple: (Program, Coach)| ProgramCoach { program: tuple.0, coach: tuple.1 })
@@ -130,7 +179,132 @@ fn insert_program(connection: &MysqlConnection, new_program: &NewProgram) -> Res
         return Err(PROGRAM_CREATION_ERROR);
     }
 
-    find(connection, new_program.id.as_str())
+    forget_programs();
+
+    find(connection, &new_program.id)
+}
+
+/**
+ * The tags replace the former ones. Only a parent program is explored, so the
+ * peer programs are left untagged.
+ */
+pub fn manage_tags(connection: &MysqlConnection, request: &ProgramTagsRequest) -> Result<Program, &'static str> {
+    let program = find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    if !program.is_parent {
+        return Err(NOT_THE_PARENT);
+    }
+
+    diesel::update(programs.filter(programs::id.eq(program.id.as_str())))
+        .set(tags.eq(request.as_tags()))
+        .execute(connection)
+        .map_err(|_| TAGS_SAVE_ERROR)?;
+
+    forget_programs();
+
+    find(connection, &request.program_id)
+}
+
+/**
+ * The price of the parent program goes to its peer programs too. The payments
+ * made so far keep the amount they were made for.
+ */
+pub fn set_price(connection: &MysqlConnection, request: &ProgramPriceRequest) -> Result<Program, &'static str> {
+    let program = find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_PRICING_COACH);
+    }
+
+    if !program.is_parent {
+        return Err(NOT_THE_PRICED_PARENT);
+    }
+
+    let the_currency = request.price_cents.and(request.currency_code());
+
+    diesel::update(programs.filter(parent_program_id.eq(program.id.as_str())))
+        .set((price_cents.eq(request.price_cents), currency.eq(the_currency)))
+        .execute(connection)
+        .map_err(|_| PRICE_SAVE_ERROR)?;
+
+    forget_programs();
+
+    find(connection, &request.program_id)
+}
+
+/**
+ * The coach submits the draft of the program for a review.
+ */
+pub fn submit_program(connection: &MysqlConnection, request: &ProgramReviewRequest) -> Result<Program, &'static str> {
+    let program = find_reviewed(connection, request)?;
+
+    move_to(connection, &program, ProgramStatus::SUBMITTED, request.coach_id.as_str())
+}
+
+/**
+ * The coach takes a published program off the catalog for good; the members
+ * enrolled go on with it.
+ */
+pub fn retire_program(connection: &MysqlConnection, request: &ProgramReviewRequest) -> Result<Program, &'static str> {
+    let program = find_reviewed(connection, request)?;
+
+    move_to(connection, &program, ProgramStatus::RETIRED, request.coach_id.as_str())
+}
+
+/**
+ * An admin publishes the submitted program to the catalog.
+ */
+pub fn publish(connection: &MysqlConnection, admin: &User, the_program_id: &ProgramId) -> Result<Program, &'static str> {
+    let program = find(connection, the_program_id)?;
+
+    if !program.is_parent {
+        return Err(NOT_THE_REVIEWED_PARENT);
+    }
+
+    move_to(connection, &program, ProgramStatus::PUBLISHED, admin.id.as_str())
+}
+
+fn find_reviewed(connection: &MysqlConnection, request: &ProgramReviewRequest) -> Result<Program, &'static str> {
+    let program = find(connection, &request.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_REVIEWED_COACH);
+    }
+
+    if !program.is_parent {
+        return Err(NOT_THE_REVIEWED_PARENT);
+    }
+
+    Ok(program)
+}
+
+/**
+ * The status moves one step at a time, along with the peer programs, and the
+ * move is audited.
+ */
+fn move_to(connection: &MysqlConnection, program: &Program, target: ProgramStatus, actor_id: &str) -> Result<Program, &'static str> {
+    let current = ProgramStatus::from_str(program.status.as_str());
+
+    if target.preceding() != Some(current) {
+        return Err(NOT_IN_REVIEW_ORDER);
+    }
+
+    let event = NewAuditEvent::from("program", program.id.as_str(), target.as_str(), actor_id).with_states(&json!({ "status": current.as_str() }), &json!({ "status": target.as_str() }));
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(programs.filter(parent_program_id.eq(program.id.as_str()))).set(status.eq(target.as_str())).execute(connection)?;
+        audit::record(connection, &event)
+    });
+
+    result.map_err(|_| STATUS_CHANGE_ERROR)?;
+
+    forget_programs();
+
+    find(connection, &program.id)
 }
 
 /***
@@ -140,9 +314,13 @@ fn insert_program(connection: &MysqlConnection, new_program: &NewProgram) -> Res
  * The state change shall be permitted only from the parent program.
  */
 pub fn change_program_state(connection: &MysqlConnection, request: &ChangeProgramStateRequest) -> Result<usize, &'static str> {
-    let program = &find(connection, request.id.as_str())?;
+    let program = &find(connection, &request.id)?;
     validate_target_state(program, request)?;
 
+    if request.target_state == ProgramTargetState::ACTIVATE {
+        gate_publication(connection, program.coach_id.as_str())?;
+    }
+
     let target_programs = programs.filter(parent_program_id.eq(request.id.as_str()));
 
     let result = match request.target_state {
@@ -154,9 +332,58 @@ pub fn change_program_state(connection: &MysqlConnection, request: &ChangeProgra
         return Err(PROGRAM_STATE_CHANGE_ERROR);
     }
 
+    forget_programs();
+    refresh_coach_profiles(connection, request.id.as_str());
+
     Ok(result.unwrap())
 }
 
+/**
+ * An admin takes the program and its peers off, whatever state they are in and
+ * whoever coaches them; the coach may activate it again once it is sorted out.
+ */
+pub fn force_deactivate(connection: &MysqlConnection, admin: &User, request: &ForceDeactivateRequest) -> Result<usize, &'static str> {
+    let program = find(connection, &request.program_id)?;
+    let the_parent_id = program.coalesce_parent_id();
+
+    let event = NewAuditEvent::from("program", the_parent_id.as_str(), "force_deactivate", admin.id.as_str()).with_reason(request.reason.trim());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let updated = diesel::update(programs.filter(parent_program_id.eq(the_parent_id.as_str()))).set(active.eq(false)).execute(connection)?;
+        audit::record(connection, &event)?;
+
+        Ok(updated)
+    });
+
+    let updated = result.map_err(|_| PROGRAM_STATE_CHANGE_ERROR)?;
+
+    forget_programs();
+    refresh_coach_profiles(connection, the_parent_id.as_str());
+
+    Ok(updated)
+}
+
+/**
+ * The coaches of the program may have gained or lost their only active program.
+ */
+fn refresh_coach_profiles(connection: &MysqlConnection, the_parent_id: &str) {
+    let coach_ids: QueryResult<Vec<String>> = programs.filter(parent_program_id.eq(the_parent_id)).select(programs::coach_id).load(connection);
+
+    let coach_ids = match coach_ids {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("Unable to find the coaches of the program {}: {}", the_parent_id, e);
+            return;
+        }
+    };
+
+    for the_coach_id in coach_ids {
+        if let Err(e) = refresh_completeness(connection, the_coach_id.as_str()) {
+            eprintln!("Unable to refresh the profile completeness of the coach {}: {}", the_coach_id, e);
+        }
+    }
+}
+
 fn validate_target_state(program: &Program, request: &ChangeProgramStateRequest) -> Result<bool, &'static str> {
     if !program.is_parent {
         return Err(PROGRAM_STATE_CHANGE_ERROR);
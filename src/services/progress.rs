@@ -0,0 +1,69 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Datetime, Varchar};
+
+use crate::commons::util;
+use crate::models::progress::ProgressCounts;
+
+const PROGRESS_COUNTS_ERROR: &str = "Unable to count the progress of the enrollment.";
+
+// The CASE follows Task::state, in the same order of precedence.
+const PROGRESS_COUNTS: &str = "
+SELECT
+    t.tasks_planned, t.tasks_due, t.tasks_in_progress, t.tasks_delayed, t.tasks_responded, t.tasks_done, t.tasks_cancelled,
+    o.objectives_planned, o.objectives_done,
+    s.sessions_completed,
+    b.observations
+FROM
+    (SELECT
+        CAST(COALESCE(SUM(state = 'PLANNED'), 0) AS SIGNED) AS tasks_planned,
+        CAST(COALESCE(SUM(state = 'DUE'), 0) AS SIGNED) AS tasks_due,
+        CAST(COALESCE(SUM(state = 'PROGRESS'), 0) AS SIGNED) AS tasks_in_progress,
+        CAST(COALESCE(SUM(state = 'DELAY'), 0) AS SIGNED) AS tasks_delayed,
+        CAST(COALESCE(SUM(state = 'RESPONDED'), 0) AS SIGNED) AS tasks_responded,
+        CAST(COALESCE(SUM(state = 'DONE'), 0) AS SIGNED) AS tasks_done,
+        CAST(COALESCE(SUM(state = 'CANCELLED'), 0) AS SIGNED) AS tasks_cancelled
+     FROM
+        (SELECT
+            CASE
+                WHEN cancelled_at IS NOT NULL THEN 'CANCELLED'
+                WHEN actual_end_date IS NOT NULL THEN 'DONE'
+                WHEN responded_date IS NOT NULL THEN 'RESPONDED'
+                WHEN COALESCE(revised_end_date, original_end_date) < ? THEN 'DELAY'
+                WHEN actual_start_date IS NOT NULL THEN 'PROGRESS'
+                WHEN COALESCE(revised_start_date, original_start_date) < ? THEN 'DUE'
+                ELSE 'PLANNED'
+            END AS state
+         FROM tasks
+         WHERE enrollment_id = ?) task_states) t
+CROSS JOIN
+    (SELECT
+        COUNT(*) AS objectives_planned,
+        CAST(COALESCE(SUM(actual_end_date IS NOT NULL), 0) AS SIGNED) AS objectives_done
+     FROM objectives
+     WHERE enrollment_id = ?) o
+CROSS JOIN
+    (SELECT COUNT(*) AS sessions_completed
+     FROM sessions
+     WHERE enrollment_id = ? AND actual_end_date IS NOT NULL AND cancelled_at IS NULL) s
+CROSS JOIN
+    (SELECT COUNT(*) AS observations
+     FROM observations
+     WHERE enrollment_id = ?) b";
+
+/**
+ * The counts of the enrollment in one round trip; the derived tables always give
+ * a row, with zeros for an enrollment with nothing yet.
+ */
+pub fn counts_of(connection: &MysqlConnection, the_enrollment_id: &str) -> Result<ProgressCounts, &'static str> {
+    let now = util::now();
+
+    diesel::sql_query(PROGRESS_COUNTS)
+        .bind::<Datetime, _>(now)
+        .bind::<Datetime, _>(now)
+        .bind::<Varchar, _>(the_enrollment_id)
+        .bind::<Varchar, _>(the_enrollment_id)
+        .bind::<Varchar, _>(the_enrollment_id)
+        .bind::<Varchar, _>(the_enrollment_id)
+        .get_result(connection)
+        .map_err(|_| PROGRESS_COUNTS_ERROR)
+}
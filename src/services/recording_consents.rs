@@ -0,0 +1,159 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use crate::commons::ids::UserId;
+use crate::models::conferences::Conference;
+use crate::models::notifications::{NewNotification, RECORDING_DECLINED};
+use crate::models::recording_consents::{
+    AttachRecordingRequest, ConferenceRecording, ConsentCriteria, ConsentDecision, ConsentRequest, ConsentRow, ConsentSheet, NewConferenceRecording, NewRecordingConsent, RecordingConsent,
+};
+use crate::models::session_users::SessionUser;
+use crate::models::sessions::Session;
+use crate::models::users::User;
+
+use crate::services::notifications::notify;
+use crate::services::programs;
+use crate::services::users;
+
+use crate::schema::conference_recordings;
+use crate::schema::conferences;
+use crate::schema::recording_consents;
+use crate::schema::session_users;
+use crate::schema::sessions;
+use crate::schema::users as users_table;
+
+const PARTICIPANT_NOT_FOUND: &str = "Unable to find the participant of the session.";
+const NOT_A_CONFERENCE: &str = "Only the participants of a conference are asked for a recording consent.";
+const CONFERENCE_NOT_FOUND: &str = "Unable to find the conference.";
+const CONFERENCE_CLOSED: &str = "The conference is either cancelled or completed.";
+const NOT_THE_HOST: &str = "Only the coach hosting the conference may do this.";
+const CONSENT_SAVE_ERROR: &str = "Unable to save the recording consent.";
+const CONSENTS_ERROR: &str = "Unable to fetch the recording consents.";
+const CONSENTS_MISSING: &str = "A recording is attached only when every participant has consented to it.";
+const RECORDING_SAVE_ERROR: &str = "Unable to attach the recording.";
+
+/**
+ * A participant may change the decision until a recording is attached; the host
+ * is told whenever a participant declines.
+ */
+pub fn give_consent(connection: &MysqlConnection, request: &ConsentRequest) -> Result<RecordingConsent, &'static str> {
+    let session_user: SessionUser = session_users::table
+        .filter(session_users::id.eq(request.session_user_id.as_str()))
+        .first(connection)
+        .map_err(|_| PARTICIPANT_NOT_FOUND)?;
+
+    let participant = users::find(connection, &UserId::from(session_user.user_id.as_str()))?;
+
+    let session: Session = sessions::table.filter(sessions::id.eq(session_user.session_id.as_str())).first(connection).map_err(|_| PARTICIPANT_NOT_FOUND)?;
+
+    let the_conference_id = session.conference_id.ok_or(NOT_A_CONFERENCE)?;
+    let conference = find_open_conference(connection, the_conference_id.as_str())?;
+    let program = programs::find(connection, &conference.program_id)?;
+
+    let consent = NewRecordingConsent::from(&session_user, conference.id.as_str(), request.decision);
+
+    let mut notices: Vec<NewNotification> = Vec::new();
+    if request.decision == ConsentDecision::DECLINE && participant.id != program.coach_id {
+        let subject = format!("{} declines the recording of {}", participant.full_name, conference.name);
+        notices.push(NewNotification::new(program.coach_id.as_str(), RECORDING_DECLINED, subject, conference.id.as_str()));
+    }
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::replace_into(recording_consents::table).values(&consent).execute(connection)?;
+
+        notify(connection, &notices)
+    });
+
+    if result.is_err() {
+        return Err(CONSENT_SAVE_ERROR);
+    }
+
+    recording_consents::table
+        .filter(recording_consents::session_user_id.eq(session_user.id.as_str()))
+        .first(connection)
+        .map_err(|_| CONSENT_SAVE_ERROR)
+}
+
+pub fn get_consents(connection: &MysqlConnection, criteria: &ConsentCriteria) -> Result<ConsentSheet, &'static str> {
+    let conference = find_hosted_conference(connection, criteria.conference_id.as_str(), &criteria.coach_id)?;
+
+    consent_sheet(connection, &conference)
+}
+
+/**
+ * Refused while any participant of the conference is yet to consent or has declined.
+ */
+pub fn attach_recording(connection: &MysqlConnection, request: &AttachRecordingRequest) -> Result<ConferenceRecording, &'static str> {
+    let conference = find_hosted_conference(connection, request.conference_id.as_str(), &request.coach_id)?;
+
+    let sheet = consent_sheet(connection, &conference)?;
+
+    if sheet.rows.is_empty() || sheet.missing_count() > 0 {
+        return Err(CONSENTS_MISSING);
+    }
+
+    let recording = NewConferenceRecording::from(request);
+
+    diesel::insert_into(conference_recordings::table).values(&recording).execute(connection).map_err(|_| RECORDING_SAVE_ERROR)?;
+
+    conference_recordings::table
+        .filter(conference_recordings::id.eq(recording.id.as_str()))
+        .first(connection)
+        .map_err(|_| RECORDING_SAVE_ERROR)
+}
+
+/**
+ * The participants are the users of the sessions of the conference that are not cancelled.
+ */
+fn consent_sheet(connection: &MysqlConnection, conference: &Conference) -> Result<ConsentSheet, &'static str> {
+    let participants: Vec<(SessionUser, User)> = session_users::table
+        .inner_join(users_table::table)
+        .inner_join(sessions::table)
+        .filter(sessions::conference_id.eq(conference.id.as_str()))
+        .filter(sessions::cancelled_at.is_null())
+        .select((session_users::all_columns, users_table::all_columns))
+        .order_by(users_table::full_name.asc())
+        .load(connection)
+        .map_err(|_| CONSENTS_ERROR)?;
+
+    let consents: Vec<RecordingConsent> = recording_consents::table
+        .filter(recording_consents::conference_id.eq(conference.id.as_str()))
+        .load(connection)
+        .map_err(|_| CONSENTS_ERROR)?;
+
+    let mut consent_of: HashMap<String, RecordingConsent> = consents.into_iter().map(|consent| (consent.session_user_id.to_owned(), consent)).collect();
+
+    let rows = participants
+        .into_iter()
+        .map(|(session_user, user)| {
+            let consent = consent_of.remove(&session_user.id);
+            ConsentRow { session_user, user, consent }
+        })
+        .collect();
+
+    Ok(ConsentSheet { rows })
+}
+
+fn find_open_conference(connection: &MysqlConnection, the_id: &str) -> Result<Conference, &'static str> {
+    let conference: Conference = conferences::table.filter(conferences::id.eq(the_id)).first(connection).map_err(|_| CONFERENCE_NOT_FOUND)?;
+
+    if conference.cancelled_at.is_some() || conference.actual_end_date.is_some() {
+        return Err(CONFERENCE_CLOSED);
+    }
+
+    Ok(conference)
+}
+
+fn find_hosted_conference(connection: &MysqlConnection, the_id: &str, the_coach_id: &UserId) -> Result<Conference, &'static str> {
+    let conference: Conference = conferences::table.filter(conferences::id.eq(the_id)).first(connection).map_err(|_| CONFERENCE_NOT_FOUND)?;
+
+    let program = programs::find(connection, &conference.program_id)?;
+
+    if program.coach_id != *the_coach_id {
+        return Err(NOT_THE_HOST);
+    }
+
+    users::find(connection, the_coach_id)?;
+
+    Ok(conference)
+}
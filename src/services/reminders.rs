@@ -0,0 +1,110 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::commons::util;
+use crate::models::correspondences::{MailOut, MailRecipient};
+use crate::models::notifications::{NewNotification, SESSION_REMINDER};
+use crate::models::reminders::{reminder_subject, NewSessionReminder, ReminderWindow};
+use crate::models::sessions::Session;
+use crate::models::user_artifacts::artifact_id;
+use crate::models::users::User;
+
+use crate::services::correspondences::queue_mails;
+use crate::services::notifications::notify;
+
+use crate::schema::programs;
+use crate::schema::session_reminders;
+use crate::schema::session_users;
+use crate::schema::sessions;
+use crate::schema::users;
+
+const BATCH_SIZE: i64 = 200;
+
+const REMINDERS_ERROR: &str = "Unable to read the sessions to remind.";
+
+/**
+ * The "session-reminders" job. The people of the sessions starting within the
+ * lead of the window, and not yet reminded of that start, get a notification and
+ * a mail; see reminders.
+ */
+pub fn send_reminders(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let now = util::now();
+    let horizon = ReminderWindow::from_env().horizon(now);
+
+    let due: Vec<(Session, String)> = sessions::table
+        .inner_join(programs::table)
+        .filter(sessions::is_request.eq(false))
+        .filter(sessions::cancelled_at.is_null())
+        .filter(sessions::expired_at.is_null())
+        .filter(sessions::actual_start_date.is_null())
+        .filter(sessions::actual_end_date.is_null())
+        .filter(
+            sessions::revised_start_date
+                .between(now, horizon)
+                .or(sessions::revised_start_date.is_null().and(sessions::original_start_date.between(now, horizon))),
+        )
+        .select((sessions::all_columns, programs::coach_id))
+        .order_by(sessions::original_start_date.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| REMINDERS_ERROR)?;
+
+    let mut reminded = 0;
+
+    for (session, coach_id) in &due {
+        match remind(connection, session, coach_id, now) {
+            Ok(count) => reminded += count,
+            Err(e) => eprintln!("Unable to remind the people of the session {}: {}", session.id, e),
+        }
+    }
+
+    Ok(reminded)
+}
+
+/**
+ * A person is marked before the notices are written, all in one transaction, so
+ * that a person marked is a person told.
+ */
+fn remind(connection: &MysqlConnection, session: &Session, coach_id: &str, now: NaiveDateTime) -> QueryResult<usize> {
+    let starts_at = session.revised_start_date.unwrap_or(session.original_start_date);
+    let event_id = artifact_id(session);
+    let subject = reminder_subject(session.name.as_str(), starts_at, now);
+
+    let people: Vec<User> = session_users::table
+        .inner_join(users::table)
+        .filter(session_users::session_id.eq(session.id.as_str()))
+        .select(users::all_columns)
+        .load(connection)?;
+
+    connection.transaction::<_, diesel::result::Error, _>(|| {
+        let mut notices: Vec<NewNotification> = Vec::new();
+        let mut mails: Vec<(MailOut, Vec<MailRecipient>)> = Vec::new();
+
+        for user in &people {
+            let mark = NewSessionReminder {
+                event_id: event_id.to_owned(),
+                user_id: user.id.to_string(),
+                starts_at,
+            };
+
+            if diesel::insert_or_ignore_into(session_reminders::table).values(&mark).execute(connection)? == 0 {
+                continue;
+            }
+
+            notices.push(NewNotification::new(user.id.as_str(), SESSION_REMINDER, subject.to_owned(), session.id.as_str()));
+
+            let mail_out = MailOut::for_session_reminder(session, coach_id, user, subject.as_str());
+            let recipients = MailRecipient::build_to(&[user], mail_out.id.as_str());
+            mails.push((mail_out, recipients));
+        }
+
+        if notices.is_empty() {
+            return Ok(0);
+        }
+
+        notify(connection, &notices)?;
+        queue_mails(connection, mails).map_err(|_| diesel::result::Error::RollbackTransaction)?;
+
+        Ok(notices.len())
+    })
+}
@@ -0,0 +1,133 @@
+use diesel::prelude::*;
+
+use crate::commons::chassis::{Page, Window};
+use crate::models::coach_members::{get_coach_member_page, MemberRow};
+use crate::models::enrollments::Enrollment;
+use crate::models::programs::Program;
+use crate::models::saved_filters::{NewSavedFilter, NewSavedFilterRequest, SavedFilter, SavedFilterCriteria, UpdateSavedFilterRequest};
+use crate::models::tasks::Task;
+
+use crate::services::tasks::get_task_page;
+use crate::services::users::find_coach_by_id;
+
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::saved_filters;
+use crate::schema::saved_filters::dsl::*;
+
+const FILTER_NOT_FOUND: &str = "Unable to find the saved filter.";
+const DUPLICATE_FILTER: &str = "A filter with the same name is already saved for this list.";
+const FILTER_SAVE_ERROR: &str = "Unable to save the filter.";
+const FILTER_DELETE_ERROR: &str = "Unable to delete the filter.";
+const FOREIGN_ENROLLMENT: &str = "The enrollment in the filter does not belong to the programs of the coach.";
+const LIST_ERROR: &str = "Unable to apply the saved filter.";
+
+pub fn create_saved_filter(connection: &MysqlConnection, request: &NewSavedFilterRequest) -> Result<SavedFilter, &'static str> {
+    let coach = find_coach_by_id(connection, request.coach_id.as_str())?;
+
+    let new_filter = NewSavedFilter::from(request);
+
+    if is_duplicate(connection, coach.id.as_str(), new_filter.target.as_str(), new_filter.name.as_str(), "") {
+        return Err(DUPLICATE_FILTER);
+    }
+
+    let result = diesel::insert_into(saved_filters).values(&new_filter).execute(connection);
+
+    if result.is_err() {
+        return Err(FILTER_SAVE_ERROR);
+    }
+
+    find_owned(connection, new_filter.id.as_str(), coach.id.as_str())
+}
+
+pub fn update_saved_filter(connection: &MysqlConnection, request: &UpdateSavedFilterRequest) -> Result<SavedFilter, &'static str> {
+    let filter = find_owned(connection, request.id.as_str(), request.coach_id.as_str())?;
+
+    filter.check_criteria(request.criteria.as_str())?;
+
+    let new_name = request.name.trim();
+
+    if is_duplicate(connection, filter.coach_id.as_str(), filter.target.as_str(), new_name, filter.id.as_str()) {
+        return Err(DUPLICATE_FILTER);
+    }
+
+    let result = diesel::update(saved_filters.filter(saved_filters::id.eq(filter.id.as_str())))
+        .set((name.eq(new_name), criteria.eq(request.criteria.as_str())))
+        .execute(connection);
+
+    if result.is_err() {
+        return Err(FILTER_SAVE_ERROR);
+    }
+
+    find_owned(connection, filter.id.as_str(), filter.coach_id.as_str())
+}
+
+pub fn delete_saved_filter(connection: &MysqlConnection, request: &SavedFilterCriteria) -> Result<usize, &'static str> {
+    let filter = find_owned(connection, request.id.as_str(), request.coach_id.as_str())?;
+
+    let result = diesel::delete(saved_filters.filter(saved_filters::id.eq(filter.id.as_str()))).execute(connection);
+
+    if result.is_err() {
+        return Err(FILTER_DELETE_ERROR);
+    }
+
+    Ok(result.unwrap())
+}
+
+pub fn get_saved_filters(connection: &MysqlConnection, the_coach_id: &str) -> QueryResult<Vec<SavedFilter>> {
+    saved_filters
+        .filter(coach_id.eq(the_coach_id))
+        .order_by((target.asc(), name.asc()))
+        .load(connection)
+}
+
+pub fn get_filtered_members(connection: &MysqlConnection, request: &SavedFilterCriteria, window: &Window) -> Result<Page<MemberRow>, &'static str> {
+    let filter = find_owned(connection, request.id.as_str(), request.coach_id.as_str())?;
+
+    let coach_criteria = filter.as_coach_criteria()?;
+
+    get_coach_member_page(connection, coach_criteria, window).map_err(|_| LIST_ERROR)
+}
+
+pub fn get_filtered_tasks(connection: &MysqlConnection, request: &SavedFilterCriteria, window: &Window) -> Result<Page<Task>, &'static str> {
+    let filter = find_owned(connection, request.id.as_str(), request.coach_id.as_str())?;
+
+    let plan_criteria = filter.as_plan_criteria()?;
+
+    let owned: QueryResult<(Enrollment, Program)> = enrollments::table
+        .inner_join(programs::table)
+        .filter(enrollments::id.eq(plan_criteria.enrollment_id.as_str()))
+        .filter(programs::coach_id.eq(filter.coach_id.as_str()))
+        .first(connection);
+
+    if owned.is_err() {
+        return Err(FOREIGN_ENROLLMENT);
+    }
+
+    get_task_page(connection, plan_criteria, window).map_err(|_| LIST_ERROR)
+}
+
+// A filter is visible only to the coach who saved it.
+fn find_owned(connection: &MysqlConnection, the_id: &str, the_coach_id: &str) -> Result<SavedFilter, &'static str> {
+    let result = saved_filters
+        .filter(saved_filters::id.eq(the_id))
+        .filter(coach_id.eq(the_coach_id))
+        .first(connection);
+
+    if result.is_err() {
+        return Err(FILTER_NOT_FOUND);
+    }
+
+    Ok(result.unwrap())
+}
+
+fn is_duplicate(connection: &MysqlConnection, the_coach_id: &str, the_target: &str, the_name: &str, except_id: &str) -> bool {
+    let result: QueryResult<SavedFilter> = saved_filters
+        .filter(coach_id.eq(the_coach_id))
+        .filter(target.eq(the_target))
+        .filter(name.eq(the_name))
+        .filter(saved_filters::id.ne(except_id))
+        .first(connection);
+
+    result.is_ok()
+}
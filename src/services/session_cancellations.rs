@@ -0,0 +1,120 @@
+use diesel::prelude::*;
+use std::collections::HashSet;
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::ids::{SessionId, UserId};
+use crate::commons::util;
+
+use crate::models::audit_events::NewAuditEvent;
+use crate::models::notifications::{NewNotification, SESSION_CANCELLED};
+use crate::models::session_cancellations::{cancellable, BulkCancelRequest, CancellationPreview};
+use crate::models::sessions::Session;
+
+use crate::services::audit;
+use crate::services::notifications::notify;
+use crate::services::sessions::follow_cancellations;
+use crate::services::users;
+
+use crate::schema::conferences;
+use crate::schema::programs;
+use crate::schema::session_users;
+use crate::schema::sessions;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const SESSIONS_ERROR: &str = "Unable to fetch the sessions of the coach.";
+const CANCEL_ERROR: &str = "Unable to cancel the sessions. Nothing was cancelled.";
+
+/**
+ * The conferences among the sessions are cancelled along with them. The members
+ * are notified in the app and, for the one to one sessions, mailed as well.
+ */
+pub fn cancel_sessions(connection: &MysqlConnection, caller: Option<&UserId>, request: &BulkCancelRequest) -> Result<CancellationPreview, &'static str> {
+    let coach_id = caller.ok_or(NOT_SIGNED_IN)?;
+
+    authorize(connection, Some(coach_id), Target::Coach(coach_id.as_str()), &[Role::Coach])?;
+
+    let coach = users::find(connection, coach_id)?;
+    let (from, to) = request.range()?;
+
+    let program_ids: Vec<String> = programs::table
+        .filter(programs::coach_id.eq(coach.id.as_str()))
+        .select(programs::id)
+        .load(connection)
+        .map_err(|_| SESSIONS_ERROR)?;
+
+    let candidates: Vec<Session> = sessions::table
+        .filter(sessions::program_id.eq_any(&program_ids))
+        .filter(sessions::cancelled_at.is_null())
+        .filter(sessions::actual_start_date.is_null())
+        .order_by(sessions::original_start_date.asc())
+        .load(connection)
+        .map_err(|_| SESSIONS_ERROR)?;
+
+    let picked = cancellable(candidates, from, to, util::now());
+    let session_ids: Vec<SessionId> = picked.iter().map(|session| session.id.to_owned()).collect();
+
+    let members: Vec<(SessionId, UserId)> = session_users::table
+        .filter(session_users::session_id.eq_any(&session_ids))
+        .filter(session_users::user_type.eq(util::MEMBER))
+        .select((session_users::session_id, session_users::user_id))
+        .load(connection)
+        .map_err(|_| SESSIONS_ERROR)?;
+
+    let reason = request.reason.trim();
+
+    let notices: Vec<NewNotification> = members
+        .iter()
+        .filter_map(|(the_session_id, member_id)| {
+            let session = picked.iter().find(|session| &session.id == the_session_id)?;
+            let subject = format!("{} cancelled the session {}: {}", coach.full_name, session.name, reason);
+            Some(NewNotification::new(member_id.as_str(), SESSION_CANCELLED, subject, the_session_id.as_str()))
+        })
+        .collect();
+
+    let mut preview = CancellationPreview {
+        sessions: picked,
+        notified: notices.len() as i32,
+        applied: false,
+    };
+
+    if request.dry_run || preview.sessions.is_empty() {
+        return Ok(preview);
+    }
+
+    let conference_ids: HashSet<&str> = preview.sessions.iter().filter_map(|session| session.conference_id.as_deref()).collect();
+    let conference_ids: Vec<&str> = conference_ids.into_iter().collect();
+
+    let events: Vec<NewAuditEvent> = session_ids
+        .iter()
+        .map(|the_id| NewAuditEvent::from("session", the_id.as_str(), "bulk_cancel", coach.id.as_str()).with_reason(reason))
+        .collect();
+
+    let now = util::now();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(sessions::table.filter(sessions::id.eq_any(&session_ids)))
+            .set((sessions::cancelled_at.eq(now), sessions::closing_notes.eq(reason)))
+            .execute(connection)?;
+
+        diesel::update(conferences::table.filter(conferences::id.eq_any(&conference_ids)).filter(conferences::cancelled_at.is_null()))
+            .set((conferences::cancelled_at.eq(now), conferences::closing_notes.eq(reason)))
+            .execute(connection)?;
+
+        for event in &events {
+            audit::record(connection, event)?;
+        }
+
+        notify(connection, &notices)
+    });
+
+    if result.is_err() {
+        return Err(CANCEL_ERROR);
+    }
+
+    preview.sessions = sessions::table.filter(sessions::id.eq_any(&session_ids)).load(connection).map_err(|_| SESSIONS_ERROR)?;
+    preview.applied = true;
+
+    follow_cancellations(connection, &preview.sessions);
+
+    Ok(preview)
+}
@@ -0,0 +1,225 @@
+use diesel::prelude::*;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::ids::UserId;
+use crate::commons::util;
+
+use crate::models::audit_events::NewAuditEvent;
+use crate::models::session_merges::{board_target, MergePreview, MergeSessionsRequest};
+use crate::models::session_users::SessionUser;
+use crate::models::sessions::Session;
+use crate::models::user_artifacts::board_dir;
+
+use crate::services::audit;
+use crate::services::coach_stats;
+use crate::services::programs;
+use crate::services::sessions;
+use crate::storage::storage;
+
+use crate::schema::board_annotations;
+use crate::schema::guest_links;
+use crate::schema::session_notes;
+use crate::schema::session_users;
+use crate::schema::sessions as sessions_table;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const NOT_OF_ONE_ENROLLMENT: &str = "The sessions should be of the same enrollment.";
+const CONFERENCE_MERGE: &str = "The conference sessions cannot be merged.";
+const ALREADY_CANCELLED: &str = "A cancelled session cannot be merged.";
+const DUPLICATE_HELD: &str = "The duplicate session has already started; please close it instead.";
+const PARTICIPANTS_DIFFER: &str = "The sessions do not have the same participants.";
+const MERGE_READ_ERROR: &str = "Unable to read the duplicate session.";
+const MERGE_ERROR: &str = "Unable to merge the sessions. Nothing was changed.";
+
+/**
+ * The notes and the guest links are moved in one transaction along with the
+ * cancellation of the duplicate; the board files are moved after it, and a
+ * file that could not be moved is logged and left in place. The caller should
+ * be the coach of both the sessions.
+ */
+pub fn merge_sessions(connection: &MysqlConnection, caller: Option<&UserId>, request: &MergeSessionsRequest) -> Result<MergePreview, &'static str> {
+    let coach_id = caller.ok_or(NOT_SIGNED_IN)?;
+
+    authorize(connection, Some(coach_id), Target::Session(request.session_id.as_str()), &[Role::Coach])?;
+    authorize(connection, Some(coach_id), Target::Session(request.duplicate_id.as_str()), &[Role::Coach])?;
+
+    let session = sessions::find(connection, &request.session_id)?;
+    let duplicate = sessions::find(connection, &request.duplicate_id)?;
+
+    let program = programs::find(connection, &session.program_id)?;
+
+    check_mergeable(&session, &duplicate)?;
+
+    let pairs = pair_participants(connection, &session, &duplicate)?;
+
+    let notes: i64 = session_notes::table
+        .filter(session_notes::session_id.eq(duplicate.id.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| MERGE_READ_ERROR)?;
+
+    let links: i64 = guest_links::table
+        .filter(guest_links::session_id.eq(duplicate.id.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| MERGE_READ_ERROR)?;
+
+    let mut taken: HashSet<String> = board_names(board_dir(&session)).into_iter().collect();
+    let moves: Vec<(String, String)> = board_names(board_dir(&duplicate))
+        .into_iter()
+        .map(|name| {
+            let target = board_target(name.as_str(), &taken, duplicate.id.as_str());
+            taken.insert(target.to_owned());
+            (name, target)
+        })
+        .collect();
+
+    let mut preview = MergePreview {
+        session,
+        duplicate_id: duplicate.id.to_string(),
+        notes: notes as i32,
+        boards: moves.iter().map(|(_, target)| target.to_owned()).collect(),
+        guest_links: links as i32,
+        applied: false,
+    };
+
+    if request.dry_run {
+        return Ok(preview);
+    }
+
+    let kept_id = preview.session.id.to_owned();
+    let closing = format!("Merged into the session {}.", kept_id);
+    let event = NewAuditEvent::from("session", duplicate.id.as_str(), "merge", coach_id.as_str())
+        .with_states(&json!({ "session_id": duplicate.id }), &json!({ "session_id": kept_id }));
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        for (from, to) in &pairs {
+            diesel::update(session_notes::table.filter(session_notes::session_user_id.eq(from.as_str())))
+                .set((session_notes::session_id.eq(kept_id.as_str()), session_notes::session_user_id.eq(to.as_str())))
+                .execute(connection)?;
+        }
+
+        diesel::update(guest_links::table.filter(guest_links::session_id.eq(duplicate.id.as_str())))
+            .set(guest_links::session_id.eq(kept_id.as_str()))
+            .execute(connection)?;
+
+        for (name, target) in &moves {
+            diesel::update(
+                board_annotations::table
+                    .filter(board_annotations::board_id.eq(duplicate.id.as_str()))
+                    .filter(board_annotations::board_name.eq(name.as_str())),
+            )
+            .set((board_annotations::board_id.eq(kept_id.as_str()), board_annotations::board_name.eq(target.as_str())))
+            .execute(connection)?;
+        }
+
+        diesel::update(sessions_table::table.filter(sessions_table::id.eq(duplicate.id.as_str())))
+            .set((sessions_table::cancelled_at.eq(util::now()), sessions_table::closing_notes.eq(closing.as_str())))
+            .execute(connection)?;
+
+        audit::record(connection, &event)
+    });
+
+    if result.is_err() {
+        return Err(MERGE_ERROR);
+    }
+
+    move_boards(&board_dir(&duplicate), &board_dir(&preview.session), &moves);
+
+    if let Err(e) = coach_stats::mark_program(connection, program.id.as_str()) {
+        eprintln!("Unable to queue the summary of the program {}: {}", program.id, e);
+    }
+
+    preview.session = sessions::find(connection, &kept_id)?;
+    preview.applied = true;
+
+    Ok(preview)
+}
+
+fn check_mergeable(session: &Session, duplicate: &Session) -> Result<(), &'static str> {
+    if session.is_conference() || duplicate.is_conference() {
+        return Err(CONFERENCE_MERGE);
+    }
+
+    if session.enrollment_id != duplicate.enrollment_id {
+        return Err(NOT_OF_ONE_ENROLLMENT);
+    }
+
+    if session.cancelled_at.is_some() || duplicate.cancelled_at.is_some() {
+        return Err(ALREADY_CANCELLED);
+    }
+
+    if duplicate.actual_start_date.is_some() {
+        return Err(DUPLICATE_HELD);
+    }
+
+    Ok(())
+}
+
+/**
+ * Each participant of the duplicate against the same participant of the session kept.
+ */
+fn pair_participants(connection: &MysqlConnection, session: &Session, duplicate: &Session) -> Result<Vec<(String, String)>, &'static str> {
+    let load = |the_session_id: &str| -> Result<Vec<SessionUser>, &'static str> {
+        session_users::table
+            .filter(session_users::session_id.eq(the_session_id))
+            .load(connection)
+            .map_err(|_| MERGE_READ_ERROR)
+    };
+
+    let kept = load(session.id.as_str())?;
+    let duplicated = load(duplicate.id.as_str())?;
+
+    duplicated
+        .iter()
+        .map(|from| {
+            kept.iter()
+                .find(|to| to.user_id == from.user_id)
+                .map(|to| (from.id.to_owned(), to.id.to_owned()))
+                .ok_or(PARTICIPANTS_DIFFER)
+        })
+        .collect()
+}
+
+// A session without boards has no directory.
+fn board_names(dir_name: PathBuf) -> Vec<String> {
+    let entries = match fs::read_dir(dir_name) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|kind| kind.is_file()).unwrap_or(false))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+
+    names.sort();
+
+    names
+}
+
+fn move_boards(from_dir: &Path, to_dir: &Path, moves: &[(String, String)]) {
+    if moves.is_empty() {
+        return;
+    }
+
+    if let Err(e) = fs::create_dir_all(to_dir) {
+        eprintln!("Unable to create the board directory {:?}: {}", to_dir, e);
+        return;
+    }
+
+    // A board kept elsewhere is brought down, moved, and kept again under its new name.
+    for (name, target) in moves {
+        let (from, to) = (from_dir.join(name), to_dir.join(target));
+
+        let moved = storage().open(&from).and_then(|_| fs::rename(&from, &to)).and_then(|_| storage().keep(&to)).and_then(|_| storage().remove(&from));
+        if let Err(e) = moved {
+            eprintln!("Unable to move the board {:?} to {:?}: {}", from, to, e);
+        }
+    }
+}
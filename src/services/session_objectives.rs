@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::enrollments::Enrollment;
+use crate::models::objectives::Objective;
+use crate::models::session_objectives::{coverage, EnrollmentProgress, ProgressCriteria, SessionTag, TagSessionRequest};
+
+use crate::services::goal_boards;
+use crate::services::programs;
+use crate::services::progress;
+use crate::services::sessions;
+
+use crate::schema::enrollments;
+use crate::schema::objectives;
+use crate::schema::session_objectives;
+use crate::schema::sessions as sessions_table;
+
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const NOT_A_PARTY: &str = "Only the coach and the member of the enrollment may see its progress.";
+const SESSION_CANCELLED: &str = "A cancelled session may not be tagged.";
+const FOREIGN_OBJECTIVE: &str = "The objectives should belong to the enrollment of the session.";
+const TAG_ERROR: &str = "Unable to tag the objectives of the session.";
+const PROGRESS_ERROR: &str = "Unable to fetch the progress of the enrollment.";
+
+/**
+ * The tags of the session are replaced as a whole.
+ */
+pub fn tag_session(connection: &MysqlConnection, caller: Option<&UserId>, request: &TagSessionRequest) -> Result<EnrollmentProgress, &'static str> {
+    authorize(connection, caller, Target::Session(request.session_id.as_str()), &[Role::Coach])?;
+
+    let session = sessions::find(connection, &request.session_id)?;
+
+    if session.cancelled_at.is_some() {
+        return Err(SESSION_CANCELLED);
+    }
+
+    let given: Vec<&str> = request.tags.iter().map(|tag| tag.objective_id.as_str()).collect();
+    let known: i64 = objectives::table
+        .filter(objectives::enrollment_id.eq(session.enrollment_id.as_str()))
+        .filter(objectives::id.eq_any(given))
+        .count()
+        .get_result(connection)
+        .map_err(|_| TAG_ERROR)?;
+
+    if known as usize != request.tags.len() {
+        return Err(FOREIGN_OBJECTIVE);
+    }
+
+    let rows = request.to_rows();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::delete(session_objectives::table.filter(session_objectives::session_id.eq(session.id.as_str()))).execute(connection)?;
+        diesel::insert_into(session_objectives::table).values(&rows).execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(TAG_ERROR);
+    }
+
+    progress_of(connection, session.enrollment_id.as_str())
+}
+
+pub fn get_enrollment_progress(connection: &MysqlConnection, criteria: &ProgressCriteria) -> Result<EnrollmentProgress, &'static str> {
+    let enrollment: Enrollment = enrollments::table
+        .filter(enrollments::id.eq(criteria.enrollment_id.as_str()))
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    let program = programs::find(connection, &enrollment.program_id)?;
+
+    if criteria.user_id != enrollment.member_id && criteria.user_id != program.coach_id {
+        return Err(NOT_A_PARTY);
+    }
+
+    progress_of(connection, enrollment.id.as_str())
+}
+
+fn progress_of(connection: &MysqlConnection, the_enrollment_id: &str) -> Result<EnrollmentProgress, &'static str> {
+    let the_objectives: Vec<Objective> = objectives::table
+        .filter(objectives::enrollment_id.eq(the_enrollment_id))
+        .order_by(objectives::original_start_date.asc())
+        .load(connection)
+        .map_err(|_| PROGRESS_ERROR)?;
+
+    let rows: Vec<(String, Option<i32>, NaiveDateTime, Option<NaiveDateTime>)> = session_objectives::table
+        .inner_join(sessions_table::table)
+        .filter(sessions_table::enrollment_id.eq(the_enrollment_id))
+        .filter(sessions_table::cancelled_at.is_null())
+        .select((
+            session_objectives::objective_id,
+            session_objectives::rating,
+            sessions_table::original_start_date,
+            sessions_table::revised_start_date,
+        ))
+        .load(connection)
+        .map_err(|_| PROGRESS_ERROR)?;
+
+    let tags: Vec<SessionTag> = rows
+        .into_iter()
+        .map(|(objective_id, rating, original_start, revised_start)| SessionTag {
+            objective_id,
+            rating,
+            held_at: revised_start.unwrap_or(original_start),
+        })
+        .collect();
+
+    Ok(EnrollmentProgress {
+        enrollment_id: the_enrollment_id.to_owned(),
+        objectives: coverage(the_objectives, &tags, util::now()),
+        counts: progress::counts_of(connection, the_enrollment_id)?,
+        goal_board: goal_boards::board_of(connection, the_enrollment_id)?,
+    })
+}
@@ -0,0 +1,72 @@
+use diesel::prelude::*;
+
+use crate::models::session_scratchpads::{SaveScratchpadRequest, Scratchpad, ScratchpadCriteria, SessionScratchpad};
+use crate::models::sessions::Session;
+
+use crate::services::programs;
+use crate::services::sessions;
+
+use crate::schema::enrollments;
+use crate::schema::programs as programs_table;
+use crate::schema::session_scratchpads;
+use crate::schema::sessions as sessions_table;
+
+const NOT_THE_COACH: &str = "Only the coach of the session may keep its scratchpad.";
+const NOT_THE_ENROLLMENT_COACH: &str = "Only the coach of the enrollment may read its scratchpads.";
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment.";
+const SCRATCHPAD_SAVE_ERROR: &str = "Unable to save the scratchpad.";
+const SCRATCHPAD_FETCH_ERROR: &str = "Unable to fetch the scratchpads.";
+
+/**
+ * The clients call this on every autosave, so the latest text simply replaces the
+ * one before.
+ */
+pub fn save_scratchpad(connection: &MysqlConnection, request: &SaveScratchpadRequest) -> Result<Scratchpad, &'static str> {
+    let session = sessions::find(connection, &request.session_id)?;
+    let program = programs::find(connection, &session.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    diesel::replace_into(session_scratchpads::table)
+        .values(&request.to_row())
+        .execute(connection)
+        .map_err(|_| SCRATCHPAD_SAVE_ERROR)?;
+
+    session_scratchpads::table
+        .filter(session_scratchpads::session_id.eq(session.id.as_str()))
+        .select((session_scratchpads::session_id, session_scratchpads::content, session_scratchpads::updated_at))
+        .first(connection)
+        .map_err(|_| SCRATCHPAD_FETCH_ERROR)
+}
+
+/**
+ * The scratchpads over the sessions of an enrollment, the latest session first. The
+ * sessions without a scratchpad are left out.
+ */
+pub fn get_scratchpads(connection: &MysqlConnection, criteria: &ScratchpadCriteria) -> Result<Vec<SessionScratchpad>, &'static str> {
+    let coach_id: String = enrollments::table
+        .inner_join(programs_table::table)
+        .filter(enrollments::id.eq(criteria.enrollment_id.as_str()))
+        .select(programs_table::coach_id)
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    if coach_id != criteria.coach_id {
+        return Err(NOT_THE_ENROLLMENT_COACH);
+    }
+
+    let rows: Vec<(Session, Scratchpad)> = sessions_table::table
+        .inner_join(session_scratchpads::table)
+        .filter(sessions_table::enrollment_id.eq(criteria.enrollment_id.as_str()))
+        .select((
+            sessions_table::all_columns,
+            (session_scratchpads::session_id, session_scratchpads::content, session_scratchpads::updated_at),
+        ))
+        .order_by(sessions_table::original_start_date.desc())
+        .load(connection)
+        .map_err(|_| SCRATCHPAD_FETCH_ERROR)?;
+
+    Ok(rows.into_iter().map(|(session, scratchpad)| SessionScratchpad { session, scratchpad }).collect())
+}
@@ -0,0 +1,77 @@
+use diesel::prelude::*;
+
+use crate::models::session_summaries::{SaveSessionSummaryRequest, SessionSummary};
+
+use crate::services::programs;
+use crate::services::sessions;
+
+use crate::schema::session_summaries;
+use crate::schema::sessions as sessions_table;
+
+const NOT_THE_COACH: &str = "Only the coach of the session may summarize it.";
+const CANCELLED_SESSION: &str = "A cancelled session keeps the reason of its cancellation, not a summary.";
+const SUMMARY_SAVE_ERROR: &str = "Unable to save the summary.";
+const SUMMARY_FETCH_ERROR: &str = "Unable to fetch the summary.";
+
+// The coach is known by the session; the summary is read without it.
+const SUMMARY_COLUMNS: (
+    session_summaries::session_id,
+    session_summaries::went_well,
+    session_summaries::action_items,
+    session_summaries::next_steps,
+    session_summaries::updated_at,
+) = (
+    session_summaries::session_id,
+    session_summaries::went_well,
+    session_summaries::action_items,
+    session_summaries::next_steps,
+    session_summaries::updated_at,
+);
+
+/**
+ * The summary replaces the one saved before, and the closing notes of the session
+ * are filled in from it, in one go.
+ */
+pub fn save_session_summary(connection: &MysqlConnection, request: &SaveSessionSummaryRequest) -> Result<SessionSummary, &'static str> {
+    let session = sessions::find(connection, &request.session_id)?;
+    let program = programs::find(connection, &session.program_id)?;
+
+    if program.coach_id != request.coach_id {
+        return Err(NOT_THE_COACH);
+    }
+
+    if session.cancelled_at.is_some() {
+        return Err(CANCELLED_SESSION);
+    }
+
+    let row = request.to_row();
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::replace_into(session_summaries::table).values(&row).execute(connection)?;
+
+        diesel::update(sessions_table::table.filter(sessions_table::id.eq(session.id.as_str())))
+            .set(sessions_table::closing_notes.eq(row.as_closing_notes()))
+            .execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(SUMMARY_SAVE_ERROR);
+    }
+
+    session_summaries::table
+        .filter(session_summaries::session_id.eq(session.id.as_str()))
+        .select(SUMMARY_COLUMNS)
+        .first(connection)
+        .map_err(|_| SUMMARY_FETCH_ERROR)
+}
+
+/**
+ * The summaries over the sessions of an enrollment.
+ */
+pub fn get_enrollment_summaries(connection: &MysqlConnection, the_enrollment_id: &str) -> QueryResult<Vec<SessionSummary>> {
+    session_summaries::table
+        .inner_join(sessions_table::table)
+        .filter(sessions_table::enrollment_id.eq(the_enrollment_id))
+        .select(SUMMARY_COLUMNS)
+        .load(connection)
+}
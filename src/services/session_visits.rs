@@ -0,0 +1,106 @@
+use diesel::prelude::*;
+
+use crate::commons::guard::{authorize, Role, Target};
+use crate::commons::ids::{SessionId, UserId};
+use crate::commons::util;
+use crate::models::session_visits::{summarize, Attendee, NewSessionVisit, Person, SessionVisit, VisitRequest};
+
+use crate::services::sessions;
+
+use crate::schema::session_users;
+use crate::schema::session_visits;
+use crate::schema::users;
+
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+const NOT_SESSION_USER: &str = "Only the people of the session may join its conference.";
+const SESSION_CLOSED: &str = "The session is closed; its conference may not be joined.";
+const NOT_IN_CONFERENCE: &str = "The user has not joined the conference of the session.";
+const VISIT_SAVE_ERROR: &str = "Unable to record the visit to the session.";
+const ATTENDANCE_ERROR: &str = "Unable to take the attendance of the session.";
+
+/**
+ * The user joins the conference of the session. A join without a leave since,
+ * as after a dropped connection, goes on with the open visit.
+ */
+pub fn record_session_visit(connection: &MysqlConnection, caller: Option<&UserId>, request: &VisitRequest) -> Result<SessionVisit, &'static str> {
+    let visitor = caller.ok_or(NOT_SIGNED_IN)?;
+
+    authorize(connection, Some(visitor), Target::Session(request.session_id.as_str()), &[Role::Coach, Role::Member])?;
+
+    let session = sessions::find(connection, &request.session_id)?;
+
+    if session.cancelled_at.is_some() || session.expired_at.is_some() || session.actual_end_date.is_some() {
+        return Err(SESSION_CLOSED);
+    }
+
+    let count: i64 = session_users::table
+        .filter(session_users::session_id.eq(request.session_id.as_str()))
+        .filter(session_users::user_id.eq(visitor.as_str()))
+        .count()
+        .get_result(connection)
+        .map_err(|_| NOT_SESSION_USER)?;
+
+    if count == 0 {
+        return Err(NOT_SESSION_USER);
+    }
+
+    if let Ok(visit) = find_open_visit(connection, request, visitor) {
+        return Ok(visit);
+    }
+
+    diesel::insert_into(session_visits::table)
+        .values(&NewSessionVisit::from(request, visitor.as_str()))
+        .execute(connection)
+        .map_err(|_| VISIT_SAVE_ERROR)?;
+
+    find_open_visit(connection, request, visitor).map_err(|_| VISIT_SAVE_ERROR)
+}
+
+pub fn leave_session(connection: &MysqlConnection, caller: Option<&UserId>, request: &VisitRequest) -> Result<SessionVisit, &'static str> {
+    let visitor = caller.ok_or(NOT_SIGNED_IN)?;
+    let visit = find_open_visit(connection, request, visitor).map_err(|_| NOT_IN_CONFERENCE)?;
+
+    diesel::update(session_visits::table.filter(session_visits::id.eq(visit.id.as_str())))
+        .set(session_visits::left_at.eq(util::now()))
+        .execute(connection)
+        .map_err(|_| VISIT_SAVE_ERROR)?;
+
+    session_visits::table.find(visit.id.as_str()).first(connection).map_err(|_| VISIT_SAVE_ERROR)
+}
+
+fn find_open_visit(connection: &MysqlConnection, request: &VisitRequest, visitor: &UserId) -> QueryResult<SessionVisit> {
+    session_visits::table
+        .filter(session_visits::session_id.eq(request.session_id.as_str()))
+        .filter(session_visits::user_id.eq(visitor.as_str()))
+        .filter(session_visits::left_at.is_null())
+        .order_by(session_visits::joined_at.desc())
+        .first(connection)
+}
+
+/**
+ * Who of the session joined its conference, when and for how long; the coach
+ * comes first. The visits still open count until the end of the session, or
+ * until now while it goes on.
+ */
+pub fn get_attendance(connection: &MysqlConnection, the_session_id: &SessionId) -> Result<Vec<Attendee>, &'static str> {
+    let session = sessions::find(connection, the_session_id)?;
+
+    let people: Vec<(String, String, String)> = session_users::table
+        .inner_join(users::table)
+        .filter(session_users::session_id.eq(the_session_id.as_str()))
+        .select((session_users::user_id, users::full_name, session_users::user_type))
+        .order_by((session_users::user_type.asc(), users::full_name.asc()))
+        .load(connection)
+        .map_err(|_| ATTENDANCE_ERROR)?;
+
+    let visits: Vec<SessionVisit> = session_visits::table
+        .filter(session_visits::session_id.eq(the_session_id.as_str()))
+        .load(connection)
+        .map_err(|_| ATTENDANCE_ERROR)?;
+
+    let people: Vec<Person> = people.into_iter().map(|(user_id, full_name, user_type)| Person { user_id, full_name, user_type }).collect();
+
+    let until = session.actual_end_date.or(session.cancelled_at).unwrap_or_else(util::now);
+
+    Ok(summarize(people, &visits, until))
+}
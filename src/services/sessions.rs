@@ -2,22 +2,35 @@ use diesel::prelude::*;
 
 use std::collections::HashMap;
 
+use crate::commons::ids::SessionId;
+use crate::commons::transactions::in_transaction;
 use crate::commons::util;
 
+use crate::services::audit;
+use crate::services::coach_availability;
+use crate::services::coach_stats;
+
 use crate::services::correspondences::create_mail;
 use crate::services::enrollments;
+use crate::services::notifications::notify;
+use crate::services::organizations;
 use crate::services::programs;
 use crate::services::users;
+use crate::services::webhooks;
 
 use crate::services::conferences::{sync_conference_state};
 
+use crate::models::audit_events::NewAuditEvent;
 use crate::models::correspondences::{MailOut, MailRecipient};
 use crate::models::enrollments::Enrollment;
+use crate::models::notifications::{NewNotification, SESSION_READY};
+use crate::models::programs::Program;
 use crate::models::session_users::{NewSessionUser, SessionUser};
 use crate::models::sessions::{ChangeSessionStateRequest, NewSession, NewSessionRequest, Session, TargetState};
 use crate::models::users::User;
 
 use crate::schema::enrollments::dsl::*;
+use crate::schema::session_visits;
 use crate::schema::session_users::dsl::*;
 use crate::schema::sessions::dsl::*;
 use crate::schema::users::dsl::*;
@@ -30,38 +43,77 @@ const SESSION_USER_CREATION_ERROR: &str = "Unable to associate users to the sess
 const SESSION_STATE_CHANGE_PROHIBITED: &str = "The session is either cancelled or completed. Hence change of state to the session is not permitted.";
 const SESSION_UPDATE_ERROR: &str = "Unable to complete the requested action on the state";
 
+const OVERRIDE_AUDIT_ERROR: &str = "Unable to record the reason for overriding the scheduling rules.";
+
+const CANCELLED_ON_DEACTIVATION: &str = "Cancelled as the account of a participant is deactivated.";
+const CANCELLED_WITH_ENROLLMENT: &str = "Cancelled as the enrollment is cancelled.";
+
 const NOT_IN_CONFERENCE: &str = "The member is not included in the conference";
 const UNREMOVABLE_SESSION: &str = "The session is not in a removable state";
 
 pub fn create_session(connection: &MysqlConnection, request: &NewSessionRequest) -> Result<Session, &'static str> {
     // Obtain the Program
-    let program = programs::find(connection, request.program_id.as_str())?;
+    let program = programs::find(connection, &request.program_id)?;
 
     // Obtain the People (We need the User corresponds to the Coach)
-    let coach: User = users::find(connection, program.coach_id.as_str())?;
+    let coach: User = users::find(connection, &program.coach_id)?;
 
-    let member: User = users::find(connection, request.member_id.as_str())?;
+    let member: User = users::find(connection, &request.member_id)?;
 
-    let enrollment: Enrollment = enrollments::find(connection, &program, &member)?;
+    let enrollment: Enrollment = enrollments::find_active(connection, &program, &member)?;
 
     let people_involved: String = util::concat(coach.full_name.as_str(), member.full_name.as_str());
 
     // Inserting the Session
     let new_session = NewSession::from(request, enrollment.id.to_owned(), people_involved);
-    let session = insert_session(connection, &new_session)?;
 
-    // Inserting a pair of entries into the Session Users (For Coach & Member)
-    let new_session_coach = NewSessionUser::from(&session, &coach, util::COACH);
-    let new_session_member = NewSessionUser::from(&session, &member, util::MEMBER);
-    insert_session_users(connection, &new_session_coach, &new_session_member)?;
+    let violation = match organizations::scheduling_rules(connection, coach.id.as_str())?.violation(new_session.original_start_date, new_session.original_end_date, member.utc_offset) {
+        Some(rule) => Some(rule),
+        None => coach_availability::violation(connection, &coach, new_session.original_start_date, new_session.original_end_date)?,
+    };
+    if let (Some(rule), None) = (violation, request.override_reason()) {
+        return Err(rule);
+    }
+
+    // The session is not kept without the reason for breaking a rule, nor without its people.
+    let session = in_transaction(connection, SESSION_CREATION_ERROR, || {
+        let session = insert_session(connection, &new_session)?;
+
+        if let (Some(_), Some(reason)) = (violation, request.override_reason()) {
+            record_override(connection, &session, &coach, reason)?;
+        }
+
+        // Inserting a pair of entries into the Session Users (For Coach & Member)
+        let new_session_coach = NewSessionUser::from(&session, &coach, util::COACH);
+        let new_session_member = NewSessionUser::from(&session, &member, util::MEMBER);
+        insert_session_users(connection, &new_session_coach, &new_session_member)?;
+
+        Ok(session)
+    })?;
 
     enrollments::mark_as_old(connection, enrollment.id())?;
 
     create_session_mail(connection, &session, &member, &coach)?;
 
+    if let Err(e) = coach_stats::mark(connection, program.coach_id.as_str()) {
+        eprintln!("Unable to queue the summary of the coach {}: {}", program.coach_id, e);
+    }
+
     Ok(session)
 }
 
+pub fn record_override(connection: &MysqlConnection, session: &Session, coach: &User, reason: &str) -> Result<usize, &'static str> {
+    let event = NewAuditEvent::from("session", session.id.as_str(), "schedule_override", coach.id.as_str()).with_reason(reason);
+
+    let result = audit::record(connection, &event);
+
+    if result.is_err() {
+        return Err(OVERRIDE_AUDIT_ERROR);
+    }
+
+    Ok(result.unwrap())
+}
+
 pub fn find_by_conference(connection: &MysqlConnection, conf_id: &str, given_member_id: &str) -> Result<Session, &'static str> {
     
     let result: Result<(Session, Enrollment), diesel::result::Error> = sessions
@@ -86,6 +138,11 @@ pub fn remove_conference_session(connection: &MysqlConnection, conf_id: &str, gi
 
     let _session_id = session.id.as_str();
 
+    let result = diesel::delete(session_visits::table.filter(session_visits::session_id.eq(_session_id))).execute(connection);
+    if result.is_err() {
+        return Err(UNREMOVABLE_SESSION);
+    }
+
     let result = diesel::delete(session_users.filter(session_id.eq(_session_id))).execute(connection);
     if result.is_err() {
         return Err(UNREMOVABLE_SESSION);
@@ -118,17 +175,65 @@ pub fn change_session_state(connection: &MysqlConnection, request: &ChangeSessio
         do_alter_mono_session_state(connection, request)?;    
     }
    
-    let session = find(connection, &request.id.as_str())?;
+    let session = find(connection, &request.id)?;
     
     if request.target_state == TargetState::CANCEL && !session.is_conference() {
         send_session_cancel_mail(connection, &session)?;
     }
 
+    // The session is ready either way; the members still see it on their calendar.
+    if request.target_state == TargetState::READY {
+        if let Err(e) = notify_ready(connection, &session) {
+            eprintln!("Unable to notify the members of the session {}: {}", session.id, e);
+        }
+    }
+
+    if request.target_state == TargetState::DONE {
+        if let Err(e) = webhooks::session_completed(connection, &session) {
+            eprintln!("Unable to queue the webhooks of the session {}: {}", session.id, e);
+        }
+    }
+
+    if let Err(e) = coach_stats::mark_program(connection, session.program_id.as_str()) {
+        eprintln!("Unable to queue the summary of the program {}: {}", session.program_id, e);
+    }
+
     Ok(session)
 }
 
+/**
+ * The users of the session, or of every session of its conference.
+ */
+pub fn users_of(connection: &MysqlConnection, session: &Session) -> QueryResult<Vec<SessionUser>> {
+    use crate::schema::session_users as members;
+    use crate::schema::sessions as conference_sessions;
+
+    let the_session_ids: Vec<SessionId> = match session.conference_id.as_deref() {
+        Some(conf_id) => conference_sessions::table
+            .filter(conference_sessions::conference_id.eq(conf_id))
+            .select(conference_sessions::id)
+            .load(connection)?,
+        None => vec![session.id.to_owned()],
+    };
+
+    members::table.filter(members::session_id.eq_any(&the_session_ids)).load(connection)
+}
+
+// The members are told that they may join.
+fn notify_ready(connection: &MysqlConnection, session: &Session) -> QueryResult<usize> {
+    let the_members: Vec<SessionUser> = users_of(connection, session)?.into_iter().filter(|member| member.user_type == util::MEMBER).collect();
+
+    let subject = format!("The session {} is ready to join", session.name);
+    let notices: Vec<NewNotification> = the_members
+        .iter()
+        .map(|member| NewNotification::new(member.user_id.as_str(), SESSION_READY, subject.to_owned(), member.session_id.as_str()))
+        .collect();
+
+    notify(connection, &notices)
+}
+
 fn can_change_session_state(connection: &MysqlConnection, request: &ChangeSessionStateRequest) -> Result<Session, &'static str> {
-    let the_id = &request.id.as_str();
+    let the_id = &request.id;
 
     let session = find(connection, the_id)?;
 
@@ -194,10 +299,10 @@ pub fn insert_session(connection: &MysqlConnection, new_session: &NewSession) ->
         return Err(SESSION_CREATION_ERROR);
     }
 
-    find(connection, new_session.id.as_str())
+    find(connection, &new_session.id)
 }
 
-pub fn find(connection: &MysqlConnection, the_id: &str) -> Result<Session, &'static str> {
+pub fn find(connection: &MysqlConnection, the_id: &SessionId) -> Result<Session, &'static str> {
     use crate::schema::sessions::dsl::id;
 
     let session_result = sessions.filter(id.eq(the_id)).first(connection);
@@ -230,10 +335,110 @@ pub fn insert_session_member(connection: &MysqlConnection, session: &Session, me
 
 
 pub fn create_session_mail(connection: &MysqlConnection, session: &Session, member: &User, coach: &User) -> Result<usize, &'static str> {
+    let (mail_out, recipients) = session_mail(session, member, coach);
+
+    create_mail(connection, mail_out, recipients)
+}
+
+/**
+ * The mail telling the member of a new session, to the member and copied to the coach.
+ */
+pub fn session_mail(session: &Session, member: &User, coach: &User) -> (MailOut, Vec<MailRecipient>) {
     let mail_out = MailOut::for_new_session(session, coach, member);
     let recipients = MailRecipient::build_recipients(member, coach, mail_out.id.as_str());
 
-    create_mail(connection, mail_out, recipients)
+    (mail_out, recipients)
+}
+
+/**
+ * The sessions of the user that are yet to start are cancelled along with the
+ * deactivation of the account; a session in progress is left to the coach to close.
+ */
+pub fn cancel_upcoming_sessions(connection: &MysqlConnection, the_user_id: &str) -> QueryResult<Vec<Session>> {
+    let the_session_ids: Vec<String> = session_users.filter(user_id.eq(the_user_id)).select(session_id).load(connection)?;
+
+    let upcoming: Vec<Session> = sessions
+        .filter(crate::schema::sessions::id.eq_any(&the_session_ids))
+        .filter(cancelled_at.is_null())
+        .filter(actual_start_date.is_null())
+        .load(connection)?;
+
+    let upcoming_ids: Vec<&str> = upcoming.iter().map(|session| session.id.as_str()).collect();
+
+    diesel::update(sessions.filter(crate::schema::sessions::id.eq_any(upcoming_ids)))
+        .set((cancelled_at.eq(util::now()), closing_notes.eq(CANCELLED_ON_DEACTIVATION)))
+        .execute(connection)?;
+
+    Ok(upcoming)
+}
+
+/**
+ * The sessions of a cancelled enrollment that are yet to start; as with the
+ * deactivation, a session in progress is left to the coach to close.
+ */
+pub fn cancel_enrollment_sessions(connection: &MysqlConnection, the_enrollment_id: &str) -> QueryResult<Vec<Session>> {
+    let upcoming: Vec<Session> = sessions
+        .filter(crate::schema::sessions::enrollment_id.eq(the_enrollment_id))
+        .filter(cancelled_at.is_null())
+        .filter(actual_start_date.is_null())
+        .load(connection)?;
+
+    let upcoming_ids: Vec<&str> = upcoming.iter().map(|session| session.id.as_str()).collect();
+
+    diesel::update(sessions.filter(crate::schema::sessions::id.eq_any(upcoming_ids)))
+        .set((cancelled_at.eq(util::now()), closing_notes.eq(CANCELLED_WITH_ENROLLMENT)))
+        .execute(connection)?;
+
+    Ok(upcoming)
+}
+
+/**
+ * The one to one sessions of the enrollment yet to start move to the program the
+ * enrollment is handed over to, with its coach in place of the one before. The
+ * seats of the member in the conferences stay with the conferences.
+ */
+pub fn hand_over_enrollment_sessions(connection: &MysqlConnection, the_enrollment_id: &str, to_program: &Program, to_coach: &User, member: &User) -> QueryResult<usize> {
+    let upcoming: Vec<Session> = sessions
+        .filter(crate::schema::sessions::enrollment_id.eq(the_enrollment_id))
+        .filter(conference_id.is_null())
+        .filter(cancelled_at.is_null())
+        .filter(actual_start_date.is_null())
+        .load(connection)?;
+
+    let upcoming_ids: Vec<&str> = upcoming.iter().map(|session| session.id.as_str()).collect();
+    let people_involved = util::concat(to_coach.full_name.as_str(), member.full_name.as_str());
+
+    diesel::update(sessions.filter(crate::schema::sessions::id.eq_any(&upcoming_ids)))
+        .set((crate::schema::sessions::program_id.eq(to_program.id.as_str()), people.eq(people_involved)))
+        .execute(connection)?;
+
+    diesel::update(
+        session_users
+            .filter(session_id.eq_any(&upcoming_ids))
+            .filter(crate::schema::session_users::user_type.eq(util::COACH)),
+    )
+    .set(crate::schema::session_users::user_id.eq(to_coach.id.as_str()))
+    .execute(connection)?;
+
+    Ok(upcoming.len())
+}
+
+/**
+ * The mails and the summaries that follow the cancellations; a failure is
+ * logged, as the sessions are cancelled already.
+ */
+pub fn follow_cancellations(connection: &MysqlConnection, cancelled: &[Session]) {
+    for session in cancelled {
+        if !session.is_conference() {
+            if let Err(e) = send_session_cancel_mail(connection, session) {
+                eprintln!("Unable to mail the cancellation of the session {}: {}", session.id, e);
+            }
+        }
+
+        if let Err(e) = coach_stats::mark_program(connection, session.program_id.as_str()) {
+            eprintln!("Unable to queue the summary of the program {}: {}", session.program_id, e);
+        }
+    }
 }
 
 fn send_session_cancel_mail(connection: &MysqlConnection, session: &Session) -> Result<usize, &'static str> {
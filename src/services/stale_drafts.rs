@@ -0,0 +1,119 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::commons::util;
+use crate::models::notifications::{NewNotification, CONFERENCE_EXPIRED, REQUEST_EXPIRED};
+use crate::models::stale_drafts::StalePeriods;
+
+use crate::services::notifications::notify;
+
+use crate::schema::conferences;
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::sessions;
+
+const BATCH_SIZE: i64 = 200;
+
+const STALE_DRAFTS_ERROR: &str = "Unable to read the stale drafts.";
+
+/**
+ * The "stale-drafts" job. A request is expired once it waited longer than its
+ * period without being made ready or started, and its member is told. A
+ * conference is expired once it is past its start by its period without being
+ * made ready or started; the sessions of its members go with it and its coach is
+ * told.
+ */
+pub fn expire_stale_drafts(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    let periods = StalePeriods::from_env();
+    let now = util::now();
+
+    let requests = expire_requests(connection, &periods, now)?;
+    let conferences = expire_conferences(connection, &periods, now)?;
+
+    Ok(requests + conferences)
+}
+
+fn expire_requests(connection: &MysqlConnection, periods: &StalePeriods, now: NaiveDateTime) -> Result<usize, &'static str> {
+    let stale: Vec<(String, String, String)> = sessions::table
+        .inner_join(enrollments::table)
+        .filter(sessions::is_request.eq(true))
+        .filter(sessions::expired_at.is_null())
+        .filter(sessions::cancelled_at.is_null())
+        .filter(sessions::actual_start_date.is_null())
+        .filter(sessions::is_ready.eq(false))
+        .filter(sessions::created_at.lt(periods.request_cutoff(now)))
+        .select((sessions::id, sessions::name, enrollments::member_id))
+        .order_by(sessions::created_at.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| STALE_DRAFTS_ERROR)?;
+
+    let mut expired = 0;
+
+    for (session_id, name, member_id) in &stale {
+        let subject = format!("Your request for the session {} expired without being scheduled", name);
+        let notices = vec![NewNotification::new(member_id.as_str(), REQUEST_EXPIRED, subject, session_id.as_str())];
+
+        let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::update(sessions::table.filter(sessions::id.eq(session_id.as_str())))
+                .set(sessions::expired_at.eq(now))
+                .execute(connection)?;
+
+            notify(connection, &notices)
+        });
+
+        match result {
+            Ok(_) => expired += 1,
+            Err(e) => eprintln!("Unable to expire the request {}: {}", session_id, e),
+        }
+    }
+
+    Ok(expired)
+}
+
+fn expire_conferences(connection: &MysqlConnection, periods: &StalePeriods, now: NaiveDateTime) -> Result<usize, &'static str> {
+    let cutoff = periods.conference_cutoff(now);
+
+    let stale: Vec<(String, String, String)> = conferences::table
+        .inner_join(programs::table)
+        .filter(conferences::expired_at.is_null())
+        .filter(conferences::cancelled_at.is_null())
+        .filter(conferences::actual_start_date.is_null())
+        .filter(conferences::is_ready.eq(false))
+        .filter(
+            conferences::revised_start_date
+                .lt(cutoff)
+                .or(conferences::revised_start_date.is_null().and(conferences::original_start_date.lt(cutoff))),
+        )
+        .select((conferences::id, conferences::name, programs::coach_id))
+        .order_by(conferences::original_start_date.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| STALE_DRAFTS_ERROR)?;
+
+    let mut expired = 0;
+
+    for (conference_id, name, coach_id) in &stale {
+        let subject = format!("The conference {} expired without being made ready", name);
+        let notices = vec![NewNotification::new(coach_id.as_str(), CONFERENCE_EXPIRED, subject, conference_id.as_str())];
+
+        let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::update(conferences::table.filter(conferences::id.eq(conference_id.as_str())))
+                .set(conferences::expired_at.eq(now))
+                .execute(connection)?;
+
+            diesel::update(sessions::table.filter(sessions::conference_id.eq(conference_id.as_str())).filter(sessions::expired_at.is_null()))
+                .set(sessions::expired_at.eq(now))
+                .execute(connection)?;
+
+            notify(connection, &notices)
+        });
+
+        match result {
+            Ok(_) => expired += 1,
+            Err(e) => eprintln!("Unable to expire the conference {}: {}", conference_id, e),
+        }
+    }
+
+    Ok(expired)
+}
@@ -0,0 +1,214 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::commons::util;
+use crate::models::correspondences::{MailOut, MailRecipient};
+use crate::models::support_tickets::{
+    next_status, NewTicketMessage, RaiseTicketRequest, SupportTicket, TicketAction, TicketActionRequest, TicketCriteria, TicketMessage, TicketReplyRequest, TicketStatus, TicketThread,
+};
+use crate::models::users::User;
+
+use crate::services::correspondences::create_mail;
+use crate::services::users;
+
+use crate::schema::support_tickets;
+use crate::schema::ticket_messages;
+use crate::schema::users as users_table;
+
+const TICKET_NOT_FOUND: &str = "Unable to find the ticket.";
+const NOT_A_PARTY: &str = "Only the user who raised the ticket and the admins may see it.";
+const TICKET_SAVE_ERROR: &str = "Unable to save the ticket.";
+const TICKETS_ERROR: &str = "Unable to fetch the tickets.";
+
+const MAX_TICKETS: i64 = 200;
+
+pub fn raise_ticket(connection: &MysqlConnection, request: &RaiseTicketRequest) -> Result<TicketThread, &'static str> {
+    let raiser = users::find(connection, &request.user_id)?;
+
+    let ticket = request.to_ticket();
+    let message = NewTicketMessage::new(ticket.id.as_str(), raiser.id.as_str(), request.body.as_str(), &request.attachments);
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(support_tickets::table).values(&ticket).execute(connection)?;
+        diesel::insert_into(ticket_messages::table).values(&message).execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(TICKET_SAVE_ERROR);
+    }
+
+    let thread = find_thread(connection, ticket.id.as_str())?;
+
+    let subject = format!("[Ticket {}] {}", thread.ticket.category, thread.ticket.subject);
+    mail_admins(connection, &raiser, subject.to_owned(), request.body.to_owned());
+    mail_user(
+        &raiser,
+        &raiser,
+        subject,
+        format!("We have your request and will answer it soon.\n\n{}", request.body.trim()),
+        connection,
+    );
+
+    Ok(thread)
+}
+
+/**
+ * A reply from an admin answers the ticket; a reply from the user opens it again.
+ * The other side is told by mail.
+ */
+pub fn reply_ticket(connection: &MysqlConnection, request: &TicketReplyRequest) -> Result<TicketThread, &'static str> {
+    let ticket = find_ticket(connection, request.ticket_id.as_str())?;
+    let author = users::find(connection, &request.author_id)?;
+    let by_admin = is_admin(&author);
+
+    if !by_admin && author.id != ticket.raised_by_id {
+        return Err(NOT_A_PARTY);
+    }
+
+    let status = next_status(ticket.state(), by_admin, None)?;
+    let message = NewTicketMessage::new(ticket.id.as_str(), author.id.as_str(), request.body.as_str(), &request.attachments);
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::insert_into(ticket_messages::table).values(&message).execute(connection)?;
+        diesel::update(support_tickets::table.filter(support_tickets::id.eq(ticket.id.as_str())))
+            .set(support_tickets::status.eq(status.as_str()))
+            .execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(TICKET_SAVE_ERROR);
+    }
+
+    tell_other_side(connection, &ticket, &author, message.body.to_owned());
+
+    find_thread(connection, ticket.id.as_str())
+}
+
+pub fn act_on_ticket(connection: &MysqlConnection, request: &TicketActionRequest) -> Result<TicketThread, &'static str> {
+    let ticket = find_ticket(connection, request.ticket_id.as_str())?;
+    let actor = users::find(connection, &request.actor_id)?;
+    let by_admin = is_admin(&actor);
+
+    if !by_admin && actor.id != ticket.raised_by_id {
+        return Err(NOT_A_PARTY);
+    }
+
+    let status = next_status(ticket.state(), by_admin, Some(request.action))?;
+    let resolved_at = if status == TicketStatus::RESOLVED { Some(util::now()) } else { None };
+
+    diesel::update(support_tickets::table.filter(support_tickets::id.eq(ticket.id.as_str())))
+        .set((support_tickets::status.eq(status.as_str()), support_tickets::resolved_at.eq(resolved_at)))
+        .execute(connection)
+        .map_err(|_| TICKET_SAVE_ERROR)?;
+
+    let content = match request.action {
+        TicketAction::RESOLVE => format!("{} marked the ticket resolved.", actor.full_name),
+        TicketAction::REOPEN => format!("{} reopened the ticket.", actor.full_name),
+    };
+    tell_other_side(connection, &ticket, &actor, content);
+
+    find_thread(connection, ticket.id.as_str())
+}
+
+/**
+ * The latest updated first; the admins see every ticket, the others only their own.
+ */
+pub fn get_tickets(connection: &MysqlConnection, criteria: &TicketCriteria) -> Result<Vec<SupportTicket>, &'static str> {
+    let user = users::find(connection, &criteria.user_id)?;
+
+    let mut query = support_tickets::table.into_boxed();
+
+    if !is_admin(&user) {
+        query = query.filter(support_tickets::raised_by_id.eq(user.id.to_owned()));
+    }
+
+    if let Some(status) = criteria.status {
+        query = query.filter(support_tickets::status.eq(status.as_str()));
+    }
+
+    query.order_by(support_tickets::updated_at.desc()).limit(MAX_TICKETS).load(connection).map_err(|_| TICKETS_ERROR)
+}
+
+pub fn get_ticket(connection: &MysqlConnection, ticket_id: &str, user_id: &UserId) -> Result<TicketThread, &'static str> {
+    let user = users::find(connection, user_id)?;
+    let thread = find_thread(connection, ticket_id)?;
+
+    if !is_admin(&user) && user.id != thread.ticket.raised_by_id {
+        return Err(NOT_A_PARTY);
+    }
+
+    Ok(thread)
+}
+
+fn is_admin(user: &User) -> bool {
+    user.user_type == util::ADMIN && !user.blocked
+}
+
+fn find_ticket(connection: &MysqlConnection, the_id: &str) -> Result<SupportTicket, &'static str> {
+    support_tickets::table.filter(support_tickets::id.eq(the_id)).first(connection).map_err(|_| TICKET_NOT_FOUND)
+}
+
+fn find_thread(connection: &MysqlConnection, the_id: &str) -> Result<TicketThread, &'static str> {
+    let ticket = find_ticket(connection, the_id)?;
+
+    let messages: Vec<TicketMessage> = ticket_messages::table
+        .filter(ticket_messages::ticket_id.eq(ticket.id.as_str()))
+        .order_by(ticket_messages::created_at.asc())
+        .load(connection)
+        .map_err(|_| TICKETS_ERROR)?;
+
+    Ok(TicketThread { ticket, messages })
+}
+
+// A reply or an action of an admin is mailed to the user, the ones of the user to the admins.
+fn tell_other_side(connection: &MysqlConnection, ticket: &SupportTicket, sender: &User, content: String) {
+    let subject = format!("[Ticket {}] {}", ticket.category, ticket.subject);
+
+    if sender.id == ticket.raised_by_id {
+        mail_admins(connection, sender, subject, content);
+        return;
+    }
+
+    match users::find(connection, &UserId::from(ticket.raised_by_id.as_str())) {
+        Ok(raiser) => mail_user(sender, &raiser, subject, content, connection),
+        Err(e) => eprintln!("Unable to mail the ticket {} to its user: {}", ticket.id, e),
+    }
+}
+
+fn mail_admins(connection: &MysqlConnection, sender: &User, subject: String, content: String) {
+    let admins: QueryResult<Vec<User>> = users_table::table
+        .filter(users_table::user_type.eq(util::ADMIN))
+        .filter(users_table::blocked.eq(false))
+        .filter(users_table::email_invalid.eq(false))
+        .load(connection);
+
+    let admins = match admins {
+        Ok(value) if !value.is_empty() => value,
+        Ok(_) => return,
+        Err(e) => {
+            eprintln!("Unable to find the admins to mail the ticket: {}", e);
+            return;
+        }
+    };
+
+    let mail_out = MailOut::for_ticket(sender.id.as_str(), subject, content);
+    let to: Vec<&User> = admins.iter().collect();
+    let recipients = MailRecipient::build_to(&to, mail_out.id.as_str());
+
+    if let Err(e) = create_mail(connection, mail_out, recipients) {
+        eprintln!("Unable to mail the ticket to the admins: {}", e);
+    }
+}
+
+fn mail_user(sender: &User, user: &User, subject: String, content: String, connection: &MysqlConnection) {
+    if user.email_invalid {
+        return;
+    }
+
+    let mail_out = MailOut::for_ticket(sender.id.as_str(), subject, content);
+    let recipients = MailRecipient::build_to(&[user], mail_out.id.as_str());
+
+    if let Err(e) = create_mail(connection, mail_out, recipients) {
+        eprintln!("Unable to mail the ticket to {}: {}", user.id, e);
+    }
+}
@@ -1,16 +1,33 @@
 use diesel::prelude::*;
 
+use crate::commons::chassis::{Page, Window};
 use crate::commons::util;
 use chrono::{Duration, NaiveDateTime};
 
 use crate::models::enrollments::PlanCriteria;
-use crate::models::tasks::{NewTask, NewTaskRequest, Task, UpdateTask, UpdateClosingNoteRequest, UpdateTaskRequest,UpdateResponseRequest, ChangeMemberTaskStateRequest, ChangeCoachTaskStateRequest, MemberTargetState, CoachTargetState};
+use crate::models::task_links::{self as links, TaskLink};
+use crate::models::tasks::{CreateTasksFromSummaryRequest, NewTask, NewTaskRequest, RescheduleCascadeRequest, Task, UpdateTask, UpdateClosingNoteRequest, UpdateTaskRequest,UpdateResponseRequest, ChangeMemberTaskStateRequest, ChangeCoachTaskStateRequest, MemberTargetState, CoachTargetState};
+use crate::models::notifications::{NewNotification, TASK_RESPONDED};
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::task_links;
+use crate::schema::users;
 use crate::schema::tasks::dsl::*;
 
+use crate::services::coach_stats;
+use crate::services::sessions;
+use crate::services::notifications::notify;
+use crate::services::webhooks;
+
 const STATE_CHANGE_PROHIBITED: &str = "The task is either cancelled or responded.";
 const TASK_NOT_FOUND: &str = "Unable to find the Task.";
 const UPDATE_ERROR: &str = "Unable to complete the requested action.";
 const UPDATE_NOTES_ERROR: &str = "Unable to update the notes.";
+const RESCHEDULE_ERROR: &str = "Unable to reschedule the tasks.";
+const CANCELLED_SESSION: &str = "The action items of a cancelled session do not make tasks.";
+const SESSION_NOT_STARTED: &str = "The action items come of a session that has taken place.";
+const ENROLLMENT_NOT_FOUND: &str = "Unable to find the enrollment of the session.";
+const CREATE_FROM_SUMMARY_ERROR: &str = "Unable to create the tasks of the action items.";
 
 pub fn create_task(connection: &MysqlConnection, request: &NewTaskRequest) -> Result<Task, diesel::result::Error> {
     let new_task = NewTask::from(request);
@@ -129,7 +146,38 @@ pub fn change_member_task_state(connection: &MysqlConnection, request: &ChangeMe
         return Err(UPDATE_ERROR);
     }
 
-    find(connection, the_id)
+    let task = find(connection, the_id)?;
+
+    // The response is saved; a webhook that could not be queued should not undo it.
+    if request.target_state == MemberTargetState::FINISH {
+        if let Err(e) = webhooks::task_responded(connection, &task) {
+            eprintln!("Unable to queue the webhooks of the task {}: {}", task.id, e);
+        }
+
+        if let Err(e) = coach_stats::mark_enrollment(connection, task.enrollment_id.as_str()) {
+            eprintln!("Unable to queue the summary of the task {}: {}", task.id, e);
+        }
+
+        if let Err(e) = notify_responded(connection, &task) {
+            eprintln!("Unable to notify the coach of the task {}: {}", task.id, e);
+        }
+    }
+
+    Ok(task)
+}
+
+// The coach of the program is told the member is through with the task.
+fn notify_responded(connection: &MysqlConnection, task: &Task) -> QueryResult<usize> {
+    let (member_name, the_coach_id): (String, String) = enrollments::table
+        .inner_join(users::table)
+        .inner_join(programs::table)
+        .filter(enrollments::id.eq(task.enrollment_id.as_str()))
+        .select((users::full_name, programs::coach_id))
+        .first(connection)?;
+
+    let subject = format!("{} responded to the task {}", member_name, task.name);
+
+    notify(connection, &[NewNotification::new(the_coach_id.as_str(), TASK_RESPONDED, subject, task.id.as_str())])
 }
 
 fn can_allow_coach_task_state_change(connection: &MysqlConnection, request: &ChangeCoachTaskStateRequest) -> Result<usize, &'static str> {
@@ -183,4 +231,90 @@ pub fn get_tasks(connection: &MysqlConnection, criteria: PlanCriteria) -> Result
         .filter(enrollment_id.eq(criteria.enrollment_id))
         .order_by(original_start_date.asc())
         .load(connection)
-}
\ No newline at end of file
+}
+
+/**
+ * The tasks a page at a time; the id keeps the tasks starting together in one order across the pages.
+ */
+pub fn get_task_page(connection: &MysqlConnection, criteria: PlanCriteria, window: &Window) -> Result<Page<Task>, diesel::result::Error> {
+    let rows: Vec<Task> = tasks
+        .filter(enrollment_id.eq(criteria.enrollment_id))
+        .order_by((original_start_date.asc(), id.asc()))
+        .offset(window.offset)
+        .limit(window.fetch())
+        .load(connection)?;
+
+    Ok(Page::of(rows, window))
+}
+
+/**
+ * The task is given the new end date, and the tasks of its enrollment waiting on
+ * it are pushed along the links. The tasks that moved are answered, the slipped
+ * one among them.
+ */
+pub fn reschedule_cascade(connection: &MysqlConnection, request: &RescheduleCascadeRequest) -> Result<Vec<Task>, &'static str> {
+    let task: Task = tasks.filter(id.eq(request.task_id.as_str())).first(connection).map_err(|_| TASK_NOT_FOUND)?;
+
+    let enrollment_tasks: Vec<Task> = tasks.filter(enrollment_id.eq(task.enrollment_id.as_str())).load(connection).map_err(|_| RESCHEDULE_ERROR)?;
+
+    let enrollment_links: Vec<TaskLink> = task_links::table
+        .filter(task_links::enrollment_id.eq(task.enrollment_id.as_str()))
+        .load(connection)
+        .map_err(|_| RESCHEDULE_ERROR)?;
+
+    let revisions = links::cascade(&enrollment_tasks, &enrollment_links, task.id.as_str(), util::as_date(request.end_date.as_str()))?;
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        for revision in &revisions {
+            diesel::update(tasks.filter(id.eq(revision.task_id.as_str())))
+                .set((revised_start_date.eq(revision.start_date), revised_end_date.eq(revision.end_date)))
+                .execute(connection)?;
+        }
+
+        let moved_ids: Vec<&str> = revisions.iter().map(|revision| revision.task_id.as_str()).collect();
+        tasks.filter(id.eq_any(moved_ids)).order_by(revised_start_date.asc()).load(connection)
+    });
+
+    result.map_err(|_| RESCHEDULE_ERROR)
+}
+
+/**
+ * The action items of the session become the tasks of its member, all of them or
+ * none. The tasks are answered by their due date.
+ */
+pub fn create_tasks_from_summary(connection: &MysqlConnection, request: &CreateTasksFromSummaryRequest) -> Result<Vec<Task>, &'static str> {
+    let session = sessions::find(connection, &request.session_id)?;
+
+    if session.cancelled_at.is_some() {
+        return Err(CANCELLED_SESSION);
+    }
+
+    // A session yet to start may still be removed, and should leave no tasks behind.
+    if session.actual_start_date.is_none() {
+        return Err(SESSION_NOT_STARTED);
+    }
+
+    let the_member_id: String = enrollments::table
+        .filter(enrollments::id.eq(session.enrollment_id.as_str()))
+        .select(enrollments::member_id)
+        .first(connection)
+        .map_err(|_| ENROLLMENT_NOT_FOUND)?;
+
+    let now = util::now();
+    let new_tasks: Vec<NewTask> = request
+        .action_items
+        .iter()
+        .map(|item| NewTask::of_action_item(session.enrollment_id.as_str(), the_member_id.as_str(), session.id.as_str(), session.name.as_str(), item, now))
+        .collect();
+
+    diesel::insert_into(tasks).values(&new_tasks).execute(connection).map_err(|_| CREATE_FROM_SUMMARY_ERROR)?;
+
+    let new_ids: Vec<&str> = new_tasks.iter().map(|task| task.id.as_str()).collect();
+    let created: Vec<Task> = tasks.filter(id.eq_any(new_ids)).order_by(original_end_date.asc()).load(connection).map_err(|_| CREATE_FROM_SUMMARY_ERROR)?;
+
+    if let Err(e) = coach_stats::mark_enrollment(connection, session.enrollment_id.as_str()) {
+        eprintln!("Unable to queue the summary of the enrollment {}: {}", session.enrollment_id, e);
+    }
+
+    Ok(created)
+}
@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::storage::storage;
+
+// The list views take the small one, the detail views the large one.
+pub const SIZES: &[u32] = &[128, 512];
+
+const THUMBS_DIR: &str = "thumbs";
+
+/**
+ * Resizes an uploaded image into a thumbnail of each size, kept in a thumbs
+ * directory beside the image. The resizing needs ImageMagick on the host, hence it
+ * runs only with the thumbnails feature; without it, the thumbnail routes serve
+ * the originals.
+ */
+pub fn generate(image_path: &Path) -> usize {
+    if !cfg!(feature = "thumbnails") {
+        return 0;
+    }
+
+    SIZES.iter().filter(|size| resize(image_path, **size)).count()
+}
+
+fn resize(image_path: &Path, size: u32) -> bool {
+    let target = match thumbnail_path(image_path, size) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    if let Some(dir) = target.parent() {
+        if std::fs::create_dir_all(dir).is_err() {
+            return false;
+        }
+    }
+
+    // The first frame of an animated image; the smaller images are never enlarged.
+    let source = format!("{}[0]", image_path.to_string_lossy());
+    let geometry = format!("{}x{}>", size, size);
+
+    match Command::new("convert").args([source.as_str(), "-auto-orient", "-thumbnail", geometry.as_str()]).arg(&target).status() {
+        Ok(status) if status.success() => {
+            if let Err(e) = storage().keep(&target) {
+                eprintln!("Unable to keep the {}px thumbnail of {}: {}", size, image_path.to_string_lossy(), e);
+            }
+            true
+        }
+        _ => {
+            eprintln!("Unable to make the {}px thumbnail of {}", size, image_path.to_string_lossy());
+            false
+        }
+    }
+}
+
+/**
+ * Where the thumbnail of the given size of an image is, or would be.
+ */
+pub fn thumbnail_path(image_path: &Path, size: u32) -> Option<PathBuf> {
+    let dir = image_path.parent()?;
+    let name = image_path.file_name()?;
+
+    Some(dir.join(THUMBS_DIR).join(size.to_string()).join(name))
+}
+
+/**
+ * The smallest thumbnail at least as large as asked for, else the largest one.
+ */
+pub fn fitting_size(requested: Option<u32>) -> u32 {
+    let largest = SIZES[SIZES.len() - 1];
+
+    match requested {
+        None => SIZES[0],
+        Some(requested) => SIZES.iter().copied().find(|size| *size >= requested).unwrap_or(largest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_fit_the_requested_size() {
+        assert_eq!(128, fitting_size(None));
+        assert_eq!(128, fitting_size(Some(64)));
+        assert_eq!(512, fitting_size(Some(129)));
+        assert_eq!(512, fitting_size(Some(2048)));
+
+        let path = thumbnail_path(Path::new("/assets/p-1/cover/banner.png"), 512).unwrap();
+        assert_eq!(Path::new("/assets/p-1/cover/thumbs/512/banner.png"), path.as_path());
+    }
+}
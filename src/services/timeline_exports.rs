@@ -0,0 +1,73 @@
+use diesel::prelude::*;
+
+use crate::models::enrollments::Enrollment;
+use crate::models::notes::Note;
+use crate::models::programs::Program;
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+use crate::models::timeline_exports::{note_title, TimelineRow};
+use crate::models::users::User;
+
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::session_notes;
+use crate::schema::session_users;
+use crate::schema::sessions;
+use crate::schema::tasks;
+
+const TIMELINE_ERROR: &str = "Unable to prepare the timeline of the member.";
+
+/**
+ * Every enrollment of the member, with its sessions and tasks in the order of
+ * their schedule. The notes are the ones the member wrote in the session.
+ */
+pub fn get_timeline(connection: &MysqlConnection, member: &User) -> Result<Vec<TimelineRow>, &'static str> {
+    let enrolled: Vec<(Enrollment, Program)> = enrollments::table
+        .inner_join(programs::table)
+        .filter(enrollments::member_id.eq(member.id.as_str()))
+        .order_by(enrollments::created_at.asc())
+        .load(connection)
+        .map_err(|_| TIMELINE_ERROR)?;
+
+    let mut rows: Vec<TimelineRow> = Vec::new();
+
+    for (enrollment, program) in &enrolled {
+        let the_sessions: Vec<Session> = sessions::table
+            .filter(sessions::enrollment_id.eq(enrollment.id.as_str()))
+            .order_by(sessions::original_start_date.asc())
+            .load(connection)
+            .map_err(|_| TIMELINE_ERROR)?;
+
+        let session_ids: Vec<&str> = the_sessions.iter().map(|session| session.id.as_str()).collect();
+
+        let notes: Vec<Note> = session_notes::table
+            .inner_join(session_users::table)
+            .filter(session_notes::session_id.eq_any(session_ids))
+            .filter(session_users::user_id.eq(member.id.as_str()))
+            .select(session_notes::all_columns)
+            .order_by(session_notes::created_at.asc())
+            .load(connection)
+            .map_err(|_| TIMELINE_ERROR)?;
+
+        for session in &the_sessions {
+            let titles: Vec<String> = notes
+                .iter()
+                .filter(|note| note.session_id == session.id)
+                .map(|note| note_title(note.description.as_str()))
+                .filter(|title| !title.is_empty())
+                .collect();
+
+            rows.push(TimelineRow::from_session(program.name.as_str(), session, titles));
+        }
+
+        let the_tasks: Vec<Task> = tasks::table
+            .filter(tasks::enrollment_id.eq(enrollment.id.as_str()))
+            .order_by(tasks::original_start_date.asc())
+            .load(connection)
+            .map_err(|_| TIMELINE_ERROR)?;
+
+        rows.extend(the_tasks.iter().map(|task| TimelineRow::from_task(program.name.as_str(), task)));
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,115 @@
+use diesel::prelude::*;
+
+use crate::commons::ids::UserId;
+use crate::models::user_locales::{is_valid_offset, is_valid_time_zone, preferred_locale, LocaleBundle, LocaleDetection, LocaleRequest, NewUserLocale, UserLocale, DEFAULT_LOCALE, DEFAULT_TIME_ZONE};
+use crate::models::users::User;
+
+use crate::services::users;
+
+use crate::schema::user_locales;
+use crate::schema::users as users_table;
+
+const LOCALE_SAVE_ERROR: &str = "Unable to save the locale of the user.";
+const LOCALE_FETCH_ERROR: &str = "Unable to fetch the locale of the user.";
+
+/**
+ * On the first call the detected locale, time zone and offset become the defaults
+ * of the user; the malformed ones fall back to English, UTC and the present offset.
+ * Later calls return the defaults as they are, so that the choices of the user
+ * are never overridden by the device of the moment.
+ */
+pub fn settle_locale(connection: &MysqlConnection, the_user_id: &UserId, accept_language: Option<&str>, detection: &LocaleDetection) -> Result<LocaleBundle, &'static str> {
+    let user = users::find(connection, the_user_id)?;
+
+    if let Some(existing) = find_locale(connection, user.id.as_str())? {
+        return Ok(bundle(existing, &user, false));
+    }
+
+    let locale = accept_language.and_then(preferred_locale).unwrap_or_else(|| String::from(DEFAULT_LOCALE));
+
+    let time_zone = detection
+        .time_zone
+        .as_deref()
+        .map(|zone| zone.trim())
+        .filter(|zone| is_valid_time_zone(zone))
+        .unwrap_or(DEFAULT_TIME_ZONE);
+
+    let utc_offset = detection.utc_offset.filter(|offset| is_valid_offset(*offset)).unwrap_or(user.utc_offset);
+
+    let new_locale = NewUserLocale {
+        user_id: user.id.to_string(),
+        locale,
+        time_zone: time_zone.to_owned(),
+    };
+
+    // A concurrent first login may have settled the defaults already; that one stands.
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let inserted = diesel::insert_or_ignore_into(user_locales::table).values(&new_locale).execute(connection)?;
+
+        if inserted > 0 {
+            diesel::update(users_table::table.filter(users_table::id.eq(user.id.as_str())))
+                .set(users_table::utc_offset.eq(utc_offset))
+                .execute(connection)?;
+        }
+
+        Ok(inserted)
+    });
+
+    if result.is_err() {
+        return Err(LOCALE_SAVE_ERROR);
+    }
+
+    let user = users::find(connection, &user.id)?;
+    let saved = find_locale(connection, user.id.as_str())?.ok_or(LOCALE_SAVE_ERROR)?;
+
+    Ok(bundle(saved, &user, result.unwrap() > 0))
+}
+
+/**
+ * The choice of the user, replacing the detected defaults.
+ */
+pub fn save_locale(connection: &MysqlConnection, request: &LocaleRequest) -> Result<LocaleBundle, &'static str> {
+    let user = users::find(connection, &request.user_id)?;
+
+    let new_locale = NewUserLocale {
+        user_id: user.id.to_string(),
+        locale: request.locale.trim().to_owned(),
+        time_zone: request.time_zone.trim().to_owned(),
+    };
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::replace_into(user_locales::table).values(&new_locale).execute(connection)?;
+
+        diesel::update(users_table::table.filter(users_table::id.eq(user.id.as_str())))
+            .set(users_table::utc_offset.eq(request.utc_offset))
+            .execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(LOCALE_SAVE_ERROR);
+    }
+
+    let user = users::find(connection, &user.id)?;
+    let saved = find_locale(connection, user.id.as_str())?.ok_or(LOCALE_SAVE_ERROR)?;
+
+    Ok(bundle(saved, &user, false))
+}
+
+fn find_locale(connection: &MysqlConnection, the_user_id: &str) -> Result<Option<UserLocale>, &'static str> {
+    user_locales::table
+        .filter(user_locales::user_id.eq(the_user_id))
+        .select((user_locales::user_id, user_locales::locale, user_locales::time_zone))
+        .first(connection)
+        .optional()
+        .map_err(|_| LOCALE_FETCH_ERROR)
+}
+
+fn bundle(locale: UserLocale, user: &User, first_detection: bool) -> LocaleBundle {
+    LocaleBundle {
+        user_id: locale.user_id,
+        locale: locale.locale,
+        time_zone: locale.time_zone,
+        utc_offset: user.utc_offset,
+        first_detection,
+    }
+}
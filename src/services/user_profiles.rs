@@ -0,0 +1,55 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+use crate::commons::ids::UserId;
+use crate::models::user_profiles::{NewUserProfile, UserProfile, UserProfileRequest};
+use crate::models::user_programs::forget_catalog;
+
+use crate::services::users;
+
+use crate::schema::user_profiles;
+
+const PROFILE_SAVE_ERROR: &str = "Unable to save the profile.";
+const PROFILE_NOT_FOUND: &str = "Unable to find the profile.";
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
+
+/**
+ * The profile of the signed in user.
+ */
+pub fn update_profile(connection: &MysqlConnection, caller: Option<&UserId>, request: &UserProfileRequest) -> Result<UserProfile, &'static str> {
+    let user = users::find(connection, caller.ok_or(NOT_SIGNED_IN)?)?;
+
+    let profile = NewUserProfile::from(request, user.id.as_str());
+    let target = user_profiles::table.filter(user_profiles::user_id.eq(user.id.as_str()));
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        let updated = diesel::update(target).set(&profile).execute(connection)?;
+
+        if updated > 0 {
+            return Ok(updated);
+        }
+
+        diesel::insert_or_ignore_into(user_profiles::table).values(&profile).execute(connection)
+    });
+
+    if result.is_err() {
+        return Err(PROFILE_SAVE_ERROR);
+    }
+
+    forget_catalog();
+
+    get_profile(connection, user.id.as_str())
+}
+
+pub fn get_profile(connection: &MysqlConnection, the_user_id: &str) -> Result<UserProfile, &'static str> {
+    user_profiles::table.filter(user_profiles::user_id.eq(the_user_id)).first(connection).map_err(|_| PROFILE_NOT_FOUND)
+}
+
+/**
+ * The profiles of the given users, in one round trip; a user without one is left out.
+ */
+pub fn profiles_of(connection: &MysqlConnection, the_user_ids: &[&str]) -> QueryResult<HashMap<String, UserProfile>> {
+    let profiles: Vec<UserProfile> = user_profiles::table.filter(user_profiles::user_id.eq_any(the_user_ids)).load(connection)?;
+
+    Ok(profiles.into_iter().map(|profile| (profile.user_id.to_owned(), profile)).collect())
+}
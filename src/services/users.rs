@@ -1,10 +1,18 @@
 use diesel::prelude::*;
 
+use crate::commons::ids::UserId;
+use crate::commons::password as passwords;
 use crate::commons::util;
 
+use crate::models::audit_events::NewAuditEvent;
 use crate::models::ferror::Ferror;
 use crate::models::coaches::Coach;
-use crate::models::users::{LoginRequest, NewUser, Registration, ResetPasswordRequest, User};
+use crate::models::user_programs::forget_catalog;
+use crate::models::users::{AccountTargetState, ChangeAccountStateRequest, DeactivateAccountRequest, LoginRequest, NewUser, Registration, ResetPasswordRequest, User};
+
+use crate::services::admin::admin_of;
+use crate::services::audit;
+use crate::services::sessions;
 
 use crate::schema::users;
 use crate::schema::users::dsl::*;
@@ -19,6 +27,12 @@ pub const INVALID_CREDENTIAL: &str = "Invalid Credential";
 pub const PASSWORD_RESET_FAILED: &str = "Failed to reset the password.";
 pub const INVALID_COACH_EMAIL: &str = "Invalid Coach email address";
 pub const INVALID_COACH_ID: &str = "Invalid Coach Id";
+pub const INVALID_ADMIN: &str = "Only an admin is allowed to perform this operation.";
+pub const ACCOUNT_DEACTIVATED: &str = "The account is deactivated.";
+
+const ACCOUNT_SAME_STATE: &str = "The account is already in the target state.";
+const ACCOUNT_STATE_ERROR: &str = "Unable to change the state of the account.";
+const NOT_SIGNED_IN: &str = "Please sign in to go on.";
 
 pub fn register(connection: &MysqlConnection, registration: &Registration) -> Result<User, Ferror> {
     
@@ -41,7 +55,9 @@ pub fn authenticate(connection: &MysqlConnection, request: LoginRequest) -> Resu
         return Err(INVALID_CREDENTIAL);
     }
 
-    let flag = util::is_equal(result.unwrap().as_str(), request.password.as_str());
+    let hashed_password = result.unwrap();
+
+    let flag = passwords::verify(hashed_password.as_str(), request.password.as_str());
     if !flag {
         return Err(INVALID_CREDENTIAL);
     }
@@ -51,7 +67,30 @@ pub fn authenticate(connection: &MysqlConnection, request: LoginRequest) -> Resu
         return Err(INVALID_CREDENTIAL);
     }
 
-    Ok(result.unwrap())
+    let user = result.unwrap();
+    if user.blocked {
+        return Err(ACCOUNT_DEACTIVATED);
+    }
+
+    if passwords::needs_rehash(hashed_password.as_str()) {
+        rehash_password(connection, &user, request.password.as_str());
+    }
+
+    Ok(user)
+}
+
+/**
+ * The password is hashed again with the current limits while it is at hand; a
+ * failure leaves the old hash, which still verifies.
+ */
+fn rehash_password(connection: &MysqlConnection, user: &User, given_password: &str) {
+    let hashed_password = passwords::hash(given_password);
+
+    let result = diesel::update(users.filter(users::id.eq(&user.id))).set(password.eq(hashed_password)).execute(connection);
+
+    if let Err(e) = result {
+        eprintln!("Unable to rehash the password of {}: {}", user.id, e);
+    }
 }
 
 pub fn reset_password(connection: &MysqlConnection, request: &ResetPasswordRequest) -> Result<User, &'static str> {
@@ -61,7 +100,7 @@ pub fn reset_password(connection: &MysqlConnection, request: &ResetPasswordReque
     };
     let user = authenticate(connection, login_request)?;
 
-    let hashed_password = util::hash(request.new_password.as_str());
+    let hashed_password = passwords::hash(request.new_password.as_str());
 
     let result = diesel::update(users).filter(users::email.eq(user.email.as_str())).set(password.eq(hashed_password)).execute(connection);
 
@@ -72,7 +111,24 @@ pub fn reset_password(connection: &MysqlConnection, request: &ResetPasswordReque
     Ok(user)
 }
 
-pub fn find(connection: &MysqlConnection, the_id: &str) -> Result<User, &'static str> {
+/**
+ * The user who may act on the platform; a deactivated account is turned away,
+ * so that none of the operations looking up their user go through for it.
+ */
+pub fn find(connection: &MysqlConnection, the_id: &UserId) -> Result<User, &'static str> {
+    let user = find_any(connection, the_id)?;
+
+    if user.blocked {
+        return Err(ACCOUNT_DEACTIVATED);
+    }
+
+    Ok(user)
+}
+
+/**
+ * The user, deactivated or not.
+ */
+pub fn find_any(connection: &MysqlConnection, the_id: &UserId) -> Result<User, &'static str> {
     
     let result = users.filter(users::id.eq(the_id)).first(connection);
 
@@ -83,6 +139,94 @@ pub fn find(connection: &MysqlConnection, the_id: &str) -> Result<User, &'static
     Ok(result.unwrap())
 }
 
+/**
+ * Deactivating an account cancels its sessions yet to start; the discussions of
+ * its enrollments are locked, and the mails to it are held back, for as long as
+ * it stays deactivated. Reactivating does not bring the cancelled sessions back.
+ *
+ * The admin is the signed in user, never an id in the request.
+ */
+pub fn change_account_state(connection: &MysqlConnection, caller: Option<&UserId>, request: &ChangeAccountStateRequest) -> Result<User, &'static str> {
+    let admin = admin_of(connection, caller)?;
+
+    apply_account_state(connection, &admin, request)
+}
+
+/**
+ * The signed in user closing the own account.
+ */
+pub fn deactivate_account(connection: &MysqlConnection, caller: Option<&UserId>, request: &DeactivateAccountRequest) -> Result<User, &'static str> {
+    let user = find(connection, caller.ok_or(NOT_SIGNED_IN)?)?;
+
+    apply_account_state(connection, &user, &request.as_state_change(&user.id))
+}
+
+// The actor is an admin, or the user closing the own account; the callers see to it.
+fn apply_account_state(connection: &MysqlConnection, actor: &User, request: &ChangeAccountStateRequest) -> Result<User, &'static str> {
+    let user = find_any(connection, &request.user_id)?;
+
+    let deactivate = request.target_state == AccountTargetState::DEACTIVATE;
+    if user.blocked == deactivate {
+        return Err(ACCOUNT_SAME_STATE);
+    }
+
+    let action = if deactivate { "deactivate" } else { "reactivate" };
+    let event = NewAuditEvent::from("user", user.id.as_str(), action, actor.id.as_str()).with_reason(request.reason.trim());
+
+    let result = connection.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(users.filter(users::id.eq(user.id.as_str()))).set(blocked.eq(deactivate)).execute(connection)?;
+
+        audit::record(connection, &event)?;
+
+        if deactivate {
+            return sessions::cancel_upcoming_sessions(connection, user.id.as_str());
+        }
+
+        Ok(Vec::new())
+    });
+
+    let cancelled = match result {
+        Ok(value) => value,
+        Err(_) => return Err(ACCOUNT_STATE_ERROR),
+    };
+
+    sessions::follow_cancellations(connection, &cancelled);
+    forget_catalog();
+
+    find_any(connection, &user.id)
+}
+
+/**
+ * Turns away when any of the given users is deactivated; the ids that are not of
+ * a user are ignored.
+ */
+pub fn gate_active(connection: &MysqlConnection, the_ids: &[&str]) -> Result<(), &'static str> {
+    let deactivated: i64 = users
+        .filter(users::id.eq_any(the_ids))
+        .filter(blocked.eq(true))
+        .count()
+        .get_result(connection)
+        .map_err(|_| INVALID_USER_ID)?;
+
+    if deactivated > 0 {
+        return Err(ACCOUNT_DEACTIVATED);
+    }
+
+    Ok(())
+}
+
+/**
+ * Operations that bypass the usual ownership rules are allowed only for the admins.
+ */
+pub fn find_admin(connection: &MysqlConnection, the_id: &UserId) -> Result<User, &'static str> {
+    let user = find(connection, the_id).map_err(|_| INVALID_ADMIN)?;
+
+    if user.user_type != util::ADMIN || user.blocked {
+        return Err(INVALID_ADMIN);
+    }
+
+    Ok(user)
+}
 
 fn create_user(connection: &MysqlConnection, registration: &Registration) -> Result<User, &'static str> {
     let new_user = NewUser::from(registration);
@@ -93,7 +237,7 @@ fn create_user(connection: &MysqlConnection, registration: &Registration) -> Res
         return Err(CREATION_ERROR);
     }
 
-    find(connection, new_user.id.as_str())
+    find(connection, &new_user.id)
 }
 
 fn is_registered(connection: &MysqlConnection, email_str: &str) -> Result<bool, &'static str> {
@@ -118,7 +262,10 @@ pub fn find_coach_by_id(connection: &MysqlConnection, the_coach_id: &str) -> Res
         return Err(INVALID_COACH_ID);
     }
 
-    Ok(coach_result.unwrap())
+    let coach: Coach = coach_result.unwrap();
+    gate_active(connection, &[coach.id.as_str()])?;
+
+    Ok(coach)
 }
 
 pub fn find_coach_by_email(connection: &MysqlConnection, peer_coach_email: &str) -> Result<Coach, &'static str> {
@@ -129,5 +276,8 @@ pub fn find_coach_by_email(connection: &MysqlConnection, peer_coach_email: &str)
         return Err(INVALID_COACH_EMAIL);
     }
 
-    Ok(coach_result.unwrap())
+    let coach: Coach = coach_result.unwrap();
+    gate_active(connection, &[coach.id.as_str()])?;
+
+    Ok(coach)
 }
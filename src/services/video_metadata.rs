@@ -0,0 +1,109 @@
+use diesel::prelude::*;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::notes::{SessionFile, METADATA_DONE, METADATA_FAILED, METADATA_PENDING};
+use crate::schema::session_files::dsl::*;
+use crate::storage::storage;
+
+const BATCH_SIZE: i64 = 5;
+
+// The poster is taken a second into the video, past the usual black opening frame.
+const POSTER_OFFSET: &str = "00:00:01";
+
+const METADATA_UPDATE_ERROR: &str = "Unable to store the metadata of the video.";
+const PENDING_FILES_ERROR: &str = "Unable to find the videos awaiting the metadata.";
+
+struct VideoMetadata {
+    duration_seconds: Option<i32>,
+    width: Option<i32>,
+    height: Option<i32>,
+    poster_path: Option<String>,
+}
+
+/**
+ * Extract the metadata of a few pending videos. The extraction needs ffprobe and
+ * ffmpeg on the host, hence it runs only with the video-metadata feature; without
+ * it, the videos stay pending until an instance with the feature picks them up.
+ */
+pub fn process_pending(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    if !cfg!(feature = "video-metadata") {
+        return Ok(0);
+    }
+
+    let pending: Vec<SessionFile> = session_files
+        .filter(metadata_status.eq(METADATA_PENDING))
+        .order_by(created_at.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| PENDING_FILES_ERROR)?;
+
+    for file in &pending {
+        let result = match extract(file.file_path.as_str()) {
+            Some(metadata) => diesel::update(session_files.filter(id.eq(file.id.as_str())))
+                .set((
+                    duration_seconds.eq(metadata.duration_seconds),
+                    width.eq(metadata.width),
+                    height.eq(metadata.height),
+                    poster_path.eq(metadata.poster_path),
+                    metadata_status.eq(METADATA_DONE),
+                ))
+                .execute(connection),
+            None => diesel::update(session_files.filter(id.eq(file.id.as_str())))
+                .set(metadata_status.eq(METADATA_FAILED))
+                .execute(connection),
+        };
+
+        if result.is_err() {
+            return Err(METADATA_UPDATE_ERROR);
+        }
+    }
+
+    Ok(pending.len())
+}
+
+fn extract(video_path: &str) -> Option<VideoMetadata> {
+    storage().open(Path::new(video_path)).ok()?;
+
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height:format=duration", "-of", "json", video_path])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let probe: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let stream = &probe["streams"][0];
+
+    let duration = probe["format"]["duration"].as_str().and_then(|value| value.parse::<f64>().ok()).map(|value| value.round() as i32);
+
+    Some(VideoMetadata {
+        duration_seconds: duration,
+        width: stream["width"].as_i64().map(|value| value as i32),
+        height: stream["height"].as_i64().map(|value| value as i32),
+        poster_path: poster(video_path),
+    })
+}
+
+// A video without a poster is still worth the rest of its metadata.
+fn poster(video_path: &str) -> Option<String> {
+    let target = format!("{}.poster.jpg", video_path);
+
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error", "-y", "-ss", POSTER_OFFSET, "-i", video_path, "-frames:v", "1", target.as_str()])
+        .status()
+        .ok()?;
+
+    if !status.success() {
+        return None;
+    }
+
+    if let Err(e) = storage().keep(Path::new(&target)) {
+        eprintln!("Unable to keep the poster {}: {}", target, e);
+    }
+
+    Some(target)
+}
@@ -0,0 +1,297 @@
+use diesel::prelude::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use chrono::Duration;
+
+use crate::commons::util;
+use crate::models::enrollments::Enrollment;
+use crate::models::programs::Program;
+use crate::models::sessions::Session;
+use crate::models::tasks::Task;
+use crate::models::users::User;
+use crate::models::webhooks::{
+    retry_delay, signature, DeliveryCriteria, NewWebhookDelivery, NewWebhookSubscription, OutboundEvent, WebhookCriteria, WebhookDelivery, WebhookRequest, WebhookSubscription, DELIVERED,
+    FAILED, MARKED, MAX_DELIVERIES, PENDING, SENDING,
+};
+
+use crate::services::users::find_coach_by_id;
+
+use crate::schema::enrollments;
+use crate::schema::programs;
+use crate::schema::users;
+use crate::schema::webhook_deliveries;
+use crate::schema::webhook_subscriptions;
+
+// Offered a few at a time, like the mails.
+const BATCH_SIZE: i64 = 10;
+
+// A url gets as long to answer; a delivery sending for much longer was left by a stopped instance.
+const POST_TIMEOUT_SECONDS: &str = "10";
+const STUCK_MINUTES: i64 = 10;
+const MAX_ERROR: usize = 255;
+
+const WEBHOOK_NOT_FOUND: &str = "Unable to find the webhook.";
+const WEBHOOK_SAVE_ERROR: &str = "Unable to save the webhook.";
+const WEBHOOK_DELETE_ERROR: &str = "Unable to delete the webhook.";
+const DELIVERIES_FETCH_ERROR: &str = "Unable to fetch the webhook deliveries.";
+const DELIVERY_UPDATE_ERROR: &str = "Unable to record the outcome of the webhook delivery.";
+
+pub fn create_webhook(connection: &MysqlConnection, request: &WebhookRequest) -> Result<WebhookSubscription, &'static str> {
+    let coach = find_coach_by_id(connection, request.coach_id.as_str())?;
+
+    let subscription = NewWebhookSubscription::from(request);
+
+    let result = diesel::insert_into(webhook_subscriptions::table).values(&subscription).execute(connection);
+
+    if result.is_err() {
+        return Err(WEBHOOK_SAVE_ERROR);
+    }
+
+    find_owned(connection, subscription.id.as_str(), coach.id.as_str())
+}
+
+pub fn delete_webhook(connection: &MysqlConnection, criteria: &WebhookCriteria) -> Result<usize, &'static str> {
+    let subscription = find_owned(connection, criteria.id.as_str(), criteria.coach_id.as_str())?;
+
+    diesel::delete(webhook_subscriptions::table.filter(webhook_subscriptions::id.eq(subscription.id.as_str())))
+        .execute(connection)
+        .map_err(|_| WEBHOOK_DELETE_ERROR)
+}
+
+pub fn get_webhooks(connection: &MysqlConnection, the_coach_id: &str) -> QueryResult<Vec<WebhookSubscription>> {
+    webhook_subscriptions::table
+        .filter(webhook_subscriptions::coach_id.eq(the_coach_id))
+        .order_by(webhook_subscriptions::created_at.asc())
+        .load(connection)
+}
+
+/**
+ * Queues the event for every active subscription of the coach to it.
+ */
+pub fn publish(connection: &MysqlConnection, the_coach_id: &str, event: &OutboundEvent) -> QueryResult<usize> {
+    let subscriptions: Vec<WebhookSubscription> = webhook_subscriptions::table
+        .filter(webhook_subscriptions::coach_id.eq(the_coach_id))
+        .filter(webhook_subscriptions::event.eq(event.event.as_str()))
+        .filter(webhook_subscriptions::is_active.eq(true))
+        .load(connection)?;
+
+    if subscriptions.is_empty() {
+        return Ok(0);
+    }
+
+    let deliveries: Vec<NewWebhookDelivery> = subscriptions.iter().map(|subscription| NewWebhookDelivery::from(subscription, event)).collect();
+
+    diesel::insert_into(webhook_deliveries::table).values(&deliveries).execute(connection)
+}
+
+/**
+ * Tells the coach of the enrollment that the member has responded to the task.
+ */
+pub fn task_responded(connection: &MysqlConnection, task: &Task) -> QueryResult<usize> {
+    let (enrollment, program): (Enrollment, Program) = enrollments::table
+        .inner_join(programs::table)
+        .filter(enrollments::id.eq(task.enrollment_id.as_str()))
+        .first(connection)?;
+
+    let member: User = users::table.filter(users::id.eq(enrollment.member_id.as_str())).first(connection)?;
+
+    let event = OutboundEvent::task_response(member.full_name.as_str(), task.name.as_str(), task.id.as_str(), enrollment.id.as_str());
+
+    publish(connection, program.coach_id.as_str(), &event)
+}
+
+/**
+ * Tells the coach of the program that a member has enrolled in it.
+ */
+pub fn enrollment_created(connection: &MysqlConnection, program: &Program, enrollment: &Enrollment, member: &User) -> QueryResult<usize> {
+    let event = OutboundEvent::enrollment_created(member.full_name.as_str(), program.name.as_str(), enrollment.id.as_str(), program.id.as_str());
+
+    publish(connection, program.coach_id.as_str(), &event)
+}
+
+/**
+ * Tells the coach of the program that the session is completed.
+ */
+pub fn session_completed(connection: &MysqlConnection, session: &Session) -> QueryResult<usize> {
+    let program: Program = programs::table.filter(programs::id.eq(session.program_id.as_str())).first(connection)?;
+
+    let event = OutboundEvent::session_completed(session.name.as_str(), session.id.as_str(), session.enrollment_id.as_str(), program.id.as_str());
+
+    publish(connection, program.coach_id.as_str(), &event)
+}
+
+/**
+ * The latest deliveries of the webhooks of the coach, to see what reached the urls.
+ */
+pub fn get_webhook_deliveries(connection: &MysqlConnection, criteria: &DeliveryCriteria) -> Result<Vec<WebhookDelivery>, &'static str> {
+    let owned = webhook_subscriptions::table.filter(webhook_subscriptions::coach_id.eq(criteria.coach_id.as_str())).select(webhook_subscriptions::id);
+
+    let mut query = webhook_deliveries::table.filter(webhook_deliveries::subscription_id.eq_any(owned)).into_boxed();
+
+    if let Some(the_webhook_id) = criteria.webhook_id.as_deref() {
+        query = query.filter(webhook_deliveries::subscription_id.eq(the_webhook_id));
+    }
+
+    query
+        .order_by(webhook_deliveries::created_at.desc())
+        .limit(MAX_DELIVERIES)
+        .load(connection)
+        .map_err(|_| DELIVERIES_FETCH_ERROR)
+}
+
+/**
+ * Posts the deliveries that are due, each signed with the secret of its webhook.
+ * A delivery is claimed before it is posted, so that one instance alone posts
+ * it; a failed one waits twice as long as the last time, and is given up after
+ * MAX_ATTEMPTS. The posts need curl on the host, hence they are made only with
+ * the webhook-delivery feature; without it, the relay posts the deliveries.
+ */
+pub fn deliver_due(connection: &MysqlConnection) -> Result<usize, &'static str> {
+    if !cfg!(feature = "webhook-delivery") {
+        return Ok(0);
+    }
+
+    let now = util::now();
+
+    diesel::update(webhook_deliveries::table.filter(webhook_deliveries::status.eq(SENDING)).filter(webhook_deliveries::updated_at.lt(now - Duration::minutes(STUCK_MINUTES))))
+        .set(webhook_deliveries::status.eq(PENDING))
+        .execute(connection)
+        .map_err(|_| DELIVERY_UPDATE_ERROR)?;
+
+    let due: Vec<(WebhookDelivery, WebhookSubscription)> = webhook_deliveries::table
+        .inner_join(webhook_subscriptions::table)
+        .filter(webhook_deliveries::status.eq(PENDING))
+        .filter(webhook_deliveries::next_attempt_at.le(now))
+        .order_by(webhook_deliveries::next_attempt_at.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)
+        .map_err(|_| DELIVERIES_FETCH_ERROR)?;
+
+    let mut delivered = 0;
+
+    for (delivery, subscription) in &due {
+        let claimed = diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq(delivery.id.as_str())).filter(webhook_deliveries::status.eq(PENDING)))
+            .set((webhook_deliveries::status.eq(SENDING), webhook_deliveries::updated_at.eq(util::now())))
+            .execute(connection)
+            .map_err(|_| DELIVERY_UPDATE_ERROR)?;
+
+        if claimed == 0 {
+            continue;
+        }
+
+        let outcome = post(delivery, subscription);
+        if outcome.is_ok() {
+            delivered += 1;
+        }
+
+        record_outcome(connection, delivery, outcome)?;
+    }
+
+    Ok(delivered)
+}
+
+fn record_outcome(connection: &MysqlConnection, delivery: &WebhookDelivery, outcome: Result<i32, (Option<i32>, String)>) -> Result<usize, &'static str> {
+    let target = webhook_deliveries::table.filter(webhook_deliveries::id.eq(delivery.id.as_str()));
+    let attempts = delivery.attempts + 1;
+    let now = util::now();
+
+    let result = match outcome {
+        Ok(code) => diesel::update(target)
+            .set((
+                webhook_deliveries::status.eq(DELIVERED),
+                webhook_deliveries::attempts.eq(attempts),
+                webhook_deliveries::response_code.eq(Some(code)),
+                webhook_deliveries::last_error.eq(None::<String>),
+                webhook_deliveries::delivered_at.eq(Some(now)),
+                webhook_deliveries::updated_at.eq(now),
+            ))
+            .execute(connection),
+        Err((code, error)) => {
+            let (next_status, next_attempt_at) = match retry_delay(attempts) {
+                Some(delay) => (PENDING, now + delay),
+                None => (FAILED, now),
+            };
+
+            diesel::update(target)
+                .set((
+                    webhook_deliveries::status.eq(next_status),
+                    webhook_deliveries::attempts.eq(attempts),
+                    webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+                    webhook_deliveries::response_code.eq(code),
+                    webhook_deliveries::last_error.eq(Some(error.chars().take(MAX_ERROR).collect::<String>())),
+                    webhook_deliveries::updated_at.eq(now),
+                ))
+                .execute(connection)
+        }
+    };
+
+    result.map_err(|_| DELIVERY_UPDATE_ERROR)
+}
+
+/**
+ * The status the url answered with when it is a 2xx; else the status, if any,
+ * and what went wrong.
+ */
+fn post(delivery: &WebhookDelivery, subscription: &WebhookSubscription) -> Result<i32, (Option<i32>, String)> {
+    let signed = signature(subscription.secret.as_str(), util::now().timestamp(), delivery.body.as_str());
+
+    let child = Command::new("curl")
+        .args(["-sS", "-o", "/dev/null", "-w", "%{http_code}", "--max-time", POST_TIMEOUT_SECONDS, "-X", "POST"])
+        .args(["-H", "Content-Type: application/json"])
+        .arg("-H")
+        .arg(format!("X-Ferris-Event: {}", delivery.event))
+        .arg("-H")
+        .arg(format!("X-Ferris-Delivery: {}", delivery.id))
+        .arg("-H")
+        .arg(format!("X-Ferris-Signature: {}", signed))
+        .args(["--data-binary", "@-"])
+        .arg(delivery.url.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = child.map_err(|e| (None, format!("Unable to run curl: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(delivery.body.as_bytes()).map_err(|e| (None, format!("Unable to hand the body to curl: {}", e)))?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| (None, format!("Unable to run curl: {}", e)))?;
+    let code = String::from_utf8_lossy(&output.stdout).trim().parse::<i32>().ok().filter(|code| *code > 0);
+
+    match code {
+        Some(code) if (200..300).contains(&code) => Ok(code),
+        Some(code) => Err((Some(code), format!("The url answered with the status {}.", code))),
+        None => Err((None, String::from_utf8_lossy(&output.stderr).trim().to_owned())),
+    }
+}
+
+/**
+ * Let us offer the pending deliveries and mark them, for avoiding repeat posts.
+ */
+pub fn sendable_webhooks(connection: &MysqlConnection) -> QueryResult<Vec<WebhookDelivery>> {
+    let deliveries: Vec<WebhookDelivery> = webhook_deliveries::table
+        .filter(webhook_deliveries::status.eq(PENDING))
+        .filter(webhook_deliveries::next_attempt_at.le(util::now()))
+        .order_by(webhook_deliveries::created_at.asc())
+        .limit(BATCH_SIZE)
+        .load(connection)?;
+
+    let ids: Vec<&str> = deliveries.iter().map(|delivery| delivery.id.as_str()).collect();
+
+    diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq_any(ids)))
+        .set(webhook_deliveries::status.eq(MARKED))
+        .execute(connection)?;
+
+    Ok(deliveries)
+}
+
+fn find_owned(connection: &MysqlConnection, the_id: &str, the_coach_id: &str) -> Result<WebhookSubscription, &'static str> {
+    webhook_subscriptions::table
+        .filter(webhook_subscriptions::id.eq(the_id))
+        .filter(webhook_subscriptions::coach_id.eq(the_coach_id))
+        .first(connection)
+        .map_err(|_| WEBHOOK_NOT_FOUND)
+}
@@ -0,0 +1,379 @@
+/**
+ * The changes of the state of the sessions, pushed to the web UI as Server-Sent
+ * Events so that it need not poll getSession.
+ *
+ * juniper 0.14 has no subscriptions, so the feed lives beside the GraphQL endpoint:
+ * alterSessionState, cancelSessions and mergeSessions publish the changes here and
+ * every client following the session gets them on GET /sessions/{session_id}/events,
+ * which only the coach and the members of the session may open. The sessions of a
+ * conference change together, so a client following one of them is told of the
+ * changes made through any other.
+ *
+ * The feed is kept in memory. Every event carries an id, and the last few events
+ * of each session, or of each conference, are kept for a while. A client that
+ * reconnects after a blip sends the id of the last event it got, in the
+ * Last-Event-ID header as the browsers do or as ?since=, and is sent only the
+ * events it missed. When some of them are no longer kept, or the server restarted
+ * meanwhile, it reads the session again, which opens every other stream.
+ *
+ * SESSION_EVENTS_BUFFER       events kept of a session or a conference, 50 by default
+ * SESSION_EVENTS_TTL_SECONDS  how long an event is kept, 300 by default
+ */
+use actix_web::web::Bytes;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::models::sessions::{Session, TargetState};
+
+// The open streams of a server; the clients beyond are asked to retry later.
+const MAX_SUBSCRIBERS: usize = 2000;
+
+const DEFAULT_BUFFER: usize = 50;
+const DEFAULT_TTL_SECONDS: u64 = 300;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEvent {
+    pub session_id: String,
+    pub conference_id: Option<String>,
+    pub change: Option<&'static str>,
+    pub status: String,
+}
+
+impl SessionEvent {
+    /**
+     * The state of the session as it stands, without a change; the first event of a stream.
+     */
+    pub fn of(session: &Session) -> SessionEvent {
+        SessionEvent {
+            session_id: session.id.to_string(),
+            conference_id: session.conference_id.to_owned(),
+            change: None,
+            status: session.status_label(),
+        }
+    }
+
+    pub fn changed(session: &Session, target_state: &TargetState) -> SessionEvent {
+        let change = match target_state {
+            TargetState::READY => "READY",
+            TargetState::START => "START",
+            TargetState::DONE => "DONE",
+            TargetState::CANCEL => "CANCEL",
+        };
+
+        SessionEvent {
+            change: Some(change),
+            ..SessionEvent::of(session)
+        }
+    }
+
+    pub fn to_frame(&self) -> Bytes {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        Bytes::from(format!("event: session\ndata: {}\n\n", data))
+    }
+
+    fn to_frame_with_id(&self, id: &EventId) -> Bytes {
+        let mut frame = format!("id: {}\n", id.label()).into_bytes();
+        frame.extend_from_slice(&self.to_frame());
+        Bytes::from(frame)
+    }
+
+    // The sessions of a conference share their events.
+    fn channel(&self) -> &str {
+        self.conference_id.as_deref().unwrap_or(self.session_id.as_str())
+    }
+}
+
+/**
+ * The epoch tells the ids of one run of the server from those of another.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct EventId {
+    epoch: u64,
+    sequence: u64,
+}
+
+impl EventId {
+    fn label(&self) -> String {
+        format!("{}-{}", self.epoch, self.sequence)
+    }
+
+    fn parse(label: &str) -> Option<EventId> {
+        let mut parts = label.trim().splitn(2, '-');
+        let epoch = parts.next()?.parse::<u64>().ok()?;
+        let sequence = parts.next()?.parse::<u64>().ok()?;
+
+        Some(EventId { epoch, sequence })
+    }
+}
+
+struct Buffered {
+    sequence: u64,
+    at: Instant,
+    frame: Bytes,
+}
+
+#[derive(Default)]
+struct Channel {
+    events: VecDeque<Buffered>,
+    // The last sequence no longer kept; a client behind it has missed events.
+    dropped_upto: u64,
+}
+
+struct ReplayBuffer {
+    epoch: u64,
+    sequence: u64,
+    size: usize,
+    ttl: Duration,
+    channels: HashMap<String, Channel>,
+    // The last sequence of the channels no longer kept at all.
+    forgotten_upto: u64,
+}
+
+impl ReplayBuffer {
+    fn new(epoch: u64, size: usize, ttl: Duration) -> ReplayBuffer {
+        ReplayBuffer {
+            epoch,
+            sequence: 0,
+            size,
+            ttl,
+            channels: HashMap::new(),
+            forgotten_upto: 0,
+        }
+    }
+
+    fn current(&self) -> EventId {
+        EventId {
+            epoch: self.epoch,
+            sequence: self.sequence,
+        }
+    }
+
+    fn record(&mut self, event: &SessionEvent, now: Instant) -> Bytes {
+        self.sequence += 1;
+        let frame = event.to_frame_with_id(&self.current());
+
+        let size = self.size;
+        let channel = self.channels.entry(event.channel().to_owned()).or_default();
+        channel.events.push_back(Buffered {
+            sequence: self.sequence,
+            at: now,
+            frame: frame.clone(),
+        });
+
+        while channel.events.len() > size {
+            if let Some(oldest) = channel.events.pop_front() {
+                channel.dropped_upto = oldest.sequence;
+            }
+        }
+
+        frame
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        let mut forgotten_upto = self.forgotten_upto;
+
+        self.channels.retain(|_, channel| {
+            while channel.events.front().is_some_and(|oldest| now.duration_since(oldest.at) > ttl) {
+                if let Some(oldest) = channel.events.pop_front() {
+                    channel.dropped_upto = oldest.sequence;
+                }
+            }
+
+            if channel.events.is_empty() {
+                forgotten_upto = forgotten_upto.max(channel.dropped_upto);
+                return false;
+            }
+
+            true
+        });
+
+        self.forgotten_upto = forgotten_upto;
+    }
+
+    /**
+     * The events of the channel after the given one, or none when some of them
+     * are no longer kept.
+     */
+    fn since(&self, channel: &str, last: &EventId) -> Option<Vec<Bytes>> {
+        if last.epoch != self.epoch || last.sequence > self.sequence {
+            return None;
+        }
+
+        match self.channels.get(channel) {
+            Some(kept) if last.sequence >= kept.dropped_upto => Some(kept.events.iter().filter(|event| event.sequence > last.sequence).map(|event| event.frame.clone()).collect()),
+            Some(_) => None,
+            None if last.sequence >= self.forgotten_upto => Some(Vec::new()),
+            None => None,
+        }
+    }
+}
+
+struct Subscriber {
+    session_id: String,
+    conference_id: Option<String>,
+    sender: UnboundedSender<Bytes>,
+}
+
+impl Subscriber {
+    fn follows(&self, event: &SessionEvent) -> bool {
+        if self.session_id == event.session_id {
+            return true;
+        }
+
+        self.conference_id.is_some() && self.conference_id == event.conference_id
+    }
+}
+
+struct Feed {
+    subscribers: Vec<Subscriber>,
+    buffer: ReplayBuffer,
+}
+
+#[derive(Clone)]
+pub struct SessionEvents {
+    feed: Arc<Mutex<Feed>>,
+}
+
+impl SessionEvents {
+    pub fn from_env() -> SessionEvents {
+        let size = env::var("SESSION_EVENTS_BUFFER").ok().and_then(|value| value.trim().parse::<usize>().ok()).unwrap_or(DEFAULT_BUFFER);
+        let ttl = env::var("SESSION_EVENTS_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_TTL_SECONDS);
+
+        SessionEvents::new(size, Duration::from_secs(ttl))
+    }
+
+    pub fn new(size: usize, ttl: Duration) -> SessionEvents {
+        let epoch = SystemTime::now().duration_since(UNIX_EPOCH).map(|value| value.as_millis() as u64).unwrap_or_default();
+
+        let feed = Feed {
+            subscribers: Vec::new(),
+            buffer: ReplayBuffer::new(epoch, size, ttl),
+        };
+
+        SessionEvents { feed: Arc::new(Mutex::new(feed)) }
+    }
+
+    /**
+     * A stream of the changes of the session; none when the server is full. It opens
+     * with the events missed since the given one when they are all kept, else with
+     * the current state of the session.
+     */
+    pub fn subscribe(&self, session: &Session, last_event_id: Option<&str>) -> Option<UnboundedReceiver<Bytes>> {
+        let mut feed = self.feed.lock().unwrap();
+        feed.subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+
+        if feed.subscribers.len() >= MAX_SUBSCRIBERS {
+            return None;
+        }
+
+        feed.buffer.expire(Instant::now());
+
+        let opening = SessionEvent::of(session);
+        let missed = last_event_id.and_then(EventId::parse).and_then(|last| feed.buffer.since(opening.channel(), &last));
+        let frames = missed.unwrap_or_else(|| vec![opening.to_frame_with_id(&feed.buffer.current())]);
+
+        let (sender, receiver) = unbounded();
+        for frame in frames {
+            sender.unbounded_send(frame).ok()?;
+        }
+
+        feed.subscribers.push(Subscriber {
+            session_id: session.id.to_string(),
+            conference_id: session.conference_id.to_owned(),
+            sender,
+        });
+
+        Some(receiver)
+    }
+
+    /**
+     * Keeps the event for the clients that reconnect and sends it to the clients
+     * following the session; the clients gone are dropped.
+     */
+    pub fn publish(&self, event: &SessionEvent) {
+        let mut feed = self.feed.lock().unwrap();
+
+        let now = Instant::now();
+        feed.buffer.expire(now);
+        let frame = feed.buffer.record(event, now);
+
+        feed.subscribers
+            .retain(|subscriber| !subscriber.follows(event) || subscriber.sender.unbounded_send(frame.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn event(session_id: &str, conference_id: Option<&str>) -> SessionEvent {
+        SessionEvent {
+            session_id: session_id.to_owned(),
+            conference_id: conference_id.map(String::from),
+            change: Some("START"),
+            status: String::from("PROGRESS"),
+        }
+    }
+
+    fn subscriber(session_id: &str, conference_id: Option<&str>) -> Subscriber {
+        Subscriber {
+            session_id: session_id.to_owned(),
+            conference_id: conference_id.map(String::from),
+            sender: unbounded().0,
+        }
+    }
+
+    #[test]
+    fn should_follow_the_session_and_its_conference() {
+        let mono = subscriber("s-1", None);
+        assert!(mono.follows(&event("s-1", None)));
+        assert!(!mono.follows(&event("s-2", None)));
+
+        let member = subscriber("s-3", Some("c-1"));
+        assert!(member.follows(&event("s-4", Some("c-1"))));
+        assert!(!member.follows(&event("s-5", Some("c-2"))));
+    }
+
+    #[test]
+    fn should_replay_only_what_is_still_kept() {
+        let start = Instant::now();
+        let mut buffer = ReplayBuffer::new(7, 2, Duration::from_secs(60));
+
+        buffer.record(&event("s-1", None), start);
+        let after_first = buffer.current();
+        buffer.record(&event("s-2", None), start);
+        buffer.record(&event("s-1", None), start);
+        buffer.record(&event("s-1", None), start);
+
+        let first = EventId { epoch: 7, sequence: 0 };
+        assert_eq!(None, buffer.since("s-1", &first));
+        assert_eq!(Some(2), buffer.since("s-1", &after_first).map(|frames| frames.len()));
+        assert_eq!(Some(0), buffer.since("s-1", &buffer.current()).map(|frames| frames.len()));
+        assert_eq!(None, buffer.since("s-1", &EventId { epoch: 6, sequence: 4 }));
+
+        buffer.expire(start + Duration::from_secs(61));
+        assert_eq!(None, buffer.since("s-1", &after_first));
+        assert_eq!(Some(0), buffer.since("s-3", &buffer.current()).map(|frames| frames.len()));
+        assert_eq!(Some(EventId { epoch: 7, sequence: 4 }), EventId::parse("7-4"));
+    }
+
+    #[test]
+    fn should_frame_the_event_as_json() {
+        let frame = event("s-1", None).to_frame();
+
+        assert_eq!(
+            "event: session\ndata: {\"sessionId\":\"s-1\",\"conferenceId\":null,\"change\":\"START\",\"status\":\"PROGRESS\"}\n\n",
+            String::from_utf8(frame.to_vec()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,299 @@
+/**
+ * Where the assets are kept.
+ *
+ * STORAGE_BACKEND   local, the default, or s3
+ * ASSET_ROOT        the local directory of the assets, /Users/pmpower/assets by default
+ * S3_BUCKET         the bucket of the assets; a must for s3
+ * S3_PREFIX         the prefix of the keys within the bucket, none by default
+ *
+ * The files are always worked on from the local disk, as the thumbnails, the
+ * posters and the exports of the boards need them there. With s3 the local disk
+ * is a cache: a stored file is copied up to the bucket, and a file missing from
+ * the disk, say in a new container, is brought down before it is read. The s3
+ * backend needs the AWS CLI on the host, with its credentials in the usual places.
+ *
+ * A file is known to the backend by its key, its path below ASSET_ROOT, which is
+ * the same on the disk and in the bucket.
+ */
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::{Arc, OnceLock};
+
+const DEFAULT_ROOT: &str = "/Users/pmpower/assets";
+const QUIET: &str = "--only-show-errors";
+
+#[derive(Clone, Copy)]
+pub enum Area {
+    Sessions,
+    Programs,
+    Users,
+    Platform,
+    Blobs,
+}
+
+impl Area {
+    fn dir_name(&self) -> &'static str {
+        match self {
+            Area::Sessions => "sessions",
+            Area::Programs => "programs",
+            Area::Users => "users",
+            Area::Platform => "platform",
+            Area::Blobs => "blobs",
+        }
+    }
+}
+
+pub trait StorageBackend: Send + Sync {
+    /**
+     * Keeps the local file of the key, once it is written.
+     */
+    fn keep(&self, key: &str, local: &Path) -> io::Result<()>;
+
+    /**
+     * Brings the file of the key down to the local path; false when there is none.
+     */
+    fn restore(&self, key: &str, local: &Path) -> io::Result<bool>;
+
+    fn remove(&self, key: &str) -> io::Result<()>;
+
+    /**
+     * The names of the files right under the prefix.
+     */
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>>;
+}
+
+/**
+ * The local disk is all there is; the files are kept where they are written.
+ */
+pub struct LocalDisk;
+
+impl StorageBackend for LocalDisk {
+    fn keep(&self, _key: &str, _local: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn restore(&self, _key: &str, _local: &Path) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    fn remove(&self, _key: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn list(&self, _prefix: &str) -> io::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+pub struct S3 {
+    bucket: String,
+    prefix: String,
+}
+
+impl S3 {
+    fn url(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            return format!("s3://{}/{}", self.bucket, key);
+        }
+
+        format!("s3://{}/{}/{}", self.bucket, self.prefix, key)
+    }
+}
+
+impl StorageBackend for S3 {
+    fn keep(&self, key: &str, local: &Path) -> io::Result<()> {
+        let url = self.url(key);
+        let output = aws(&["cp", QUIET], &[local.as_os_str(), OsStr::new(&url)])?;
+        succeeded(output)
+    }
+
+    // The CLI tells a missing key from a failure only in its message; both leave the file missing.
+    fn restore(&self, key: &str, local: &Path) -> io::Result<bool> {
+        let url = self.url(key);
+        let output = aws(&["cp", QUIET], &[OsStr::new(&url), local.as_os_str()])?;
+        Ok(output.status.success())
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        let url = self.url(key);
+        let output = aws(&["rm", QUIET], &[OsStr::new(&url)])?;
+        succeeded(output)
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let url = format!("{}/", self.url(prefix));
+        let output = aws(&["ls"], &[OsStr::new(&url)])?;
+
+        // ls fails for a prefix without files.
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).lines().filter_map(listed_name).map(String::from).collect())
+    }
+}
+
+fn aws(command: &[&str], paths: &[&OsStr]) -> io::Result<Output> {
+    Command::new("aws").arg("s3").args(command).args(paths).output()
+}
+
+fn succeeded(output: Output) -> io::Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_owned()))
+}
+
+// A line of `aws s3 ls` is the date, the time, the size and the name; the sub prefixes start with PRE.
+fn listed_name(line: &str) -> Option<&str> {
+    let mut rest = line.trim_start();
+
+    for _ in 0..3 {
+        let (field, after) = rest.split_once(char::is_whitespace)?;
+        if field == "PRE" {
+            return None;
+        }
+        rest = after.trim_start();
+    }
+
+    Some(rest).filter(|name| !name.is_empty())
+}
+
+pub struct Storage {
+    root: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+}
+
+impl Storage {
+    pub fn from_env() -> Storage {
+        let root = dotenv::var("ASSET_ROOT").ok().filter(|value| !value.trim().is_empty()).unwrap_or_else(|| String::from(DEFAULT_ROOT));
+
+        let backend: Arc<dyn StorageBackend> = match dotenv::var("STORAGE_BACKEND").unwrap_or_default().as_str() {
+            "s3" => Arc::new(S3 {
+                bucket: dotenv::var("S3_BUCKET").expect("The S3_BUCKET should be set for the s3 storage"),
+                prefix: dotenv::var("S3_PREFIX").unwrap_or_default().trim_matches('/').to_owned(),
+            }),
+            _ => Arc::new(LocalDisk),
+        };
+
+        Storage::new(root, backend)
+    }
+
+    pub fn new<P: AsRef<Path>>(root: P, backend: Arc<dyn StorageBackend>) -> Storage {
+        Storage {
+            root: root.as_ref().to_path_buf(),
+            backend,
+        }
+    }
+
+    pub fn dir(&self, area: Area) -> PathBuf {
+        self.root.join(area.dir_name())
+    }
+
+    fn key_of(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let parts: Vec<String> = relative.components().map(|part| part.as_os_str().to_string_lossy().into_owned()).collect();
+
+        Some(parts.join("/"))
+    }
+
+    /**
+     * The file just written to the path is handed to the backend.
+     */
+    pub fn keep(&self, path: &Path) -> io::Result<()> {
+        match self.key_of(path) {
+            Some(key) => self.backend.keep(key.as_str(), path),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * The path, once the file is on the local disk; NotFound when it is nowhere.
+     */
+    pub fn open(&self, path: &Path) -> io::Result<PathBuf> {
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+
+        if let Some(key) = self.key_of(path) {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            if self.backend.restore(key.as_str(), path)? {
+                return Ok(path.to_path_buf());
+            }
+        }
+
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("{} is not found", path.to_string_lossy())))
+    }
+
+    pub fn remove(&self, path: &Path) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+
+        match self.key_of(path) {
+            Some(key) => self.backend.remove(key.as_str()),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * The names of the files in the directory, on the disk or with the backend, in order.
+     */
+    pub fn list(&self, dir: &Path) -> io::Result<Vec<String>> {
+        let mut names: Vec<String> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .flatten()
+                .filter(|entry| entry.file_type().map(|kind| kind.is_file()).unwrap_or(false))
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        if let Some(key) = self.key_of(dir) {
+            names.extend(self.backend.list(key.as_str())?);
+        }
+
+        names.sort();
+        names.dedup();
+
+        Ok(names)
+    }
+}
+
+/**
+ * The storage of the server, read from the environment once.
+ */
+pub fn storage() -> &'static Storage {
+    static STORAGE: OnceLock<Storage> = OnceLock::new();
+    STORAGE.get_or_init(Storage::from_env)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_read_the_names_listed_by_the_cli() {
+        assert_eq!(Some("board 1.png"), listed_name("2021-03-01 10:15:02      20480 board 1.png"));
+        assert_eq!(None, listed_name("                           PRE thumbs/"));
+        assert_eq!(None, listed_name(""));
+    }
+
+    #[test]
+    fn should_key_the_files_below_the_root() {
+        let storage = Storage::new("/assets", Arc::new(LocalDisk));
+
+        assert_eq!(PathBuf::from("/assets/sessions"), storage.dir(Area::Sessions));
+        assert_eq!(Some(String::from("sessions/s-1/boards/b.png")), storage.key_of(Path::new("/assets/sessions/s-1/boards/b.png")));
+        assert_eq!(None, storage.key_of(Path::new("/elsewhere/b.png")));
+    }
+}
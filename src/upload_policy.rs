@@ -0,0 +1,237 @@
+/**
+ * The size and the type rules of the uploads, by the class of the asset.
+ *
+ * The type of a file is sniffed from its first bytes, never taken from the
+ * browser; its extension should agree with what was sniffed, as the assets are
+ * served by their extension. Each class reads its largest file from the
+ * environment, falling back to the defaults below.
+ *
+ * UPLOAD_<CLASS>_MAX_MB  the largest file of the class, in megabytes
+ *
+ * A file that breaks a rule is rejected with a code the UI can tell apart:
+ * TOO_LARGE, UNKNOWN_TYPE, TYPE_NOT_ALLOWED or EXTENSION_MISMATCH.
+ */
+use serde::Serialize;
+use std::path::Path;
+
+// Enough of the head of a file to tell every kind below.
+pub const SNIFF_BYTES: usize = 16;
+
+pub const TOO_LARGE: &str = "TOO_LARGE";
+const UNKNOWN_TYPE: &str = "UNKNOWN_TYPE";
+const TYPE_NOT_ALLOWED: &str = "TYPE_NOT_ALLOWED";
+const EXTENSION_MISMATCH: &str = "EXTENSION_MISMATCH";
+
+const MEGABYTE: usize = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileKind {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Pdf,
+    Mp4,
+    Webm,
+}
+
+impl FileKind {
+    pub fn sniff(head: &[u8]) -> Option<FileKind> {
+        if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Some(FileKind::Png);
+        }
+
+        if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return Some(FileKind::Jpeg);
+        }
+
+        if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+            return Some(FileKind::Gif);
+        }
+
+        if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+            return Some(FileKind::Webp);
+        }
+
+        if head.starts_with(b"%PDF-") {
+            return Some(FileKind::Pdf);
+        }
+
+        if head.get(4..8) == Some(b"ftyp") {
+            return Some(FileKind::Mp4);
+        }
+
+        if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+            return Some(FileKind::Webm);
+        }
+
+        None
+    }
+
+    pub fn is_image(&self) -> bool {
+        matches!(self, FileKind::Png | FileKind::Jpeg | FileKind::Gif | FileKind::Webp)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FileKind::Png => "PNG",
+            FileKind::Jpeg => "JPEG",
+            FileKind::Gif => "GIF",
+            FileKind::Webp => "WebP",
+            FileKind::Pdf => "PDF",
+            FileKind::Mp4 => "MP4",
+            FileKind::Webm => "WebM",
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            FileKind::Png => &["png"],
+            FileKind::Jpeg => &["jpg", "jpeg"],
+            FileKind::Gif => &["gif"],
+            FileKind::Webp => &["webp"],
+            FileKind::Pdf => &["pdf"],
+            FileKind::Mp4 => &["mp4", "m4v", "mov"],
+            FileKind::Webm => &["webm"],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum UploadClass {
+    Notes,
+    Boards,
+    Programs,
+    Users,
+}
+
+impl UploadClass {
+    fn key(&self) -> &'static str {
+        match self {
+            UploadClass::Notes => "NOTES",
+            UploadClass::Boards => "BOARDS",
+            UploadClass::Programs => "PROGRAMS",
+            UploadClass::Users => "USERS",
+        }
+    }
+
+    // The notes carry the recordings of the sessions, hence the room.
+    fn default_max_mb(&self) -> usize {
+        match self {
+            UploadClass::Notes => 200,
+            UploadClass::Boards => 10,
+            UploadClass::Programs => 50,
+            UploadClass::Users => 10,
+        }
+    }
+
+    fn allowed(&self) -> &'static [FileKind] {
+        match self {
+            UploadClass::Notes => &[FileKind::Png, FileKind::Jpeg, FileKind::Gif, FileKind::Webp, FileKind::Pdf, FileKind::Mp4, FileKind::Webm],
+            UploadClass::Boards => &[FileKind::Png, FileKind::Jpeg],
+            UploadClass::Programs => &[FileKind::Pdf, FileKind::Png, FileKind::Jpeg, FileKind::Gif, FileKind::Webp],
+            UploadClass::Users => &[FileKind::Pdf, FileKind::Png, FileKind::Jpeg, FileKind::Gif, FileKind::Webp],
+        }
+    }
+
+    pub fn rule(&self) -> UploadRule {
+        let max_mb = std::env::var(format!("UPLOAD_{}_MAX_MB", self.key()))
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or_else(|| self.default_max_mb());
+
+        UploadRule {
+            max_bytes: max_mb * MEGABYTE,
+            allowed: self.allowed(),
+        }
+    }
+}
+
+pub struct UploadRule {
+    pub max_bytes: usize,
+    pub allowed: &'static [FileKind],
+}
+
+#[derive(Serialize, Debug)]
+pub struct Rejection {
+    pub file_name: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Rejection {
+    fn new(file_name: &str, code: &'static str, message: String) -> Rejection {
+        Rejection {
+            file_name: file_name.to_owned(),
+            code,
+            message,
+        }
+    }
+}
+
+impl UploadRule {
+    /**
+     * The kind of the file from its head, when it is one of the allowed kinds
+     * and its extension agrees.
+     */
+    pub fn check_type(&self, file_name: &str, head: &[u8]) -> Result<FileKind, Rejection> {
+        let kind = match FileKind::sniff(head) {
+            Some(kind) => kind,
+            None => return Err(Rejection::new(file_name, UNKNOWN_TYPE, format!("{}: the type of the file is not known.", file_name))),
+        };
+
+        if !self.allowed.contains(&kind) {
+            let allowed: Vec<&str> = self.allowed.iter().map(|kind| kind.name()).collect();
+            let message = format!("{}: a {} file is not allowed here; it should be one of {}.", file_name, kind.name(), allowed.join(", "));
+            return Err(Rejection::new(file_name, TYPE_NOT_ALLOWED, message));
+        }
+
+        let extension = Path::new(file_name).extension().and_then(|value| value.to_str()).unwrap_or_default().to_lowercase();
+
+        if !kind.extensions().contains(&extension.as_str()) {
+            let message = format!("{}: the file is a {} but is not named like one.", file_name, kind.name());
+            return Err(Rejection::new(file_name, EXTENSION_MISMATCH, message));
+        }
+
+        Ok(kind)
+    }
+
+    pub fn check_size(&self, file_name: &str, size: usize) -> Result<(), Rejection> {
+        if size > self.max_bytes {
+            let message = format!("{}: the file should not be larger than {} MB.", file_name, self.max_bytes / MEGABYTE);
+            return Err(Rejection::new(file_name, TOO_LARGE, message));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn boards() -> UploadRule {
+        UploadRule {
+            max_bytes: MEGABYTE,
+            allowed: UploadClass::Boards.allowed(),
+        }
+    }
+
+    #[test]
+    fn should_go_by_the_bytes_and_not_the_name() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+        assert_eq!(Ok(FileKind::Png), boards().check_type("board.PNG", png).map_err(|e| e.code));
+        assert_eq!(Err(EXTENSION_MISMATCH), boards().check_type("board.jpg", png).map_err(|e| e.code));
+        assert_eq!(Err(TYPE_NOT_ALLOWED), boards().check_type("board.pdf", b"%PDF-1.4\n%").map_err(|e| e.code));
+        assert_eq!(Err(UNKNOWN_TYPE), boards().check_type("board.png", b"<svg onload=''>").map_err(|e| e.code));
+    }
+
+    #[test]
+    fn should_reject_the_files_past_the_size() {
+        assert!(boards().check_size("board.png", MEGABYTE).is_ok());
+        assert_eq!(Err(TOO_LARGE), boards().check_size("board.png", MEGABYTE + 1).map_err(|e| e.code));
+    }
+}
@@ -0,0 +1,213 @@
+/**
+ * The uploads are kept apart from the interactive traffic.
+ *
+ * The file writes of the uploads used to go through web::block, the same pool
+ * that runs every GraphQL request; a burst of large uploads then kept the queries
+ * waiting behind the writes. The uploads now write on threads of their own, and
+ * only a few of them are admitted at a time. The others are turned away with a
+ * 503 and a Retry-After, so that the client backs off instead of piling up.
+ *
+ * UPLOAD_THREADS       threads writing the files, 2 by default
+ * UPLOAD_CONCURRENCY   uploads admitted at a time, 8 by default
+ *
+ * As every admitted upload waits on at most one write, the queue of the pool
+ * never grows beyond UPLOAD_CONCURRENCY.
+ */
+use actix_web::error::BlockingError;
+use actix_web::{Error, HttpResponse};
+use futures::channel::oneshot;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const DEFAULT_THREADS: usize = 2;
+const DEFAULT_CONCURRENCY: usize = 8;
+const RETRY_AFTER_SECONDS: &str = "5";
+
+type Work = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct Counters {
+    admitted: AtomicUsize,
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    completed: AtomicUsize,
+    rejected: AtomicUsize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UploadMetrics {
+    pub threads: usize,
+    pub concurrency: usize,
+    pub admitted: usize,
+    pub queued: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub rejected: usize,
+}
+
+#[derive(Clone)]
+pub struct UploadPool {
+    sender: Arc<Mutex<Sender<Work>>>,
+    counters: Arc<Counters>,
+    threads: usize,
+    concurrency: usize,
+}
+
+/**
+ * Holds a place among the admitted uploads until the upload is over.
+ */
+pub struct UploadPermit {
+    counters: Arc<Counters>,
+}
+
+impl Drop for UploadPermit {
+    fn drop(&mut self) {
+        self.counters.admitted.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl UploadPool {
+    pub fn from_env() -> UploadPool {
+        UploadPool::new(env_size("UPLOAD_THREADS", DEFAULT_THREADS), env_size("UPLOAD_CONCURRENCY", DEFAULT_CONCURRENCY))
+    }
+
+    pub fn new(threads: usize, concurrency: usize) -> UploadPool {
+        let (sender, receiver) = mpsc::channel::<Work>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let counters = Arc::new(Counters::default());
+
+        for index in 0..threads {
+            let receiver = receiver.clone();
+            let counters = counters.clone();
+
+            thread::Builder::new()
+                .name(format!("upload-{}", index))
+                .spawn(move || work(receiver, counters))
+                .expect("Unable to start the upload pool");
+        }
+
+        UploadPool {
+            sender: Arc::new(Mutex::new(sender)),
+            counters,
+            threads,
+            concurrency,
+        }
+    }
+
+    pub fn admit(&self) -> Result<UploadPermit, Error> {
+        let admitted = self.counters.admitted.fetch_add(1, Ordering::SeqCst);
+
+        if admitted >= self.concurrency {
+            self.counters.admitted.fetch_sub(1, Ordering::SeqCst);
+            self.counters.rejected.fetch_add(1, Ordering::SeqCst);
+
+            return Err(HttpResponse::ServiceUnavailable().header("Retry-After", RETRY_AFTER_SECONDS).finish().into());
+        }
+
+        Ok(UploadPermit { counters: self.counters.clone() })
+    }
+
+    /**
+     * Like web::block, but on the threads of the uploads.
+     */
+    pub async fn block<F, I, E>(&self, f: F) -> Result<I, BlockingError<E>>
+    where
+        F: FnOnce() -> Result<I, E> + Send + 'static,
+        I: Send + 'static,
+        E: Send + Debug + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        let work: Work = Box::new(move || {
+            // The upload may have gone away meanwhile; nobody is left to tell.
+            let _ = tx.send(f());
+        });
+
+        self.counters.queued.fetch_add(1, Ordering::SeqCst);
+
+        if self.sender.lock().unwrap().send(work).is_err() {
+            self.counters.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(BlockingError::Canceled);
+        }
+
+        match rx.await {
+            Ok(result) => result.map_err(BlockingError::Error),
+            Err(_) => Err(BlockingError::Canceled),
+        }
+    }
+
+    pub fn metrics(&self) -> UploadMetrics {
+        UploadMetrics {
+            threads: self.threads,
+            concurrency: self.concurrency,
+            admitted: self.counters.admitted.load(Ordering::SeqCst),
+            queued: self.counters.queued.load(Ordering::SeqCst),
+            running: self.counters.running.load(Ordering::SeqCst),
+            completed: self.counters.completed.load(Ordering::SeqCst),
+            rejected: self.counters.rejected.load(Ordering::SeqCst),
+        }
+    }
+}
+
+// A panicking write drops its sender, which cancels the upload; the thread carries on.
+fn work(receiver: Arc<Mutex<Receiver<Work>>>, counters: Arc<Counters>) {
+    loop {
+        let next = receiver.lock().unwrap().recv();
+
+        let work = match next {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+
+        counters.queued.fetch_sub(1, Ordering::SeqCst);
+        counters.running.fetch_add(1, Ordering::SeqCst);
+
+        let _ = panic::catch_unwind(AssertUnwindSafe(work));
+
+        counters.running.fetch_sub(1, Ordering::SeqCst);
+        counters.completed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn env_size(key: &str, default: usize) -> usize {
+    dotenv::var(key).ok().and_then(|value| value.parse::<usize>().ok()).filter(|value| *value > 0).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn should_turn_away_beyond_the_concurrency() {
+        let pool = UploadPool::new(1, 2);
+
+        let first = pool.admit();
+        let second = pool.admit();
+        assert!(first.is_ok() && second.is_ok());
+        assert!(pool.admit().is_err());
+
+        drop(first);
+        assert!(pool.admit().is_ok());
+
+        let metrics = pool.metrics();
+        assert_eq!(1, metrics.rejected);
+        assert_eq!(1, metrics.admitted);
+    }
+
+    #[actix_rt::test]
+    async fn should_run_on_the_pool() {
+        let pool = UploadPool::new(1, 1);
+
+        let name = pool.block(|| Ok::<_, std::io::Error>(thread::current().name().map(String::from))).await.unwrap();
+        assert_eq!(Some(String::from("upload-0")), name);
+
+        let failed = pool.block(|| Err::<(), _>("disk full")).await;
+        assert!(matches!(failed, Err(BlockingError::Error("disk full"))));
+    }
+}